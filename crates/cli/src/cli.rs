@@ -1,8 +1,12 @@
 use std::path::PathBuf;
 
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
 
-use crate::commands::{config::ConfigOpts, node::NodeOpts, wallet::WalletOpts};
+use crate::commands::{
+    config::ConfigOpts,
+    node::{NodeCmd, NodeOpts},
+    wallet::WalletOpts,
+};
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None, arg_required_else_help(true))]
@@ -23,6 +27,32 @@ pub struct Args {
     pub command: Option<Commands>,
 }
 
+impl Args {
+    /// Parses the command line like `Args::parse`, additionally recording
+    /// which options were passed explicitly to `node run` so they can
+    /// override a config file's values, see `RunOpts::merge`
+    pub fn parse_tracking_explicit_flags() -> Self {
+        let matches = Args::command().get_matches();
+        let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+
+        let run_matches = matches
+            .subcommand_matches("node")
+            .and_then(|node_matches| node_matches.subcommand_matches("run"));
+
+        if let (
+            Some(Commands::Node(NodeOpts {
+                subcommand: NodeCmd::Run(opts),
+            })),
+            Some(run_matches),
+        ) = (&mut args.command, run_matches)
+        {
+            opts.track_explicit_flags(run_matches);
+        }
+
+        args
+    }
+}
+
 #[derive(Debug, Subcommand)]
 pub enum Commands {
     /// Manage configuration for this CLI tool
@@ -3,20 +3,37 @@ pub mod node;
 pub(crate) mod utils;
 pub mod wallet;
 
+use telemetry::TelemetrySubscriber;
+
 use crate::{
     cli::{Args, Commands},
     result::{CliError, Result},
 };
 
 pub async fn exec(args: Args) -> Result<()> {
+    // NOTE: nodes set up telemetry themselves once their config is resolved,
+    // since it decides how they log
+    if !runs_node(&args) {
+        TelemetrySubscriber::init(std::io::stdout)?;
+    }
+
     telemetry::debug!("args: {:?}", args);
 
     let cmd = args.command;
 
     match cmd {
-        Some(Commands::Node(node_args)) => node::exec(node_args).await,
+        Some(Commands::Node(node_args)) => node::exec(node_args, args.config).await,
         Some(Commands::Wallet(wallet_args)) => wallet::exec(wallet_args).await,
         None => Err(CliError::NoSubcommand),
         _ => Err(CliError::InvalidCommand(format!("{:?}", cmd))),
     }
 }
+
+fn runs_node(args: &Args) -> bool {
+    matches!(
+        &args.command,
+        Some(Commands::Node(node::NodeOpts {
+            subcommand: node::NodeCmd::Run(_)
+        }))
+    )
+}
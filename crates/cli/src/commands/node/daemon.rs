@@ -0,0 +1,95 @@
+//! Helpers to manage nodes running as background processes.
+//!
+//! A detached node is tracked through a few files kept within the node's data
+//! directory: a PID file identifying the running process, a ready file the
+//! node writes once it is listening for connections and a log file its output
+//! is redirected to.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use crate::result::{CliError, Result};
+
+const PID_FILE_NAME: &str = "node.pid";
+const READY_FILE_NAME: &str = "node.ready";
+const LOG_FILE_NAME: &str = "node.log";
+
+pub fn pid_file_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(PID_FILE_NAME)
+}
+
+pub fn ready_file_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(READY_FILE_NAME)
+}
+
+pub fn log_file_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(LOG_FILE_NAME)
+}
+
+/// Reads the PID stored in a PID file, if any
+pub fn read_pid(pid_file_path: &Path) -> Option<u32> {
+    fs::read_to_string(pid_file_path)
+        .ok()
+        .and_then(|contents| contents.trim().parse().ok())
+}
+
+pub fn write_pid(pid_file_path: &Path, pid: u32) -> Result<()> {
+    fs::write(pid_file_path, pid.to_string()).map_err(CliError::from)
+}
+
+/// Returns the PID recorded in the PID file only if that process is still
+/// alive
+pub fn read_running_pid(pid_file_path: &Path) -> Option<u32> {
+    read_pid(pid_file_path).filter(|pid| is_process_alive(*pid))
+}
+
+/// Checks whether a process with the given PID exists without affecting it
+#[cfg(unix)]
+pub fn is_process_alive(pid: u32) -> bool {
+    Command::new("kill")
+        .arg("-0")
+        .arg(pid.to_string())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+pub fn is_process_alive(_pid: u32) -> bool {
+    false
+}
+
+/// Removes every file used to track a detached node
+pub fn clean_up(data_dir: &Path) {
+    fs::remove_file(pid_file_path(data_dir)).unwrap_or_default();
+    fs::remove_file(ready_file_path(data_dir)).unwrap_or_default();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pid_files_round_trip() {
+        let data_dir = std::env::temp_dir().join(vrrb_core::helpers::generate_random_string());
+        fs::create_dir_all(&data_dir).unwrap();
+
+        let pid_path = pid_file_path(&data_dir);
+
+        assert_eq!(read_pid(&pid_path), None);
+
+        write_pid(&pid_path, std::process::id()).unwrap();
+
+        assert_eq!(read_pid(&pid_path), Some(std::process::id()));
+        assert_eq!(read_running_pid(&pid_path), Some(std::process::id()));
+
+        clean_up(&data_dir);
+
+        assert_eq!(read_pid(&pid_path), None);
+    }
+}
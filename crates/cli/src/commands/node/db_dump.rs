@@ -0,0 +1,151 @@
+use std::path::PathBuf;
+
+use clap::{Parser, ValueEnum};
+use primitives::{Address, DEFAULT_VRRB_DATA_DIR_PATH};
+use serde::Serialize;
+use storage::vrrbdb::{VrrbDb, VrrbDbConfig, VrrbDbReadHandle};
+use vrrb_core::account::Account;
+
+use crate::result::{CliError, Result};
+
+#[derive(Parser, Debug)]
+pub struct DbDumpOpts {
+    /// Data dir of the node whose db should be inspected
+    #[clap(long, value_parser, default_value = DEFAULT_VRRB_DATA_DIR_PATH)]
+    pub data_dir: PathBuf,
+
+    /// Also prints the account stored under this address
+    #[clap(long, value_parser)]
+    pub address: Option<Address>,
+
+    #[clap(long, value_enum, default_value = "text")]
+    pub format: DumpFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DumpFormat {
+    Text,
+    Json,
+}
+
+/// Overview of the contents of a node's db
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DbSummary {
+    pub account_count: usize,
+
+    /// Sum of every account's credits minus its debits
+    pub total_supply: u128,
+
+    pub transaction_count: usize,
+
+    /// Account requested through `--address`, if it was found
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account: Option<Account>,
+}
+
+/// Prints an overview of the db within the given data dir. The db is opened
+/// read-only, so it can be inspected while a node is running on it.
+pub fn db_dump(opts: DbDumpOpts) -> Result<()> {
+    let config = VrrbDbConfig {
+        path: opts.data_dir.join("node").join("db"),
+        ..Default::default()
+    };
+
+    let db = VrrbDb::open_read_only(&config).map_err(|err| CliError::DbOpen {
+        path: config.path.clone(),
+        source: err.into(),
+    })?;
+
+    let summary = summarize_db(&db.read_handle(), opts.address.as_ref());
+
+    match opts.format {
+        DumpFormat::Json => {
+            let out =
+                serde_json::to_string_pretty(&summary).map_err(|err| CliError::Serialization {
+                    context: String::from("unable to serialize db summary"),
+                    source: err,
+                })?;
+
+            println!("{out}");
+        },
+        DumpFormat::Text => {
+            println!("accounts: {}", summary.account_count);
+            println!("total supply: {}", summary.total_supply);
+            println!("transactions: {}", summary.transaction_count);
+
+            if let Some(address) = &opts.address {
+                match &summary.account {
+                    Some(account) => println!("account {address}: {account:?}"),
+                    None => println!("account {address}: not found"),
+                }
+            }
+        },
+    }
+
+    Ok(())
+}
+
+pub fn summarize_db(read_handle: &VrrbDbReadHandle, address: Option<&Address>) -> DbSummary {
+    let accounts = read_handle.state_store_values();
+
+    let total_supply = accounts
+        .values()
+        .map(|account| account.credits.saturating_sub(account.debits))
+        .sum();
+
+    DbSummary {
+        account_count: accounts.len(),
+        total_supply,
+        transaction_count: read_handle.transaction_store_values().len(),
+        account: address.and_then(|address| read_handle.get_account(address)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use primitives::generate_account_keypair;
+    use vrrb_core::txn::null_txn;
+
+    use super::*;
+
+    #[test]
+    fn summaries_cover_accounts_supply_and_transactions() {
+        let mut db = VrrbDb::new(VrrbDbConfig {
+            path: std::env::temp_dir().join(vrrb_core::helpers::generate_random_string()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let (_, public_key) = generate_account_keypair();
+        let address = Address::new(public_key);
+        let account = Account {
+            credits: 150,
+            ..Default::default()
+        };
+
+        let (_, other_public_key) = generate_account_keypair();
+
+        db.insert_account(address.clone(), account.clone()).unwrap();
+        db.insert_account(
+            Address::new(other_public_key),
+            Account {
+                credits: 50,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        db.insert_transaction(null_txn()).unwrap();
+
+        let summary = summarize_db(&db.read_handle(), Some(&address));
+
+        assert_eq!(
+            summary,
+            DbSummary {
+                account_count: 2,
+                total_supply: 200,
+                transaction_count: 1,
+                account: Some(account),
+            }
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use std::{fs, path::PathBuf};
+
+use clap::Parser;
+use primitives::NodeType;
+use vrrb_config::NodeConfig;
+
+use crate::result::{CliError, Result};
+
+#[derive(Parser, Debug)]
+pub struct InitConfigOpts {
+    /// TOML file the sample config is written to
+    #[clap(long, value_parser)]
+    pub output: PathBuf,
+
+    /// Type of node the sample config is meant for
+    #[clap(long, default_value = "full")]
+    pub node_type: String,
+
+    /// Overwrites the output file if it already exists
+    #[clap(long)]
+    pub force: bool,
+}
+
+/// Writes a sample config holding every field's default value, documented
+/// inline, for `run --config` to load
+pub fn init_config(opts: InitConfigOpts) -> Result<()> {
+    if opts.output.extension().and_then(|ext| ext.to_str()) != Some("toml") {
+        return Err(CliError::OptsError(format!(
+            "sample configs are written as TOML, {} should end in .toml",
+            opts.output.display()
+        )));
+    }
+
+    if opts.output.exists() && !opts.force {
+        return Err(CliError::OptsError(format!(
+            "{} already exists, pass --force to overwrite it",
+            opts.output.display()
+        )));
+    }
+
+    let node_type = opts.node_type.parse::<NodeType>()?;
+
+    let config = NodeConfig {
+        node_type,
+        ..Default::default()
+    };
+
+    let template = config
+        .to_toml_template()
+        .map_err(|err| CliError::ConfigWrite {
+            context: String::from("unable to render config template"),
+            source: err.into(),
+        })?;
+
+    if let Some(parent) = opts.output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(&opts.output, template)?;
+
+    println!(
+        "wrote a sample {node_type:?} node config to {}",
+        opts.output.display()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn existing_files_are_only_overwritten_when_forced() {
+        let dir = std::env::temp_dir().join(vrrb_core::helpers::generate_random_string());
+        let output = dir.join("node.toml");
+
+        let opts = |force| InitConfigOpts {
+            output: output.clone(),
+            node_type: String::from("validator"),
+            force,
+        };
+
+        init_config(opts(false)).unwrap();
+
+        let loaded = NodeConfig::from_file(&output).unwrap();
+        assert_eq!(loaded.node_type, NodeType::Validator);
+
+        fs::write(&output, "node_type = \"miner\"").unwrap();
+
+        assert!(matches!(
+            init_config(opts(false)),
+            Err(CliError::OptsError(_))
+        ));
+        assert_eq!(
+            NodeConfig::from_file(&output).unwrap().node_type,
+            NodeType::Miner
+        );
+
+        init_config(opts(true)).unwrap();
+        assert_eq!(
+            NodeConfig::from_file(&output).unwrap().node_type,
+            NodeType::Validator
+        );
+
+        fs::remove_dir_all(&dir).unwrap_or_default();
+    }
+}
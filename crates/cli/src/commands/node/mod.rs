@@ -1,11 +1,21 @@
+mod daemon;
+mod db_dump;
 mod info;
+mod init_config;
 mod run;
+mod stop;
+mod validate;
+
+use std::path::PathBuf;
 
 use clap::{Parser, Subcommand};
+pub use db_dump::*;
 pub use info::*;
+pub use init_config::*;
 pub use run::*;
+pub use stop::*;
 
-use crate::result::{CliError, Result};
+use crate::result::Result;
 
 #[derive(Debug, Subcommand)]
 pub enum NodeCmd {
@@ -15,8 +25,14 @@ pub enum NodeCmd {
     /// Prints currrent node configuration
     Info,
 
-    /// Stops any node currrently running in dettached mode
-    Stop,
+    /// Stops any node currrently running in detached mode
+    Stop(StopOpts),
+
+    /// Prints an overview of a node's db without modifying it
+    DbDump(DbDumpOpts),
+
+    /// Writes a sample config file documenting every option
+    InitConfig(InitConfigOpts),
 }
 
 #[derive(Parser, Debug)]
@@ -25,12 +41,14 @@ pub struct NodeOpts {
     pub subcommand: NodeCmd,
 }
 
-pub async fn exec(args: NodeOpts) -> Result<()> {
+pub async fn exec(args: NodeOpts, config_file_path: Option<PathBuf>) -> Result<()> {
     let sub_cmd = args.subcommand;
 
     match sub_cmd {
-        NodeCmd::Run(opts) => run(opts).await,
+        NodeCmd::Run(opts) => run(opts, config_file_path).await,
         NodeCmd::Info => Ok(()),
-        _ => Err(CliError::InvalidCommand(format!("{:?}", sub_cmd))),
+        NodeCmd::Stop(opts) => stop(opts).await,
+        NodeCmd::DbDump(opts) => db_dump(opts),
+        NodeCmd::InitConfig(opts) => init_config(opts),
     }
 }
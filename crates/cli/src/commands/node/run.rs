@@ -1,25 +1,55 @@
 use std::{
+    collections::HashSet,
+    fs::{self, OpenOptions},
+    future::Future,
     net::{IpAddr, Ipv4Addr, SocketAddr},
-    path::PathBuf,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
     time::Duration,
 };
 
-use clap::{Parser, Subcommand};
+use clap::{ArgMatches, CommandFactory, Subcommand, ValueSource};
 use config::{Config, ConfigError, File};
-use hbbft::crypto::{serde_impl::SerdeSecret, PublicKey, SecretKey};
 use node::{Node, NodeType};
-use primitives::{DEFAULT_VRRB_DATA_DIR_PATH, DEFAULT_VRRB_DB_PATH};
-use secp256k1::{rand, Secp256k1};
+use primitives::{
+    ChainId,
+    NodeId,
+    NodeIdExt,
+    NodeIdx,
+    DEFAULT_CHAIN_ID,
+    DEFAULT_VRRB_DATA_DIR_PATH,
+    DEFAULT_VRRB_DB_PATH,
+};
 use serde::Deserialize;
-use telemetry::{error, info, warn};
+use telemetry::{error, info, warn, LogFormat, LogLevel, TelemetrySubscriber};
 use uuid::Uuid;
-use vrrb_config::NodeConfig;
+use vrrb_config::{
+    NodeConfig,
+    RuntimeConfig,
+    DEFAULT_BOOTSTRAP_QUORUM_TIMEOUT,
+    DEFAULT_MAX_TXN_SIZE_BYTES,
+    DEFAULT_MEMPOOL_MAX_SIZE,
+    DEFAULT_MEMPOOL_TXN_TTL,
+    DEFAULT_READINESS_TIMEOUT,
+    DEFAULT_SHUTDOWN_TIMEOUT,
+    DEFAULT_VALIDATION_PARALLELISM,
+    DEFAULT_VALIDATION_QUEUE_DEPTH,
+};
 use vrrb_core::{
-    event_router::Event,
-    keypair::{self, read_keypair_file, write_keypair_file, Keypair},
+    event_router::{Event, ShutdownReason},
+    keyfile,
+    keypair::{self, read_keypair_file, write_keypair_file_with, Keypair},
+};
+
+use super::daemon;
+use crate::{
+    commands::utils::{config_file_bool, config_file_sets, read_node_config_from_file},
+    result::{CliError, Result},
 };
 
-use crate::result::{CliError, Result};
+/// How long to wait for a detached node to report it is listening
+const DETACHED_NODE_STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+const DETACHED_NODE_POLL_INTERVAL: Duration = Duration::from_millis(250);
 
 const DEFAULT_OS_ASSIGNED_PORT_ADDRESS: &str = "127.0.0.1:0";
 const DEFAULT_JSONRPC_ADDRESS: &str = "127.0.0.1:9293";
@@ -29,19 +59,64 @@ const DEFAULT_RAPTORQ_GOSSIP_ADDRESS: &str = DEFAULT_OS_ASSIGNED_PORT_ADDRESS;
 #[derive(clap::Parser, Debug, Clone, Deserialize)]
 pub struct RunOpts {
     /// Start node as a background process
-    #[clap(short, long, action, default_value = "false")]
-    pub dettached: bool,
+    // NOTE: `dettached`, on the command line or within config files, is a
+    // deprecated spelling kept working until it's removed
+    #[clap(
+        short,
+        long,
+        action,
+        default_value = "false",
+        alias = "dettached",
+        conflicts_with = "foreground"
+    )]
+    #[serde(default, alias = "dettached")]
+    pub detached: bool,
+
+    /// Keeps the node in the foreground, even if its config file asks for it
+    /// to run detached
+    #[clap(long, action, default_value = "false")]
+    #[serde(default)]
+    pub foreground: bool,
 
     ///Shows debugging config information
     #[clap(long, action, default_value = "false")]
     pub debug_config: bool,
 
+    /// Checks the resolved configuration, including whether paths are
+    /// writable and addresses can be bound, then exits without starting the
+    /// node
+    #[clap(long = "validate", action, default_value = "false")]
+    #[serde(default)]
+    pub validate_only: bool,
+
     #[clap(short, long, value_parser)]
     pub id: Option<primitives::NodeId>,
 
     #[clap(long, value_parser)]
     pub idx: Option<primitives::NodeIdx>,
 
+    /// Network the node belongs to, defaults to the development network's
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub chain_id: Option<ChainId>,
+
+    /// Turns away transactions that don't specify a chain id, i.e. the ones
+    /// signed before chain ids were introduced
+    #[clap(long, action, default_value = "false")]
+    #[serde(default)]
+    pub reject_txns_without_chain_id: bool,
+
+    /// Most verbose level of the events logged: trace, debug, info, warn or
+    /// error. Defaults to info
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub log_level: Option<LogLevel>,
+
+    /// Layout of log lines: pretty or json. Defaults to json
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub log_format: Option<LogFormat>,
+
     /// Defines the type of node created by this program
     #[clap(short = 't', long, value_parser, default_value = "full")]
     pub node_type: String,
@@ -52,6 +127,12 @@ pub struct RunOpts {
     #[clap(long, value_parser, default_value = DEFAULT_VRRB_DB_PATH)]
     pub db_path: PathBuf,
 
+    /// JSON file of the accounts the chain starts out with, only applied to
+    /// an empty db
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub genesis_path: Option<PathBuf>,
+
     #[clap(long, value_parser, default_value = DEFAULT_UDP_GOSSIP_ADDRESS)]
     pub udp_gossip_address: SocketAddr,
 
@@ -64,6 +145,11 @@ pub struct RunOpts {
     #[clap(long, value_parser, default_value = DEFAULT_JSONRPC_ADDRESS)]
     pub jsonrpc_api_address: SocketAddr,
 
+    /// Token JSON-RPC clients must present to manage the node's peers
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub jsonrpc_admin_token: Option<String>,
+
     #[clap(long, default_value = "false")]
     pub bootstrap: bool,
 
@@ -81,27 +167,61 @@ pub struct RunOpts {
     /// Disables networking capabilities of the node
     #[clap(long, action, default_value = "false")]
     pub disable_networking: bool,
+
+    /// Derives the node id from the node's public key, so it stays the same
+    /// across restarts. Ignored when an id is set explicitly
+    #[clap(long, action, default_value = "false")]
+    #[serde(default)]
+    pub derive_id_from_key: bool,
+
+    /// Replaces the idx persisted within the node's data dir with the
+    /// configured one instead of refusing to start
+    #[clap(long, action, default_value = "false")]
+    #[serde(default)]
+    pub force_idx: bool,
+
+    /// Logs significant events, e.g. confirmed blocks or peers joining, to an
+    /// event store within the node's data dir
+    #[clap(long, action, default_value = "false")]
+    #[serde(default)]
+    pub enable_event_store: bool,
+
+    /// Encrypts the keypair generated for the node with a passphrase, read
+    /// from VRRB_KEYFILE_PASSPHRASE or prompted for. Encrypted keypairs are
+    /// decrypted the same way on every run
+    #[clap(long, action, default_value = "false")]
+    #[serde(default)]
+    pub encrypt_keypair: bool,
+
+    /// Path to a fully resolved node config. Used internally to hand the
+    /// config over to nodes started in detached mode
+    #[clap(long, value_parser, hide = true)]
+    pub resolved_config: Option<PathBuf>,
+
+    /// Ids of the options passed explicitly on the command line, see
+    /// `RunOpts::track_explicit_flags`. Only those override a config file's
+    /// values, even when they're set to their default value.
+    #[clap(skip)]
+    #[serde(skip)]
+    pub explicit_flags: HashSet<String>,
 }
 
+/// Converts CLI options into a node config. The resulting config has no
+/// keypair, `run` is responsible for loading or generating it.
 impl From<RunOpts> for NodeConfig {
     fn from(opts: RunOpts) -> Self {
-        let default_node_config = NodeConfig::default();
-
-        let node_type = match opts.node_type.parse() {
-            Ok(node_type) => node_type,
-            Err(_) => default_node_config.node_type,
-        };
+        let node_type = opts.node_type.parse().unwrap_or(NodeType::Full);
 
         let http_api_title = if !opts.http_api_title.is_empty() {
             opts.http_api_title.clone()
         } else {
-            default_node_config.http_api_title.clone()
+            String::from("VRRB Node")
         };
 
         Self {
-            id: opts.id.unwrap_or(default_node_config.id),
-            idx: opts.idx.unwrap_or(default_node_config.idx),
-            data_dir: opts.data_dir,
+            id: opts.id.unwrap_or_else(|| Uuid::new_v4().to_string()),
+            idx: opts.idx.unwrap_or_default(),
+            data_dir: opts.data_dir.clone(),
             db_path: opts.db_path,
             node_type,
             raptorq_gossip_address: opts.raptorq_gossip_address,
@@ -109,20 +229,40 @@ impl From<RunOpts> for NodeConfig {
             http_api_address: opts.http_api_address,
             http_api_title,
             http_api_version: opts.http_api_version,
-            http_api_shutdown_timeout: default_node_config.http_api_shutdown_timeout,
+            http_api_shutdown_timeout: None,
             jsonrpc_server_address: opts.jsonrpc_api_address,
-            preload_mock_state: default_node_config.preload_mock_state,
-            bootstrap_config: default_node_config.bootstrap_config,
-            bootstrap_node_addresses: opts
-                .bootstrap_node_addresses
-                .unwrap_or(default_node_config.bootstrap_node_addresses),
-
-            // TODO: avoid double key generation
-            // This a random keypair gets generated here, but then afterwards we read it from disk
-            // and use that if its available thus making this generation wasteful. This is a bit of
-            // a hack, but it works for now.
-            keypair: default_node_config.keypair,
+            jsonrpc_admin_token: opts.jsonrpc_admin_token,
+            preload_mock_state: false,
+            genesis_path: opts.genesis_path,
+            bootstrap_config: None,
+            bootstrap_node_addresses: opts.bootstrap_node_addresses.unwrap_or_default(),
+            keypair: None,
             disable_networking: opts.disable_networking,
+            accept_txns_without_networking: false,
+            shutdown_timeout: DEFAULT_SHUTDOWN_TIMEOUT,
+            readiness_timeout: DEFAULT_READINESS_TIMEOUT,
+            min_bootstrap_peers: 0,
+            bootstrap_quorum_timeout: DEFAULT_BOOTSTRAP_QUORUM_TIMEOUT,
+            exit_without_bootstrap_quorum: false,
+            // NOTE: both are placed within the node's data dir once it's
+            // resolved, see `place_stores_in_data_dir`
+            mempool_snapshot_path: None,
+            event_store_path: None,
+            event_router_config: Default::default(),
+            supervision_config: Default::default(),
+            runtime_config: Default::default(),
+            mempool_max_size: Some(DEFAULT_MEMPOOL_MAX_SIZE),
+            mempool_txn_ttl: DEFAULT_MEMPOOL_TXN_TTL,
+            mining_config: Default::default(),
+            broadcast_engine_config: Default::default(),
+            fee_config: Default::default(),
+            validation_queue_depth: DEFAULT_VALIDATION_QUEUE_DEPTH,
+            validation_parallelism: DEFAULT_VALIDATION_PARALLELISM,
+            chain_id: opts.chain_id.unwrap_or(DEFAULT_CHAIN_ID),
+            reject_txns_without_chain_id: opts.reject_txns_without_chain_id,
+            max_txn_size_bytes: DEFAULT_MAX_TXN_SIZE_BYTES,
+            log_level: opts.log_level.unwrap_or_default(),
+            log_format: opts.log_format.unwrap_or_default(),
         }
     }
 }
@@ -133,28 +273,73 @@ impl Default for RunOpts {
             SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 0);
 
         Self {
-            dettached: Default::default(),
+            detached: Default::default(),
+            foreground: Default::default(),
             debug_config: Default::default(),
+            validate_only: Default::default(),
             id: Default::default(),
             idx: Default::default(),
+            chain_id: Default::default(),
+            reject_txns_without_chain_id: Default::default(),
+            log_level: Default::default(),
+            log_format: Default::default(),
             node_type: Default::default(),
             data_dir: Default::default(),
             db_path: Default::default(),
+            genesis_path: Default::default(),
             udp_gossip_address: ipv4_localhost_with_random_port,
             raptorq_gossip_address: ipv4_localhost_with_random_port,
             http_api_address: ipv4_localhost_with_random_port,
             jsonrpc_api_address: ipv4_localhost_with_random_port,
+            jsonrpc_admin_token: Default::default(),
             bootstrap: Default::default(),
             bootstrap_node_addresses: Default::default(),
             http_api_title: Default::default(),
             http_api_version: Default::default(),
             disable_networking: Default::default(),
+            derive_id_from_key: Default::default(),
+            force_idx: Default::default(),
+            enable_event_store: Default::default(),
+            encrypt_keypair: Default::default(),
+            resolved_config: Default::default(),
+            explicit_flags: Default::default(),
         }
     }
 }
 
 impl RunOpts {
-    #[deprecated(note = "prefer global config file")]
+    /// Ensures none of the addresses the node binds to collide with each
+    /// other. Addresses on port 0 are assigned by the OS and thus exempt.
+    pub fn validate(&self) -> Result<()> {
+        if self.detached && self.foreground {
+            return Err(CliError::OptsError(String::from(
+                "--detached and --foreground cannot be used together",
+            )));
+        }
+
+        let bind_addresses = [
+            ("udp-gossip-address", self.udp_gossip_address),
+            ("raptorq-gossip-address", self.raptorq_gossip_address),
+            ("http-api-address", self.http_api_address),
+            ("jsonrpc-api-address", self.jsonrpc_api_address),
+        ];
+
+        for (i, (flag, addr)) in bind_addresses.iter().enumerate() {
+            for (other_flag, other_addr) in bind_addresses.iter().skip(i + 1) {
+                if addresses_collide(addr, other_addr) {
+                    return Err(CliError::ConflictingAddresses(
+                        flag.to_string(),
+                        other_flag.to_string(),
+                        *addr,
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[deprecated(note = "use NodeConfig::from_file instead")]
     pub fn from_file(config_path: &str) -> std::result::Result<Self, ConfigError> {
         let default_bootstrap_addresses: Vec<String> = Vec::new();
 
@@ -171,143 +356,1354 @@ impl RunOpts {
             .set_default("preload_mock_state", false)?
             .set_default("debug_config", false)?
             .set_default("bootstrap", false)?
-            .set_default("dettached", false)?
+            // NOTE: `detached` is left to its serde default, setting it here
+            // would clash with files using the deprecated `dettached` key
             .add_source(File::with_name(config_path))
             .build()?;
 
         Ok(s.try_deserialize().unwrap_or_default())
     }
 
-    pub fn merge(&self, other: &Self) -> Self {
-        let node_type = match self.node_type.parse::<NodeType>() {
-            Ok(_) => self.node_type.clone(),
-            Err(_) => other.node_type.clone(),
-        };
+    /// Records which options were passed explicitly within `matches`, the
+    /// arguments these options were parsed from, see `explicit_flags`
+    pub fn track_explicit_flags(&mut self, matches: &ArgMatches) {
+        for arg in RunOpts::command().get_arguments() {
+            let id = arg.get_id();
 
-        let data_dir = if !self.data_dir.to_str().unwrap_or_default().is_empty() {
-            self.data_dir.clone()
-        } else {
-            other.data_dir.clone()
-        };
+            if matches.value_source(id) == Some(ValueSource::CommandLine) {
+                self.explicit_flags.insert(id.to_string());
+            }
+        }
+    }
 
-        let db_path = if !self.db_path.to_str().unwrap_or_default().is_empty() {
-            self.db_path.clone()
-        } else {
-            other.db_path.clone()
-        };
+    fn is_explicit(&self, flag: &str) -> bool {
+        self.explicit_flags.contains(flag)
+    }
 
-        let bootstrap_node_addresses = if other.bootstrap_node_addresses.is_none() {
-            self.bootstrap_node_addresses.clone()
+    /// Returns the command line's value of `flag` if it was passed
+    /// explicitly, or the file's otherwise
+    fn cli_or_file<T: Clone>(&self, flag: &str, cli: &T, file: &T) -> T {
+        if self.is_explicit(flag) {
+            cli.clone()
         } else {
-            other.bootstrap_node_addresses.clone()
-        };
+            file.clone()
+        }
+    }
 
-        let http_api_title = if !self.http_api_title.is_empty() {
-            self.http_api_title.clone()
+    /// Combines the options passed on the command line (`self`) with the ones
+    /// read from a config file (`other`).
+    ///
+    /// Options passed explicitly on the command line override the file's,
+    /// even when they're set to their default value, see `explicit_flags`.
+    /// Every other option falls back to the file's. An explicit --foreground
+    /// wins over a file asking for the node to run detached, and the other
+    /// way around.
+    pub fn merge(&self, other: &Self) -> Self {
+        let (detached, foreground) = if self.is_explicit("detached") {
+            (true, false)
+        } else if self.is_explicit("foreground") {
+            (false, true)
         } else {
-            other.http_api_title.clone()
+            (other.detached, other.foreground)
         };
 
-        let http_api_version = if !self.http_api_version.is_empty() {
-            self.http_api_version.clone()
-        } else {
-            other.http_api_version.clone()
-        };
+        Self {
+            detached,
+            foreground,
+            debug_config: self.cli_or_file("debug_config", &self.debug_config, &other.debug_config),
+            validate_only: self.cli_or_file(
+                "validate_only",
+                &self.validate_only,
+                &other.validate_only,
+            ),
+            id: self.cli_or_file("id", &self.id, &other.id),
+            idx: self.cli_or_file("idx", &self.idx, &other.idx),
+            chain_id: self.cli_or_file("chain_id", &self.chain_id, &other.chain_id),
+            reject_txns_without_chain_id: self.cli_or_file(
+                "reject_txns_without_chain_id",
+                &self.reject_txns_without_chain_id,
+                &other.reject_txns_without_chain_id,
+            ),
+            log_level: self.cli_or_file("log_level", &self.log_level, &other.log_level),
+            log_format: self.cli_or_file("log_format", &self.log_format, &other.log_format),
+            node_type: self.cli_or_file("node_type", &self.node_type, &other.node_type),
+            data_dir: self.cli_or_file("data_dir", &self.data_dir, &other.data_dir),
+            db_path: self.cli_or_file("db_path", &self.db_path, &other.db_path),
+            genesis_path: self.cli_or_file("genesis_path", &self.genesis_path, &other.genesis_path),
+            udp_gossip_address: self.cli_or_file(
+                "udp_gossip_address",
+                &self.udp_gossip_address,
+                &other.udp_gossip_address,
+            ),
+            raptorq_gossip_address: self.cli_or_file(
+                "raptorq_gossip_address",
+                &self.raptorq_gossip_address,
+                &other.raptorq_gossip_address,
+            ),
+            http_api_address: self.cli_or_file(
+                "http_api_address",
+                &self.http_api_address,
+                &other.http_api_address,
+            ),
+            jsonrpc_api_address: self.cli_or_file(
+                "jsonrpc_api_address",
+                &self.jsonrpc_api_address,
+                &other.jsonrpc_api_address,
+            ),
+            jsonrpc_admin_token: self.cli_or_file(
+                "jsonrpc_admin_token",
+                &self.jsonrpc_admin_token,
+                &other.jsonrpc_admin_token,
+            ),
+            bootstrap: self.cli_or_file("bootstrap", &self.bootstrap, &other.bootstrap),
+            bootstrap_node_addresses: self.cli_or_file(
+                "bootstrap_node_addresses",
+                &self.bootstrap_node_addresses,
+                &other.bootstrap_node_addresses,
+            ),
+            http_api_title: self.cli_or_file(
+                "http_api_title",
+                &self.http_api_title,
+                &other.http_api_title,
+            ),
+            http_api_version: self.cli_or_file(
+                "http_api_version",
+                &self.http_api_version,
+                &other.http_api_version,
+            ),
+            disable_networking: self.cli_or_file(
+                "disable_networking",
+                &self.disable_networking,
+                &other.disable_networking,
+            ),
+            derive_id_from_key: self.cli_or_file(
+                "derive_id_from_key",
+                &self.derive_id_from_key,
+                &other.derive_id_from_key,
+            ),
+            force_idx: self.cli_or_file("force_idx", &self.force_idx, &other.force_idx),
+            enable_event_store: self.cli_or_file(
+                "enable_event_store",
+                &self.enable_event_store,
+                &other.enable_event_store,
+            ),
+            encrypt_keypair: self.cli_or_file(
+                "encrypt_keypair",
+                &self.encrypt_keypair,
+                &other.encrypt_keypair,
+            ),
+            resolved_config: self.cli_or_file(
+                "resolved_config",
+                &self.resolved_config,
+                &other.resolved_config,
+            ),
+            explicit_flags: self.explicit_flags.clone(),
+        }
+    }
+}
 
+/// The options a config file sets, so they can be merged with the ones passed
+/// on the command line, see `RunOpts::merge`. Options with no counterpart
+/// within node configs are left to their defaults.
+impl From<&NodeConfig> for RunOpts {
+    fn from(config: &NodeConfig) -> Self {
         Self {
-            dettached: other.dettached,
-            debug_config: other.debug_config,
-            id: self.id.clone().or(other.id.clone()),
-            idx: self.idx.clone().or(other.idx),
-            node_type,
-            data_dir,
-            db_path,
-            // TODO: reconsider override strategies
-            udp_gossip_address: other.udp_gossip_address,
-            raptorq_gossip_address: other.raptorq_gossip_address,
-            jsonrpc_api_address: other.jsonrpc_api_address,
-            bootstrap: other.bootstrap,
-            bootstrap_node_addresses,
-            http_api_address: other.http_api_address,
-            http_api_title,
-            http_api_version,
-            disable_networking: false,
+            id: Some(config.id.clone()),
+            idx: Some(config.idx),
+            chain_id: Some(config.chain_id),
+            reject_txns_without_chain_id: config.reject_txns_without_chain_id,
+            log_level: Some(config.log_level),
+            log_format: Some(config.log_format),
+            node_type: config.node_type.to_string(),
+            data_dir: config.data_dir.clone(),
+            db_path: config.db_path.clone(),
+            genesis_path: config.genesis_path.clone(),
+            udp_gossip_address: config.udp_gossip_address,
+            raptorq_gossip_address: config.raptorq_gossip_address,
+            http_api_address: config.http_api_address,
+            jsonrpc_api_address: config.jsonrpc_server_address,
+            jsonrpc_admin_token: config.jsonrpc_admin_token.clone(),
+            bootstrap_node_addresses: Some(config.bootstrap_node_addresses.clone()),
+            http_api_title: config.http_api_title.clone(),
+            http_api_version: config.http_api_version.clone(),
+            disable_networking: config.disable_networking,
+            ..Default::default()
         }
     }
 }
 
-/// Configures and runs a VRRB Node
-pub async fn run(args: RunOpts) -> Result<()> {
-    let data_dir = vrrb_core::storage_utils::get_node_data_dir()?;
+/// Two bind addresses collide when they use the same concrete port on the same
+/// IP, or when either of them binds to all interfaces
+fn addresses_collide(a: &SocketAddr, b: &SocketAddr) -> bool {
+    if a.port() == 0 || a.port() != b.port() {
+        return false;
+    }
 
-    std::fs::create_dir_all(&data_dir)?;
+    a.ip() == b.ip() || a.ip().is_unspecified() || b.ip().is_unspecified()
+}
 
-    let keypair_file_path = PathBuf::from(&data_dir).join("keypair");
-    let keypair = match read_keypair_file(&keypair_file_path) {
-        Ok(keypair) => keypair,
-        Err(err) => {
-            warn!("Failed to read keypair file: {}", err);
-            info!("Generating new keypair");
-            let keypair = Keypair::random();
+/// Configures and runs a VRRB Node. Options passed on the command line take
+/// precedence over the ones read from `config_file_path`, if provided.
+pub async fn run(args: RunOpts, config_file_path: Option<PathBuf>) -> Result<()> {
+    args.validate()?;
 
-            write_keypair_file(&keypair, &keypair_file_path)
-                .map_err(|err| CliError::Other(format!("failed to write keypair file: {err}")))?;
+    if let Some(resolved_config_path) = &args.resolved_config {
+        // NOTE: the parent process passes the data dir it resolved along
+        // with the config
+        let data_dir = resolve_node_data_dir(&args.data_dir)?;
 
-            keypair
-        },
-    };
+        let mut node_config = read_resolved_config(resolved_config_path)?;
+        // NOTE: the parent process already generated the keypair if needed
+        node_config.keypair = Some(read_or_generate_keypair(&data_dir, false)?);
+
+        let config_source = ConfigSource::Resolved(resolved_config_path.clone());
+
+        init_telemetry(&node_config)?;
+
+        return run_detached_child(node_config, &data_dir, config_source).await;
+    }
+
+    if args.validate_only {
+        let mut node_config = NodeConfig::from(args.clone());
+
+        if let Some(config_file_path) = config_file_path {
+            let file_config = read_node_config_from_file(config_file_path)?;
+            node_config = merge_with_file_config(&args, file_config);
+        }
+
+        let data_dir = resolve_node_data_dir(&node_config.data_dir)?;
+        place_stores_in_data_dir(&mut node_config, &data_dir, args.enable_event_store);
+
+        init_telemetry(&node_config)?;
+
+        return super::validate::exec(&node_config, &data_dir);
+    }
 
     let mut node_config = NodeConfig::from(args.clone());
-    node_config.keypair = keypair;
+    let mut explicit_id = args.id.is_some();
+    let mut explicit_idx = args.idx.is_some();
+
+    if let Some(config_file_path) = &config_file_path {
+        let file_config = read_node_config_from_file(config_file_path.clone())?;
+        node_config = merge_with_file_config(&args, file_config);
+        explicit_id |= config_file_sets(config_file_path, "id");
+        explicit_idx |= config_file_sets(config_file_path, "idx");
+    }
+
+    // NOTE: every path the node uses is derived from this one dir, which may
+    // come from the config file
+    let data_dir = resolve_node_data_dir(&node_config.data_dir)?;
+
+    std::fs::create_dir_all(&data_dir)?;
+
+    node_config.keypair = Some(read_or_generate_keypair(&data_dir, args.encrypt_keypair)?);
+    place_stores_in_data_dir(&mut node_config, &data_dir, args.enable_event_store);
+
+    init_telemetry(&node_config)?;
+
+    node_config.idx = resolve_node_idx(node_config.idx, explicit_idx, args.force_idx, &data_dir)?;
+
+    if args.derive_id_from_key {
+        derive_node_id(&mut node_config, explicit_id);
+    }
+
+    let detached = resolve_detached(&args, config_file_path.as_deref());
+
+    let config_source = config_file_path.map(|path| {
+        // NOTE: the id is pinned so reloads don't pick a new random one
+        let mut args = args.clone();
+        args.id = Some(node_config.id.clone());
+        args.explicit_flags.insert(String::from("id"));
+
+        ConfigSource::File { path, args }
+    });
 
     if args.debug_config {
         dbg!(&node_config);
     }
 
-    if args.dettached {
-        run_dettached(node_config).await
+    if detached {
+        run_detached(node_config, &data_dir).await
     } else {
-        run_blocking(node_config).await
+        run_blocking(node_config, config_source).await
+    }
+}
+
+/// Whether the node runs as a background process. Flags passed on the
+/// command line win, the config file's `detached` key is read otherwise.
+fn resolve_detached(args: &RunOpts, config_file_path: Option<&Path>) -> bool {
+    if args.detached || args.foreground {
+        return args.detached;
+    }
+
+    config_file_path
+        .and_then(|path| config_file_bool(path, "detached", "dettached"))
+        .unwrap_or_default()
+}
+
+/// Sets up logging at the level and in the format the node is configured with
+fn init_telemetry(node_config: &NodeConfig) -> Result<()> {
+    TelemetrySubscriber::init_with(
+        std::io::stdout,
+        node_config.log_level,
+        node_config.log_format,
+    )?;
+
+    Ok(())
+}
+
+/// Replaces the node's generated id with one derived from its public key.
+/// Ids set explicitly, on the command line or within a config file, are kept
+/// so nodes that already have one don't change identity.
+fn derive_node_id(node_config: &mut NodeConfig, explicit_id: bool) {
+    let keypair = match &node_config.keypair {
+        Some(keypair) => keypair,
+        None => return,
+    };
+
+    let derived_id = NodeId::from_public_key(keypair.get_miner_public_key());
+
+    if explicit_id {
+        if node_config.id != derived_id {
+            warn!(
+                "keeping configured node id {} instead of deriving one from the keypair",
+                node_config.id
+            );
+        }
+
+        return;
+    }
+
+    node_config.id = derived_id;
+}
+
+/// Where a running node's config is read from again when it is asked to
+/// reload it
+#[derive(Debug, Clone)]
+enum ConfigSource {
+    /// A config file, with the options passed on the command line layered
+    /// over it
+    File { path: PathBuf, args: RunOpts },
+    /// The fully resolved config handed over to a detached node
+    Resolved(PathBuf),
+}
+
+impl ConfigSource {
+    fn load(&self) -> Result<NodeConfig> {
+        match self {
+            ConfigSource::File { path, args } => {
+                let file_config = read_node_config_from_file(path.clone())?;
+                let mut node_config = merge_with_file_config(args, file_config);
+
+                // NOTE: paths are resolved the same way they were at startup
+                // so they don't read as changed
+                let data_dir = resolve_node_data_dir(&node_config.data_dir)?;
+                place_stores_in_data_dir(&mut node_config, &data_dir, args.enable_event_store);
+
+                Ok(node_config)
+            },
+            ConfigSource::Resolved(path) => read_resolved_config(path),
+        }
+    }
+}
+
+/// Returns the directory a node keeps its keypair, database, snapshots and
+/// resolved configs in: the `node` directory within `data_dir` if a custom
+/// one is set, or the default node data dir otherwise
+pub(super) fn resolve_node_data_dir(data_dir: &Path) -> Result<PathBuf> {
+    if data_dir.as_os_str().is_empty() || data_dir == Path::new(DEFAULT_VRRB_DATA_DIR_PATH) {
+        return Ok(vrrb_core::storage_utils::get_node_data_dir()?);
+    }
+
+    Ok(data_dir.join("node"))
+}
+
+/// Keeps the database, the mempool snapshot and, if enabled, the event store
+/// within `data_dir` unless their paths were configured explicitly
+fn place_stores_in_data_dir(
+    node_config: &mut NodeConfig,
+    data_dir: &Path,
+    enable_event_store: bool,
+) {
+    if node_config.db_path.as_os_str().is_empty()
+        || node_config.db_path == Path::new(DEFAULT_VRRB_DB_PATH)
+    {
+        node_config.db_path = data_dir.join("db");
+    }
+
+    if node_config.mempool_snapshot_path.is_none() {
+        node_config.mempool_snapshot_path = Some(data_dir.join("mempool.snapshot"));
+    }
+
+    if enable_event_store && node_config.event_store_path.is_none() {
+        node_config.event_store_path = Some(data_dir.join("events"));
+    }
+}
+
+/// Layers the options passed on the command line over a config read from a
+/// file, see `RunOpts::merge` for which of them take precedence. Fields no
+/// option maps to keep the file's values.
+fn merge_with_file_config(args: &RunOpts, file_config: NodeConfig) -> NodeConfig {
+    let options = NodeConfig::from(args.merge(&RunOpts::from(&file_config)));
+
+    NodeConfig {
+        id: options.id,
+        idx: options.idx,
+        data_dir: options.data_dir,
+        db_path: options.db_path,
+        node_type: options.node_type,
+        raptorq_gossip_address: options.raptorq_gossip_address,
+        udp_gossip_address: options.udp_gossip_address,
+        http_api_address: options.http_api_address,
+        http_api_title: options.http_api_title,
+        http_api_version: options.http_api_version,
+        jsonrpc_server_address: options.jsonrpc_server_address,
+        jsonrpc_admin_token: options.jsonrpc_admin_token,
+        genesis_path: options.genesis_path,
+        bootstrap_node_addresses: options.bootstrap_node_addresses,
+        disable_networking: options.disable_networking,
+        chain_id: options.chain_id,
+        reject_txns_without_chain_id: options.reject_txns_without_chain_id,
+        log_level: options.log_level,
+        log_format: options.log_format,
+        // NOTE: the keypair always comes from the node's data dir
+        keypair: None,
+        ..file_config
+    }
+}
+
+/// Reads the node's keypair from disk, generating and persisting a new one if
+/// it doesn't exist yet. This is the only place a node keypair is generated.
+///
+/// A keypair file that exists but can't be read or parsed is never replaced,
+/// since doing so would silently rotate the node's identity.
+///
+/// Newly generated keypairs are encrypted with a passphrase if `encrypt` is
+/// set, see `keyfile::read_new_passphrase`.
+fn read_or_generate_keypair(data_dir: &Path, encrypt: bool) -> Result<Keypair> {
+    let keypair_file_path = PathBuf::from(data_dir).join("keypair");
+
+    match fs::metadata(&keypair_file_path) {
+        Ok(_) => read_keypair_file(&keypair_file_path)
+            .map_err(|err| CliError::UnreadableKeypair(keypair_file_path, err.to_string())),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            info!("No keypair found, generating new keypair");
+            let keypair = Keypair::random();
+
+            let passphrase = if encrypt {
+                Some(
+                    keyfile::read_new_passphrase().map_err(|err| CliError::KeypairIo {
+                        context: String::from("unable to read keypair passphrase"),
+                        source: err.into(),
+                    })?,
+                )
+            } else {
+                None
+            };
+
+            backup_keypair_file(&keypair_file_path)?;
+
+            write_keypair_file_with(&keypair, &keypair_file_path, passphrase.as_deref()).map_err(
+                |err| CliError::KeypairIo {
+                    context: format!(
+                        "failed to write keypair file {}",
+                        keypair_file_path.display()
+                    ),
+                    source: err.into(),
+                },
+            )?;
+
+            Ok(keypair)
+        },
+        Err(err) => Err(CliError::UnreadableKeypair(
+            keypair_file_path,
+            err.to_string(),
+        )),
+    }
+}
+
+/// Returns the idx the node runs with. The first idx a node runs with is
+/// persisted within its data dir and reused on subsequent runs, so restarts
+/// don't change the node's position within its quorum.
+///
+/// An explicitly configured idx that differs from the persisted one is an
+/// error, unless `force` is set, in which case it replaces the persisted one.
+fn resolve_node_idx(
+    configured_idx: NodeIdx,
+    explicit: bool,
+    force: bool,
+    data_dir: &Path,
+) -> Result<NodeIdx> {
+    let idx_file_path = data_dir.join("idx");
+
+    let persisted_idx = match fs::read_to_string(&idx_file_path) {
+        Ok(contents) => {
+            Some(
+                contents
+                    .trim()
+                    .parse::<NodeIdx>()
+                    .map_err(|err| CliError::ConfigRead {
+                        context: format!(
+                            "unable to parse node idx file {}",
+                            idx_file_path.display()
+                        ),
+                        source: err.into(),
+                    })?,
+            )
+        },
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+        Err(err) => return Err(err.into()),
+    };
+
+    match persisted_idx {
+        Some(persisted) if !explicit || persisted == configured_idx => Ok(persisted),
+        Some(persisted) if !force => Err(CliError::ConflictingIdx {
+            configured: configured_idx,
+            persisted,
+        }),
+        Some(persisted) => {
+            warn!("Replacing persisted node idx {persisted} with {configured_idx}");
+            fs::write(&idx_file_path, configured_idx.to_string())?;
+            Ok(configured_idx)
+        },
+        None => {
+            fs::write(&idx_file_path, configured_idx.to_string())?;
+            Ok(configured_idx)
+        },
+    }
+}
+
+/// Copies an existing keypair file aside before it gets overwritten, in case
+/// one was written after the node checked for it.
+fn backup_keypair_file(keypair_file_path: &Path) -> Result<()> {
+    if !keypair_file_path.exists() {
+        return Ok(());
     }
+
+    let backup_path =
+        keypair_file_path.with_extension(format!("{}.bak", chrono::Utc::now().timestamp()));
+
+    warn!(
+        "Backing up existing keypair file to {}",
+        backup_path.display()
+    );
+
+    fs::copy(keypair_file_path, &backup_path)?;
+
+    Ok(())
+}
+
+fn read_resolved_config(path: &Path) -> Result<NodeConfig> {
+    let contents = fs::read(path)?;
+
+    serde_json::from_slice(&contents).map_err(|err| CliError::ConfigRead {
+        context: format!("failed to read resolved node config {}", path.display()),
+        source: err.into(),
+    })
 }
 
 #[telemetry::instrument]
-async fn run_blocking(node_config: NodeConfig) -> Result<()> {
+async fn run_blocking(node_config: NodeConfig, config_source: Option<ConfigSource>) -> Result<()> {
+    let runtime_config = node_config.runtime_config.clone();
+
+    run_on_node_runtime(&runtime_config, run_node(node_config, None, config_source)).await
+}
+
+/// Returns a builder of the multi-threaded runtime nodes run on, sized
+/// according to `config`
+fn node_runtime_builder(config: &RuntimeConfig) -> tokio::runtime::Builder {
+    let mut builder = tokio::runtime::Builder::new_multi_thread();
+
+    builder
+        .worker_threads(config.worker_threads())
+        .max_blocking_threads(config.max_blocking_threads.max(1))
+        .thread_name("vrrb-node")
+        .enable_all();
+
+    builder
+}
+
+/// Runs `node` on a runtime of its own built according to `config`, rather
+/// than on the one the CLI parses its arguments on
+async fn run_on_node_runtime<F>(config: &RuntimeConfig, node: F) -> Result<()>
+where
+    F: Future<Output = Result<()>> + Send + 'static,
+{
+    let runtime = node_runtime_builder(config).build()?;
+
+    info!(
+        "running node on {} worker threads and up to {} blocking threads",
+        config.worker_threads(),
+        config.max_blocking_threads.max(1)
+    );
+
+    let result = runtime.spawn(node).await;
+
+    // NOTE: the node already stopped its modules by now, whatever tasks are
+    // left are let go of without blocking the CLI's runtime
+    runtime.shutdown_background();
+
+    result.map_err(|err| CliError::NodeStop {
+        context: String::from("failed to join node runtime task"),
+        source: err.into(),
+    })?
+}
+
+/// Starts a node and keeps it running until a stop signal is received. If a
+/// ready file path is provided, the node's JSON-RPC address is written to it
+/// once the node is listening. On unix systems, a SIGHUP reloads the node's
+/// config from `config_source`.
+async fn run_node(
+    node_config: NodeConfig,
+    ready_file_path: Option<PathBuf>,
+    config_source: Option<ConfigSource>,
+) -> Result<()> {
     let (ctrl_tx, mut ctrl_rx) = tokio::sync::mpsc::unbounded_channel::<Event>();
 
     let vrrb_node = Node::start(&node_config, ctrl_rx)
         .await
-        .map_err(|err| CliError::Other(String::from("failed to listen for ctrl+c")))?;
+        .map_err(CliError::NodeStart)?;
 
     let node_type = vrrb_node.node_type();
 
     info!("running {node_type:?} node in blocking mode");
 
+    if let Some(ready_file_path) = ready_file_path {
+        fs::write(
+            ready_file_path,
+            vrrb_node.jsonrpc_server_address().to_string(),
+        )?;
+    }
+
+    #[cfg(unix)]
+    let reload_handle = tokio::spawn(reload_config_on_sighup(
+        config_source,
+        vrrb_node.config_reload_handle(),
+    ));
+
     let node_handle = tokio::spawn(async move {
         // NOTE: starts the main node service
         vrrb_node.wait().await
     });
 
-    tokio::signal::ctrl_c()
-        .await
-        .map_err(|err| CliError::Other(format!("failed to listen for ctrl+c: {err}")))?;
+    wait_for_stop_signal().await?;
+
+    #[cfg(unix)]
+    reload_handle.abort();
+
+    #[cfg(not(unix))]
+    drop(config_source);
 
     ctrl_tx
-        .send(Event::Stop)
-        .map_err(|err| CliError::Other(format!("failed to send stop event to node: {err}")))?;
+        .send(Event::Shutdown {
+            reason: ShutdownReason::Operator,
+        })
+        .map_err(|err| CliError::NodeStop {
+            context: String::from("failed to send stop event to node"),
+            source: err.into(),
+        })?;
 
-    node_handle
-        .await
-        .map_err(|err| CliError::Other(format!("failed to join node task handle: {err}")))?;
+    let result = node_handle.await.map_err(|err| CliError::NodeStop {
+        context: String::from("failed to join node task handle"),
+        source: err.into(),
+    })?;
 
-    info!("node stopped");
+    match result {
+        Ok(reason) => info!("node stopped: {reason}"),
+        Err(err) => error!("node stopped with errors: {err}"),
+    }
 
     Ok(())
 }
 
-#[telemetry::instrument]
-async fn run_dettached(node_config: NodeConfig) -> Result<()> {
-    info!("running node in dettached mode");
-    // start child process, run node within it
+/// Reads the node's config from `config_source` again every time the process
+/// receives a SIGHUP and hands it over to the running node
+#[cfg(unix)]
+async fn reload_config_on_sighup(
+    config_source: Option<ConfigSource>,
+    config_reload_tx: tokio::sync::mpsc::UnboundedSender<NodeConfig>,
+) -> Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut sighup = signal(SignalKind::hangup()).map_err(|source| CliError::Signal {
+        signal: "SIGHUP",
+        source,
+    })?;
+
+    while sighup.recv().await.is_some() {
+        let config_source = match &config_source {
+            Some(config_source) => config_source,
+            None => {
+                warn!("received SIGHUP but the node wasn't started from a config file, ignoring");
+                continue;
+            },
+        };
+
+        match config_source.load() {
+            Ok(new_config) => {
+                info!("received SIGHUP, reloading node config");
+
+                if config_reload_tx.send(new_config).is_err() {
+                    break;
+                }
+            },
+            Err(err) => error!("failed to reload node config: {err}"),
+        }
+    }
+
     Ok(())
 }
+
+/// Resolves once the process is asked to stop, either through ctrl+c or, on
+/// unix systems, a SIGTERM
+async fn wait_for_stop_signal() -> Result<()> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut sigterm = signal(SignalKind::terminate()).map_err(|source| CliError::Signal {
+            signal: "SIGTERM",
+            source,
+        })?;
+
+        tokio::select! {
+            res = tokio::signal::ctrl_c() => {
+                res.map_err(|source| CliError::Signal { signal: "ctrl+c", source })
+            },
+            _ = sigterm.recv() => Ok(()),
+        }
+    }
+
+    #[cfg(not(unix))]
+    tokio::signal::ctrl_c()
+        .await
+        .map_err(|source| CliError::Signal {
+            signal: "ctrl+c",
+            source,
+        })
+}
+
+/// Runs a node as a background process. The resolved config is handed over to
+/// a child process running this same binary, whose output is redirected to a
+/// log file within the node's data dir. Returns once the child reports it is
+/// listening for connections.
+#[telemetry::instrument]
+async fn run_detached(node_config: NodeConfig, data_dir: &Path) -> Result<()> {
+    info!("running node in detached mode");
+
+    let pid_file_path = daemon::pid_file_path(data_dir);
+
+    if let Some(pid) = daemon::read_running_pid(&pid_file_path) {
+        return Err(CliError::DetachedNode(format!(
+            "a node is already running with PID {pid}"
+        )));
+    }
+
+    daemon::clean_up(data_dir);
+
+    let resolved_config_path = data_dir.join(format!("node-{}.json", node_config.id));
+    let serialized_config =
+        serde_json::to_vec_pretty(&node_config).map_err(|err| CliError::ConfigWrite {
+            context: String::from("failed to serialize node config"),
+            source: err.into(),
+        })?;
+
+    fs::write(&resolved_config_path, serialized_config)?;
+
+    let log_file_path = daemon::log_file_path(data_dir);
+    let log_file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_file_path)?;
+
+    // NOTE: the child resolves the same data dir out of `--data-dir`, so it
+    // reads the keypair and writes its ready file where this process expects
+    let mut cmd = Command::new(std::env::current_exe()?);
+    cmd.arg("node")
+        .arg("run")
+        .arg("--data-dir")
+        .arg(&node_config.data_dir)
+        .arg("--resolved-config")
+        .arg(&resolved_config_path)
+        .stdin(Stdio::null())
+        .stdout(log_file.try_clone()?)
+        .stderr(log_file);
+
+    // NOTE: detach the child from the terminal's process group so it isn't
+    // interrupted along with the parent
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+
+    let mut child = cmd.spawn()?;
+    let pid = child.id();
+
+    daemon::write_pid(&pid_file_path, pid)?;
+
+    let ready_file_path = daemon::ready_file_path(data_dir);
+    let started_at = std::time::Instant::now();
+
+    loop {
+        if let Some(status) = child.try_wait()? {
+            daemon::clean_up(data_dir);
+
+            return Err(CliError::DetachedNode(format!(
+                "node exited during startup with {status}, see {} for details",
+                log_file_path.display()
+            )));
+        }
+
+        if let Ok(address) = fs::read_to_string(&ready_file_path) {
+            info!("node running in the background with PID {pid}, JSON-RPC server listening on {address}");
+            println!("{pid}");
+
+            return Ok(());
+        }
+
+        if started_at.elapsed() > DETACHED_NODE_STARTUP_TIMEOUT {
+            return Err(CliError::DetachedNode(format!(
+                "node with PID {pid} did not report it was listening within {DETACHED_NODE_STARTUP_TIMEOUT:?}, see {} for details",
+                log_file_path.display()
+            )));
+        }
+
+        tokio::time::sleep(DETACHED_NODE_POLL_INTERVAL).await;
+    }
+}
+
+/// Entrypoint of the background process spawned by `run_detached`
+async fn run_detached_child(
+    node_config: NodeConfig,
+    data_dir: &Path,
+    config_source: ConfigSource,
+) -> Result<()> {
+    let runtime_config = node_config.runtime_config.clone();
+
+    let result = run_on_node_runtime(
+        &runtime_config,
+        run_node(
+            node_config,
+            Some(daemon::ready_file_path(data_dir)),
+            Some(config_source),
+        ),
+    )
+    .await;
+
+    daemon::clean_up(data_dir);
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+        Barrier,
+    };
+
+    use clap::{FromArgMatches, Parser};
+
+    use super::*;
+
+    #[test]
+    fn validate_rejects_duplicate_bind_addresses() {
+        let addr: SocketAddr = "127.0.0.1:9000".parse().unwrap();
+
+        let opts = RunOpts {
+            http_api_address: addr,
+            jsonrpc_api_address: addr,
+            ..Default::default()
+        };
+
+        match opts.validate() {
+            Err(CliError::ConflictingAddresses(first, second, conflicting_addr)) => {
+                assert_eq!(first, "http-api-address");
+                assert_eq!(second, "jsonrpc-api-address");
+                assert_eq!(conflicting_addr, addr);
+            },
+            other => panic!("expected an address conflict, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn validate_rejects_ports_shared_with_unspecified_addresses() {
+        let opts = RunOpts {
+            udp_gossip_address: "0.0.0.0:9000".parse().unwrap(),
+            raptorq_gossip_address: "127.0.0.1:9000".parse().unwrap(),
+            ..Default::default()
+        };
+
+        assert!(opts.validate().is_err());
+    }
+
+    /// Parses `args` the way the command line is, recording which options
+    /// were passed explicitly
+    fn parse_opts(args: &[&str]) -> RunOpts {
+        let matches = RunOpts::command().get_matches_from(args);
+        let mut opts = RunOpts::from_arg_matches(&matches).unwrap();
+        opts.track_explicit_flags(&matches);
+
+        opts
+    }
+
+    #[test]
+    fn merge_prefers_explicit_cli_addresses_over_the_files() {
+        let defaults = RunOpts::parse_from(["run"]);
+        let explicit = |port: u16| SocketAddr::from(([127, 0, 0, 1], port));
+
+        let cli = parse_opts(&[
+            "run",
+            "--udp-gossip-address",
+            "127.0.0.1:9001",
+            "--raptorq-gossip-address",
+            "127.0.0.1:9002",
+            "--http-api-address",
+            "127.0.0.1:9003",
+            "--jsonrpc-api-address",
+            "127.0.0.1:9004",
+            "--bootstrap-node-addresses",
+            "127.0.0.1:9005",
+        ]);
+
+        let file = RunOpts {
+            udp_gossip_address: explicit(8001),
+            raptorq_gossip_address: explicit(8002),
+            http_api_address: explicit(8003),
+            jsonrpc_api_address: explicit(8004),
+            bootstrap_node_addresses: Some(vec![explicit(8005)]),
+            ..defaults.clone()
+        };
+
+        let merged = cli.merge(&file);
+
+        assert_eq!(merged.udp_gossip_address, explicit(9001));
+        assert_eq!(merged.raptorq_gossip_address, explicit(9002));
+        assert_eq!(merged.http_api_address, explicit(9003));
+        assert_eq!(merged.jsonrpc_api_address, explicit(9004));
+        assert_eq!(merged.bootstrap_node_addresses, Some(vec![explicit(9005)]));
+    }
+
+    #[test]
+    fn merge_falls_back_to_the_files_addresses_when_cli_ones_are_defaults() {
+        let defaults = RunOpts::parse_from(["run"]);
+        let explicit = |port: u16| SocketAddr::from(([127, 0, 0, 1], port));
+
+        let file = RunOpts {
+            udp_gossip_address: explicit(8001),
+            raptorq_gossip_address: explicit(8002),
+            http_api_address: explicit(8003),
+            jsonrpc_api_address: explicit(8004),
+            bootstrap_node_addresses: Some(vec![explicit(8005)]),
+            ..defaults.clone()
+        };
+
+        let merged = defaults.merge(&file);
+
+        assert_eq!(merged.udp_gossip_address, explicit(8001));
+        assert_eq!(merged.raptorq_gossip_address, explicit(8002));
+        assert_eq!(merged.http_api_address, explicit(8003));
+        assert_eq!(merged.jsonrpc_api_address, explicit(8004));
+        assert_eq!(merged.bootstrap_node_addresses, Some(vec![explicit(8005)]));
+
+        // NOTE: defaults on both sides stay defaults
+        let merged = defaults.merge(&defaults);
+
+        assert_eq!(merged.udp_gossip_address, defaults.udp_gossip_address);
+        assert_eq!(merged.jsonrpc_api_address, defaults.jsonrpc_api_address);
+    }
+
+    #[test]
+    fn merge_lets_flags_passed_with_their_default_value_override_the_file() {
+        let file = RunOpts {
+            jsonrpc_api_address: "127.0.0.1:7000".parse().unwrap(),
+            node_type: String::from("validator"),
+            ..RunOpts::parse_from(["run"])
+        };
+
+        let cli = parse_opts(&[
+            "run",
+            "--jsonrpc-api-address",
+            DEFAULT_JSONRPC_ADDRESS,
+            "--node-type",
+            "full",
+        ]);
+
+        let merged = cli.merge(&file);
+
+        assert_eq!(
+            merged.jsonrpc_api_address,
+            DEFAULT_JSONRPC_ADDRESS.parse().unwrap()
+        );
+        assert_eq!(merged.node_type, "full");
+
+        // NOTE: the same values parsed without being tracked as explicit
+        let merged = RunOpts::parse_from(["run"]).merge(&file);
+
+        assert_eq!(merged.jsonrpc_api_address, file.jsonrpc_api_address);
+        assert_eq!(merged.node_type, "validator");
+    }
+
+    #[test]
+    fn merge_disables_networking_if_either_source_does() {
+        let enabled = parse_opts(&["run"]);
+        let disabled = parse_opts(&["run", "--disable-networking"]);
+
+        assert!(!enabled.merge(&enabled).disable_networking);
+        assert!(disabled.merge(&enabled).disable_networking);
+        assert!(enabled.merge(&disabled).disable_networking);
+    }
+
+    #[test]
+    fn detached_flag_accepts_its_deprecated_spelling() {
+        for spelling in ["--detached", "--dettached", "-d"] {
+            let opts = RunOpts::parse_from(["run", spelling]);
+
+            assert!(opts.detached, "{spelling} did not detach the node");
+            assert!(!opts.foreground);
+        }
+
+        let opts = RunOpts::parse_from(["run", "--foreground"]);
+        assert!(opts.foreground);
+        assert!(!opts.detached);
+
+        assert!(RunOpts::try_parse_from(["run", "--detached", "--foreground"]).is_err());
+        assert!(RunOpts::try_parse_from(["run", "--dettached", "--foreground"]).is_err());
+
+        let opts = RunOpts {
+            detached: true,
+            foreground: true,
+            ..Default::default()
+        };
+
+        assert!(matches!(opts.validate(), Err(CliError::OptsError(_))));
+    }
+
+    #[test]
+    fn config_files_may_use_either_detached_spelling() {
+        let dir = std::env::temp_dir().join(vrrb_core::helpers::generate_random_string());
+        std::fs::create_dir_all(&dir).unwrap();
+
+        for key in ["detached", "dettached"] {
+            let path = dir.join(format!("{key}.toml"));
+            std::fs::write(&path, format!("{key} = true\n")).unwrap();
+
+            assert!(resolve_detached(&RunOpts::default(), Some(&path)));
+
+            // NOTE: flags passed on the command line win over the file
+            let foreground = RunOpts {
+                foreground: true,
+                ..Default::default()
+            };
+
+            assert!(!resolve_detached(&foreground, Some(&path)));
+        }
+
+        assert!(!resolve_detached(&RunOpts::default(), None));
+
+        std::fs::remove_dir_all(&dir).unwrap_or_default();
+    }
+
+    #[test]
+    fn merge_lets_explicit_foreground_win_over_the_files_detached() {
+        let foreground = parse_opts(&["run", "--foreground"]);
+        let detached = parse_opts(&["run", "--detached"]);
+        let defaults = parse_opts(&["run"]);
+
+        let merged = foreground.merge(&detached);
+        assert!(merged.foreground);
+        assert!(!merged.detached);
+
+        let merged = defaults.merge(&detached);
+        assert!(merged.detached);
+        assert!(!merged.foreground);
+    }
+
+    #[test]
+    fn stores_live_within_a_custom_data_dir() {
+        let data_dir = std::env::temp_dir().join(vrrb_core::helpers::generate_random_string());
+
+        let args = RunOpts {
+            data_dir: data_dir.clone(),
+            enable_event_store: true,
+            ..Default::default()
+        };
+
+        let mut node_config = NodeConfig::from(args);
+
+        let node_data_dir = resolve_node_data_dir(&node_config.data_dir).unwrap();
+        assert_eq!(node_data_dir, data_dir.join("node"));
+
+        place_stores_in_data_dir(&mut node_config, &node_data_dir, true);
+
+        assert_eq!(node_config.db_path, node_data_dir.join("db"));
+        assert_eq!(
+            node_config.mempool_snapshot_path,
+            Some(node_data_dir.join("mempool.snapshot"))
+        );
+        assert_eq!(
+            node_config.event_store_path,
+            Some(node_data_dir.join("events"))
+        );
+
+        // NOTE: paths set explicitly, e.g. within a config file, are kept
+        node_config.mempool_snapshot_path = Some(data_dir.join("elsewhere"));
+        place_stores_in_data_dir(&mut node_config, &node_data_dir, true);
+
+        assert_eq!(
+            node_config.mempool_snapshot_path,
+            Some(data_dir.join("elsewhere"))
+        );
+
+        std::fs::remove_dir_all(&data_dir).unwrap_or_default();
+    }
+
+    #[test]
+    fn node_runs_with_the_keypair_stored_on_disk() {
+        let data_dir = std::env::temp_dir().join(vrrb_core::helpers::generate_random_string());
+        std::fs::create_dir_all(&data_dir).unwrap();
+
+        let node_keypair = read_or_generate_keypair(&data_dir, false).unwrap();
+
+        let stored_keypair = read_keypair_file(&data_dir.join("keypair")).unwrap();
+
+        assert_eq!(node_keypair.txn_keypair(), stored_keypair.txn_keypair());
+        assert_eq!(
+            node_keypair.consensus_keypair().public_key(),
+            stored_keypair.consensus_keypair().public_key()
+        );
+
+        // NOTE: subsequent runs must reuse the stored keypair rather than generating
+        // a new one
+        assert_eq!(
+            read_or_generate_keypair(&data_dir, false)
+                .unwrap()
+                .txn_keypair(),
+            stored_keypair.txn_keypair()
+        );
+
+        std::fs::remove_dir_all(&data_dir).unwrap_or_default();
+    }
+
+    #[test]
+    fn corrupt_keypair_files_are_not_overwritten() {
+        let data_dir = std::env::temp_dir().join(vrrb_core::helpers::generate_random_string());
+        std::fs::create_dir_all(&data_dir).unwrap();
+
+        let keypair_file_path = data_dir.join("keypair");
+        std::fs::write(&keypair_file_path, "not a keypair").unwrap();
+
+        let err = read_or_generate_keypair(&data_dir, false).unwrap_err();
+
+        assert!(matches!(err, CliError::UnreadableKeypair(..)));
+        assert_eq!(
+            std::fs::read_to_string(&keypair_file_path).unwrap(),
+            "not a keypair"
+        );
+
+        std::fs::remove_dir_all(&data_dir).unwrap_or_default();
+    }
+
+    #[test]
+    fn config_file_sources_reapply_cli_overrides_on_reload() {
+        let dir = std::env::temp_dir().join(vrrb_core::helpers::generate_random_string());
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join("node.toml");
+
+        let args = parse_opts(&["run", "--http-api-title", "from cli", "--id", "node-1"]);
+        let config_source = ConfigSource::File {
+            path: path.clone(),
+            args,
+        };
+
+        // NOTE: the config file changed since the node was started
+        std::fs::write(
+            &path,
+            "mempool_max_size = 20\nhttp_api_title = \"from file\"\n",
+        )
+        .unwrap();
+
+        let node_config = config_source.load().unwrap();
+
+        assert_eq!(node_config.mempool_max_size, Some(20));
+        assert_eq!(node_config.http_api_title, "from cli");
+        assert_eq!(node_config.id, "node-1");
+
+        std::fs::remove_dir_all(&dir).unwrap_or_default();
+    }
+
+    #[test]
+    fn consecutive_runs_reuse_the_persisted_idx() {
+        let data_dir = std::env::temp_dir().join(vrrb_core::helpers::generate_random_string());
+        std::fs::create_dir_all(&data_dir).unwrap();
+
+        let first_run = RunOpts::parse_from(["run"]);
+        let first_idx = resolve_node_idx(
+            NodeConfig::from(first_run.clone()).idx,
+            first_run.idx.is_some(),
+            first_run.force_idx,
+            &data_dir,
+        )
+        .unwrap();
+
+        // NOTE: the idx a node runs with when none is configured must survive
+        // restarts, even if the default changes in between
+        let second_idx = resolve_node_idx(first_idx + 1, false, false, &data_dir).unwrap();
+
+        assert_eq!(first_idx, second_idx);
+        assert_eq!(
+            std::fs::read_to_string(data_dir.join("idx")).unwrap(),
+            first_idx.to_string()
+        );
+
+        std::fs::remove_dir_all(&data_dir).unwrap_or_default();
+    }
+
+    #[test]
+    fn conflicting_idx_requires_force() {
+        let data_dir = std::env::temp_dir().join(vrrb_core::helpers::generate_random_string());
+        std::fs::create_dir_all(&data_dir).unwrap();
+
+        resolve_node_idx(3, true, false, &data_dir).unwrap();
+
+        assert!(matches!(
+            resolve_node_idx(5, true, false, &data_dir),
+            Err(CliError::ConflictingIdx {
+                configured: 5,
+                persisted: 3
+            })
+        ));
+
+        assert_eq!(resolve_node_idx(5, true, true, &data_dir).unwrap(), 5);
+        assert_eq!(resolve_node_idx(0, false, false, &data_dir).unwrap(), 5);
+
+        std::fs::remove_dir_all(&data_dir).unwrap_or_default();
+    }
+
+    #[test]
+    fn derived_node_ids_only_replace_generated_ones() {
+        let keypair = Keypair::random();
+        let derived_id = NodeId::from_public_key(keypair.get_miner_public_key());
+
+        let mut node_config = NodeConfig {
+            keypair: Some(keypair.clone()),
+            ..Default::default()
+        };
+
+        derive_node_id(&mut node_config, false);
+        assert_eq!(node_config.id, derived_id);
+
+        // NOTE: restarting with the same keypair yields the same id
+        let mut restarted_node_config = NodeConfig {
+            keypair: Some(keypair),
+            ..Default::default()
+        };
+
+        derive_node_id(&mut restarted_node_config, false);
+        assert_eq!(restarted_node_config.id, derived_id);
+
+        restarted_node_config.id = String::from("persisted-id");
+        derive_node_id(&mut restarted_node_config, true);
+        assert_eq!(restarted_node_config.id, "persisted-id");
+    }
+
+    #[test]
+    fn config_files_with_an_id_are_detected() {
+        let dir = std::env::temp_dir().join(vrrb_core::helpers::generate_random_string());
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let with_id = dir.join("with_id.toml");
+        std::fs::write(&with_id, "id = \"persisted-id\"\n").unwrap();
+
+        let without_id = dir.join("without_id.toml");
+        std::fs::write(&without_id, "idx = 1\n").unwrap();
+
+        assert!(config_file_sets(&with_id, "id"));
+        assert!(!config_file_sets(&without_id, "id"));
+
+        std::fs::remove_dir_all(&dir).unwrap_or_default();
+    }
+
+    #[test]
+    fn cli_options_override_config_file_values() {
+        let mut file_config = NodeConfig::default();
+        file_config.http_api_title = String::from("from file");
+        file_config.jsonrpc_server_address = "127.0.0.1:7000".parse().unwrap();
+
+        let args = parse_opts(&["run", "--jsonrpc-api-address", "127.0.0.1:8000"]);
+
+        let node_config = merge_with_file_config(&args, file_config);
+
+        assert_eq!(
+            node_config.jsonrpc_server_address,
+            "127.0.0.1:8000".parse().unwrap()
+        );
+        assert_eq!(node_config.http_api_title, "from file");
+    }
+
+    #[test]
+    fn validate_allows_os_assigned_ports() {
+        let opts = RunOpts::default();
+
+        assert!(opts.validate().is_ok());
+    }
+
+    #[test]
+    fn node_runtimes_are_built_with_the_configured_worker_threads() {
+        let started_threads = Arc::new(AtomicUsize::new(0));
+        let counter = started_threads.clone();
+
+        let runtime = node_runtime_builder(&RuntimeConfig {
+            worker_threads: Some(3),
+            ..Default::default()
+        })
+        .on_thread_start(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .build()
+        .unwrap();
+
+        // NOTE: every task holds on to the worker it runs on until all of
+        // them run at once, which takes at least three workers
+        let barrier = Arc::new(Barrier::new(3));
+
+        runtime.block_on(async {
+            let tasks = (0..3)
+                .map(|_| {
+                    let barrier = barrier.clone();
+                    tokio::spawn(async move {
+                        barrier.wait();
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            for task in tasks {
+                task.await.unwrap();
+            }
+        });
+
+        // NOTE: nothing was spawned onto blocking threads, so every thread
+        // started is a worker
+        assert_eq!(started_threads.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn node_runtimes_default_to_one_worker_per_core() {
+        let cores = std::thread::available_parallelism()
+            .map(usize::from)
+            .unwrap_or(1);
+
+        assert_eq!(RuntimeConfig::default().worker_threads(), cores);
+        assert_eq!(
+            RuntimeConfig {
+                worker_threads: Some(0),
+                ..Default::default()
+            }
+            .worker_threads(),
+            1
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use std::{
+    path::PathBuf,
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+};
+
+use clap::Parser;
+use primitives::DEFAULT_VRRB_DATA_DIR_PATH;
+use telemetry::info;
+
+use super::{daemon, run::resolve_node_data_dir};
+use crate::result::{CliError, Result};
+
+/// How long to wait for a detached node to exit after being asked to stop
+const STOP_TIMEOUT: Duration = Duration::from_secs(60);
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Parser, Debug)]
+pub struct StopOpts {
+    /// Data dir the node was run with
+    #[clap(long, value_parser, default_value = DEFAULT_VRRB_DATA_DIR_PATH)]
+    pub data_dir: PathBuf,
+}
+
+/// Stops a node running in detached mode.
+///
+/// The node is sent a SIGTERM, which it handles the same way as ctrl+c by
+/// issuing an operator `Event::Shutdown` to all of its runtime modules.
+pub async fn stop(opts: StopOpts) -> Result<()> {
+    let data_dir = resolve_node_data_dir(&opts.data_dir)?;
+    let pid_file_path = daemon::pid_file_path(&data_dir);
+
+    let pid = match daemon::read_pid(&pid_file_path) {
+        Some(pid) if daemon::is_process_alive(pid) => pid,
+        Some(pid) => {
+            daemon::clean_up(&data_dir);
+            return Err(CliError::DetachedNode(format!(
+                "no node is running, removed stale PID file for process {pid}"
+            )));
+        },
+        None => {
+            return Err(CliError::DetachedNode(String::from(
+                "no node is running in detached mode",
+            )))
+        },
+    };
+
+    info!("stopping node with PID {pid}");
+
+    send_terminate_signal(pid)?;
+
+    let started_at = Instant::now();
+
+    while daemon::is_process_alive(pid) {
+        if started_at.elapsed() > STOP_TIMEOUT {
+            return Err(CliError::DetachedNode(format!(
+                "node with PID {pid} did not stop within {STOP_TIMEOUT:?}"
+            )));
+        }
+
+        tokio::time::sleep(STOP_POLL_INTERVAL).await;
+    }
+
+    daemon::clean_up(&data_dir);
+
+    println!("node with PID {pid} stopped");
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn send_terminate_signal(pid: u32) -> Result<()> {
+    let status = Command::new("kill")
+        .arg("-TERM")
+        .arg(pid.to_string())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()?;
+
+    if !status.success() {
+        return Err(CliError::DetachedNode(format!(
+            "failed to send stop signal to node with PID {pid}"
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn send_terminate_signal(pid: u32) -> Result<()> {
+    Err(CliError::DetachedNode(format!(
+        "stopping detached nodes is not supported on this platform, PID {pid}"
+    )))
+}
@@ -0,0 +1,140 @@
+use std::{
+    fs,
+    net::{SocketAddr, TcpListener, UdpSocket},
+    path::Path,
+};
+
+use vrrb_config::NodeConfig;
+use vrrb_core::keypair::read_keypair_file;
+
+use crate::result::{CliError, Result};
+
+type CheckResult = std::result::Result<String, String>;
+
+/// Runs every check a node would otherwise only fail at startup, printing a
+/// summary of their outcomes. Nothing is started and no keypair is generated.
+pub(super) fn exec(node_config: &NodeConfig, data_dir: &Path) -> Result<()> {
+    let mut checks = vec![
+        ("keypair", check_keypair(&data_dir.join("keypair"))),
+        ("data dir", check_writable_dir(data_dir)),
+        ("node data dir", check_writable_dir(&node_config.data_dir)),
+        ("db path", check_writable_dir(&node_config.db_path)),
+        (
+            "http api address",
+            check_tcp_bind(node_config.http_api_address),
+        ),
+        (
+            "jsonrpc server address",
+            check_tcp_bind(node_config.jsonrpc_server_address),
+        ),
+    ];
+
+    if node_config.disable_networking {
+        checks.push((
+            "gossip addresses",
+            Ok(String::from("skipped, networking is disabled")),
+        ));
+    } else {
+        checks.push((
+            "udp gossip address",
+            check_udp_bind(node_config.udp_gossip_address),
+        ));
+        checks.push((
+            "raptorq gossip address",
+            check_udp_bind(node_config.raptorq_gossip_address),
+        ));
+    }
+
+    println!("node {} ({:?})", node_config.id, node_config.node_type);
+
+    let mut failed = 0;
+
+    for (label, outcome) in checks {
+        match outcome {
+            Ok(detail) => println!("  {label:<24} ok: {detail}"),
+            Err(reason) => {
+                failed += 1;
+                println!("  {label:<24} FAILED: {reason}");
+            },
+        }
+    }
+
+    if failed > 0 {
+        return Err(CliError::ValidationFailed(failed));
+    }
+
+    println!("configuration is valid");
+
+    Ok(())
+}
+
+fn check_keypair(path: &Path) -> CheckResult {
+    match fs::metadata(path) {
+        Ok(_) => read_keypair_file(path)
+            .map(|_| format!("loaded from {}", path.display()))
+            .map_err(|err| format!("unable to read {}: {err}", path.display())),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(format!(
+            "none found, a new one will be generated at {}",
+            path.display()
+        )),
+        Err(err) => Err(format!("unable to access {}: {err}", path.display())),
+    }
+}
+
+fn check_writable_dir(path: &Path) -> CheckResult {
+    fs::create_dir_all(path)
+        .map_err(|err| format!("unable to create {}: {err}", path.display()))?;
+
+    let probe = path.join(".vrrb_write_probe");
+
+    fs::write(&probe, b"")
+        .and_then(|_| fs::remove_file(&probe))
+        .map_err(|err| format!("{} is not writable: {err}", path.display()))?;
+
+    Ok(format!("{} is writable", path.display()))
+}
+
+fn check_tcp_bind(addr: SocketAddr) -> CheckResult {
+    TcpListener::bind(addr)
+        .map(|_| format!("{addr} is available"))
+        .map_err(|err| format!("unable to bind {addr}: {err}"))
+}
+
+fn check_udp_bind(addr: SocketAddr) -> CheckResult {
+    UdpSocket::bind(addr)
+        .map(|_| format!("{addr} is available"))
+        .map_err(|err| format!("unable to bind {addr}: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn addresses_in_use_fail_validation() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        assert!(check_tcp_bind(addr).is_err());
+
+        drop(listener);
+
+        assert!(check_tcp_bind(addr).is_ok());
+    }
+
+    #[test]
+    fn corrupt_keypairs_fail_validation() {
+        let data_dir = std::env::temp_dir().join(vrrb_core::helpers::generate_random_string());
+        fs::create_dir_all(&data_dir).unwrap();
+
+        let keypair_path = data_dir.join("keypair");
+
+        assert!(check_keypair(&keypair_path).is_ok());
+
+        fs::write(&keypair_path, "not a keypair").unwrap();
+
+        assert!(check_keypair(&keypair_path).is_err());
+
+        fs::remove_dir_all(&data_dir).unwrap_or_default();
+    }
+}
@@ -1,14 +1,48 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use crate::{commands::node::RunOpts, result::CliError};
+use config::{Config, File};
+use vrrb_config::NodeConfig;
 
-pub fn read_node_config_from_file(config_file_path: PathBuf) -> crate::result::Result<RunOpts> {
-    let path_str = config_file_path.to_str().unwrap_or_default();
+use crate::result::CliError;
 
-    let node_config = RunOpts::from_file(path_str)
-        .map_err(|err| CliError::Other(format!("failed to read config file: {err}")))?;
+pub fn read_node_config_from_file(config_file_path: PathBuf) -> crate::result::Result<NodeConfig> {
+    NodeConfig::from_file(&config_file_path).map_err(|err| CliError::ConfigRead {
+        context: format!("failed to read config file {}", config_file_path.display()),
+        source: err.into(),
+    })
+}
+
+/// Returns true if the config file at `config_file_path` sets `key`, rather
+/// than leaving it to its default value
+pub fn config_file_sets(config_file_path: &Path, key: &str) -> bool {
+    Config::builder()
+        .add_source(File::from(config_file_path))
+        .build()
+        .map(|config| config.get_string(key).is_ok())
+        .unwrap_or_default()
+}
+
+/// Reads the boolean `key` from the config file at `config_file_path`,
+/// falling back to its `deprecated_key` spelling, with a warning, if only
+/// that one is set
+pub fn config_file_bool(config_file_path: &Path, key: &str, deprecated_key: &str) -> Option<bool> {
+    let config = Config::builder()
+        .add_source(File::from(config_file_path))
+        .build()
+        .ok()?;
+
+    if let Ok(value) = config.get_bool(key) {
+        return Some(value);
+    }
+
+    let value = config.get_bool(deprecated_key).ok()?;
+
+    telemetry::warn!(
+        "{} sets the deprecated `{deprecated_key}` key, rename it to `{key}`",
+        config_file_path.display()
+    );
 
-    Ok(node_config)
+    Some(value)
 }
 
 // TODO: fix state I/O && test writing txns to state
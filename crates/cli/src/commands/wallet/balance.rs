@@ -0,0 +1,66 @@
+use primitives::Address;
+use telemetry::warn;
+use vrrb_core::account::Account;
+use wallet::v2::Wallet;
+
+use crate::result::Result;
+
+/// Prints the balance of the account stored under `address`.
+///
+/// The node is queried first. When it can't be reached the cached
+/// `account.json` is used instead and the output is marked as stale.
+pub async fn exec(
+    wallet: Option<&mut Wallet>,
+    address: Address,
+    cached_account: Option<&Account>,
+) -> Result<()> {
+    let account = match wallet {
+        Some(wallet) => match wallet.get_account(address.clone()).await {
+            Ok(account) => Some(account),
+            Err(err) => {
+                warn!("unable to fetch account {address} from node: {err}");
+                None
+            },
+        },
+        None => None,
+    };
+
+    match account {
+        Some(Some(account)) => println!("{}: {}", address, account_balance(&account)),
+        Some(None) => println!("no account found for address {}", address),
+        None => match cached_account {
+            Some(account) => println!(
+                "{}: {} (stale: node unreachable, read from cached account.json)",
+                address,
+                account_balance(account)
+            ),
+            None => println!(
+                "node unreachable and no cached account found for address {}",
+                address
+            ),
+        },
+    }
+
+    Ok(())
+}
+
+/// Returns the spendable balance of an account, credits minus debits.
+pub fn account_balance(account: &Account) -> u128 {
+    account.credits.saturating_sub(account.debits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn balance_is_credits_minus_debits() {
+        let account = Account {
+            credits: 150,
+            debits: 50,
+            ..Default::default()
+        };
+
+        assert_eq!(account_balance(&account), 100);
+    }
+}
@@ -4,10 +4,13 @@ pub(crate) async fn exec(
     wallet: &mut wallet::v2::Wallet,
     limit: Option<usize>,
 ) -> crate::result::Result<()> {
-    let result = wallet.get_mempool().await?;
+    let result = wallet.get_mempool(limit).await?;
 
     let ser_result =
-        serde_json::to_string_pretty(&result).map_err(|e| CliError::Other(e.to_string()))?;
+        serde_json::to_string_pretty(&result).map_err(|err| CliError::Serialization {
+            context: String::from("unable to serialize mempool"),
+            source: err,
+        })?;
 
     println!("{}", ser_result);
 
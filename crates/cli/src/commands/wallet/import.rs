@@ -0,0 +1,42 @@
+use std::path::{Path, PathBuf};
+
+use vrrb_core::helpers::read_keypair_file;
+use wallet::v2::{AddressAlias, Wallet};
+
+use super::new::write_account_files;
+use crate::result::CliError;
+
+/// Imports an existing keypair file as a new account under `alias`.
+///
+/// Refuses to overwrite an existing alias directory unless `force` is set.
+pub async fn exec(
+    wallet: &mut Wallet,
+    path: &PathBuf,
+    alias: AddressAlias,
+    keys_path: &Path,
+    force: bool,
+    passphrase: Option<&str>,
+) -> Result<(), CliError> {
+    let keypair = read_keypair_file(keys_path).map_err(|err| CliError::KeypairIo {
+        context: format!("unable to read keypair file {}", keys_path.display()),
+        source: err.into(),
+    })?;
+
+    let account_data_dir = path.join(format!("{}", alias));
+
+    if account_data_dir.exists() && !force {
+        return Err(CliError::AccountExists(format!(
+            "an account already exists under alias {alias}, pass --force to overwrite it"
+        )));
+    }
+
+    std::fs::create_dir_all(&account_data_dir)?;
+
+    let (address, account) = wallet.create_account(alias, keypair.1).await?;
+
+    write_account_files(&account_data_dir, &keypair, &account, passphrase)?;
+
+    println!("imported account {} as alias {}", address, alias);
+
+    Ok(())
+}
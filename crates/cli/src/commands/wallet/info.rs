@@ -8,8 +8,11 @@ use crate::result::{CliError, Result};
 
 pub async fn exec(wallet: &Wallet) -> Result<()> {
     let wallet_info = wallet.info();
-    let wallet_info = serde_json::to_string_pretty(&wallet_info)
-        .map_err(|err| CliError::Other(format!("unable to serialize wallet information: {err}")))?;
+    let wallet_info =
+        serde_json::to_string_pretty(&wallet_info).map_err(|err| CliError::Serialization {
+            context: String::from("unable to serialize wallet information"),
+            source: err,
+        })?;
 
     println!("{}", wallet_info);
 
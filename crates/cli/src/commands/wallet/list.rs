@@ -0,0 +1,19 @@
+use std::path::Path;
+
+use wallet::v2::{WalletIndex, WALLET_INDEX_FILE_NAME};
+
+use crate::result::{CliError, Result};
+
+pub fn exec(data_dir: &Path) -> Result<()> {
+    let index = WalletIndex::load(&data_dir.join(WALLET_INDEX_FILE_NAME))?;
+
+    let accounts =
+        serde_json::to_string_pretty(&index.accounts).map_err(|err| CliError::Serialization {
+            context: String::from("unable to serialize wallet index"),
+            source: err,
+        })?;
+
+    println!("{}", accounts);
+
+    Ok(())
+}
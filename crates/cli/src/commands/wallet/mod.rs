@@ -1,16 +1,26 @@
+mod balance;
 mod get;
 mod get_mempool;
+mod import;
 mod info;
+mod list;
 mod new;
+mod rotate_keys;
 mod transfer;
 
-use std::{collections::HashMap, hash::Hash, net::SocketAddr, path::PathBuf, str::FromStr};
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 use clap::{Parser, Subcommand};
-use primitives::Address;
+use primitives::{Address, PublicKey};
 use secp256k1::{generate_keypair, rand};
 use serde_json;
-use vrrb_core::{account::Account, helpers::read_or_generate_keypair_file, txn::Token};
+use vrrb_core::{account::Account, helpers::read_or_generate_keypair_file, keyfile, txn::Token};
 use wallet::v2::{AddressAlias, Wallet, WalletConfig};
 
 use crate::result::{CliError, Result};
@@ -53,14 +63,62 @@ pub enum WalletCmd {
     New {
         #[clap(long)]
         alias: AddressAlias,
+
+        /// Replace the account already stored under this alias
+        #[clap(long)]
+        overwrite: bool,
+
+        /// Encrypt the account's keypair file with a passphrase
+        #[clap(long)]
+        encrypt: bool,
+    },
+
+    /// Import an existing keypair file as a new account
+    Import {
+        #[clap(long)]
+        alias: AddressAlias,
+
+        /// Path to the keypair file to import
+        #[clap(long)]
+        keys: PathBuf,
+
+        /// Overwrite the account already stored under this alias
+        #[clap(long)]
+        force: bool,
+
+        /// Encrypt the imported keypair file with a passphrase
+        #[clap(long)]
+        encrypt: bool,
+    },
+
+    /// Replace the keypair of an account, archiving the previous one
+    RotateKeys {
+        alias: AddressAlias,
+
+        /// Encrypt the new keypair file with a passphrase
+        #[clap(long)]
+        encrypt: bool,
     },
 
+    /// List the accounts registered within this wallet's index
+    List,
+
     /// Gets information about an account
     Get {
         #[clap(long)]
         address: String,
     },
 
+    /// Gets the balance of an account, falling back to the cached account data
+    /// if the node can't be reached
+    Balance {
+        #[clap(long, conflicts_with = "address", required_unless_present = "address")]
+        alias: Option<AddressAlias>,
+
+        #[clap(long)]
+        address: Option<String>,
+    },
+
     /// Retrieves a snapshot of the values within mempool
     GetMempool {
         #[clap(long)]
@@ -94,13 +152,32 @@ pub async fn exec(args: WalletOpts) -> Result<()> {
         rpc_server_address,
         secret_key,
         public_key,
-        accounts,
-        addresses,
+        accounts: accounts.clone(),
+        addresses: addresses.clone(),
+        data_dir: Some(data_dir.clone()),
     };
 
-    let mut wallet = Wallet::new(wallet_config)
+    let wallet = Wallet::new(wallet_config)
         .await
-        .map_err(|err| CliError::Other(format!("unable to create wallet: {err}")))?;
+        .map_err(|err| CliError::RpcConnect {
+            address: rpc_server_address,
+            source: err.into(),
+        });
+
+    // NOTE: balances can still be read from the cached account data when the node
+    // is unreachable, so the wallet is allowed to fail here
+    if let WalletCmd::Balance { alias, address } = &sub_cmd {
+        let address = resolve_address(*alias, address.as_deref(), &addresses)?;
+        let mut wallet = wallet.ok();
+
+        return balance::exec(wallet.as_mut(), address.clone(), accounts.get(&address)).await;
+    }
+
+    if let WalletCmd::List = &sub_cmd {
+        return list::exec(&data_dir);
+    }
+
+    let mut wallet = wallet?;
 
     match sub_cmd {
         WalletCmd::Info => info::exec(&wallet).await,
@@ -123,19 +200,74 @@ pub async fn exec(args: WalletOpts) -> Result<()> {
 
             Ok(())
         },
-        WalletCmd::New { alias } => {
-            new::exec(&mut wallet, &accounts_data_dir, alias).await?;
+        WalletCmd::New {
+            alias,
+            overwrite,
+            encrypt,
+        } => {
+            let passphrase = read_new_passphrase(encrypt)?;
+
+            new::exec(
+                &mut wallet,
+                &accounts_data_dir,
+                alias,
+                overwrite,
+                passphrase.as_deref(),
+            )
+            .await?;
+
+            Ok(())
+        },
+        WalletCmd::Import {
+            alias,
+            keys,
+            force,
+            encrypt,
+        } => {
+            let passphrase = read_new_passphrase(encrypt)?;
+
+            import::exec(
+                &mut wallet,
+                &accounts_data_dir,
+                alias,
+                &keys,
+                force,
+                passphrase.as_deref(),
+            )
+            .await?;
+
+            Ok(())
+        },
+        WalletCmd::RotateKeys { alias, encrypt } => {
+            let passphrase = read_new_passphrase(encrypt)?;
+
+            rotate_keys::exec(
+                &mut wallet,
+                &accounts_data_dir,
+                alias,
+                &addresses,
+                passphrase.as_deref(),
+            )
+            .await?;
 
             Ok(())
         },
         WalletCmd::Get { address } => {
             let address = Address::from_str(&address)?;
 
-            if let Ok(account) = get::exec(&mut wallet, address).await {
-                let account_info = serde_json::to_string_pretty(&account)
-                    .map_err(|err| CliError::Other(err.to_string()))?;
-
-                println!("{}", account_info);
+            match get::exec(&mut wallet, address.clone()).await {
+                Ok(Some(account)) => {
+                    let account_info = serde_json::to_string_pretty(&account).map_err(|err| {
+                        CliError::Serialization {
+                            context: String::from("unable to serialize account"),
+                            source: err,
+                        }
+                    })?;
+
+                    println!("{}", account_info);
+                },
+                Ok(None) => println!("no account found for address {}", address),
+                Err(_) => {},
             };
 
             Ok(())
@@ -150,39 +282,101 @@ pub async fn exec(args: WalletOpts) -> Result<()> {
     }
 }
 
+fn resolve_address(
+    alias: Option<AddressAlias>,
+    address: Option<&str>,
+    addresses: &HashMap<AddressAlias, Address>,
+) -> Result<Address> {
+    match (alias, address) {
+        (Some(alias), _) => addresses
+            .get(&alias)
+            .cloned()
+            .ok_or(CliError::UnknownAlias(alias)),
+        (None, Some(address)) => Ok(Address::from_str(address)?),
+        (None, None) => Err(CliError::OptsError(
+            "either --alias or --address must be provided".to_string(),
+        )),
+    }
+}
+
+/// Reads the passphrase to encrypt new keypair files with, if `encrypt` is set
+fn read_new_passphrase(encrypt: bool) -> Result<Option<String>> {
+    if !encrypt {
+        return Ok(None);
+    }
+
+    keyfile::read_new_passphrase()
+        .map(Some)
+        .map_err(|err| CliError::KeypairIo {
+            context: String::from("unable to read passphrase"),
+            source: err.into(),
+        })
+}
+
 fn restore_accounts_and_addresses(
     path: &PathBuf,
 ) -> Result<(HashMap<Address, Account>, HashMap<AddressAlias, Address>)> {
     let mut accounts = HashMap::new();
     let mut addresses = HashMap::new();
 
-    let entries = std::fs::read_dir(path).map_err(|err| CliError::Other(err.to_string()))?;
+    let entries = std::fs::read_dir(path).map_err(|err| {
+        account_io(
+            format!("unable to list accounts in {}", path.display()),
+            err,
+        )
+    })?;
 
     for entry in entries {
-        let entry = entry.map_err(|err| CliError::Other(err.to_string()))?;
+        let entry = entry.map_err(|err| account_io(String::from("unable to read account"), err))?;
         let path = entry.path();
 
         let file_name = path
             .file_name()
-            .ok_or(CliError::Other("unable to get file name".to_string()))
-            .map_err(|err| CliError::Other(err.to_string()))?
-            .to_str()
-            .ok_or(CliError::Other("unable to get file name".to_string()))
-            .map_err(|err| CliError::Other(err.to_string()))?;
+            .and_then(|file_name| file_name.to_str())
+            .ok_or_else(|| {
+                CliError::Other(format!("unable to get file name of {}", path.display()))
+            })?;
 
-        let alias =
-            AddressAlias::from_str(file_name).map_err(|err| CliError::Other(err.to_string()))?;
+        let alias = AddressAlias::from_str(file_name)
+            .map_err(|err| account_io(format!("invalid account alias {file_name}"), err))?;
 
-        let account_string = std::fs::read_to_string(&path.join("account.json"))
-            .map_err(|err| CliError::Other(err.to_string()))?;
+        let account_string = std::fs::read_to_string(path.join("account.json"))
+            .map_err(|err| account_io(format!("unable to read account of alias {alias}"), err))?;
 
         let account: Account = serde_json::from_str(&account_string)
-            .map_err(|err| CliError::Other(err.to_string()))?;
-
-        let (secret, public) = read_or_generate_keypair_file(&path.join("keys"))
-            .map_err(|err| CliError::Other(err.to_string()))?;
-
-        let address = Address::new(public.clone());
+            .map_err(|err| account_io(format!("unable to parse account of alias {alias}"), err))?;
+
+        let keys_path = path.join("keys");
+
+        // NOTE: accounts whose keys were rotated record their address, since
+        // their current keys don't derive it anymore
+        let address_path = path.join(rotate_keys::ADDRESS_FILE_NAME);
+        let address = if address_path.exists() {
+            let address = std::fs::read_to_string(&address_path).map_err(|err| {
+                account_io(format!("unable to read address of alias {alias}"), err)
+            })?;
+
+            // NOTE: address files written before addresses were checksummed
+            // hold the bare public key hex
+            let address = address.trim();
+            Address::from_str(address)
+                .or_else(|err| Address::from_public_key_hex(address).map_err(|_| err))?
+        } else if is_encrypted_keyfile(&keys_path) {
+            // NOTE: encrypted keys are only decrypted when they're needed, the
+            // address is derived from the public key cached with the account
+            let public = PublicKey::from_slice(&account.pubkey)
+                .map_err(|err| account_io(format!("invalid public key for alias {alias}"), err))?;
+
+            Address::new(public)
+        } else {
+            let (_, public) =
+                read_or_generate_keypair_file(&keys_path).map_err(|err| CliError::KeypairIo {
+                    context: format!("unable to read keys of alias {alias}"),
+                    source: err.into(),
+                })?;
+
+            Address::new(public)
+        };
 
         accounts.insert(address.clone(), account);
         addresses.insert(alias, address.clone());
@@ -191,6 +385,19 @@ fn restore_accounts_and_addresses(
     Ok((accounts, addresses))
 }
 
+fn account_io(context: String, err: impl std::error::Error + Send + Sync + 'static) -> CliError {
+    CliError::AccountIo {
+        context,
+        source: Box::new(err),
+    }
+}
+
+fn is_encrypted_keyfile(path: &Path) -> bool {
+    std::fs::read_to_string(path)
+        .map(|contents| keyfile::is_encrypted(&contents))
+        .unwrap_or(false)
+}
+
 fn load_account_secret_key() {
     //
 }
@@ -1,19 +1,23 @@
-use std::{
-    net::{IpAddr, Ipv4Addr, SocketAddr},
-    path::PathBuf,
-};
+use std::path::{Path, PathBuf};
 
-use primitives::{Address, PublicKey, SecretKey};
-use secp256k1::{generate_keypair, hashes::sha256, rand, Message};
-use vrrb_core::{account::Account, helpers::write_keypair_file};
-use wallet::v2::{AddressAlias, Wallet, WalletConfig};
+use primitives::{PublicKey, SecretKey};
+use secp256k1::{generate_keypair, rand};
+use vrrb_core::{account::Account, helpers::write_keypair_file_with};
+use wallet::v2::{AddressAlias, Wallet, WalletError};
 
+use super::rotate_keys::ADDRESS_FILE_NAME;
 use crate::result::CliError;
 
+/// Creates an account with a freshly generated keypair under `alias`.
+///
+/// Refuses to replace an account already stored under `alias` unless
+/// `overwrite` is set.
 pub async fn exec(
     wallet: &mut Wallet,
     path: &PathBuf,
     alias: AddressAlias,
+    overwrite: bool,
+    passphrase: Option<&str>,
 ) -> Result<(), CliError> {
     // TODO: read keypair from file
 
@@ -21,24 +25,79 @@ pub async fn exec(
 
     let account_data_dir = path.join(format!("{}", alias));
 
+    if account_data_dir.exists() && !overwrite {
+        return Err(CliError::AccountExists(format!(
+            "an account already exists under alias {alias}, pass --overwrite to replace it"
+        )));
+    }
+
+    let (address, account) =
+        wallet
+            .create_account(alias, public_key)
+            .await
+            .map_err(|err| match err {
+                WalletError::AccountExists(address) => {
+                    CliError::AccountExists(format!("account {address} already exists in state"))
+                },
+                err => CliError::WalletError(err),
+            })?;
+
     std::fs::create_dir_all(&account_data_dir)?;
 
+    // NOTE: an address recorded by a previous key rotation belongs to the
+    // account being replaced
+    let address_file_path = account_data_dir.join(ADDRESS_FILE_NAME);
+    if address_file_path.exists() {
+        std::fs::remove_file(address_file_path)?;
+    }
+
+    write_account_files(
+        &account_data_dir,
+        &(secret_key, public_key),
+        &account,
+        passphrase,
+    )?;
+
+    println!("created account {address} as alias {alias}");
+
+    Ok(())
+}
+
+/// Writes the `keys` and `account.json` files of an account into its alias
+/// directory. The keys are encrypted if a `passphrase` is provided.
+pub(super) fn write_account_files(
+    account_data_dir: &Path,
+    keypair: &(SecretKey, PublicKey),
+    account: &Account,
+    passphrase: Option<&str>,
+) -> Result<(), CliError> {
     let key_path = account_data_dir.join("keys");
-    let account_path = account_data_dir.join("account.json");
 
-    let (address, account) = wallet
-        .create_account(alias, public_key)
-        .await
-        .map_err(|err| CliError::Other("unable to create account in state".to_string()))?;
+    write_keypair_file_with(key_path, keypair, passphrase).map_err(|err| CliError::KeypairIo {
+        context: String::from("unable to write keypair file"),
+        source: err.into(),
+    })?;
 
-    write_keypair_file(key_path, &(secret_key, public_key))
-        .map_err(|err| CliError::Other(format!("unable to write keypair file: {err}")))?;
+    write_account_file(account_data_dir, account)
+}
+
+/// Writes the `account.json` file of an account into its alias directory
+pub(super) fn write_account_file(
+    account_data_dir: &Path,
+    account: &Account,
+) -> Result<(), CliError> {
+    let account_path = account_data_dir.join("account.json");
 
-    let account_ser = serde_json::to_string_pretty(&account)
-        .map_err(|err| CliError::Other(format!("unable to serialize account data: {err}")))?;
+    let account_ser =
+        serde_json::to_string_pretty(account).map_err(|err| CliError::Serialization {
+            context: String::from("unable to serialize account data"),
+            source: err,
+        })?;
 
-    std::fs::write(account_path, account_ser)
-        .map_err(|err| CliError::Other(format!("unable to write account file: {err}")))?;
+    std::fs::write(account_path, account_ser).map_err(|err| CliError::AccountIo {
+        context: String::from("unable to write account file"),
+        source: err.into(),
+    })?;
 
     Ok(())
 }
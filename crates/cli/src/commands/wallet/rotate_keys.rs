@@ -0,0 +1,337 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+use primitives::{Address, PublicKey, SecretKey};
+use secp256k1::{generate_keypair, rand};
+use vrrb_core::{
+    account::Account,
+    helpers::{read_keypair_file, write_keypair_file_with},
+};
+use wallet::v2::{AddressAlias, Wallet};
+
+use super::new::write_account_file;
+use crate::result::{BoxedError, CliError};
+
+/// Name of the file recording an account's address once its keys no longer
+/// derive it
+pub(super) const ADDRESS_FILE_NAME: &str = "address";
+
+/// Name of the file new keys are staged in until their rotation is applied
+const PENDING_KEYS_FILE_NAME: &str = "keys.pending";
+
+/// Replaces the keypair of the account stored under `alias` with a freshly
+/// generated one, updating its public key in state.
+///
+/// The previous keypair is archived next to the new one rather than deleted,
+/// so transactions it signed can still be verified. Both are encrypted if a
+/// `passphrase` is provided, and both are on disk before the rotation is
+/// submitted, so the account can't end up bound to a key whose secret was
+/// never written.
+pub async fn exec(
+    wallet: &mut Wallet,
+    path: &PathBuf,
+    alias: AddressAlias,
+    addresses: &HashMap<AddressAlias, Address>,
+    passphrase: Option<&str>,
+) -> Result<(), CliError> {
+    let account_data_dir = path.join(alias.to_string());
+
+    let address = addresses
+        .get(&alias)
+        .cloned()
+        .ok_or(CliError::UnknownAlias(alias))?;
+
+    let current_keypair =
+        read_keypair_file(account_data_dir.join("keys")).map_err(|err| CliError::KeypairIo {
+            context: format!("unable to read keys of alias {alias}"),
+            source: err.into(),
+        })?;
+
+    let new_keypair = generate_keypair(&mut rand::thread_rng());
+
+    let rotated_at = chrono::Utc::now().timestamp();
+
+    let archive_path = stage_rotated_keys(
+        &account_data_dir,
+        &current_keypair,
+        &new_keypair,
+        rotated_at,
+        passphrase,
+    )?;
+
+    let account = match wallet
+        .rotate_account_key(address.clone(), &current_keypair.0, new_keypair.1)
+        .await
+    {
+        Ok(account) => account,
+        Err(err) => {
+            // NOTE: state still points at the current keys, which stay in place
+            discard_staged_keys(&account_data_dir, &archive_path);
+            return Err(err.into());
+        },
+    };
+
+    commit_rotated_keys(&account_data_dir, &address, &account)?;
+
+    println!(
+        "rotated the keys of account {address} (alias {alias}), previous keys archived at {}",
+        archive_path.display()
+    );
+
+    Ok(())
+}
+
+/// Writes the keys of an account about to be rotated at `rotated_at` to
+/// disk, syncing them before returning: the previous keypair is archived as
+/// `keys.<rotated_at>`, or `keys.<rotated_at>.<n>` if that archive already
+/// exists, and the new one is staged as `keys.pending` until the rotation is
+/// applied.
+///
+/// Refuses to replace keys left staged by an earlier rotation, since they
+/// may be the ones the account is bound to.
+///
+/// Returns the path of the archived keypair.
+pub(super) fn stage_rotated_keys(
+    account_data_dir: &Path,
+    previous_keypair: &(SecretKey, PublicKey),
+    new_keypair: &(SecretKey, PublicKey),
+    rotated_at: i64,
+    passphrase: Option<&str>,
+) -> Result<PathBuf, CliError> {
+    let pending_path = account_data_dir.join(PENDING_KEYS_FILE_NAME);
+
+    if pending_path.exists() {
+        return Err(CliError::AccountExists(format!(
+            "keys of an unfinished rotation are staged at {}, move them aside once you've \
+             checked which keys the account is bound to",
+            pending_path.display()
+        )));
+    }
+
+    let archive_path = unused_archive_path(account_data_dir, rotated_at);
+
+    write_synced_keypair_file(&archive_path, previous_keypair, passphrase).map_err(|err| {
+        CliError::KeypairIo {
+            context: String::from("unable to archive previous keys"),
+            source: err,
+        }
+    })?;
+
+    if let Err(err) = write_synced_keypair_file(&pending_path, new_keypair, passphrase) {
+        discard_staged_keys(account_data_dir, &archive_path);
+
+        return Err(CliError::KeypairIo {
+            context: String::from("unable to write new keys"),
+            source: err,
+        });
+    }
+
+    sync_dir(account_data_dir).map_err(|err| CliError::KeypairIo {
+        context: String::from("unable to sync account directory"),
+        source: err.into(),
+    })?;
+
+    Ok(archive_path)
+}
+
+/// Moves the staged keys of an applied rotation in place of the account's
+/// keys and refreshes `account.json`. Since the new keys don't derive the
+/// account's address, it is recorded in an `address` file.
+pub(super) fn commit_rotated_keys(
+    account_data_dir: &Path,
+    address: &Address,
+    account: &Account,
+) -> Result<(), CliError> {
+    std::fs::write(
+        account_data_dir.join(ADDRESS_FILE_NAME),
+        address.to_string(),
+    )
+    .map_err(|err| CliError::AccountIo {
+        context: String::from("unable to write address file"),
+        source: err.into(),
+    })?;
+
+    std::fs::rename(
+        account_data_dir.join(PENDING_KEYS_FILE_NAME),
+        account_data_dir.join("keys"),
+    )
+    .and_then(|_| sync_dir(account_data_dir))
+    .map_err(|err| CliError::KeypairIo {
+        context: String::from("unable to move new keys in place"),
+        source: err.into(),
+    })?;
+
+    write_account_file(account_data_dir, account)
+}
+
+/// Removes the keys staged by `stage_rotated_keys`, once the rotation they
+/// were staged for failed
+fn discard_staged_keys(account_data_dir: &Path, archive_path: &Path) {
+    // NOTE: best effort, the account's current keys are left untouched either
+    // way
+    let _ = std::fs::remove_file(account_data_dir.join(PENDING_KEYS_FILE_NAME));
+    let _ = std::fs::remove_file(archive_path);
+}
+
+/// Returns `keys.<rotated_at>`, or the first `keys.<rotated_at>.<n>` that
+/// doesn't exist yet, so archives are never overwritten
+fn unused_archive_path(account_data_dir: &Path, rotated_at: i64) -> PathBuf {
+    let mut archive_path = account_data_dir.join(format!("keys.{rotated_at}"));
+    let mut n = 1;
+
+    while archive_path.exists() {
+        archive_path = account_data_dir.join(format!("keys.{rotated_at}.{n}"));
+        n += 1;
+    }
+
+    archive_path
+}
+
+fn write_synced_keypair_file(
+    path: &Path,
+    keypair: &(SecretKey, PublicKey),
+    passphrase: Option<&str>,
+) -> Result<(), BoxedError> {
+    write_keypair_file_with(path, keypair, passphrase)?;
+    File::open(path)?.sync_all()?;
+
+    Ok(())
+}
+
+fn sync_dir(dir: &Path) -> std::io::Result<()> {
+    File::open(dir)?.sync_all()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::new::write_account_files, *};
+
+    #[test]
+    fn rotated_accounts_keep_their_address_and_previous_keys() {
+        let accounts_dir = std::env::temp_dir().join(vrrb_core::helpers::generate_random_string());
+        let account_data_dir = accounts_dir.join("0");
+        std::fs::create_dir_all(&account_data_dir).unwrap();
+
+        let previous_keypair = generate_keypair(&mut rand::thread_rng());
+        let new_keypair = generate_keypair(&mut rand::thread_rng());
+        let address = Address::new(previous_keypair.1);
+
+        write_account_files(
+            &account_data_dir,
+            &previous_keypair,
+            &Account::new(previous_keypair.1),
+            None,
+        )
+        .unwrap();
+
+        let mut account = Account::new(previous_keypair.1);
+        account.pubkey = new_keypair.1.serialize().to_vec();
+        account.bump_nonce();
+
+        let archive_path = stage_rotated_keys(
+            &account_data_dir,
+            &previous_keypair,
+            &new_keypair,
+            1_700_000_000,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(archive_path, account_data_dir.join("keys.1700000000"));
+        assert_eq!(read_keypair_file(&archive_path).unwrap(), previous_keypair);
+
+        // NOTE: the account's keys are only replaced once the rotation applies
+        assert_eq!(
+            read_keypair_file(account_data_dir.join("keys")).unwrap(),
+            previous_keypair
+        );
+        assert_eq!(
+            read_keypair_file(account_data_dir.join(PENDING_KEYS_FILE_NAME)).unwrap(),
+            new_keypair
+        );
+
+        commit_rotated_keys(&account_data_dir, &address, &account).unwrap();
+
+        assert!(!account_data_dir.join(PENDING_KEYS_FILE_NAME).exists());
+        assert_eq!(
+            read_keypair_file(account_data_dir.join("keys")).unwrap(),
+            new_keypair
+        );
+
+        let stored_account: Account = serde_json::from_str(
+            &std::fs::read_to_string(account_data_dir.join("account.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(stored_account, account);
+
+        let (accounts, addresses) =
+            super::super::restore_accounts_and_addresses(&accounts_dir).unwrap();
+        assert_eq!(addresses.get(&0), Some(&address));
+        assert_eq!(accounts.get(&address), Some(&account));
+
+        // NOTE: archives are never overwritten
+        let second_archive_path = stage_rotated_keys(
+            &account_data_dir,
+            &new_keypair,
+            &previous_keypair,
+            1_700_000_000,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(
+            second_archive_path,
+            account_data_dir.join("keys.1700000000.1")
+        );
+        assert_eq!(read_keypair_file(&archive_path).unwrap(), previous_keypair);
+    }
+
+    #[test]
+    fn failed_rotations_leave_the_current_keys_in_place() {
+        let account_data_dir =
+            std::env::temp_dir().join(vrrb_core::helpers::generate_random_string());
+        std::fs::create_dir_all(&account_data_dir).unwrap();
+
+        let current_keypair = generate_keypair(&mut rand::thread_rng());
+        let new_keypair = generate_keypair(&mut rand::thread_rng());
+
+        write_account_files(
+            &account_data_dir,
+            &current_keypair,
+            &Account::new(current_keypair.1),
+            None,
+        )
+        .unwrap();
+
+        let archive_path = stage_rotated_keys(
+            &account_data_dir,
+            &current_keypair,
+            &new_keypair,
+            1_700_000_000,
+            None,
+        )
+        .unwrap();
+
+        // NOTE: keys staged by a rotation that may have been applied are kept
+        assert!(stage_rotated_keys(
+            &account_data_dir,
+            &current_keypair,
+            &new_keypair,
+            1_700_000_001,
+            None,
+        )
+        .is_err());
+
+        discard_staged_keys(&account_data_dir, &archive_path);
+
+        assert!(!archive_path.exists());
+        assert!(!account_data_dir.join(PENDING_KEYS_FILE_NAME).exists());
+        assert_eq!(
+            read_keypair_file(account_data_dir.join("keys")).unwrap(),
+            current_keypair
+        );
+    }
+}
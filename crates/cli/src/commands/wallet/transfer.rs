@@ -20,8 +20,7 @@ pub async fn exec(
     // or we need to initialize accounts with tokens on testnet
     let digest = wallet
         .send_transaction(address_number, to, amount, token, timestamp)
-        .await
-        .map_err(|err| CliError::Other(err.to_string()))?;
+        .await?;
 
     Ok(digest)
 }
@@ -1,16 +1,14 @@
-use clap::Parser;
-
 mod cli;
 pub mod result;
 
 pub(crate) use crate::cli::*;
 pub(crate) mod commands;
 
+/// Runs the command given on the command line. Errors carry the code the
+/// process should exit with, see `CliError::exit_code`.
 #[telemetry::instrument]
-pub async fn run() -> anyhow::Result<()> {
-    let args = Args::parse();
-
-    commands::exec(args).await?;
+pub async fn run() -> result::Result<()> {
+    let args = Args::parse_tracking_explicit_flags();
 
-    Ok(())
+    commands::exec(args).await
 }
@@ -1,10 +1,9 @@
-use telemetry::TelemetrySubscriber;
-
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
-    TelemetrySubscriber::init(std::io::stdout)?;
-
-    cli::run().await?;
-
-    Ok(())
+// NOTE: nodes run on a runtime of their own, sized according to their config,
+// so the CLI itself gets by with a single thread
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    if let Err(err) = cli::run().await {
+        eprintln!("Error: {err}");
+        std::process::exit(err.exit_code());
+    }
 }
@@ -1,5 +1,9 @@
+use std::{net::SocketAddr, path::PathBuf};
+
 use thiserror::Error;
 
+pub(crate) type BoxedError = Box<dyn std::error::Error + Send + Sync>;
+
 #[derive(Debug, Error)]
 pub enum CliError {
     #[error("invalid command {0} provided")]
@@ -11,6 +15,96 @@ pub enum CliError {
     #[error("opts error: {0}")]
     OptsError(String),
 
+    #[error("--{0} and --{1} cannot both bind to {2}")]
+    ConflictingAddresses(String, String, std::net::SocketAddr),
+
+    #[error("{0} startup checks failed")]
+    ValidationFailed(usize),
+
+    #[error("unable to read keypair file {}: {1}, refusing to overwrite it", .0.display())]
+    UnreadableKeypair(std::path::PathBuf, String),
+
+    #[error("configured node idx {configured} conflicts with persisted idx {persisted}, pass --force-idx to replace it")]
+    ConflictingIdx {
+        configured: primitives::NodeIdx,
+        persisted: primitives::NodeIdx,
+    },
+
+    #[error("{context}: {source}")]
+    KeypairIo {
+        context: String,
+        #[source]
+        source: BoxedError,
+    },
+
+    #[error("{context}: {source}")]
+    ConfigRead {
+        context: String,
+        #[source]
+        source: BoxedError,
+    },
+
+    #[error("{context}: {source}")]
+    ConfigWrite {
+        context: String,
+        #[source]
+        source: BoxedError,
+    },
+
+    #[error("failed to start node: {0}")]
+    NodeStart(#[source] node::result::NodeError),
+
+    #[error("{context}: {source}")]
+    NodeStop {
+        context: String,
+        #[source]
+        source: BoxedError,
+    },
+
+    #[error("{0}")]
+    DetachedNode(String),
+
+    #[error("failed to listen for {signal}: {source}")]
+    Signal {
+        signal: &'static str,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("unable to connect to the node at {address}: {source}")]
+    RpcConnect {
+        address: SocketAddr,
+        #[source]
+        source: BoxedError,
+    },
+
+    #[error("unable to open db at {}: {source}", .path.display())]
+    DbOpen {
+        path: PathBuf,
+        #[source]
+        source: BoxedError,
+    },
+
+    #[error("no account found for alias {0}")]
+    UnknownAlias(wallet::v2::AddressAlias),
+
+    #[error("{0}")]
+    AccountExists(String),
+
+    #[error("{context}: {source}")]
+    AccountIo {
+        context: String,
+        #[source]
+        source: BoxedError,
+    },
+
+    #[error("{context}: {source}")]
+    Serialization {
+        context: String,
+        #[source]
+        source: serde_json::Error,
+    },
+
     #[error("unable to setup telemetry subscriber: {0}")]
     Telemetry(#[from] telemetry::TelemetryError),
 
@@ -36,4 +130,209 @@ pub enum CliError {
     Other(String),
 }
 
+/// Process exit codes, one per category of error, so scripts can tell e.g. a
+/// config error apart from a network one
+impl CliError {
+    /// Config files that can't be read, written or don't pass validation
+    pub const CONFIG: i32 = 3;
+    /// Errors that fit none of the other categories
+    pub const GENERAL: i32 = 1;
+    /// Keypair files that can't be read, written or decrypted
+    pub const KEYPAIR: i32 = 4;
+    /// Nodes that can't be reached over the network
+    pub const NETWORK: i32 = 6;
+    /// Nodes that fail to start, stop or run
+    pub const NODE: i32 = 5;
+    /// Dbs that can't be opened or read
+    pub const STORAGE: i32 = 7;
+    /// Invalid commands, options or combinations thereof
+    pub const USAGE: i32 = 2;
+    /// Wallet accounts that can't be found, created or persisted
+    pub const WALLET: i32 = 8;
+
+    /// Returns the code the process exits with on this error
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::InvalidCommand(_)
+            | CliError::NoSubcommand
+            | CliError::OptsError(_)
+            | CliError::ConflictingAddresses(..)
+            | CliError::ConflictingIdx { .. } => Self::USAGE,
+
+            CliError::ValidationFailed(_)
+            | CliError::ConfigRead { .. }
+            | CliError::ConfigWrite { .. } => Self::CONFIG,
+
+            CliError::UnreadableKeypair(..) | CliError::KeypairIo { .. } => Self::KEYPAIR,
+
+            CliError::NodeStart(_)
+            | CliError::NodeStop { .. }
+            | CliError::DetachedNode(_)
+            | CliError::Signal { .. }
+            | CliError::Node(_) => Self::NODE,
+
+            CliError::RpcConnect { .. }
+            | CliError::WalletError(wallet::v2::WalletError::RpcError(_)) => Self::NETWORK,
+
+            CliError::DbOpen { .. } | CliError::Storage(_) => Self::STORAGE,
+
+            CliError::UnknownAlias(_)
+            | CliError::AccountExists(_)
+            | CliError::AccountIo { .. }
+            | CliError::WalletError(_) => Self::WALLET,
+
+            CliError::Serialization { .. }
+            | CliError::Telemetry(_)
+            | CliError::Primitive(_)
+            | CliError::Io(_)
+            | CliError::CoreError(_)
+            | CliError::Other(_) => Self::GENERAL,
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, CliError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn io_error() -> std::io::Error {
+        std::io::Error::new(std::io::ErrorKind::Other, "mock io error")
+    }
+
+    #[test]
+    fn errors_map_to_the_exit_code_of_their_category() {
+        let address = "127.0.0.1:9293".parse().unwrap();
+
+        let errors = [
+            (CliError::NoSubcommand, CliError::USAGE),
+            (CliError::OptsError(String::from("--id")), CliError::USAGE),
+            (
+                CliError::ConflictingIdx {
+                    configured: 1,
+                    persisted: 2,
+                },
+                CliError::USAGE,
+            ),
+            (CliError::ValidationFailed(1), CliError::CONFIG),
+            (
+                CliError::ConfigRead {
+                    context: String::from("failed to read config file"),
+                    source: io_error().into(),
+                },
+                CliError::CONFIG,
+            ),
+            (
+                CliError::ConfigWrite {
+                    context: String::from("failed to serialize node config"),
+                    source: io_error().into(),
+                },
+                CliError::CONFIG,
+            ),
+            (
+                CliError::KeypairIo {
+                    context: String::from("failed to write keypair file"),
+                    source: io_error().into(),
+                },
+                CliError::KEYPAIR,
+            ),
+            (
+                CliError::UnreadableKeypair(PathBuf::from("keypair"), String::from("invalid")),
+                CliError::KEYPAIR,
+            ),
+            (
+                CliError::NodeStart(node::result::NodeError::Other(String::from("bind"))),
+                CliError::NODE,
+            ),
+            (
+                CliError::NodeStop {
+                    context: String::from("failed to join node task"),
+                    source: io_error().into(),
+                },
+                CliError::NODE,
+            ),
+            (
+                CliError::DetachedNode(String::from("no node is running")),
+                CliError::NODE,
+            ),
+            (
+                CliError::Signal {
+                    signal: "SIGTERM",
+                    source: io_error(),
+                },
+                CliError::NODE,
+            ),
+            (
+                CliError::RpcConnect {
+                    address,
+                    source: io_error().into(),
+                },
+                CliError::NETWORK,
+            ),
+            (
+                CliError::WalletError(wallet::v2::WalletError::RpcError(
+                    jsonrpsee::core::Error::Custom(String::from("connection refused")),
+                )),
+                CliError::NETWORK,
+            ),
+            (
+                CliError::DbOpen {
+                    path: PathBuf::from("db"),
+                    source: io_error().into(),
+                },
+                CliError::STORAGE,
+            ),
+            (CliError::UnknownAlias(0), CliError::WALLET),
+            (
+                CliError::AccountIo {
+                    context: String::from("unable to write account file"),
+                    source: io_error().into(),
+                },
+                CliError::WALLET,
+            ),
+            (
+                CliError::WalletError(wallet::v2::WalletError::Custom(String::from("mock"))),
+                CliError::WALLET,
+            ),
+            (CliError::Io(io_error()), CliError::GENERAL),
+            (CliError::Other(String::from("mock")), CliError::GENERAL),
+        ];
+
+        for (err, exit_code) in errors {
+            assert_eq!(err.exit_code(), exit_code, "unexpected exit code for {err}");
+        }
+    }
+
+    #[test]
+    fn exit_codes_are_distinct_per_category() {
+        let mut exit_codes = vec![
+            CliError::GENERAL,
+            CliError::USAGE,
+            CliError::CONFIG,
+            CliError::KEYPAIR,
+            CliError::NODE,
+            CliError::NETWORK,
+            CliError::STORAGE,
+            CliError::WALLET,
+        ];
+
+        exit_codes.sort();
+        exit_codes.dedup();
+
+        assert_eq!(exit_codes.len(), 8);
+        assert!(!exit_codes.contains(&0));
+    }
+
+    #[test]
+    fn errors_keep_their_source() {
+        use std::error::Error;
+
+        let err = CliError::KeypairIo {
+            context: String::from("failed to write keypair file"),
+            source: io_error().into(),
+        };
+
+        assert_eq!(err.source().unwrap().to_string(), "mock io error");
+    }
+}
@@ -23,3 +23,26 @@ SUBCOMMANDS:
 
     cmd.arg("--help").assert().stdout(help_text).success();
 }
+
+#[test]
+fn cli_errors_exit_with_the_code_of_their_category() {
+    let data_dir = std::env::temp_dir().join(vrrb_core::helpers::generate_random_string());
+    std::fs::create_dir_all(&data_dir).unwrap();
+
+    Command::cargo_bin("cli")
+        .unwrap()
+        .args(["node", "init-config", "--output"])
+        .arg(data_dir.join("node.json"))
+        .assert()
+        .code(cli::result::CliError::USAGE);
+
+    // NOTE: the data dir holds no db to open
+    Command::cargo_bin("cli")
+        .unwrap()
+        .args(["node", "db-dump", "--data-dir"])
+        .arg(&data_dir)
+        .assert()
+        .code(cli::result::CliError::STORAGE);
+
+    std::fs::remove_dir_all(&data_dir).unwrap_or_default();
+}
@@ -57,7 +57,7 @@ impl Election for Quorum {
         }
         let mut vvrf = VVRF::new(
             (payload.2).as_bytes(),
-            &kp.miner_kp.0.secret_bytes().to_vec(),
+            &kp.txn_keypair().secret_key().secret_bytes().to_vec(),
         );
 
         if VVRF::verify_seed(&mut vvrf).is_err() {
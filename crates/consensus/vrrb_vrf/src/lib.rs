@@ -20,7 +20,7 @@ mod tests {
     fn same_seed_equals_same_random_8() {
         let kp = KeyPair::random();
         let message = b"test";
-        let sk = kp.miner_kp.0.secret_bytes().to_vec();
+        let sk = kp.txn_keypair().secret_key().secret_bytes().to_vec();
         let mut vvrf1: VVRF = VVRF::new(message, &sk);
         let mut vvrf2: VVRF = VVRF::new(message, &sk);
         let rn1 = vvrf1.generate_u8();
@@ -34,7 +34,7 @@ mod tests {
     fn same_seed_equals_same_random_u16() {
         let kp = KeyPair::random();
         let message = b"test";
-        let sk = kp.miner_kp.0.secret_bytes().to_vec();
+        let sk = kp.txn_keypair().secret_key().secret_bytes().to_vec();
         let mut vvrf1: VVRF = VVRF::new(message, &sk);
         let mut vvrf2: VVRF = VVRF::new(message, &sk);
         let rn1 = vvrf1.generate_u16();
@@ -48,7 +48,7 @@ mod tests {
     fn same_seed_equals_same_random_32() {
         let kp = KeyPair::random();
         let message = b"test";
-        let sk = kp.miner_kp.0.secret_bytes().to_vec();
+        let sk = kp.txn_keypair().secret_key().secret_bytes().to_vec();
         let mut vvrf1: VVRF = VVRF::new(message, &sk);
         let mut vvrf2: VVRF = VVRF::new(message, &sk);
         let rn1 = vvrf1.generate_u32();
@@ -61,7 +61,7 @@ mod tests {
     fn same_seed_equals_same_random_u64() {
         let kp = KeyPair::random();
         let message = b"test";
-        let sk = kp.miner_kp.0.secret_bytes().to_vec();
+        let sk = kp.txn_keypair().secret_key().secret_bytes().to_vec();
         let mut vvrf1: VVRF = VVRF::new(message, &sk);
         let mut vvrf2: VVRF = VVRF::new(message, &sk);
         let rn1 = vvrf1.generate_u64();
@@ -75,7 +75,7 @@ mod tests {
     fn same_seed_equals_same_random_u128() {
         let message = b"test";
         let kp = KeyPair::random();
-        let sk = kp.miner_kp.0.secret_bytes().to_vec();
+        let sk = kp.txn_keypair().secret_key().secret_bytes().to_vec();
         let mut vvrf1: VVRF = VVRF::new(message, &sk);
         let mut vvrf2: VVRF = VVRF::new(message, &sk);
         let rn1 = vvrf1.generate_u128();
@@ -89,7 +89,7 @@ mod tests {
     fn hash_is_verifiable() {
         let kp = KeyPair::random();
         let message = b"test";
-        let sk = kp.miner_kp.0.secret_bytes().to_vec();
+        let sk = kp.txn_keypair().secret_key().secret_bytes().to_vec();
         let vvrf: VVRF = VVRF::new(message, &sk);
         let mut vrf = ECVRF::from_suite(CipherSuite::SECP256K1_SHA256_TAI).unwrap();
         let beta = vrf
@@ -103,7 +103,7 @@ mod tests {
     fn generates_word_from_lib() {
         let kp = KeyPair::random();
         let message = b"test";
-        let sk = kp.miner_kp.0.secret_bytes().to_vec();
+        let sk = kp.txn_keypair().secret_key().secret_bytes().to_vec();
         let mut vvrf: VVRF = VVRF::new(message, &sk);
         assert!(WORDS.contains(&(vvrf.generate_word()).as_str()));
     }
@@ -112,7 +112,7 @@ mod tests {
     fn generates_right_num_words() {
         let kp = KeyPair::random();
         let message = b"test";
-        let sk = kp.miner_kp.0.secret_bytes().to_vec();
+        let sk = kp.txn_keypair().secret_key().secret_bytes().to_vec();
         let mut vvrf1: VVRF = VVRF::new(message, &sk);
         assert_eq!((vvrf1.generate_words(7)).len(), 7);
     }
@@ -121,7 +121,7 @@ mod tests {
     fn generates_rng_in_range() {
         let kp = KeyPair::random();
         let message = b"test";
-        let sk = kp.miner_kp.0.secret_bytes().to_vec();
+        let sk = kp.txn_keypair().secret_key().secret_bytes().to_vec();
         let mut vvrf1: VVRF = VVRF::new(message, &sk);
         let rn = vvrf1.generate_u8_in_range(10, 100);
         assert!(10 <= rn && rn <= 100);
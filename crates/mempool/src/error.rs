@@ -14,4 +14,7 @@ pub enum MempoolError {
 
     #[error("transaction {0} already exists")]
     TransactionExists(TransactionDigest),
+
+    #[error("failed to read or write mempool snapshot: {0}")]
+    Snapshot(String),
 }
@@ -3,14 +3,15 @@ pub mod mempool;
 
 // TODO: merge pool w Mempool later on
 pub mod pool;
-pub use crate::mempool::*;
+pub mod validation_queue;
+pub use crate::{mempool::*, validation_queue::*};
 
 #[cfg(test)]
 mod tests {
 
     use std::{
         collections::{HashMap, HashSet},
-        time::{SystemTime, UNIX_EPOCH},
+        time::{Duration, SystemTime, UNIX_EPOCH},
     };
 
     use primitives::Signature;
@@ -21,7 +22,15 @@ mod tests {
         txn::{NewTxnArgs, Txn},
     };
 
-    use crate::mempool::{LeftRightMempool, TxnRecord, TxnStatus};
+    use crate::mempool::{
+        LeftRightMempool,
+        MempoolEvictionPolicy,
+        MempoolInsertOutcome,
+        MempoolMetrics,
+        TxnRecord,
+        TxnStatus,
+        MAX_QUEUED_NONCE_GAP,
+    };
 
     fn mock_txn_signature() -> Signature {
         ecdsa::Signature::from_compact(&[
@@ -109,6 +118,36 @@ mod tests {
         assert_eq!(1, mpooldb.size());
     }
 
+    #[test]
+    fn inserting_the_same_txn_twice_is_reported_as_duplicate() {
+        let keypair = KeyPair::random();
+
+        let txn = Txn::new(NewTxnArgs {
+            timestamp: 0,
+            sender_address: String::from("aaa1"),
+            sender_public_key: keypair.get_miner_public_key().clone(),
+            receiver_address: String::from("bbb1"),
+            token: None,
+            amount: 0,
+            validators: Some(HashMap::<String, bool>::new()),
+            nonce: 0,
+            signature: mock_txn_signature(),
+        });
+
+        let mut mpooldb = LeftRightMempool::new();
+
+        assert_eq!(
+            mpooldb.insert(txn.clone()).unwrap(),
+            MempoolInsertOutcome::Inserted
+        );
+        assert_eq!(
+            mpooldb.insert(txn).unwrap(),
+            MempoolInsertOutcome::Duplicate
+        );
+
+        assert_eq!(mpooldb.factory().entries().len(), 1);
+    }
+
     #[test]
     fn add_two_different_txn() {
         let keypair = KeyPair::random();
@@ -512,4 +551,392 @@ mod tests {
                 handle.join().unwrap();
             });
     }
+
+    #[test]
+    fn snapshot_round_trip_preserves_pending_txns() {
+        let keypair = KeyPair::random();
+        let mut lrmpooldb = LeftRightMempool::new();
+
+        let txns = (1..=5u128)
+            .map(|n| {
+                Txn::new(NewTxnArgs {
+                    timestamp: 0,
+                    sender_address: String::from("aaa1"),
+                    sender_public_key: keypair.get_miner_public_key().clone(),
+                    receiver_address: String::from("bbb1"),
+                    token: None,
+                    amount: n,
+                    validators: Some(HashMap::<String, bool>::new()),
+                    nonce: 0,
+                    signature: mock_txn_signature(),
+                })
+            })
+            .collect::<HashSet<Txn>>();
+
+        lrmpooldb.extend(txns).unwrap();
+
+        let snapshot_path = std::env::temp_dir()
+            .join(vrrb_core::helpers::generate_random_string())
+            .join("mempool.snapshot");
+
+        lrmpooldb.save_snapshot(&snapshot_path).unwrap();
+
+        // NOTE: snapshots are renamed into place once fully written
+        assert!(!snapshot_path.with_extension("snapshot.tmp").exists());
+
+        let restored = LeftRightMempool::load_snapshot(&snapshot_path).unwrap();
+
+        assert_eq!(restored.size(), 5);
+        assert_eq!(restored.factory().entries(), lrmpooldb.factory().entries());
+
+        std::fs::remove_file(&snapshot_path).unwrap_or_default();
+    }
+
+    #[test]
+    fn snapshot_with_unknown_version_loads_empty() {
+        let snapshot_path = std::env::temp_dir()
+            .join(vrrb_core::helpers::generate_random_string())
+            .join("mempool.snapshot");
+
+        std::fs::create_dir_all(snapshot_path.parent().unwrap()).unwrap();
+        std::fs::write(&snapshot_path, r#"{"version":999,"records":"not a list"}"#).unwrap();
+
+        let restored = LeftRightMempool::load_snapshot(&snapshot_path).unwrap();
+
+        assert_eq!(restored.size(), 0);
+
+        std::fs::remove_file(&snapshot_path).unwrap_or_default();
+    }
+
+    fn mock_txn_with_fee(keypair: &KeyPair, amount: u128, fee: u128) -> Txn {
+        let mut txn = Txn::new(NewTxnArgs {
+            timestamp: 0,
+            sender_address: String::from("aaa1"),
+            sender_public_key: keypair.get_miner_public_key().clone(),
+            receiver_address: String::from("bbb1"),
+            token: None,
+            amount,
+            validators: Some(HashMap::<String, bool>::new()),
+            nonce: 0,
+            signature: mock_txn_signature(),
+        });
+
+        txn.set_fee(fee);
+        txn
+    }
+
+    #[test]
+    fn full_mempool_evicts_lowest_fee_txns_first() {
+        let keypair = KeyPair::random();
+        let mut lrmpooldb = LeftRightMempool::new();
+        lrmpooldb.set_max_size(Some(3), MempoolEvictionPolicy::FeeThenAge);
+
+        for (amount, fee) in [(1, 10), (2, 50), (3, 20), (4, 40), (5, 30)] {
+            lrmpooldb
+                .insert(mock_txn_with_fee(&keypair, amount, fee))
+                .unwrap();
+        }
+
+        let mut surviving_fees = lrmpooldb
+            .factory()
+            .values()
+            .iter()
+            .map(|txn| txn.fee())
+            .collect::<Vec<u128>>();
+
+        surviving_fees.sort();
+
+        assert_eq!(surviving_fees, vec![30, 40, 50]);
+        assert_eq!(lrmpooldb.factory().size(), 3);
+        assert_eq!(lrmpooldb.factory().eviction_count(), 2);
+    }
+
+    #[test]
+    fn full_mempool_evicts_incoming_txn_paying_the_lowest_fee() {
+        let keypair = KeyPair::random();
+        let mut lrmpooldb = LeftRightMempool::new();
+        lrmpooldb.set_max_size(Some(2), MempoolEvictionPolicy::FeeThenAge);
+
+        lrmpooldb
+            .insert(mock_txn_with_fee(&keypair, 1, 20))
+            .unwrap();
+        lrmpooldb
+            .insert(mock_txn_with_fee(&keypair, 2, 30))
+            .unwrap();
+
+        let outcome = lrmpooldb.insert(mock_txn_with_fee(&keypair, 3, 5)).unwrap();
+
+        assert_eq!(outcome, MempoolInsertOutcome::Evicted);
+        assert_eq!(lrmpooldb.size(), 2);
+        assert_eq!(lrmpooldb.eviction_count(), 1);
+    }
+
+    #[test]
+    fn txns_that_left_the_mempool_are_not_picked_for_eviction() {
+        let keypair = KeyPair::random();
+        let mut lrmpooldb = LeftRightMempool::new();
+
+        let txns = [(1, 10), (2, 20), (3, 30)]
+            .into_iter()
+            .map(|(amount, fee)| mock_txn_with_fee(&keypair, amount, fee))
+            .collect::<Vec<Txn>>();
+
+        for txn in txns.iter() {
+            lrmpooldb.insert(txn.clone()).unwrap();
+        }
+
+        lrmpooldb.remove(&txns[0].digest()).unwrap();
+        lrmpooldb.set_max_size(Some(1), MempoolEvictionPolicy::FeeThenAge);
+
+        assert_eq!(lrmpooldb.take_evicted(), vec![txns[1].digest()]);
+        assert_eq!(lrmpooldb.factory().values(), vec![txns[2].clone()]);
+        assert!(lrmpooldb.take_evicted().is_empty());
+    }
+
+    #[test]
+    fn read_handle_records_respect_limit() {
+        let keypair = KeyPair::random();
+        let mut lrmpooldb = LeftRightMempool::new();
+
+        for amount in 0..5 {
+            lrmpooldb
+                .insert(mock_txn_with_fee(&keypair, amount, 10))
+                .unwrap();
+        }
+
+        let factory = lrmpooldb.factory();
+
+        assert_eq!(factory.records(None).len(), 5);
+
+        let records = factory.records(Some(2));
+
+        assert_eq!(records.len(), 2);
+        assert!(records.iter().all(|record| record.txn.fee() == 10));
+    }
+
+    #[test]
+    fn read_handle_looks_up_pending_txns_by_digest() {
+        let keypair = KeyPair::random();
+        let mut lrmpooldb = LeftRightMempool::new();
+
+        let pending = mock_txn_with_fee(&keypair, 1, 10);
+        let unknown = mock_txn_with_fee(&keypair, 2, 10);
+
+        lrmpooldb.insert(pending.clone()).unwrap();
+
+        let factory = lrmpooldb.factory();
+
+        assert!(factory.contains(&pending.digest()));
+        assert_eq!(factory.get(&pending.digest()).unwrap().txn, pending);
+        assert!(!factory.contains(&unknown.digest()));
+        assert!(factory.get(&unknown.digest()).is_none());
+    }
+
+    fn mock_txn_with_nonce(keypair: &KeyPair, nonce: u128) -> Txn {
+        Txn::new(NewTxnArgs {
+            timestamp: 0,
+            sender_address: String::from("aaa1"),
+            sender_public_key: keypair.get_miner_public_key().clone(),
+            receiver_address: String::from("bbb1"),
+            token: None,
+            amount: 10,
+            validators: Some(HashMap::<String, bool>::new()),
+            nonce,
+            signature: mock_txn_signature(),
+        })
+    }
+
+    #[test]
+    fn txns_ahead_of_the_account_nonce_are_queued_until_the_gap_fills() {
+        let keypair = KeyPair::random();
+        let mut lrmpooldb = LeftRightMempool::new();
+
+        for nonce in [8, 7, 6] {
+            assert_eq!(
+                lrmpooldb
+                    .insert_at_nonce(mock_txn_with_nonce(&keypair, nonce), 5)
+                    .unwrap(),
+                MempoolInsertOutcome::Queued
+            );
+        }
+
+        assert_eq!(lrmpooldb.ready_count(), 0);
+        assert_eq!(lrmpooldb.queued_count(), 3);
+        assert!(lrmpooldb.contains(&mock_txn_with_nonce(&keypair, 7).digest().to_string()));
+
+        assert_eq!(
+            lrmpooldb
+                .insert_at_nonce(mock_txn_with_nonce(&keypair, 5), 5)
+                .unwrap(),
+            MempoolInsertOutcome::Inserted
+        );
+
+        let factory = lrmpooldb.factory();
+
+        assert_eq!(lrmpooldb.ready_count(), 4);
+        assert_eq!(lrmpooldb.queued_count(), 0);
+        assert_eq!(factory.size(), 4);
+        assert_eq!(factory.queued_count(), 0);
+        assert_eq!(
+            lrmpooldb
+                .pool()
+                .values()
+                .map(|record| record.txn.nonce)
+                .collect::<Vec<_>>(),
+            vec![5, 6, 7, 8]
+        );
+    }
+
+    #[test]
+    fn txns_too_far_ahead_of_the_next_nonce_are_turned_away() {
+        let keypair = KeyPair::random();
+        let mut lrmpooldb = LeftRightMempool::new();
+
+        let max = 5 + MAX_QUEUED_NONCE_GAP;
+
+        assert_eq!(
+            lrmpooldb
+                .insert_at_nonce(mock_txn_with_nonce(&keypair, max), 5)
+                .unwrap(),
+            MempoolInsertOutcome::Queued
+        );
+        assert_eq!(
+            lrmpooldb
+                .insert_at_nonce(mock_txn_with_nonce(&keypair, max + 1), 5)
+                .unwrap(),
+            MempoolInsertOutcome::NonceTooFarAhead { max }
+        );
+        assert_eq!(lrmpooldb.queued_count(), 1);
+    }
+
+    #[test]
+    fn senders_without_ready_txns_fall_back_to_their_account_nonce() {
+        let keypair = KeyPair::random();
+        let mut lrmpooldb = LeftRightMempool::new();
+
+        let ready = mock_txn_with_nonce(&keypair, 100);
+        let queued = mock_txn_with_nonce(&keypair, 102);
+
+        lrmpooldb.insert_at_nonce(ready.clone(), 100).unwrap();
+        lrmpooldb.insert_at_nonce(queued.clone(), 100).unwrap();
+
+        lrmpooldb.remove(&queued.digest()).unwrap();
+        assert_eq!(lrmpooldb.queued_count(), 0);
+        assert!(!lrmpooldb.contains(&queued.digest().to_string()));
+
+        lrmpooldb.remove(&ready.digest()).unwrap();
+
+        // NOTE: nonce 101 would still be expected next if the sender had
+        // been kept track of
+        assert_eq!(
+            lrmpooldb
+                .insert_at_nonce(mock_txn_with_nonce(&keypair, 80), 1)
+                .unwrap(),
+            MempoolInsertOutcome::NonceTooFarAhead {
+                max: 1 + MAX_QUEUED_NONCE_GAP
+            }
+        );
+    }
+
+    #[test]
+    fn txns_are_removed_once_they_outlive_their_ttl() {
+        let keypair = KeyPair::random();
+        let mut lrmpooldb = LeftRightMempool::new();
+
+        let ready = mock_txn_with_nonce(&keypair, 5);
+        let queued = mock_txn_with_nonce(&keypair, 7);
+
+        lrmpooldb.insert_at_nonce(ready.clone(), 5).unwrap();
+        lrmpooldb.insert_at_nonce(queued.clone(), 5).unwrap();
+
+        let added_at = lrmpooldb
+            .factory()
+            .get(&ready.digest())
+            .unwrap()
+            .added_timestamp;
+        let ttl = Duration::from_secs(60);
+
+        assert!(lrmpooldb.remove_expired(ttl, added_at + 59).is_empty());
+        assert_eq!(lrmpooldb.ready_count(), 1);
+        assert_eq!(lrmpooldb.queued_count(), 1);
+
+        // NOTE: the queued txn may have been added a second later
+        let mut expired = lrmpooldb
+            .remove_expired(ttl, added_at + 61)
+            .into_iter()
+            .map(|record| record.txn)
+            .collect::<Vec<Txn>>();
+        expired.sort_by_key(|txn| txn.nonce);
+
+        assert_eq!(expired, vec![ready, queued]);
+        assert_eq!(lrmpooldb.ready_count(), 0);
+        assert_eq!(lrmpooldb.queued_count(), 0);
+    }
+
+    #[test]
+    fn metrics_reflect_inserts_duplicates_and_evictions() {
+        let keypair = KeyPair::random();
+        let mut lrmpooldb = LeftRightMempool::new();
+        lrmpooldb.set_max_size(Some(2), MempoolEvictionPolicy::FeeThenAge);
+
+        let factory = lrmpooldb.factory();
+        assert_eq!(factory.metrics(), MempoolMetrics::default());
+
+        let txns = [(1, 20), (2, 30), (3, 40)]
+            .into_iter()
+            .map(|(amount, fee)| mock_txn_with_fee(&keypair, amount, fee))
+            .collect::<Vec<Txn>>();
+
+        for txn in txns.iter() {
+            lrmpooldb.insert(txn.clone()).unwrap();
+        }
+
+        assert_eq!(
+            lrmpooldb.insert(txns[2].clone()).unwrap(),
+            MempoolInsertOutcome::Duplicate
+        );
+
+        lrmpooldb.remove(&txns[1].digest()).unwrap();
+
+        // NOTE: reads go through the factory while the writer keeps going
+        assert_eq!(factory.values(), vec![txns[2].clone()]);
+
+        let metrics = factory.metrics();
+
+        assert_eq!(
+            metrics,
+            MempoolMetrics {
+                inserts: 3,
+                duplicates: 1,
+                evictions: 1,
+                size: 1,
+                queued: 0,
+                read_lag: 0,
+            }
+        );
+        assert_eq!(lrmpooldb.metrics(), metrics);
+    }
+
+    #[test]
+    fn metrics_count_duplicates_within_a_batch() {
+        let keypair = KeyPair::random();
+        let mut lrmpooldb = LeftRightMempool::new();
+
+        let txn = mock_txn_with_fee(&keypair, 1, 20);
+
+        let record = TxnRecord::new(txn.clone());
+        let mut resubmitted = record.clone();
+        resubmitted.added_timestamp += 1;
+
+        lrmpooldb
+            .extend_with_records(HashSet::from([record, resubmitted]))
+            .unwrap();
+
+        let metrics = lrmpooldb.metrics();
+
+        assert_eq!(metrics.inserts, 1);
+        assert_eq!(metrics.duplicates, 1);
+        assert_eq!(metrics.size, 1);
+        assert_eq!(lrmpooldb.factory().values(), vec![txn]);
+    }
 }
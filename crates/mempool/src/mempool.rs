@@ -1,15 +1,24 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fs,
     hash::Hash,
+    io::Write,
+    path::{Path, PathBuf},
     result::Result as StdResult,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
 };
 
 use fxhash::FxBuildHasher;
 use indexmap::IndexMap;
 use left_right::{Absorb, ReadHandle, ReadHandleFactory, WriteHandle};
-use primitives::TxHashString;
+use primitives::{Address, TxHashString};
 use serde::{Deserialize, Serialize};
-use vrrb_core::txn::{TransactionDigest, TxTimestamp, Txn};
+use telemetry::warn;
+use vrrb_core::txn::{TransactionDigest, TxAmount, TxNonce, TxTimestamp, Txn};
 
 use super::error::MempoolError;
 
@@ -47,8 +56,17 @@ impl TxnRecord {
             ..Default::default()
         }
     }
+
+    /// Position of the record within the mempool's eviction order
+    fn eviction_key(&self) -> EvictionKey {
+        (self.txn.fee(), self.added_timestamp, self.txn_id.clone())
+    }
 }
 
+/// Orders pending transactions by fee, then by age, i.e. in the order
+/// `MempoolEvictionPolicy::FeeThenAge` evicts them in
+type EvictionKey = (TxAmount, TxTimestamp, TxHashString);
+
 pub type PoolType = IndexMap<TxHashString, TxnRecord, FxBuildHasher>;
 
 #[derive(Debug, Clone, Default, Hash, PartialEq, Eq, Serialize, Deserialize)]
@@ -60,10 +78,36 @@ pub enum TxnStatus {
     Rejected,
 }
 
-/// Mempool stores unprocessed transactions
+/// Transactions waiting on lower nonces of the same sender, by sender and
+/// nonce
+pub type QueuedPoolType = HashMap<Address, BTreeMap<TxNonce, TxnRecord>>;
+
+/// How far ahead of its sender's next nonce a transaction may be queued.
+/// Transactions any further ahead are turned away rather than held.
+pub const MAX_QUEUED_NONCE_GAP: TxNonce = 64;
+
+/// Mempool stores unprocessed transactions.
+///
+/// Transactions that can be executed right away are kept in `pool`, in the
+/// order they became ready. Those whose nonce is ahead of their sender's next
+/// nonce are held in `queued` until the gap is filled, at which point they
+/// are promoted to `pool` in nonce order.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Mempool {
     pool: PoolType,
+    queued: QueuedPoolType,
+    /// Sender and nonce of each queued transaction, by digest
+    queued_ids: HashMap<TxHashString, (Address, TxNonce)>,
+    /// Amount of ready transactions of each sender
+    ready_counts: HashMap<Address, usize>,
+    /// Nonce the next ready transaction of each sender is expected to have.
+    /// Only kept for senders with ready transactions.
+    next_nonces: HashMap<Address, TxNonce>,
+    /// Every pending transaction, be it ready or queued, in eviction order
+    eviction_index: BTreeSet<EvictionKey>,
+    /// Amount of operations absorbed so far, used to tell how far behind the
+    /// writer readers are
+    version: u64,
 }
 
 pub const DEFAULT_INITIAL_MEMPOOL_CAPACITY: usize = 10000;
@@ -75,11 +119,18 @@ impl Default for Mempool {
                 DEFAULT_INITIAL_MEMPOOL_CAPACITY,
                 <_>::default(),
             ),
+            queued: QueuedPoolType::default(),
+            queued_ids: HashMap::default(),
+            ready_counts: HashMap::default(),
+            next_nonces: HashMap::default(),
+            eviction_index: BTreeSet::default(),
+            version: 0,
         }
     }
 }
 
 impl Mempool {
+    /// Amount of ready transactions
     pub fn len(&self) -> usize {
         self.pool.len()
     }
@@ -87,24 +138,200 @@ impl Mempool {
     pub fn is_empty(&self) -> bool {
         self.pool.is_empty()
     }
+
+    /// Amount of transactions waiting for a nonce gap to be filled
+    pub fn queued_len(&self) -> usize {
+        self.queued_ids.len()
+    }
+
+    fn contains(&self, txn_id: &str) -> bool {
+        self.pool.contains_key(txn_id) || self.queued_record(txn_id).is_some()
+    }
+
+    fn queued_record(&self, txn_id: &str) -> Option<&TxnRecord> {
+        let (sender, nonce) = self.queued_ids.get(txn_id)?;
+
+        self.queued.get(sender)?.get(nonce)
+    }
+
+    /// Returns up to `count` pending records, ready or queued, in the order
+    /// they're evicted in
+    fn eviction_candidates(&self, count: usize) -> Vec<&TxnRecord> {
+        self.eviction_index
+            .iter()
+            .take(count)
+            .filter_map(|(_, _, txn_id)| {
+                self.pool.get(txn_id).or_else(|| self.queued_record(txn_id))
+            })
+            .collect()
+    }
+
+    /// Nonce the next ready transaction of `sender` is expected to have,
+    /// given the one its account expects next, if known
+    fn next_nonce_of(&self, sender: &Address, next_nonce: Option<TxNonce>) -> Option<TxNonce> {
+        match (self.next_nonces.get(sender), next_nonce) {
+            (Some(pending), Some(next_nonce)) => Some((*pending).max(next_nonce)),
+            (pending, next_nonce) => pending.copied().or(next_nonce),
+        }
+    }
+
+    /// Highest nonce a txn of `sender` may be queued with, at most
+    /// `MAX_QUEUED_NONCE_GAP` ahead of its next nonce
+    fn max_queued_nonce(&self, sender: &Address, next_nonce: TxNonce) -> TxNonce {
+        self.next_nonce_of(sender, Some(next_nonce))
+            .unwrap_or(next_nonce)
+            .saturating_add(MAX_QUEUED_NONCE_GAP)
+    }
+
+    /// Adds `record` as ready, or queues it if `next_nonce`, the nonce its
+    /// sender's account expects next, is known and the record's nonce is
+    /// ahead of its sender's next nonce. Records that become ready promote
+    /// the queued ones following them.
+    fn add(&mut self, record: &TxnRecord, next_nonce: Option<TxNonce>) {
+        // NOTE: records already in the pool are kept as they are
+        if self.contains(&record.txn_id) {
+            return;
+        }
+
+        let sender = record.txn.sender();
+        let nonce = record.txn.nonce;
+
+        let expected_nonce = self.next_nonce_of(&sender, next_nonce).unwrap_or(nonce);
+
+        self.eviction_index.insert(record.eviction_key());
+
+        if next_nonce.is_some() && nonce > expected_nonce {
+            let replaced = self
+                .queued
+                .entry(sender.clone())
+                .or_default()
+                .insert(nonce, record.clone());
+
+            // NOTE: a later txn with the same nonce takes the place of the
+            // earlier one
+            if let Some(replaced) = replaced {
+                self.queued_ids.remove(&replaced.txn_id);
+                self.eviction_index.remove(&replaced.eviction_key());
+            }
+
+            self.queued_ids
+                .insert(record.txn_id.clone(), (sender, nonce));
+
+            return;
+        }
+
+        self.insert_ready(&sender, record.clone());
+
+        let mut expected_nonce = expected_nonce.max(nonce.saturating_add(1));
+
+        if let Some(mut queue) = self.queued.remove(&sender) {
+            while let Some(promoted) = queue.remove(&expected_nonce) {
+                self.queued_ids.remove(&promoted.txn_id);
+                self.insert_ready(&sender, promoted);
+                expected_nonce = expected_nonce.saturating_add(1);
+            }
+
+            if !queue.is_empty() {
+                self.queued.insert(sender.clone(), queue);
+            }
+        }
+
+        self.next_nonces.insert(sender, expected_nonce);
+    }
+
+    fn insert_ready(&mut self, sender: &Address, record: TxnRecord) {
+        self.pool.insert(record.txn_id.clone(), record);
+        *self.ready_counts.entry(sender.clone()).or_default() += 1;
+    }
+
+    /// Removes a ready or queued record. Senders left without ready records
+    /// stop being tracked, their next nonce is taken from their account
+    /// again.
+    fn remove(&mut self, txn_id: &str) {
+        if let Some(record) = self.pool.remove(txn_id) {
+            let sender = record.txn.sender();
+
+            self.eviction_index.remove(&record.eviction_key());
+
+            let remaining = self.ready_counts.get_mut(&sender).map(|count| {
+                *count = count.saturating_sub(1);
+                *count
+            });
+
+            if remaining.unwrap_or_default() == 0 {
+                self.ready_counts.remove(&sender);
+                self.next_nonces.remove(&sender);
+            }
+
+            return;
+        }
+
+        if let Some((sender, nonce)) = self.queued_ids.remove(txn_id) {
+            if let Some(queue) = self.queued.get_mut(&sender) {
+                if let Some(record) = queue.remove(&nonce) {
+                    self.eviction_index.remove(&record.eviction_key());
+                }
+
+                if queue.is_empty() {
+                    self.queued.remove(&sender);
+                }
+            }
+        }
+    }
+}
+
+/// Outcome of inserting a transaction into the mempool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MempoolInsertOutcome {
+    Inserted,
+    /// A transaction with the same digest was already pending, the mempool
+    /// was left untouched
+    Duplicate,
+    /// The mempool was full and the transaction was the first one picked for
+    /// eviction
+    Evicted,
+    /// The transaction's nonce is ahead of its sender's next nonce, it was
+    /// queued until the transactions filling the gap arrive
+    Queued,
+    /// The transaction's nonce is more than `MAX_QUEUED_NONCE_GAP` ahead of
+    /// its sender's next nonce, i.e. over `max`. The mempool was left
+    /// untouched
+    NonceTooFarAhead {
+        max: TxNonce,
+    },
+}
+
+/// Decides which pending transactions are dropped once the mempool is full
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MempoolEvictionPolicy {
+    /// Evicts the transactions paying the lowest fees first, the oldest ones
+    /// among those paying the same fee
+    #[default]
+    FeeThenAge,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum MempoolOp {
+    /// Adds a transaction as ready, regardless of its nonce
     Add(TxnRecord),
+    /// Adds a transaction whose sender's account expects `next_nonce` next,
+    /// queueing it if earlier nonces are missing
+    AddAtNonce {
+        record: TxnRecord,
+        next_nonce: TxNonce,
+    },
     Remove(String),
 }
 
 impl Absorb<MempoolOp> for Mempool {
     fn absorb_first(&mut self, op: &mut MempoolOp, _: &Self) {
         match op {
-            MempoolOp::Add(record) => {
-                self.pool.insert(record.txn_id.clone(), record.clone());
-            },
-            MempoolOp::Remove(id) => {
-                self.pool.remove(id);
-            },
+            MempoolOp::Add(record) => self.add(record, None),
+            MempoolOp::AddAtNonce { record, next_nonce } => self.add(record, Some(*next_nonce)),
+            MempoolOp::Remove(id) => self.remove(id),
         }
+
+        self.version += 1;
     }
 
     fn sync_with(&mut self, first: &Self) {
@@ -138,17 +365,102 @@ impl FetchFiltered for ReadHandle<Mempool> {
     }
 }
 
+/// Version of the on-disk mempool snapshot format. Bump it whenever the
+/// layout of `MempoolSnapshot` or `TxnRecord` changes.
+pub const MEMPOOL_SNAPSHOT_VERSION: u32 = 1;
+
+/// On-disk representation of the mempool's pending transactions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MempoolSnapshot {
+    pub version: u32,
+    pub records: Vec<TxnRecord>,
+    /// Transactions queued behind a nonce gap. Missing from snapshots taken
+    /// before queueing was introduced.
+    #[serde(default)]
+    pub queued: Vec<TxnRecord>,
+}
+
+/// Used to peek at a snapshot's version before attempting to decode the rest
+/// of it
+#[derive(Deserialize)]
+struct MempoolSnapshotHeader {
+    version: u32,
+}
+
+/// File snapshots are written to before being renamed to `path`
+fn snapshot_tmp_path(path: &Path) -> PathBuf {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+
+    PathBuf::from(tmp_path)
+}
+
+/// Operational counters of a mempool, shared with its read handle factories
+#[derive(Debug, Default)]
+struct MempoolCounters {
+    inserts: AtomicU64,
+    duplicates: AtomicU64,
+    evictions: AtomicU64,
+    /// Amount of operations appended by the writer so far
+    appended: AtomicU64,
+}
+
+impl MempoolCounters {
+    fn snapshot(&self, mempool: Option<&Mempool>) -> MempoolMetrics {
+        let appended = self.appended.load(Ordering::Relaxed);
+
+        MempoolMetrics {
+            inserts: self.inserts.load(Ordering::Relaxed),
+            duplicates: self.duplicates.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            size: mempool.map(Mempool::len).unwrap_or_default(),
+            queued: mempool.map(Mempool::queued_len).unwrap_or_default(),
+            read_lag: appended.saturating_sub(mempool.map_or(0, |mempool| mempool.version)),
+        }
+    }
+}
+
+/// Snapshot of a mempool's operational metrics
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MempoolMetrics {
+    /// Transactions taken in so far, including the ones evicted since
+    pub inserts: u64,
+    /// Transactions turned away for already being pending
+    pub duplicates: u64,
+    /// Transactions evicted to keep the mempool within its maximum size
+    pub evictions: u64,
+    /// Transactions currently ready to be executed
+    pub size: usize,
+    /// Transactions currently queued behind a nonce gap
+    pub queued: usize,
+    /// Operations appended by the writer that readers can't see yet
+    pub read_lag: u64,
+}
+
 #[derive(Debug)]
 pub struct LeftRightMempool {
     pub read: ReadHandle<Mempool>,
     pub write: WriteHandle<Mempool, MempoolOp>,
+    max_size: Option<usize>,
+    eviction_policy: MempoolEvictionPolicy,
+    counters: Arc<MempoolCounters>,
+    /// Digests of the transactions evicted since `take_evicted` was last
+    /// called
+    evicted: Vec<TransactionDigest>,
 }
 
 impl Default for LeftRightMempool {
     fn default() -> Self {
         let (write, read) = left_right::new::<Mempool, MempoolOp>();
 
-        LeftRightMempool { read, write }
+        LeftRightMempool {
+            read,
+            write,
+            max_size: None,
+            eviction_policy: MempoolEvictionPolicy::default(),
+            counters: Arc::new(MempoolCounters::default()),
+            evicted: vec![],
+        }
     }
 }
 
@@ -177,20 +489,139 @@ impl LeftRightMempool {
     pub fn factory(&self) -> MempoolReadHandleFactory {
         let factory = self.read.factory();
 
-        MempoolReadHandleFactory { factory }
+        MempoolReadHandleFactory {
+            factory,
+            counters: self.counters.clone(),
+        }
+    }
+
+    /// Bounds the amount of pending transactions. Once full, transactions
+    /// are evicted according to the given policy. Transactions in excess are
+    /// evicted right away.
+    pub fn set_max_size(&mut self, max_size: Option<usize>, policy: MempoolEvictionPolicy) {
+        self.max_size = max_size;
+        self.eviction_policy = policy;
+        self.enforce_max_size();
+    }
+
+    pub fn max_size(&self) -> Option<usize> {
+        self.max_size
+    }
+
+    /// Returns how many transactions have been evicted so far
+    pub fn eviction_count(&self) -> u64 {
+        self.counters.evictions.load(Ordering::Relaxed)
+    }
+
+    /// Returns a snapshot of the mempool's operational metrics
+    pub fn metrics(&self) -> MempoolMetrics {
+        self.counters.snapshot(self.read.enter().as_deref())
+    }
+
+    /// Returns the digests of the transactions evicted since this was last
+    /// called, so whoever submitted them can be let know
+    pub fn take_evicted(&mut self) -> Vec<TransactionDigest> {
+        std::mem::take(&mut self.evicted)
     }
 
     /// Adds a new transaction, makes sure it is unique in db.
     /// Pushes to the ReadHandle.
     #[deprecated(note = "use Self::insert instead")]
     pub fn add_txn(&mut self, txn: &Txn, _status: TxnStatus) -> Result<()> {
-        self.insert(txn.to_owned())
+        self.insert(txn.to_owned()).map(|_| ())
     }
 
-    pub fn insert(&mut self, txn: Txn) -> Result<()> {
+    /// Adds a new transaction to the mempool as ready, unless one with the
+    /// same digest is already pending. Use `insert_at_nonce` when the nonce of
+    /// the sender's account is known.
+    pub fn insert(&mut self, txn: Txn) -> Result<MempoolInsertOutcome> {
         let txn_record = TxnRecord::new(txn);
-        self.write.append(MempoolOp::Add(txn_record)).publish();
-        Ok(())
+
+        self.insert_op(txn_record.txn_id.clone(), MempoolOp::Add(txn_record))
+    }
+
+    /// Adds a new transaction sent from an account expecting `next_nonce`
+    /// next, i.e. the account's nonce + 1. Transactions ahead of the sender's
+    /// next nonce are queued, and promoted to ready in nonce order once the
+    /// transactions filling the gap arrive. Those more than
+    /// `MAX_QUEUED_NONCE_GAP` ahead are turned away.
+    pub fn insert_at_nonce(
+        &mut self,
+        txn: Txn,
+        next_nonce: TxNonce,
+    ) -> Result<MempoolInsertOutcome> {
+        let max = self
+            .read
+            .enter()
+            .map(|guard| guard.max_queued_nonce(&txn.sender(), next_nonce))
+            .unwrap_or_else(|| next_nonce.saturating_add(MAX_QUEUED_NONCE_GAP));
+
+        if txn.nonce > max {
+            return Ok(MempoolInsertOutcome::NonceTooFarAhead { max });
+        }
+
+        let txn_record = TxnRecord::new(txn);
+
+        self.insert_op(
+            txn_record.txn_id.clone(),
+            MempoolOp::AddAtNonce {
+                record: txn_record,
+                next_nonce,
+            },
+        )
+    }
+
+    fn insert_op(&mut self, txn_id: TxHashString, op: MempoolOp) -> Result<MempoolInsertOutcome> {
+        if self.contains(&txn_id) {
+            self.counters.duplicates.fetch_add(1, Ordering::Relaxed);
+            return Ok(MempoolInsertOutcome::Duplicate);
+        }
+
+        self.counters.inserts.fetch_add(1, Ordering::Relaxed);
+        self.append(op);
+        self.publish();
+        self.enforce_max_size();
+
+        if self.is_queued(&txn_id) {
+            return Ok(MempoolInsertOutcome::Queued);
+        }
+
+        if !self.contains(&txn_id) {
+            return Ok(MempoolInsertOutcome::Evicted);
+        }
+
+        Ok(MempoolInsertOutcome::Inserted)
+    }
+
+    /// Returns true if a transaction with the given digest is pending, be it
+    /// ready or queued
+    pub fn contains(&self, txn_id: &str) -> bool {
+        self.read
+            .enter()
+            .map(|guard| guard.contains(txn_id))
+            .unwrap_or(false)
+    }
+
+    /// Returns true if a transaction with the given digest is queued behind
+    /// a nonce gap
+    pub fn is_queued(&self, txn_id: &str) -> bool {
+        self.read
+            .enter()
+            .map(|guard| guard.queued_record(txn_id).is_some())
+            .unwrap_or(false)
+    }
+
+    /// Amount of transactions ready to be executed
+    pub fn ready_count(&self) -> usize {
+        self.read.enter().map(|guard| guard.len()).unwrap_or(0)
+    }
+
+    /// Amount of transactions queued behind a nonce gap
+    pub fn queued_count(&self) -> usize {
+        self.read
+            .enter()
+            .map(|guard| guard.queued_len())
+            .unwrap_or(0)
     }
 
     /// Retrieves a single transaction identified by id, makes sure it exists in
@@ -245,20 +676,29 @@ impl LeftRightMempool {
     }
 
     pub fn extend(&mut self, txn_batch: HashSet<Txn>) -> Result<()> {
-        txn_batch.into_iter().for_each(|t| {
-            self.write.append(MempoolOp::Add(TxnRecord::new(t)));
-        });
-
-        self.publish();
-        Ok(())
+        self.extend_with_records(txn_batch.into_iter().map(TxnRecord::new).collect())
     }
 
     pub fn extend_with_records(&mut self, record_batch: HashSet<TxnRecord>) -> Result<()> {
-        record_batch.into_iter().for_each(|t| {
-            self.write.append(MempoolOp::Add(t));
+        // NOTE: records are only published once the whole batch is appended,
+        // so txns seen earlier in the batch aren't visible to `contains` yet
+        let mut seen = HashSet::with_capacity(record_batch.len());
+
+        record_batch.into_iter().for_each(|record| {
+            let is_duplicate = self.contains(&record.txn_id) || !seen.insert(record.txn_id.clone());
+
+            let counter = if is_duplicate {
+                &self.counters.duplicates
+            } else {
+                &self.counters.inserts
+            };
+            counter.fetch_add(1, Ordering::Relaxed);
+
+            self.append(MempoolOp::Add(record));
         });
 
         self.publish();
+        self.enforce_max_size();
         Ok(())
     }
 
@@ -277,12 +717,44 @@ impl LeftRightMempool {
     }
 
     pub fn remove(&mut self, txn_hash: &TransactionDigest) -> Result<()> {
-        self.write
-            .append(MempoolOp::Remove(txn_hash.to_string()))
-            .publish();
+        self.append(MempoolOp::Remove(txn_hash.to_string()));
+        self.publish();
         Ok(())
     }
 
+    /// Removes every transaction, ready or queued, added to the mempool at
+    /// least `ttl` before `now`, both Unix timestamps in seconds. Returns the
+    /// records of the removed transactions.
+    pub fn remove_expired(&mut self, ttl: Duration, now: TxTimestamp) -> Vec<TxnRecord> {
+        let ttl = TxTimestamp::try_from(ttl.as_secs()).unwrap_or(TxTimestamp::MAX);
+
+        let expired = self
+            .read
+            .enter()
+            .map(|guard| {
+                guard
+                    .pool
+                    .values()
+                    .chain(guard.queued.values().flat_map(BTreeMap::values))
+                    .filter(|record| record.added_timestamp.saturating_add(ttl) <= now)
+                    .cloned()
+                    .collect::<Vec<TxnRecord>>()
+            })
+            .unwrap_or_default();
+
+        if expired.is_empty() {
+            return expired;
+        }
+
+        expired.iter().for_each(|record| {
+            self.append(MempoolOp::Remove(record.txn_id.clone()));
+        });
+
+        self.publish();
+
+        expired
+    }
+
     /// Removes a batch of transactions, makes sure that each is unique in db.
     /// Pushes to ReadHandle after processing of the entire batch.
     #[deprecated]
@@ -292,7 +764,7 @@ impl LeftRightMempool {
         _txns_status: TxnStatus,
     ) -> Result<()> {
         txn_batch.iter().for_each(|t| {
-            self.write.append(MempoolOp::Remove(t.digest().to_string()));
+            self.append(MempoolOp::Remove(t.digest().to_string()));
         });
 
         self.publish();
@@ -302,7 +774,7 @@ impl LeftRightMempool {
 
     pub fn remove_txns(&mut self, txn_batch: &HashSet<TxHashString>) -> Result<()> {
         txn_batch.iter().for_each(|t| {
-            self.write.append(MempoolOp::Remove(t.to_string()));
+            self.append(MempoolOp::Remove(t.to_string()));
         });
 
         self.publish();
@@ -321,21 +793,170 @@ impl LeftRightMempool {
         }
     }
 
-    /// Retrieves actual size of the mempooldb.
+    /// Retrieves actual size of the mempooldb, not counting queued
+    /// transactions.
     pub fn size(&self) -> usize {
         self.pool().len()
     }
 
+    /// Writes every pending transaction to `path` so it can be restored after
+    /// a restart.
+    ///
+    /// The snapshot is written next to `path` first and then renamed into
+    /// place, so a crash mid-write leaves the previous snapshot intact.
+    pub fn save_snapshot<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let snapshot = MempoolSnapshot {
+            version: MEMPOOL_SNAPSHOT_VERSION,
+            records: self.pool().values().cloned().collect(),
+            queued: self
+                .read
+                .enter()
+                .map(|guard| {
+                    guard
+                        .queued
+                        .values()
+                        .flat_map(BTreeMap::values)
+                        .cloned()
+                        .collect()
+                })
+                .unwrap_or_default(),
+        };
+
+        let bytes =
+            serde_json::to_vec(&snapshot).map_err(|err| MempoolError::Snapshot(err.to_string()))?;
+
+        let path = path.as_ref();
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|err| MempoolError::Snapshot(err.to_string()))?;
+        }
+
+        let tmp_path = snapshot_tmp_path(path);
+
+        let mut tmp_file =
+            fs::File::create(&tmp_path).map_err(|err| MempoolError::Snapshot(err.to_string()))?;
+
+        tmp_file
+            .write_all(&bytes)
+            .and_then(|_| tmp_file.sync_all())
+            .and_then(|_| fs::rename(&tmp_path, path))
+            .map_err(|err| {
+                let _ = fs::remove_file(&tmp_path);
+                MempoolError::Snapshot(err.to_string())
+            })
+    }
+
+    /// Restores a mempool from a snapshot written by `save_snapshot`.
+    ///
+    /// A missing snapshot file or one written with an unrecognized format
+    /// version yields an empty mempool rather than an error.
+    pub fn load_snapshot<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+
+        if !path.exists() {
+            return Ok(Self::new());
+        }
+
+        let bytes = fs::read(path).map_err(|err| MempoolError::Snapshot(err.to_string()))?;
+
+        let header: MempoolSnapshotHeader = serde_json::from_slice(&bytes)
+            .map_err(|err| MempoolError::Snapshot(err.to_string()))?;
+
+        if header.version != MEMPOOL_SNAPSHOT_VERSION {
+            warn!(
+                "ignoring mempool snapshot at {} with unsupported version {}, expected {}",
+                path.display(),
+                header.version,
+                MEMPOOL_SNAPSHOT_VERSION
+            );
+
+            return Ok(Self::new());
+        }
+
+        let snapshot: MempoolSnapshot = serde_json::from_slice(&bytes)
+            .map_err(|err| MempoolError::Snapshot(err.to_string()))?;
+
+        let mut mempool = Self::new();
+        mempool.extend_with_records(snapshot.records.into_iter().collect())?;
+
+        // NOTE: account nonces aren't known here, queued records are requeued
+        // behind the ready ones of their senders
+        snapshot.queued.into_iter().for_each(|record| {
+            mempool.append(MempoolOp::AddAtNonce {
+                record,
+                next_nonce: 0,
+            });
+        });
+
+        mempool.publish();
+        mempool.enforce_max_size();
+
+        Ok(mempool)
+    }
+
+    /// Queues up an operation, readers only see it once published
+    fn append(&mut self, op: MempoolOp) {
+        self.write.append(op);
+        self.counters.appended.fetch_add(1, Ordering::Relaxed);
+    }
+
     /// Pushes changes to Reader.
     fn publish(&mut self) {
         self.write.publish();
     }
+
+    /// Evicts as many transactions as needed to fit within `max_size`
+    fn enforce_max_size(&mut self) {
+        let max_size = match self.max_size {
+            Some(max_size) => max_size,
+            None => return,
+        };
+
+        let policy = self.eviction_policy;
+
+        let evicted: Vec<(TxHashString, TransactionDigest)> = self
+            .read
+            .enter()
+            .map(|guard| {
+                let excess = (guard.len() + guard.queued_len()).saturating_sub(max_size);
+                if excess == 0 {
+                    return vec![];
+                }
+
+                // NOTE: the index is kept in eviction order, so only the
+                // records being evicted are visited
+                let records = match policy {
+                    MempoolEvictionPolicy::FeeThenAge => guard.eviction_candidates(excess),
+                };
+
+                records
+                    .into_iter()
+                    .map(|record| (record.txn_id.clone(), record.txn.digest()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        if evicted.is_empty() {
+            return;
+        }
+
+        let eviction_count = evicted.len() as u64;
+
+        evicted.into_iter().for_each(|(txn_id, digest)| {
+            self.append(MempoolOp::Remove(txn_id));
+            self.evicted.push(digest);
+        });
+
+        self.publish();
+        self.counters
+            .evictions
+            .fetch_add(eviction_count, Ordering::Relaxed);
+    }
 }
 
 impl From<PoolType> for LeftRightMempool {
     fn from(pool: PoolType) -> Self {
-        let (write, read) = left_right::new::<Mempool, MempoolOp>();
-        let mut mempool_db = Self { read, write };
+        let mut mempool_db = Self::new();
 
         let records = pool.values().cloned().collect::<HashSet<TxnRecord>>();
 
@@ -347,16 +968,65 @@ impl From<PoolType> for LeftRightMempool {
 
 impl Clone for LeftRightMempool {
     fn clone(&self) -> Self {
-        Self::from(self.pool())
+        let mut mempool_db = Self::from(self.pool());
+        mempool_db.max_size = self.max_size;
+        mempool_db.eviction_policy = self.eviction_policy;
+        mempool_db
+            .counters
+            .evictions
+            .store(self.eviction_count(), Ordering::Relaxed);
+
+        mempool_db
     }
 }
 
 #[derive(Clone, Debug)]
 pub struct MempoolReadHandleFactory {
     factory: ReadHandleFactory<Mempool>,
+    counters: Arc<MempoolCounters>,
 }
 
 impl MempoolReadHandleFactory {
+    /// Returns the amount of pending transactions ready to be executed
+    pub fn size(&self) -> usize {
+        self.factory
+            .handle()
+            .enter()
+            .map(|guard| guard.len())
+            .unwrap_or_default()
+    }
+
+    /// Returns how many transactions the mempool has evicted so far
+    pub fn eviction_count(&self) -> u64 {
+        self.counters.evictions.load(Ordering::Relaxed)
+    }
+
+    /// Returns a snapshot of the mempool's operational metrics, as seen by
+    /// readers
+    pub fn metrics(&self) -> MempoolMetrics {
+        self.counters
+            .snapshot(self.factory.handle().enter().as_deref())
+    }
+
+    /// Returns the amount of transactions queued behind a nonce gap
+    pub fn queued_count(&self) -> usize {
+        self.factory
+            .handle()
+            .enter()
+            .map(|guard| guard.queued_len())
+            .unwrap_or_default()
+    }
+
+    /// Returns the nonce following the highest one among the ready
+    /// transactions of `sender`, if it has any. Queued transactions are left
+    /// out, the nonces missing ahead of them have yet to be used.
+    pub fn next_nonce_of(&self, sender: &Address) -> Option<TxNonce> {
+        self.factory
+            .handle()
+            .enter()
+            .and_then(|guard| guard.next_nonce_of(sender, None))
+    }
+
     pub fn handle(&self) -> PoolType {
         self.factory
             .handle()
@@ -366,6 +1036,24 @@ impl MempoolReadHandleFactory {
             .pool
     }
 
+    /// Returns the pending record of the transaction with the given digest
+    pub fn get(&self, txn_hash: &TransactionDigest) -> Option<TxnRecord> {
+        self.factory
+            .handle()
+            .enter()
+            .and_then(|guard| guard.pool.get(&txn_hash.to_string()).cloned())
+    }
+
+    /// Returns true if a transaction with the given digest is pending, be it
+    /// ready or queued
+    pub fn contains(&self, txn_hash: &TransactionDigest) -> bool {
+        self.factory
+            .handle()
+            .enter()
+            .map(|guard| guard.contains(&txn_hash.to_string()))
+            .unwrap_or(false)
+    }
+
     /// Returns a hash map of all the key value pairs within the mempool
     pub fn entries(&self) -> HashMap<TxHashString, TxnRecord> {
         self.handle()
@@ -375,6 +1063,23 @@ impl MempoolReadHandleFactory {
             .collect()
     }
 
+    /// Returns up to `limit` pending transaction records in pool order, or all
+    /// of them when no limit is given. Only the returned records are cloned.
+    pub fn records(&self, limit: Option<usize>) -> Vec<TxnRecord> {
+        self.factory
+            .handle()
+            .enter()
+            .map(|guard| {
+                guard
+                    .pool
+                    .values()
+                    .take(limit.unwrap_or(usize::MAX))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     /// Returns a vector of all transactions within the mempool
     pub fn values(&self) -> Vec<Txn> {
         self.handle()
@@ -0,0 +1,104 @@
+use tokio::sync::mpsc::{self, error::TrySendError, Permit, Receiver, Sender};
+use vrrb_core::txn::Txn;
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum ValidationQueueError {
+    #[error("validation queue is full")]
+    Busy,
+
+    #[error("validation queue is closed")]
+    Closed,
+}
+
+/// Bounded queue of transactions taken in by the node and waiting to be
+/// validated. Submitters are turned away with `ValidationQueueError::Busy`
+/// once it fills up, rather than having their transactions dropped further
+/// down the pipeline.
+#[derive(Debug, Clone)]
+pub struct ValidationQueue {
+    sender: Sender<Txn>,
+    max_depth: usize,
+}
+
+/// A reserved slot within the validation queue. Sending through it can't
+/// fail for lack of room.
+#[derive(Debug)]
+pub struct ValidationPermit<'a> {
+    permit: Permit<'a, Txn>,
+}
+
+impl ValidationPermit<'_> {
+    pub fn send(self, txn: Txn) {
+        self.permit.send(txn);
+    }
+}
+
+impl ValidationQueue {
+    /// Creates a queue holding up to `max_depth` transactions, along with the
+    /// receiving end the validator pulls them from
+    pub fn new(max_depth: usize) -> (Self, Receiver<Txn>) {
+        let max_depth = max_depth.max(1);
+        let (sender, receiver) = mpsc::channel(max_depth);
+
+        (Self { sender, max_depth }, receiver)
+    }
+
+    /// Reserves room for a transaction without waiting
+    pub fn try_reserve(&self) -> Result<ValidationPermit<'_>, ValidationQueueError> {
+        self.sender
+            .try_reserve()
+            .map(|permit| ValidationPermit { permit })
+            .map_err(|err| match err {
+                TrySendError::Full(_) => ValidationQueueError::Busy,
+                TrySendError::Closed(_) => ValidationQueueError::Closed,
+            })
+    }
+
+    /// Queues a transaction for validation without waiting
+    pub fn try_send(&self, txn: Txn) -> Result<(), ValidationQueueError> {
+        self.try_reserve().map(|permit| permit.send(txn))
+    }
+
+    /// Amount of transactions currently waiting to be validated
+    pub fn depth(&self) -> usize {
+        self.max_depth.saturating_sub(self.sender.capacity())
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use vrrb_core::txn::null_txn;
+
+    use super::*;
+
+    #[test]
+    fn submitters_are_turned_away_once_the_queue_is_full() {
+        let (queue, mut receiver) = ValidationQueue::new(2);
+
+        assert_eq!(queue.try_send(null_txn()), Ok(()));
+        assert_eq!(queue.try_send(null_txn()), Ok(()));
+        assert_eq!(queue.depth(), 2);
+
+        assert_eq!(queue.try_send(null_txn()), Err(ValidationQueueError::Busy));
+
+        receiver.try_recv().unwrap();
+
+        assert_eq!(queue.depth(), 1);
+        assert_eq!(queue.try_send(null_txn()), Ok(()));
+    }
+
+    #[test]
+    fn closed_queues_are_reported() {
+        let (queue, receiver) = ValidationQueue::new(2);
+        drop(receiver);
+
+        assert_eq!(
+            queue.try_send(null_txn()),
+            Err(ValidationQueueError::Closed)
+        );
+    }
+}
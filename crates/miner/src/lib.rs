@@ -671,8 +671,7 @@ pub(crate) mod test_helpers {
     }
 
     pub(crate) fn create_keypair() -> (SecretKey, PublicKey) {
-        let kp = KeyPair::random();
-        kp.miner_kp
+        KeyPair::random().txn_keypair().clone().into()
     }
 
     pub(crate) fn create_address(pubkey: &PublicKey) -> Address {
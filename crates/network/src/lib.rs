@@ -1,7 +1,10 @@
 pub mod components;
 pub mod config_utils;
+pub mod limiter;
 pub mod network;
+pub mod ordering;
 pub mod packet;
+pub mod peer_keys;
 pub mod protocol;
 pub mod types;
 
@@ -0,0 +1,436 @@
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use telemetry::warn;
+
+use crate::types::config::{BroadCastError, BroadcastEngineConfig};
+
+/// How long a peer has to stay within its rate for the messages it had
+/// dropped before to be forgotten, so occasional bursts don't add up to a ban
+const RATE_VIOLATION_WINDOW: Duration = Duration::from_secs(60);
+
+/// How long the budget of a peer that stopped sending messages is kept
+const IDLE_BUDGET_TTL: Duration = Duration::from_secs(600);
+
+/// How often budgets are checked for having gone idle
+const IDLE_BUDGET_PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// What to do with a message received from a peer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InboundVerdict {
+    Accepted,
+    /// The peer exceeded its message rate, the message must be discarded
+    Dropped,
+    /// The peer kept exceeding its message rate and is banned, its connection
+    /// should be closed
+    Banned,
+}
+
+/// Counters describing how an `InboundLimiter` treated incoming traffic
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InboundStats {
+    pub active_connections: usize,
+    pub rejected_connections: u64,
+    pub dropped_messages: u64,
+    pub banned_peers: u64,
+}
+
+/// Per-peer token bucket, refilled at `peer_message_rate` tokens per second up
+/// to `peer_message_burst` tokens
+#[derive(Debug)]
+struct PeerBudget {
+    tokens: f64,
+    last_refill: Instant,
+    violations: u32,
+    last_violation: Option<Instant>,
+    banned_until: Option<Instant>,
+}
+
+impl PeerBudget {
+    fn new(burst: u32, now: Instant) -> Self {
+        Self {
+            tokens: burst as f64,
+            last_refill: now,
+            violations: 0,
+            last_violation: None,
+            banned_until: None,
+        }
+    }
+
+    fn is_banned(&self, now: Instant) -> bool {
+        matches!(self.banned_until, Some(until) if until > now)
+    }
+
+    /// Counts a dropped message, forgetting the earlier ones if the peer
+    /// stayed within its rate for a whole `RATE_VIOLATION_WINDOW` since
+    fn record_violation(&mut self, now: Instant) {
+        let window_elapsed = self.last_violation.map_or(false, |last_violation| {
+            now.saturating_duration_since(last_violation) >= RATE_VIOLATION_WINDOW
+        });
+
+        if window_elapsed {
+            self.violations = 0;
+        }
+
+        self.violations += 1;
+        self.last_violation = Some(now);
+    }
+
+    fn refill(&mut self, rate: u32, burst: u32, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * rate as f64).min(burst as f64);
+        self.last_refill = now;
+    }
+}
+
+/// Budgets of the peers that sent messages lately, by IP
+#[derive(Debug)]
+struct PeerBudgets {
+    budgets: HashMap<IpAddr, PeerBudget>,
+    last_pruned: Instant,
+}
+
+impl PeerBudgets {
+    /// Forgets the budgets of the peers that went quiet, unless they're
+    /// banned. Runs at most once per `IDLE_BUDGET_PRUNE_INTERVAL`.
+    fn prune_idle(&mut self, now: Instant) {
+        if now.saturating_duration_since(self.last_pruned) < IDLE_BUDGET_PRUNE_INTERVAL {
+            return;
+        }
+
+        self.budgets.retain(|_, budget| {
+            budget.is_banned(now)
+                || now.saturating_duration_since(budget.last_refill) < IDLE_BUDGET_TTL
+        });
+
+        self.last_pruned = now;
+    }
+}
+
+/// InboundLimiter guards a `BroadcastEngine` against peers flooding it. It
+/// caps the number of inbound connections handled at once and rate limits
+/// the messages each peer sends. Peers that keep exceeding their rate can be
+/// banned for a while, see `BroadcastEngineConfig::max_rate_violations`.
+///
+/// Peers are told apart by IP, so reconnecting from another port neither
+/// refills a peer's budget nor lifts its ban.
+///
+/// Like `PeerReconnector`, clones share their state with the engine.
+#[derive(Debug, Clone)]
+pub struct InboundLimiter {
+    config: BroadcastEngineConfig,
+    peers: Arc<Mutex<PeerBudgets>>,
+    active_connections: Arc<AtomicUsize>,
+    rejected_connections: Arc<AtomicU64>,
+    dropped_messages: Arc<AtomicU64>,
+    banned_peers: Arc<AtomicU64>,
+}
+
+/// Slot taken by an inbound connection, released once dropped
+#[derive(Debug)]
+pub struct InboundConnection {
+    active_connections: Arc<AtomicUsize>,
+}
+
+impl Drop for InboundConnection {
+    fn drop(&mut self) {
+        self.active_connections.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl InboundLimiter {
+    pub fn new(config: BroadcastEngineConfig) -> Self {
+        Self {
+            config,
+            peers: Arc::new(Mutex::new(PeerBudgets {
+                budgets: HashMap::new(),
+                last_pruned: Instant::now(),
+            })),
+            active_connections: Arc::new(AtomicUsize::new(0)),
+            rejected_connections: Arc::new(AtomicU64::new(0)),
+            dropped_messages: Arc::new(AtomicU64::new(0)),
+            banned_peers: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Reserves a slot for an inbound connection from `addr`. Connections
+    /// from banned peers and connections past `max_inbound_connections` are
+    /// refused.
+    pub fn admit_connection(&self, addr: SocketAddr) -> Result<InboundConnection, BroadCastError> {
+        if self.is_banned(addr) {
+            self.rejected_connections.fetch_add(1, Ordering::Relaxed);
+            return Err(BroadCastError::PeerBanned(addr));
+        }
+
+        let max = self.config.max_inbound_connections;
+
+        let admitted = self
+            .active_connections
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |active| {
+                (active < max).then_some(active + 1)
+            })
+            .is_ok();
+
+        if !admitted {
+            self.rejected_connections.fetch_add(1, Ordering::Relaxed);
+            return Err(BroadCastError::TooManyConnections(addr));
+        }
+
+        Ok(InboundConnection {
+            active_connections: self.active_connections.clone(),
+        })
+    }
+
+    /// Charges a message received from `addr` against the peer's budget
+    pub fn check_message(&self, addr: SocketAddr) -> InboundVerdict {
+        self.check_message_at(addr, Instant::now())
+    }
+
+    fn check_message_at(&self, addr: SocketAddr, now: Instant) -> InboundVerdict {
+        let mut peers = match self.peers.lock() {
+            Ok(peers) => peers,
+            Err(_) => {
+                telemetry::error!("Error acquiring lock on inbound peer budgets");
+                return InboundVerdict::Accepted;
+            },
+        };
+
+        let rate = self.config.peer_message_rate;
+        let burst = self.config.peer_message_burst;
+
+        peers.prune_idle(now);
+
+        let budget = peers
+            .budgets
+            .entry(addr.ip())
+            .or_insert_with(|| PeerBudget::new(burst, now));
+
+        match budget.banned_until {
+            Some(until) if until > now => {
+                self.dropped_messages.fetch_add(1, Ordering::Relaxed);
+                return InboundVerdict::Banned;
+            },
+            Some(_) => *budget = PeerBudget::new(burst, now),
+            None => {},
+        }
+
+        budget.refill(rate, burst, now);
+
+        if budget.tokens >= 1.0 {
+            budget.tokens -= 1.0;
+            return InboundVerdict::Accepted;
+        }
+
+        self.dropped_messages.fetch_add(1, Ordering::Relaxed);
+        budget.record_violation(now);
+
+        match self.config.max_rate_violations {
+            Some(max_violations) if budget.violations >= max_violations => {
+                warn!(
+                    "Banning peer {addr} for {:?} after {} messages over its rate",
+                    self.config.peer_ban_duration, budget.violations
+                );
+
+                budget.banned_until = Some(now + self.config.peer_ban_duration);
+                self.banned_peers.fetch_add(1, Ordering::Relaxed);
+
+                InboundVerdict::Banned
+            },
+            _ => InboundVerdict::Dropped,
+        }
+    }
+
+    /// Returns true if the peer at `addr`, on any port, is currently banned
+    pub fn is_banned(&self, addr: SocketAddr) -> bool {
+        let now = Instant::now();
+
+        self.peers
+            .lock()
+            .ok()
+            .and_then(|peers| {
+                peers
+                    .budgets
+                    .get(&addr.ip())
+                    .map(|budget| budget.is_banned(now))
+            })
+            .unwrap_or(false)
+    }
+
+    pub fn stats(&self) -> InboundStats {
+        InboundStats {
+            active_connections: self.active_connections.load(Ordering::SeqCst),
+            rejected_connections: self.rejected_connections.load(Ordering::Relaxed),
+            dropped_messages: self.dropped_messages.load(Ordering::Relaxed),
+            banned_peers: self.banned_peers.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::{Ipv4Addr, SocketAddr},
+        time::{Duration, Instant},
+    };
+
+    use super::{InboundLimiter, InboundVerdict, IDLE_BUDGET_TTL, RATE_VIOLATION_WINDOW};
+    use crate::types::config::{BroadCastError, BroadcastEngineConfig};
+
+    fn peer(host: u8) -> SocketAddr {
+        SocketAddr::from((Ipv4Addr::new(10, 0, 0, host), 4000))
+    }
+
+    fn limiter(max_rate_violations: Option<u32>) -> InboundLimiter {
+        InboundLimiter::new(BroadcastEngineConfig {
+            max_inbound_connections: 2,
+            peer_message_rate: 10,
+            peer_message_burst: 10,
+            max_rate_violations,
+            peer_ban_duration: Duration::from_secs(60),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn peers_over_their_rate_are_dropped_without_affecting_others() {
+        let limiter = limiter(None);
+        let flooder = peer(1);
+        let well_behaved = peer(2);
+        let start = Instant::now();
+
+        let mut flooder_accepted = 0;
+        let mut well_behaved_accepted = 0;
+
+        // NOTE: over one second, the flooder sends 100 messages while the
+        // well behaved peer sends 5, well within a rate of 10 per second
+        for i in 0..100 {
+            let now = start + Duration::from_millis(i * 10);
+
+            if limiter.check_message_at(flooder, now) == InboundVerdict::Accepted {
+                flooder_accepted += 1;
+            }
+
+            if i % 20 == 0
+                && limiter.check_message_at(well_behaved, now) == InboundVerdict::Accepted
+            {
+                well_behaved_accepted += 1;
+            }
+        }
+
+        assert!(flooder_accepted < 25, "{flooder_accepted} accepted");
+        assert_eq!(well_behaved_accepted, 5);
+        assert_eq!(limiter.stats().dropped_messages, 100 - flooder_accepted);
+        assert_eq!(limiter.stats().banned_peers, 0);
+    }
+
+    #[test]
+    fn persistent_offenders_are_banned() {
+        let limiter = limiter(Some(3));
+        let flooder = peer(1);
+        let now = Instant::now();
+
+        let verdicts = (0..13)
+            .map(|_| limiter.check_message_at(flooder, now))
+            .collect::<Vec<InboundVerdict>>();
+
+        assert!(verdicts[..10]
+            .iter()
+            .all(|v| *v == InboundVerdict::Accepted));
+        assert_eq!(
+            verdicts[10..],
+            [
+                InboundVerdict::Dropped,
+                InboundVerdict::Dropped,
+                InboundVerdict::Banned
+            ]
+        );
+
+        // NOTE: reconnecting from another port doesn't lift the ban
+        let other_port = SocketAddr::new(flooder.ip(), flooder.port() + 1);
+
+        assert!(limiter.is_banned(flooder));
+        assert!(limiter.is_banned(other_port));
+        assert_eq!(limiter.stats().banned_peers, 1);
+        assert!(matches!(
+            limiter.admit_connection(other_port),
+            Err(BroadCastError::PeerBanned(_))
+        ));
+
+        // NOTE: bans expire, after which the peer starts with a full budget
+        let later = now + Duration::from_secs(61);
+        assert_eq!(
+            limiter.check_message_at(flooder, later),
+            InboundVerdict::Accepted
+        );
+    }
+
+    #[test]
+    fn inbound_connections_are_capped() {
+        let limiter = limiter(None);
+
+        let first = limiter.admit_connection(peer(1)).unwrap();
+        let _second = limiter.admit_connection(peer(2)).unwrap();
+
+        assert!(matches!(
+            limiter.admit_connection(peer(3)),
+            Err(BroadCastError::TooManyConnections(_))
+        ));
+
+        drop(first);
+
+        assert!(limiter.admit_connection(peer(3)).is_ok());
+        assert_eq!(limiter.stats().rejected_connections, 1);
+    }
+
+    #[test]
+    fn occasional_bursts_are_forgiven() {
+        let limiter = limiter(Some(3));
+        let bursty = peer(1);
+        let start = Instant::now();
+
+        // NOTE: every burst goes 2 messages over the budget, and the peer
+        // stays quiet long enough in between for its violations to be
+        // forgotten
+        for burst in 0..5 {
+            let now = start + RATE_VIOLATION_WINDOW * burst;
+
+            for _ in 0..12 {
+                assert_ne!(
+                    limiter.check_message_at(bursty, now),
+                    InboundVerdict::Banned
+                );
+            }
+        }
+
+        assert_eq!(limiter.stats().dropped_messages, 10);
+        assert_eq!(limiter.stats().banned_peers, 0);
+    }
+
+    #[test]
+    fn idle_peers_are_forgotten() {
+        let limiter = limiter(None);
+        let idle = peer(1);
+        let active = peer(2);
+        let start = Instant::now();
+
+        limiter.check_message_at(idle, start);
+        limiter.check_message_at(active, start);
+        limiter.check_message_at(active, start + IDLE_BUDGET_TTL / 2);
+
+        limiter.check_message_at(active, start + IDLE_BUDGET_TTL);
+
+        let peers = limiter.peers.lock().unwrap();
+
+        assert!(!peers.budgets.contains_key(&idle.ip()));
+        assert!(peers.budgets.contains_key(&active.ip()));
+    }
+}
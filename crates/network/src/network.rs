@@ -2,14 +2,19 @@ use std::{
     borrow::BorrowMut,
     collections::{HashMap, HashSet},
     net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+        Mutex,
+    },
     thread,
-    time::Duration,
+    time::{Duration, SystemTime},
 };
 
 use bytes::Bytes;
 use crossbeam_channel::{unbounded, Sender};
-use futures::{stream::FuturesUnordered, StreamExt};
+use futures::{future::join_all, stream::FuturesUnordered, StreamExt};
+use primitives::{PeerId, PeerVersion, ProtocolVersion, SecretKey, PROTOCOL_VERSION};
 use qp2p::{
     Config,
     Connection,
@@ -19,13 +24,15 @@ use qp2p::{
     IncomingConnections,
     RetryConfig,
 };
-use raptorq::Decoder;
 use serde::{Deserialize, Serialize};
-use telemetry::{info, tracing};
+use telemetry::{debug, info, tracing, warn};
 use tokio::net::UdpSocket;
+use vrrb_core::event_router::PeerData;
 
 use crate::{
-    message::Message,
+    limiter::{InboundLimiter, InboundStats},
+    message::{Message, MessageBody},
+    ordering::MessageSequencer,
     packet::{
         generate_batch_id,
         packet_forwarder,
@@ -33,11 +40,13 @@ use crate::{
         recv_mmsg,
         split_into_packets,
         RaptorBroadCastedData,
+        ReassemblyBuffer,
         BATCH_ID_SIZE,
         MTU_SIZE,
         NUM_RCVMMSGS,
     },
-    types::config::{BroadCastError, BroadCastResult},
+    peer_keys::PeerKeys,
+    types::config::{BroadCastError, BroadCastResult, BroadcastEngineConfig},
 };
 
 type BroadCastStatus = Result<BroadCastResult, BroadCastError>;
@@ -58,6 +67,559 @@ pub struct BroadcastEngine {
     pub endpoint: (Endpoint, IncomingConnections),
     pub raptor_udp_port: u16,
     pub raptor_num_packet_blast: usize,
+    /// IP the RaptorQ UDP sockets bind to
+    pub raptor_bind_ip: IpAddr,
+    pub config: BroadcastEngineConfig,
+    known_peers: Arc<Mutex<HashMap<SocketAddr, PeerId>>>,
+    /// Protocol version negotiated with each peer during the handshake
+    peer_versions: Arc<Mutex<HashMap<SocketAddr, ProtocolVersion>>>,
+    dropped_peers: Arc<Mutex<HashSet<SocketAddr>>>,
+    /// When each peer was last heard from or connected to
+    last_seen: Arc<Mutex<HashMap<SocketAddr, SystemTime>>>,
+    in_flight_reassemblies: Arc<AtomicUsize>,
+    inbound_limiter: InboundLimiter,
+    sequencer: MessageSequencer,
+    /// Key each peer signs its messages with
+    peer_keys: PeerKeys,
+}
+
+/// Outcome of trying to re-establish a dropped peer connection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReconnectOutcome {
+    Reconnected(PeerId),
+    Unreachable(PeerId),
+}
+
+/// PeerReconnector re-establishes connections to peers a `BroadcastEngine`
+/// lost track of. It shares the engine's peer lists so it can run on its own
+/// task while the engine keeps listening for incoming connections.
+#[derive(Debug, Clone)]
+pub struct PeerReconnector {
+    endpoint: Endpoint,
+    config: BroadcastEngineConfig,
+    peer_connection_list: Arc<Mutex<Vec<(SocketAddr, Connection)>>>,
+    known_peers: Arc<Mutex<HashMap<SocketAddr, PeerId>>>,
+    dropped_peers: Arc<Mutex<HashSet<SocketAddr>>>,
+}
+
+impl PeerReconnector {
+    pub fn config(&self) -> &BroadcastEngineConfig {
+        &self.config
+    }
+
+    /// Attempts to reconnect to every peer marked as dropped, backing off
+    /// exponentially between attempts. Peers that can't be reached within
+    /// `max_reconnect_attempts` are forgotten.
+    ///
+    /// Peers are reconnected concurrently, so an unreachable peer backing off
+    /// doesn't hold up reconnecting the others.
+    pub async fn reconnect_dropped_peers(&self) -> Vec<ReconnectOutcome> {
+        let dropped = match self.dropped_peers.lock() {
+            Ok(mut dropped) => dropped.drain().collect::<Vec<SocketAddr>>(),
+            Err(_) => {
+                telemetry::error!("Error acquiring lock on dropped peer list");
+                return vec![];
+            },
+        };
+
+        join_all(
+            dropped
+                .into_iter()
+                .map(|addr| self.reconnect_dropped_peer(addr)),
+        )
+        .await
+    }
+
+    async fn reconnect_dropped_peer(&self, addr: SocketAddr) -> ReconnectOutcome {
+        let peer_id = self.peer_id(&addr);
+
+        match self.reconnect_peer(addr).await {
+            Ok(connection) => {
+                if let Ok(mut peers) = self.peer_connection_list.lock() {
+                    peers.retain(|(peer_addr, _)| *peer_addr != addr);
+                    peers.push((addr, connection));
+                }
+
+                info!("Reconnected to peer {addr}");
+                ReconnectOutcome::Reconnected(peer_id)
+            },
+            Err(err) => {
+                warn!("{err}");
+
+                if let Ok(mut known_peers) = self.known_peers.lock() {
+                    known_peers.remove(&addr);
+                }
+
+                ReconnectOutcome::Unreachable(peer_id)
+            },
+        }
+    }
+
+    async fn reconnect_peer(&self, addr: SocketAddr) -> Result<Connection, BroadCastError> {
+        for attempt in 0..self.config.max_reconnect_attempts {
+            match self.endpoint.connect_to(&addr).await {
+                Ok((connection, _)) => return Ok(connection),
+                Err(err) => {
+                    let delay = self.config.backoff_delay(attempt);
+                    warn!(
+                        "Reconnection attempt {} to {addr} failed: {err}. Retrying in {delay:?}",
+                        attempt + 1
+                    );
+                    tokio::time::sleep(delay).await;
+                },
+            }
+        }
+
+        Err(BroadCastError::PeerUnreachable(addr))
+    }
+
+    fn peer_id(&self, addr: &SocketAddr) -> PeerId {
+        self.known_peers
+            .lock()
+            .ok()
+            .and_then(|known_peers| known_peers.get(addr).cloned())
+            .unwrap_or_else(|| addr.to_string().into_bytes())
+    }
+}
+
+/// State of the connection to a peer listed by `PeerManager::peers`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PeerConnectionState {
+    /// This node holds an open connection to the peer
+    Connected,
+    /// The connection dropped and is waiting to be re-established
+    Reconnecting,
+    /// The peer is known to this node, e.g. because it handshaked with it,
+    /// but this node holds no connection to it
+    Known,
+}
+
+/// Entry of the peer table, see `PeerManager::peers`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerStatus {
+    pub peer_id: PeerId,
+    pub address: SocketAddr,
+    pub state: PeerConnectionState,
+    pub last_seen: Option<SystemTime>,
+}
+
+/// PeerManager lets operators inspect and edit the peer table of a
+/// `BroadcastEngine` while it runs. Like `PeerReconnector`, it shares the
+/// engine's peer lists.
+#[derive(Debug, Clone)]
+pub struct PeerManager {
+    endpoint: Endpoint,
+    peer_connection_list: Arc<Mutex<Vec<(SocketAddr, Connection)>>>,
+    known_peers: Arc<Mutex<HashMap<SocketAddr, PeerId>>>,
+    peer_versions: Arc<Mutex<HashMap<SocketAddr, ProtocolVersion>>>,
+    dropped_peers: Arc<Mutex<HashSet<SocketAddr>>>,
+    last_seen: Arc<Mutex<HashMap<SocketAddr, SystemTime>>>,
+    peer_keys: PeerKeys,
+}
+
+impl PeerManager {
+    /// Lists every peer this node is connected to, reconnecting to or knows
+    /// about, ordered by address
+    pub fn peers(&self) -> Vec<PeerStatus> {
+        let mut states = HashMap::new();
+
+        if let Ok(known_peers) = self.known_peers.lock() {
+            for addr in known_peers.keys() {
+                states.insert(*addr, PeerConnectionState::Known);
+            }
+        }
+
+        if let Ok(dropped) = self.dropped_peers.lock() {
+            for addr in dropped.iter() {
+                states.insert(*addr, PeerConnectionState::Reconnecting);
+            }
+        }
+
+        if let Ok(peers) = self.peer_connection_list.lock() {
+            for (addr, _) in peers.iter() {
+                states.insert(*addr, PeerConnectionState::Connected);
+            }
+        }
+
+        let mut peers = states
+            .into_iter()
+            .map(|(address, state)| PeerStatus {
+                peer_id: self.peer_id(&address),
+                address,
+                state,
+                last_seen: self.last_seen(&address),
+            })
+            .collect::<Vec<PeerStatus>>();
+
+        peers.sort_by_key(|peer| peer.address);
+        peers
+    }
+
+    /// Connects to the peer at `addr`, giving up after `timeout`. Peers this
+    /// node is already connected to are left as they are.
+    pub async fn connect(
+        &self,
+        addr: SocketAddr,
+        timeout: Duration,
+    ) -> Result<PeerStatus, BroadCastError> {
+        if is_own_address(&self.endpoint, &addr) {
+            return Err(BroadCastError::SelfConnection(addr));
+        }
+
+        if !self.is_connected(&addr) {
+            let (connection, _) = tokio::time::timeout(timeout, self.endpoint.connect_to(&addr))
+                .await
+                .map_err(|_| BroadCastError::ConnectTimeout(addr, timeout))??;
+
+            if let Ok(mut peers) = self.peer_connection_list.lock() {
+                peers.retain(|(peer_addr, _)| *peer_addr != addr);
+                peers.push((addr, connection));
+            }
+
+            if let Ok(mut dropped) = self.dropped_peers.lock() {
+                dropped.remove(&addr);
+            }
+
+            if let Ok(mut known_peers) = self.known_peers.lock() {
+                known_peers
+                    .entry(addr)
+                    .or_insert_with(|| addr.to_string().into_bytes());
+            }
+
+            record_last_seen(&self.last_seen, addr);
+            info!("Connected to peer {addr}");
+        }
+
+        Ok(PeerStatus {
+            peer_id: self.peer_id(&addr),
+            address: addr,
+            state: PeerConnectionState::Connected,
+            last_seen: self.last_seen(&addr),
+        })
+    }
+
+    /// Closes the connection to the peer identified by `peer_id` and forgets
+    /// about it, returning its address
+    pub fn disconnect(&self, peer_id: &PeerId) -> Result<SocketAddr, BroadCastError> {
+        let addr = self
+            .peers()
+            .into_iter()
+            .find(|peer| &peer.peer_id == peer_id)
+            .map(|peer| peer.address)
+            .ok_or_else(|| BroadCastError::UnknownPeer(String::from_utf8_lossy(peer_id).into()))?;
+
+        if let Ok(mut peers) = self.peer_connection_list.lock() {
+            peers.retain(|(peer_addr, connection)| {
+                if *peer_addr == addr {
+                    connection.close(Some(String::from(CONNECTION_CLOSED)));
+                }
+                *peer_addr != addr
+            });
+        }
+
+        if let Ok(mut dropped) = self.dropped_peers.lock() {
+            dropped.remove(&addr);
+        }
+
+        if let Ok(mut known_peers) = self.known_peers.lock() {
+            known_peers.remove(&addr);
+        }
+
+        if let Ok(mut peer_versions) = self.peer_versions.lock() {
+            peer_versions.remove(&addr);
+        }
+
+        if let Ok(mut last_seen) = self.last_seen.lock() {
+            last_seen.remove(&addr);
+        }
+
+        self.peer_keys.unbind(&addr);
+
+        info!("Disconnected from peer {addr}");
+
+        Ok(addr)
+    }
+
+    /// Records that the peer at `addr` was just heard from
+    pub fn record_activity(&self, addr: SocketAddr) {
+        record_last_seen(&self.last_seen, addr);
+    }
+
+    fn is_connected(&self, addr: &SocketAddr) -> bool {
+        self.peer_connection_list
+            .lock()
+            .map(|peers| peers.iter().any(|(peer_addr, _)| peer_addr == addr))
+            .unwrap_or_default()
+    }
+
+    fn peer_id(&self, addr: &SocketAddr) -> PeerId {
+        self.known_peers
+            .lock()
+            .ok()
+            .and_then(|known_peers| known_peers.get(addr).cloned())
+            .unwrap_or_else(|| addr.to_string().into_bytes())
+    }
+
+    fn last_seen(&self, addr: &SocketAddr) -> Option<SystemTime> {
+        self.last_seen
+            .lock()
+            .ok()
+            .and_then(|last_seen| last_seen.get(addr).copied())
+    }
+}
+
+/// Returns true if dialing `addr` from `endpoint` would connect it to itself
+fn is_own_address(endpoint: &Endpoint, addr: &SocketAddr) -> bool {
+    let local_addr = endpoint.local_addr();
+
+    // NOTE: endpoints bound to every interface are reachable through loopback
+    let reachable_through_loopback = local_addr.ip().is_unspecified()
+        && addr.ip().is_loopback()
+        && local_addr.port() == addr.port();
+
+    *addr == local_addr || *addr == endpoint.public_addr() || reachable_through_loopback
+}
+
+fn record_last_seen(last_seen: &Mutex<HashMap<SocketAddr, SystemTime>>, addr: SocketAddr) {
+    if let Ok(mut last_seen) = last_seen.lock() {
+        last_seen.insert(addr, SystemTime::now());
+    }
+}
+
+/// How long a bootstrap node is given to answer a handshake
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a peer is given to answer a request, see
+/// `PeerBootstrapper::request`
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// PeerBootstrapper performs the handshake a joining node sends to bootstrap
+/// nodes. Like `PeerReconnector`, it shares the engine's peer lists so it can
+/// run on its own task.
+#[derive(Debug, Clone)]
+pub struct PeerBootstrapper {
+    endpoint: Endpoint,
+    config: BroadcastEngineConfig,
+    peer_connection_list: Arc<Mutex<Vec<(SocketAddr, Connection)>>>,
+    known_peers: Arc<Mutex<HashMap<SocketAddr, PeerId>>>,
+    peer_versions: Arc<Mutex<HashMap<SocketAddr, ProtocolVersion>>>,
+    peer_keys: PeerKeys,
+}
+
+impl PeerBootstrapper {
+    /// Announces `local_peer` to the bootstrap node at `addr`, retrying with
+    /// exponential backoff up to `max_reconnect_attempts` times. The handshake
+    /// is signed with `secret_key`.
+    ///
+    /// On success the bootstrap node and every peer it reported are added to
+    /// the local peer table and returned. Bootstrap nodes speaking an
+    /// incompatible protocol version are not retried, and neither are
+    /// addresses that turn out to be this node's own.
+    pub async fn bootstrap(
+        &self,
+        addr: SocketAddr,
+        local_peer: &PeerData,
+        secret_key: &SecretKey,
+    ) -> Result<Vec<PeerData>, BroadCastError> {
+        if addr == local_peer.address || is_own_address(&self.endpoint, &addr) {
+            return Err(BroadCastError::SelfConnection(addr));
+        }
+
+        let mut last_err = BroadCastError::PeerUnreachable(addr);
+
+        for attempt in 0..self.config.max_reconnect_attempts {
+            match self.handshake(addr, local_peer, secret_key).await {
+                Ok(peers) => return Ok(peers),
+                Err(err @ BroadCastError::IncompatiblePeer { .. })
+                | Err(err @ BroadCastError::SelfConnection(_)) => return Err(err),
+                Err(err) => {
+                    let delay = self.config.backoff_delay(attempt);
+                    warn!(
+                        "Bootstrap attempt {} with {addr} failed: {err}. Retrying in {delay:?}",
+                        attempt + 1
+                    );
+                    last_err = err;
+                    tokio::time::sleep(delay).await;
+                },
+            }
+        }
+
+        Err(last_err)
+    }
+
+    /// Handshakes once with the peer at `addr`, e.g. one discovered through a
+    /// bootstrap node, so both nodes learn the key the other signs with
+    pub async fn introduce(
+        &self,
+        addr: SocketAddr,
+        local_peer: &PeerData,
+        secret_key: &SecretKey,
+    ) -> Result<(), BroadCastError> {
+        if addr == local_peer.address || is_own_address(&self.endpoint, &addr) {
+            return Err(BroadCastError::SelfConnection(addr));
+        }
+
+        self.handshake(addr, local_peer, secret_key)
+            .await
+            .map(|_| ())
+    }
+
+    /// Returns the key the peer at `addr` signs its messages with, if this
+    /// node handshook with it
+    pub fn peer_key(&self, addr: &SocketAddr) -> Option<primitives::PublicKey> {
+        self.peer_keys.get(addr)
+    }
+
+    /// Associates a peer id with an address, see
+    /// `BroadcastEngine::add_known_peer`
+    pub fn add_known_peer(&self, address: SocketAddr, peer_id: PeerId) {
+        if let Ok(mut known_peers) = self.known_peers.lock() {
+            known_peers.insert(address, peer_id);
+        }
+    }
+
+    /// Records the protocol version negotiated with a peer, see
+    /// `BroadcastEngine::peer_protocol_version`
+    pub fn set_peer_protocol_version(&self, address: SocketAddr, version: ProtocolVersion) {
+        if let Ok(mut peer_versions) = self.peer_versions.lock() {
+            peer_versions.insert(address, version);
+        }
+    }
+
+    /// Sends `body` to the peer at `addr` over a connection of its own,
+    /// signed with `secret_key`, and waits for the peer's reply.
+    ///
+    /// Unlike handshakes, requests are not retried.
+    pub async fn request(
+        &self,
+        addr: SocketAddr,
+        body: MessageBody,
+        secret_key: &SecretKey,
+    ) -> Result<MessageBody, BroadCastError> {
+        let (connection, mut incoming) = self.endpoint.connect_to(&addr).await?;
+
+        let request = Message::signed(body, secret_key);
+
+        connection
+            .send((Bytes::new(), Bytes::new(), Bytes::from(request.as_bytes())))
+            .await?;
+
+        let (_, _, raw_reply) = tokio::time::timeout(REQUEST_TIMEOUT, incoming.next())
+            .await
+            .map_err(|_| BroadCastError::RequestFailed(addr, "timed out".to_string()))?
+            .map_err(|err| BroadCastError::RequestFailed(addr, err.to_string()))?
+            .ok_or_else(|| BroadCastError::RequestFailed(addr, "connection closed".to_string()))?;
+
+        connection.close(Some(String::from(CONNECTION_CLOSED)));
+
+        let reply = Message::from(raw_reply.to_vec());
+
+        // NOTE: peers that never handshook with this node are bound to the key
+        // their reply was signed with
+        self.peer_keys
+            .bind(addr, &reply)
+            .map_err(|err| BroadCastError::RequestFailed(addr, err.to_string()))?;
+
+        Ok(reply.data.into())
+    }
+
+    async fn handshake(
+        &self,
+        addr: SocketAddr,
+        local_peer: &PeerData,
+        secret_key: &SecretKey,
+    ) -> Result<Vec<PeerData>, BroadCastError> {
+        let (connection, mut incoming) = self.endpoint.connect_to(&addr).await?;
+
+        let handshake = Message::signed(
+            MessageBody::Handshake {
+                peer: local_peer.clone(),
+                version: PeerVersion::current(),
+            },
+            secret_key,
+        );
+
+        connection
+            .send((
+                Bytes::new(),
+                Bytes::new(),
+                Bytes::from(handshake.as_bytes()),
+            ))
+            .await?;
+
+        let (_, _, raw_reply) = tokio::time::timeout(HANDSHAKE_TIMEOUT, incoming.next())
+            .await
+            .map_err(|_| BroadCastError::HandshakeFailed(addr, "timed out".to_string()))?
+            .map_err(|err| BroadCastError::HandshakeFailed(addr, err.to_string()))?
+            .ok_or_else(|| {
+                BroadCastError::HandshakeFailed(addr, "connection closed".to_string())
+            })?;
+
+        let reply = Message::from(raw_reply.to_vec());
+
+        self.peer_keys
+            .bind(addr, &reply)
+            .map_err(|err| BroadCastError::HandshakeFailed(addr, err.to_string()))?;
+
+        let reply: MessageBody = reply.data.into();
+
+        let (responder, peers, version) = match reply {
+            // NOTE: an address that wasn't recognized as this node's own, e.g.
+            // one of its other interfaces, may still lead back to it
+            MessageBody::HandshakeAck { responder, .. }
+            | MessageBody::HandshakeRejected { responder, .. }
+                if responder.peer_id == local_peer.peer_id =>
+            {
+                connection.close(Some(String::from(CONNECTION_CLOSED)));
+                return Err(BroadCastError::SelfConnection(addr));
+            },
+            MessageBody::HandshakeAck {
+                responder,
+                peers,
+                version,
+            } => (responder, peers, version),
+            MessageBody::HandshakeRejected { responder, version } => {
+                return Err(BroadCastError::IncompatiblePeer {
+                    peer: responder,
+                    remote_version: version.protocol,
+                })
+            },
+            _ => {
+                return Err(BroadCastError::HandshakeFailed(
+                    addr,
+                    "unexpected reply".to_string(),
+                ))
+            },
+        };
+
+        let negotiated_version = match PROTOCOL_VERSION.negotiate(&version.protocol) {
+            Some(negotiated_version) => negotiated_version,
+            None => {
+                return Err(BroadCastError::IncompatiblePeer {
+                    peer: responder,
+                    remote_version: version.protocol,
+                })
+            },
+        };
+
+        self.set_peer_protocol_version(addr, negotiated_version);
+
+        if let Ok(mut peer_connections) = self.peer_connection_list.lock() {
+            peer_connections.retain(|(peer_addr, _)| *peer_addr != addr);
+            peer_connections.push((addr, connection));
+        }
+
+        let discovered = std::iter::once(responder)
+            .chain(peers)
+            .filter(|peer| peer.peer_id != local_peer.peer_id)
+            .collect::<Vec<PeerData>>();
+
+        for peer in discovered.iter() {
+            self.add_known_peer(peer.address, peer.peer_id.clone());
+        }
+
+        Ok(discovered)
+    }
 }
 
 const CONNECTION_CLOSED: &str = "The connection was closed intentionally by qp2p.";
@@ -73,8 +635,14 @@ pub type EndPointResult = Result<
 
 impl BroadcastEngine {
     pub async fn new_endpoint(port: u16) -> EndPointResult {
+        Self::new_endpoint_on(SocketAddr::new(IpAddr::V6(Ipv6Addr::LOCALHOST), port)).await
+    }
+
+    /// Creates a QUIC endpoint bound to the given address, which may be
+    /// either IPv4 or IPv6
+    pub async fn new_endpoint_on(addr: SocketAddr) -> EndPointResult {
         Endpoint::new_peer(
-            (Ipv6Addr::LOCALHOST, port),
+            addr,
             &[],
             Config {
                 retry_config: RetryConfig {
@@ -100,11 +668,152 @@ impl BroadcastEngine {
                 endpoint: (node, incoming_conns),
                 raptor_udp_port,
                 raptor_num_packet_blast,
+                raptor_bind_ip: IpAddr::V4(Ipv4Addr::LOCALHOST),
+                config: BroadcastEngineConfig::default(),
+                known_peers: Arc::new(Mutex::new(HashMap::new())),
+                peer_versions: Arc::new(Mutex::new(HashMap::new())),
+                dropped_peers: Arc::new(Mutex::new(HashSet::new())),
+                last_seen: Arc::new(Mutex::new(HashMap::new())),
+                in_flight_reassemblies: Arc::new(AtomicUsize::new(0)),
+                inbound_limiter: InboundLimiter::new(BroadcastEngineConfig::default()),
+                sequencer: MessageSequencer::new(),
+                peer_keys: PeerKeys::new(),
             }),
             Err(e) => Err(BroadCastError::EndpointError(e)),
         }
     }
 
+    /// Creates a broadcast engine bound to `addr`, which may be either an IPv4
+    /// or IPv6 address. Both the QUIC endpoint and the RaptorQ UDP sockets
+    /// use the IP of `addr`.
+    pub async fn new_with_config(
+        addr: SocketAddr,
+        raptor_num_packet_blast: usize,
+        config: BroadcastEngineConfig,
+    ) -> Result<BroadcastEngine, BroadCastError> {
+        match BroadcastEngine::new_endpoint_on(addr).await {
+            Ok((node, incoming_conns, _contact)) => Ok(BroadcastEngine {
+                peer_connection_list: Arc::new(Mutex::new(Vec::new())),
+                raptor_list: Arc::new(Mutex::new(vec![])),
+                endpoint: (node, incoming_conns),
+                raptor_udp_port: addr.port(),
+                raptor_num_packet_blast,
+                raptor_bind_ip: addr.ip(),
+                inbound_limiter: InboundLimiter::new(config.clone()),
+                sequencer: MessageSequencer::new(),
+                config,
+                known_peers: Arc::new(Mutex::new(HashMap::new())),
+                peer_versions: Arc::new(Mutex::new(HashMap::new())),
+                dropped_peers: Arc::new(Mutex::new(HashSet::new())),
+                last_seen: Arc::new(Mutex::new(HashMap::new())),
+                in_flight_reassemblies: Arc::new(AtomicUsize::new(0)),
+                peer_keys: PeerKeys::new(),
+            }),
+            Err(e) => Err(BroadCastError::EndpointError(e)),
+        }
+    }
+
+    /// Returns a handle that can reconnect dropped peers independently of the
+    /// engine
+    pub fn reconnector(&self) -> PeerReconnector {
+        PeerReconnector {
+            endpoint: self.endpoint.0.clone(),
+            config: self.config.clone(),
+            peer_connection_list: self.peer_connection_list.clone(),
+            known_peers: self.known_peers.clone(),
+            dropped_peers: self.dropped_peers.clone(),
+        }
+    }
+
+    /// Returns a handle that can perform the bootstrap handshake independently
+    /// of the engine
+    pub fn bootstrapper(&self) -> PeerBootstrapper {
+        PeerBootstrapper {
+            endpoint: self.endpoint.0.clone(),
+            config: self.config.clone(),
+            peer_connection_list: self.peer_connection_list.clone(),
+            known_peers: self.known_peers.clone(),
+            peer_versions: self.peer_versions.clone(),
+            peer_keys: self.peer_keys.clone(),
+        }
+    }
+
+    /// Returns a handle that can list, add and remove peers independently of
+    /// the engine
+    pub fn peer_manager(&self) -> PeerManager {
+        PeerManager {
+            endpoint: self.endpoint.0.clone(),
+            peer_connection_list: self.peer_connection_list.clone(),
+            known_peers: self.known_peers.clone(),
+            peer_versions: self.peer_versions.clone(),
+            dropped_peers: self.dropped_peers.clone(),
+            last_seen: self.last_seen.clone(),
+            peer_keys: self.peer_keys.clone(),
+        }
+    }
+
+    /// Returns the keys the peers of this node sign their messages with,
+    /// shared with the engine
+    pub fn peer_keys(&self) -> PeerKeys {
+        self.peer_keys.clone()
+    }
+
+    /// Signs the messages this engine numbers for its peers with
+    /// `secret_key`, see `MessageSequencer`. Signed messages are sent
+    /// without a sequence number until a key is set.
+    pub fn set_secret_key(&mut self, secret_key: SecretKey) {
+        self.sequencer.set_secret_key(secret_key);
+    }
+
+    /// Returns a handle that enforces the engine's inbound connection and
+    /// message rate limits
+    pub fn inbound_limiter(&self) -> InboundLimiter {
+        self.inbound_limiter.clone()
+    }
+
+    /// Returns how many inbound connections and messages were turned away so
+    /// far, and how many peers got banned
+    pub fn inbound_stats(&self) -> InboundStats {
+        self.inbound_limiter.stats()
+    }
+
+    /// Associates a peer id with an address so reconnection outcomes can be
+    /// reported in terms of the peer rather than its address
+    pub fn add_known_peer(&self, address: SocketAddr, peer_id: PeerId) {
+        if let Ok(mut known_peers) = self.known_peers.lock() {
+            known_peers.insert(address, peer_id);
+        }
+    }
+
+    /// Returns the protocol version negotiated with the peer at `address`, if
+    /// it completed a handshake with this node
+    pub fn peer_protocol_version(&self, address: &SocketAddr) -> Option<ProtocolVersion> {
+        self.peer_versions
+            .lock()
+            .ok()
+            .and_then(|peer_versions| peer_versions.get(address).copied())
+    }
+
+    /// Drops the connection to a peer and schedules it for reconnection
+    pub fn mark_peer_dropped(&self, address: SocketAddr) {
+        if let Ok(mut peers) = self.peer_connection_list.lock() {
+            peers.retain(|(addr, _)| *addr != address);
+        }
+
+        // NOTE: the peer may have restarted by the time it is reconnected
+        self.sequencer.reset(&address);
+
+        if let Ok(mut dropped) = self.dropped_peers.lock() {
+            dropped.insert(address);
+        }
+    }
+
+    /// Attempts to reconnect every peer whose connection was lost. See
+    /// `PeerReconnector::reconnect_dropped_peers`.
+    pub async fn reconnect_dropped_peers(&self) -> Vec<ReconnectOutcome> {
+        self.reconnector().reconnect_dropped_peers().await
+    }
+
     /// > This function takes a vector of socket addresses and attempts to
     /// > connect to each one. If the
     /// connection is successful, it adds the connection to the peer connection
@@ -122,10 +831,23 @@ impl BroadcastEngine {
     pub async fn add_peer_connection(&mut self, address: Vec<SocketAddr>) -> BroadCastStatus {
         if let Ok(mut peers) = self.peer_connection_list.lock() {
             for addr in address.iter() {
+                if is_own_address(&self.endpoint.0, addr) {
+                    debug!("Skipping connection to {addr}: it is this node's own address");
+                    continue;
+                }
+
                 let result = self.endpoint.0.connect_to(addr).await;
                 match result {
                     Ok((connection, _)) => {
                         peers.push((*addr, connection));
+                        record_last_seen(&self.last_seen, *addr);
+                        self.sequencer.reset(addr);
+
+                        if let Ok(mut known_peers) = self.known_peers.lock() {
+                            known_peers
+                                .entry(*addr)
+                                .or_insert_with(|| addr.to_string().into_bytes());
+                        }
                     },
                     Err(e) => {
                         return Err(BroadCastError::ConnectionError(e));
@@ -165,6 +887,21 @@ impl BroadcastEngine {
                     address.0 != *addr
                 });
             }
+
+            if let Ok(mut known_peers) = self.known_peers.lock() {
+                address.iter().for_each(|addr| {
+                    known_peers.remove(addr);
+                });
+            }
+
+            address.iter().for_each(|addr| self.sequencer.reset(addr));
+
+            if let Ok(mut peer_versions) = self.peer_versions.lock() {
+                address.iter().for_each(|addr| {
+                    peer_versions.remove(addr);
+                });
+            }
+
             return Ok(BroadCastResult::Success);
         } else {
             telemetry::error!("Error acquiring lock on peer connection list");
@@ -176,6 +913,9 @@ impl BroadcastEngine {
     /// > This function takes a message and sends it to all the peers in the
     /// > peer list
     ///
+    /// Each peer receives the message numbered within the sequence of
+    /// messages this engine sent it, see `MessageSequencer`.
+    ///
     /// Arguments:
     ///
     /// * `message`: Message - The message to be broadcasted
@@ -192,7 +932,10 @@ impl BroadcastEngine {
                 return Err(BroadCastError::NoPeers);
             }
             for connection in peers.clone().into_iter() {
-                let new_data = message.as_bytes().clone();
+                let new_data = self
+                    .sequencer
+                    .stamp(message.clone(), connection.0)
+                    .as_bytes();
                 futs.push(tokio::spawn(async move {
                     let msg = Bytes::from(new_data);
                     let status = connection
@@ -200,12 +943,20 @@ impl BroadcastEngine {
                         .send((Bytes::new(), Bytes::new(), msg.clone()))
                         .await;
                     if let Err(err) = status {
-                        return Err(BroadCastError::BroadcastingDataError(err));
+                        return Err((connection.0, BroadCastError::BroadcastingDataError(err)));
                     }
                     Ok(())
                 }))
             }
-            while futs.next().await.is_some() {}
+            std::mem::drop(peers);
+
+            while let Some(result) = futs.next().await {
+                // NOTE: peers whose connection failed are scheduled for reconnection
+                if let Ok(Err((addr, err))) = result {
+                    warn!("Lost connection to peer {addr}: {err}");
+                    self.mark_peer_dropped(addr);
+                }
+            }
         } else {
             telemetry::error!("Error acquiring lock on peer connection list");
         }
@@ -224,7 +975,7 @@ impl BroadcastEngine {
     ///
     /// A future that resolves to a BroadCastStatus
     pub async fn send_data_via_quic(&self, message: Message, addr: SocketAddr) -> BroadCastStatus {
-        let msg = Bytes::from(message.as_bytes());
+        let msg = Bytes::from(self.sequencer.stamp(message, addr).as_bytes());
         let node = self.endpoint.0.clone();
         let result = node.connect_to(&addr).await;
         let conn = match result {
@@ -261,12 +1012,7 @@ impl BroadcastEngine {
         println!("Broadcasting to Port {:?}", port);
         let batch_id = generate_batch_id();
         let chunks = split_into_packets(&data, batch_id, erasure_count);
-        if let Ok(udp_socket) = UdpSocket::bind(SocketAddr::new(
-            std::net::IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
-            port,
-        ))
-        .await
-        {
+        if let Ok(udp_socket) = UdpSocket::bind(SocketAddr::new(self.raptor_bind_ip, port)).await {
             let udp_socket = Arc::new(udp_socket);
             let mut futs = FuturesUnordered::new();
             if let Ok(peers) = self.raptor_list.lock() {
@@ -324,18 +1070,17 @@ impl BroadcastEngine {
         port: u16,
         batch_sender: Sender<RaptorBroadCastedData>,
     ) -> Result<(), BroadCastError> {
-        if let Ok(sock_recv) = UdpSocket::bind(SocketAddr::new(
-            IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
-            port,
-        ))
-        .await
-        {
+        if let Ok(sock_recv) = UdpSocket::bind(SocketAddr::new(self.raptor_bind_ip, port)).await {
             info!("Listening on {}", port);
             let buf = [0; MTU_SIZE];
             let (reassembler_channel_send, reassembler_channel_receive) = unbounded();
             let (forwarder_send, forwarder_receive) = unbounded();
             let mut batch_id_store: HashSet<[u8; BATCH_ID_SIZE]> = HashSet::new();
-            let mut decoder_hash: HashMap<[u8; BATCH_ID_SIZE], (usize, Decoder)> = HashMap::new();
+            let mut reassembly_buffer = ReassemblyBuffer::new(
+                self.config.reassembly_timeout,
+                self.config.max_reassembled_message_size,
+                self.in_flight_reassemblies.clone(),
+            );
 
             thread::spawn({
                 let assemble_send = reassembler_channel_send.clone();
@@ -346,7 +1091,7 @@ impl BroadcastEngine {
                     reassemble_packets(
                         reassembler_channel_receive,
                         &mut batch_id_store,
-                        &mut decoder_hash,
+                        &mut reassembly_buffer,
                         fwd_send.clone(),
                         batch_send.clone(),
                     );
@@ -399,6 +1144,11 @@ impl BroadcastEngine {
     pub fn local_addr(&self) -> SocketAddr {
         self.endpoint.0.local_addr()
     }
+
+    /// Returns how many RaptorQ batches are currently awaiting reassembly
+    pub fn in_flight_reassemblies(&self) -> usize {
+        self.in_flight_reassemblies.load(Ordering::Relaxed)
+    }
 }
 
 #[cfg(test)]
@@ -409,8 +1159,15 @@ mod tests {
     };
 
     use bytes::Bytes;
+    use primitives::{NodeType, PeerVersion, ProtocolVersion, PROTOCOL_VERSION};
+    use secp256k1::{generate_keypair, rand};
+    use vrrb_core::event_router::PeerData;
 
-    use crate::{message::Message, network::BroadcastEngine};
+    use crate::{
+        message::{Message, MessageBody},
+        network::{BroadcastEngine, PeerConnectionState},
+        types::config::{BroadCastError, BroadcastEngineConfig},
+    };
 
     #[tokio::test]
     async fn test_successful_connection() {
@@ -503,6 +1260,314 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn engine_binds_to_ipv4_addresses() {
+        let addr = SocketAddr::new(std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST), 0);
+
+        let engine = BroadcastEngine::new_with_config(addr, 32, Default::default())
+            .await
+            .unwrap();
+
+        assert!(engine.local_addr().is_ipv4());
+        assert!(engine.raptor_bind_ip.is_ipv4());
+    }
+
+    #[tokio::test]
+    async fn engine_binds_to_ipv6_addresses() {
+        let addr = SocketAddr::new(std::net::IpAddr::V6(Ipv6Addr::LOCALHOST), 0);
+
+        let engine = BroadcastEngine::new_with_config(addr, 32, Default::default())
+            .await
+            .unwrap();
+
+        assert!(engine.local_addr().is_ipv6());
+        assert!(engine.raptor_bind_ip.is_ipv6());
+    }
+
+    #[tokio::test]
+    async fn bootstrapper_discovers_peers_known_to_bootstrap_node() {
+        let addr = SocketAddr::new(std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST), 0);
+
+        let mut bootstrap_engine = BroadcastEngine::new_with_config(addr, 32, Default::default())
+            .await
+            .unwrap();
+        let joining_engine = BroadcastEngine::new_with_config(addr, 32, Default::default())
+            .await
+            .unwrap();
+
+        let peer = |address: SocketAddr, id: &str| PeerData {
+            address,
+            node_type: NodeType::Full,
+            peer_id: id.as_bytes().to_vec(),
+        };
+
+        let bootstrap_peer = PeerData {
+            node_type: NodeType::Bootstrap,
+            ..peer(bootstrap_engine.local_addr(), "bootstrap")
+        };
+        let known_peer = peer("127.0.0.1:9000".parse().unwrap(), "known");
+        let joining_peer = peer(joining_engine.local_addr(), "joining");
+
+        let bootstrap_addr = bootstrap_engine.local_addr();
+        let (bootstrap_secret_key, _) = generate_keypair(&mut rand::thread_rng());
+        let (joining_secret_key, joining_public_key) = generate_keypair(&mut rand::thread_rng());
+
+        let ack = Message::signed(
+            MessageBody::HandshakeAck {
+                responder: bootstrap_peer.clone(),
+                peers: vec![known_peer.clone()],
+                version: PeerVersion::current(),
+            },
+            &bootstrap_secret_key,
+        );
+
+        let responder = tokio::spawn(async move {
+            let (conn, mut incoming) = bootstrap_engine
+                .get_incomming_connections()
+                .next()
+                .await
+                .unwrap();
+
+            let (_, _, raw_message) = incoming.next().await.unwrap().unwrap();
+            let message = Message::from(raw_message.to_vec());
+
+            assert_eq!(message.verify(&joining_public_key), Ok(()));
+
+            let body: MessageBody = message.data.into();
+
+            conn.send((Bytes::new(), Bytes::new(), Bytes::from(ack.as_bytes())))
+                .await
+                .unwrap();
+
+            body
+        });
+
+        let discovered = joining_engine
+            .bootstrapper()
+            .bootstrap(bootstrap_addr, &joining_peer, &joining_secret_key)
+            .timeout()
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(discovered, vec![bootstrap_peer, known_peer]);
+        assert_eq!(
+            joining_engine.peer_protocol_version(&bootstrap_addr),
+            Some(PROTOCOL_VERSION)
+        );
+
+        match responder.await.unwrap() {
+            MessageBody::Handshake { peer, version } => {
+                assert_eq!(peer, joining_peer);
+                assert_eq!(version, PeerVersion::current());
+            },
+            other => panic!("expected a handshake, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn bootstrapper_refuses_peers_with_a_different_protocol_major_version() {
+        let addr = SocketAddr::new(std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST), 0);
+
+        let mut bootstrap_engine = BroadcastEngine::new_with_config(addr, 32, Default::default())
+            .await
+            .unwrap();
+        let joining_engine = BroadcastEngine::new_with_config(addr, 32, Default::default())
+            .await
+            .unwrap();
+
+        let bootstrap_peer = PeerData {
+            address: bootstrap_engine.local_addr(),
+            node_type: NodeType::Bootstrap,
+            peer_id: b"bootstrap".to_vec(),
+        };
+        let joining_peer = PeerData {
+            address: joining_engine.local_addr(),
+            node_type: NodeType::Full,
+            peer_id: b"joining".to_vec(),
+        };
+
+        let bootstrap_addr = bootstrap_engine.local_addr();
+        let (bootstrap_secret_key, _) = generate_keypair(&mut rand::thread_rng());
+        let (joining_secret_key, _) = generate_keypair(&mut rand::thread_rng());
+
+        let future_version = ProtocolVersion {
+            major: PROTOCOL_VERSION.major + 1,
+            minor: 0,
+        };
+
+        let ack = Message::signed(
+            MessageBody::HandshakeAck {
+                responder: bootstrap_peer.clone(),
+                peers: vec![],
+                version: PeerVersion {
+                    protocol: future_version,
+                    crate_version: "99.0.0".to_string(),
+                },
+            },
+            &bootstrap_secret_key,
+        );
+
+        // NOTE: answers a single handshake, so a retry would time out
+        tokio::spawn(async move {
+            let (conn, mut incoming) = bootstrap_engine
+                .get_incomming_connections()
+                .next()
+                .await
+                .unwrap();
+
+            incoming.next().await.unwrap().unwrap();
+
+            conn.send((Bytes::new(), Bytes::new(), Bytes::from(ack.as_bytes())))
+                .await
+                .unwrap();
+        });
+
+        let err = joining_engine
+            .bootstrapper()
+            .bootstrap(bootstrap_addr, &joining_peer, &joining_secret_key)
+            .timeout()
+            .await
+            .unwrap()
+            .unwrap_err();
+
+        match err {
+            BroadCastError::IncompatiblePeer {
+                peer,
+                remote_version,
+            } => {
+                assert_eq!(peer, bootstrap_peer);
+                assert_eq!(remote_version, future_version);
+            },
+            other => panic!("expected an incompatible peer error, got {other:?}"),
+        }
+
+        assert_eq!(joining_engine.peer_protocol_version(&bootstrap_addr), None);
+        assert!(joining_engine
+            .peer_connection_list
+            .lock()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn peer_manager_adds_and_removes_peers() {
+        let addr = SocketAddr::new(std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST), 0);
+
+        let engine = BroadcastEngine::new_with_config(addr, 32, Default::default())
+            .await
+            .unwrap();
+        let remote_engine = BroadcastEngine::new_with_config(addr, 32, Default::default())
+            .await
+            .unwrap();
+
+        let remote_addr = remote_engine.local_addr();
+        let peer_manager = engine.peer_manager();
+
+        let peer = peer_manager
+            .connect(remote_addr, Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        assert_eq!(peer.address, remote_addr);
+        assert_eq!(peer.state, PeerConnectionState::Connected);
+        assert!(peer.last_seen.is_some());
+        assert_eq!(peer_manager.peers(), vec![peer.clone()]);
+
+        assert_eq!(peer_manager.disconnect(&peer.peer_id).unwrap(), remote_addr);
+        assert!(peer_manager.peers().is_empty());
+        assert!(matches!(
+            peer_manager.disconnect(&peer.peer_id),
+            Err(BroadCastError::UnknownPeer(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn bootstrapper_skips_this_nodes_own_address() {
+        let addr = SocketAddr::new(std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST), 0);
+
+        let engine = BroadcastEngine::new_with_config(addr, 32, Default::default())
+            .await
+            .unwrap();
+
+        let own_addr = engine.local_addr();
+        let local_peer = PeerData {
+            address: own_addr,
+            node_type: NodeType::Full,
+            peer_id: "local".as_bytes().to_vec(),
+        };
+        let (secret_key, _) = generate_keypair(&mut rand::thread_rng());
+
+        let err = engine
+            .bootstrapper()
+            .bootstrap(own_addr, &local_peer, &secret_key)
+            .timeout()
+            .await
+            .unwrap()
+            .unwrap_err();
+
+        assert!(matches!(err, BroadCastError::SelfConnection(addr) if addr == own_addr));
+        assert!(engine.peer_connection_list.lock().unwrap().is_empty());
+        assert!(engine.peer_manager().peers().is_empty());
+
+        assert!(matches!(
+            engine
+                .peer_manager()
+                .connect(own_addr, Duration::from_secs(1))
+                .await,
+            Err(BroadCastError::SelfConnection(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn unreachable_peers_dont_hold_up_reconnecting_others() {
+        let addr = SocketAddr::new(std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST), 0);
+
+        let config = BroadcastEngineConfig {
+            reconnect_base_delay: Duration::from_secs(1),
+            max_reconnect_attempts: 3,
+            ..Default::default()
+        };
+
+        let engine = BroadcastEngine::new_with_config(addr, 32, config)
+            .await
+            .unwrap();
+        let remote_engine = BroadcastEngine::new_with_config(addr, 32, Default::default())
+            .await
+            .unwrap();
+
+        // NOTE: bound but silent, so every attempt to reach it fails and
+        // backs off for at least 3 seconds in total
+        let unreachable = std::net::UdpSocket::bind(addr).unwrap();
+
+        let remote_addr = remote_engine.local_addr();
+        let unreachable_addr = unreachable.local_addr().unwrap();
+
+        engine
+            .dropped_peers
+            .lock()
+            .unwrap()
+            .extend([unreachable_addr, remote_addr]);
+
+        let reconnector = engine.reconnector();
+        let reconnecting = tokio::spawn(async move { reconnector.reconnect_dropped_peers().await });
+
+        let peer_manager = engine.peer_manager();
+        let reconnected = async {
+            while !peer_manager.is_connected(&remote_addr) {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        };
+
+        tokio::time::timeout(Duration::from_secs(2), reconnected)
+            .await
+            .expect("reachable peer should reconnect while the other backs off");
+
+        assert!(!reconnecting.is_finished());
+
+        reconnecting.abort();
+    }
+
     pub fn test_message() -> Message {
         let msg = Message {
             id: uuid::Uuid::new_v4(),
@@ -510,6 +1575,8 @@ mod tests {
             source: Some("vrrb".to_string().as_bytes().to_vec()),
             sequence_number: Some(1i32.to_ne_bytes().to_vec()),
             return_receipt: 0u8,
+            sender_public_key: None,
+            signature: None,
         };
         msg
     }
@@ -0,0 +1,443 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::SocketAddr,
+    ops::Range,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use primitives::SecretKey;
+
+use crate::message::Message;
+
+/// How many out of order messages are buffered per sender before the missing
+/// ones are given up on
+pub const DEFAULT_REORDER_WINDOW: usize = 64;
+
+/// How long messages that arrived early are held back waiting for the ones
+/// before them, before the missing ones are given up on
+pub const DEFAULT_REORDER_GAP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Session and next sequence number of the messages sent to a peer
+#[derive(Debug, Clone, Copy)]
+struct PeerSequence {
+    session: u64,
+    next: u64,
+}
+
+impl PeerSequence {
+    /// Starts a session past `previous`. Sessions are timestamped, so the
+    /// ones started after a restart are past the earlier ones as well.
+    fn after(previous: Option<u64>) -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos() as u64)
+            .unwrap_or_default();
+
+        Self {
+            session: previous.map_or(now, |previous| now.max(previous + 1)),
+            next: 0,
+        }
+    }
+}
+
+/// Numbers the messages a node sends to each of its peers. Every peer sees a
+/// contiguous sequence starting at zero within each session, which lets it
+/// restore the order the messages were sent in, see `ReorderBuffer`. A new
+/// session starts whenever the node restarts or reconnects to the peer.
+///
+/// Clones share their counters.
+#[derive(Debug, Clone, Default)]
+pub struct MessageSequencer {
+    next: Arc<Mutex<HashMap<SocketAddr, PeerSequence>>>,
+    /// Signs stamped messages, so their signature covers the sequence number
+    secret_key: Option<SecretKey>,
+}
+
+impl MessageSequencer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Signs the messages this sequencer stamps with `secret_key`
+    pub fn set_secret_key(&mut self, secret_key: SecretKey) {
+        self.secret_key = Some(secret_key);
+    }
+
+    /// Assigns `message` the next sequence number for the peer at `to`.
+    /// Messages already carrying a sequence number are left as they are, and
+    /// so are signed ones if this sequencer has no key to sign them again.
+    pub fn stamp(&self, mut message: Message, to: SocketAddr) -> Message {
+        if message.sequence_number.is_some() {
+            return message;
+        }
+
+        // NOTE: the signature has to cover the sequence number
+        if message.signature.is_some() && self.secret_key.is_none() {
+            return message;
+        }
+
+        if let Ok(mut next) = self.next.lock() {
+            let sequence = next.entry(to).or_insert_with(|| PeerSequence::after(None));
+            message.set_sequence(sequence.session, sequence.next);
+            sequence.next += 1;
+        } else {
+            telemetry::error!("Error acquiring lock on message sequence numbers");
+            return message;
+        }
+
+        if let Some(secret_key) = &self.secret_key {
+            message.sign(secret_key);
+        }
+
+        message
+    }
+
+    /// Starts a new session with the peer at `to`, e.g. once it connected or
+    /// its connection dropped
+    pub fn reset(&self, to: &SocketAddr) {
+        if let Ok(mut next) = self.next.lock() {
+            let previous = next.get(to).map(|sequence| sequence.session);
+            next.insert(*to, PeerSequence::after(previous));
+        }
+    }
+}
+
+/// Messages a `ReorderBuffer` can hand over, in the order their sender sent
+/// them
+#[derive(Debug, Default)]
+pub struct Delivery {
+    pub messages: Vec<Message>,
+    /// Sequence numbers that were given up on because the window filled up
+    /// before they arrived
+    pub skipped: Option<Range<u64>>,
+}
+
+#[derive(Debug, Default)]
+struct SenderQueue {
+    session: u64,
+    next_expected: u64,
+    pending: BTreeMap<u64, Message>,
+    /// When the messages pending now started waiting on a gap
+    gap_since: Option<Instant>,
+}
+
+impl SenderQueue {
+    fn drain_ready(&mut self, messages: &mut Vec<Message>) {
+        while let Some(message) = self.pending.remove(&self.next_expected) {
+            messages.push(message);
+            self.next_expected += 1;
+            self.gap_since = None;
+        }
+    }
+
+    /// Gives up on the messages missing before the first pending one,
+    /// returning their sequence numbers
+    fn skip_gap(&mut self, messages: &mut Vec<Message>) -> Range<u64> {
+        let resume_at = self
+            .pending
+            .keys()
+            .next()
+            .copied()
+            .unwrap_or(self.next_expected);
+
+        let skipped = self.next_expected..resume_at;
+        self.next_expected = resume_at;
+        self.drain_ready(messages);
+
+        skipped
+    }
+
+    fn track_gap(&mut self, now: Instant) {
+        self.gap_since = if self.pending.is_empty() {
+            None
+        } else {
+            self.gap_since.or(Some(now))
+        };
+    }
+
+    fn gap_timed_out(&self, now: Instant, gap_timeout: Duration) -> bool {
+        self.gap_since.map_or(false, |gap_since| {
+            now.duration_since(gap_since) >= gap_timeout
+        })
+    }
+}
+
+/// Restores the send order of messages coming from each peer. Messages that
+/// arrive early are held back until the ones before them show up, for as
+/// long as no more than `window` of them are pending and for no longer than
+/// `gap_timeout`. Past that, the missing messages are skipped and reported
+/// as a gap.
+///
+/// Messages without a sequence number are delivered right away, and
+/// messages with a sequence number that was already delivered are discarded.
+/// A peer starting a new sequence session, e.g. after a restart, starts over
+/// from zero, while messages left over from its earlier sessions are
+/// discarded.
+#[derive(Debug)]
+pub struct ReorderBuffer {
+    window: usize,
+    gap_timeout: Duration,
+    senders: HashMap<SocketAddr, SenderQueue>,
+}
+
+impl Default for ReorderBuffer {
+    fn default() -> Self {
+        Self::new(DEFAULT_REORDER_WINDOW)
+    }
+}
+
+impl ReorderBuffer {
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            gap_timeout: DEFAULT_REORDER_GAP_TIMEOUT,
+            senders: HashMap::new(),
+        }
+    }
+
+    pub fn with_gap_timeout(mut self, gap_timeout: Duration) -> Self {
+        self.gap_timeout = gap_timeout;
+        self
+    }
+
+    /// Accepts a message received from `from`, returning every message from
+    /// that peer that is now ready for delivery
+    pub fn push(&mut self, from: SocketAddr, message: Message) -> Delivery {
+        self.push_at(from, message, Instant::now())
+    }
+
+    /// Same as `push`, for a message received at `now`
+    pub fn push_at(&mut self, from: SocketAddr, message: Message, now: Instant) -> Delivery {
+        let (session, sequence) = match (message.sequence_session(), message.sequence()) {
+            (Some(session), Some(sequence)) => (session, sequence),
+            _ => {
+                return Delivery {
+                    messages: vec![message],
+                    skipped: None,
+                }
+            },
+        };
+
+        let queue = self.senders.entry(from).or_default();
+        let mut delivery = Delivery::default();
+
+        if session < queue.session {
+            return delivery;
+        }
+
+        if session > queue.session {
+            *queue = SenderQueue {
+                session,
+                ..Default::default()
+            };
+        }
+
+        if sequence < queue.next_expected {
+            return delivery;
+        }
+
+        queue.pending.insert(sequence, message);
+        queue.drain_ready(&mut delivery.messages);
+
+        if queue.pending.len() >= self.window || queue.gap_timed_out(now, self.gap_timeout) {
+            delivery.skipped = Some(queue.skip_gap(&mut delivery.messages));
+        }
+
+        queue.track_gap(now);
+
+        delivery
+    }
+
+    /// Gives up on the gaps that have been holding back messages for longer
+    /// than `gap_timeout` as of `now`, returning the messages delivered as a
+    /// result by peer
+    pub fn expire(&mut self, now: Instant) -> Vec<(SocketAddr, Delivery)> {
+        let gap_timeout = self.gap_timeout;
+
+        self.senders
+            .iter_mut()
+            .filter(|(_, queue)| queue.gap_timed_out(now, gap_timeout))
+            .map(|(from, queue)| {
+                let mut messages = vec![];
+                let skipped = Some(queue.skip_gap(&mut messages));
+                queue.track_gap(now);
+
+                (*from, Delivery { messages, skipped })
+            })
+            .collect()
+    }
+
+    /// Forgets everything about the messages from `from`, e.g. once it
+    /// handshook with this node again
+    pub fn reset(&mut self, from: &SocketAddr) {
+        self.senders.remove(from);
+    }
+
+    /// Returns how many messages from `from` are waiting for earlier ones
+    pub fn pending(&self, from: &SocketAddr) -> usize {
+        self.senders
+            .get(from)
+            .map(|queue| queue.pending.len())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::{Ipv4Addr, SocketAddr},
+        time::{Duration, Instant},
+    };
+
+    use secp256k1::{generate_keypair, rand};
+
+    use super::{MessageSequencer, ReorderBuffer};
+    use crate::message::{AsMessage, Message, MessageBody};
+
+    fn sender() -> SocketAddr {
+        SocketAddr::from((Ipv4Addr::LOCALHOST, 9000))
+    }
+
+    fn sequenced_messages(count: usize) -> Vec<Message> {
+        let sequencer = MessageSequencer::new();
+
+        (0..count)
+            .map(|_| sequencer.stamp(MessageBody::Empty.into_message(0), sender()))
+            .collect()
+    }
+
+    #[test]
+    fn shuffled_messages_are_delivered_in_send_order() {
+        let sent = sequenced_messages(3);
+        let mut buffer = ReorderBuffer::default();
+
+        let mut delivered = vec![];
+        for index in [2, 0, 1] {
+            let delivery = buffer.push(sender(), sent[index].clone());
+
+            assert!(delivery.skipped.is_none());
+            delivered.extend(delivery.messages);
+        }
+
+        assert_eq!(
+            delivered.iter().map(|m| m.id).collect::<Vec<_>>(),
+            sent.iter().map(|m| m.id).collect::<Vec<_>>()
+        );
+        assert_eq!(buffer.pending(&sender()), 0);
+    }
+
+    #[test]
+    fn gaps_are_skipped_once_the_window_fills_up() {
+        let sent = sequenced_messages(5);
+        let mut buffer = ReorderBuffer::new(3);
+
+        assert!(buffer.push(sender(), sent[2].clone()).messages.is_empty());
+        assert!(buffer.push(sender(), sent[3].clone()).messages.is_empty());
+
+        let delivery = buffer.push(sender(), sent[4].clone());
+
+        assert_eq!(delivery.skipped, Some(0..2));
+        assert_eq!(
+            delivery.messages.iter().map(|m| m.id).collect::<Vec<_>>(),
+            sent[2..].iter().map(|m| m.id).collect::<Vec<_>>()
+        );
+
+        // NOTE: messages that show up after being skipped are stale
+        assert!(buffer.push(sender(), sent[0].clone()).messages.is_empty());
+    }
+
+    #[test]
+    fn gaps_are_skipped_once_they_time_out() {
+        let sent = sequenced_messages(5);
+        let mut buffer = ReorderBuffer::default().with_gap_timeout(Duration::from_secs(1));
+        let received_at = Instant::now();
+
+        assert!(buffer
+            .push_at(sender(), sent[1].clone(), received_at)
+            .messages
+            .is_empty());
+        assert!(buffer
+            .expire(received_at + Duration::from_millis(500))
+            .is_empty());
+
+        let expired = buffer.expire(received_at + Duration::from_secs(1));
+
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].0, sender());
+        assert_eq!(expired[0].1.skipped, Some(0..1));
+        assert_eq!(expired[0].1.messages[0].id, sent[1].id);
+
+        // NOTE: gaps also time out on the next message showing up late
+        let gap_at = received_at + Duration::from_secs(1);
+        assert!(buffer
+            .push_at(sender(), sent[3].clone(), gap_at)
+            .messages
+            .is_empty());
+
+        let delivery = buffer.push_at(sender(), sent[4].clone(), gap_at + Duration::from_secs(1));
+
+        assert_eq!(delivery.skipped, Some(2..3));
+        assert_eq!(
+            delivery.messages.iter().map(|m| m.id).collect::<Vec<_>>(),
+            sent[3..].iter().map(|m| m.id).collect::<Vec<_>>()
+        );
+        assert_eq!(buffer.pending(&sender()), 0);
+    }
+
+    #[test]
+    fn peers_starting_a_new_session_start_over() {
+        let sequencer = MessageSequencer::new();
+        let mut buffer = ReorderBuffer::default();
+
+        let stale = sequencer.stamp(MessageBody::Empty.into_message(0), sender());
+        let first = sequencer.stamp(MessageBody::Empty.into_message(0), sender());
+        assert_eq!(buffer.push(sender(), stale.clone()).messages.len(), 1);
+        assert_eq!(buffer.push(sender(), first).messages.len(), 1);
+
+        // NOTE: e.g. the connection to the peer dropped and was re-established
+        sequencer.reset(&sender());
+
+        let restarted = sequencer.stamp(MessageBody::Empty.into_message(0), sender());
+        assert_eq!(restarted.sequence(), Some(0));
+        assert_eq!(buffer.push(sender(), restarted).messages.len(), 1);
+
+        // NOTE: earlier sessions are over for good
+        let mut replayed = stale;
+        replayed.set_sequence(replayed.sequence_session().unwrap(), 5);
+        assert!(buffer.push(sender(), replayed).messages.is_empty());
+    }
+
+    #[test]
+    fn stamped_messages_are_signed_over_their_sequence_number() {
+        let (secret_key, public_key) = generate_keypair(&mut rand::thread_rng());
+
+        let message = Message::signed(MessageBody::Empty, &secret_key);
+
+        // NOTE: signed messages are left alone by sequencers that can't sign
+        // them again
+        let unstamped = MessageSequencer::new().stamp(message.clone(), sender());
+        assert_eq!(unstamped.sequence(), None);
+
+        let mut sequencer = MessageSequencer::new();
+        sequencer.set_secret_key(secret_key);
+
+        let stamped = sequencer.stamp(message, sender());
+        assert_eq!(stamped.sequence(), Some(0));
+        assert_eq!(stamped.verify(&public_key), Ok(()));
+    }
+
+    #[test]
+    fn each_peer_gets_its_own_sequence() {
+        let sequencer = MessageSequencer::new();
+        let other = SocketAddr::from((Ipv4Addr::LOCALHOST, 9001));
+
+        let first = sequencer.stamp(MessageBody::Empty.into_message(0), sender());
+        let second = sequencer.stamp(MessageBody::Empty.into_message(0), sender());
+        let to_other = sequencer.stamp(MessageBody::Empty.into_message(0), other);
+
+        assert_eq!(first.sequence(), Some(0));
+        assert_eq!(second.sequence(), Some(1));
+        assert_eq!(to_other.sequence(), Some(0));
+    }
+}
@@ -9,11 +9,15 @@ use std::{
     path::PathBuf,
     result::Result as StdResult,
     str,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
 };
 
 use block::Block;
-use crossbeam_channel::{Receiver, Sender};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
 use futures::future::try_join_all;
 use log::error;
 use rand::{distributions::Alphanumeric, thread_rng, Rng, RngCore};
@@ -257,6 +261,119 @@ pub fn batch_writer(batch_recv: Receiver<(String, Vec<u8>)>) {
     }
 }
 
+/// A batch whose packets are still being collected
+struct PendingBatch {
+    received: usize,
+    bytes_received: usize,
+    decoder: Decoder,
+    started_at: Instant,
+}
+
+/// Holds the decoders of RaptorQ batches awaiting reassembly.
+///
+/// Batches that don't complete within `timeout` of their first packet are
+/// dropped, so packets of messages that will never complete don't pile up.
+/// Batches are also dropped once their packets add up to more than
+/// `max_message_size` bytes, which bounds the memory a single sender can tie
+/// up.
+/// The number of batches currently held is mirrored into a shared counter so
+/// it can be observed from outside the reassembly thread.
+pub struct ReassemblyBuffer {
+    pending: HashMap<[u8; BATCH_ID_SIZE], PendingBatch>,
+    timeout: Duration,
+    max_message_size: usize,
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl ReassemblyBuffer {
+    pub fn new(timeout: Duration, max_message_size: usize, in_flight: Arc<AtomicUsize>) -> Self {
+        Self {
+            pending: HashMap::new(),
+            timeout,
+            max_message_size,
+            in_flight,
+        }
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Largest message, in bytes, batches are reassembled into
+    pub fn max_message_size(&self) -> usize {
+        self.max_message_size
+    }
+
+    /// Returns the number of batches awaiting reassembly
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Drops every batch that has been pending for longer than the timeout,
+    /// returning how many were dropped
+    pub fn evict_expired(&mut self) -> usize {
+        let timeout = self.timeout;
+        let before = self.pending.len();
+
+        self.pending.retain(|batch_id, batch| {
+            let expired = batch.started_at.elapsed() >= timeout;
+
+            if expired {
+                telemetry::warn!(
+                    "Dropping incomplete batch {} after {:?}, {} packets received",
+                    String::from_utf8_lossy(batch_id),
+                    timeout,
+                    batch.received
+                );
+            }
+
+            !expired
+        });
+
+        self.sync_in_flight();
+
+        before - self.pending.len()
+    }
+
+    /// Returns how many bytes of the batch's packets were received so far
+    fn bytes_received(&self, batch_id: &[u8; BATCH_ID_SIZE]) -> usize {
+        self.pending
+            .get(batch_id)
+            .map_or(0, |batch| batch.bytes_received)
+    }
+
+    fn get_mut(&mut self, batch_id: &[u8; BATCH_ID_SIZE]) -> Option<&mut PendingBatch> {
+        self.pending.get_mut(batch_id)
+    }
+
+    fn insert(&mut self, batch_id: [u8; BATCH_ID_SIZE], decoder: Decoder, bytes_received: usize) {
+        self.pending.insert(
+            batch_id,
+            PendingBatch {
+                received: 1,
+                bytes_received,
+                decoder,
+                started_at: Instant::now(),
+            },
+        );
+
+        self.sync_in_flight();
+    }
+
+    fn remove(&mut self, batch_id: &[u8; BATCH_ID_SIZE]) {
+        self.pending.remove(batch_id);
+        self.sync_in_flight();
+    }
+
+    fn sync_in_flight(&self) {
+        self.in_flight.store(self.pending.len(), Ordering::Relaxed);
+    }
+}
+
 /// It receives packets from the `receiver` channel, checks if the packet is a
 /// duplicate, and if not, it checks if the packet is a forwarder packet. If it
 /// is, it forwards the packet to the `forwarder` channel. If it is not, it
@@ -270,24 +387,30 @@ pub fn batch_writer(batch_recv: Receiver<(String, Vec<u8>)>) {
 /// * `batch_id_hashset`: A hashset that contains the batch_ids of all the
 ///   batches that have been
 /// reassembled.
-/// * `decoder_hash`: A hashmap that stores the batch_id as the key and a tuple
-///   of the number of packets
-/// received and the decoder as the value.
+/// * `reassembly_buffer`: Decoders of the batches awaiting reassembly. Batches
+///   that time out are dropped whenever a packet arrives or the channel stays
+///   idle for a whole timeout. Batches growing past its maximum message size
+///   are dropped, and none of their later packets are forwarded or decoded.
 /// * `forwarder`: Sender<Vec<u8>>
 /// * `file_send`: Sender<(String, Vec<u8>)>
+///
+/// Returns once every sender of `receiver` has been dropped.
 pub fn reassemble_packets(
     receiver: Receiver<([u8; 1280], usize)>,
     batch_id_hashset: &mut HashSet<[u8; BATCH_ID_SIZE]>,
-    decoder_hash: &mut HashMap<[u8; BATCH_ID_SIZE], (usize, Decoder)>,
+    reassembly_buffer: &mut ReassemblyBuffer,
     forwarder: Sender<Vec<u8>>,
     batch_send: Sender<RaptorBroadCastedData>,
 ) {
     loop {
-        let mut received_packet = match receiver.recv() {
+        let received_packet = receiver.recv_timeout(reassembly_buffer.timeout());
+
+        reassembly_buffer.evict_expired();
+
+        let mut received_packet = match received_packet {
             Ok(pr) => pr,
-            Err(_e) => {
-                continue;
-            },
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => return,
         };
 
         let batch_id = get_batch_id(&received_packet.0);
@@ -295,6 +418,22 @@ pub fn reassemble_packets(
         if batch_id_hashset.contains(&batch_id) {
             continue;
         }
+
+        let bytes_received = reassembly_buffer.bytes_received(&batch_id) + received_packet.1;
+
+        // NOTE: remembering the batch as done keeps its remaining packets from
+        // being looked at any further
+        if bytes_received > reassembly_buffer.max_message_size() {
+            telemetry::warn!(
+                "Dropping batch {} after receiving {bytes_received} bytes, over the maximum message size of {} bytes",
+                String::from_utf8_lossy(&batch_id),
+                reassembly_buffer.max_message_size()
+            );
+            reassembly_buffer.remove(&batch_id);
+            batch_id_hashset.insert(batch_id);
+            continue;
+        }
+
         let payload_length = get_payload_length(&received_packet.0);
         let symbol_size = get_symbol_size(&received_packet.0);
         // This is to check if the packet is a forwarder packet. If it is, it forwards
@@ -308,11 +447,12 @@ pub fn reassemble_packets(
             }
         }
 
-        match decoder_hash.get_mut(&batch_id) {
-            Some((num_packets, decoder)) => {
-                *num_packets += 1;
+        match reassembly_buffer.get_mut(&batch_id) {
+            Some(batch) => {
+                batch.received += 1;
+                batch.bytes_received = bytes_received;
                 // Decoding the packet.
-                let result = decoder.decode(EncodingPacket::deserialize(
+                let result = batch.decoder.decode(EncodingPacket::deserialize(
                     &received_packet.0[40_usize..received_packet.1],
                 ));
                 if result.is_some() {}
@@ -335,24 +475,22 @@ pub fn reassemble_packets(
                                 },
                             }
                         }
-                        decoder_hash.remove(&batch_id);
+                        reassembly_buffer.remove(&batch_id);
                     }
                 }
             },
             None => {
                 // This is creating a new decoder for a new batch.
-                decoder_hash.insert(
+                reassembly_buffer.insert(
                     batch_id,
-                    (
-                        1_usize,
-                        Decoder::new(ObjectTransmissionInformation::new(
-                            payload_length as u64,
-                            symbol_size as u16,
-                            1,
-                            1,
-                            8,
-                        )),
-                    ),
+                    Decoder::new(ObjectTransmissionInformation::new(
+                        payload_length as u64,
+                        symbol_size as u16,
+                        1,
+                        1,
+                        8,
+                    )),
+                    bytes_received,
                 );
             },
         }
@@ -554,3 +692,95 @@ impl std::fmt::Display for NotCompleteError {
         write!(f, "NotCompleteError")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn incomplete_batches_are_dropped_after_timeout() {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let mut reassembly_buffer =
+            ReassemblyBuffer::new(Duration::from_millis(200), usize::MAX, in_flight.clone());
+
+        let (packet_tx, packet_rx) = crossbeam_channel::unbounded();
+        let (forwarder_tx, _forwarder_rx) = crossbeam_channel::unbounded();
+        let (batch_tx, _batch_rx) = crossbeam_channel::unbounded();
+
+        let reassembler = thread::spawn(move || {
+            reassemble_packets(
+                packet_rx,
+                &mut HashSet::new(),
+                &mut reassembly_buffer,
+                forwarder_tx,
+                batch_tx,
+            );
+        });
+
+        let data = vec![7u8; PAYLOAD_SIZE * 4];
+        let packets = split_into_packets(&data, generate_batch_id(), 0);
+
+        // NOTE: withholding the last packet keeps the batch from ever completing
+        for packet in packets.iter().take(packets.len() - 1) {
+            let mut buf = [0u8; MTU_SIZE];
+            buf[..packet.len()].copy_from_slice(packet);
+            packet_tx.send((buf, packet.len())).unwrap();
+        }
+
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(in_flight.load(Ordering::Relaxed), 1);
+
+        thread::sleep(Duration::from_millis(600));
+        assert_eq!(in_flight.load(Ordering::Relaxed), 0);
+
+        drop(packet_tx);
+        reassembler.join().unwrap();
+    }
+
+    #[test]
+    fn batches_over_the_maximum_message_size_are_not_reassembled() {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let mut reassembly_buffer =
+            ReassemblyBuffer::new(Duration::from_secs(30), MTU_SIZE * 2, in_flight.clone());
+
+        let (packet_tx, packet_rx) = crossbeam_channel::unbounded();
+        let (forwarder_tx, forwarder_rx) = crossbeam_channel::unbounded();
+        let (batch_tx, batch_rx) = crossbeam_channel::unbounded();
+
+        let reassembler = thread::spawn(move || {
+            let mut batch_id_hashset = HashSet::new();
+
+            reassemble_packets(
+                packet_rx,
+                &mut batch_id_hashset,
+                &mut reassembly_buffer,
+                forwarder_tx,
+                batch_tx,
+            );
+
+            batch_id_hashset
+        });
+
+        let data = vec![7u8; PAYLOAD_SIZE * 4];
+        let batch_id = generate_batch_id();
+        let packets = split_into_packets(&data, batch_id, 0);
+
+        for packet in packets.iter() {
+            let mut buf = [0u8; MTU_SIZE];
+            buf[..packet.len()].copy_from_slice(packet);
+            packet_tx.send((buf, packet.len())).unwrap();
+        }
+
+        drop(packet_tx);
+        let batch_id_hashset = reassembler.join().unwrap();
+
+        // NOTE: only the packets received ahead of the batch going over the
+        // maximum are passed on
+        assert!(batch_id_hashset.contains(&batch_id));
+        assert_eq!(in_flight.load(Ordering::Relaxed), 0);
+        assert_eq!(forwarder_rx.try_iter().count(), 2);
+        assert!(batch_rx.try_recv().is_err());
+    }
+}
@@ -0,0 +1,158 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use primitives::PublicKey;
+
+use crate::message::{Message, MessageError};
+
+/// Public keys of the peers this node exchanged a handshake or request with,
+/// by address.
+///
+/// A peer is bound to the key that signed the first message this node got
+/// from it, normally its handshake. Every message it sends afterwards must be
+/// signed by that key, so signing a message with some other key and
+/// embedding that key within the message doesn't pass for the peer.
+///
+/// Clones share their keys.
+#[derive(Debug, Clone, Default)]
+pub struct PeerKeys {
+    keys: Arc<Mutex<HashMap<SocketAddr, PublicKey>>>,
+}
+
+impl PeerKeys {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Binds the peer at `addr` to the key `message` was signed with,
+    /// returning that key. Peers already bound to another key are refused,
+    /// so an address can't be taken over by whoever handshakes with it next.
+    pub fn bind(&self, addr: SocketAddr, message: &Message) -> Result<PublicKey, MessageError> {
+        message.verify_sender()?;
+
+        let key = message.sender_public_key.ok_or(MessageError::Unsigned)?;
+
+        let mut keys = self
+            .keys
+            .lock()
+            .map_err(|_| MessageError::UnknownSender(addr))?;
+
+        match keys.get(&addr) {
+            Some(bound) if *bound != key => Err(MessageError::KeyMismatch(addr)),
+            _ => {
+                keys.insert(addr, key);
+                Ok(key)
+            },
+        }
+    }
+
+    /// Verifies `message` was signed by the key the peer at `addr` is bound
+    /// to. Messages from peers that aren't bound yet are refused, and so are
+    /// the ones embedding any other key.
+    pub fn verify(&self, addr: SocketAddr, message: &Message) -> Result<(), MessageError> {
+        let key = self.get(&addr).ok_or(MessageError::UnknownSender(addr))?;
+
+        if matches!(message.sender_public_key, Some(embedded) if embedded != key) {
+            return Err(MessageError::KeyMismatch(addr));
+        }
+
+        message.verify(&key)
+    }
+
+    /// Returns the key the peer at `addr` is bound to
+    pub fn get(&self, addr: &SocketAddr) -> Option<PublicKey> {
+        self.keys
+            .lock()
+            .ok()
+            .and_then(|keys| keys.get(addr).copied())
+    }
+
+    /// Forgets the key of the peer at `addr`, e.g. once it was disconnected
+    pub fn unbind(&self, addr: &SocketAddr) {
+        if let Ok(mut keys) = self.keys.lock() {
+            keys.remove(addr);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use primitives::SecretKey;
+    use secp256k1::{rand::rngs::OsRng, Secp256k1};
+
+    use super::*;
+    use crate::message::MessageBody;
+
+    fn secret_key() -> SecretKey {
+        Secp256k1::new().generate_keypair(&mut OsRng).0
+    }
+
+    fn addr() -> SocketAddr {
+        "127.0.0.1:9000".parse().unwrap()
+    }
+
+    #[test]
+    fn messages_must_be_signed_by_the_key_the_peer_is_bound_to() {
+        let peer_keys = PeerKeys::new();
+        let peer_key = secret_key();
+
+        let handshake = Message::signed(MessageBody::Empty, &peer_key);
+        peer_keys.bind(addr(), &handshake).unwrap();
+
+        let genuine = Message::signed(MessageBody::Empty, &peer_key);
+        assert_eq!(peer_keys.verify(addr(), &genuine), Ok(()));
+
+        // NOTE: signed by an attacker, who embedded their own key
+        let forged = Message::signed(MessageBody::Empty, &secret_key());
+        assert_eq!(
+            peer_keys.verify(addr(), &forged),
+            Err(MessageError::KeyMismatch(addr()))
+        );
+
+        let mut unsigned = genuine;
+        unsigned.sender_public_key = None;
+        unsigned.signature = None;
+        assert_eq!(
+            peer_keys.verify(addr(), &unsigned),
+            Err(MessageError::Unsigned)
+        );
+    }
+
+    #[test]
+    fn peers_that_never_handshook_are_refused() {
+        let peer_keys = PeerKeys::new();
+        let message = Message::signed(MessageBody::Empty, &secret_key());
+
+        assert_eq!(
+            peer_keys.verify(addr(), &message),
+            Err(MessageError::UnknownSender(addr()))
+        );
+    }
+
+    #[test]
+    fn bound_peers_cannot_be_rebound_to_another_key() {
+        let peer_keys = PeerKeys::new();
+        let peer_key = secret_key();
+
+        peer_keys
+            .bind(addr(), &Message::signed(MessageBody::Empty, &peer_key))
+            .unwrap();
+
+        assert_eq!(
+            peer_keys.bind(addr(), &Message::signed(MessageBody::Empty, &secret_key())),
+            Err(MessageError::KeyMismatch(addr()))
+        );
+        assert!(peer_keys
+            .bind(addr(), &Message::signed(MessageBody::Empty, &peer_key))
+            .is_ok());
+
+        peer_keys.unbind(&addr());
+
+        assert!(peer_keys
+            .bind(addr(), &Message::signed(MessageBody::Empty, &secret_key()))
+            .is_ok());
+    }
+}
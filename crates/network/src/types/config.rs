@@ -1,7 +1,11 @@
+use std::{net::SocketAddr, time::Duration};
+
+use primitives::ProtocolVersion;
 use qp2p::{ConnectionError, EndpointError, SendError};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use udp2p::node::peer_id::PeerId;
+use vrrb_core::event_router::PeerData;
 
 /// `Topology` is a struct that contains the number of master nodes, the number
 /// of quorum nodes, and the miner network id.
@@ -32,6 +36,68 @@ impl Topology {
     }
 }
 
+/// Tuning knobs for a `BroadcastEngine`'s handling of dropped peer
+/// connections.
+///
+/// Reconnection attempts back off exponentially, starting at
+/// `reconnect_base_delay` and doubling after every failed attempt up to
+/// `reconnect_max_delay`. A peer is given up on after
+/// `max_reconnect_attempts` consecutive failures.
+///
+/// RaptorQ batches that haven't been fully received within
+/// `reassembly_timeout` of their first packet are dropped, as are batches
+/// whose packets add up to more than `max_reassembled_message_size` bytes.
+///
+/// At most `max_inbound_connections` inbound connections are handled at once.
+/// Each peer may send `peer_message_rate` messages per second on average, in
+/// bursts of up to `peer_message_burst`; messages past that are dropped. If
+/// `max_rate_violations` is set, a peer that had that many messages dropped is
+/// banned for `peer_ban_duration`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BroadcastEngineConfig {
+    pub reconnect_base_delay: Duration,
+    pub reconnect_max_delay: Duration,
+    pub max_reconnect_attempts: u32,
+    pub reassembly_timeout: Duration,
+    pub max_reassembled_message_size: usize,
+    pub max_inbound_connections: usize,
+    pub peer_message_rate: u32,
+    pub peer_message_burst: u32,
+    pub max_rate_violations: Option<u32>,
+    pub peer_ban_duration: Duration,
+}
+
+impl Default for BroadcastEngineConfig {
+    fn default() -> Self {
+        Self {
+            reconnect_base_delay: Duration::from_millis(500),
+            reconnect_max_delay: Duration::from_secs(30),
+            max_reconnect_attempts: 8,
+            reassembly_timeout: Duration::from_secs(30),
+            max_reassembled_message_size: 16 * 1024 * 1024,
+            max_inbound_connections: 128,
+            peer_message_rate: 100,
+            peer_message_burst: 200,
+            max_rate_violations: None,
+            peer_ban_duration: Duration::from_secs(600),
+        }
+    }
+}
+
+impl BroadcastEngineConfig {
+    /// Returns how long to wait before the given (zero-based) reconnection
+    /// attempt
+    pub fn backoff_delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt);
+
+        self.reconnect_base_delay
+            .checked_mul(factor)
+            .unwrap_or(self.reconnect_max_delay)
+            .min(self.reconnect_max_delay)
+    }
+}
+
 #[derive(Debug)]
 pub enum BroadCastResult {
     ConnectionEstablished,
@@ -51,4 +117,48 @@ pub enum BroadCastError {
     EaddrInUse,
     #[error("Current Node doesn't have any peers")]
     NoPeers,
+    #[error("Peer {0} could not be reached after exhausting all reconnection attempts")]
+    PeerUnreachable(SocketAddr),
+    #[error("Handshake with {0} failed: {1}")]
+    HandshakeFailed(SocketAddr, String),
+    #[error("Request to {0} failed: {1}")]
+    RequestFailed(SocketAddr, String),
+    #[error("Peer {} speaks incompatible protocol version {remote_version}", .peer.address)]
+    IncompatiblePeer {
+        peer: PeerData,
+        remote_version: ProtocolVersion,
+    },
+    #[error("Refusing connection from {0}: too many inbound connections")]
+    TooManyConnections(SocketAddr),
+    #[error("Refusing connection from banned peer {0}")]
+    PeerBanned(SocketAddr),
+    #[error("Connecting to {0} timed out after {1:?}")]
+    ConnectTimeout(SocketAddr, Duration),
+    #[error("No peer with id {0} is known")]
+    UnknownPeer(String),
+    #[error("Refusing to connect to {0}: it is this node")]
+    SelfConnection(SocketAddr),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::BroadcastEngineConfig;
+
+    #[test]
+    fn backoff_delay_grows_exponentially_up_to_max() {
+        let config = BroadcastEngineConfig {
+            reconnect_base_delay: Duration::from_millis(100),
+            reconnect_max_delay: Duration::from_secs(1),
+            max_reconnect_attempts: 10,
+            ..Default::default()
+        };
+
+        assert_eq!(config.backoff_delay(0), Duration::from_millis(100));
+        assert_eq!(config.backoff_delay(1), Duration::from_millis(200));
+        assert_eq!(config.backoff_delay(3), Duration::from_millis(800));
+        assert_eq!(config.backoff_delay(4), Duration::from_secs(1));
+        assert_eq!(config.backoff_delay(64), Duration::from_secs(1));
+    }
 }
@@ -1,10 +1,12 @@
 use std::{collections::HashMap, net::SocketAddr};
 
-use primitives::{NodeType, PublicKey};
+use primitives::{Address, NodeType, PeerVersion, PublicKey, SecretKey, Signature};
+use secp256k1::{hashes::Hash, Secp256k1};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 use udp2p::node::peer_id::PeerId;
 use uuid::Uuid;
-use vrrb_core::event_router::{Event, PeerData};
+use vrrb_core::event_router::{AccountBytes, Event, PeerData};
 
 use crate::packet::{NotCompleteError, Packet, Packetize};
 
@@ -16,9 +18,27 @@ pub const PROPOSAL_EXPIRATION_KEY: &str = "expires";
 pub const PROPOSAL_YES_VOTE_KEY: &str = "yes";
 pub const PROPOSAL_NO_VOTE_KEY: &str = "no";
 
+/// Most accounts a node sends back in reply to a single state sync request
+pub const MAX_STATE_SYNC_CHUNK_SIZE: u64 = 256;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct StateBlock(pub u128);
 
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum MessageError {
+    #[error("message is not signed")]
+    Unsigned,
+
+    #[error("invalid message signature: {0}")]
+    InvalidSignature(String),
+
+    #[error("{0} never handshook with this node")]
+    UnknownSender(SocketAddr),
+
+    #[error("message from {0} was not signed by the key it handshook with")]
+    KeyMismatch(SocketAddr),
+}
+
 /// Message types are the different types of messages that can be
 /// packed and sent across the network.
 //TODO: Convert Vec<u8>, String, u128 and other standard types with custom types
@@ -158,6 +178,8 @@ impl AsMessage for MessageType {
             data: self.as_bytes(),
             sequence_number: None,
             return_receipt,
+            sender_public_key: None,
+            signature: None,
         }
     }
 }
@@ -173,6 +195,12 @@ pub struct Message {
     pub source: Option<Vec<u8>>,
     pub sequence_number: Option<Vec<u8>>,
     pub return_receipt: u8,
+    /// Public key of the node claiming to have sent this message
+    #[serde(default)]
+    pub sender_public_key: Option<PublicKey>,
+    /// Signature over `data`, see `Message::signed`
+    #[serde(default)]
+    pub signature: Option<Signature>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -249,6 +277,44 @@ pub enum MessageBody {
         socket_addr: SocketAddr,
         node_type: NodeType,
     },
+    /// Sent by a joining node to a bootstrap node to announce itself
+    Handshake {
+        peer: PeerData,
+        version: PeerVersion,
+    },
+    /// A bootstrap node's reply to a handshake, listing the peers it knows
+    /// about
+    HandshakeAck {
+        responder: PeerData,
+        peers: Vec<PeerData>,
+        version: PeerVersion,
+    },
+    /// A bootstrap node's reply to a handshake from a peer speaking an
+    /// incompatible protocol version
+    HandshakeRejected {
+        responder: PeerData,
+        version: PeerVersion,
+    },
+    /// Sent by a node catching up with the network, asking a peer for up to
+    /// `limit` of its accounts starting at `offset`. The first request of a
+    /// sync leaves `snapshot` unset, the following ones ask for the snapshot
+    /// the peer replied with.
+    StateSyncRequest {
+        requestor: PeerData,
+        snapshot: Option<u64>,
+        offset: u64,
+        limit: u64,
+    },
+    /// A peer's reply to a state sync request. Accounts are read from the
+    /// version of the peer's state pinned as `snapshot`, they're ordered by
+    /// address and `total` is the number of accounts within the snapshot.
+    StateSyncChunk {
+        responder: PeerData,
+        snapshot: u64,
+        offset: u64,
+        total: u64,
+        accounts: Vec<(Address, AccountBytes)>,
+    },
     Empty,
 }
 
@@ -264,6 +330,20 @@ impl From<MessageBody> for Vec<u8> {
     }
 }
 
+impl AsMessage for MessageBody {
+    fn into_message(self, return_receipt: u8) -> Message {
+        Message {
+            id: Uuid::new_v4(),
+            source: None,
+            data: self.into(),
+            sequence_number: None,
+            return_receipt,
+            sender_public_key: None,
+            signature: None,
+        }
+    }
+}
+
 /// AsMessage is a trait that when implemented on a custom type allows
 /// for the easy conversion of the type into a message that can be packed
 /// into a byte array and sent across the network.
@@ -272,6 +352,88 @@ pub trait AsMessage {
 }
 
 impl Message {
+    /// Builds a message carrying `body`, signed by `secret_key` so peers can
+    /// verify who sent it
+    pub fn signed(body: MessageBody, secret_key: &SecretKey) -> Message {
+        let mut message = body.into_message(0);
+        message.sign(secret_key);
+        message
+    }
+
+    /// Signs this message's body and sequence number with `secret_key`,
+    /// replacing any earlier signature
+    pub fn sign(&mut self, secret_key: &SecretKey) {
+        let secp = Secp256k1::signing_only();
+
+        self.signature = Some(secp.sign_ecdsa(&self.signing_message(), secret_key));
+        self.sender_public_key = Some(PublicKey::from_secret_key(&secp, secret_key));
+    }
+
+    /// Verifies this message's body was signed by `public_key`
+    pub fn verify(&self, public_key: &PublicKey) -> Result<(), MessageError> {
+        let signature = self.signature.as_ref().ok_or(MessageError::Unsigned)?;
+
+        Secp256k1::verification_only()
+            .verify_ecdsa(&self.signing_message(), signature, public_key)
+            .map_err(|err| MessageError::InvalidSignature(err.to_string()))
+    }
+
+    /// Verifies this message against the public key of the sender it claims
+    /// to come from. This only proves the message is self-consistent, see
+    /// `PeerKeys` to check who sent it.
+    pub fn verify_sender(&self) -> Result<(), MessageError> {
+        let public_key = self
+            .sender_public_key
+            .as_ref()
+            .ok_or(MessageError::Unsigned)?;
+
+        self.verify(public_key)
+    }
+
+    /// Returns the position of this message among those its sender sent to
+    /// this node within the current session, see `MessageSequencer`
+    pub fn sequence(&self) -> Option<u64> {
+        let bytes = self.sequence_number.as_deref()?;
+        let number = bytes.get(bytes.len().checked_sub(8)?..)?.try_into().ok()?;
+
+        Some(u64::from_be_bytes(number))
+    }
+
+    /// Returns the session the sequence number of this message belongs to.
+    /// Senders start a new session whenever they restart or reconnect.
+    pub fn sequence_session(&self) -> Option<u64> {
+        let bytes = self.sequence_number.as_deref()?;
+
+        match bytes.len() {
+            16 => Some(u64::from_be_bytes(bytes[..8].try_into().ok()?)),
+            // NOTE: sequence numbers without a session predate sessions
+            _ => Some(0),
+        }
+    }
+
+    pub fn set_sequence(&mut self, session: u64, sequence: u64) {
+        let mut bytes = session.to_be_bytes().to_vec();
+        bytes.extend_from_slice(&sequence.to_be_bytes());
+
+        self.sequence_number = Some(bytes);
+    }
+
+    /// Digest signatures are made over. Covers the sequence number too, if
+    /// any, so it can't be rewritten to reorder or suppress messages.
+    fn signing_message(&self) -> secp256k1::Message {
+        type H = secp256k1::hashes::sha256::Hash;
+
+        match &self.sequence_number {
+            Some(sequence_number) => {
+                let mut preimage = H::hash(&self.data).into_inner().to_vec();
+                preimage.extend_from_slice(sequence_number);
+
+                secp256k1::Message::from_hashed_data::<H>(&preimage)
+            },
+            None => secp256k1::Message::from_hashed_data::<H>(&self.data),
+        }
+    }
+
     /// Serializes a Message struct into a vector of bytes
     pub fn as_bytes(&self) -> Vec<u8> {
         self.clone().into()
@@ -290,6 +452,8 @@ pub const NULL_MESSAGE: Message = Message {
     source: None,
     sequence_number: None,
     return_receipt: 0,
+    sender_public_key: None,
+    signature: None,
 };
 
 impl From<Vec<u8>> for Message {
@@ -331,6 +495,10 @@ impl From<MessageBody> for Event {
                 node_type,
                 peer_id,
             }),
+            MessageBody::Handshake { peer, .. } => Event::PeerJoined(peer),
+            MessageBody::StateSyncRequest { requestor, .. } => {
+                Event::PeerRequestedStateSync(requestor)
+            },
             _ => Event::NoOp,
         }
     }
@@ -463,3 +631,78 @@ impl Packetize for Message {
         Err(NotCompleteError)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use secp256k1::{generate_keypair, rand};
+
+    use super::*;
+
+    #[test]
+    fn signed_messages_verify_against_sender_key() {
+        let (secret_key, public_key) = generate_keypair(&mut rand::thread_rng());
+
+        let message = Message::signed(MessageBody::Empty, &secret_key);
+
+        assert_eq!(message.sender_public_key, Some(public_key));
+        assert_eq!(message.verify(&public_key), Ok(()));
+
+        let received = Message::from(message.as_bytes());
+
+        assert_eq!(received.verify_sender(), Ok(()));
+    }
+
+    #[test]
+    fn tampered_messages_fail_verification() {
+        let (secret_key, public_key) = generate_keypair(&mut rand::thread_rng());
+        let (_, other_public_key) = generate_keypair(&mut rand::thread_rng());
+
+        let mut message = Message::signed(MessageBody::Empty, &secret_key);
+
+        assert!(matches!(
+            message.verify(&other_public_key),
+            Err(MessageError::InvalidSignature(_))
+        ));
+
+        message.data = MessageBody::Disconnect {
+            sender_id: String::from("tampered"),
+            pubkey: public_key.to_string(),
+        }
+        .into();
+
+        assert!(matches!(
+            message.verify(&public_key),
+            Err(MessageError::InvalidSignature(_))
+        ));
+    }
+
+    #[test]
+    fn sequence_numbers_are_signed_over() {
+        let (secret_key, public_key) = generate_keypair(&mut rand::thread_rng());
+
+        let mut message = MessageBody::Empty.into_message(0);
+        message.set_sequence(7, 3);
+        message.sign(&secret_key);
+
+        assert_eq!(message.sequence_session(), Some(7));
+        assert_eq!(message.sequence(), Some(3));
+        assert_eq!(message.verify(&public_key), Ok(()));
+
+        message.set_sequence(7, 4);
+
+        assert!(matches!(
+            message.verify(&public_key),
+            Err(MessageError::InvalidSignature(_))
+        ));
+    }
+
+    #[test]
+    fn unsigned_messages_fail_verification() {
+        let (_, public_key) = generate_keypair(&mut rand::thread_rng());
+
+        let message = MessageBody::Empty.into_message(0);
+
+        assert_eq!(message.verify(&public_key), Err(MessageError::Unsigned));
+        assert_eq!(message.verify_sender(), Err(MessageError::Unsigned));
+    }
+}
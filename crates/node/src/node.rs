@@ -1,4 +1,12 @@
-use std::{io::Read, net::SocketAddr, path::PathBuf, sync::mpsc::channel, thread};
+use std::{
+    collections::HashMap,
+    io::Read,
+    net::SocketAddr,
+    path::PathBuf,
+    sync::mpsc::channel,
+    thread,
+    time::Duration,
+};
 
 use crossbeam_channel::unbounded;
 use mempool::{LeftRightMempool, MempoolReadHandleFactory};
@@ -8,7 +16,7 @@ use storage::{
     storage_utils,
     vrrbdb::{VrrbDbConfig, VrrbDbReadHandle},
 };
-use telemetry::info;
+use telemetry::{error, info, warn, TelemetrySubscriber};
 use theater::{Actor, ActorImpl};
 use tokio::{
     sync::{
@@ -17,11 +25,11 @@ use tokio::{
     },
     task::JoinHandle,
 };
-use trecho::vm::Cpu;
 use vrrb_config::NodeConfig;
 use vrrb_core::{
-    event_router::{DirectedEvent, Event, EventRouter, Topic},
+    event_router::{DirectedEvent, Event, EventCounter, EventRouterMetrics, ShutdownReason, Topic},
     keypair::KeyPair,
+    node_status::NodeStatusHandle,
     txn::Txn,
 };
 use vrrb_rpc::{
@@ -35,13 +43,36 @@ use crate::{
     mempool_module::{MempoolModule, MempoolModuleConfig},
     mining_module,
     result::{NodeError, Result},
-    runtime::setup_runtime_components,
+    runtime::{
+        setup_audit_topic,
+        setup_event_routing_system,
+        setup_runtime_components,
+        supervisor::ModuleSupervisor,
+        BROADCAST_CONTROLLER_MODULE,
+        EVENT_STORE_MODULE,
+        GOSSIP_NETWORK_MODULE,
+        JSONRPC_SERVER_MODULE,
+        MEMPOOL_MODULE,
+        MINER_MODULE,
+        STATE_STORE_MODULE,
+        STATE_SYNC_MODULE,
+        VALIDATOR_MODULE,
+        VM_MODULE,
+    },
     validator_module,
     NodeType,
     RuntimeModule,
     RuntimeModuleState,
 };
 
+/// Interval at which module states are checked while waiting for the node
+/// to be ready
+const READINESS_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Interval at which the peer count is checked while waiting for the
+/// bootstrap quorum
+const BOOTSTRAP_QUORUM_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
 /// Node represents a member of the VRRB network and it is responsible for
 /// carrying out the different operations permitted within the chain.
 #[derive(Debug)]
@@ -50,44 +81,59 @@ pub struct Node {
 
     // NOTE: core node features
     event_router_handle: JoinHandle<()>,
+    mempool_sweeper_handle: Option<JoinHandle<Result<()>>>,
+    event_metrics: EventRouterMetrics,
     running_status: RuntimeModuleState,
+    node_status_handle: NodeStatusHandle,
     control_rx: UnboundedReceiver<Event>,
     events_tx: UnboundedSender<DirectedEvent>,
+    config_reload_tx: UnboundedSender<NodeConfig>,
+    config_reload_rx: UnboundedReceiver<NodeConfig>,
 
     // TODO: make this private
     pub keypair: KeyPair,
 
-    // NOTE: optional node components
-    vm: Option<Cpu>,
-    state_handle: Option<JoinHandle<Result<()>>>,
-    mempool_handle: Option<JoinHandle<Result<()>>>,
-    gossip_handle: Option<JoinHandle<Result<()>>>,
-    broadcast_controller_handle: Option<JoinHandle<Result<()>>>,
-    miner_handle: Option<JoinHandle<Result<()>>>,
-    txn_validator_handle: Option<JoinHandle<Result<()>>>,
-    jsonrpc_server_handle: Option<JoinHandle<Result<()>>>,
+    // NOTE: optional node components, supervised while the node runs
+    modules: ModuleSupervisor,
 }
 
 impl Node {
-    /// Initializes and returns a new Node instance
+    /// Initializes and returns a new Node instance once every one of its
+    /// runtime modules is running, see `is_ready`. Fails with a
+    /// `NodeError::NotReady` should they not all be running within the
+    /// configured `readiness_timeout`, tearing down whatever was started.
     pub async fn start(config: &NodeConfig, control_rx: UnboundedReceiver<Event>) -> Result<Self> {
         // Copy the original config to avoid overriding the original
         let mut config = config.clone();
 
 
-        let vm = None;
-        let keypair = config.keypair.clone();
+        let keypair = config.keypair.clone().ok_or_else(|| {
+            NodeError::Other(String::from("node config does not contain a keypair"))
+        })?;
+        let node_status_handle = NodeStatusHandle::new();
 
         let (events_tx, mut events_rx) = unbounded_channel::<DirectedEvent>();
-        let mut event_router = Self::setup_event_routing_system();
+        let (config_reload_tx, config_reload_rx) = unbounded_channel::<NodeConfig>();
+        let mut event_router = setup_event_routing_system(&config.event_router_config);
+        let event_metrics = event_router.metrics();
 
         let mempool_events_rx = event_router.subscribe(&Topic::Storage)?;
         let vrrbdb_events_rx = event_router.subscribe(&Topic::Storage)?;
         let network_events_rx = event_router.subscribe(&Topic::Network)?;
         let controller_events_rx = event_router.subscribe(&Topic::Network)?;
+        let state_sync_events_rx = event_router.subscribe(&Topic::Network)?;
         let validator_events_rx = event_router.subscribe(&Topic::Consensus)?;
         let miner_events_rx = event_router.subscribe(&Topic::Consensus)?;
         let jsonrpc_events_rx = event_router.subscribe(&Topic::Control)?;
+        let jsonrpc_consensus_events_rx = event_router.subscribe(&Topic::Consensus)?;
+        let vm_events_rx = event_router.subscribe(&Topic::State)?;
+
+        let audit_events_rx = if config.event_store_path.is_some() {
+            setup_audit_topic(&mut event_router, &config.event_router_config);
+            Some(event_router.subscribe(&Topic::Audit)?)
+        } else {
+            None
+        };
 
         let (
             updated_config,
@@ -95,100 +141,441 @@ impl Node {
             state_handle,
             gossip_handle,
             broadcast_controller_handle,
+            state_sync_handle,
             jsonrpc_server_handle,
             txn_validator_handle,
             miner_handle,
+            vm_handle,
+            event_store_handle,
+            mempool_sweeper_handle,
+            mut restarters,
         ) = setup_runtime_components(
             &config,
+            node_status_handle.clone(),
             events_tx.clone(),
             mempool_events_rx,
             vrrbdb_events_rx,
             network_events_rx,
             controller_events_rx,
+            state_sync_events_rx,
             validator_events_rx,
             miner_events_rx,
             jsonrpc_events_rx,
+            jsonrpc_consensus_events_rx,
+            vm_events_rx,
+            audit_events_rx,
         )
         .await?;
 
         config = updated_config;
 
+        let mut modules =
+            ModuleSupervisor::new(config.supervision_config.clone(), event_router.subscriber());
+
+        let module_handles = [
+            (MEMPOOL_MODULE, mempool_handle),
+            (STATE_STORE_MODULE, state_handle),
+            (GOSSIP_NETWORK_MODULE, gossip_handle),
+            (BROADCAST_CONTROLLER_MODULE, broadcast_controller_handle),
+            (STATE_SYNC_MODULE, state_sync_handle),
+            (JSONRPC_SERVER_MODULE, jsonrpc_server_handle),
+            (VALIDATOR_MODULE, txn_validator_handle),
+            (MINER_MODULE, miner_handle),
+            (VM_MODULE, vm_handle),
+            (EVENT_STORE_MODULE, event_store_handle),
+        ];
+
+        for (module, handle) in module_handles {
+            modules.supervise(module, handle, restarters.remove(module));
+        }
+
         // TODO: report error from handle
         let event_router_handle =
             tokio::spawn(async move { event_router.start(&mut events_rx).await });
 
-        Ok(Self {
+        let node = Self {
             config,
-            vm,
             event_router_handle,
-            state_handle,
-            mempool_handle,
-            jsonrpc_server_handle,
-            gossip_handle,
-            broadcast_controller_handle,
+            mempool_sweeper_handle,
+            event_metrics,
+            modules,
             running_status: RuntimeModuleState::Stopped,
+            node_status_handle,
             control_rx,
             events_tx,
-            txn_validator_handle,
-            miner_handle,
+            config_reload_tx,
+            config_reload_rx,
             keypair,
-        })
+        };
+
+        match node.wait_until_ready().await {
+            Ok(_) => Ok(node),
+            Err(err) => {
+                node.tear_down().await;
+                Err(err)
+            },
+        }
     }
 
-    pub async fn wait(mut self) -> anyhow::Result<()> {
-        // TODO: notify bootstrap nodes that this node is joining the network so they
-        // can add it to their peer list
+    /// Waits up to the configured `readiness_timeout` for every runtime
+    /// module to report it is running, then marks the node as ready, which
+    /// lets it announce itself to and accept peers.
+    ///
+    /// Module states are read from the node status handle, which modules
+    /// record their transitions on directly rather than through the lossy
+    /// Control topic.
+    async fn wait_until_ready(&self) -> Result<()> {
+        let timeout = self.config.readiness_timeout;
+
+        // NOTE: actor based modules may only report they're running once they
+        // handle an event, so idle ones are handed a no-op. No module listens
+        // to the Network topic without networking.
+        let topics = [
+            Topic::Storage,
+            Topic::Network,
+            Topic::Consensus,
+            Topic::State,
+            Topic::Audit,
+        ];
+
+        for topic in topics {
+            if topic == Topic::Network && self.config.disable_networking {
+                continue;
+            }
+
+            self.events_tx
+                .send((topic, Event::NoOp))
+                .map_err(|err| NodeError::Other(err.to_string()))?;
+        }
 
-        self.running_status = RuntimeModuleState::Running;
+        let modules_running = async {
+            while !self.node_status_handle.modules_running() {
+                tokio::time::sleep(READINESS_POLL_INTERVAL).await;
+            }
+        };
 
-        // NOTE: wait for stop signal
-        self.control_rx
-            .recv()
+        if tokio::time::timeout(timeout, modules_running)
             .await
-            .ok_or_else(|| NodeError::Other(String::from("failed to receive control signal")))?;
+            .is_err()
+        {
+            let modules = self.node_status_handle.modules_not_running();
+            error!(
+                "modules did not start within {timeout:?}: {}",
+                modules.join(", ")
+            );
+
+            return Err(NodeError::NotReady { timeout, modules });
+        }
+
+        self.node_status_handle.set_ready(true);
+
+        info!("node is ready");
 
-        info!("node received stop signal");
+        if self.config.disable_networking && self.config.min_bootstrap_peers > 0 {
+            info!("networking is disabled, not waiting for a bootstrap quorum");
+        }
+
+        if !self.requires_bootstrap_quorum() {
+            self.node_status_handle.set_operational(true);
+        }
 
-        self.events_tx.send((Topic::Control, Event::Stop))?;
+        Ok(())
+    }
 
-        if let Some(handle) = self.state_handle {
-            handle.await??;
-            info!("shutdown complete for state management module ");
+    /// Whether the node has to be connected to `min_bootstrap_peers` peers
+    /// before it is operational
+    fn requires_bootstrap_quorum(&self) -> bool {
+        self.config.min_bootstrap_peers > 0 && !self.config.disable_networking
+    }
+
+    /// Waits up to `timeout` for the node to be connected to `min_peers`
+    /// peers
+    async fn wait_for_bootstrap_quorum(
+        node_status_handle: NodeStatusHandle,
+        min_peers: usize,
+        timeout: Duration,
+    ) -> Result<()> {
+        info!("syncing until connected to {min_peers} peers");
+
+        let quorum = async {
+            while node_status_handle.peer_count() < min_peers {
+                tokio::time::sleep(BOOTSTRAP_QUORUM_POLL_INTERVAL).await;
+            }
+        };
+
+        if tokio::time::timeout(timeout, quorum).await.is_err() {
+            return Err(NodeError::NoBootstrapQuorum {
+                min_peers,
+                peers: node_status_handle.peer_count(),
+                timeout,
+            });
         }
 
-        if let Some(handle) = self.miner_handle {
-            handle.await??;
-            info!("shutdown complete for mining module ");
+        info!("connected to {} peers", node_status_handle.peer_count());
+
+        Ok(())
+    }
+
+    /// Aborts every task the node started and waits for them to be dropped
+    /// along with whatever they hold
+    async fn tear_down(mut self) {
+        for handle in self.modules.take_all() {
+            handle.abort();
+            let _ = handle.await;
         }
 
-        if let Some(handle) = self.gossip_handle {
-            handle.await??;
-            info!("shutdown complete for gossip module");
+        if let Some(handle) = self.mempool_sweeper_handle.take() {
+            handle.abort();
+            let _ = handle.await;
         }
 
-        if let Some(handle) = self.txn_validator_handle {
-            handle.await??;
-            info!("shutdown complete for mining module ");
+        self.event_router_handle.abort();
+
+        let _ = self.event_router_handle.await;
+    }
+
+    /// Blocks until a stop signal is received, then shuts down every runtime
+    /// module. Configs sent through `config_reload_handle` in the meantime
+    /// are applied through `reload_config`. Modules that fail to stop within
+    /// the configured `shutdown_timeout` are aborted and reported back as a
+    /// `NodeError::PartialShutdown`. Returns the reason the node was asked to
+    /// shut down.
+    ///
+    /// Nodes configured with `min_bootstrap_peers` keep syncing, neither
+    /// mining nor validating, until they're connected to that many peers.
+    /// Should that not happen within the `bootstrap_quorum_timeout`, they
+    /// either become operational anyway or, with
+    /// `exit_without_bootstrap_quorum`, shut down.
+    ///
+    /// Runtime modules that stop in the meantime, be it because they
+    /// panicked, failed or returned, are restarted, shut the node down or
+    /// are let go of, as configured through `supervision_config`.
+    pub async fn wait(mut self) -> anyhow::Result<ShutdownReason> {
+        self.running_status = RuntimeModuleState::Running;
+        self.node_status_handle
+            .set_running_status(RuntimeModuleState::Running);
+
+        let mut awaiting_bootstrap_quorum = self.requires_bootstrap_quorum();
+        let bootstrap_quorum = Self::wait_for_bootstrap_quorum(
+            self.node_status_handle.clone(),
+            self.config.min_bootstrap_peers,
+            self.config.bootstrap_quorum_timeout,
+        );
+        tokio::pin!(bootstrap_quorum);
+
+        // NOTE: wait for stop signal
+        let reason = loop {
+            tokio::select! {
+                quorum = &mut bootstrap_quorum, if awaiting_bootstrap_quorum => {
+                    awaiting_bootstrap_quorum = false;
+
+                    if let Err(err) = quorum {
+                        if self.config.exit_without_bootstrap_quorum {
+                            error!("{err}, shutting down");
+                            break ShutdownReason::FatalError(err.to_string());
+                        }
+
+                        warn!("{err}, mining and validating regardless");
+                    }
+
+                    self.node_status_handle.set_operational(true);
+
+                    info!("node is operational");
+                },
+                exit = self.modules.next_exit() => {
+                    if let Some(reason) = self.modules.handle_exit(exit).await {
+                        break reason;
+                    }
+                },
+                event = self.control_rx.recv() => {
+                    let event = event.ok_or_else(|| {
+                        NodeError::Other(String::from("failed to receive control signal"))
+                    })?;
+
+                    if let Some(reason) = event.shutdown_reason() {
+                        break reason;
+                    }
+                },
+                Some(new_config) = self.config_reload_rx.recv() => {
+                    match self.reload_config(new_config) {
+                        Ok(_) => info!("node config reloaded"),
+                        Err(err) => warn!("node config partially reloaded: {err}"),
+                    }
+                },
+            }
+        };
+
+        info!("node received stop signal: {reason}");
+
+        self.running_status = RuntimeModuleState::Terminating;
+        self.node_status_handle
+            .set_running_status(RuntimeModuleState::Terminating);
+        self.node_status_handle.set_ready(false);
+        self.node_status_handle.set_operational(false);
+
+        self.events_tx.send((
+            Topic::Control,
+            Event::Shutdown {
+                reason: reason.clone(),
+            },
+        ))?;
+
+        // NOTE: the sweeper would otherwise keep queueing sweeps for a
+        // mempool module that's stopping
+        if let Some(handle) = self.mempool_sweeper_handle.take() {
+            handle.abort();
+            let _ = handle.await;
         }
 
-        if let Some(handle) = self.jsonrpc_server_handle {
-            handle.await??;
-            info!("rpc server shut down");
+        let timeout = self.config.shutdown_timeout;
+        let mut failed_modules = vec![];
+
+        for (label, handle) in Self::take_modules_for_shutdown(&mut self.modules) {
+            if let Some(handle) = handle {
+                if let Err(reason) = Self::shutdown_module(label, handle, timeout).await {
+                    failed_modules.push(reason);
+                }
+            }
         }
 
-        self.event_router_handle.await?;
+        match tokio::time::timeout(timeout, &mut self.event_router_handle).await {
+            Ok(Ok(_)) => info!("event router shut down"),
+            Ok(Err(err)) => {
+                error!("event router failed to shut down: {err}");
+                failed_modules.push(format!("event router: {err}"));
+            },
+            Err(_) => {
+                error!("event router did not stop within {timeout:?}, aborting");
+                self.event_router_handle.abort();
+                failed_modules.push(String::from("event router: timed out"));
+            },
+        }
 
-        info!("node shutdown complete");
+        // NOTE: aborted modules never got to report they stopped. Every module
+        // task has either finished or been aborted at this point.
+        for module_name in self.node_status_handle.module_states().keys() {
+            self.node_status_handle
+                .set_module_state(module_name, RuntimeModuleState::Stopped);
+        }
 
         self.running_status = RuntimeModuleState::Stopped;
+        self.node_status_handle
+            .set_running_status(RuntimeModuleState::Stopped);
 
-        Ok(())
+        if !failed_modules.is_empty() {
+            return Err(NodeError::PartialShutdown(failed_modules).into());
+        }
+
+        info!("node shutdown complete");
+
+        Ok(reason)
+    }
+
+    /// Waits up to `timeout` for a module's task to finish, aborting it if it
+    /// doesn't. Returns a description of the failure if the module did not
+    /// exit cleanly.
+    /// Stops supervising every runtime module, returning their tasks in the
+    /// order they're shut down in along with the label they're reported under
+    fn take_modules_for_shutdown(
+        modules: &mut ModuleSupervisor,
+    ) -> [(&'static str, Option<JoinHandle<Result<()>>>); 10] {
+        [
+            ("state management module", modules.take(STATE_STORE_MODULE)),
+            ("mempool module", modules.take(MEMPOOL_MODULE)),
+            ("mining module", modules.take(MINER_MODULE)),
+            ("gossip module", modules.take(GOSSIP_NETWORK_MODULE)),
+            (
+                "broadcast controller",
+                modules.take(BROADCAST_CONTROLLER_MODULE),
+            ),
+            ("state sync module", modules.take(STATE_SYNC_MODULE)),
+            ("txn validator module", modules.take(VALIDATOR_MODULE)),
+            ("rpc server", modules.take(JSONRPC_SERVER_MODULE)),
+            ("vm module", modules.take(VM_MODULE)),
+            ("event store module", modules.take(EVENT_STORE_MODULE)),
+        ]
+    }
+
+    async fn shutdown_module(
+        label: &str,
+        mut handle: JoinHandle<Result<()>>,
+        timeout: Duration,
+    ) -> std::result::Result<(), String> {
+        match tokio::time::timeout(timeout, &mut handle).await {
+            Ok(Ok(Ok(_))) => {
+                info!("shutdown complete for {label}");
+                Ok(())
+            },
+            Ok(Ok(Err(err))) => {
+                error!("{label} exited with an error: {err}");
+                Err(format!("{label}: {err}"))
+            },
+            Ok(Err(err)) => {
+                error!("failed to join {label}: {err}");
+                Err(format!("{label}: {err}"))
+            },
+            Err(_) => {
+                error!("{label} did not stop within {timeout:?}, aborting");
+                handle.abort();
+                Err(format!("{label}: timed out"))
+            },
+        }
     }
 
     pub async fn config(&self) -> NodeConfig {
         self.config.clone()
     }
 
+    /// Applies the settings within `new_config` that can be changed while
+    /// the node is running: the mempool's capacity, the log level of the
+    /// global subscriber and the shutdown timeout.
+    ///
+    /// Every other setting that differs from the current config is left
+    /// untouched and listed in a `NodeError::RestartRequired`, which is
+    /// returned after the reloadable settings have been applied. So is the
+    /// log level if the global subscriber can't change it.
+    pub fn reload_config(&mut self, new_config: NodeConfig) -> Result<()> {
+        let mut restart_required = self.config.restart_required_changes(&new_config);
+
+        if new_config.mempool_max_size != self.config.mempool_max_size {
+            self.events_tx
+                .send((
+                    Topic::Storage,
+                    Event::MempoolMaxSizeUpdated(new_config.mempool_max_size),
+                ))
+                .map_err(|err| NodeError::Other(err.to_string()))?;
+
+            self.config.mempool_max_size = new_config.mempool_max_size;
+        }
+
+        if new_config.log_level != self.config.log_level {
+            match TelemetrySubscriber::set_level(new_config.log_level) {
+                Ok(_) => self.config.log_level = new_config.log_level,
+                Err(err) => {
+                    warn!("failed to change the log level: {err}");
+                    restart_required.push("log_level");
+                },
+            }
+        }
+
+        // NOTE: read when the node shuts down
+        self.config.shutdown_timeout = new_config.shutdown_timeout;
+
+        if !restart_required.is_empty() {
+            return Err(NodeError::RestartRequired(
+                restart_required.into_iter().map(String::from).collect(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Returns a sender through which a running node can be handed a new
+    /// config to reload, see `reload_config`
+    pub fn config_reload_handle(&self) -> UnboundedSender<NodeConfig> {
+        self.config_reload_tx.clone()
+    }
+
     /// Returns a string representation of the Node id
     pub fn id(&self) -> String {
         self.config.id.clone()
@@ -222,6 +609,30 @@ impl Node {
         self.running_status.clone()
     }
 
+    /// Whether every runtime module is running and the node takes part in
+    /// the network. Nodes are ready once `start` returns and stop being so
+    /// as soon as they start shutting down.
+    pub fn is_ready(&self) -> bool {
+        self.node_status_handle.is_ready()
+    }
+
+    /// Whether the node is connected to as many peers as it requires and
+    /// mines and validates transactions, see `wait`
+    pub fn is_operational(&self) -> bool {
+        self.node_status_handle.is_operational()
+    }
+
+    /// Returns the latest reported state of each of the node's runtime
+    /// modules, keyed by module name
+    pub fn module_statuses(&self) -> HashMap<String, RuntimeModuleState> {
+        self.node_status_handle.module_states()
+    }
+
+    /// Returns a handle to the node's live runtime status
+    pub fn status_handle(&self) -> NodeStatusHandle {
+        self.node_status_handle.clone()
+    }
+
     pub fn keypair(&self) -> KeyPair {
         self.keypair.clone()
     }
@@ -242,14 +653,64 @@ impl Node {
         self.config.jsonrpc_server_address
     }
 
-    fn setup_event_routing_system() -> EventRouter {
-        let mut event_router = EventRouter::new();
-        event_router.add_topic(Topic::Control, Some(1));
-        event_router.add_topic(Topic::State, Some(1));
-        event_router.add_topic(Topic::Network, Some(100));
-        event_router.add_topic(Topic::Consensus, Some(100));
-        event_router.add_topic(Topic::Storage, Some(100));
+    /// Returns a handle to the counts of events the node's event router
+    /// received on each topic
+    pub fn processed_events(&self) -> EventCounter {
+        self.event_metrics.published.clone()
+    }
 
-        event_router
+    /// Returns a handle to the counts of events the node's event router
+    /// dropped on each topic
+    pub fn dropped_events(&self) -> EventCounter {
+        self.event_metrics.dropped.clone()
+    }
+
+    /// Returns a handle to the counts of events the node's event router
+    /// received, delivered and dropped on each topic
+    pub fn event_metrics(&self) -> EventRouterMetrics {
+        self.event_metrics.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use vrrb_config::SupervisionConfig;
+    use vrrb_core::event_router::EventRouter;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn shutdown_takes_every_supervised_module() {
+        let mut router = EventRouter::new();
+        router.add_topic(Topic::State, Some(10));
+
+        let mut modules = ModuleSupervisor::new(SupervisionConfig::default(), router.subscriber());
+
+        let supervised = [
+            MEMPOOL_MODULE,
+            STATE_STORE_MODULE,
+            GOSSIP_NETWORK_MODULE,
+            BROADCAST_CONTROLLER_MODULE,
+            STATE_SYNC_MODULE,
+            JSONRPC_SERVER_MODULE,
+            VALIDATOR_MODULE,
+            MINER_MODULE,
+            VM_MODULE,
+            EVENT_STORE_MODULE,
+        ];
+
+        for module in supervised {
+            modules.supervise(module, Some(tokio::spawn(std::future::pending())), None);
+        }
+
+        let handles = Node::take_modules_for_shutdown(&mut modules);
+
+        assert!(modules.is_empty());
+
+        for (label, handle) in handles {
+            handle
+                .unwrap_or_else(|| panic!("{label} wasn't taken"))
+                .abort();
+        }
     }
 }
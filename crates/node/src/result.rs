@@ -1,4 +1,7 @@
-use std::net::AddrParseError;
+use std::{
+    net::{AddrParseError, SocketAddr},
+    time::Duration,
+};
 
 use network::types::config::BroadCastError;
 use thiserror::Error;
@@ -27,6 +30,33 @@ pub enum NodeError {
     #[error("{0}")]
     Core(#[from] vrrb_core::Error),
 
+    #[error("{module} could not bind to {addr}: {source}")]
+    BindFailed {
+        module: &'static str,
+        addr: SocketAddr,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    #[error("modules failed to shut down cleanly: {}", .0.join(", "))]
+    PartialShutdown(Vec<String>),
+
+    #[error("modules did not start within {timeout:?}: {}", .modules.join(", "))]
+    NotReady {
+        timeout: Duration,
+        modules: Vec<String>,
+    },
+
+    #[error("only {peers} of the {min_peers} peers required were connected within {timeout:?}")]
+    NoBootstrapQuorum {
+        min_peers: usize,
+        peers: usize,
+        timeout: Duration,
+    },
+
+    #[error("settings require a restart to take effect: {}", .0.join(", "))]
+    RestartRequired(Vec<String>),
+
     #[error("{0}")]
     Other(String),
 }
@@ -1,51 +1,80 @@
 // use blockchain::blockchain::Blockchain;
-use tokio::sync::mpsc::error::TryRecvError;
-use vrrb_core::event_router::Event;
+use async_trait::async_trait;
+use tokio::sync::{broadcast::Receiver, mpsc::UnboundedSender};
+use vrrb_core::{
+    event_router::{recv_reporting_lag, DirectedEvent, Event, Topic},
+    node_status::NodeStatusHandle,
+};
 
-use crate::{result::Result, RuntimeModule};
+use crate::{report_state_change, result::Result, RuntimeModule, RuntimeModuleState};
+
+pub struct BlockchainModuleConfig {
+    pub events_tx: UnboundedSender<DirectedEvent>,
+    pub node_status_handle: NodeStatusHandle,
+}
 
 pub struct BlockchainModule {
+    running_status: RuntimeModuleState,
+    events_tx: UnboundedSender<DirectedEvent>,
+    node_status_handle: NodeStatusHandle,
     // blockchain: Blockchain,
     // ::new(&format!("{}/test_chain_{}.db", directory, file_suffix));
 }
 
 impl BlockchainModule {
-    pub fn new() -> Self {
+    pub fn new(config: BlockchainModuleConfig) -> Self {
         Self {
+            running_status: RuntimeModuleState::Stopped,
+            events_tx: config.events_tx,
+            node_status_handle: config.node_status_handle,
             // blockchain: Blockchain::new("dummy.db"),
         }
     }
+
+    fn set_running_status(&mut self, status: RuntimeModuleState, reason: Option<String>) {
+        report_state_change(
+            &self.events_tx,
+            &self.node_status_handle,
+            self.name(),
+            self.running_status.clone(),
+            status.clone(),
+            reason,
+        );
+        self.running_status = status;
+    }
 }
 
+#[async_trait]
 impl RuntimeModule for BlockchainModule {
     fn name(&self) -> String {
         String::from("Blockchain module")
     }
 
-    fn status(&self) -> crate::RuntimeModuleState {
-        todo!()
+    fn status(&self) -> RuntimeModuleState {
+        self.running_status.clone()
     }
 
-    fn start(
-        &mut self,
-        control_rx: &mut tokio::sync::mpsc::UnboundedReceiver<Event>,
-    ) -> Result<()> {
-        // TODO: rethink this loop
-        loop {
-            match control_rx.try_recv() {
-                Ok(sig) => {
-                    telemetry::info!("Received stop signal");
-                    break;
-                },
-                Err(err) if err == TryRecvError::Disconnected => {
-                    telemetry::warn!("Failed to process stop signal. Reason: {0}", err);
-                    telemetry::warn!("{} shutting down", self.name());
-                    break;
-                },
-                _ => {},
+    async fn start(&mut self, events_rx: &mut Receiver<Event>) -> Result<()> {
+        self.set_running_status(RuntimeModuleState::Running, None);
+
+        // NOTE: awaiting events rather than polling for them keeps the module
+        // from spinning while the node is idle
+        while let Ok(event) = recv_reporting_lag(events_rx, &Topic::Control, &self.events_tx).await
+        {
+            if event.is_shutdown() {
+                telemetry::info!("{} received stop signal. Stopping", self.name());
+
+                self.set_running_status(
+                    RuntimeModuleState::Terminating,
+                    Some(String::from("stop signal received")),
+                );
+
+                break;
             }
         }
 
+        self.set_running_status(RuntimeModuleState::Stopped, None);
+
         Ok(())
     }
 }
@@ -599,3 +628,65 @@ pub fn setup_blockchain() {
     });
 }
 */
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::{broadcast, mpsc::unbounded_channel};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn blockchain_module_reports_its_lifecycle() {
+        let (events_tx, mut events_rx) = unbounded_channel::<DirectedEvent>();
+        let node_status_handle = NodeStatusHandle::new();
+
+        let mut module = BlockchainModule::new(BlockchainModuleConfig {
+            events_tx,
+            node_status_handle: node_status_handle.clone(),
+        });
+        let name = module.name();
+        let (ctrl_tx, mut ctrl_rx) = broadcast::channel::<Event>(10);
+
+        let handle = tokio::spawn(async move {
+            module.start(&mut ctrl_rx).await.unwrap();
+            module
+        });
+
+        while node_status_handle.module_states().get(&name) != Some(&RuntimeModuleState::Running) {
+            tokio::task::yield_now().await;
+        }
+
+        ctrl_tx.send(Event::Stop).unwrap();
+        let module = handle.await.unwrap();
+
+        assert_eq!(module.status(), RuntimeModuleState::Stopped);
+        assert_eq!(
+            node_status_handle.module_states().get(&name),
+            Some(&RuntimeModuleState::Stopped)
+        );
+
+        let mut transitions = vec![];
+        while let Ok((topic, event)) = events_rx.try_recv() {
+            if let Event::ModuleStateChanged {
+                module_name,
+                from,
+                to,
+                ..
+            } = event
+            {
+                assert_eq!(topic, Topic::Control);
+                assert_eq!(module_name, name);
+                transitions.push((from, to));
+            }
+        }
+
+        assert_eq!(
+            transitions,
+            vec![
+                (RuntimeModuleState::Stopped, RuntimeModuleState::Running),
+                (RuntimeModuleState::Running, RuntimeModuleState::Terminating),
+                (RuntimeModuleState::Terminating, RuntimeModuleState::Stopped),
+            ]
+        );
+    }
+}
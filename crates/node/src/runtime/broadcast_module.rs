@@ -3,6 +3,7 @@ use std::{collections::HashSet, net::SocketAddr, result::Result as StdResult};
 use async_trait::async_trait;
 use bytes::Bytes;
 use network::{
+    config::BroadcastEngineConfig,
     message::{Message, MessageBody},
     network::BroadcastEngine,
 };
@@ -21,17 +22,29 @@ use tokio::{
     task::JoinHandle,
 };
 use uuid::Uuid;
-use vrrb_core::event_router::{DirectedEvent, Event};
+use vrrb_core::{
+    event_router::{DirectedEvent, Event},
+    node_status::NodeStatusHandle,
+};
 
-use crate::{NodeError, Result, RuntimeModule, RuntimeModuleState};
+use crate::{
+    report_state_change,
+    runtime_state_from_actor_state,
+    NodeError,
+    Result,
+    RuntimeModule,
+    RuntimeModuleState,
+};
 
 pub struct BroadcastModuleConfig {
     pub events_tx: tokio::sync::mpsc::UnboundedSender<DirectedEvent>,
     pub node_type: NodeType,
     pub vrrbdb_read_handle: VrrbDbReadHandle,
-    pub udp_gossip_address_port: u16,
-    pub raptorq_gossip_address_port: u16,
+    pub node_status_handle: NodeStatusHandle,
+    pub udp_gossip_address: SocketAddr,
+    pub raptorq_gossip_address: SocketAddr,
     pub node_id: PeerId,
+    pub broadcast_engine_config: BroadcastEngineConfig,
 }
 
 // TODO: rename to GossipNetworkModule
@@ -40,6 +53,7 @@ pub struct BroadcastModule {
     id: Uuid,
     events_tx: tokio::sync::mpsc::UnboundedSender<DirectedEvent>,
     vrrbdb_read_handle: VrrbDbReadHandle,
+    node_status_handle: NodeStatusHandle,
     // broadcast_handle: JoinHandle<Result<()>>,
     addr: SocketAddr,
     // controller_rx: MpscReceiver<Event>,
@@ -48,11 +62,17 @@ pub struct BroadcastModule {
 
 impl BroadcastModule {
     pub async fn new(config: BroadcastModuleConfig) -> Result<Self> {
-        let broadcast_engine = BroadcastEngine::new(config.udp_gossip_address_port, 32)
-            .await
-            .map_err(|err| {
-                NodeError::Other(format!("unable to setup broadcast engine: {}", err))
-            })?;
+        let broadcast_engine = BroadcastEngine::new_with_config(
+            config.udp_gossip_address,
+            32,
+            config.broadcast_engine_config.clone(),
+        )
+        .await
+        .map_err(|err| NodeError::BindFailed {
+            module: "broadcast_module",
+            addr: config.udp_gossip_address,
+            source: Box::new(err),
+        })?;
 
         let addr = broadcast_engine.local_addr();
 
@@ -60,6 +80,7 @@ impl BroadcastModule {
             events_tx: config.events_tx,
             status: ActorState::Stopped,
             vrrbdb_read_handle: config.vrrbdb_read_handle,
+            node_status_handle: config.node_status_handle,
             addr,
             // broadcast_handle,
             // controller_rx,
@@ -91,54 +112,69 @@ impl Handler<Event> for BroadcastModule {
     }
 
     fn set_status(&mut self, actor_status: ActorState) {
+        report_state_change(
+            &self.events_tx,
+            &self.node_status_handle,
+            self.name(),
+            runtime_state_from_actor_state(&self.status),
+            runtime_state_from_actor_state(&actor_status),
+            None,
+        );
         self.status = actor_status;
     }
 
     async fn handle(&mut self, event: Event) -> theater::Result<ActorState> {
-        if event == Event::Stop {
-            info!("{0} received stop signal. Stopping", self.name());
-            return Ok(ActorState::Terminating);
+        match event {
+            Event::Stop | Event::Shutdown { .. } => {
+                info!("{0} received stop signal. Stopping", self.name());
+                return Ok(ActorState::Terminating);
+            },
+            Event::PeerJoined(peer) => self.node_status_handle.peer_joined(peer.node_type),
+            Event::PeerLeft(peer) => self.node_status_handle.peer_left(peer.node_type),
+            _ => {},
         }
 
-        // do something with the event
-
         Ok(ActorState::Running)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::sync::mpsc::channel;
-
     use primitives::NodeType;
-    use storage::vrrbdb::{VrrbDb, VrrbDbConfig};
-    use tokio::sync::mpsc::unbounded_channel;
-    use vrrb_core::event_router::Event;
+    use serial_test::serial;
 
-    use super::{BroadcastModule, BroadcastModuleConfig};
+    use crate::test_utils::spawn_test_network;
 
     #[tokio::test]
-    #[ignore]
+    #[serial]
     async fn test_broadcast_module() {
-        let (internal_events_tx, mut internal_events_rx) = unbounded_channel();
-
-        let node_id = uuid::Uuid::new_v4().to_string().into_bytes();
-
-        let db_config = VrrbDbConfig::default();
-
-        let db = VrrbDb::new(db_config);
-
-        let vrrbdb_read_handle = db.read_handle();
-
-        let config = BroadcastModuleConfig {
-            events_tx: internal_events_tx,
-            vrrbdb_read_handle,
-            node_type: NodeType::Full,
-            udp_gossip_address_port: 0,
-            raptorq_gossip_address_port: 0,
-            node_id,
-        };
-
-        let broadcast_module = BroadcastModule::new(config).await.unwrap();
+        let nodes = spawn_test_network(3).await;
+
+        let bootstrap_node = &nodes[0];
+        assert_eq!(bootstrap_node.node_type(), NodeType::Bootstrap);
+        assert_eq!(bootstrap_node.status_handle().peer_count(), 2);
+        assert_eq!(
+            bootstrap_node
+                .status_handle()
+                .peer_node_types()
+                .get(&NodeType::Full),
+            Some(&2)
+        );
+
+        // NOTE: the last node to join learns about everyone through the
+        // bootstrap node's handshake ack
+        let last_node = &nodes[2];
+        assert_eq!(last_node.status_handle().peer_count(), 2);
+        assert_eq!(
+            last_node
+                .status_handle()
+                .peer_node_types()
+                .get(&NodeType::Bootstrap),
+            Some(&1)
+        );
+
+        for node in nodes {
+            node.stop().await.unwrap();
+        }
     }
 }
@@ -112,7 +112,7 @@ impl Handler<Event> for DkgModule {
 
     async fn handle(&mut self, event: Event) -> theater::Result<ActorState> {
         match event {
-            Event::Stop => {
+            Event::Stop | Event::Shutdown { .. } => {
                 return Ok(ActorState::Stopped);
             },
             Event::DkgInitiate => {
@@ -0,0 +1,151 @@
+use async_trait::async_trait;
+use storage::vrrbdb::EventStore;
+use telemetry::{error, info};
+use theater::{ActorId, ActorLabel, ActorState, Handler};
+use vrrb_core::{
+    event_router::{DirectedEvent, Event},
+    node_status::NodeStatusHandle,
+};
+
+use crate::{report_state_change, runtime_state_from_actor_state};
+
+pub struct EventStoreModuleConfig {
+    pub events_tx: tokio::sync::mpsc::UnboundedSender<DirectedEvent>,
+    pub event_store: EventStore,
+    pub node_status_handle: NodeStatusHandle,
+}
+
+/// Keeps a durable log of the significant events published to the `Audit`
+/// topic
+#[derive(Debug)]
+pub struct EventStoreModule {
+    status: ActorState,
+    label: ActorLabel,
+    id: ActorId,
+    events_tx: tokio::sync::mpsc::UnboundedSender<DirectedEvent>,
+    event_store: EventStore,
+    node_status_handle: NodeStatusHandle,
+}
+
+impl EventStoreModule {
+    pub fn new(config: EventStoreModuleConfig) -> Self {
+        Self {
+            events_tx: config.events_tx,
+            event_store: config.event_store,
+            node_status_handle: config.node_status_handle,
+            status: ActorState::Stopped,
+            label: String::from("EventStore"),
+            id: uuid::Uuid::new_v4().to_string(),
+        }
+    }
+
+    fn name(&self) -> String {
+        String::from("Event store module")
+    }
+}
+
+#[async_trait]
+impl Handler<Event> for EventStoreModule {
+    fn id(&self) -> ActorId {
+        self.id.clone()
+    }
+
+    fn label(&self) -> ActorLabel {
+        self.name()
+    }
+
+    fn status(&self) -> ActorState {
+        self.status.clone()
+    }
+
+    fn set_status(&mut self, actor_status: ActorState) {
+        report_state_change(
+            &self.events_tx,
+            &self.node_status_handle,
+            self.name(),
+            runtime_state_from_actor_state(&self.status),
+            runtime_state_from_actor_state(&actor_status),
+            None,
+        );
+        self.status = actor_status;
+    }
+
+    fn on_stop(&self) {
+        info!(
+            "{}-{} received stop signal. Stopping",
+            self.name(),
+            self.label()
+        );
+    }
+
+    async fn handle(&mut self, event: Event) -> theater::Result<ActorState> {
+        match event {
+            Event::Stop | Event::Shutdown { .. } => {
+                return Ok(ActorState::Stopped);
+            },
+
+            // NOTE: a failed write is logged rather than treated as fatal, the
+            // audit log is a record of the node's activity, not a dependency
+            // of it
+            event if event.is_auditable() => {
+                if let Err(err) = self.event_store.append(event) {
+                    error!("failed to persist event: {err}");
+                }
+            },
+
+            _ => {},
+        }
+
+        Ok(ActorState::Running)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use theater::{Actor, ActorImpl};
+    use tokio::sync::{broadcast, mpsc::unbounded_channel};
+    use vrrb_core::txn::null_txn;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn auditable_events_are_persisted() {
+        let (events_tx, _events_rx) = unbounded_channel::<DirectedEvent>();
+
+        let event_store = EventStore::new(
+            &std::env::temp_dir().join(vrrb_core::helpers::generate_random_string()),
+        )
+        .unwrap();
+
+        let module = EventStoreModule::new(EventStoreModuleConfig {
+            events_tx,
+            event_store: event_store.clone(),
+            node_status_handle: NodeStatusHandle::default(),
+        });
+
+        let mut module = ActorImpl::new(module);
+
+        let (ctrl_tx, mut ctrl_rx) = broadcast::channel::<Event>(10);
+
+        let handle = tokio::spawn(async move {
+            module.start(&mut ctrl_rx).await.unwrap();
+        });
+
+        let committed = Event::TxnValidated(null_txn());
+
+        ctrl_tx.send(Event::NoOp).unwrap();
+        ctrl_tx.send(committed.clone()).unwrap();
+        ctrl_tx.send(Event::Stop).unwrap();
+
+        handle.await.unwrap();
+
+        let events = event_store
+            .get_events(0, 10)
+            .unwrap()
+            .into_iter()
+            .map(|record| record.event)
+            .collect::<Vec<Event>>();
+
+        assert_eq!(events, vec![committed]);
+    }
+}
@@ -144,7 +144,7 @@ impl Handler<Event> for FarmerHarvesterModule {
 
     async fn handle(&mut self, event: Event) -> theater::Result<ActorState> {
         match event {
-            Event::Stop => {
+            Event::Stop | Event::Shutdown { .. } => {
                 return Ok(ActorState::Stopped);
             },
             Event::Farm => {
@@ -275,7 +275,6 @@ mod tests {
 
     use dkg_engine::{test_utils, types::config::ThresholdConfig};
     use primitives::{NodeType, QuorumType::Farmer};
-    use secp256k1::Message;
     use theater::ActorImpl;
     use vrrb_core::{
         cache,
@@ -371,10 +370,7 @@ mod tests {
         let txn_amount: u128 = 1010101;
 
         for n in 1..101 {
-            let sig = keypair
-                .miner_kp
-                .0
-                .sign_ecdsa(Message::from_hashed_data::<secp256k1::hashes::sha256::Hash>(b"vrrb"));
+            let sig = keypair.txn_keypair().sign(b"vrrb");
 
             let mut txn = Txn::new(NewTxnArgs {
                 timestamp: 0,
@@ -470,10 +466,7 @@ mod tests {
         let txn_amount: u128 = 1010101;
 
         for n in 0..1 {
-            let sig = keypair
-                .miner_kp
-                .0
-                .sign_ecdsa(Message::from_hashed_data::<secp256k1::hashes::sha256::Hash>(b"vrrb"));
+            let sig = keypair.txn_keypair().sign(b"vrrb");
 
             let mut txn = Txn::new(NewTxnArgs {
                 timestamp: 0,
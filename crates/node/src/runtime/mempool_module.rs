@@ -1,25 +1,50 @@
-use std::{hash::Hash, path::PathBuf};
+use std::{
+    hash::Hash,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 use async_trait::async_trait;
 use lr_trie::ReadHandleFactory;
-use mempool::LeftRightMempool;
+use mempool::{LeftRightMempool, MempoolEvictionPolicy, MempoolInsertOutcome};
 use patriecia::{db::MemoryDB, inner::InnerTrie};
 use storage::vrrbdb::{VrrbDb, VrrbDbReadHandle};
-use telemetry::info;
+use telemetry::{error, info};
 use theater::{Actor, ActorId, ActorLabel, ActorState, Handler, Message, TheaterError};
-use tokio::sync::broadcast::error::TryRecvError;
+use tokio::{sync::broadcast::error::TryRecvError, task::JoinHandle};
 use vrrb_core::{
     event_router::{DirectedEvent, Event, Topic},
-    txn::Txn,
+    node_status::NodeStatusHandle,
+    txn::{TransactionDigest, TxNonce, TxTimestamp, Txn, ValidationError},
 };
 
-use crate::{result::Result, NodeError, RuntimeModule};
+use crate::{
+    report_state_change,
+    result::Result,
+    runtime_state_from_actor_state,
+    NodeError,
+    RuntimeModule,
+};
 
 pub struct MempoolModuleConfig {
     pub mempool: LeftRightMempool,
     pub events_tx: tokio::sync::mpsc::UnboundedSender<DirectedEvent>,
+    /// Used to look up the nonce of the accounts txns are sent from
+    pub vrrbdb_read_handle: VrrbDbReadHandle,
+    /// Where pending transactions are persisted when the module stops
+    pub snapshot_path: Option<PathBuf>,
+    /// Upper bound on the amount of pending transactions. Unbounded if unset
+    pub max_size: Option<usize>,
+    pub eviction_policy: MempoolEvictionPolicy,
+    /// Time transactions are kept before they expire
+    pub txn_ttl: Duration,
+    pub node_status_handle: NodeStatusHandle,
 }
 
+/// Longest interval at which the mempool is swept for expired transactions,
+/// shorter TTLs are swept at every TTL
+pub const MEMPOOL_SWEEP_INTERVAL: Duration = Duration::from_secs(10);
+
 #[derive(Debug)]
 pub struct MempoolModule {
     mempool: LeftRightMempool,
@@ -27,13 +52,26 @@ pub struct MempoolModule {
     label: ActorLabel,
     id: ActorId,
     events_tx: tokio::sync::mpsc::UnboundedSender<DirectedEvent>,
+    vrrbdb_read_handle: VrrbDbReadHandle,
+    snapshot_path: Option<PathBuf>,
+    eviction_policy: MempoolEvictionPolicy,
+    txn_ttl: Duration,
+    node_status_handle: NodeStatusHandle,
 }
 
 impl MempoolModule {
     pub fn new(config: MempoolModuleConfig) -> Self {
+        let mut mempool = config.mempool;
+        mempool.set_max_size(config.max_size, config.eviction_policy);
+
         Self {
-            mempool: config.mempool,
+            mempool,
             events_tx: config.events_tx,
+            vrrbdb_read_handle: config.vrrbdb_read_handle,
+            snapshot_path: config.snapshot_path,
+            eviction_policy: config.eviction_policy,
+            txn_ttl: config.txn_ttl,
+            node_status_handle: config.node_status_handle,
             status: ActorState::Stopped,
             label: String::from("State"),
             id: uuid::Uuid::new_v4().to_string(),
@@ -45,6 +83,108 @@ impl MempoolModule {
     fn name(&self) -> String {
         String::from("Mempool module")
     }
+
+    fn save_snapshot(&self) {
+        if let Some(path) = &self.snapshot_path {
+            match self.mempool.save_snapshot(path) {
+                Ok(_) => info!(
+                    "Saved {} pending transactions to {}",
+                    self.mempool.size(),
+                    path.display()
+                ),
+                Err(err) => error!("Failed to save mempool snapshot: {err}"),
+            }
+        }
+    }
+
+    /// Drops the transactions that have been pending for longer than their
+    /// TTL as of `now`, a Unix timestamp in seconds, letting the rest of the
+    /// node know through `Event::TxnExpired`s
+    fn remove_expired_txns(&mut self, now: TxTimestamp) -> Result<()> {
+        let expired = self.mempool.remove_expired(self.txn_ttl, now);
+
+        if !expired.is_empty() {
+            info!(
+                "Dropped {} transactions pending for longer than {:?}",
+                expired.len(),
+                self.txn_ttl
+            );
+        }
+
+        for record in expired {
+            let event = Event::TxnExpired {
+                txn_hash: record.txn.digest(),
+            };
+
+            self.events_tx
+                .send((Topic::Storage, event))
+                .map_err(|err| NodeError::Other(err.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Lets the rest of the node know about the txns evicted to keep the
+    /// mempool within its maximum size through `Event::TxnEvicted`s
+    fn report_evicted_txns(&mut self) -> Result<()> {
+        for txn_hash in self.mempool.take_evicted() {
+            self.events_tx
+                .send((Topic::Storage, Event::TxnEvicted { txn_hash }))
+                .map_err(|err| NodeError::Other(err.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Nonce the next txn sent from `txn`'s account is expected to have
+    fn next_nonce_of(&self, txn: &Txn) -> TxNonce {
+        self.vrrbdb_read_handle
+            .get_account(&txn.sender())
+            .map(|account| account.nonce as TxNonce)
+            .unwrap_or_default()
+            + 1
+    }
+
+    fn reject(&self, txn_hash: TransactionDigest, reason: ValidationError) -> Result<()> {
+        self.events_tx
+            .send((Topic::Storage, Event::TxnRejected { txn_hash, reason }))
+            .map_err(|err| NodeError::Other(err.to_string()))
+    }
+}
+
+/// Periodically asks the mempool module to drop its expired transactions
+/// through `Event::MempoolSweepDue`s, sweeps never hold up inserts in the
+/// meantime. Runs for as long as the node's event router does, or until the
+/// returned handle is aborted.
+pub fn spawn_mempool_sweeper(
+    events_tx: tokio::sync::mpsc::UnboundedSender<DirectedEvent>,
+    txn_ttl: Duration,
+) -> JoinHandle<Result<()>> {
+    // NOTE: transactions are timestamped to the second, sweeping any more
+    // often would be pointless
+    let interval = MEMPOOL_SWEEP_INTERVAL
+        .min(txn_ttl)
+        .max(Duration::from_secs(1));
+
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(interval).await;
+
+            if events_tx
+                .send((Topic::Storage, Event::MempoolSweepDue))
+                .is_err()
+            {
+                return Ok(());
+            }
+        }
+    })
+}
+
+fn unix_timestamp() -> TxTimestamp {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() as TxTimestamp)
+        .unwrap_or_default()
 }
 
 #[async_trait]
@@ -62,6 +202,14 @@ impl Handler<Event> for MempoolModule {
     }
 
     fn set_status(&mut self, actor_status: ActorState) {
+        report_state_change(
+            &self.events_tx,
+            &self.node_status_handle,
+            self.name(),
+            runtime_state_from_actor_state(&self.status),
+            runtime_state_from_actor_state(&actor_status),
+            None,
+        );
         self.status = actor_status;
     }
 
@@ -75,23 +223,79 @@ impl Handler<Event> for MempoolModule {
 
     async fn handle(&mut self, event: Event) -> theater::Result<ActorState> {
         match event {
-            Event::Stop => {
+            Event::Stop | Event::Shutdown { .. } => {
+                self.save_snapshot();
                 return Ok(ActorState::Stopped);
             },
 
             Event::BlockReceived => {},
 
+            Event::MempoolMaxSizeUpdated(max_size) => {
+                info!("Updating mempool max size to {max_size:?}");
+                self.mempool.set_max_size(max_size, self.eviction_policy);
+                self.report_evicted_txns()
+                    .map_err(|err| TheaterError::Other(err.to_string()))?;
+            },
+
+            Event::MempoolSweepDue => {
+                self.remove_expired_txns(unix_timestamp())
+                    .map_err(|err| TheaterError::Other(err.to_string()))?;
+            },
+
             Event::NewTxnCreated(txn) => {
                 info!("Storing transaction in mempool for validation");
 
                 let txn_hash = txn.digest();
+                let nonce = txn.nonce;
+                let next_nonce = self.next_nonce_of(&txn);
 
-                self.mempool
-                    .insert(txn)
+                let outcome = self
+                    .mempool
+                    .insert_at_nonce(txn, next_nonce)
                     .map_err(|err| TheaterError::Other(err.to_string()))?;
 
+                self.report_evicted_txns()
+                    .map_err(|err| TheaterError::Other(err.to_string()))?;
+
+                match outcome {
+                    MempoolInsertOutcome::Duplicate => {
+                        info!("Transaction {} is already in mempool", txn_hash);
+
+                        self.reject(txn_hash, ValidationError::Duplicate)
+                            .map_err(|err| TheaterError::Other(err.to_string()))?;
+
+                        return Ok(ActorState::Running);
+                    },
+                    MempoolInsertOutcome::NonceTooFarAhead { max } => {
+                        info!(
+                            "Transaction {} is too far ahead of its sender's nonce",
+                            txn_hash
+                        );
+
+                        self.reject(txn_hash, ValidationError::NonceTooHigh { max, got: nonce })
+                            .map_err(|err| TheaterError::Other(err.to_string()))?;
+
+                        return Ok(ActorState::Running);
+                    },
+                    MempoolInsertOutcome::Queued => {
+                        info!(
+                            "Transaction {} queued until the nonces before {} arrive",
+                            txn_hash, nonce
+                        );
+
+                        return Ok(ActorState::Running);
+                    },
+                    MempoolInsertOutcome::Evicted => {
+                        info!("Transaction {} was evicted from the full mempool", txn_hash);
+
+                        return Ok(ActorState::Running);
+                    },
+                    MempoolInsertOutcome::Inserted => {},
+                }
+
+                // NOTE: lets the miner know there are new txns worth mining
                 self.events_tx
-                    .send((Topic::Storage, Event::TxnAddedToMempool(txn_hash.clone())))
+                    .send((Topic::Consensus, Event::TxnAddedToMempool(txn_hash.clone())))
                     .map_err(|err| TheaterError::Other(err.to_string()))?;
 
                 info!("Transaction {} sent to mempool", txn_hash);
@@ -103,6 +307,14 @@ impl Handler<Event> for MempoolModule {
                     .map_err(|err| TheaterError::Other(err.to_string()))?;
             },
 
+            // NOTE: a duplicate refers to a txn that is still legitimately
+            // pending, so only the other rejections evict it
+            Event::TxnRejected { txn_hash, reason } if reason != ValidationError::Duplicate => {
+                self.mempool
+                    .remove(&txn_hash)
+                    .map_err(|err| TheaterError::Other(err.to_string()))?;
+            },
+
             Event::NoOp => {},
             _ => {},
         }
@@ -110,3 +322,127 @@ impl Handler<Event> for MempoolModule {
         Ok(ActorState::Running)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use storage::vrrbdb::VrrbDbConfig;
+    use theater::ActorImpl;
+    use tokio::sync::{broadcast, mpsc::unbounded_channel};
+    use vrrb_core::txn::null_txn;
+
+    use super::*;
+
+    fn vrrbdb_read_handle() -> VrrbDbReadHandle {
+        let mut vrrbdb_config = VrrbDbConfig::default();
+        vrrbdb_config.path =
+            std::env::temp_dir().join(vrrb_core::helpers::generate_random_string());
+
+        VrrbDb::new(vrrbdb_config).unwrap().read_handle()
+    }
+
+    #[tokio::test]
+    async fn max_size_updates_evict_txns_over_the_new_capacity() {
+        let (events_tx, mut events_rx) = unbounded_channel::<DirectedEvent>();
+
+        let mempool = LeftRightMempool::new();
+        let mempool_read_handle_factory = mempool.factory();
+
+        let module = MempoolModule::new(MempoolModuleConfig {
+            mempool,
+            events_tx,
+            vrrbdb_read_handle: vrrbdb_read_handle(),
+            snapshot_path: None,
+            max_size: None,
+            eviction_policy: MempoolEvictionPolicy::FeeThenAge,
+            txn_ttl: Duration::from_secs(60),
+            node_status_handle: NodeStatusHandle::default(),
+        });
+
+        let mut module = ActorImpl::new(module);
+
+        let (ctrl_tx, mut ctrl_rx) = broadcast::channel::<Event>(10);
+
+        let handle = tokio::spawn(async move {
+            module.start(&mut ctrl_rx).await.unwrap();
+        });
+
+        let mut txn_hashes = vec![];
+
+        for nonce in 1..=3 {
+            let mut txn = null_txn();
+            txn.nonce = nonce;
+            txn.set_fee(nonce);
+            txn_hashes.push(txn.digest());
+
+            ctrl_tx.send(Event::NewTxnCreated(txn)).unwrap();
+        }
+
+        ctrl_tx.send(Event::MempoolMaxSizeUpdated(Some(1))).unwrap();
+        ctrl_tx.send(Event::Stop).unwrap();
+
+        handle.await.unwrap();
+
+        assert_eq!(mempool_read_handle_factory.size(), 1);
+        assert_eq!(mempool_read_handle_factory.eviction_count(), 2);
+
+        let mut evicted = vec![];
+        while let Ok((_, event)) = events_rx.try_recv() {
+            if let Event::TxnEvicted { txn_hash } = event {
+                evicted.push(txn_hash);
+            }
+        }
+
+        // NOTE: the txns paying the lowest fees are evicted first
+        assert_eq!(evicted, txn_hashes[..2].to_vec());
+    }
+
+    #[tokio::test]
+    async fn txns_pending_past_their_ttl_expire_on_sweep() {
+        let (events_tx, mut events_rx) = unbounded_channel::<DirectedEvent>();
+
+        let mempool = LeftRightMempool::new();
+        let mempool_read_handle_factory = mempool.factory();
+
+        let mut module = MempoolModule::new(MempoolModuleConfig {
+            mempool,
+            events_tx,
+            vrrbdb_read_handle: vrrbdb_read_handle(),
+            snapshot_path: None,
+            max_size: None,
+            eviction_policy: MempoolEvictionPolicy::FeeThenAge,
+            txn_ttl: Duration::from_secs(5),
+            node_status_handle: NodeStatusHandle::default(),
+        });
+
+        let txn = null_txn();
+        let txn_hash = txn.digest();
+
+        module.handle(Event::NewTxnCreated(txn)).await.unwrap();
+
+        let added_at = mempool_read_handle_factory
+            .get(&txn_hash)
+            .unwrap()
+            .added_timestamp;
+
+        module.remove_expired_txns(added_at + 4).unwrap();
+        assert!(mempool_read_handle_factory.contains(&txn_hash));
+
+        // NOTE: sweeps are handed the time rather than the test sleeping
+        // through the TTL
+        module.remove_expired_txns(added_at + 5).unwrap();
+        assert!(!mempool_read_handle_factory.contains(&txn_hash));
+
+        let mut events = vec![];
+        while let Ok(event) = events_rx.try_recv() {
+            events.push(event);
+        }
+
+        assert_eq!(
+            events,
+            vec![
+                (Topic::Consensus, Event::TxnAddedToMempool(txn_hash.clone())),
+                (Topic::Storage, Event::TxnExpired { txn_hash }),
+            ]
+        );
+    }
+}
@@ -1,28 +1,84 @@
-use std::result::Result as StdResult;
+use std::{
+    result::Result as StdResult,
+    time::{Duration, Instant, SystemTime},
+};
 
 use async_trait::async_trait;
+use mempool::MempoolReadHandleFactory;
 use telemetry::info;
-use tokio::sync::broadcast::{error::TryRecvError, Receiver};
-use vrrb_core::event_router::Event;
-
-use crate::{result::Result, RuntimeModule, RuntimeModuleState};
+use tokio::sync::{
+    broadcast::{error::TryRecvError, Receiver},
+    mpsc::UnboundedSender,
+};
+use vrrb_core::{
+    event_router::{recv_reporting_lag, DirectedEvent, Event, Topic},
+    node_status::NodeStatusHandle,
+};
+
+use crate::{report_state_change, result::Result, RuntimeModule, RuntimeModuleState};
+
+/// Shortest wait between two checks of an empty mempool
+const MIN_IDLE_BACKOFF: Duration = Duration::from_millis(50);
+
+pub struct MiningModuleConfig {
+    pub events_tx: UnboundedSender<DirectedEvent>,
+    pub mempool_read_handle_factory: MempoolReadHandleFactory,
+    /// Minimum spacing between two consecutive block production attempts
+    pub target_block_interval: Duration,
+    /// Difficulty blocks are mined at
+    pub difficulty: u64,
+    /// Upper bound on the wait between checks of an empty mempool
+    pub max_idle_backoff: Duration,
+    /// No blocks are attempted until the node is operational
+    pub node_status_handle: NodeStatusHandle,
+}
 
 pub struct MiningModule {
     running_status: RuntimeModuleState,
+    events_tx: UnboundedSender<DirectedEvent>,
+    mempool_read_handle_factory: MempoolReadHandleFactory,
+    target_block_interval: Duration,
+    difficulty: u64,
+    max_idle_backoff: Duration,
+    node_status_handle: NodeStatusHandle,
+    idle_backoff: Duration,
+    last_attempt: Option<Instant>,
+    last_mined_block_time: Option<SystemTime>,
+    attempt_count: u64,
 }
 
 impl MiningModule {
-    pub fn new() -> Self {
+    pub fn new(config: MiningModuleConfig) -> Self {
         Self {
             running_status: RuntimeModuleState::Stopped,
+            events_tx: config.events_tx,
+            mempool_read_handle_factory: config.mempool_read_handle_factory,
+            target_block_interval: config.target_block_interval,
+            difficulty: config.difficulty,
+            max_idle_backoff: config.max_idle_backoff,
+            node_status_handle: config.node_status_handle,
+            idle_backoff: Duration::ZERO,
+            last_attempt: None,
+            last_mined_block_time: None,
+            attempt_count: 0,
         }
     }
+
+    /// Returns when the last block production attempt took place, if any
+    pub fn last_mined_block_time(&self) -> Option<SystemTime> {
+        self.last_mined_block_time
+    }
+
+    /// Returns how many block production attempts were made so far
+    pub fn attempt_count(&self) -> u64 {
+        self.attempt_count
+    }
 }
 
 #[async_trait]
 impl RuntimeModule for MiningModule {
     fn name(&self) -> String {
-        String::from("State module")
+        String::from("Mining module")
     }
 
     fn status(&self) -> RuntimeModuleState {
@@ -32,13 +88,34 @@ impl RuntimeModule for MiningModule {
     async fn start(&mut self, events_rx: &mut Receiver<Event>) -> Result<()> {
         info!("{0} started", self.name());
 
-        while let Ok(event) = events_rx.recv().await {
+        self.set_running_status(RuntimeModuleState::Running, None);
+
+        loop {
+            let event = tokio::select! {
+                event = recv_reporting_lag(events_rx, &Topic::Consensus, &self.events_tx) => {
+                    match event {
+                        Ok(event) => event,
+                        Err(_) => break,
+                    }
+                },
+                _ = self.node_status_handle.operational(), if !self.node_status_handle.is_operational() => {
+                    continue;
+                },
+                _ = tokio::time::sleep(self.time_until_next_attempt(Instant::now())), if self.node_status_handle.is_operational() => {
+                    self.try_mine(Instant::now());
+                    continue;
+                },
+            };
+
             info!("{} received {event:?}", self.name());
 
-            if event == Event::Stop {
+            if event.is_shutdown() {
                 info!("{0} received stop signal. Stopping", self.name());
 
-                self.running_status = RuntimeModuleState::Terminating;
+                self.set_running_status(
+                    RuntimeModuleState::Terminating,
+                    Some(String::from("stop signal received")),
+                );
 
                 break;
             }
@@ -46,13 +123,63 @@ impl RuntimeModule for MiningModule {
             self.process_event(event);
         }
 
-        self.running_status = RuntimeModuleState::Stopped;
+        self.set_running_status(RuntimeModuleState::Stopped, None);
 
         Ok(())
     }
 }
 
 impl MiningModule {
+    fn set_running_status(&mut self, status: RuntimeModuleState, reason: Option<String>) {
+        report_state_change(
+            &self.events_tx,
+            &self.node_status_handle,
+            self.name(),
+            self.running_status.clone(),
+            status.clone(),
+            reason,
+        );
+        self.running_status = status;
+    }
+
+    /// Time left until the miner may attempt to produce a block again, taking
+    /// into account both the target block interval and the idle backoff
+    fn time_until_next_attempt(&self, now: Instant) -> Duration {
+        let until_interval_elapses = match self.last_attempt {
+            Some(last_attempt) => {
+                (last_attempt + self.target_block_interval).saturating_duration_since(now)
+            },
+            None => Duration::ZERO,
+        };
+
+        until_interval_elapses.max(self.idle_backoff)
+    }
+
+    /// Attempts to produce a block, unless there is nothing to include in it,
+    /// in which case the miner backs off before checking the mempool again
+    fn try_mine(&mut self, now: Instant) {
+        if self.mempool_read_handle_factory.size() == 0 {
+            self.idle_backoff = (self.idle_backoff * 2)
+                .max(MIN_IDLE_BACKOFF)
+                .min(self.max_idle_backoff);
+
+            return;
+        }
+
+        self.idle_backoff = Duration::ZERO;
+        self.last_attempt = Some(now);
+        self.last_mined_block_time = Some(SystemTime::now());
+        self.attempt_count += 1;
+
+        let event = Event::MineBlock {
+            difficulty: self.difficulty,
+        };
+
+        if let Err(err) = self.events_tx.send((Topic::Consensus, event)) {
+            telemetry::error!("failed to request a block production attempt: {err}");
+        }
+    }
+
     fn decode_event(&mut self, event: StdResult<Event, TryRecvError>) -> Event {
         match event {
             Ok(cmd) => cmd,
@@ -81,6 +208,11 @@ impl MiningModule {
             // Event::PeerRequestedStateSync(_) => {
             //     // do something
             // },
+            // NOTE: new txns are worth checking for right away, whatever
+            // the idle backoff reached so far
+            Event::TxnAddedToMempool(_) => self.idle_backoff = Duration::ZERO,
+            // NOTE: the miner's own block production requests
+            Event::MineBlock { .. } => {},
             Event::NoOp => {},
             _ => telemetry::warn!("unrecognized command received: {:?}", event),
         }
@@ -516,3 +648,148 @@ fn setup_mining() {
     });
 }
 */
+
+#[cfg(test)]
+mod tests {
+    use mempool::LeftRightMempool;
+    use tokio::sync::{broadcast, mpsc::unbounded_channel};
+    use vrrb_core::txn::null_txn;
+
+    use super::*;
+
+    fn mining_module(
+        mempool: &LeftRightMempool,
+        target_block_interval: Duration,
+    ) -> (
+        MiningModule,
+        tokio::sync::mpsc::UnboundedReceiver<DirectedEvent>,
+    ) {
+        let node_status_handle = NodeStatusHandle::new();
+        node_status_handle.set_operational(true);
+
+        mining_module_with_status(mempool, target_block_interval, node_status_handle)
+    }
+
+    fn mining_module_with_status(
+        mempool: &LeftRightMempool,
+        target_block_interval: Duration,
+        node_status_handle: NodeStatusHandle,
+    ) -> (
+        MiningModule,
+        tokio::sync::mpsc::UnboundedReceiver<DirectedEvent>,
+    ) {
+        let (events_tx, events_rx) = unbounded_channel::<DirectedEvent>();
+
+        let module = MiningModule::new(MiningModuleConfig {
+            events_tx,
+            mempool_read_handle_factory: mempool.factory(),
+            target_block_interval,
+            difficulty: 4,
+            max_idle_backoff: Duration::from_millis(200),
+            node_status_handle,
+        });
+
+        (module, events_rx)
+    }
+
+    fn mine_requests(events_rx: &mut tokio::sync::mpsc::UnboundedReceiver<DirectedEvent>) -> usize {
+        let mut mine_requests = 0;
+
+        while let Ok((_, event)) = events_rx.try_recv() {
+            if let Event::MineBlock { .. } = event {
+                mine_requests += 1;
+            }
+        }
+
+        mine_requests
+    }
+
+    #[tokio::test]
+    async fn miner_does_not_attempt_blocks_faster_than_the_target_interval() {
+        let mut mempool = LeftRightMempool::new();
+        mempool.insert(null_txn()).unwrap();
+
+        let (mut module, mut events_rx) = mining_module(&mempool, Duration::from_millis(100));
+        let (ctrl_tx, mut ctrl_rx) = broadcast::channel::<Event>(10);
+
+        let handle = tokio::spawn(async move {
+            module.start(&mut ctrl_rx).await.unwrap();
+            module
+        });
+
+        tokio::time::sleep(Duration::from_millis(550)).await;
+        ctrl_tx.send(Event::Stop).unwrap();
+
+        let module = handle.await.unwrap();
+
+        // NOTE: one attempt right away, then at most one per elapsed interval
+        assert!(module.attempt_count() >= 1);
+        assert!(module.attempt_count() <= 6);
+        assert!(module.last_mined_block_time().is_some());
+
+        let mut mine_requests = 0;
+        while let Ok((topic, event)) = events_rx.try_recv() {
+            if let Event::MineBlock { difficulty } = event {
+                assert_eq!(topic, Topic::Consensus);
+                assert_eq!(difficulty, 4);
+                mine_requests += 1;
+            }
+        }
+
+        assert_eq!(mine_requests, module.attempt_count());
+    }
+
+    #[tokio::test]
+    async fn miner_waits_for_the_node_to_be_operational() {
+        let mut mempool = LeftRightMempool::new();
+        mempool.insert(null_txn()).unwrap();
+
+        let node_status_handle = NodeStatusHandle::new();
+        let (mut module, mut events_rx) = mining_module_with_status(
+            &mempool,
+            Duration::from_millis(100),
+            node_status_handle.clone(),
+        );
+        let (ctrl_tx, mut ctrl_rx) = broadcast::channel::<Event>(10);
+
+        let handle = tokio::spawn(async move {
+            module.start(&mut ctrl_rx).await.unwrap();
+            module
+        });
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert_eq!(mine_requests(&mut events_rx), 0);
+
+        node_status_handle.set_operational(true);
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        ctrl_tx.send(Event::Stop).unwrap();
+
+        let module = handle.await.unwrap();
+
+        assert!(module.attempt_count() >= 1);
+        assert_eq!(mine_requests(&mut events_rx) as u64, module.attempt_count());
+    }
+
+    #[tokio::test]
+    async fn miner_backs_off_while_the_mempool_is_empty() {
+        let mempool = LeftRightMempool::new();
+
+        let (mut module, _events_rx) = mining_module(&mempool, Duration::ZERO);
+        let (ctrl_tx, mut ctrl_rx) = broadcast::channel::<Event>(10);
+
+        let handle = tokio::spawn(async move {
+            module.start(&mut ctrl_rx).await.unwrap();
+            module
+        });
+
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        ctrl_tx.send(Event::Stop).unwrap();
+
+        let module = handle.await.unwrap();
+
+        assert_eq!(module.attempt_count(), 0);
+        assert!(module.last_mined_block_time().is_none());
+        assert_eq!(module.idle_backoff, Duration::from_millis(200));
+    }
+}
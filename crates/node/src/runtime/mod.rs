@@ -1,199 +1,621 @@
-use std::net::SocketAddr;
+use std::{collections::HashMap, net::SocketAddr, sync::Arc};
 
-use mempool::{LeftRightMempool, MempoolReadHandleFactory};
-use network::network::BroadcastEngine;
+use mempool::{LeftRightMempool, MempoolEvictionPolicy, MempoolReadHandleFactory, ValidationQueue};
+use network::network::{BroadcastEngine, PeerManager};
+use primitives::NodeType;
 use storage::{
     storage_utils,
-    vrrbdb::{VrrbDbConfig, VrrbDbReadHandle},
+    vrrbdb::{EventStore, Genesis, GenesisOutcome, VrrbDbConfig, VrrbDbReadHandle},
 };
-use telemetry::info;
-use theater::{Actor, ActorImpl};
+use telemetry::{info, warn};
+use theater::{Actor, ActorImpl, Handler};
 use tokio::{
-    sync::{broadcast::Receiver, mpsc::UnboundedSender},
+    sync::{
+        broadcast::{self, Receiver},
+        mpsc::{self, UnboundedSender},
+    },
     task::JoinHandle,
 };
-use vrrb_config::NodeConfig;
-use vrrb_core::event_router::{DirectedEvent, Event, EventRouter, Topic};
-use vrrb_rpc::rpc::{JsonRpcServer, JsonRpcServerConfig};
+use vrrb_config::{EventRouterConfig, NodeConfig};
+use vrrb_core::{
+    event_router::{recv_reporting_lag, DirectedEvent, Event, EventRouter, PeerData, Topic},
+    node_status::NodeStatusHandle,
+    txn::Txn,
+    txn_receipts::TxnReceiptsHandle,
+};
+use vrrb_rpc::rpc::{JsonRpcServer, JsonRpcServerConfig, PeerTable, CONFIRMED_BLOCKS_BUFFER_SIZE};
 
 use self::{
     broadcast_module::{BroadcastModule, BroadcastModuleConfig},
-    mempool_module::{MempoolModule, MempoolModuleConfig},
+    event_store_module::{EventStoreModule, EventStoreModuleConfig},
+    mempool_module::{spawn_mempool_sweeper, MempoolModule, MempoolModuleConfig},
+    startup::StartupTimer,
+    state_sync_module::{StateSyncModule, StateSyncModuleConfig},
+    supervisor::{ModuleHandle, ModuleRestarter},
+    vm_module::{VmModule, VmModuleConfig},
 };
 use crate::{
     broadcast_controller::{BroadcastEngineController, BROADCAST_CONTROLLER_BUFFER_SIZE},
+    peer_table::BroadcastPeerTable,
     NodeError,
     Result,
     RuntimeModule,
 };
 
+pub mod blockchain_module;
 pub mod broadcast_module;
 pub mod dkg_module;
+pub mod event_store_module;
 pub mod farmer_harvester_module;
 pub mod mempool_module;
 pub mod mining_module;
+pub mod startup;
 pub mod state_module;
+pub mod state_sync_module;
+pub mod supervisor;
 pub mod swarm_module;
 pub mod validator_module;
+pub mod vm_module;
+
+/// Restarters of the runtime modules that can be restarted, keyed by module
+/// name
+pub type ModuleRestarters = HashMap<&'static str, ModuleRestarter>;
 
+/// Runtime module handles, in the order `setup_runtime_components` returns
+/// them, followed by the restarters of the modules that can be restarted
+pub type RuntimeComponents = (
+    NodeConfig,
+    Option<JoinHandle<Result<()>>>,
+    Option<JoinHandle<Result<()>>>,
+    Option<JoinHandle<Result<()>>>,
+    Option<JoinHandle<Result<()>>>,
+    Option<JoinHandle<Result<()>>>,
+    Option<JoinHandle<Result<()>>>,
+    Option<JoinHandle<Result<()>>>,
+    Option<JoinHandle<Result<()>>>,
+    Option<JoinHandle<Result<()>>>,
+    Option<JoinHandle<Result<()>>>,
+    Option<JoinHandle<Result<()>>>,
+    ModuleRestarters,
+);
+
+/// Sets up and starts every runtime module of a node. Nodes with networking
+/// disabled leave the gossip network, broadcast controller and state sync
+/// handles unset.
+///
+/// Modules are started one after the other. Should one of them fail to set
+/// up, the ones already running are torn down before the error is returned,
+/// so a failed startup leaves no orphaned tasks or bound ports behind.
 pub async fn setup_runtime_components(
     original_config: &NodeConfig,
+    node_status_handle: NodeStatusHandle,
     events_tx: UnboundedSender<(Topic, Event)>,
-    mut mempool_events_rx: Receiver<Event>,
+    mempool_events_rx: Receiver<Event>,
     vrrbdb_events_rx: Receiver<Event>,
     network_events_rx: Receiver<Event>,
     controller_events_rx: Receiver<Event>,
+    state_sync_events_rx: Receiver<Event>,
     validator_events_rx: Receiver<Event>,
     miner_events_rx: Receiver<Event>,
     jsonrpc_events_rx: Receiver<Event>,
-) -> Result<(
-    NodeConfig,
-    Option<JoinHandle<Result<()>>>,
-    Option<JoinHandle<Result<()>>>,
-    Option<JoinHandle<Result<()>>>,
-    Option<JoinHandle<Result<()>>>,
-    Option<JoinHandle<Result<()>>>,
-    Option<JoinHandle<Result<()>>>,
-    Option<JoinHandle<Result<()>>>,
-)> {
-    let mut config = original_config.clone();
+    jsonrpc_consensus_events_rx: Receiver<Event>,
+    vm_events_rx: Receiver<Event>,
+    audit_events_rx: Option<Receiver<Event>>,
+) -> Result<RuntimeComponents> {
+    let mut started_modules = StartedModules::default();
+
+    let result = start_runtime_modules(
+        &mut started_modules,
+        original_config,
+        node_status_handle,
+        events_tx,
+        mempool_events_rx,
+        vrrbdb_events_rx,
+        network_events_rx,
+        controller_events_rx,
+        state_sync_events_rx,
+        validator_events_rx,
+        miner_events_rx,
+        jsonrpc_events_rx,
+        jsonrpc_consensus_events_rx,
+        vm_events_rx,
+        audit_events_rx,
+    )
+    .await;
+
+    match result {
+        Ok(config) => Ok((
+            config,
+            started_modules.take(MEMPOOL_MODULE),
+            started_modules.take(STATE_STORE_MODULE),
+            started_modules.take(GOSSIP_NETWORK_MODULE),
+            started_modules.take(BROADCAST_CONTROLLER_MODULE),
+            started_modules.take(STATE_SYNC_MODULE),
+            started_modules.take(JSONRPC_SERVER_MODULE),
+            started_modules.take(VALIDATOR_MODULE),
+            started_modules.take(MINER_MODULE),
+            started_modules.take(VM_MODULE),
+            started_modules.take(EVENT_STORE_MODULE),
+            started_modules.take(MEMPOOL_SWEEPER),
+            std::mem::take(&mut started_modules.restarters),
+        )),
+        Err(err) => {
+            started_modules.tear_down().await;
+            Err(err)
+        },
+    }
+}
 
-    let mempool = LeftRightMempool::new();
-    let mempool_read_handle_factory = mempool.factory();
+pub const MEMPOOL_MODULE: &str = "mempool";
+pub const STATE_STORE_MODULE: &str = "state_store";
+pub const GOSSIP_NETWORK_MODULE: &str = "gossip_network";
+pub const BROADCAST_CONTROLLER_MODULE: &str = "broadcast_controller";
+pub const STATE_SYNC_MODULE: &str = "state_sync";
+pub const JSONRPC_SERVER_MODULE: &str = "jsonrpc_server";
+pub const VALIDATOR_MODULE: &str = "validator";
+pub const MINER_MODULE: &str = "miner";
+pub const VM_MODULE: &str = "vm";
+pub const EVENT_STORE_MODULE: &str = "event_store";
+
+/// Task asking the mempool module to sweep its expired txns. Not a module on
+/// its own, it never reports a state and can't be asked to stop, so it's
+/// aborted instead.
+pub const MEMPOOL_SWEEPER: &str = "mempool_sweeper";
+
+/// Handles of the runtime modules started so far, keyed by module name,
+/// along with the restarters of those that can be restarted
+#[derive(Debug, Default)]
+struct StartedModules {
+    handles: Vec<(&'static str, JoinHandle<Result<()>>)>,
+    restarters: ModuleRestarters,
+}
 
-    let mempool_module = MempoolModule::new(MempoolModuleConfig {
-        mempool,
-        events_tx: events_tx.clone(),
-    });
+impl StartedModules {
+    fn track(&mut self, module: &'static str, handle: Option<JoinHandle<Result<()>>>) {
+        if let Some(handle) = handle {
+            self.handles.push((module, handle));
+        }
+    }
 
-    let mut mempool_module_actor = ActorImpl::new(mempool_module);
+    fn restart_with(&mut self, module: &'static str, restarter: ModuleRestarter) {
+        self.restarters.insert(module, restarter);
+    }
 
-    let mempool_handle = tokio::spawn(async move {
-        mempool_module_actor
-            .start(&mut mempool_events_rx)
-            .await
-            .map_err(|err| NodeError::Other(err.to_string()))
-    });
+    fn take(&mut self, module: &'static str) -> Option<JoinHandle<Result<()>>> {
+        let idx = self.handles.iter().position(|(name, _)| *name == module)?;
 
-    let mempool_handle = Some(mempool_handle);
+        Some(self.handles.remove(idx).1)
+    }
 
-    println!("Setting up state_store");
-    let (state_read_handle, state_handle) = setup_state_store(
-        &config,
-        events_tx.clone(),
-        vrrbdb_events_rx,
-        mempool_read_handle_factory.clone(),
-    )
-    .await?;
+    /// Aborts every started module, most recently started first, and waits
+    /// for them to be dropped along with whatever they hold.
+    ///
+    /// NOTE: modules can't be asked to stop through `Event::Stop` here since
+    /// the event router only starts routing events once every module is up
+    async fn tear_down(self) {
+        for (module, handle) in self.handles.into_iter().rev() {
+            handle.abort();
+
+            match handle.await {
+                Err(err) if err.is_cancelled() => {
+                    info!("stopped {module} after the node failed to start")
+                },
+                Err(err) => warn!("{module} failed while the node was starting: {err}"),
+                Ok(Err(err)) => warn!("{module} failed while the node was starting: {err}"),
+                Ok(Ok(())) => {},
+            }
+        }
+    }
+}
+
+async fn start_runtime_modules(
+    started_modules: &mut StartedModules,
+    original_config: &NodeConfig,
+    node_status_handle: NodeStatusHandle,
+    events_tx: UnboundedSender<(Topic, Event)>,
+    mut mempool_events_rx: Receiver<Event>,
+    vrrbdb_events_rx: Receiver<Event>,
+    network_events_rx: Receiver<Event>,
+    controller_events_rx: Receiver<Event>,
+    state_sync_events_rx: Receiver<Event>,
+    validator_events_rx: Receiver<Event>,
+    miner_events_rx: Receiver<Event>,
+    jsonrpc_events_rx: Receiver<Event>,
+    jsonrpc_consensus_events_rx: Receiver<Event>,
+    vm_events_rx: Receiver<Event>,
+    audit_events_rx: Option<Receiver<Event>>,
+) -> Result<NodeConfig> {
+    let mut config = original_config.clone();
+    let mut startup_timer = StartupTimer::new();
+
+    let (validation_queue, validation_rx) = ValidationQueue::new(config.validation_queue_depth);
+    let txn_receipts = TxnReceiptsHandle::default();
 
-    let mut gossip_handle = None;
-    let mut broadcast_controller_handle = None;
+    // NOTE: the state store reads pending txns and the mempool module reads
+    // account nonces, so the mempool is created ahead of both modules
+    let mempool = setup_mempool(&config);
+    let mempool_read_handle_factory = mempool.factory();
 
-    if !config.disable_networking {
-        let (new_gossip_handle, new_broadcast_controller_handle, gossip_addr) =
-            setup_gossip_network(
+    let (state_read_handle, state_handle) = startup_timer
+        .time(
+            STATE_STORE_MODULE,
+            setup_state_store(
                 &config,
                 events_tx.clone(),
-                network_events_rx,
-                controller_events_rx,
-                state_read_handle.clone(),
+                vrrbdb_events_rx,
+                mempool_read_handle_factory.clone(),
+                node_status_handle.clone(),
+                txn_receipts.clone(),
+            ),
+        )
+        .await?;
+
+    started_modules.track(STATE_STORE_MODULE, state_handle);
+
+    let mempool_handle = startup_timer
+        .time(MEMPOOL_MODULE, async {
+            let mempool_module = MempoolModule::new(MempoolModuleConfig {
+                mempool,
+                events_tx: events_tx.clone(),
+                vrrbdb_read_handle: state_read_handle.clone(),
+                snapshot_path: config.mempool_snapshot_path.clone(),
+                max_size: config.mempool_max_size,
+                eviction_policy: MempoolEvictionPolicy::FeeThenAge,
+                txn_ttl: config.mempool_txn_ttl,
+                node_status_handle: node_status_handle.clone(),
+            });
+
+            node_status_handle.expect_module(&mempool_module.label());
+
+            let mut mempool_module_actor = ActorImpl::new(mempool_module);
+
+            let mempool_handle = tokio::spawn(async move {
+                mempool_module_actor
+                    .start(&mut mempool_events_rx)
+                    .await
+                    .map_err(|err| NodeError::Other(err.to_string()))
+            });
+
+            Ok(Some(mempool_handle))
+        })
+        .await?;
+
+    started_modules.track(MEMPOOL_MODULE, mempool_handle);
+    started_modules.track(
+        MEMPOOL_SWEEPER,
+        Some(spawn_mempool_sweeper(
+            events_tx.clone(),
+            config.mempool_txn_ttl,
+        )),
+    );
+
+    let mut peer_table = None;
+
+    // NOTE: without networking the node is still usable locally, the RPC
+    // server then reports it has no peers and can't reach any, and only takes
+    // txns in if `accept_txns_without_networking` is set
+    if config.disable_networking {
+        info!(
+            "Networking is disabled, not starting the {GOSSIP_NETWORK_MODULE}, \
+             {BROADCAST_CONTROLLER_MODULE} and {STATE_SYNC_MODULE} modules"
+        );
+    } else {
+        let (
+            gossip_handle,
+            broadcast_controller_handle,
+            state_sync_handle,
+            gossip_addr,
+            peer_manager,
+        ) = startup_timer
+            .time(
+                GOSSIP_NETWORK_MODULE,
+                setup_gossip_network(
+                    &config,
+                    events_tx.clone(),
+                    network_events_rx,
+                    controller_events_rx,
+                    state_sync_events_rx,
+                    state_read_handle.clone(),
+                    node_status_handle.clone(),
+                ),
             )
             .await?;
 
-        gossip_handle = new_gossip_handle;
-        broadcast_controller_handle = new_broadcast_controller_handle;
+        started_modules.track(GOSSIP_NETWORK_MODULE, gossip_handle);
+        started_modules.restart_with(
+            GOSSIP_NETWORK_MODULE,
+            broadcast_module_restarter(
+                &config,
+                events_tx.clone(),
+                state_read_handle.clone(),
+                node_status_handle.clone(),
+            ),
+        );
+        started_modules.track(BROADCAST_CONTROLLER_MODULE, broadcast_controller_handle);
+        started_modules.track(STATE_SYNC_MODULE, state_sync_handle);
+
         config.udp_gossip_address = gossip_addr;
+        peer_table = Some(Arc::new(BroadcastPeerTable::new(peer_manager)) as Arc<dyn PeerTable>);
     }
 
-    let (jsonrpc_server_handle, resolved_jsonrpc_server_addr) = setup_rpc_api_server(
-        &config,
-        events_tx.clone(),
-        state_read_handle.clone(),
-        mempool_read_handle_factory.clone(),
-        jsonrpc_events_rx,
-    )
-    .await?;
+    let (jsonrpc_server_handle, resolved_jsonrpc_server_addr) = startup_timer
+        .time(
+            JSONRPC_SERVER_MODULE,
+            setup_rpc_api_server(
+                &config,
+                node_status_handle.clone(),
+                events_tx.clone(),
+                state_read_handle.clone(),
+                mempool_read_handle_factory.clone(),
+                validation_queue,
+                txn_receipts,
+                peer_table,
+                jsonrpc_events_rx,
+                jsonrpc_consensus_events_rx,
+            ),
+        )
+        .await?;
+
+    started_modules.track(JSONRPC_SERVER_MODULE, jsonrpc_server_handle);
 
     config.jsonrpc_server_address = resolved_jsonrpc_server_addr;
 
     info!("JSON-RPC server address: {}", config.jsonrpc_server_address);
 
-    // TODO: make nodes start with some preconfigured state
-    let txn_validator_handle = setup_validation_module(
-        events_tx.clone(),
-        validator_events_rx,
-        mempool_read_handle_factory.clone(),
-    )?;
+    // NOTE: read-only nodes follow the network without taking part in
+    // consensus, their RPC server turns away every transaction
+    if config.node_type.is_read_only() {
+        info!(
+            "{:?} node, not starting the {VALIDATOR_MODULE} and {MINER_MODULE} modules",
+            config.node_type
+        );
+    } else {
+        // TODO: make nodes start with some preconfigured state
+        let txn_validator_handle = startup_timer
+            .time(VALIDATOR_MODULE, async {
+                setup_validation_module(
+                    &config,
+                    node_status_handle.clone(),
+                    events_tx.clone(),
+                    validator_events_rx,
+                    state_read_handle.clone(),
+                    validation_rx,
+                )
+            })
+            .await?;
 
-    let miner_handle = setup_mining_module(events_tx.clone(), miner_events_rx)?;
+        started_modules.track(VALIDATOR_MODULE, txn_validator_handle);
+
+        let miner_handle = startup_timer
+            .time(MINER_MODULE, async {
+                setup_mining_module(
+                    &config,
+                    node_status_handle.clone(),
+                    events_tx.clone(),
+                    miner_events_rx,
+                    mempool_read_handle_factory.clone(),
+                )
+                .map(Some)
+            })
+            .await?;
 
-    Ok((
-        config,
-        mempool_handle,
-        state_handle,
-        gossip_handle,
-        broadcast_controller_handle,
-        jsonrpc_server_handle,
-        txn_validator_handle,
-        miner_handle,
-    ))
+        started_modules.track(MINER_MODULE, miner_handle);
+
+        let (miner_config, miner_status_handle, miner_events_tx, miner_mempool_factory) = (
+            config.clone(),
+            node_status_handle.clone(),
+            events_tx.clone(),
+            mempool_read_handle_factory.clone(),
+        );
+
+        started_modules.restart_with(
+            MINER_MODULE,
+            ModuleRestarter::new(Topic::Consensus, move |miner_events_rx| {
+                let handle = setup_mining_module(
+                    &miner_config,
+                    miner_status_handle.clone(),
+                    miner_events_tx.clone(),
+                    miner_events_rx,
+                    miner_mempool_factory.clone(),
+                );
+
+                async move { handle }
+            }),
+        );
+    }
+
+    let vm_handle = startup_timer
+        .time(VM_MODULE, async {
+            setup_vm_module(node_status_handle.clone(), events_tx.clone(), vm_events_rx).map(Some)
+        })
+        .await?;
+
+    started_modules.track(VM_MODULE, vm_handle);
+
+    let (vm_status_handle, vm_events_tx) = (node_status_handle.clone(), events_tx.clone());
+
+    started_modules.restart_with(
+        VM_MODULE,
+        ModuleRestarter::new(Topic::State, move |vm_events_rx| {
+            let handle =
+                setup_vm_module(vm_status_handle.clone(), vm_events_tx.clone(), vm_events_rx);
+
+            async move { handle }
+        }),
+    );
+
+    if let (Some(path), Some(audit_events_rx)) = (&config.event_store_path, audit_events_rx) {
+        let event_store_handle = startup_timer
+            .time(EVENT_STORE_MODULE, async {
+                setup_event_store_module(
+                    path,
+                    node_status_handle.clone(),
+                    events_tx.clone(),
+                    audit_events_rx,
+                )
+                .map(Some)
+            })
+            .await?;
+
+        started_modules.track(EVENT_STORE_MODULE, event_store_handle);
+
+        let (event_store_path, event_store_status_handle, event_store_events_tx) =
+            (path.clone(), node_status_handle.clone(), events_tx.clone());
+
+        started_modules.restart_with(
+            EVENT_STORE_MODULE,
+            ModuleRestarter::new(Topic::Audit, move |audit_events_rx| {
+                let handle = setup_event_store_module(
+                    &event_store_path,
+                    event_store_status_handle.clone(),
+                    event_store_events_tx.clone(),
+                    audit_events_rx,
+                );
+
+                async move { handle }
+            }),
+        );
+    }
+
+    startup_timer.log_summary();
+
+    Ok(config)
+}
+
+/// Restores the mempool from its snapshot if one is configured, falling back to
+/// an empty mempool when the snapshot can't be read
+fn setup_mempool(config: &NodeConfig) -> LeftRightMempool {
+    match &config.mempool_snapshot_path {
+        Some(path) => LeftRightMempool::load_snapshot(path).unwrap_or_else(|err| {
+            warn!("unable to restore mempool from {}: {err}", path.display());
+            LeftRightMempool::new()
+        }),
+        None => LeftRightMempool::new(),
+    }
 }
 
-fn setup_event_routing_system() -> EventRouter {
+pub fn setup_event_routing_system(config: &EventRouterConfig) -> EventRouter {
     let mut event_router = EventRouter::new();
-    event_router.add_topic(Topic::Control, Some(1));
-    event_router.add_topic(Topic::State, Some(1));
-    event_router.add_topic(Topic::Network, Some(100));
-    event_router.add_topic(Topic::Storage, Some(100));
-    event_router.add_topic(Topic::Consensus, Some(100));
+    event_router.add_topic(Topic::Control, Some(config.control_buffer));
+    event_router.add_topic(Topic::State, Some(config.state_buffer));
+    event_router.add_topic(Topic::Network, Some(config.network_buffer));
+    event_router.add_topic(Topic::Storage, Some(config.storage_buffer));
+    event_router.add_topic(Topic::Consensus, Some(config.consensus_buffer));
+
+    event_router.set_backpressure_policy(Topic::Control, config.control_policy);
+    event_router.set_backpressure_policy(Topic::State, config.state_policy);
+    event_router.set_backpressure_policy(Topic::Network, config.network_policy);
+    event_router.set_backpressure_policy(Topic::Storage, config.storage_policy);
+    event_router.set_backpressure_policy(Topic::Consensus, config.consensus_policy);
 
     event_router
 }
 
+/// Adds the topic auditable events are copied to. Only nodes keeping an event
+/// store need it, everyone else is spared the extra copies.
+pub fn setup_audit_topic(event_router: &mut EventRouter, config: &EventRouterConfig) {
+    event_router.add_topic(Topic::Audit, Some(config.storage_buffer));
+    event_router.set_backpressure_policy(Topic::Audit, config.storage_policy);
+}
+
 async fn setup_gossip_network(
     config: &NodeConfig,
     events_tx: UnboundedSender<DirectedEvent>,
-    mut network_events_rx: Receiver<Event>,
+    network_events_rx: Receiver<Event>,
     mut controller_events_rx: Receiver<Event>,
+    mut state_sync_events_rx: Receiver<Event>,
     vrrbdb_read_handle: VrrbDbReadHandle,
+    node_status_handle: NodeStatusHandle,
 ) -> Result<(
+    Option<JoinHandle<Result<()>>>,
     Option<JoinHandle<Result<()>>>,
     Option<JoinHandle<Result<()>>>,
     SocketAddr,
+    PeerManager,
 )> {
-    let broadcast_module = BroadcastModule::new(BroadcastModuleConfig {
-        events_tx: events_tx.clone(),
-        vrrbdb_read_handle,
-        udp_gossip_address_port: config.udp_gossip_address.port(),
-        raptorq_gossip_address_port: config.raptorq_gossip_address.port(),
-        node_type: config.node_type,
-        node_id: config.id.as_bytes().to_vec(),
-    })
+    let broadcast_handle = start_broadcast_module(
+        config,
+        events_tx.clone(),
+        network_events_rx,
+        vrrbdb_read_handle.clone(),
+        node_status_handle.clone(),
+    )
     .await?;
 
-    let addr = broadcast_module.local_addr();
-
     let (controller_tx, controller_rx) =
         tokio::sync::mpsc::channel::<Event>(BROADCAST_CONTROLLER_BUFFER_SIZE);
 
-    let broadcast_engine = BroadcastEngine::new(config.udp_gossip_address.port(), 32)
-        .await
-        .map_err(|err| NodeError::Other(format!("unable to setup broadcast engine: {}", err)))?;
+    let broadcast_engine = BroadcastEngine::new_with_config(
+        config.udp_gossip_address,
+        32,
+        config.broadcast_engine_config.clone(),
+    )
+    .await
+    .map_err(|err| NodeError::BindFailed {
+        module: "broadcast_controller",
+        addr: config.udp_gossip_address,
+        source: Box::new(err),
+    })?;
+
+    // NOTE: the controller's engine is the one listening for peers, so its
+    // address is the one advertised to the rest of the network
+    let addr = broadcast_engine.local_addr();
+
+    let local_peer = PeerData {
+        address: addr,
+        node_type: config.node_type,
+        peer_id: config.id.as_bytes().to_vec(),
+    };
+
+    // NOTE: bootstrap nodes are the entry point of the network and don't
+    // bootstrap through anyone else
+    let bootstrap_node_addresses = if config.node_type == NodeType::Bootstrap {
+        vec![]
+    } else {
+        config.bootstrap_node_addresses.clone()
+    };
 
-    let mut bcast_controller = BroadcastEngineController::new(broadcast_engine);
+    let secret_key = *config
+        .keypair
+        .as_ref()
+        .ok_or_else(|| NodeError::Other(String::from("node keypair is not set")))?
+        .get_miner_secret_key();
 
-    // NOTE: starts the listening loop
+    let mut bcast_controller = BroadcastEngineController::new(
+        broadcast_engine,
+        events_tx.clone(),
+        local_peer.clone(),
+        secret_key,
+        bootstrap_node_addresses,
+        vrrbdb_read_handle.clone(),
+        node_status_handle.clone(),
+    );
+
+    let peer_manager = bcast_controller.peer_manager();
+
+    let state_sync_module = StateSyncModule::new(StateSyncModuleConfig {
+        events_tx: events_tx.clone(),
+        vrrbdb_read_handle,
+        bootstrapper: bcast_controller.bootstrapper(),
+        local_peer,
+        secret_key,
+        node_status_handle: node_status_handle.clone(),
+    });
+
+    node_status_handle.expect_module(&state_sync_module.label());
+
+    // NOTE: starts the listening loop once the node is ready
     let broadcast_controller_handle = tokio::spawn(async move {
         bcast_controller
             .listen(controller_tx, controller_events_rx)
             .await
     });
 
-    let mut broadcast_module_actor = ActorImpl::new(broadcast_module);
+    let mut state_sync_module_actor = ActorImpl::new(state_sync_module);
 
-    let broadcast_handle = tokio::spawn(async move {
-        broadcast_module_actor
-            .start(&mut network_events_rx)
+    let state_sync_handle = tokio::spawn(async move {
+        state_sync_module_actor
+            .start(&mut state_sync_events_rx)
             .await
             .map_err(|err| NodeError::Other(err.to_string()))
     });
@@ -201,22 +623,132 @@ async fn setup_gossip_network(
     Ok((
         Some(broadcast_handle),
         Some(broadcast_controller_handle),
+        Some(state_sync_handle),
         addr,
+        peer_manager,
     ))
 }
 
+async fn start_broadcast_module(
+    config: &NodeConfig,
+    events_tx: UnboundedSender<DirectedEvent>,
+    mut network_events_rx: Receiver<Event>,
+    vrrbdb_read_handle: VrrbDbReadHandle,
+    node_status_handle: NodeStatusHandle,
+) -> Result<ModuleHandle> {
+    let broadcast_module = BroadcastModule::new(BroadcastModuleConfig {
+        events_tx,
+        vrrbdb_read_handle,
+        node_status_handle: node_status_handle.clone(),
+        udp_gossip_address: config.udp_gossip_address,
+        raptorq_gossip_address: config.raptorq_gossip_address,
+        node_type: config.node_type,
+        node_id: config.id.as_bytes().to_vec(),
+        broadcast_engine_config: config.broadcast_engine_config.clone(),
+    })
+    .await?;
+
+    node_status_handle.expect_module(&broadcast_module.label());
+
+    let mut broadcast_module_actor = ActorImpl::new(broadcast_module);
+
+    Ok(tokio::spawn(async move {
+        broadcast_module_actor
+            .start(&mut network_events_rx)
+            .await
+            .map_err(|err| NodeError::Other(err.to_string()))
+    }))
+}
+
+/// Restarts the gossip network's broadcast module, the broadcast controller
+/// and state sync module keep running alongside it
+fn broadcast_module_restarter(
+    config: &NodeConfig,
+    events_tx: UnboundedSender<DirectedEvent>,
+    vrrbdb_read_handle: VrrbDbReadHandle,
+    node_status_handle: NodeStatusHandle,
+) -> ModuleRestarter {
+    let mut config = config.clone();
+
+    // NOTE: the broadcast controller listens on the configured gossip address
+    // by now, the broadcast module only binds to it while it's created
+    config.udp_gossip_address.set_port(0);
+
+    ModuleRestarter::new(Topic::Network, move |network_events_rx| {
+        let config = config.clone();
+        let events_tx = events_tx.clone();
+        let vrrbdb_read_handle = vrrbdb_read_handle.clone();
+        let node_status_handle = node_status_handle.clone();
+
+        async move {
+            start_broadcast_module(
+                &config,
+                events_tx,
+                network_events_rx,
+                vrrbdb_read_handle,
+                node_status_handle,
+            )
+            .await
+        }
+    })
+}
+
 async fn setup_state_store(
     config: &NodeConfig,
     events_tx: UnboundedSender<DirectedEvent>,
     mut state_events_rx: Receiver<Event>,
     mempool_read_handle_factory: MempoolReadHandleFactory,
+    node_status_handle: NodeStatusHandle,
+    txn_receipts: TxnReceiptsHandle,
 ) -> Result<(VrrbDbReadHandle, Option<JoinHandle<Result<()>>>)> {
-    let vrrbdb_config = VrrbDbConfig::default();
-    let db = storage::vrrbdb::VrrbDb::new(vrrbdb_config);
+    // NOTE: the event store is written to by its own module, the db only
+    // reads it to export snapshots
+    let vrrbdb_config = VrrbDbConfig {
+        path: config.db_path().clone(),
+        event_store_path: config
+            .event_store_path
+            .as_ref()
+            .map(|path| path.display().to_string()),
+        ..Default::default()
+    };
+
+    let mut db = storage::vrrbdb::VrrbDb::new(vrrbdb_config.clone())
+        .map_err(|err| NodeError::Other(format!("failed to open the state store: {err}")))?;
+
+    if let Some(genesis_path) = &config.genesis_path {
+        let genesis = Genesis::from_file(genesis_path).map_err(|err| {
+            NodeError::Other(format!(
+                "failed to read genesis from {}: {err}",
+                genesis_path.display()
+            ))
+        })?;
+
+        let outcome = db
+            .apply_genesis(&genesis, &vrrbdb_config)
+            .map_err(|err| NodeError::Other(err.to_string()))?;
+
+        match outcome {
+            GenesisOutcome::Applied => info!(
+                "Initialized state from genesis {} with {} accounts",
+                genesis.hash(),
+                genesis.allocations().len()
+            ),
+            GenesisOutcome::AlreadyApplied => {
+                info!("State was initialized from genesis {}", genesis.hash())
+            },
+        }
+    }
+
     let vrrbdb_read_handle = db.read_handle();
 
-    let mut state_module =
-        state_module::StateModule::new(state_module::StateModuleConfig { events_tx, db });
+    let mut state_module = state_module::StateModule::new(state_module::StateModuleConfig {
+        events_tx,
+        db,
+        node_status_handle: node_status_handle.clone(),
+        txn_receipts,
+    });
+
+    node_status_handle.expect_module(&state_module.label());
 
     let mut state_module_actor = ActorImpl::new(state_module);
 
@@ -232,31 +764,81 @@ async fn setup_state_store(
 
 async fn setup_rpc_api_server(
     config: &NodeConfig,
+    node_status_handle: NodeStatusHandle,
     events_tx: UnboundedSender<DirectedEvent>,
     vrrbdb_read_handle: VrrbDbReadHandle,
     mempool_read_handle_factory: MempoolReadHandleFactory,
+    validation_queue: ValidationQueue,
+    txn_receipts: TxnReceiptsHandle,
+    peer_table: Option<Arc<dyn PeerTable>>,
     mut jsonrpc_events_rx: Receiver<Event>,
+    mut jsonrpc_consensus_events_rx: Receiver<Event>,
 ) -> Result<(Option<JoinHandle<Result<()>>>, SocketAddr)> {
+    let lag_events_tx = events_tx.clone();
+    let (confirmed_blocks_tx, _) = broadcast::channel(CONFIRMED_BLOCKS_BUFFER_SIZE);
+
     let jsonrpc_server_config = JsonRpcServerConfig {
         address: config.jsonrpc_server_address,
+        node_id: config.id.clone(),
         node_type: config.node_type,
+        chain_id: config.chain_id,
+        reject_txns_without_chain_id: config.reject_txns_without_chain_id,
+        fee_model: config.fee_config.fee_model(),
+        max_txn_size_bytes: config.max_txn_size_bytes,
+        reject_txns_without_networking: config.disable_networking
+            && !config.accept_txns_without_networking,
+        node_status_handle,
         events_tx,
         vrrbdb_read_handle,
         mempool_read_handle_factory,
+        validation_queue,
+        txn_receipts,
+        confirmed_blocks_tx: confirmed_blocks_tx.clone(),
+        peer_table,
+        admin_token: config.jsonrpc_admin_token.clone(),
     };
 
     let (jsonrpc_server_handle, resolved_jsonrpc_server_addr) =
         JsonRpcServer::run(&jsonrpc_server_config)
             .await
-            .map_err(|err| NodeError::Other(format!("unable to satrt JSON-RPC server: {}", err)))?;
+            .map_err(|err| NodeError::BindFailed {
+                module: "jsonrpc_server",
+                addr: config.jsonrpc_server_address,
+                source: err.into(),
+            })?;
 
     let jsonrpc_server_handle = Some(tokio::spawn(async move {
-        if let Ok(evt) = jsonrpc_events_rx.recv().await {
-            if let Event::Stop = evt {
-                jsonrpc_server_handle.stop();
-                return Ok(());
+        let mut consensus_open = true;
+
+        loop {
+            tokio::select! {
+                evt = recv_reporting_lag(&mut jsonrpc_events_rx, &Topic::Control, &lag_events_tx) => {
+                    match evt {
+                        Ok(event) if event.is_shutdown() => {
+                            jsonrpc_server_handle.stop();
+                            return Ok(());
+                        },
+                        Ok(_) => {},
+                        Err(_) => break,
+                    }
+                },
+                evt = recv_reporting_lag(
+                    &mut jsonrpc_consensus_events_rx,
+                    &Topic::Consensus,
+                    &lag_events_tx,
+                ), if consensus_open => {
+                    match evt {
+                        Ok(Event::BlockConfirmed(block)) => {
+                            // NOTE: only fails when no client is subscribed to blocks
+                            let _ = confirmed_blocks_tx.send(block);
+                        },
+                        Ok(_) => {},
+                        Err(_) => consensus_open = false,
+                    }
+                },
             }
         }
+
         Ok(())
     }));
 
@@ -264,11 +846,27 @@ async fn setup_rpc_api_server(
 }
 
 fn setup_validation_module(
+    config: &NodeConfig,
+    node_status_handle: NodeStatusHandle,
     events_tx: UnboundedSender<DirectedEvent>,
     mut validator_events_rx: Receiver<Event>,
-    mempool_read_handle_factory: MempoolReadHandleFactory,
+    vrrbdb_read_handle: VrrbDbReadHandle,
+    validation_rx: mpsc::Receiver<Txn>,
 ) -> Result<Option<JoinHandle<Result<()>>>> {
-    let mut module = validator_module::ValidatorModule::new();
+    let mut module =
+        validator_module::ValidatorModule::new(validator_module::ValidatorModuleConfig {
+            events_tx,
+            vrrbdb_read_handle,
+            validation_rx,
+            chain_id: config.chain_id,
+            reject_txns_without_chain_id: config.reject_txns_without_chain_id,
+            parallelism: config.validation_parallelism,
+            fee_model: config.fee_config.fee_model(),
+            max_txn_size_bytes: config.max_txn_size_bytes,
+            node_status_handle: node_status_handle.clone(),
+        })?;
+
+    node_status_handle.expect_module(&module.name());
 
     let txn_validator_handle =
         tokio::spawn(async move { module.start(&mut validator_events_rx).await });
@@ -277,12 +875,80 @@ fn setup_validation_module(
 }
 
 fn setup_mining_module(
+    config: &NodeConfig,
+    node_status_handle: NodeStatusHandle,
     events_tx: UnboundedSender<DirectedEvent>,
     mut miner_events_rx: Receiver<Event>,
-) -> Result<Option<JoinHandle<Result<()>>>> {
-    let mut module = mining_module::MiningModule::new();
+    mempool_read_handle_factory: MempoolReadHandleFactory,
+) -> Result<ModuleHandle> {
+    // NOTE: the miner would otherwise attempt to produce blocks back to back
+    if config.mining_config.target_block_interval.is_zero() {
+        return Err(NodeError::Other(String::from(
+            "the mining target block interval must be greater than zero",
+        )));
+    }
 
-    let miner_handle = tokio::spawn(async move { module.start(&mut miner_events_rx).await });
+    let mut module = mining_module::MiningModule::new(mining_module::MiningModuleConfig {
+        events_tx,
+        mempool_read_handle_factory,
+        target_block_interval: config.mining_config.target_block_interval,
+        difficulty: config.mining_config.difficulty,
+        max_idle_backoff: config.mining_config.max_idle_backoff,
+        node_status_handle: node_status_handle.clone(),
+    });
+
+    node_status_handle.expect_module(&module.name());
 
-    Ok(Some(miner_handle))
+    Ok(tokio::spawn(async move {
+        module.start(&mut miner_events_rx).await
+    }))
+}
+
+fn setup_vm_module(
+    node_status_handle: NodeStatusHandle,
+    events_tx: UnboundedSender<DirectedEvent>,
+    mut vm_events_rx: Receiver<Event>,
+) -> Result<ModuleHandle> {
+    let module = VmModule::new(VmModuleConfig {
+        events_tx,
+        node_status_handle: node_status_handle.clone(),
+    });
+
+    node_status_handle.expect_module(&module.label());
+
+    let mut vm_module_actor = ActorImpl::new(module);
+
+    Ok(tokio::spawn(async move {
+        vm_module_actor
+            .start(&mut vm_events_rx)
+            .await
+            .map_err(|err| NodeError::Other(err.to_string()))
+    }))
+}
+
+fn setup_event_store_module(
+    path: &std::path::Path,
+    node_status_handle: NodeStatusHandle,
+    events_tx: UnboundedSender<DirectedEvent>,
+    mut audit_events_rx: Receiver<Event>,
+) -> Result<ModuleHandle> {
+    let event_store = EventStore::new(&path.to_path_buf())
+        .map_err(|err| NodeError::Other(format!("failed to open the event store: {err}")))?;
+
+    let module = EventStoreModule::new(EventStoreModuleConfig {
+        events_tx,
+        event_store,
+        node_status_handle: node_status_handle.clone(),
+    });
+
+    node_status_handle.expect_module(&module.label());
+
+    let mut event_store_module_actor = ActorImpl::new(module);
+
+    Ok(tokio::spawn(async move {
+        event_store_module_actor
+            .start(&mut audit_events_rx)
+            .await
+            .map_err(|err| NodeError::Other(err.to_string()))
+    }))
 }
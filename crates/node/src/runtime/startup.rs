@@ -0,0 +1,123 @@
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use telemetry::{error, field, info, info_span, Instrument};
+
+use crate::Result;
+
+/// Times the setup of each runtime module while a node starts.
+///
+/// Every setup runs within a `module_setup` span carrying the module's name,
+/// how long it took and, if it failed, the error it failed with. Once all
+/// modules are up, `log_summary` reports the total startup time along with a
+/// per-module breakdown.
+#[derive(Debug)]
+pub struct StartupTimer {
+    started_at: Instant,
+    modules: Vec<(&'static str, Duration)>,
+}
+
+impl Default for StartupTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StartupTimer {
+    pub fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            modules: vec![],
+        }
+    }
+
+    /// Runs the setup of `module`, recording how long it took
+    pub async fn time<T, F>(&mut self, module: &'static str, setup: F) -> Result<T>
+    where
+        F: Future<Output = Result<T>>,
+    {
+        let span = info_span!(
+            "module_setup",
+            module,
+            duration_ms = field::Empty,
+            error = field::Empty
+        );
+
+        let started_at = Instant::now();
+        let result = setup.instrument(span.clone()).await;
+        let elapsed = started_at.elapsed();
+
+        span.record("duration_ms", &(elapsed.as_millis() as u64));
+
+        if let Err(err) = &result {
+            span.record("error", &field::display(err));
+            error!(parent: &span, "failed to set up {module} after {elapsed:?}: {err}");
+        }
+
+        self.modules.push((module, elapsed));
+
+        result
+    }
+
+    /// Setup durations of the modules timed so far, in the order they were
+    /// set up
+    pub fn modules(&self) -> &[(&'static str, Duration)] {
+        &self.modules
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    pub fn log_summary(&self) {
+        let breakdown = self
+            .modules
+            .iter()
+            .map(|(module, elapsed)| format!("{module}: {elapsed:?}"))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        info!(
+            "Runtime modules started in {:?} ({breakdown})",
+            self.elapsed()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NodeError;
+
+    #[tokio::test]
+    async fn every_module_setup_is_timed_including_failed_ones() {
+        let mut timer = StartupTimer::new();
+
+        let value = timer
+            .time("mempool", async {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                Ok(1)
+            })
+            .await
+            .unwrap();
+
+        let failed = timer
+            .time("state_store", async {
+                Err::<(), _>(NodeError::Other(String::from("boom")))
+            })
+            .await;
+
+        assert_eq!(value, 1);
+        assert!(matches!(failed, Err(NodeError::Other(reason)) if reason == "boom"));
+
+        let modules = timer.modules();
+        assert_eq!(
+            modules.iter().map(|(name, _)| *name).collect::<Vec<_>>(),
+            ["mempool", "state_store"]
+        );
+        assert!(modules[0].1 >= Duration::from_millis(10));
+        assert!(timer.elapsed() >= modules[0].1 + modules[1].1);
+    }
+}
@@ -11,15 +11,26 @@ use tokio::sync::broadcast::error::TryRecvError;
 use vrrb_core::{
     account::Account,
     event_router::{DirectedEvent, Event, Topic},
+    node_status::NodeStatusHandle,
     serde_helpers::decode_from_binary_byte_slice,
-    txn::Txn,
+    txn::{Txn, ValidationError},
+    txn_receipts::{TxnReceipt, TxnReceiptsHandle},
 };
 
-use crate::{result::Result, NodeError, RuntimeModule};
+use crate::{
+    report_state_change,
+    result::Result,
+    runtime_state_from_actor_state,
+    NodeError,
+    RuntimeModule,
+};
 
 pub struct StateModuleConfig {
     pub db: VrrbDb,
     pub events_tx: tokio::sync::mpsc::UnboundedSender<DirectedEvent>,
+    /// Source of the block height included txns are recorded at
+    pub node_status_handle: NodeStatusHandle,
+    pub txn_receipts: TxnReceiptsHandle,
 }
 
 #[derive(Debug)]
@@ -29,6 +40,8 @@ pub struct StateModule {
     label: ActorLabel,
     id: ActorId,
     events_tx: tokio::sync::mpsc::UnboundedSender<DirectedEvent>,
+    node_status_handle: NodeStatusHandle,
+    txn_receipts: TxnReceiptsHandle,
 }
 
 /// StateModule manages all state persistence and updates within VrrbNodes
@@ -39,6 +52,8 @@ impl StateModule {
         Self {
             db: config.db,
             events_tx: config.events_tx,
+            node_status_handle: config.node_status_handle,
+            txn_receipts: config.txn_receipts,
             status: ActorState::Stopped,
             label: String::from("State"),
             id: uuid::Uuid::new_v4().to_string(),
@@ -73,6 +88,10 @@ impl StateModule {
             .insert_transaction(txn)
             .map_err(|err| NodeError::Other(err.to_string()))?;
 
+        // NOTE: the txn is only included once a block holding it is
+        // committed, see `Event::BlockCommitted`
+        self.record_receipt(TxnReceipt::pending(txn_hash.clone()));
+
         self.events_tx
             .send((Topic::Storage, Event::TxnAddedToMempool(txn_hash)))
             .map_err(|err| NodeError::Other(err.to_string()))?;
@@ -80,6 +99,13 @@ impl StateModule {
         Ok(())
     }
 
+    /// Records `receipt` for the RPC server to look up, and stores it so it
+    /// outlives the receipts held in memory
+    fn record_receipt(&mut self, receipt: TxnReceipt) {
+        self.txn_receipts.record(receipt.clone());
+        self.db.insert_txn_receipt(receipt);
+    }
+
     fn insert_account(&mut self, key: Address, account: Account) -> Result<()> {
         self.db
             .insert_account(key, account)
@@ -108,6 +134,14 @@ impl Handler<Event> for StateModule {
     }
 
     fn set_status(&mut self, actor_status: ActorState) {
+        report_state_change(
+            &self.events_tx,
+            &self.node_status_handle,
+            self.name(),
+            runtime_state_from_actor_state(&self.status),
+            runtime_state_from_actor_state(&actor_status),
+            None,
+        );
         self.status = actor_status;
     }
 
@@ -121,7 +155,7 @@ impl Handler<Event> for StateModule {
 
     async fn handle(&mut self, event: Event) -> theater::Result<ActorState> {
         match event {
-            Event::Stop => {
+            Event::Stop | Event::Shutdown { .. } => {
                 return Ok(ActorState::Stopped);
             },
 
@@ -130,6 +164,35 @@ impl Handler<Event> for StateModule {
                     .map_err(|err| TheaterError::Other(err.to_string()))?;
             },
 
+            // NOTE: a duplicate refers to a txn that is still pending, its
+            // receipt is left as is
+            Event::TxnRejected { txn_hash, reason } if reason != ValidationError::Duplicate => {
+                self.record_receipt(TxnReceipt::rejected(txn_hash, reason.to_string()));
+            },
+
+            Event::TxnExpired { txn_hash } => {
+                self.record_receipt(TxnReceipt::dropped(
+                    txn_hash,
+                    String::from("expired in the mempool"),
+                ));
+            },
+
+            Event::TxnEvicted { txn_hash } => {
+                self.record_receipt(TxnReceipt::dropped(
+                    txn_hash,
+                    String::from("evicted from the full mempool"),
+                ));
+            },
+
+            Event::BlockCommitted { block_height, txns } => {
+                // NOTE: read by the RPC status and state sync snapshots
+                self.node_status_handle.set_block_height(block_height);
+
+                for txn_hash in txns {
+                    self.record_receipt(TxnReceipt::included(txn_hash, Some(block_height)));
+                }
+            },
+
             Event::CreateAccountRequested((address, account_bytes)) => {
                 telemetry::info!(
                     "creating account {address} with new state",
@@ -149,6 +212,69 @@ impl Handler<Event> for StateModule {
                         .map_err(|err| TheaterError::Other(err.to_string()))?;
                 }
             },
+            Event::AccountKeyRotationRequested(rotation) => {
+                let address = rotation.address.clone();
+
+                // NOTE: rotations are checked against the stored key, so a
+                // rejected one is logged rather than stopping the module
+                match self.db.rotate_account_key(rotation) {
+                    Ok(_) => telemetry::info!("rotated the key of account {address}"),
+                    Err(err) => {
+                        telemetry::warn!("rejected key rotation for account {address}: {err}")
+                    },
+                }
+            },
+
+            Event::StateSyncChunk {
+                peer,
+                offset,
+                total,
+                accounts,
+            } => {
+                let accounts = accounts
+                    .into_iter()
+                    .map(|(address, account_bytes)| {
+                        decode_from_binary_byte_slice(&account_bytes)
+                            .map(|account| (address, account))
+                    })
+                    .collect::<std::result::Result<Vec<(Address, Account)>, _>>();
+
+                let applied = match accounts {
+                    Ok(accounts) => {
+                        let count = accounts.len() as u64;
+                        self.db.restore_accounts(accounts);
+
+                        telemetry::info!(
+                            "stored accounts {offset}..{} of {total} synced from {}",
+                            offset + count,
+                            peer.address
+                        );
+
+                        Ok(count)
+                    },
+                    Err(err) => {
+                        telemetry::warn!(
+                            "discarded invalid accounts synced from {}: {err}",
+                            peer.address
+                        );
+
+                        Err(err.to_string())
+                    },
+                };
+
+                // NOTE: the state sync module waits for every chunk to be
+                // applied before requesting the next one
+                self.events_tx
+                    .send((
+                        Topic::Network,
+                        Event::StateSyncChunkApplied {
+                            peer,
+                            offset,
+                            applied,
+                        },
+                    ))
+                    .map_err(|err| TheaterError::Other(err.to_string()))?;
+            },
 
             Event::NoOp => {},
             _ => {},
@@ -168,6 +294,7 @@ mod tests {
     use vrrb_core::{
         event_router::{DirectedEvent, Event},
         txn::null_txn,
+        txn_receipts::TxnReceiptStatus,
     };
 
     use super::*;
@@ -181,9 +308,14 @@ mod tests {
 
         let db_config = VrrbDbConfig::default();
 
-        let db = VrrbDb::new(db_config);
+        let db = VrrbDb::new(db_config).unwrap();
 
-        let mut state_module = StateModule::new(StateModuleConfig { events_tx, db });
+        let mut state_module = StateModule::new(StateModuleConfig {
+            events_tx,
+            db,
+            node_status_handle: NodeStatusHandle::default(),
+            txn_receipts: TxnReceiptsHandle::default(),
+        });
 
         let mut state_module = ActorImpl::new(state_module);
 
@@ -210,9 +342,14 @@ mod tests {
 
         let db_config = VrrbDbConfig::default();
 
-        let db = VrrbDb::new(db_config);
+        let db = VrrbDb::new(db_config).unwrap();
 
-        let mut state_module = StateModule::new(StateModuleConfig { events_tx, db });
+        let mut state_module = StateModule::new(StateModuleConfig {
+            events_tx,
+            db,
+            node_status_handle: NodeStatusHandle::default(),
+            txn_receipts: TxnReceiptsHandle::default(),
+        });
 
         let mut state_module = ActorImpl::new(state_module);
 
@@ -239,9 +376,14 @@ mod tests {
 
         let db_config = VrrbDbConfig::default();
 
-        let db = VrrbDb::new(db_config);
+        let db = VrrbDb::new(db_config).unwrap();
 
-        let mut state_module = StateModule::new(StateModuleConfig { events_tx, db });
+        let mut state_module = StateModule::new(StateModuleConfig {
+            events_tx,
+            db,
+            node_status_handle: NodeStatusHandle::default(),
+            txn_receipts: TxnReceiptsHandle::default(),
+        });
 
         let mut state_module = ActorImpl::new(state_module);
 
@@ -266,4 +408,120 @@ mod tests {
         handle.await.unwrap();
         events_handle.await.unwrap();
     }
+
+    #[tokio::test]
+    #[serial]
+    async fn state_runtime_records_txn_receipts() {
+        let (events_tx, _events_rx) = tokio::sync::mpsc::unbounded_channel::<DirectedEvent>();
+
+        let db_config = VrrbDbConfig {
+            path: env::temp_dir().join(vrrb_core::helpers::generate_random_string()),
+            ..Default::default()
+        };
+
+        let db = VrrbDb::new(db_config).unwrap();
+        let db_read_handle = db.read_handle();
+
+        let node_status_handle = NodeStatusHandle::default();
+        let txn_receipts = TxnReceiptsHandle::default();
+
+        let state_module = StateModule::new(StateModuleConfig {
+            events_tx,
+            db,
+            node_status_handle: node_status_handle.clone(),
+            txn_receipts: txn_receipts.clone(),
+        });
+
+        let mut state_module = ActorImpl::new(state_module);
+
+        let (ctrl_tx, mut ctrl_rx) = tokio::sync::broadcast::channel::<Event>(10);
+
+        let handle = tokio::spawn(async move {
+            state_module.start(&mut ctrl_rx).await.unwrap();
+        });
+
+        let included = null_txn();
+
+        let mut rejected = null_txn();
+        rejected.nonce = 1;
+
+        let mut expired = null_txn();
+        expired.nonce = 2;
+
+        let mut evicted = null_txn();
+        evicted.nonce = 3;
+
+        let mut validated = null_txn();
+        validated.nonce = 4;
+
+        ctrl_tx.send(Event::TxnValidated(included.clone())).unwrap();
+        ctrl_tx
+            .send(Event::TxnValidated(validated.clone()))
+            .unwrap();
+        ctrl_tx
+            .send(Event::BlockCommitted {
+                block_height: 7,
+                txns: vec![included.digest()],
+            })
+            .unwrap();
+        ctrl_tx
+            .send(Event::TxnRejected {
+                txn_hash: rejected.digest(),
+                reason: ValidationError::InsufficientBalance {
+                    balance: 0,
+                    amount: 1,
+                },
+            })
+            .unwrap();
+        ctrl_tx
+            .send(Event::TxnExpired {
+                txn_hash: expired.digest(),
+            })
+            .unwrap();
+        ctrl_tx
+            .send(Event::TxnEvicted {
+                txn_hash: evicted.digest(),
+            })
+            .unwrap();
+        ctrl_tx.send(Event::Stop).unwrap();
+
+        handle.await.unwrap();
+
+        assert_eq!(node_status_handle.block_height(), 7);
+
+        for txn in [&expired, &evicted] {
+            assert_eq!(
+                txn_receipts
+                    .get(&txn.digest())
+                    .map(|receipt| receipt.status),
+                Some(TxnReceiptStatus::Dropped)
+            );
+        }
+
+        // NOTE: validated txns stay pending until a block holding them is
+        // committed
+        assert_eq!(
+            txn_receipts.get(&validated.digest()),
+            Some(TxnReceipt::pending(validated.digest()))
+        );
+        assert_eq!(
+            txn_receipts.get(&included.digest()),
+            Some(TxnReceipt::included(included.digest(), Some(7)))
+        );
+        assert_eq!(
+            db_read_handle.get_txn_receipt(&included.digest()),
+            Some(TxnReceipt::included(included.digest(), Some(7)))
+        );
+        assert_eq!(
+            txn_receipts.get(&rejected.digest()),
+            Some(TxnReceipt::rejected(
+                rejected.digest(),
+                ValidationError::InsufficientBalance {
+                    balance: 0,
+                    amount: 1
+                }
+                .to_string()
+            ))
+        );
+    }
 }
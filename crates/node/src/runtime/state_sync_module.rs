@@ -0,0 +1,390 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use network::{
+    message::{MessageBody, MAX_STATE_SYNC_CHUNK_SIZE},
+    network::PeerBootstrapper,
+};
+use primitives::SecretKey;
+use storage::vrrbdb::VrrbDbReadHandle;
+use telemetry::{info, warn};
+use theater::{ActorId, ActorLabel, ActorState, Handler};
+use tokio::{
+    sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+    task::JoinHandle,
+};
+use vrrb_core::{
+    account::Account,
+    event_router::{DirectedEvent, Event, PeerData, Topic},
+    node_status::NodeStatusHandle,
+    serde_helpers::decode_from_binary_byte_slice,
+};
+
+use crate::{report_state_change, runtime_state_from_actor_state, NodeError, Result};
+
+pub struct StateSyncModuleConfig {
+    pub events_tx: UnboundedSender<DirectedEvent>,
+    pub vrrbdb_read_handle: VrrbDbReadHandle,
+    pub bootstrapper: PeerBootstrapper,
+    /// This node, as advertised to the peers it syncs from
+    pub local_peer: PeerData,
+    /// Key used to sign the requests sent to peers
+    pub secret_key: SecretKey,
+    pub node_status_handle: NodeStatusHandle,
+}
+
+/// How long the state module may take to apply a synced chunk before the
+/// sync from the peer that served it is given up on
+const CHUNK_APPLY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Catches a node joining an existing network up with the state of its peers.
+///
+/// Once a peer joins while the node's state is still empty, the peer's
+/// accounts are requested chunk by chunk, all of them read from a single
+/// snapshot of the peer's state. Each chunk is handed over to the state module
+/// through an `Event::StateSyncChunk`, and the next one is only requested once
+/// the state module confirmed it applied it. Should a peer fail to serve a
+/// chunk, or a chunk not be applied, the sync starts over from the next known
+/// peer, and so on until one of them serves every chunk. If none does, the
+/// sync is retried once another peer joins.
+///
+/// Syncs run in the background, so the module keeps handling events, the
+/// state module's confirmations included, while one is in progress.
+#[derive(Debug)]
+pub struct StateSyncModule {
+    status: ActorState,
+    label: ActorLabel,
+    id: ActorId,
+    events_tx: UnboundedSender<DirectedEvent>,
+    vrrbdb_read_handle: VrrbDbReadHandle,
+    bootstrapper: PeerBootstrapper,
+    local_peer: PeerData,
+    secret_key: SecretKey,
+    node_status_handle: NodeStatusHandle,
+    /// Peers the node may sync from, in the order they joined
+    peers: Vec<PeerData>,
+    synced: bool,
+    /// Hands the state module's confirmations over to the sync in progress
+    applied_tx: Option<UnboundedSender<AppliedChunk>>,
+    sync_handle: Option<JoinHandle<()>>,
+}
+
+impl StateSyncModule {
+    pub fn new(config: StateSyncModuleConfig) -> Self {
+        Self {
+            events_tx: config.events_tx,
+            vrrbdb_read_handle: config.vrrbdb_read_handle,
+            bootstrapper: config.bootstrapper,
+            local_peer: config.local_peer,
+            secret_key: config.secret_key,
+            node_status_handle: config.node_status_handle,
+            peers: vec![],
+            synced: false,
+            applied_tx: None,
+            sync_handle: None,
+            status: ActorState::Stopped,
+            label: String::from("StateSync"),
+            id: uuid::Uuid::new_v4().to_string(),
+        }
+    }
+
+    fn name(&self) -> String {
+        String::from("State sync module")
+    }
+
+    /// Starts syncing from the known peers in the background, in place of
+    /// the sync in progress if any
+    fn start_sync(&mut self) {
+        self.stop_sync();
+
+        let (applied_tx, applied_rx) = unbounded_channel();
+
+        let mut sync = StateSync {
+            events_tx: self.events_tx.clone(),
+            bootstrapper: self.bootstrapper.clone(),
+            local_peer: self.local_peer.clone(),
+            secret_key: self.secret_key,
+            applied_rx,
+        };
+
+        let peers = self.peers.clone();
+
+        self.applied_tx = Some(applied_tx);
+        self.sync_handle = Some(tokio::spawn(async move { sync.run(peers).await }));
+    }
+
+    fn stop_sync(&mut self) {
+        if let Some(sync_handle) = self.sync_handle.take() {
+            sync_handle.abort();
+        }
+
+        self.applied_tx = None;
+    }
+
+    /// Whether a sync is still running, the confirmations channel closes once
+    /// it's done
+    fn is_syncing(&self) -> bool {
+        self.applied_tx
+            .as_ref()
+            .map_or(false, |applied_tx| !applied_tx.is_closed())
+    }
+}
+
+/// The state module's reply to a chunk: the peer that served it, the offset
+/// it started at and the amount of accounts applied
+type AppliedChunk = (PeerData, u64, std::result::Result<u64, String>);
+
+/// A single run of a state sync, moved into the task it runs in
+struct StateSync {
+    events_tx: UnboundedSender<DirectedEvent>,
+    bootstrapper: PeerBootstrapper,
+    local_peer: PeerData,
+    secret_key: SecretKey,
+    applied_rx: UnboundedReceiver<AppliedChunk>,
+}
+
+impl StateSync {
+    /// Syncs from the given peers one after the other until one of them
+    /// serves its whole state
+    async fn run(&mut self, peers: Vec<PeerData>) {
+        for peer in peers.iter() {
+            info!("Syncing state from {}", peer.address);
+
+            match self.sync_from(peer).await {
+                Ok(accounts) => {
+                    info!("Synced {accounts} accounts from {}", peer.address);
+
+                    let event = Event::StateSyncComplete {
+                        peer: peer.clone(),
+                        accounts,
+                    };
+
+                    if let Err(err) = self.events_tx.send((Topic::Network, event)) {
+                        warn!("failed to report completed state sync: {err}");
+                    }
+
+                    return;
+                },
+                Err(err) => {
+                    warn!(
+                        "State sync from {} failed: {err}, trying another peer",
+                        peer.address
+                    );
+                },
+            }
+        }
+
+        warn!(
+            "Unable to sync state from any of the {} known peers, retrying once another one joins",
+            peers.len()
+        );
+    }
+
+    /// Requests every account held by `peer`, returning how many were synced
+    async fn sync_from(&mut self, peer: &PeerData) -> Result<u64> {
+        let mut snapshot = None;
+        let mut offset = 0;
+
+        loop {
+            let request = MessageBody::StateSyncRequest {
+                requestor: self.local_peer.clone(),
+                snapshot,
+                offset,
+                limit: MAX_STATE_SYNC_CHUNK_SIZE,
+            };
+
+            let reply = self
+                .bootstrapper
+                .request(peer.address, request, &self.secret_key)
+                .await
+                .map_err(|err| NodeError::Other(err.to_string()))?;
+
+            // NOTE: a peer that released the snapshot replies from a new one,
+            // mixing both would skip or duplicate accounts
+            let (chunk_snapshot, total, accounts) = match reply {
+                MessageBody::StateSyncChunk {
+                    snapshot: chunk_snapshot,
+                    offset: chunk_offset,
+                    total,
+                    accounts,
+                    ..
+                } if chunk_offset == offset
+                    && snapshot.map_or(true, |snapshot| snapshot == chunk_snapshot) =>
+                {
+                    (chunk_snapshot, total, accounts)
+                },
+                _ => {
+                    return Err(NodeError::Other(format!(
+                        "unexpected reply to state sync request for offset {offset}"
+                    )))
+                },
+            };
+
+            snapshot = Some(chunk_snapshot);
+
+            // NOTE: a chunk is only handed over once every account in it is
+            // known to be valid, so a faulty peer can't leave state half
+            // written
+            for (address, account_bytes) in accounts.iter() {
+                decode_from_binary_byte_slice::<Account>(account_bytes).map_err(|err| {
+                    NodeError::Other(format!("peer sent invalid account {address}: {err}"))
+                })?;
+            }
+
+            let received = accounts.len() as u64;
+
+            if received > 0 {
+                self.events_tx
+                    .send((
+                        Topic::Storage,
+                        Event::StateSyncChunk {
+                            peer: peer.clone(),
+                            offset,
+                            total,
+                            accounts,
+                        },
+                    ))
+                    .map_err(|err| NodeError::Other(err.to_string()))?;
+
+                let applied = tokio::time::timeout(
+                    CHUNK_APPLY_TIMEOUT,
+                    self.wait_until_applied(peer, offset),
+                )
+                .await
+                .map_err(|_| {
+                    NodeError::Other(format!(
+                        "accounts {offset}..{} were not applied within {CHUNK_APPLY_TIMEOUT:?}",
+                        offset + received
+                    ))
+                })??;
+
+                if applied != received {
+                    return Err(NodeError::Other(format!(
+                        "only {applied} of the {received} accounts from offset {offset} were applied"
+                    )));
+                }
+            }
+
+            offset += received;
+
+            info!("Synced {offset} of {total} accounts from {}", peer.address);
+
+            if received == 0 || offset >= total {
+                return Ok(offset);
+            }
+        }
+    }
+
+    /// Waits for the state module to apply the chunk `peer` served from
+    /// `offset`, returning the amount of accounts it applied
+    async fn wait_until_applied(&mut self, peer: &PeerData, offset: u64) -> Result<u64> {
+        while let Some((applied_peer, applied_offset, applied)) = self.applied_rx.recv().await {
+            // NOTE: replies to chunks of an earlier attempt are stale
+            if applied_peer == *peer && applied_offset == offset {
+                return applied.map_err(|err| {
+                    NodeError::Other(format!(
+                        "accounts from offset {offset} were rejected: {err}"
+                    ))
+                });
+            }
+        }
+
+        Err(NodeError::Other(String::from("state sync was stopped")))
+    }
+}
+
+#[async_trait]
+impl Handler<Event> for StateSyncModule {
+    fn id(&self) -> ActorId {
+        self.id.clone()
+    }
+
+    fn label(&self) -> ActorLabel {
+        self.name()
+    }
+
+    fn status(&self) -> ActorState {
+        self.status.clone()
+    }
+
+    fn set_status(&mut self, actor_status: ActorState) {
+        report_state_change(
+            &self.events_tx,
+            &self.node_status_handle,
+            self.name(),
+            runtime_state_from_actor_state(&self.status),
+            runtime_state_from_actor_state(&actor_status),
+            None,
+        );
+        self.status = actor_status;
+    }
+
+    fn on_stop(&self) {
+        info!(
+            "{}-{} received stop signal. Stopping",
+            self.name(),
+            self.label()
+        );
+    }
+
+    async fn handle(&mut self, event: Event) -> theater::Result<ActorState> {
+        match event {
+            Event::Stop | Event::Shutdown { .. } => {
+                self.stop_sync();
+                return Ok(ActorState::Stopped);
+            },
+
+            Event::PeerJoined(peer) => {
+                if !self.peers.contains(&peer) {
+                    self.peers.push(peer);
+                }
+
+                if self.synced || self.is_syncing() {
+                    return Ok(ActorState::Running);
+                }
+
+                // NOTE: nodes that already hold state, e.g. from a genesis,
+                // are the ones others sync from
+                if !self.vrrbdb_read_handle.state_store_snapshot().is_empty() {
+                    info!("State is already initialized, not syncing from peers");
+                    self.synced = true;
+                    return Ok(ActorState::Running);
+                }
+
+                self.start_sync();
+            },
+
+            Event::PeerLeft(peer) => {
+                self.peers.retain(|known| *known != peer);
+            },
+
+            // NOTE: explicit requests sync from the given peer first, even if
+            // the node already holds state
+            Event::StateSyncRequest(peer) => {
+                self.peers.retain(|known| *known != peer);
+                self.peers.insert(0, peer);
+
+                self.start_sync();
+            },
+
+            Event::StateSyncChunkApplied {
+                peer,
+                offset,
+                applied,
+            } => {
+                if let Some(applied_tx) = &self.applied_tx {
+                    let _ = applied_tx.send((peer, offset, applied));
+                }
+            },
+
+            Event::StateSyncComplete { .. } => {
+                self.synced = true;
+            },
+
+            Event::NoOp => {},
+            _ => {},
+        }
+
+        Ok(ActorState::Running)
+    }
+}
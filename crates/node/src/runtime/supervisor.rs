@@ -0,0 +1,393 @@
+use std::{fmt, future::Future, pin::Pin, task::Poll};
+
+use telemetry::{error, info, warn};
+use tokio::{
+    sync::broadcast::Receiver,
+    task::{JoinError, JoinHandle},
+};
+use vrrb_config::{ModuleFailurePolicy, SupervisionConfig};
+use vrrb_core::event_router::{Event, EventSubscriber, ShutdownReason, Topic};
+
+use crate::Result;
+
+pub type ModuleHandle = JoinHandle<Result<()>>;
+
+type RestartFuture = Pin<Box<dyn Future<Output = Result<ModuleHandle>> + Send>>;
+
+/// Starts a runtime module over from scratch, handing it a fresh
+/// subscription to the topic it listens to. Events published while the
+/// module was down are never delivered to it.
+pub struct ModuleRestarter {
+    topic: Topic,
+    restart: Box<dyn FnMut(Receiver<Event>) -> RestartFuture + Send>,
+}
+
+impl ModuleRestarter {
+    pub fn new<F, Fut>(topic: Topic, mut restart: F) -> Self
+    where
+        F: FnMut(Receiver<Event>) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<ModuleHandle>> + Send + 'static,
+    {
+        Self {
+            topic,
+            restart: Box::new(move |events_rx| Box::pin(restart(events_rx))),
+        }
+    }
+}
+
+impl fmt::Debug for ModuleRestarter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ModuleRestarter")
+            .field("topic", &self.topic)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A runtime module that stopped while it was supervised
+#[derive(Debug)]
+pub struct ModuleExit {
+    pub module: &'static str,
+    pub outcome: std::result::Result<Result<()>, JoinError>,
+}
+
+impl fmt::Display for ModuleExit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.outcome {
+            Ok(Ok(_)) => write!(f, "{} module stopped unexpectedly", self.module),
+            Ok(Err(err)) => write!(f, "{} module failed: {err}", self.module),
+            Err(err) if err.is_panic() => write!(f, "{} module panicked", self.module),
+            Err(err) => write!(f, "{} module was aborted: {err}", self.module),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct SupervisedModule {
+    name: &'static str,
+    handle: Option<ModuleHandle>,
+    restarter: Option<ModuleRestarter>,
+    restarts: u32,
+}
+
+/// Watches the tasks of a node's runtime modules while the node runs.
+///
+/// Modules that stop, be it because they panicked, failed or returned, are
+/// either restarted, shut the node down or are let go of, according to the
+/// failure policy configured for them. Modules handed no `ModuleRestarter`
+/// can't be restarted and shut the node down instead.
+#[derive(Debug)]
+pub struct ModuleSupervisor {
+    config: SupervisionConfig,
+    subscriber: EventSubscriber,
+    modules: Vec<SupervisedModule>,
+}
+
+impl ModuleSupervisor {
+    pub fn new(config: SupervisionConfig, subscriber: EventSubscriber) -> Self {
+        Self {
+            config,
+            subscriber,
+            modules: vec![],
+        }
+    }
+
+    /// Starts supervising the task of `module`, if it was started
+    pub fn supervise(
+        &mut self,
+        module: &'static str,
+        handle: Option<ModuleHandle>,
+        restarter: Option<ModuleRestarter>,
+    ) {
+        if let Some(handle) = handle {
+            self.modules.push(SupervisedModule {
+                name: module,
+                handle: Some(handle),
+                restarter,
+                restarts: 0,
+            });
+        }
+    }
+
+    /// Stops supervising `module`, returning its task if it's still around
+    pub fn take(&mut self, module: &'static str) -> Option<ModuleHandle> {
+        let idx = self.modules.iter().position(|m| m.name == module)?;
+
+        self.modules.remove(idx).handle
+    }
+
+    /// Stops supervising every module, returning the tasks still around
+    pub fn take_all(&mut self) -> Vec<ModuleHandle> {
+        self.modules
+            .drain(..)
+            .filter_map(|module| module.handle)
+            .collect()
+    }
+
+    /// Whether no module is supervised anymore
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+
+    /// Amount of times `module` was restarted
+    pub fn restarts(&self, module: &str) -> u32 {
+        self.modules
+            .iter()
+            .find(|m| m.name == module)
+            .map(|m| m.restarts)
+            .unwrap_or_default()
+    }
+
+    /// Waits for any supervised module to stop. Never completes while no
+    /// module is supervised.
+    ///
+    /// This is cancel safe, a module's task is only let go of once its exit
+    /// is returned.
+    pub async fn next_exit(&mut self) -> ModuleExit {
+        std::future::poll_fn(|cx| {
+            for module in self.modules.iter_mut() {
+                if let Some(handle) = module.handle.as_mut() {
+                    if let Poll::Ready(outcome) = Pin::new(handle).poll(cx) {
+                        module.handle = None;
+
+                        return Poll::Ready(ModuleExit {
+                            module: module.name,
+                            outcome,
+                        });
+                    }
+                }
+            }
+
+            Poll::Pending
+        })
+        .await
+    }
+
+    /// Reacts to a module stopping according to its failure policy. Returns
+    /// the reason the node has to shut down for, unless the module was
+    /// restarted.
+    pub async fn handle_exit(&mut self, exit: ModuleExit) -> Option<ShutdownReason> {
+        let shutdown = Some(ShutdownReason::FatalError(exit.to_string()));
+
+        let module = match self.modules.iter_mut().find(|m| m.name == exit.module) {
+            Some(module) => module,
+            None => return shutdown,
+        };
+
+        match self.config.policy(module.name) {
+            ModuleFailurePolicy::Restart => {},
+            ModuleFailurePolicy::Shutdown => {
+                error!("{exit}, shutting down");
+                return shutdown;
+            },
+            ModuleFailurePolicy::Ignore => {
+                error!("{exit}, carrying on without it");
+                return None;
+            },
+        }
+
+        let restarter = match module.restarter.as_mut() {
+            Some(restarter) => restarter,
+            None => {
+                error!("{exit} and can't be restarted, shutting down");
+                return shutdown;
+            },
+        };
+
+        if module.restarts >= self.config.max_restarts {
+            error!("{exit} after {} restarts, shutting down", module.restarts);
+            return shutdown;
+        }
+
+        module.restarts += 1;
+
+        warn!(
+            "{exit}, restarting it ({}/{})",
+            module.restarts, self.config.max_restarts
+        );
+
+        tokio::time::sleep(self.config.restart_delay).await;
+
+        let events_rx = match self.subscriber.subscribe(&restarter.topic) {
+            Ok(events_rx) => events_rx,
+            Err(err) => {
+                error!("failed to restart the {} module: {err}", module.name);
+                return shutdown;
+            },
+        };
+
+        match (restarter.restart)(events_rx).await {
+            Ok(handle) => {
+                info!("restarted the {} module", module.name);
+                module.handle = Some(handle);
+                None
+            },
+            Err(err) => {
+                error!("failed to restart the {} module: {err}", module.name);
+                shutdown
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicU32, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    use vrrb_core::event_router::EventRouter;
+
+    use super::*;
+    use crate::NodeError;
+
+    fn supervisor(config: SupervisionConfig) -> ModuleSupervisor {
+        let mut router = EventRouter::new();
+        router.add_topic(Topic::State, Some(10));
+
+        ModuleSupervisor::new(
+            SupervisionConfig {
+                restart_delay: Duration::ZERO,
+                ..config
+            },
+            router.subscriber(),
+        )
+    }
+
+    fn panicking_module() -> ModuleHandle {
+        tokio::spawn(async { panic!("deliberate module panic") })
+    }
+
+    /// Restarts modules into `restarted`, counting the restarts in `count`
+    fn counting_restarter(
+        count: Arc<AtomicU32>,
+        restarted: fn() -> ModuleHandle,
+    ) -> ModuleRestarter {
+        ModuleRestarter::new(Topic::State, move |_events_rx| {
+            count.fetch_add(1, Ordering::SeqCst);
+            async move { Ok(restarted()) }
+        })
+    }
+
+    fn idle_module() -> ModuleHandle {
+        tokio::spawn(std::future::pending())
+    }
+
+    #[tokio::test]
+    async fn panicking_modules_are_restarted_when_configured_to() {
+        let restarts = Arc::new(AtomicU32::new(0));
+        let mut supervisor = supervisor(SupervisionConfig {
+            vm: ModuleFailurePolicy::Restart,
+            ..Default::default()
+        });
+
+        supervisor.supervise(
+            "vm",
+            Some(panicking_module()),
+            Some(counting_restarter(restarts.clone(), idle_module)),
+        );
+
+        let exit = supervisor.next_exit().await;
+        assert_eq!(exit.module, "vm");
+        assert!(matches!(&exit.outcome, Err(err) if err.is_panic()));
+
+        assert_eq!(supervisor.handle_exit(exit).await, None);
+        assert_eq!(restarts.load(Ordering::SeqCst), 1);
+        assert_eq!(supervisor.restarts("vm"), 1);
+
+        // NOTE: the restarted module is supervised in place of the old one
+        let handle = supervisor.take("vm").unwrap();
+        assert!(!handle.is_finished());
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn panicking_modules_shut_the_node_down_when_configured_to() {
+        let restarts = Arc::new(AtomicU32::new(0));
+        let mut supervisor = supervisor(SupervisionConfig {
+            vm: ModuleFailurePolicy::Shutdown,
+            ..Default::default()
+        });
+
+        supervisor.supervise(
+            "vm",
+            Some(panicking_module()),
+            Some(counting_restarter(restarts.clone(), idle_module)),
+        );
+
+        let exit = supervisor.next_exit().await;
+
+        assert!(matches!(
+            supervisor.handle_exit(exit).await,
+            Some(ShutdownReason::FatalError(reason)) if reason == "vm module panicked"
+        ));
+        assert_eq!(restarts.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn ignored_modules_are_let_go_of() {
+        let mut supervisor = supervisor(SupervisionConfig {
+            state_sync: ModuleFailurePolicy::Ignore,
+            ..Default::default()
+        });
+
+        supervisor.supervise("state_sync", Some(panicking_module()), None);
+
+        let exit = supervisor.next_exit().await;
+
+        assert_eq!(supervisor.handle_exit(exit).await, None);
+        assert!(supervisor.take("state_sync").is_none());
+    }
+
+    #[tokio::test]
+    async fn modules_that_cannot_be_restarted_shut_the_node_down() {
+        let mut supervisor = supervisor(SupervisionConfig {
+            state_store: ModuleFailurePolicy::Restart,
+            ..Default::default()
+        });
+
+        supervisor.supervise(
+            "state_store",
+            Some(tokio::spawn(async {
+                Err(NodeError::Other(String::from("disk full")))
+            })),
+            None,
+        );
+
+        let exit = supervisor.next_exit().await;
+
+        assert!(matches!(
+            supervisor.handle_exit(exit).await,
+            Some(ShutdownReason::FatalError(reason)) if reason.contains("disk full")
+        ));
+    }
+
+    #[tokio::test]
+    async fn modules_failing_past_max_restarts_shut_the_node_down() {
+        let restarts = Arc::new(AtomicU32::new(0));
+        let mut supervisor = supervisor(SupervisionConfig {
+            miner: ModuleFailurePolicy::Restart,
+            max_restarts: 2,
+            ..Default::default()
+        });
+
+        supervisor.supervise(
+            "miner",
+            Some(panicking_module()),
+            Some(counting_restarter(restarts.clone(), panicking_module)),
+        );
+
+        for _ in 0..2 {
+            let exit = supervisor.next_exit().await;
+            assert_eq!(supervisor.handle_exit(exit).await, None);
+        }
+
+        let exit = supervisor.next_exit().await;
+
+        assert!(supervisor.handle_exit(exit).await.is_some());
+        assert_eq!(restarts.load(Ordering::SeqCst), 2);
+    }
+}
@@ -111,7 +111,7 @@ impl Handler<Event> for SwarmModule {
 
     async fn handle(&mut self, event: Event) -> theater::Result<ActorState> {
         match event {
-            Event::Stop => {
+            Event::Stop | Event::Shutdown { .. } => {
                 self.node.kill();
                 return Ok(ActorState::Stopped);
             },
@@ -1,24 +1,90 @@
-use std::{path::PathBuf, result::Result as StdResult};
+use std::{collections::HashMap, path::PathBuf, result::Result as StdResult, sync::Arc};
 
 use async_trait::async_trait;
+use indexmap::IndexMap;
+use primitives::{Address, ChainId};
+use rayon::{prelude::*, ThreadPool, ThreadPoolBuilder};
+use storage::vrrbdb::VrrbDbReadHandle;
 use telemetry::info;
-use tokio::sync::broadcast::{error::TryRecvError, Receiver};
-use vrrb_core::event_router::{Event, Topic};
+use tokio::sync::{
+    broadcast::{error::TryRecvError, Receiver},
+    mpsc::{Receiver as MpscReceiver, UnboundedSender},
+};
+use vrrb_core::{
+    account::{Account, AccountNonce},
+    event_router::{recv_reporting_lag, DirectedEvent, Event, Topic},
+    fee::FeeModel,
+    node_status::NodeStatusHandle,
+    txn::{TxAmount, Txn, ValidationError},
+};
 
-use crate::{result::Result, NodeError, RuntimeModule, RuntimeModuleState};
+use crate::{report_state_change, result::Result, NodeError, RuntimeModule, RuntimeModuleState};
+
+pub struct ValidatorModuleConfig {
+    pub events_tx: UnboundedSender<DirectedEvent>,
+    pub vrrbdb_read_handle: VrrbDbReadHandle,
+    /// Receiving end of the node's validation queue
+    pub validation_rx: MpscReceiver<Txn>,
+    /// Chain id transactions must be meant for
+    pub chain_id: ChainId,
+    /// Turns away transactions that don't specify a chain id
+    pub reject_txns_without_chain_id: bool,
+    /// Amount of worker threads queued transactions are validated on
+    pub parallelism: usize,
+    /// Computes the minimum fee each transaction has to offer
+    pub fee_model: Arc<dyn FeeModel>,
+    /// Transactions encoding to more bytes than this are rejected
+    pub max_txn_size_bytes: usize,
+    /// Queued transactions are left waiting until the node is operational
+    pub node_status_handle: NodeStatusHandle,
+}
+
+/// Upper bound on the amount of queued transactions validated together
+pub const MAX_VALIDATION_BATCH_SIZE: usize = 1024;
 
 pub struct ValidatorModule {
     running_status: RuntimeModuleState,
+    events_tx: UnboundedSender<DirectedEvent>,
+    vrrbdb_read_handle: VrrbDbReadHandle,
+    validation_rx: MpscReceiver<Txn>,
+    chain_id: ChainId,
+    reject_txns_without_chain_id: bool,
+    fee_model: Arc<dyn FeeModel>,
+    max_txn_size_bytes: usize,
+    node_status_handle: NodeStatusHandle,
+    worker_pool: ThreadPool,
 }
 
 /// ValidatorModule manages all validation tasks within VrrbNodes
 /// it runs as an indepdendant module such that it can be enabled and disabled
 /// as necessary.
 impl ValidatorModule {
-    pub fn new() -> Self {
-        Self {
+    pub fn new(config: ValidatorModuleConfig) -> Result<Self> {
+        let worker_pool = ThreadPoolBuilder::new()
+            .num_threads(config.parallelism.max(1))
+            .thread_name(|idx| format!("txn-validator-{idx}"))
+            .build()
+            .map_err(|err| {
+                NodeError::Other(format!("failed to create validator worker pool: {err}"))
+            })?;
+
+        Ok(Self {
             running_status: RuntimeModuleState::Stopped,
-        }
+            events_tx: config.events_tx,
+            vrrbdb_read_handle: config.vrrbdb_read_handle,
+            validation_rx: config.validation_rx,
+            chain_id: config.chain_id,
+            reject_txns_without_chain_id: config.reject_txns_without_chain_id,
+            fee_model: config.fee_model,
+            max_txn_size_bytes: config.max_txn_size_bytes,
+            node_status_handle: config.node_status_handle,
+            worker_pool,
+        })
+    }
+
+    /// Amount of worker threads queued transactions are validated on
+    pub fn parallelism(&self) -> usize {
+        self.worker_pool.current_num_threads()
     }
 }
 
@@ -33,15 +99,41 @@ impl RuntimeModule for ValidatorModule {
     }
 
     async fn start(&mut self, events_rx: &mut Receiver<Event>) -> Result<()> {
-        info!("{0} started", self.name());
+        info!(
+            "{0} started, validating on {1} workers",
+            self.name(),
+            self.parallelism()
+        );
+
+        self.set_running_status(RuntimeModuleState::Running, None);
+
+        loop {
+            let event = tokio::select! {
+                event = recv_reporting_lag(events_rx, &Topic::Consensus, &self.events_tx) => {
+                    match event {
+                        Ok(event) => event,
+                        Err(_) => break,
+                    }
+                },
+                _ = self.node_status_handle.operational(), if !self.node_status_handle.is_operational() => {
+                    continue;
+                },
+                Some(txn) = self.validation_rx.recv(), if self.node_status_handle.is_operational() => {
+                    let batch = self.drain_validation_queue(txn);
+                    self.validate_queued_batch(batch);
+                    continue;
+                },
+            };
 
-        while let Ok(event) = events_rx.recv().await {
             info!("{} received {event:?}", self.name());
 
-            if event == Event::Stop {
+            if event.is_shutdown() {
                 info!("{0} received stop signal. Stopping", self.name());
 
-                self.running_status = RuntimeModuleState::Terminating;
+                self.set_running_status(
+                    RuntimeModuleState::Terminating,
+                    Some(String::from("stop signal received")),
+                );
 
                 break;
             }
@@ -49,13 +141,25 @@ impl RuntimeModule for ValidatorModule {
             self.process_event(event);
         }
 
-        self.running_status = RuntimeModuleState::Stopped;
+        self.set_running_status(RuntimeModuleState::Stopped, None);
 
         Ok(())
     }
 }
 
 impl ValidatorModule {
+    fn set_running_status(&mut self, status: RuntimeModuleState, reason: Option<String>) {
+        report_state_change(
+            &self.events_tx,
+            &self.node_status_handle,
+            self.name(),
+            self.running_status.clone(),
+            status.clone(),
+            reason,
+        );
+        self.running_status = status;
+    }
+
     fn decode_event(&mut self, event: StdResult<Event, TryRecvError>) -> Event {
         match event {
             Ok(cmd) => cmd,
@@ -75,8 +179,79 @@ impl ValidatorModule {
         }
     }
 
+    /// Collects `first` along with every other transaction already waiting
+    /// in the validation queue, up to `MAX_VALIDATION_BATCH_SIZE` of them
+    fn drain_validation_queue(&mut self, first: Txn) -> Vec<Txn> {
+        let mut batch = vec![first];
+
+        while batch.len() < MAX_VALIDATION_BATCH_SIZE {
+            match self.validation_rx.try_recv() {
+                Ok(txn) => batch.push(txn),
+                Err(_) => break,
+            }
+        }
+
+        batch
+    }
+
+    /// Validates a batch of queued transactions against the current state of
+    /// their senders' accounts and reports the outcomes
+    fn validate_queued_batch(&mut self, batch: Vec<Txn>) {
+        let rules = ValidationRules {
+            chain_id: self.chain_id,
+            reject_txns_without_chain_id: self.reject_txns_without_chain_id,
+            fee_model: self.fee_model.clone(),
+            max_txn_size_bytes: self.max_txn_size_bytes,
+        };
+
+        let accounts = self.sender_accounts(&batch);
+
+        let outcomes = validate_batch(&self.worker_pool, batch, &accounts, &rules);
+
+        for (mut txn, outcome) in outcomes {
+            let txn_hash = txn.digest();
+
+            let event = match outcome {
+                Ok(fee) => {
+                    txn.set_computed_fee(fee);
+                    Event::TxnValidated(txn)
+                },
+                Err(reason) => {
+                    info!("Transaction {txn_hash} rejected: {reason}");
+                    Event::TxnRejected { txn_hash, reason }
+                },
+            };
+
+            if let Err(err) = self.events_tx.send((Topic::Storage, event)) {
+                telemetry::error!("failed to report validation outcome: {err}");
+            }
+        }
+    }
+
+    /// Looks up the account of every sender within `batch` once, ahead of
+    /// handing the batch to the worker pool
+    fn sender_accounts(&self, batch: &[Txn]) -> HashMap<Address, Account> {
+        let mut accounts = HashMap::new();
+
+        for txn in batch {
+            let address = txn.sender();
+
+            if accounts.contains_key(&address) {
+                continue;
+            }
+
+            if let Some(account) = self.vrrbdb_read_handle.get_account(&address) {
+                accounts.insert(address, account);
+            }
+        }
+
+        accounts
+    }
+
     fn process_event(&mut self, event: Event) {
         match event {
+            // NOTE: txns reach the validator through the validation queue
+            Event::TxnAddedToMempool(_) => {},
             Event::BlockConfirmed(_) => {
                 // do something
             },
@@ -85,3 +260,520 @@ impl ValidatorModule {
         }
     }
 }
+
+/// Node settings transactions are checked against on top of their senders'
+/// accounts
+#[derive(Debug, Clone)]
+pub struct ValidationRules {
+    pub chain_id: ChainId,
+    pub reject_txns_without_chain_id: bool,
+    pub fee_model: Arc<dyn FeeModel>,
+    pub max_txn_size_bytes: usize,
+}
+
+/// Checks a single transaction against its sender's account, returning the
+/// minimum fee computed for it
+pub fn validate_txn(
+    txn: &Txn,
+    sender_account: Option<&Account>,
+    rules: &ValidationRules,
+) -> StdResult<TxAmount, ValidationError> {
+    let fee = rules.fee_model.estimate_fee(txn);
+
+    txn.validate_size(rules.max_txn_size_bytes)?;
+    txn.validate_chain_id(rules.chain_id, !rules.reject_txns_without_chain_id)?;
+    txn.validate_fee(fee)?;
+    txn.validate_against(sender_account)?;
+
+    Ok(fee)
+}
+
+/// Validates a batch of transactions on `worker_pool`, returning every
+/// transaction along with its outcome, i.e. its computed fee if it passed.
+///
+/// Transactions are grouped by sender. Groups are validated in parallel,
+/// while the transactions within a group are validated one after the other
+/// in nonce order, which is also the order their outcomes are returned in.
+/// Each transaction is checked against the nonce and balance its sender's
+/// account is left with by the ones that passed before it.
+pub fn validate_batch(
+    worker_pool: &ThreadPool,
+    batch: Vec<Txn>,
+    accounts: &HashMap<Address, Account>,
+    rules: &ValidationRules,
+) -> Vec<(Txn, StdResult<TxAmount, ValidationError>)> {
+    let mut by_sender: IndexMap<Address, Vec<Txn>> = IndexMap::new();
+
+    for txn in batch {
+        by_sender.entry(txn.sender()).or_default().push(txn);
+    }
+
+    let groups = by_sender
+        .into_iter()
+        .map(|(sender, mut txns)| {
+            txns.sort_by_key(|txn| txn.nonce);
+            (sender, txns)
+        })
+        .collect::<Vec<(Address, Vec<Txn>)>>();
+
+    let outcomes = worker_pool.install(|| {
+        groups
+            .into_par_iter()
+            .map(|(sender, txns)| {
+                let mut sender_account = accounts.get(&sender).cloned();
+
+                txns.into_iter()
+                    .map(|txn| {
+                        let outcome = validate_txn(&txn, sender_account.as_ref(), rules);
+
+                        if outcome.is_ok() {
+                            apply_to_account(&txn, &mut sender_account);
+                        }
+
+                        (txn, outcome)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+    });
+
+    outcomes.into_iter().flatten().collect()
+}
+
+/// Moves the working copy of `txn`'s sender account past `txn`, which
+/// passed validation, so it can't be replayed and its amount and fee can't
+/// be spent again within the same batch
+fn apply_to_account(txn: &Txn, sender_account: &mut Option<Account>) {
+    let account = sender_account.get_or_insert_with(|| Account::new(txn.sender_public_key));
+
+    // NOTE: validation already rejected txns whose total cost overflows
+    let total_cost = txn.total_cost().unwrap_or(TxAmount::MAX);
+
+    account.nonce = AccountNonce::try_from(txn.nonce).unwrap_or(AccountNonce::MAX);
+    account.debits = account.debits.saturating_add(total_cost);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use mempool::ValidationQueue;
+    use primitives::DEFAULT_CHAIN_ID;
+    use storage::vrrbdb::{VrrbDb, VrrbDbConfig};
+    use tokio::sync::{broadcast, mpsc::unbounded_channel};
+    use vrrb_core::{
+        fee::{FlatFee, PerByteFee},
+        keypair::KeyPair,
+        txn::{null_txn, TransactionDigest, DEFAULT_MAX_TXN_SIZE_BYTES},
+    };
+
+    use super::*;
+
+    fn operational_node_status() -> NodeStatusHandle {
+        let node_status_handle = NodeStatusHandle::new();
+        node_status_handle.set_operational(true);
+        node_status_handle
+    }
+
+    /// Runs `txn` through a validator module expecting `DEFAULT_CHAIN_ID`
+    /// and charging no fees, returning the reason it was rejected for
+    async fn rejection_reason(txn: Txn) -> ValidationError {
+        rejection_reason_with_fees(txn, Arc::new(FlatFee::default())).await
+    }
+
+    async fn rejection_reason_with_fees(txn: Txn, fee_model: Arc<dyn FeeModel>) -> ValidationError {
+        let (events_tx, mut events_rx) = unbounded_channel::<DirectedEvent>();
+
+        let mut vrrbdb_config = VrrbDbConfig::default();
+        vrrbdb_config.path =
+            std::env::temp_dir().join(vrrb_core::helpers::generate_random_string());
+        let vrrbdb = VrrbDb::new(vrrbdb_config).unwrap();
+
+        let (validation_queue, validation_rx) = ValidationQueue::new(10);
+
+        let mut module = ValidatorModule::new(ValidatorModuleConfig {
+            events_tx,
+            vrrbdb_read_handle: vrrbdb.read_handle(),
+            validation_rx,
+            chain_id: DEFAULT_CHAIN_ID,
+            reject_txns_without_chain_id: false,
+            parallelism: 2,
+            fee_model,
+            max_txn_size_bytes: DEFAULT_MAX_TXN_SIZE_BYTES,
+            node_status_handle: operational_node_status(),
+        })
+        .unwrap();
+
+        let (ctrl_tx, mut ctrl_rx) = broadcast::channel::<Event>(10);
+
+        let handle = tokio::spawn(async move { module.start(&mut ctrl_rx).await });
+
+        validation_queue.try_send(txn.clone()).unwrap();
+
+        let mut rejections = vec![];
+        while rejections.is_empty() {
+            if let Some((topic, Event::TxnRejected { txn_hash, reason })) = events_rx.recv().await {
+                assert_eq!(topic, Topic::Storage);
+                rejections.push((txn_hash, reason));
+            }
+        }
+
+        ctrl_tx.send(Event::Stop).unwrap();
+        handle.await.unwrap().unwrap();
+
+        assert_eq!(validation_queue.depth(), 0);
+        assert_eq!(rejections[0].0, txn.digest());
+
+        rejections.remove(0).1
+    }
+
+    #[tokio::test]
+    async fn rejected_txns_are_reported_with_a_reason() {
+        // NOTE: null txns aren't signed over their own payload
+        let reason = rejection_reason(null_txn()).await;
+
+        assert!(matches!(reason, ValidationError::BadSignature(_)));
+    }
+
+    #[tokio::test]
+    async fn txns_meant_for_other_chains_are_rejected() {
+        let keypair = KeyPair::random();
+        let mut txn = null_txn();
+        txn.sender_public_key = *keypair.get_miner_public_key();
+        txn.set_chain_id(DEFAULT_CHAIN_ID + 1);
+        txn.sign(keypair.get_miner_secret_key());
+
+        assert_eq!(
+            rejection_reason(txn).await,
+            ValidationError::ChainIdMismatch {
+                provided: DEFAULT_CHAIN_ID + 1,
+                expected: DEFAULT_CHAIN_ID,
+            }
+        );
+    }
+
+    #[test]
+    fn txns_over_the_maximum_size_are_rejected() {
+        let keypair = KeyPair::random();
+        let mut txn = null_txn();
+        txn.sender_public_key = *keypair.get_miner_public_key();
+        txn.sign(keypair.get_miner_secret_key());
+
+        let rules = ValidationRules {
+            chain_id: DEFAULT_CHAIN_ID,
+            reject_txns_without_chain_id: false,
+            fee_model: Arc::new(FlatFee::default()),
+            max_txn_size_bytes: txn.size() - 1,
+        };
+
+        assert_eq!(
+            validate_txn(&txn, None, &rules),
+            Err(ValidationError::TooLarge {
+                size: txn.size(),
+                max: txn.size() - 1,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn txns_offering_less_than_the_minimum_fee_are_rejected() {
+        let fee_model = PerByteFee {
+            base_fee: 10,
+            fee_per_byte: 1,
+        };
+
+        let keypair = KeyPair::random();
+        let mut txn = null_txn();
+        txn.sender_public_key = *keypair.get_miner_public_key();
+        txn.nonce = 1;
+        txn.sign(keypair.get_miner_secret_key());
+
+        let minimum = fee_model.estimate_fee(&txn);
+        txn.set_fee(minimum - 1);
+        txn.sign(keypair.get_miner_secret_key());
+
+        assert_eq!(
+            rejection_reason_with_fees(txn.clone(), Arc::new(fee_model)).await,
+            ValidationError::FeeTooLow {
+                declared: minimum - 1,
+                minimum,
+            }
+        );
+
+        // NOTE: offering the minimum gets the txn through, with the minimum
+        // recorded as its computed fee
+        txn.set_fee(minimum);
+        txn.sign(keypair.get_miner_secret_key());
+        let rules = ValidationRules {
+            chain_id: DEFAULT_CHAIN_ID,
+            reject_txns_without_chain_id: false,
+            fee_model: Arc::new(fee_model),
+            max_txn_size_bytes: DEFAULT_MAX_TXN_SIZE_BYTES,
+        };
+
+        assert_eq!(validate_txn(&txn, None, &rules), Ok(minimum));
+    }
+
+    #[tokio::test]
+    async fn txns_must_use_a_nonce_past_the_current_account_nonce() {
+        let (events_tx, mut events_rx) = unbounded_channel::<DirectedEvent>();
+
+        let mut vrrbdb_config = VrrbDbConfig::default();
+        vrrbdb_config.path =
+            std::env::temp_dir().join(vrrb_core::helpers::generate_random_string());
+        let mut vrrbdb = VrrbDb::new(vrrbdb_config).unwrap();
+
+        let keypair = KeyPair::random();
+        let address = Address::new(*keypair.get_miner_public_key());
+        let mut account = Account::new(*keypair.get_miner_public_key());
+        account.credits = 100;
+        vrrbdb
+            .insert_account(address.clone(), account.clone())
+            .unwrap();
+
+        // NOTE: new accounts start at nonce 0, each update bumps it by one
+        for _ in 0..5 {
+            vrrbdb
+                .update_account(address.clone(), account.clone())
+                .unwrap();
+            account.bump_nonce();
+        }
+        assert_eq!(vrrbdb.read_handle().get_account(&address).unwrap().nonce, 5);
+
+        let (validation_queue, validation_rx) = ValidationQueue::new(10);
+
+        let mut module = ValidatorModule::new(ValidatorModuleConfig {
+            events_tx,
+            vrrbdb_read_handle: vrrbdb.read_handle(),
+            validation_rx,
+            chain_id: DEFAULT_CHAIN_ID,
+            reject_txns_without_chain_id: false,
+            parallelism: 2,
+            fee_model: Arc::new(FlatFee::default()),
+            max_txn_size_bytes: DEFAULT_MAX_TXN_SIZE_BYTES,
+            node_status_handle: operational_node_status(),
+        })
+        .unwrap();
+
+        let (ctrl_tx, mut ctrl_rx) = broadcast::channel::<Event>(10);
+        let handle = tokio::spawn(async move { module.start(&mut ctrl_rx).await });
+
+        let mut outcomes = HashMap::new();
+        for nonce in [4, 5, 6] {
+            let mut txn = null_txn();
+            txn.sender_public_key = *keypair.get_miner_public_key();
+            txn.nonce = nonce;
+            txn.sign(keypair.get_miner_secret_key());
+
+            outcomes.insert(txn.digest(), nonce);
+            validation_queue.try_send(txn).unwrap();
+        }
+
+        let mut rejections = HashMap::new();
+        let mut validated = vec![];
+        while rejections.len() + validated.len() < outcomes.len() {
+            match events_rx.recv().await {
+                Some((_, Event::TxnRejected { txn_hash, reason })) => {
+                    rejections.insert(outcomes[&txn_hash], reason);
+                },
+                Some((_, Event::TxnValidated(txn))) => {
+                    assert_eq!(txn.computed_fee(), Some(0));
+                    validated.push(txn.nonce)
+                },
+                Some(_) => {},
+                None => break,
+            }
+        }
+
+        ctrl_tx.send(Event::Stop).unwrap();
+        handle.await.unwrap().unwrap();
+
+        assert_eq!(
+            rejections.get(&4),
+            Some(&ValidationError::NonceTooLow {
+                expected: 6,
+                got: 4,
+            })
+        );
+
+        // NOTE: reusing the current nonce is a replay as well
+        assert_eq!(
+            rejections.get(&5),
+            Some(&ValidationError::NonceTooLow {
+                expected: 6,
+                got: 5,
+            })
+        );
+
+        assert_eq!(validated, vec![6]);
+    }
+
+    #[test]
+    fn txns_are_checked_against_the_account_left_by_earlier_txns_of_the_batch() {
+        let rules = ValidationRules {
+            chain_id: DEFAULT_CHAIN_ID,
+            reject_txns_without_chain_id: false,
+            fee_model: Arc::new(FlatFee::default()),
+            max_txn_size_bytes: DEFAULT_MAX_TXN_SIZE_BYTES,
+        };
+
+        let keypair = KeyPair::random();
+
+        let mut account = Account::new(*keypair.get_miner_public_key());
+        account.credits = 100;
+
+        let accounts = HashMap::from([(Address::new(*keypair.get_miner_public_key()), account)]);
+
+        let batch = [(2, 60), (1, 60), (1, 10), (3, 40)]
+            .into_iter()
+            .map(|(nonce, amount)| {
+                let mut txn = null_txn();
+                txn.sender_public_key = *keypair.get_miner_public_key();
+                txn.nonce = nonce;
+                txn.set_amount(amount);
+                txn.sign(keypair.get_miner_secret_key());
+                txn
+            })
+            .collect::<Vec<_>>();
+
+        let worker_pool = ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+
+        let outcomes = validate_batch(&worker_pool, batch, &accounts, &rules)
+            .into_iter()
+            .map(|(txn, outcome)| (txn.nonce, txn.amount, outcome))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            outcomes,
+            vec![
+                (1, 60, Ok(0)),
+                (
+                    1,
+                    10,
+                    Err(ValidationError::NonceTooLow {
+                        expected: 2,
+                        got: 1
+                    })
+                ),
+                (
+                    2,
+                    60,
+                    Err(ValidationError::InsufficientBalance {
+                        balance: 40,
+                        amount: 60,
+                    })
+                ),
+                (3, 40, Ok(0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn fees_are_debited_along_with_the_amount() {
+        let rules = ValidationRules {
+            chain_id: DEFAULT_CHAIN_ID,
+            reject_txns_without_chain_id: false,
+            fee_model: Arc::new(FlatFee::default()),
+            max_txn_size_bytes: DEFAULT_MAX_TXN_SIZE_BYTES,
+        };
+
+        let keypair = KeyPair::random();
+
+        let mut account = Account::new(*keypair.get_miner_public_key());
+        account.credits = 100;
+
+        let accounts = HashMap::from([(Address::new(*keypair.get_miner_public_key()), account)]);
+
+        let batch = [(1, 50, 10), (2, 45, 0), (3, 40, 0)]
+            .into_iter()
+            .map(|(nonce, amount, fee)| {
+                let mut txn = null_txn();
+                txn.sender_public_key = *keypair.get_miner_public_key();
+                txn.nonce = nonce;
+                txn.set_amount(amount);
+                txn.set_fee(fee);
+                txn.sign(keypair.get_miner_secret_key());
+                txn
+            })
+            .collect::<Vec<_>>();
+
+        let worker_pool = ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+
+        let outcomes = validate_batch(&worker_pool, batch, &accounts, &rules)
+            .into_iter()
+            .map(|(txn, outcome)| (txn.nonce, outcome.is_ok()))
+            .collect::<Vec<_>>();
+
+        // NOTE: the first txn leaves 40 behind, not the 50 its amount alone
+        // would
+        assert_eq!(outcomes, vec![(1, true), (2, false), (3, true)]);
+    }
+
+    #[test]
+    fn parallel_validation_matches_serial_validation() {
+        const SENDERS: usize = 16;
+        const TXNS_PER_SENDER: usize = 25;
+
+        let rules = ValidationRules {
+            chain_id: DEFAULT_CHAIN_ID,
+            reject_txns_without_chain_id: false,
+            fee_model: Arc::new(FlatFee::default()),
+            max_txn_size_bytes: DEFAULT_MAX_TXN_SIZE_BYTES,
+        };
+
+        let keypairs = (0..SENDERS).map(|_| KeyPair::random()).collect::<Vec<_>>();
+
+        // NOTE: every other sender is funded and some have used nonces up, so
+        // the batch holds a mix of valid and rejected txns
+        let mut accounts = HashMap::new();
+        for (idx, keypair) in keypairs.iter().enumerate().filter(|(idx, _)| idx % 2 == 0) {
+            let mut account = Account::new(*keypair.get_miner_public_key());
+            account.credits = 100;
+            account.nonce = (idx % 4) as u32;
+            accounts.insert(Address::new(*keypair.get_miner_public_key()), account);
+        }
+
+        let mut batch = vec![];
+        for nonce in (0..TXNS_PER_SENDER).rev() {
+            for keypair in &keypairs {
+                let mut txn = null_txn();
+                txn.sender_public_key = *keypair.get_miner_public_key();
+                txn.nonce = nonce as u128;
+                txn.set_amount(nonce as u128 * 5);
+                txn.sign(keypair.get_miner_secret_key());
+
+                batch.push(txn);
+            }
+        }
+
+        let serial_pool = ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+        let serial = validate_batch(&serial_pool, batch.clone(), &accounts, &rules)
+            .into_iter()
+            .map(|(txn, outcome)| (txn.digest(), outcome))
+            .collect::<HashSet<(TransactionDigest, StdResult<TxAmount, ValidationError>)>>();
+
+        let worker_pool = ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+        let outcomes = validate_batch(&worker_pool, batch, &accounts, &rules);
+
+        assert_eq!(outcomes.len(), SENDERS * TXNS_PER_SENDER);
+
+        let parallel = outcomes
+            .iter()
+            .map(|(txn, outcome)| (txn.digest(), outcome.clone()))
+            .collect::<HashSet<_>>();
+
+        assert_eq!(parallel, serial);
+        assert!(parallel.iter().any(|(_, outcome)| outcome.is_ok()));
+        assert!(parallel.iter().any(|(_, outcome)| outcome.is_err()));
+
+        // NOTE: each sender's txns come out in nonce order
+        let mut last_nonces: HashMap<Address, u128> = HashMap::new();
+        for (txn, _) in &outcomes {
+            let sender = txn.sender();
+
+            if let Some(last_nonce) = last_nonces.insert(sender, txn.nonce) {
+                assert!(last_nonce < txn.nonce);
+            }
+        }
+    }
+}
@@ -0,0 +1,267 @@
+use async_trait::async_trait;
+use telemetry::info;
+use theater::{ActorId, ActorLabel, ActorState, Handler, TheaterError};
+use trecho::vm::Cpu;
+use vrrb_core::{
+    event_router::{DirectedEvent, Event, Topic, VmError, VmExecutionRequest, VmExecutionResult},
+    node_status::NodeStatusHandle,
+};
+
+use crate::{report_state_change, runtime_state_from_actor_state};
+
+/// Upper bound on the gas limit of a single execution. Every node must agree on
+/// it for executions to have the same outcome across the network, so it isn't
+/// configurable.
+pub const MAX_VM_STEPS: u64 = 1_000_000;
+
+/// Index of the `a0` register, which holds a program's return value
+const RETURN_VALUE_REGISTER: usize = 10;
+
+pub struct VmModuleConfig {
+    pub events_tx: tokio::sync::mpsc::UnboundedSender<DirectedEvent>,
+    pub node_status_handle: NodeStatusHandle,
+}
+
+/// Runs programs, e.g. contract calls, on a `trecho` VM and reports back
+/// their results
+#[derive(Debug)]
+pub struct VmModule {
+    status: ActorState,
+    label: ActorLabel,
+    id: ActorId,
+    events_tx: tokio::sync::mpsc::UnboundedSender<DirectedEvent>,
+    node_status_handle: NodeStatusHandle,
+}
+
+impl VmModule {
+    pub fn new(config: VmModuleConfig) -> Self {
+        Self {
+            events_tx: config.events_tx,
+            node_status_handle: config.node_status_handle,
+            status: ActorState::Stopped,
+            label: String::from("VM"),
+            id: uuid::Uuid::new_v4().to_string(),
+        }
+    }
+
+    fn name(&self) -> String {
+        String::from("VM module")
+    }
+
+    /// Runs the requested program on a fresh `Cpu` until it runs past the end
+    /// of its code or faults. Each executed instruction consumes one unit of
+    /// gas, and the program is aborted with `VmError::OutOfGas` once it would
+    /// exceed its `max_steps`, capped at `MAX_VM_STEPS`.
+    pub fn execute(&self, request: &VmExecutionRequest) -> VmExecutionResult {
+        let max_steps = request.max_steps.min(MAX_VM_STEPS);
+        let mut cpu = Cpu::new(request.code.clone());
+        let mut steps = 0;
+
+        let outcome = loop {
+            let inst = match cpu.fetch() {
+                Ok(0) | Err(_) => break Ok(cpu.regs[RETURN_VALUE_REGISTER]),
+                Ok(inst) => inst,
+            };
+
+            if steps == max_steps {
+                break Err(VmError::OutOfGas { steps });
+            }
+
+            match cpu.execute(inst) {
+                Ok(next_pc) => cpu.pc = next_pc,
+                Err(err) => break Err(VmError::Fault(format!("{err:?}"))),
+            }
+
+            steps += 1;
+        };
+
+        VmExecutionResult {
+            id: request.id.clone(),
+            steps,
+            outcome,
+        }
+    }
+}
+
+#[async_trait]
+impl Handler<Event> for VmModule {
+    fn id(&self) -> ActorId {
+        self.id.clone()
+    }
+
+    fn label(&self) -> ActorLabel {
+        self.name()
+    }
+
+    fn status(&self) -> ActorState {
+        self.status.clone()
+    }
+
+    fn set_status(&mut self, actor_status: ActorState) {
+        report_state_change(
+            &self.events_tx,
+            &self.node_status_handle,
+            self.name(),
+            runtime_state_from_actor_state(&self.status),
+            runtime_state_from_actor_state(&actor_status),
+            None,
+        );
+        self.status = actor_status;
+    }
+
+    fn on_stop(&self) {
+        info!(
+            "{}-{} received stop signal. Stopping",
+            self.name(),
+            self.label()
+        );
+    }
+
+    async fn handle(&mut self, event: Event) -> theater::Result<ActorState> {
+        match event {
+            Event::Stop | Event::Shutdown { .. } => {
+                return Ok(ActorState::Stopped);
+            },
+
+            Event::VmExecutionRequested(request) => {
+                let result = self.execute(&request);
+
+                info!(
+                    "Program {} finished after {} steps: {:?}",
+                    result.id, result.steps, result.outcome
+                );
+
+                self.events_tx
+                    .send((Topic::State, Event::VmExecutionCompleted(result)))
+                    .map_err(|err| TheaterError::Other(err.to_string()))?;
+            },
+
+            Event::NoOp => {},
+            _ => {},
+        }
+
+        Ok(ActorState::Running)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use theater::{Actor, ActorImpl};
+    use tokio::sync::{broadcast, mpsc::unbounded_channel};
+
+    use super::*;
+
+    fn assemble(program: &[u32]) -> Vec<u8> {
+        program.iter().flat_map(|inst| inst.to_le_bytes()).collect()
+    }
+
+    /// addi a1, zero, 37; addi a2, zero, 5; add a0, a1, a2
+    fn mock_program() -> Vec<u8> {
+        assemble(&[0x02500593, 0x00500613, 0x00c58533])
+    }
+
+    /// jal zero, 0, i.e. jumps to itself forever
+    fn mock_endless_program() -> Vec<u8> {
+        assemble(&[0x0000006f])
+    }
+
+    fn mock_request(code: Vec<u8>, max_steps: u64) -> VmExecutionRequest {
+        VmExecutionRequest {
+            id: String::from("mock-program"),
+            code,
+            max_steps,
+        }
+    }
+
+    fn mock_module() -> VmModule {
+        let (events_tx, _) = unbounded_channel::<DirectedEvent>();
+
+        VmModule::new(VmModuleConfig {
+            events_tx,
+            node_status_handle: NodeStatusHandle::default(),
+        })
+    }
+
+    #[test]
+    fn programs_produce_deterministic_results() {
+        let module = mock_module();
+
+        let result = module.execute(&mock_request(mock_program(), 10));
+
+        assert_eq!(result.steps, 3);
+        assert_eq!(result.outcome, Ok(42));
+        assert_eq!(module.execute(&mock_request(mock_program(), 10)), result);
+    }
+
+    #[test]
+    fn endless_programs_run_out_of_gas() {
+        let module = mock_module();
+
+        let result = module.execute(&mock_request(mock_endless_program(), 1_000));
+
+        assert_eq!(result.steps, 1_000);
+        assert_eq!(result.outcome, Err(VmError::OutOfGas { steps: 1_000 }));
+        assert_eq!(
+            module.execute(&mock_request(mock_endless_program(), 1_000)),
+            result
+        );
+    }
+
+    #[test]
+    fn gas_limits_are_capped() {
+        let result = mock_module().execute(&mock_request(mock_endless_program(), u64::MAX));
+
+        assert_eq!(
+            result.outcome,
+            Err(VmError::OutOfGas {
+                steps: MAX_VM_STEPS
+            })
+        );
+    }
+
+    #[test]
+    fn programs_need_gas_for_every_instruction() {
+        let result = mock_module().execute(&mock_request(mock_program(), 2));
+
+        assert_eq!(result.steps, 2);
+        assert_eq!(result.outcome, Err(VmError::OutOfGas { steps: 2 }));
+    }
+
+    #[tokio::test]
+    async fn execution_requests_are_answered_with_their_result() {
+        let (events_tx, mut events_rx) = unbounded_channel::<DirectedEvent>();
+
+        let module = VmModule::new(VmModuleConfig {
+            events_tx,
+            node_status_handle: NodeStatusHandle::default(),
+        });
+
+        let mut module = ActorImpl::new(module);
+
+        let (ctrl_tx, mut ctrl_rx) = broadcast::channel::<Event>(10);
+
+        let handle = tokio::spawn(async move {
+            module.start(&mut ctrl_rx).await.unwrap();
+        });
+
+        ctrl_tx
+            .send(Event::VmExecutionRequested(mock_request(
+                mock_program(),
+                10,
+            )))
+            .unwrap();
+        ctrl_tx.send(Event::Stop).unwrap();
+
+        handle.await.unwrap();
+
+        let result = std::iter::from_fn(|| events_rx.try_recv().ok())
+            .find_map(|(topic, event)| match event {
+                Event::VmExecutionCompleted(result) if topic == Topic::State => Some(result),
+                _ => None,
+            })
+            .unwrap();
+
+        assert_eq!(result.id, "mock-program");
+        assert_eq!(result.outcome, Ok(42));
+    }
+}
@@ -1,22 +1,125 @@
 use async_trait::async_trait;
-use tokio::sync::broadcast::Receiver;
-use vrrb_core::event_router::Event;
+pub use primitives::RuntimeModuleState;
+use telemetry::{error, info};
+use theater::ActorState;
+use tokio::sync::{broadcast::Receiver, mpsc::UnboundedSender};
+use vrrb_core::{
+    event_router::{DirectedEvent, Event, Topic},
+    node_status::NodeStatusHandle,
+};
 
 use crate::result::Result;
 
-#[derive(Debug, Clone, Eq, PartialEq)]
-pub enum RuntimeModuleState {
-    Starting,
-    Running,
-    Stopped,
-    Terminating,
-}
-
 /// RuntimeModule represents a node component that is loaded on startup and
-/// controls whenever a node is terminated
+/// controls whenever a node is terminated.
+///
+/// `start` is async and is expected to run on its own tokio task until a
+/// shutdown event arrives on `events_rx`, awaiting events rather than polling
+/// for them. `BlockchainModule` is the minimal reference; see `MiningModule`
+/// and `ValidatorModule` for modules built around it.
 #[async_trait]
 pub trait RuntimeModule {
     fn name(&self) -> String;
     fn status(&self) -> RuntimeModuleState;
     async fn start(&mut self, events_rx: &mut Receiver<Event>) -> Result<()>;
 }
+
+/// Maps the state of a theater actor onto the lifecycle states the node
+/// reports for its runtime modules
+pub(crate) fn runtime_state_from_actor_state(state: &ActorState) -> RuntimeModuleState {
+    if *state == ActorState::Running {
+        RuntimeModuleState::Running
+    } else if *state == ActorState::Terminating {
+        RuntimeModuleState::Terminating
+    } else {
+        RuntimeModuleState::Stopped
+    }
+}
+
+/// Records a module's state transition on `node_status_handle`, which the
+/// node's readiness is read from, then announces it on the Control topic.
+/// Transitions into the state the module is already in are not reported.
+///
+/// The Control topic may drop events under load, so only the state recorded
+/// on `node_status_handle` is authoritative.
+pub(crate) fn report_state_change(
+    events_tx: &UnboundedSender<DirectedEvent>,
+    node_status_handle: &NodeStatusHandle,
+    module_name: String,
+    from: RuntimeModuleState,
+    to: RuntimeModuleState,
+    reason: Option<String>,
+) {
+    if from == to {
+        return;
+    }
+
+    match &reason {
+        Some(reason) => info!("{module_name} went from {from:?} to {to:?}: {reason}"),
+        None => info!("{module_name} went from {from:?} to {to:?}"),
+    }
+
+    node_status_handle.set_module_state(&module_name, to.clone());
+
+    let event = Event::ModuleStateChanged {
+        module_name,
+        from,
+        to,
+        reason,
+    };
+
+    if let Err(err) = events_tx.send((Topic::Control, event)) {
+        error!("failed to report module state change: {err}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc::unbounded_channel;
+
+    use super::*;
+
+    #[test]
+    fn only_actual_transitions_are_reported() {
+        let (events_tx, mut events_rx) = unbounded_channel::<DirectedEvent>();
+        let node_status_handle = NodeStatusHandle::new();
+
+        report_state_change(
+            &events_tx,
+            &node_status_handle,
+            String::from("Mempool module"),
+            RuntimeModuleState::Running,
+            RuntimeModuleState::Running,
+            None,
+        );
+
+        assert!(node_status_handle.module_states().is_empty());
+
+        report_state_change(
+            &events_tx,
+            &node_status_handle,
+            String::from("Mempool module"),
+            RuntimeModuleState::Running,
+            RuntimeModuleState::Stopped,
+            Some(String::from("stop signal received")),
+        );
+
+        assert_eq!(
+            events_rx.try_recv().unwrap(),
+            (
+                Topic::Control,
+                Event::ModuleStateChanged {
+                    module_name: String::from("Mempool module"),
+                    from: RuntimeModuleState::Running,
+                    to: RuntimeModuleState::Stopped,
+                    reason: Some(String::from("stop signal received")),
+                }
+            )
+        );
+        assert!(events_rx.try_recv().is_err());
+        assert_eq!(
+            node_status_handle.module_states().get("Mempool module"),
+            Some(&RuntimeModuleState::Stopped)
+        );
+    }
+}
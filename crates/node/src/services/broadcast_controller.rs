@@ -1,13 +1,22 @@
-use std::{collections::HashSet, net::SocketAddr, result::Result as StdResult};
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+    result::Result as StdResult,
+    time::{Duration, Instant},
+};
 
 use async_trait::async_trait;
 use bytes::Bytes;
 use network::{
-    message::{Message, MessageBody},
-    network::BroadcastEngine,
+    config::BroadCastError,
+    limiter::InboundVerdict,
+    message::{Message, MessageBody, MAX_STATE_SYNC_CHUNK_SIZE},
+    network::{BroadcastEngine, PeerBootstrapper, PeerManager, ReconnectOutcome},
+    ordering::ReorderBuffer,
 };
-use primitives::{NodeType, PeerId};
-use telemetry::{error, info, warn};
+use primitives::{NodeType, PeerId, PeerVersion, ProtocolVersion, SecretKey, PROTOCOL_VERSION};
+use storage::vrrbdb::{StateStoreReadHandle, VrrbDbReadHandle};
+use telemetry::{debug, error, info, warn};
 use theater::{ActorLabel, ActorState, Handler};
 use tokio::{
     sync::{
@@ -15,48 +24,542 @@ use tokio::{
             error::{RecvError, TryRecvError},
             Receiver,
         },
-        mpsc::Sender,
+        mpsc::{Sender, UnboundedSender},
     },
     task::JoinHandle,
 };
 use uuid::Uuid;
-use vrrb_core::event_router::{DirectedEvent, Event};
+use vrrb_core::{
+    event_router::{DirectedEvent, Event, PeerData, Topic},
+    node_status::NodeStatusHandle,
+    serde_helpers::encode_to_binary,
+};
 
 use crate::{NodeError, Result, RuntimeModule, RuntimeModuleState};
 
 pub const BROADCAST_CONTROLLER_BUFFER_SIZE: usize = 10000;
 
+/// How often messages held back by gaps in their sender's sequence are
+/// checked for having waited long enough, see `ReorderBuffer::expire`
+const REORDER_GAP_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How long a snapshot pinned for a peer's state sync is kept once the peer
+/// stops requesting chunks of it
+const STATE_SYNC_SNAPSHOT_TTL: Duration = Duration::from_secs(60);
+
+/// Most snapshots pinned for state syncs at once, the least recently used
+/// one is released to make room for another
+const MAX_STATE_SYNC_SNAPSHOTS: usize = 8;
+
 #[derive(Debug)]
 pub struct BroadcastEngineController {
     addr: SocketAddr,
     engine: BroadcastEngine,
+    events_tx: UnboundedSender<DirectedEvent>,
+    /// This node, as advertised to the peers it handshakes with
+    local_peer: PeerData,
+    /// Key used to sign messages sent by this node
+    secret_key: SecretKey,
+    bootstrap_node_addresses: Vec<SocketAddr>,
+    /// Peers that announced themselves to this node through a handshake
+    peers: HashMap<SocketAddr, PeerData>,
+    /// Restores the send order of the messages each peer sends this node
+    reorder_buffer: ReorderBuffer,
+    /// State served to peers catching up with the network
+    vrrbdb_read_handle: VrrbDbReadHandle,
+    /// Versions of the state pinned for the peers syncing from this node
+    state_sync_snapshots: StateSyncSnapshots,
+    /// Tells when the node is ready to take part in the network
+    node_status_handle: NodeStatusHandle,
 }
 
 impl BroadcastEngineController {
-    pub fn new(engine: BroadcastEngine) -> Self {
+    pub fn new(
+        mut engine: BroadcastEngine,
+        events_tx: UnboundedSender<DirectedEvent>,
+        local_peer: PeerData,
+        secret_key: SecretKey,
+        bootstrap_node_addresses: Vec<SocketAddr>,
+        vrrbdb_read_handle: VrrbDbReadHandle,
+        node_status_handle: NodeStatusHandle,
+    ) -> Self {
         let addr = engine.local_addr();
-        Self { engine, addr }
+        engine.set_secret_key(secret_key);
+
+        Self {
+            engine,
+            addr,
+            events_tx,
+            local_peer,
+            secret_key,
+            bootstrap_node_addresses,
+            peers: HashMap::new(),
+            reorder_buffer: ReorderBuffer::default(),
+            vrrbdb_read_handle,
+            state_sync_snapshots: StateSyncSnapshots::default(),
+            node_status_handle,
+        }
+    }
+
+    /// Returns a handle to the engine's peer table, see `PeerManager`
+    pub fn peer_manager(&self) -> PeerManager {
+        self.engine.peer_manager()
     }
 
-    pub async fn listen(&mut self, tx: Sender<Event>, rx: Receiver<Event>) -> Result<()> {
+    /// Returns a handle able to send requests to peers, see
+    /// `PeerBootstrapper::request`
+    pub fn bootstrapper(&self) -> PeerBootstrapper {
+        self.engine.bootstrapper()
+    }
+
+    /// Waits for the node to be ready, then announces it to the bootstrap
+    /// nodes and handles incoming peer connections until the engine stops
+    /// listening or a shutdown event arrives on `rx`
+    pub async fn listen(&mut self, tx: Sender<Event>, mut rx: Receiver<Event>) -> Result<()> {
+        // NOTE: connection attempts made in the meantime wait in the engine's
+        // queue of incoming connections
+        tokio::select! {
+            _ = self.node_status_handle.ready() => {},
+            _ = shutdown_requested(&mut rx) => return Ok(()),
+        }
+
+        let reconnect_handle = self.spawn_peer_reconnection_loop();
+        let bootstrap_handle = self.spawn_bootstrap_routine();
+
+        let result = self.listen_for_connections(tx, &mut rx).await;
+
+        reconnect_handle.abort();
+        bootstrap_handle.abort();
+
+        result
+    }
+
+    async fn listen_for_connections(
+        &mut self,
+        tx: Sender<Event>,
+        rx: &mut Receiver<Event>,
+    ) -> Result<()> {
+        let bootstrapper = self.engine.bootstrapper();
+        let peer_manager = self.engine.peer_manager();
+        let limiter = self.engine.inbound_limiter();
+        let peer_keys = self.engine.peer_keys();
         let listener = self.engine.get_incomming_connections();
+        let mut gap_check = tokio::time::interval(REORDER_GAP_CHECK_INTERVAL);
+
+        loop {
+            let (conn, mut conn_incoming) = tokio::select! {
+                incoming = listener.next() => match incoming {
+                    Some(incoming) => incoming,
+                    None => break,
+                },
+                _ = shutdown_requested(rx) => {
+                    info!("broadcast controller received stop signal. Stopping");
+                    break;
+                },
+                _ = gap_check.tick() => {
+                    // NOTE: messages held back by a gap that timed out are
+                    // handed over even if their sender went quiet
+                    for (peer_addr, delivery) in self.reorder_buffer.expire(Instant::now()) {
+                        if let Some(skipped) = delivery.skipped {
+                            warn!(
+                                "gave up waiting on messages {}..{} from {peer_addr}",
+                                skipped.start, skipped.end
+                            );
+                        }
+
+                        for message in delivery.messages {
+                            let body: MessageBody = message.data.into();
+
+                            if let Err(err) = tx.send(body.into()).await {
+                                error!("failed to forward data received from network: {err}");
+                            }
+                        }
+                    }
+
+                    continue;
+                },
+            };
+
+            let peer_addr = conn.remote_address();
+
+            let _slot = match limiter.admit_connection(peer_addr) {
+                Ok(slot) => slot,
+                Err(err) => {
+                    warn!("{err}");
+                    conn.close(Some(err.to_string()));
+                    continue;
+                },
+            };
 
-        while let Some((conn, mut conn_incoming)) = listener.next().await {
             let res = conn_incoming.next().await.map_err(|err| {
                 NodeError::Other(format!("unable to listen for new connections: {err}"))
             })?;
 
             let (_, _, raw_message) = res.unwrap_or((Bytes::new(), Bytes::new(), Bytes::new()));
 
+            match limiter.check_message(peer_addr) {
+                InboundVerdict::Accepted => {},
+                InboundVerdict::Dropped => continue,
+                InboundVerdict::Banned => {
+                    conn.close(Some(format!("{peer_addr} is banned")));
+                    continue;
+                },
+            }
+
             let message = Message::from(raw_message.to_vec());
 
-            let body: MessageBody = message.data.into();
+            // NOTE: handshakes bind their sender to the key they were signed
+            // with, everything else has to be signed by the bound key
+            let verified = match MessageBody::from(message.data.clone()) {
+                MessageBody::Handshake { .. } => {
+                    // NOTE: peers handshake when they (re)start, so the
+                    // sequence they number their messages with starts over
+                    peer_keys.bind(peer_addr, &message).map(|_| {
+                        self.reorder_buffer.reset(&peer_addr);
+                    })
+                },
+                _ => peer_keys.verify(peer_addr, &message),
+            };
+
+            if let Err(err) = verified {
+                warn!(
+                    "dropping message {} that failed verification: {err}",
+                    message.id
+                );
+                continue;
+            }
+
+            peer_manager.record_activity(peer_addr);
 
-            if let Err(err) = tx.send(body.into()).await {
-                error!("failed to forward data received from network: {err}");
+            let delivery = self.reorder_buffer.push(peer_addr, message);
+
+            if let Some(skipped) = delivery.skipped {
+                warn!(
+                    "gave up waiting on messages {}..{} from {peer_addr}",
+                    skipped.start, skipped.end
+                );
+            }
+
+            // NOTE: messages are handled in the order their sender sent them
+            for message in delivery.messages {
+                let body: MessageBody = message.data.into();
+
+                // NOTE: joining peers expect the list of known peers as a reply to
+                // their handshake
+                if let MessageBody::Handshake { peer, version } = &body {
+                    let negotiated_version = PROTOCOL_VERSION.negotiate(&version.protocol);
+
+                    let reply = match negotiated_version {
+                        Some(_) => {
+                            handshake_ack(&self.local_peer, &self.peers, peer, &self.secret_key)
+                        },
+                        None => handshake_rejection(&self.local_peer, &self.secret_key),
+                    };
+
+                    if let Err(err) = conn
+                        .send((Bytes::new(), Bytes::new(), Bytes::from(reply.as_bytes())))
+                        .await
+                    {
+                        warn!("failed to answer handshake from {}: {err}", peer.address);
+                        continue;
+                    }
+
+                    match negotiated_version {
+                        Some(negotiated_version) => {
+                            info!(
+                                "Peer {} joined through this node speaking protocol version {negotiated_version}",
+                                peer.address
+                            );
+
+                            bootstrapper.add_known_peer(peer.address, peer.peer_id.clone());
+                            bootstrapper
+                                .set_peer_protocol_version(peer.address, negotiated_version);
+
+                            // NOTE: joining peers learn about this node from its ack,
+                            // but it only learns about them from their handshake
+                            if self.peers.insert(peer.address, peer.clone()).is_none() {
+                                if let Err(err) = self
+                                    .events_tx
+                                    .send((Topic::Network, Event::PeerJoined(peer.clone())))
+                                {
+                                    error!("failed to report joined peer: {err}");
+                                }
+                            }
+                        },
+                        None => {
+                            report_incompatible_peer(
+                                &self.events_tx,
+                                peer.clone(),
+                                version.protocol,
+                            );
+                            continue;
+                        },
+                    }
+                }
+
+                // NOTE: peers catching up with the network expect a chunk of
+                // this node's accounts as a reply to their request
+                if let MessageBody::StateSyncRequest {
+                    requestor,
+                    snapshot,
+                    offset,
+                    limit,
+                } = &body
+                {
+                    let (snapshot, state) = self
+                        .state_sync_snapshots
+                        .get_or_pin(*snapshot, &self.vrrbdb_read_handle);
+
+                    let reply = state_sync_chunk(
+                        &self.local_peer,
+                        snapshot,
+                        &state,
+                        *offset,
+                        *limit,
+                        &self.secret_key,
+                    );
+
+                    if let Err(err) = conn
+                        .send((Bytes::new(), Bytes::new(), Bytes::from(reply.as_bytes())))
+                        .await
+                    {
+                        warn!(
+                            "failed to answer state sync request from {}: {err}",
+                            requestor.address
+                        );
+                    }
+                }
+
+                if let Err(err) = tx.send(body.into()).await {
+                    error!("failed to forward data received from network: {err}");
+                }
             }
         }
 
         Ok(())
     }
+
+    /// Handshakes with every configured bootstrap node, reporting each peer
+    /// discovered along the way. Unreachable bootstrap nodes are logged and
+    /// skipped rather than treated as fatal.
+    fn spawn_bootstrap_routine(&self) -> JoinHandle<()> {
+        let bootstrapper = self.engine.bootstrapper();
+        let bootstrap_node_addresses = self.bootstrap_node_addresses.clone();
+        let local_peer = self.local_peer.clone();
+        let secret_key = self.secret_key;
+        let events_tx = self.events_tx.clone();
+
+        tokio::spawn(async move {
+            for addr in bootstrap_node_addresses {
+                let peers = match bootstrapper.bootstrap(addr, &local_peer, &secret_key).await {
+                    Ok(peers) => peers,
+                    Err(BroadCastError::IncompatiblePeer {
+                        peer,
+                        remote_version,
+                    }) => {
+                        report_incompatible_peer(&events_tx, peer, remote_version);
+                        continue;
+                    },
+                    Err(BroadCastError::SelfConnection(addr)) => {
+                        debug!("Skipping bootstrap node {addr}: it is this node");
+                        continue;
+                    },
+                    Err(err) => {
+                        error!("unable to bootstrap through {addr}: {err}");
+                        continue;
+                    },
+                };
+
+                info!("Discovered {} peers through {addr}", peers.len());
+
+                for peer in peers {
+                    // NOTE: peers only take messages from nodes that handshook
+                    // with them, discovered ones are introduced to right away
+                    if bootstrapper.peer_key(&peer.address).is_none() {
+                        if let Err(err) = bootstrapper
+                            .introduce(peer.address, &local_peer, &secret_key)
+                            .await
+                        {
+                            debug!("unable to handshake with {}: {err}", peer.address);
+                        }
+                    }
+
+                    if let Err(err) = events_tx.send((Topic::Network, Event::PeerJoined(peer))) {
+                        error!("failed to report discovered peer: {err}");
+                    }
+                }
+            }
+        })
+    }
+
+    /// Periodically attempts to reconnect peers whose connections dropped,
+    /// reporting the outcome of each attempt to the rest of the node
+    fn spawn_peer_reconnection_loop(&self) -> JoinHandle<()> {
+        let reconnector = self.engine.reconnector();
+        let events_tx = self.events_tx.clone();
+        let period = reconnector.config().reconnect_base_delay;
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+
+            loop {
+                interval.tick().await;
+
+                for outcome in reconnector.reconnect_dropped_peers().await {
+                    let event = match outcome {
+                        ReconnectOutcome::Reconnected(peer_id) => Event::PeerReconnected(peer_id),
+                        ReconnectOutcome::Unreachable(peer_id) => Event::PeerUnreachable(peer_id),
+                    };
+
+                    if let Err(err) = events_tx.send((Topic::Network, event)) {
+                        error!("failed to report peer reconnection outcome: {err}");
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// Completes once a shutdown event arrives on `rx` or it is closed, skipping
+/// every other event. Cancel safe, so it can be raced against other futures.
+async fn shutdown_requested(rx: &mut Receiver<Event>) {
+    loop {
+        match rx.recv().await {
+            Ok(event) if event.is_shutdown() => return,
+            Ok(_) | Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => return,
+        }
+    }
+}
+
+/// Builds the reply to a joining peer's handshake, listing every other peer
+/// this node knows about
+fn handshake_ack(
+    local_peer: &PeerData,
+    peers: &HashMap<SocketAddr, PeerData>,
+    peer: &PeerData,
+    secret_key: &SecretKey,
+) -> Message {
+    let ack = MessageBody::HandshakeAck {
+        responder: local_peer.clone(),
+        peers: peers
+            .values()
+            .filter(|known| known.peer_id != peer.peer_id)
+            .cloned()
+            .collect(),
+        version: PeerVersion::current(),
+    };
+
+    Message::signed(ack, secret_key)
+}
+
+/// Builds the reply to a handshake from a peer speaking an incompatible
+/// protocol version
+fn handshake_rejection(local_peer: &PeerData, secret_key: &SecretKey) -> Message {
+    let rejection = MessageBody::HandshakeRejected {
+        responder: local_peer.clone(),
+        version: PeerVersion::current(),
+    };
+
+    Message::signed(rejection, secret_key)
+}
+
+/// Versions of this node's state pinned for the peers syncing from it, so
+/// every chunk of a sync is read from the same version no matter what's
+/// written to the state in the meantime
+#[derive(Debug, Default)]
+struct StateSyncSnapshots {
+    snapshots: HashMap<u64, (StateStoreReadHandle, Instant)>,
+}
+
+impl StateSyncSnapshots {
+    /// Returns the snapshot a sync reads from along with its id. Syncs that
+    /// just started get a new snapshot pinned, and so do the ones asking for
+    /// a snapshot that was released already.
+    fn get_or_pin(
+        &mut self,
+        snapshot: Option<u64>,
+        vrrbdb_read_handle: &VrrbDbReadHandle,
+    ) -> (u64, StateStoreReadHandle) {
+        let now = Instant::now();
+
+        self.snapshots
+            .retain(|_, (_, last_used)| now.duration_since(*last_used) < STATE_SYNC_SNAPSHOT_TTL);
+
+        if let Some(id) = snapshot {
+            if let Some((state, last_used)) = self.snapshots.get_mut(&id) {
+                *last_used = now;
+                return (id, state.clone());
+            }
+        }
+
+        if self.snapshots.len() >= MAX_STATE_SYNC_SNAPSHOTS {
+            let least_recently_used = self
+                .snapshots
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(id, _)| *id);
+
+            if let Some(id) = least_recently_used {
+                self.snapshots.remove(&id);
+            }
+        }
+
+        let id = rand::random::<u64>();
+        let state = vrrbdb_read_handle.state_store_snapshot();
+
+        self.snapshots.insert(id, (state.clone(), now));
+
+        (id, state)
+    }
+}
+
+/// Builds the reply to a peer's state sync request, sending back at most
+/// `MAX_STATE_SYNC_CHUNK_SIZE` accounts of the given snapshot
+fn state_sync_chunk(
+    local_peer: &PeerData,
+    snapshot: u64,
+    state: &StateStoreReadHandle,
+    offset: u64,
+    limit: u64,
+    secret_key: &SecretKey,
+) -> Message {
+    let (accounts, total) = state.entries_paged(
+        offset as usize,
+        limit.min(MAX_STATE_SYNC_CHUNK_SIZE) as usize,
+    );
+
+    let chunk = MessageBody::StateSyncChunk {
+        responder: local_peer.clone(),
+        snapshot,
+        offset,
+        total: total as u64,
+        accounts: accounts
+            .into_iter()
+            .map(|(address, account)| (address, encode_to_binary(&account).unwrap_or_default()))
+            .collect(),
+    };
+
+    Message::signed(chunk, secret_key)
+}
+
+fn report_incompatible_peer(
+    events_tx: &UnboundedSender<DirectedEvent>,
+    peer: PeerData,
+    remote_version: ProtocolVersion,
+) {
+    warn!(
+        "Refusing to peer with {}: it speaks protocol version {remote_version}, this node speaks {PROTOCOL_VERSION}",
+        peer.address
+    );
+
+    let event = Event::PeerIncompatible {
+        peer,
+        local_version: PROTOCOL_VERSION,
+        remote_version,
+    };
+
+    if let Err(err) = events_tx.send((Topic::Network, event)) {
+        error!("failed to report incompatible peer: {err}");
+    }
 }
@@ -1 +1,2 @@
 pub mod broadcast_controller;
+pub mod peer_table;
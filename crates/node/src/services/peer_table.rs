@@ -0,0 +1,75 @@
+use std::{
+    net::SocketAddr,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use network::{
+    config::BroadCastError,
+    network::{PeerConnectionState, PeerManager, PeerStatus},
+};
+use primitives::PeerId;
+use vrrb_rpc::rpc::{api, PeerTable, PeerTableError};
+
+/// Exposes the peer table of the broadcast engine to the JSON-RPC server
+#[derive(Debug, Clone)]
+pub struct BroadcastPeerTable {
+    peer_manager: PeerManager,
+}
+
+impl BroadcastPeerTable {
+    pub fn new(peer_manager: PeerManager) -> Self {
+        Self { peer_manager }
+    }
+}
+
+#[async_trait]
+impl PeerTable for BroadcastPeerTable {
+    fn peers(&self) -> Vec<api::PeerInfo> {
+        self.peer_manager
+            .peers()
+            .into_iter()
+            .map(peer_info)
+            .collect()
+    }
+
+    async fn add_peer(
+        &self,
+        addr: SocketAddr,
+        timeout: Duration,
+    ) -> Result<api::PeerInfo, PeerTableError> {
+        self.peer_manager
+            .connect(addr, timeout)
+            .await
+            .map(peer_info)
+            .map_err(|err| match err {
+                BroadCastError::ConnectTimeout(addr, _) => PeerTableError::Timeout(addr),
+                err => PeerTableError::Unreachable(addr, err.to_string()),
+            })
+    }
+
+    fn remove_peer(&self, peer_id: &PeerId) -> Result<(), PeerTableError> {
+        self.peer_manager
+            .disconnect(peer_id)
+            .map(|_| ())
+            .map_err(|_| PeerTableError::UnknownPeer(String::from_utf8_lossy(peer_id).into()))
+    }
+}
+
+fn peer_info(peer: PeerStatus) -> api::PeerInfo {
+    let connection_state = match peer.state {
+        PeerConnectionState::Connected => api::PeerConnectionState::Connected,
+        PeerConnectionState::Reconnecting => api::PeerConnectionState::Reconnecting,
+        PeerConnectionState::Known => api::PeerConnectionState::Known,
+    };
+
+    api::PeerInfo {
+        peer_id: peer.peer_id,
+        address: peer.address,
+        connection_state,
+        last_seen: peer
+            .last_seen
+            .and_then(|last_seen| last_seen.duration_since(UNIX_EPOCH).ok())
+            .map(|since_epoch| since_epoch.as_secs()),
+    }
+}
@@ -1,13 +1,26 @@
 use std::{
     env,
     net::{IpAddr, Ipv4Addr, SocketAddr},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use primitives::NodeType;
+use tokio::{
+    sync::mpsc::{unbounded_channel, UnboundedSender},
+    task::JoinHandle,
+};
 use uuid::Uuid;
 use vrrb_config::{NodeConfig, NodeConfigBuilder};
-use vrrb_core::keypair::Keypair;
+use vrrb_core::{
+    event_router::{Event, ShutdownReason},
+    keypair::Keypair,
+    node_status::NodeStatusHandle,
+};
+
+use crate::Node;
+
+/// How long `spawn_test_network` waits for its nodes to peer with each other
+pub const TEST_NETWORK_PEERING_TIMEOUT: Duration = Duration::from_secs(30);
 
 pub fn create_mock_full_node_config() -> NodeConfig {
     let data_dir = env::temp_dir();
@@ -40,7 +53,7 @@ pub fn create_mock_full_node_config() -> NodeConfig {
         .raptorq_gossip_address(raptorq_gossip_address)
         .udp_gossip_address(udp_gossip_address)
         .jsonrpc_server_address(jsonrpc_server_address)
-        .keypair(Keypair::random())
+        .keypair(Some(Keypair::random()))
         .disable_networking(false)
         .build()
         .unwrap()
@@ -63,3 +76,148 @@ pub fn create_mock_bootstrap_node_config() -> NodeConfig {
 
     node_config
 }
+
+/// Running node of a network started by `spawn_test_network`. The node is
+/// stopped when its handle is dropped.
+#[derive(Debug)]
+pub struct NodeHandle {
+    id: String,
+    node_type: NodeType,
+    udp_gossip_address: SocketAddr,
+    jsonrpc_server_address: SocketAddr,
+    status_handle: NodeStatusHandle,
+    control_tx: UnboundedSender<Event>,
+    handle: Option<JoinHandle<anyhow::Result<ShutdownReason>>>,
+}
+
+impl NodeHandle {
+    /// Starts a node from `config` and keeps it running in the background
+    pub async fn start(config: &NodeConfig) -> crate::Result<Self> {
+        let (control_tx, control_rx) = unbounded_channel::<Event>();
+        let node = Node::start(config, control_rx).await?;
+
+        Ok(Self {
+            id: node.id(),
+            node_type: node.node_type(),
+            udp_gossip_address: node.udp_gossip_address(),
+            jsonrpc_server_address: node.jsonrpc_server_address(),
+            status_handle: node.status_handle(),
+            control_tx,
+            handle: Some(tokio::spawn(node.wait())),
+        })
+    }
+
+    pub fn id(&self) -> String {
+        self.id.clone()
+    }
+
+    pub fn node_type(&self) -> NodeType {
+        self.node_type
+    }
+
+    pub fn udp_gossip_address(&self) -> SocketAddr {
+        self.udp_gossip_address
+    }
+
+    pub fn jsonrpc_server_address(&self) -> SocketAddr {
+        self.jsonrpc_server_address
+    }
+
+    pub fn status_handle(&self) -> NodeStatusHandle {
+        self.status_handle.clone()
+    }
+
+    /// Returns a sender of control events, e.g. `Event::Stop`, to the node
+    pub fn control_tx(&self) -> UnboundedSender<Event> {
+        self.control_tx.clone()
+    }
+
+    /// Stops the node and waits for it to shut down
+    pub async fn stop(mut self) -> anyhow::Result<ShutdownReason> {
+        self.control_tx.send(Event::Stop)?;
+
+        match self.handle.take() {
+            Some(handle) => handle.await?,
+            None => Err(anyhow::anyhow!("node {} already stopped", self.id)),
+        }
+    }
+}
+
+impl Drop for NodeHandle {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            // NOTE: the node may have stopped already, in which case there's
+            // nobody left to receive the signal
+            self.control_tx.send(Event::Stop).ok();
+
+            // NOTE: drops can't wait for the node to shut down gracefully, so
+            // whatever is left of it is torn down right away
+            handle.abort();
+        }
+    }
+}
+
+/// Starts a network of `n` nodes on OS-assigned ports and waits until every
+/// one of them is peered.
+///
+/// The first node is the network's bootstrap node, every other node is a full
+/// node bootstrapping through it. Nodes are started one after the other, each
+/// one only once the previous one joined, so the network always comes up the
+/// same way: the bootstrap node ends up peered with every node, and each full
+/// node with the bootstrap node and the nodes started before it. Each node
+/// gets a database of its own.
+///
+/// Panics if a node fails to start or the network isn't peered within
+/// `TEST_NETWORK_PEERING_TIMEOUT`.
+pub async fn spawn_test_network(n: usize) -> Vec<NodeHandle> {
+    let mut nodes: Vec<NodeHandle> = Vec::with_capacity(n);
+
+    for idx in 0..n {
+        let mut config = match nodes.first() {
+            Some(bootstrap_node) => create_mock_full_node_config_with_bootstrap(vec![
+                bootstrap_node.udp_gossip_address(),
+            ]),
+            None => create_mock_bootstrap_node_config(),
+        };
+
+        config.idx = idx as u16;
+        config.db_path = env::temp_dir()
+            .join(vrrb_core::helpers::generate_random_string())
+            .join("node.db");
+
+        let node = NodeHandle::start(&config)
+            .await
+            .unwrap_or_else(|err| panic!("unable to start test node {idx}: {err}"));
+
+        // NOTE: every node after the bootstrap node discovers it along with
+        // every node that joined before
+        if idx > 0 {
+            wait_for_peers(&node, idx).await;
+        }
+
+        nodes.push(node);
+    }
+
+    if let Some(bootstrap_node) = nodes.first() {
+        wait_for_peers(bootstrap_node, n.saturating_sub(1)).await;
+    }
+
+    nodes
+}
+
+async fn wait_for_peers(node: &NodeHandle, expected: usize) {
+    let started_at = Instant::now();
+
+    while node.status_handle.peer_count() < expected {
+        if started_at.elapsed() > TEST_NETWORK_PEERING_TIMEOUT {
+            panic!(
+                "node {} only peered with {} of {expected} nodes within {:?}",
+                node.id,
+                node.status_handle.peer_count(),
+                TEST_NETWORK_PEERING_TIMEOUT
+            );
+        }
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
@@ -1,14 +1,29 @@
 use std::{
     env,
     net::{IpAddr, Ipv4Addr, SocketAddr},
+    time::Duration,
 };
 
-use node::{test_utils::create_mock_bootstrap_node_config, Node, NodeType, RuntimeModuleState};
+use node::{
+    test_utils::{create_mock_bootstrap_node_config, create_mock_full_node_config},
+    Node,
+    NodeError,
+    NodeType,
+    RuntimeModuleState,
+};
 use poem::http::status;
+use primitives::DEFAULT_CHAIN_ID;
 use serial_test::serial;
+use telemetry::{LogFormat, LogLevel, TelemetrySubscriber};
 use tokio::sync::mpsc::unbounded_channel;
 use uuid::Uuid;
-use vrrb_config::NodeConfig;
+use vrrb_config::{
+    NodeConfig,
+    DEFAULT_MAX_TXN_SIZE_BYTES,
+    DEFAULT_MEMPOOL_TXN_TTL,
+    DEFAULT_VALIDATION_PARALLELISM,
+    DEFAULT_VALIDATION_QUEUE_DEPTH,
+};
 use vrrb_core::{event_router::Event, keypair::Keypair};
 
 #[tokio::test]
@@ -45,9 +60,34 @@ async fn node_runtime_starts_and_stops() {
         http_api_shutdown_timeout: None,
         jsonrpc_server_address,
         preload_mock_state: false,
+        genesis_path: None,
         bootstrap_config: None,
-        keypair: Keypair::random(),
+        keypair: Some(Keypair::random()),
         disable_networking: false,
+        accept_txns_without_networking: false,
+        shutdown_timeout: Duration::from_secs(5),
+        readiness_timeout: Duration::from_secs(5),
+        min_bootstrap_peers: 0,
+        bootstrap_quorum_timeout: Duration::from_secs(5),
+        exit_without_bootstrap_quorum: false,
+        mempool_snapshot_path: None,
+        event_store_path: None,
+        event_router_config: Default::default(),
+        supervision_config: Default::default(),
+        runtime_config: Default::default(),
+        mempool_max_size: None,
+        mempool_txn_ttl: DEFAULT_MEMPOOL_TXN_TTL,
+        mining_config: Default::default(),
+        broadcast_engine_config: Default::default(),
+        fee_config: Default::default(),
+        validation_queue_depth: DEFAULT_VALIDATION_QUEUE_DEPTH,
+        validation_parallelism: DEFAULT_VALIDATION_PARALLELISM,
+        chain_id: DEFAULT_CHAIN_ID,
+        reject_txns_without_chain_id: false,
+        max_txn_size_bytes: DEFAULT_MAX_TXN_SIZE_BYTES,
+        log_level: Default::default(),
+        log_format: Default::default(),
+        jsonrpc_admin_token: None,
     };
 
     let (ctrl_tx, mut ctrl_rx) = tokio::sync::mpsc::unbounded_channel::<Event>();
@@ -63,3 +103,99 @@ async fn node_runtime_starts_and_stops() {
 
     handle.await.unwrap();
 }
+
+#[tokio::test]
+#[serial]
+async fn node_reloads_live_settings_without_restarting() {
+    let node_config = create_mock_full_node_config();
+
+    TelemetrySubscriber::init_with(std::io::sink, LogLevel::Info, LogFormat::Json).unwrap();
+
+    let (ctrl_tx, ctrl_rx) = unbounded_channel::<Event>();
+    let mut vrrb_node = Node::start(&node_config, ctrl_rx).await.unwrap();
+
+    assert!(!telemetry::level_enabled!(telemetry::Level::DEBUG));
+
+    let mut new_config = node_config.clone();
+    new_config.mempool_max_size = Some(2);
+    new_config.log_level = LogLevel::Debug;
+    new_config.shutdown_timeout = Duration::from_secs(1);
+
+    vrrb_node.reload_config(new_config).unwrap();
+
+    // NOTE: the global subscriber picks up the new level right away
+    assert!(telemetry::level_enabled!(telemetry::Level::DEBUG));
+
+    let config = vrrb_node.config().await;
+    assert_eq!(config.mempool_max_size, Some(2));
+    assert_eq!(config.log_level, LogLevel::Debug);
+    assert_eq!(config.shutdown_timeout, Duration::from_secs(1));
+
+    let handle = tokio::spawn(async move { vrrb_node.wait().await.unwrap() });
+
+    ctrl_tx.send(Event::Stop).unwrap();
+
+    handle.await.unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn node_reports_settings_that_require_a_restart() {
+    let node_config = create_mock_full_node_config();
+
+    let (ctrl_tx, ctrl_rx) = unbounded_channel::<Event>();
+    let mut vrrb_node = Node::start(&node_config, ctrl_rx).await.unwrap();
+
+    let mut new_config = node_config.clone();
+    new_config.node_type = NodeType::Bootstrap;
+    new_config.mempool_max_size = Some(2);
+    // NOTE: nothing serves the HTTP API's metadata after startup
+    new_config.http_api_title = String::from("Reloaded Node API");
+
+    let err = vrrb_node.reload_config(new_config).unwrap_err();
+
+    assert!(matches!(
+        err,
+        NodeError::RestartRequired(fields) if fields == vec!["node_type", "http_api_title"]
+    ));
+
+    // NOTE: reloadable settings still take effect
+    assert_eq!(vrrb_node.config().await.mempool_max_size, Some(2));
+    assert_eq!(vrrb_node.node_type(), NodeType::Full);
+
+    let handle = tokio::spawn(async move { vrrb_node.wait().await.unwrap() });
+
+    ctrl_tx.send(Event::Stop).unwrap();
+
+    handle.await.unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn node_fails_to_start_when_its_jsonrpc_address_is_taken() {
+    let node_config = create_mock_full_node_config();
+
+    let (ctrl_tx, ctrl_rx) = unbounded_channel::<Event>();
+    let vrrb_node = Node::start(&node_config, ctrl_rx).await.unwrap();
+    let taken_addr = vrrb_node.jsonrpc_server_address();
+
+    let mut other_config = create_mock_full_node_config();
+    other_config.db_path = env::temp_dir()
+        .join(vrrb_core::helpers::generate_random_string())
+        .join("node.db");
+    other_config.jsonrpc_server_address = taken_addr;
+
+    let (_other_ctrl_tx, other_ctrl_rx) = unbounded_channel::<Event>();
+    let err = Node::start(&other_config, other_ctrl_rx).await.unwrap_err();
+
+    assert!(matches!(
+        err,
+        NodeError::BindFailed { module: "jsonrpc_server", addr, .. } if addr == taken_addr
+    ));
+
+    let handle = tokio::spawn(async move { vrrb_node.wait().await.unwrap() });
+
+    ctrl_tx.send(Event::Stop).unwrap();
+
+    handle.await.unwrap();
+}
@@ -1,10 +1,37 @@
-use node::{test_utils::create_mock_full_node_config, Node, NodeType, RuntimeModuleState};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use mempool::ValidationQueue;
+use node::{
+    setup_event_routing_system,
+    test_utils::create_mock_full_node_config,
+    Node,
+    NodeType,
+    RuntimeModuleState,
+};
+use primitives::Address;
 use serial_test::serial;
+use storage::vrrbdb::{VrrbDb, VrrbDbConfig};
 use telemetry::TelemetrySubscriber;
 use tokio::sync::mpsc::unbounded_channel;
-use vrrb_config::NodeConfig;
-use vrrb_core::event_router::Event;
-use vrrb_rpc::rpc::{api::RpcClient, client::create_client};
+use vrrb_config::{EventRouterConfig, NodeConfig};
+use vrrb_core::{
+    account::Account,
+    event_router::{DirectedEvent, Event, Topic},
+    keypair::KeyPair,
+    txn::{null_txn, NewTxnArgs, Txn},
+    txn_receipts::TxnReceiptStatus,
+};
+use vrrb_rpc::rpc::{
+    api::{RpcClient, RpcServer},
+    client::create_client,
+    error_code,
+    JsonRpcServerConfig,
+    RpcError,
+    RpcServerImpl,
+};
 
 #[tokio::test]
 #[serial]
@@ -32,3 +59,215 @@ async fn node_rpc_api_returns_node_type() {
 
     handle.await.unwrap();
 }
+
+#[tokio::test]
+#[serial]
+async fn node_rpc_api_returns_node_status_without_networking() {
+    let mut node_config = create_mock_full_node_config();
+    node_config.disable_networking = true;
+
+    let (ctrl_tx_1, mut ctrl_rx_1) = unbounded_channel::<Event>();
+
+    let mut vrrb_node = Node::start(&node_config, ctrl_rx_1).await.unwrap();
+
+    let client = create_client(vrrb_node.jsonrpc_server_address())
+        .await
+        .unwrap();
+
+    let handle = tokio::spawn(async move {
+        vrrb_node.wait().await.unwrap();
+    });
+
+    let status = client.get_node_status().await.unwrap();
+
+    assert_eq!(status.node_id, node_config.id);
+    assert_eq!(status.node_type, NodeType::Full);
+    assert_eq!(status.peer_count, 0);
+    assert_eq!(status.mempool_size, 0);
+
+    ctrl_tx_1.send(Event::Stop).unwrap();
+
+    handle.await.unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn nodes_without_networking_reject_txns_by_default() {
+    let mut node_config = create_mock_full_node_config();
+    node_config.disable_networking = true;
+
+    let (ctrl_tx_1, mut ctrl_rx_1) = unbounded_channel::<Event>();
+
+    let mut vrrb_node = Node::start(&node_config, ctrl_rx_1).await.unwrap();
+
+    let client = create_client(vrrb_node.jsonrpc_server_address())
+        .await
+        .unwrap();
+
+    let handle = tokio::spawn(async move {
+        vrrb_node.wait().await.unwrap();
+    });
+
+    let keypair = KeyPair::random();
+    let mut txn = null_txn();
+    txn.sender_public_key = *keypair.get_miner_public_key();
+    txn.sign(keypair.get_miner_secret_key());
+
+    let err = client.submit_transaction(txn).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(RpcError::NOT_ACCEPTED));
+
+    ctrl_tx_1.send(Event::Stop).unwrap();
+
+    handle.await.unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn nodes_without_networking_accept_txns_locally_when_configured_to() {
+    let keypair = KeyPair::random();
+    let sender = Address::new(*keypair.get_miner_public_key());
+
+    let data_dir = std::env::temp_dir().join(vrrb_core::helpers::generate_random_string());
+    std::fs::create_dir_all(&data_dir).unwrap();
+
+    let genesis_path = data_dir.join("genesis.json");
+    std::fs::write(
+        &genesis_path,
+        format!(r#"{{ "{sender}": {{ "credits": 1000 }} }}"#),
+    )
+    .unwrap();
+
+    let mut node_config = create_mock_full_node_config();
+    node_config.disable_networking = true;
+    node_config.accept_txns_without_networking = true;
+    node_config.db_path = data_dir.join("node.db");
+    node_config.genesis_path = Some(genesis_path);
+
+    let (ctrl_tx_1, mut ctrl_rx_1) = unbounded_channel::<Event>();
+
+    let mut vrrb_node = Node::start(&node_config, ctrl_rx_1).await.unwrap();
+
+    let client = create_client(vrrb_node.jsonrpc_server_address())
+        .await
+        .unwrap();
+
+    let handle = tokio::spawn(async move {
+        vrrb_node.wait().await.unwrap();
+    });
+
+    let mut txn = Txn::new(NewTxnArgs {
+        timestamp: 0,
+        sender_address: sender.to_string(),
+        sender_public_key: *keypair.get_miner_public_key(),
+        receiver_address: String::from("0x192abcdef01234567890fedcba09876543211"),
+        token: None,
+        amount: 10,
+        signature: null_txn().signature,
+        validators: Some(HashMap::new()),
+        nonce: 1,
+    });
+    txn.sign(keypair.get_miner_secret_key());
+
+    let txn_digest = txn.digest();
+
+    assert_eq!(client.submit_transaction(txn).await.unwrap(), txn_digest);
+
+    let receipt = client
+        .get_transaction_receipt(txn_digest)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(receipt.status, TxnReceiptStatus::Pending);
+
+    ctrl_tx_1.send(Event::Stop).unwrap();
+
+    handle.await.unwrap();
+}
+
+#[tokio::test]
+async fn txns_accepted_during_a_burst_all_reach_the_storage_topic() {
+    // NOTE: far smaller than the burst, so the storage topic saturates
+    let event_router_config = EventRouterConfig {
+        storage_buffer: 4,
+        ..Default::default()
+    };
+
+    let mut event_router = setup_event_routing_system(&event_router_config);
+    let mut storage_events_rx = event_router.subscribe(&Topic::Storage).unwrap();
+    let metrics = event_router.metrics();
+
+    let (events_tx, mut events_rx) = unbounded_channel::<DirectedEvent>();
+    let router_handle = tokio::spawn(async move {
+        event_router.start(&mut events_rx).await;
+    });
+
+    let keypair = KeyPair::random();
+    let mut vrrbdb_config = VrrbDbConfig::default();
+    vrrbdb_config.path = std::env::temp_dir().join(vrrb_core::helpers::generate_random_string());
+
+    let mut vrrbdb = VrrbDb::new(vrrbdb_config).unwrap();
+    let mut account = Account::new(*keypair.get_miner_public_key());
+    account.credits = 1_000;
+    vrrbdb
+        .insert_account(Address::new(*keypair.get_miner_public_key()), account)
+        .unwrap();
+
+    let config = JsonRpcServerConfig::default();
+    let (validation_queue, _validation_rx) = ValidationQueue::new(50);
+
+    let server_impl = RpcServerImpl {
+        node_id: config.node_id,
+        node_type: config.node_type,
+        chain_id: config.chain_id,
+        reject_txns_without_chain_id: false,
+        fee_model: config.fee_model,
+        max_txn_size_bytes: config.max_txn_size_bytes,
+        reject_txns_without_networking: false,
+        node_status_handle: config.node_status_handle,
+        vrrbdb_read_handle: vrrbdb.read_handle(),
+        mempool_read_handle_factory: config.mempool_read_handle_factory,
+        validation_queue,
+        txn_receipts: config.txn_receipts,
+        events_tx: events_tx.clone(),
+        confirmed_blocks_tx: config.confirmed_blocks_tx,
+        peer_table: None,
+        admin_token: None,
+    };
+
+    let mut accepted = HashSet::new();
+    for nonce in 1..=50 {
+        let mut txn = Txn::new(NewTxnArgs {
+            timestamp: 0,
+            sender_address: String::from("0x192abcdef01234567890fedcba09876543210"),
+            sender_public_key: *keypair.get_miner_public_key(),
+            receiver_address: String::from("0x192abcdef01234567890fedcba09876543211"),
+            token: None,
+            amount: 10,
+            signature: null_txn().signature,
+            validators: Some(HashMap::new()),
+            nonce,
+        });
+        txn.sign(keypair.get_miner_secret_key());
+
+        accepted.insert(server_impl.submit_transaction(txn).await.unwrap());
+    }
+
+    assert_eq!(accepted.len(), 50);
+
+    // NOTE: a slow mempool, the router must wait for it rather than drop txns
+    let mut received = HashSet::new();
+    while received.len() < accepted.len() {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        match storage_events_rx.recv().await.unwrap() {
+            Event::NewTxnCreated(txn) => received.insert(txn.digest()),
+            event => panic!("unexpected event: {event:?}"),
+        };
+    }
+
+    assert_eq!(received, accepted);
+    assert_eq!(metrics.dropped.get(&Topic::Storage), 0);
+
+    events_tx.send((Topic::Control, Event::Stop)).unwrap();
+    router_handle.await.unwrap();
+}
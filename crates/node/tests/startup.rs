@@ -1,16 +1,27 @@
+use std::{env, time::Duration};
+
 use node::{
+    setup_runtime_components,
     test_utils::{
         create_mock_bootstrap_node_config,
         create_mock_full_node_config,
         create_mock_full_node_config_with_bootstrap,
+        NodeHandle,
     },
     Node,
+    NodeError,
     NodeType,
     RuntimeModuleState,
 };
 use serial_test::serial;
-use tokio::sync::mpsc::unbounded_channel;
-use vrrb_core::event_router::Event;
+use storage::vrrbdb::{Genesis, VrrbDbConfig};
+use tokio::sync::{broadcast, mpsc::unbounded_channel};
+use vrrb_core::{
+    event_router::{Event, ShutdownReason},
+    helpers::generate_random_string,
+    keypair::Keypair,
+    node_status::NodeStatusHandle,
+};
 use vrrb_rpc::rpc::{api::RpcClient, client::create_client};
 
 #[tokio::test]
@@ -97,3 +108,427 @@ async fn bootstrap_node_can_add_newly_joined_peers_to_peer_list() {
 
     handle.await.unwrap();
 }
+
+#[tokio::test]
+#[serial]
+async fn node_tracks_the_state_of_its_runtime_modules() {
+    let node_config = create_mock_full_node_config();
+
+    let (ctrl_tx_1, ctrl_rx_1) = unbounded_channel::<Event>();
+
+    let vrrb_node = Node::start(&node_config, ctrl_rx_1).await.unwrap();
+    let status_handle = vrrb_node.status_handle();
+
+    let handle = tokio::spawn(async move {
+        vrrb_node.wait().await.unwrap();
+    });
+
+    // NOTE: modules record their state as they transition, so every one of
+    // them is known to be running as soon as the node started
+    let module_states = status_handle.module_states();
+
+    assert_eq!(
+        module_states.get("Validator module"),
+        Some(&RuntimeModuleState::Running)
+    );
+    assert!(module_states
+        .values()
+        .all(|state| *state == RuntimeModuleState::Running));
+
+    ctrl_tx_1.send(Event::Stop).unwrap();
+
+    handle.await.unwrap();
+
+    assert!(status_handle
+        .module_states()
+        .values()
+        .all(|state| *state == RuntimeModuleState::Stopped));
+}
+
+#[tokio::test]
+#[serial]
+async fn node_is_ready_only_once_every_module_is_running() {
+    let node_config = create_mock_full_node_config();
+
+    let (ctrl_tx_1, ctrl_rx_1) = unbounded_channel::<Event>();
+
+    let vrrb_node = Node::start(&node_config, ctrl_rx_1).await.unwrap();
+    let status_handle = vrrb_node.status_handle();
+
+    assert!(vrrb_node.is_ready());
+
+    let module_states = vrrb_node.module_statuses();
+    assert!(module_states.contains_key("Validator module"));
+    assert!(module_states.contains_key("State module"));
+    assert!(module_states
+        .values()
+        .all(|state| *state == RuntimeModuleState::Running));
+
+    let handle = tokio::spawn(async move {
+        vrrb_node.wait().await.unwrap();
+    });
+
+    ctrl_tx_1.send(Event::Stop).unwrap();
+
+    handle.await.unwrap();
+
+    assert!(!status_handle.is_ready());
+}
+
+#[tokio::test]
+#[serial]
+async fn node_fails_to_start_if_modules_are_not_running_in_time() {
+    let mut node_config = create_mock_full_node_config();
+
+    // NOTE: modules can't report they're running before the node starts
+    // routing events, so they can't possibly make it in time
+    node_config.readiness_timeout = std::time::Duration::ZERO;
+
+    let (_ctrl_tx_1, ctrl_rx_1) = unbounded_channel::<Event>();
+
+    match Node::start(&node_config, ctrl_rx_1).await {
+        Err(NodeError::NotReady { modules, .. }) => {
+            assert!(modules.contains(&String::from("Validator module")));
+        },
+        Err(err) => panic!("unexpected error: {err}"),
+        Ok(_) => panic!("node started without its modules running"),
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn node_returns_the_reason_it_was_shut_down_for() {
+    let node_config = create_mock_full_node_config();
+
+    let (ctrl_tx_1, ctrl_rx_1) = unbounded_channel::<Event>();
+
+    let vrrb_node = Node::start(&node_config, ctrl_rx_1).await.unwrap();
+
+    let handle = tokio::spawn(async move { vrrb_node.wait().await.unwrap() });
+
+    ctrl_tx_1
+        .send(Event::Shutdown {
+            reason: ShutdownReason::ConfigReload,
+        })
+        .unwrap();
+
+    assert_eq!(handle.await.unwrap(), ShutdownReason::ConfigReload);
+}
+
+#[tokio::test]
+#[serial]
+async fn modules_started_before_a_failing_one_are_torn_down() {
+    let mut node_config = create_mock_full_node_config();
+    node_config.mining_config.target_block_interval = std::time::Duration::ZERO;
+
+    let (events_tx, _events_rx) = unbounded_channel();
+    let (mempool_tx, mempool_rx) = broadcast::channel::<Event>(16);
+    let (network_tx, network_rx) = broadcast::channel::<Event>(16);
+    let (controller_tx, controller_rx) = broadcast::channel::<Event>(16);
+    let subscribe = || broadcast::channel::<Event>(16).1;
+
+    let result = setup_runtime_components(
+        &node_config,
+        NodeStatusHandle::new(),
+        events_tx,
+        mempool_rx,
+        subscribe(),
+        network_rx,
+        controller_rx,
+        subscribe(),
+        subscribe(),
+        subscribe(),
+        subscribe(),
+        subscribe(),
+        subscribe(),
+        None,
+    )
+    .await;
+
+    assert!(matches!(result, Err(NodeError::Other(reason)) if reason.contains("block interval")));
+
+    // NOTE: the modules' event receivers only get dropped along with their
+    // tasks
+    assert_eq!(mempool_tx.receiver_count(), 0);
+    assert_eq!(network_tx.receiver_count(), 0);
+    assert_eq!(controller_tx.receiver_count(), 0);
+}
+
+#[tokio::test]
+#[serial]
+async fn node_loads_genesis_accounts_into_an_empty_db_only() {
+    let mut node_config = create_mock_full_node_config();
+
+    // NOTE: mock configs share a db, genesis is only applied to fresh ones
+    node_config.db_path = node_config
+        .data_dir
+        .join(format!("{}-genesis.db", node_config.id));
+
+    let address = Keypair::random().txn_keypair().address();
+    let genesis_path = node_config
+        .data_dir
+        .join(format!("{}-genesis.json", node_config.id));
+    std::fs::write(
+        &genesis_path,
+        format!(r#"{{ "{address}": {{ "credits": 1000 }} }}"#),
+    )
+    .unwrap();
+
+    node_config.genesis_path = Some(genesis_path.clone());
+
+    let (ctrl_tx, ctrl_rx) = unbounded_channel::<Event>();
+    let vrrb_node = Node::start(&node_config, ctrl_rx).await.unwrap();
+
+    let client = create_client(vrrb_node.jsonrpc_server_address())
+        .await
+        .unwrap();
+
+    let handle = tokio::spawn(async move { vrrb_node.wait().await.unwrap() });
+
+    let account = client.get_account(address.clone()).await.unwrap().unwrap();
+    assert_eq!(account.credits, 1000);
+
+    ctrl_tx.send(Event::Stop).unwrap();
+    handle.await.unwrap();
+
+    let vrrbdb_config = VrrbDbConfig {
+        path: node_config.db_path.clone(),
+        ..Default::default()
+    };
+    let genesis = Genesis::from_file(&genesis_path).unwrap();
+    assert_eq!(
+        vrrbdb_config.recorded_genesis_hash().unwrap().as_deref(),
+        Some(genesis.hash())
+    );
+
+    // NOTE: restarting on the initialized db leaves it as it is rather than
+    // refusing to start
+    let (ctrl_tx, ctrl_rx) = unbounded_channel::<Event>();
+    let vrrb_node = Node::start(&node_config, ctrl_rx).await.unwrap();
+    let handle = tokio::spawn(async move { vrrb_node.wait().await.unwrap() });
+
+    ctrl_tx.send(Event::Stop).unwrap();
+    handle.await.unwrap();
+
+    // NOTE: a different genesis can't be applied over it
+    std::fs::write(
+        &genesis_path,
+        format!(r#"{{ "{address}": {{ "credits": 2000 }} }}"#),
+    )
+    .unwrap();
+
+    let (_ctrl_tx, ctrl_rx) = unbounded_channel::<Event>();
+    assert!(matches!(
+        Node::start(&node_config, ctrl_rx).await,
+        Err(NodeError::Other(reason)) if reason.contains("refusing to apply genesis")
+    ));
+}
+
+#[tokio::test]
+#[serial]
+async fn observer_nodes_start_without_miner_and_validator_modules() {
+    let mut node_config = create_mock_full_node_config();
+    node_config.node_type = NodeType::Observer;
+
+    let (events_tx, _events_rx) = unbounded_channel();
+    let subscribe = || broadcast::channel::<Event>(16).1;
+
+    let (
+        config,
+        mempool_handle,
+        state_handle,
+        gossip_handle,
+        broadcast_controller_handle,
+        state_sync_handle,
+        jsonrpc_server_handle,
+        txn_validator_handle,
+        miner_handle,
+        vm_handle,
+        event_store_handle,
+        mempool_sweeper_handle,
+        _restarters,
+    ) = setup_runtime_components(
+        &node_config,
+        NodeStatusHandle::new(),
+        events_tx,
+        subscribe(),
+        subscribe(),
+        subscribe(),
+        subscribe(),
+        subscribe(),
+        subscribe(),
+        subscribe(),
+        subscribe(),
+        subscribe(),
+        subscribe(),
+        None,
+    )
+    .await
+    .unwrap();
+
+    assert!(txn_validator_handle.is_none());
+    assert!(miner_handle.is_none());
+
+    // NOTE: observers still follow the network and serve reads
+    assert!(gossip_handle.is_some());
+    assert!(broadcast_controller_handle.is_some());
+    assert!(state_sync_handle.is_some());
+    assert!(jsonrpc_server_handle.is_some());
+
+    let client = create_client(config.jsonrpc_server_address).await.unwrap();
+    assert_eq!(client.get_node_type().await.unwrap(), NodeType::Observer);
+
+    let handles = [
+        mempool_handle,
+        state_handle,
+        gossip_handle,
+        broadcast_controller_handle,
+        state_sync_handle,
+        jsonrpc_server_handle,
+        vm_handle,
+        event_store_handle,
+        mempool_sweeper_handle,
+    ];
+
+    for handle in handles.into_iter().flatten() {
+        handle.abort();
+        let _ = handle.await;
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn nodes_without_networking_start_no_network_modules() {
+    let mut node_config = create_mock_full_node_config();
+    node_config.disable_networking = true;
+
+    let (events_tx, _events_rx) = unbounded_channel();
+    let subscribe = || broadcast::channel::<Event>(16).1;
+
+    let (
+        config,
+        mempool_handle,
+        state_handle,
+        gossip_handle,
+        broadcast_controller_handle,
+        state_sync_handle,
+        jsonrpc_server_handle,
+        txn_validator_handle,
+        miner_handle,
+        vm_handle,
+        event_store_handle,
+        mempool_sweeper_handle,
+        _restarters,
+    ) = setup_runtime_components(
+        &node_config,
+        NodeStatusHandle::new(),
+        events_tx,
+        subscribe(),
+        subscribe(),
+        subscribe(),
+        subscribe(),
+        subscribe(),
+        subscribe(),
+        subscribe(),
+        subscribe(),
+        subscribe(),
+        subscribe(),
+        None,
+    )
+    .await
+    .unwrap();
+
+    assert!(gossip_handle.is_none());
+    assert!(broadcast_controller_handle.is_none());
+    assert!(state_sync_handle.is_none());
+
+    // NOTE: the node remains usable locally
+    assert!(mempool_handle.is_some());
+    assert!(state_handle.is_some());
+    assert!(jsonrpc_server_handle.is_some());
+
+    let client = create_client(config.jsonrpc_server_address).await.unwrap();
+    assert!(client.get_peers(None).await.unwrap().is_empty());
+
+    let handles = [
+        mempool_handle,
+        state_handle,
+        jsonrpc_server_handle,
+        txn_validator_handle,
+        miner_handle,
+        vm_handle,
+        event_store_handle,
+        mempool_sweeper_handle,
+    ];
+
+    for handle in handles.into_iter().flatten() {
+        handle.abort();
+        let _ = handle.await;
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn nodes_sync_until_they_reach_their_bootstrap_quorum() {
+    let mut bootstrap_config = create_mock_bootstrap_node_config();
+    bootstrap_config.db_path = env::temp_dir().join(generate_random_string());
+    bootstrap_config.min_bootstrap_peers = 1;
+
+    let bootstrap_node = NodeHandle::start(&bootstrap_config).await.unwrap();
+    let status_handle = bootstrap_node.status_handle();
+
+    // NOTE: the node is up but neither mines nor validates while alone
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(status_handle.is_ready());
+    assert!(!status_handle.is_operational());
+
+    let mut full_config =
+        create_mock_full_node_config_with_bootstrap(vec![bootstrap_node.udp_gossip_address()]);
+    full_config.db_path = env::temp_dir().join(generate_random_string());
+
+    let full_node = NodeHandle::start(&full_config).await.unwrap();
+
+    tokio::time::timeout(Duration::from_secs(30), status_handle.operational())
+        .await
+        .expect("bootstrap node did not become operational once peered");
+
+    assert!(status_handle.peer_count() >= 1);
+
+    full_node.stop().await.unwrap();
+    bootstrap_node.stop().await.unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn nodes_carry_on_or_exit_without_bootstrap_quorum_as_configured() {
+    let mut node_config = create_mock_full_node_config();
+    node_config.min_bootstrap_peers = 1;
+    node_config.bootstrap_quorum_timeout = Duration::from_millis(100);
+
+    let (ctrl_tx, ctrl_rx) = unbounded_channel::<Event>();
+    let vrrb_node = Node::start(&node_config, ctrl_rx).await.unwrap();
+    let status_handle = vrrb_node.status_handle();
+
+    let handle = tokio::spawn(vrrb_node.wait());
+
+    tokio::time::timeout(Duration::from_secs(5), status_handle.operational())
+        .await
+        .expect("node did not become operational once the quorum timed out");
+
+    ctrl_tx.send(Event::Stop).unwrap();
+    assert_eq!(handle.await.unwrap().unwrap(), ShutdownReason::Operator);
+
+    node_config.exit_without_bootstrap_quorum = true;
+
+    let (_ctrl_tx, ctrl_rx) = unbounded_channel::<Event>();
+    let vrrb_node = Node::start(&node_config, ctrl_rx).await.unwrap();
+    let status_handle = vrrb_node.status_handle();
+
+    let reason = tokio::time::timeout(Duration::from_secs(5), vrrb_node.wait())
+        .await
+        .expect("node did not shut down once the quorum timed out")
+        .unwrap();
+
+    assert!(matches!(reason, ShutdownReason::FatalError(err) if err.contains("peers")));
+    assert!(!status_handle.is_operational());
+}
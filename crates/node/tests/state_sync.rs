@@ -1,44 +1,76 @@
 use std::{
     env,
-    net::{IpAddr, Ipv4Addr, SocketAddr},
+    time::{Duration, Instant},
 };
 
-use jsonrpsee::{core::client::Subscription, ws_client::WsClientBuilder};
-use node::{test_utils::create_mock_full_node_config, Node, NodeType, RuntimeModuleState};
-use tokio::sync::mpsc::unbounded_channel;
-use vrrb_config::NodeConfig;
-use vrrb_core::event_router::Event;
+use node::test_utils::{
+    create_mock_bootstrap_node_config,
+    create_mock_full_node_config_with_bootstrap,
+    NodeHandle,
+};
+use serial_test::serial;
+use vrrb_core::{helpers::generate_random_string, keypair::Keypair};
 use vrrb_rpc::rpc::{api::RpcClient, client::create_client};
 
+const SYNC_TIMEOUT: Duration = Duration::from_secs(30);
+
 #[tokio::test]
-#[ignore]
+#[serial]
 async fn nodes_can_synchronize_state() {
-    let node_config = create_mock_full_node_config();
+    let data_dir = env::temp_dir().join(generate_random_string());
+    std::fs::create_dir_all(&data_dir).unwrap();
+
+    let addresses = (0..3)
+        .map(|_| Keypair::random().txn_keypair().address())
+        .collect::<Vec<_>>();
+
+    let genesis = addresses
+        .iter()
+        .enumerate()
+        .map(|(idx, address)| format!(r#""{address}": {{ "credits": {} }}"#, (idx + 1) * 100))
+        .collect::<Vec<String>>()
+        .join(", ");
 
-    let (ctrl_tx_1, ctrl_rx_1) = unbounded_channel::<Event>();
-    let (ctrl_tx_2, ctrl_rx_2) = unbounded_channel::<Event>();
+    let genesis_path = data_dir.join("genesis.json");
+    std::fs::write(&genesis_path, format!("{{ {genesis} }}")).unwrap();
 
-    let vrrb_node_1 = Node::start(&node_config, ctrl_rx_1).await.unwrap();
-    let vrrb_node_2 = Node::start(&node_config, ctrl_rx_2).await.unwrap();
+    let mut node_a_config = create_mock_bootstrap_node_config();
+    node_a_config.db_path = data_dir.join("node_a.db");
+    node_a_config.genesis_path = Some(genesis_path);
 
-    let client = create_client(vrrb_node_1.jsonrpc_server_address())
+    let node_a = NodeHandle::start(&node_a_config).await.unwrap();
+
+    // NOTE: node B starts out with an empty db and catches up with node A as
+    // soon as it joins the network through it
+    let mut node_b_config =
+        create_mock_full_node_config_with_bootstrap(vec![node_a.udp_gossip_address()]);
+    node_b_config.db_path = data_dir.join("node_b.db");
+
+    let node_b = NodeHandle::start(&node_b_config).await.unwrap();
+
+    let client = create_client(node_b.jsonrpc_server_address())
         .await
         .unwrap();
 
-    assert_eq!(vrrb_node_1.status(), RuntimeModuleState::Stopped);
-    assert_eq!(vrrb_node_2.status(), RuntimeModuleState::Stopped);
+    let started_at = Instant::now();
+
+    for (idx, address) in addresses.iter().enumerate() {
+        let account = loop {
+            if let Some(account) = client.get_account(address.clone()).await.unwrap() {
+                break account;
+            }
 
-    let handle_1 = tokio::spawn(async move {
-        vrrb_node_1.wait().await.unwrap();
-    });
+            assert!(
+                started_at.elapsed() < SYNC_TIMEOUT,
+                "node B did not sync account {address} within {SYNC_TIMEOUT:?}"
+            );
 
-    let handle_2 = tokio::spawn(async move {
-        vrrb_node_2.wait().await.unwrap();
-    });
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        };
 
-    ctrl_tx_1.send(Event::Stop).unwrap();
-    ctrl_tx_2.send(Event::Stop).unwrap();
+        assert_eq!(account.credits, (idx as u128 + 1) * 100);
+    }
 
-    handle_1.await.unwrap();
-    handle_2.await.unwrap();
+    node_b.stop().await.unwrap();
+    node_a.stop().await.unwrap();
 }
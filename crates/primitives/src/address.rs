@@ -1,12 +1,20 @@
 use std::str::FromStr;
 
+use bech32::{FromBase32, ToBase32, Variant};
 use secp256k1::{rand::rngs::OsRng, Secp256k1};
-use serde::{Deserialize, Serialize};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::{ByteVec, PublicKey};
 
+/// Human readable part every address string starts with
+pub const ADDRESS_HRP: &str = "vrrb";
+
 /// Represents a secp256k1 public key, hashed with sha256::digest
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+///
+/// Addresses are written as bech32m strings, e.g. `vrrb1qfumu...`, whose
+/// checksum catches mistyped characters. Human readable formats such as JSON
+/// serialize addresses the same way, binary ones as the bare public key.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Address(PublicKey);
 
 impl Address {
@@ -14,6 +22,11 @@ impl Address {
         Self(public_key)
     }
 
+    /// Derives the address every peer computes for `public_key`
+    pub fn from_public_key(public_key: &PublicKey) -> Self {
+        Self::new(*public_key)
+    }
+
     pub fn public_key(&self) -> PublicKey {
         self.0
     }
@@ -22,21 +35,85 @@ impl Address {
         // TODO: revisit later
         self.0.to_string().into_bytes()
     }
+
+    /// Parses the unchecksummed public key hex older releases wrote addresses
+    /// as. Prefer `Address::from_str` for anything typed in by users.
+    pub fn from_public_key_hex(s: &str) -> Result<Self, crate::Error> {
+        PublicKey::from_str(s)
+            .map_err(|err| crate::Error::InvalidAddress(format!("{s}: {err}")))
+            .map(Self)
+    }
 }
 
 impl FromStr for Address {
     type Err = crate::Error;
 
+    /// Parses a bech32m address, rejecting it if its checksum doesn't match
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        PublicKey::from_str(s)
-            .map_err(|err| crate::Error::Other(err.to_string()))
+        let invalid = |reason: String| crate::Error::InvalidAddress(format!("{s}: {reason}"));
+
+        let (hrp, data, variant) = bech32::decode(s).map_err(|err| invalid(err.to_string()))?;
+
+        if hrp != ADDRESS_HRP {
+            return Err(invalid(format!("expected a {ADDRESS_HRP} address")));
+        }
+
+        if variant != Variant::Bech32m {
+            return Err(invalid(String::from("expected a bech32m checksum")));
+        }
+
+        let bytes = Vec::<u8>::from_base32(&data).map_err(|err| invalid(err.to_string()))?;
+
+        PublicKey::from_slice(&bytes)
+            .map_err(|err| invalid(err.to_string()))
             .map(Self)
     }
 }
 
 impl std::fmt::Display for Address {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(&self.0.to_string())
+        let encoded = bech32::encode(
+            ADDRESS_HRP,
+            self.0.serialize().to_base32(),
+            Variant::Bech32m,
+        )
+        .map_err(|_| std::fmt::Error)?;
+
+        f.write_str(&encoded)
+    }
+}
+
+impl Serialize for Address {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Address {
+    /// NOTE: human readable formats also take the public key hex older
+    /// releases wrote addresses as, so files written by them still load
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if !deserializer.is_human_readable() {
+            return PublicKey::deserialize(deserializer).map(Self);
+        }
+
+        let s = String::deserialize(deserializer)?;
+
+        if s.starts_with(ADDRESS_HRP) {
+            Self::from_str(&s).map_err(D::Error::custom)
+        } else {
+            Self::from_public_key_hex(&s).map_err(D::Error::custom)
+        }
     }
 }
 
@@ -46,3 +123,88 @@ pub fn generate_account_keypair() -> AccountKeypair {
     let secp = Secp256k1::new();
     secp.generate_keypair(&mut OsRng)
 }
+
+#[cfg(test)]
+mod tests {
+    use secp256k1::SecretKey;
+
+    use super::*;
+
+    /// Compressed secp256k1 generator point, i.e. the public key of the secret
+    /// key `1`
+    const KNOWN_PUBLIC_KEY: &str =
+        "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+
+    /// Address of `KNOWN_PUBLIC_KEY`
+    const KNOWN_ADDRESS: &str = "vrrb1qfumuen7l8wthtz45p3ftn58pvrs9xlumvkuu2xet8egzkcklqtesgcyrk8";
+
+    const BECH32_CHARSET: &str = "qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+    #[test]
+    fn address_derivation_matches_known_vector() {
+        let mut secret_key_bytes = [0u8; 32];
+        secret_key_bytes[31] = 1;
+
+        let secret_key = SecretKey::from_slice(&secret_key_bytes).unwrap();
+        let public_key = PublicKey::from_secret_key(&Secp256k1::new(), &secret_key);
+
+        let address = Address::from_public_key(&public_key);
+
+        assert_eq!(address.public_key().to_string(), KNOWN_PUBLIC_KEY);
+        assert_eq!(address.to_string(), KNOWN_ADDRESS);
+        assert_eq!(Address::from_str(KNOWN_ADDRESS).unwrap(), address);
+        assert_eq!(
+            Address::from_public_key_hex(KNOWN_PUBLIC_KEY).unwrap(),
+            address
+        );
+    }
+
+    #[test]
+    fn addresses_round_trip_through_strings() {
+        let (_, public_key) = generate_account_keypair();
+        let address = Address::new(public_key);
+
+        let encoded = address.to_string();
+
+        assert!(encoded.starts_with("vrrb1"));
+        assert_eq!(Address::from_str(&encoded).unwrap(), address);
+        assert_eq!(Address::from_str(&encoded.to_uppercase()).unwrap(), address);
+
+        let json = serde_json::to_string(&address).unwrap();
+        assert_eq!(json, format!("\"{encoded}\""));
+        assert_eq!(serde_json::from_str::<Address>(&json).unwrap(), address);
+    }
+
+    #[test]
+    fn addresses_with_a_mistyped_character_are_rejected() {
+        let data_start = ADDRESS_HRP.len() + 1;
+
+        for (idx, c) in KNOWN_ADDRESS.char_indices().skip(data_start) {
+            let replacement = BECH32_CHARSET.chars().find(|other| *other != c).unwrap();
+
+            let mut mutated = KNOWN_ADDRESS.to_string();
+            mutated.replace_range(idx..idx + 1, &replacement.to_string());
+
+            assert!(
+                matches!(
+                    Address::from_str(&mutated),
+                    Err(crate::Error::InvalidAddress(_))
+                ),
+                "{mutated} was accepted"
+            );
+        }
+    }
+
+    #[test]
+    fn unchecksummed_addresses_are_rejected() {
+        assert!(Address::from_str(KNOWN_PUBLIC_KEY).is_err());
+        assert!(Address::from_str("btc1qfumuen7l8wthtz45p3ftn58pvrs9xlumvk").is_err());
+
+        // NOTE: files written before addresses were checksummed still load
+        let json = format!("\"{KNOWN_PUBLIC_KEY}\"");
+        assert_eq!(
+            serde_json::from_str::<Address>(&json).unwrap().to_string(),
+            KNOWN_ADDRESS
+        );
+    }
+}
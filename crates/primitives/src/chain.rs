@@ -0,0 +1,11 @@
+/// Identifies the network a node belongs to. Nodes of different networks
+/// share no state, so anything signed for one of them must not be accepted by
+/// another.
+pub type ChainId = u64;
+
+pub const MAINNET_CHAIN_ID: ChainId = 1;
+
+/// Chain id of local and development networks
+pub const DEVNET_CHAIN_ID: ChainId = 1337;
+
+pub const DEFAULT_CHAIN_ID: ChainId = DEVNET_CHAIN_ID;
@@ -1,15 +1,19 @@
 pub mod address;
 pub mod base;
+pub mod chain;
 pub mod crypto;
 pub mod digest;
 pub mod environment;
 pub mod node;
 pub mod signal;
+pub mod version;
 
 pub use address::*;
 pub use base::*;
+pub use chain::*;
 pub use crypto::*;
 pub use digest::*;
 pub use environment::*;
 pub use node::*;
 pub use signal::*;
+pub use version::*;
@@ -1,16 +1,35 @@
 use std::str::FromStr;
 
+use secp256k1::hashes::{sha256, Hash};
 use serde::{Deserialize, Serialize};
 
+use crate::PublicKey;
+
 pub type NodeId = String;
 pub type NodeIdx = u16;
 pub type NodeIdentifier = String;
 
+/// Derives node ids from a node's cryptographic identity
+pub trait NodeIdExt {
+    /// Returns the hex encoded SHA-256 hash of the compressed `public_key`,
+    /// so the same keypair always yields the same node id
+    fn from_public_key(public_key: &PublicKey) -> NodeId;
+}
+
+impl NodeIdExt for NodeId {
+    fn from_public_key(public_key: &PublicKey) -> NodeId {
+        sha256::Hash::hash(&public_key.serialize()).to_string()
+    }
+}
+
 #[derive(Debug, Clone, thiserror::Error)]
 pub enum Error {
     #[error("invalid environment: {0}")]
     InvalidEnvironment(String),
 
+    #[error("invalid address {0}")]
+    InvalidAddress(String),
+
     #[error("{0}")]
     Other(String),
 }
@@ -32,9 +51,20 @@ pub enum NodeType {
     Validator = 5,
     MasterNode = 6,
     RPCNode = 7,
+    /// Follows the network through gossip and serves RPC reads, but never
+    /// mines, validates transactions or takes writes in
+    Observer = 8,
     Unknown = 100,
 }
 
+impl NodeType {
+    /// Returns true for node types that refuse transactions and any other
+    /// write requested by clients
+    pub fn is_read_only(&self) -> bool {
+        matches!(self, NodeType::Observer)
+    }
+}
+
 impl FromStr for NodeType {
     type Err = Error;
 
@@ -48,11 +78,32 @@ impl FromStr for NodeType {
             "validator" => Ok(NodeType::Validator),
             "masternode" => Ok(NodeType::MasterNode),
             "rpc" => Ok(NodeType::RPCNode),
+            "observer" => Ok(NodeType::Observer),
             _ => Err(Error::Other("invalid node type".into())),
         }
     }
 }
 
+/// Writes node types the way `NodeType::from_str` parses them
+impl std::fmt::Display for NodeType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            NodeType::Full => "full",
+            NodeType::Light => "light",
+            NodeType::Archive => "archive",
+            NodeType::Miner => "miner",
+            NodeType::Bootstrap => "bootstrap",
+            NodeType::Validator => "validator",
+            NodeType::MasterNode => "masternode",
+            NodeType::RPCNode => "rpc",
+            NodeType::Observer => "observer",
+            NodeType::Unknown => "unknown",
+        };
+
+        f.write_str(name)
+    }
+}
+
 impl From<String> for NodeType {
     fn from(src: String) -> Self {
         match src.to_ascii_lowercase().as_str() {
@@ -64,6 +115,7 @@ impl From<String> for NodeType {
             "validator" => NodeType::Validator,
             "masternode" => NodeType::MasterNode,
             "rpc" => NodeType::RPCNode,
+            "observer" => NodeType::Observer,
             _ => NodeType::Unknown,
         }
     }
@@ -80,7 +132,43 @@ impl From<usize> for NodeType {
             5 => NodeType::Validator,
             6 => NodeType::MasterNode,
             7 => NodeType::RPCNode,
+            8 => NodeType::Observer,
             _ => NodeType::Unknown,
         }
     }
 }
+
+/// Lifecycle state of a node or one of its runtime modules
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuntimeModuleState {
+    Starting,
+    Running,
+    Stopped,
+    Terminating,
+}
+
+#[cfg(test)]
+mod tests {
+    use secp256k1::{Secp256k1, SecretKey};
+
+    use super::*;
+
+    #[test]
+    fn node_ids_derived_from_a_public_key_are_stable() {
+        let mut secret_key_bytes = [0u8; 32];
+        secret_key_bytes[31] = 1;
+
+        let secret_key = SecretKey::from_slice(&secret_key_bytes).unwrap();
+        let public_key = PublicKey::from_secret_key(&Secp256k1::new(), &secret_key);
+
+        assert_eq!(
+            NodeId::from_public_key(&public_key),
+            "0f715baf5d4c2ed329785cef29e562f73488c8a2bb9dbc5700b361d54b9b0554"
+        );
+        assert_eq!(
+            NodeId::from_public_key(&public_key),
+            NodeId::from_public_key(&public_key)
+        );
+    }
+}
@@ -0,0 +1,73 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Version of the wire protocol spoken by this build. Nodes refuse to peer
+/// with nodes speaking a different major version.
+pub const PROTOCOL_VERSION: ProtocolVersion = ProtocolVersion { major: 1, minor: 0 };
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct ProtocolVersion {
+    pub major: u16,
+    pub minor: u16,
+}
+
+impl ProtocolVersion {
+    /// Returns the version two nodes should speak to each other, or `None`
+    /// when their major versions differ and they can't talk at all
+    pub fn negotiate(&self, other: &ProtocolVersion) -> Option<ProtocolVersion> {
+        if self.major != other.major {
+            return None;
+        }
+
+        Some(ProtocolVersion {
+            major: self.major,
+            minor: self.minor.min(other.minor),
+        })
+    }
+}
+
+impl fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// Versions a node advertises to its peers when handshaking
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PeerVersion {
+    pub protocol: ProtocolVersion,
+    /// Version of the crates the node was built from
+    pub crate_version: String,
+}
+
+impl PeerVersion {
+    pub fn current() -> Self {
+        Self {
+            protocol: PROTOCOL_VERSION,
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_sharing_a_major_negotiate_the_lowest_minor() {
+        let local = ProtocolVersion { major: 1, minor: 3 };
+        let remote = ProtocolVersion { major: 1, minor: 1 };
+
+        assert_eq!(local.negotiate(&remote), Some(remote));
+        assert_eq!(remote.negotiate(&local), Some(remote));
+    }
+
+    #[test]
+    fn versions_with_different_majors_are_incompatible() {
+        let local = ProtocolVersion { major: 1, minor: 0 };
+        let remote = ProtocolVersion { major: 2, minor: 0 };
+
+        assert_eq!(local.negotiate(&remote), None);
+    }
+}
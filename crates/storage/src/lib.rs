@@ -1,3 +1,5 @@
 pub use lr_trie;
 pub use storage_utils;
 pub use vrrbdb;
+
+pub mod migration;
@@ -0,0 +1,132 @@
+//! Utilities to move data persisted by older releases into VrrbDb
+
+use pickledb::{PickleDb, SerializationMethod};
+use primitives::Address;
+use storage_utils::{Result, StorageError};
+use telemetry::{info, warn};
+use vrrb_core::account::Account;
+use vrrbdb::VrrbDb;
+
+/// Summary of a PickleDB to VrrbDb migration
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// Accounts inserted into the new db
+    pub migrated: usize,
+    /// Entries left out, either because they couldn't be decoded or because
+    /// the new db already holds an account under the same address
+    pub skipped: usize,
+}
+
+/// Copies every account stored within the legacy PickleDB state file at
+/// `old_path` into `new`.
+///
+/// Accounts already present in `new` are left untouched, so the migration can
+/// be safely re-run. Entries that can't be decoded are logged and skipped.
+pub fn migrate_pickledb_to_vrrbdb(old_path: &str, new: &mut VrrbDb) -> Result<MigrationReport> {
+    let legacy_db = PickleDb::load_read_only(old_path, SerializationMethod::Bin)
+        .map_err(|err| StorageError::Other(format!("unable to open {old_path}: {err}")))?;
+
+    let mut report = MigrationReport::default();
+    let mut accounts = vec![];
+
+    for key in legacy_db.get_all() {
+        // NOTE: legacy entries are keyed by unchecksummed public key hex
+        let address = match Address::from_public_key_hex(&key) {
+            Ok(address) => address,
+            Err(err) => {
+                warn!("skipping legacy entry {key}: invalid address: {err}");
+                report.skipped += 1;
+                continue;
+            },
+        };
+
+        match legacy_db.get::<Account>(&key) {
+            Some(account) => accounts.push((address, account)),
+            None => {
+                warn!("skipping legacy entry {key}: unable to decode account");
+                report.skipped += 1;
+            },
+        }
+    }
+
+    for (address, outcome) in new.extend_accounts(accounts) {
+        match outcome {
+            Ok(_) => report.migrated += 1,
+            Err(StorageError::RecordExists) => report.skipped += 1,
+            Err(err) => {
+                warn!("skipping legacy account {address}: {err}");
+                report.skipped += 1;
+            },
+        }
+    }
+
+    info!(
+        "migrated {} accounts from {old_path}, skipped {}",
+        report.migrated, report.skipped
+    );
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use pickledb::PickleDbDumpPolicy;
+    use vrrb_core::{helpers::generate_random_string, keypair::KeyPair};
+    use vrrbdb::VrrbDbConfig;
+
+    use super::*;
+
+    #[test]
+    fn migration_can_be_rerun_without_duplicating_accounts() {
+        let temp_dir = std::env::temp_dir();
+        let legacy_path = temp_dir.join(generate_random_string());
+
+        let mut legacy_db = PickleDb::new(
+            &legacy_path,
+            PickleDbDumpPolicy::DumpUponRequest,
+            SerializationMethod::Bin,
+        );
+
+        let address = Address::new(*KeyPair::random().get_miner_public_key());
+
+        legacy_db
+            .set(&address.public_key().to_string(), &Account::default())
+            .unwrap();
+        legacy_db
+            .set("not an address", &Account::default())
+            .unwrap();
+        legacy_db.dump().unwrap();
+
+        let mut new_db = VrrbDb::new(VrrbDbConfig {
+            path: temp_dir.join(generate_random_string()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let legacy_path = legacy_path.to_str().unwrap();
+
+        let report = migrate_pickledb_to_vrrbdb(legacy_path, &mut new_db).unwrap();
+
+        assert_eq!(
+            report,
+            MigrationReport {
+                migrated: 1,
+                skipped: 1
+            }
+        );
+
+        let report = migrate_pickledb_to_vrrbdb(legacy_path, &mut new_db).unwrap();
+
+        assert_eq!(
+            report,
+            MigrationReport {
+                migrated: 0,
+                skipped: 2
+            }
+        );
+
+        assert_eq!(new_db.state_store_factory().handle().entries().len(), 1);
+
+        std::fs::remove_file(legacy_path).unwrap_or_default();
+    }
+}
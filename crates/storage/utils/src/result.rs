@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 #[derive(Debug, thiserror::Error)]
 pub enum StorageError {
     #[error("{0}")]
@@ -12,6 +14,25 @@ pub enum StorageError {
     #[error("entry {0} not found")]
     NotFound(String),
 
+    #[error("{first} and {second} stores both point at {}", .path.display())]
+    ConflictingStorePaths {
+        first: &'static str,
+        second: &'static str,
+        path: PathBuf,
+    },
+
+    #[error("cannot create store directory {}: {source}", .path.display())]
+    UncreatableStorePath {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("invalid genesis: {0}")]
+    InvalidGenesis(String),
+
+    #[error("refusing to apply genesis: {0}")]
+    GenesisRefused(String),
+
     #[error("unknown error occurred")]
     Unknown,
 }
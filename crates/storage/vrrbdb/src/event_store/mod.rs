@@ -1 +1,113 @@
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
 
+use rocksdb::{Direction, IteratorMode, Options, DB};
+use serde::{Deserialize, Serialize};
+use storage_utils::{Result, StorageError};
+use vrrb_core::event_router::Event;
+
+/// An event persisted to the event store, along with the time it was
+/// recorded at
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventRecord {
+    /// Milliseconds since the unix epoch
+    pub timestamp: u64,
+    pub event: Event,
+}
+
+/// Durable, append-only log of significant node events, ordered by the time
+/// they were recorded at. Clones share the same underlying log.
+#[derive(Debug, Clone)]
+pub struct EventStore {
+    db: Arc<DB>,
+    sequence: Arc<AtomicU64>,
+}
+
+impl EventStore {
+    /// Opens the event log stored within `path`, creating it if needed
+    pub fn new(path: &PathBuf) -> Result<Self> {
+        fs::create_dir_all(path)?;
+
+        let db = DB::open_default(path).map_err(|err| StorageError::Other(err.to_string()))?;
+
+        Ok(Self {
+            db: Arc::new(db),
+            sequence: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    /// Opens the event log stored within `path` without locking it, e.g.
+    /// while the node keeps appending to it. The returned store only sees the
+    /// events appended before it was opened, and appending to it fails.
+    pub fn open_read_only(path: &PathBuf) -> Result<Self> {
+        let db = DB::open_for_read_only(&Options::default(), path, false)
+            .map_err(|err| StorageError::Other(err.to_string()))?;
+
+        Ok(Self {
+            db: Arc::new(db),
+            sequence: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    /// Appends an event to the log, stamped with the current time
+    pub fn append(&self, event: Event) -> Result<EventRecord> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default();
+
+        let record = EventRecord { timestamp, event };
+
+        self.put(&record)?;
+
+        Ok(record)
+    }
+
+    /// Appends records read from another log, keeping the time they were
+    /// recorded at and their order
+    pub fn restore(&self, records: Vec<EventRecord>) -> Result<()> {
+        records.iter().try_for_each(|record| self.put(record))
+    }
+
+    fn put(&self, record: &EventRecord) -> Result<()> {
+        let value =
+            serde_json::to_vec(record).map_err(|err| StorageError::Other(err.to_string()))?;
+
+        // NOTE: the sequence number keeps events recorded within the same
+        // millisecond apart, in the order they were appended
+        let sequence = self.sequence.fetch_add(1, Ordering::SeqCst);
+
+        self.db
+            .put(Self::key(record.timestamp, sequence), value)
+            .map_err(|err| StorageError::Other(err.to_string()))
+    }
+
+    /// Returns up to `limit` events recorded at or after `since`, oldest
+    /// first
+    pub fn get_events(&self, since: u64, limit: usize) -> Result<Vec<EventRecord>> {
+        let start = Self::key(since, 0);
+
+        self.db
+            .iterator(IteratorMode::From(&start, Direction::Forward))
+            .take(limit)
+            .map(|entry| {
+                let (_, value) = entry.map_err(|err| StorageError::Other(err.to_string()))?;
+
+                serde_json::from_slice(&value).map_err(|err| StorageError::Other(err.to_string()))
+            })
+            .collect()
+    }
+
+    /// Keys sort by timestamp first, so the log can be scanned from any point
+    /// in time onwards
+    fn key(timestamp: u64, sequence: u64) -> Vec<u8> {
+        [timestamp.to_be_bytes(), sequence.to_be_bytes()].concat()
+    }
+}
@@ -0,0 +1,188 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use primitives::Address;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use storage_utils::{Result, StorageError};
+use vrrb_core::account::{Account, AccountField};
+
+use crate::{VrrbDb, VrrbDbConfig};
+
+/// Name of the file, within a db's directory, recording the hash of the
+/// genesis the db was initialized from
+pub const GENESIS_HASH_FILE_NAME: &str = "genesis.hash";
+
+/// Initial state of a single genesis account
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GenesisAllocation {
+    pub credits: u128,
+    pub storage: Option<String>,
+    pub code: Option<String>,
+}
+
+/// Accounts a chain starts out with, read from a JSON file mapping addresses
+/// to their allocations:
+///
+/// ```json
+/// { "<address>": { "credits": 1000 } }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Genesis {
+    allocations: BTreeMap<Address, GenesisAllocation>,
+    hash: String,
+}
+
+/// What applying a genesis to a db amounted to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenesisOutcome {
+    /// The db was empty, the genesis accounts were written to it
+    Applied,
+    /// The db was already initialized from the same genesis and was left
+    /// untouched
+    AlreadyApplied,
+}
+
+impl Genesis {
+    /// Reads and parses the genesis file at `path`
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)?;
+
+        Self::from_json(&contents)
+    }
+
+    pub fn from_json(contents: &str) -> Result<Self> {
+        let raw: BTreeMap<String, GenesisAllocation> = serde_json::from_str(contents)
+            .map_err(|err| StorageError::InvalidGenesis(err.to_string()))?;
+
+        let allocations = raw
+            .into_iter()
+            .map(|(address, allocation)| {
+                // NOTE: genesis files written before addresses were
+                // checksummed key accounts by their public key hex
+                Address::from_str(&address)
+                    .or_else(|err| Address::from_public_key_hex(&address).map_err(|_| err))
+                    .map(|address| (address, allocation))
+                    .map_err(|err| {
+                        StorageError::InvalidGenesis(format!("invalid address {address}: {err}"))
+                    })
+            })
+            .collect::<Result<BTreeMap<Address, GenesisAllocation>>>()?;
+
+        // NOTE: the hash is taken over the parsed allocations so formatting
+        // and key order within the file don't affect it. Addresses are hashed
+        // as public key hex, which genesis hashes were taken over before
+        // addresses were checksummed.
+        let canonical = allocations
+            .iter()
+            .map(|(address, allocation)| (address.public_key().to_string(), allocation))
+            .collect::<BTreeMap<String, &GenesisAllocation>>();
+
+        let bytes = serde_json::to_vec(&canonical)
+            .map_err(|err| StorageError::InvalidGenesis(err.to_string()))?;
+
+        Ok(Self {
+            allocations,
+            hash: format!("{:x}", Sha256::digest(bytes)),
+        })
+    }
+
+    /// Hex encoded SHA-256 digest identifying this genesis
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
+
+    pub fn allocations(&self) -> &BTreeMap<Address, GenesisAllocation> {
+        &self.allocations
+    }
+
+    /// Builds the accounts this genesis allocates
+    pub fn accounts(&self) -> Result<Vec<(Address, Account)>> {
+        self.allocations
+            .iter()
+            .map(|(address, allocation)| {
+                let mut account = Account::new(address.public_key());
+
+                let fields = [
+                    AccountField::Credits(allocation.credits),
+                    AccountField::Storage(allocation.storage.clone()),
+                    AccountField::Code(allocation.code.clone()),
+                ];
+
+                for field in fields {
+                    account
+                        .update_field(field)
+                        .map_err(|err| StorageError::InvalidGenesis(err.to_string()))?;
+                }
+
+                Ok((address.clone(), account))
+            })
+            .collect()
+    }
+}
+
+impl VrrbDbConfig {
+    /// File the hash of the genesis the db was initialized from is recorded
+    /// in
+    pub fn genesis_hash_path(&self) -> PathBuf {
+        self.path.join(GENESIS_HASH_FILE_NAME)
+    }
+
+    /// Returns the hash of the genesis the db was initialized from, if any
+    pub fn recorded_genesis_hash(&self) -> Result<Option<String>> {
+        match fs::read_to_string(self.genesis_hash_path()) {
+            Ok(hash) => Ok(Some(hash.trim().to_string())),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+impl VrrbDb {
+    /// Writes the accounts allocated by `genesis` to an empty db, recording
+    /// the genesis hash next to the db's stores.
+    ///
+    /// A db already initialized from the same genesis is left untouched, so
+    /// this can run on every startup. Applying a genesis to a db that holds
+    /// any other state is refused.
+    pub fn apply_genesis(
+        &mut self,
+        genesis: &Genesis,
+        config: &VrrbDbConfig,
+    ) -> Result<GenesisOutcome> {
+        match config.recorded_genesis_hash()? {
+            Some(recorded) if recorded == genesis.hash() => {
+                return Ok(GenesisOutcome::AlreadyApplied);
+            },
+            Some(recorded) => {
+                return Err(StorageError::GenesisRefused(format!(
+                    "db was initialized from genesis {recorded}, not {}",
+                    genesis.hash()
+                )));
+            },
+            None => {},
+        }
+
+        if self.state_store().len() > 0 {
+            return Err(StorageError::GenesisRefused(String::from(
+                "db already holds state",
+            )));
+        }
+
+        for (address, result) in self.extend_accounts(genesis.accounts()?) {
+            result.map_err(|err| {
+                StorageError::GenesisRefused(format!("unable to allocate {address}: {err}"))
+            })?;
+        }
+
+        fs::create_dir_all(&config.path)?;
+        fs::write(config.genesis_hash_path(), genesis.hash())?;
+
+        Ok(GenesisOutcome::Applied)
+    }
+}
@@ -1,4 +1,5 @@
 mod event_store;
+mod genesis;
 pub mod result;
 mod rocksdb_adapter;
 mod state_store;
@@ -8,6 +9,7 @@ mod vrrbdb_read_handle;
 mod vrrbdb_serialized_values;
 
 pub use event_store::*;
+pub use genesis::*;
 pub use rocksdb_adapter::*;
 pub use state_store::*;
 pub use transaction_store::*;
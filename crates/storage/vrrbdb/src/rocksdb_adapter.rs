@@ -64,6 +64,26 @@ impl RocksDbAdapter {
             column: column_family.to_string(),
         })
     }
+
+    /// Opens an existing db without locking it, so it can be read while
+    /// another process has it open. Writes through the returned adapter fail.
+    pub fn new_read_only(
+        path: std::path::PathBuf,
+        column_family: &str,
+    ) -> storage_utils::Result<Self> {
+        let options = base_db_options();
+
+        let cfs = rocksdb::DB::list_cf(&options, &path)
+            .map_err(|err| StorageError::Other(err.to_string()))?;
+
+        let instance = rocksdb::DB::open_cf_for_read_only(&options, &path, cfs, false)
+            .map_err(|err| StorageError::Other(err.to_string()))?;
+
+        Ok(Self {
+            db: instance,
+            column: column_family.to_string(),
+        })
+    }
 }
 
 // TODO: handle these unwrap
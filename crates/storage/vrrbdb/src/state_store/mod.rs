@@ -1,6 +1,11 @@
-use std::{collections::HashMap, path::PathBuf, sync::Arc};
-
-use lr_trie::{LeftRightTrie, H256};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::PathBuf,
+    sync::Arc,
+};
+
+use lr_trie::{InnerTrieWrapper, LeftRightTrie, H256};
 use primitives::Address;
 use sha2::Digest;
 use storage_utils::{Result, StorageError};
@@ -35,14 +40,24 @@ impl Default for StateStore {
 }
 
 impl StateStore {
-    /// Returns new, empty instance of StateDb
+    /// Returns new, empty instance of StateDb stored within `path`, creating
+    /// the directory if needed
+    pub fn new(path: &PathBuf) -> Result<Self> {
+        fs::create_dir_all(path)?;
 
-    pub fn new(path: &PathBuf) -> Self {
-        let path = path.join("state");
-        let db_adapter = RocksDbAdapter::new(path.to_owned(), "state").unwrap_or_default();
+        let db_adapter = RocksDbAdapter::new(path.to_owned(), "state")?;
         let trie = LeftRightTrie::new(Arc::new(db_adapter));
 
-        Self { trie }
+        Ok(Self { trie })
+    }
+
+    /// Opens the StateDb stored within `path` without locking it. Writes to
+    /// the returned store fail.
+    pub fn open_read_only(path: &PathBuf) -> Result<Self> {
+        let db_adapter = RocksDbAdapter::new_read_only(path.to_owned(), "state")?;
+        let trie = LeftRightTrie::new(Arc::new(db_adapter));
+
+        Ok(Self { trie })
     }
 
     /// Returns new ReadHandle to the VrrDb data. As long as the returned value
@@ -52,6 +67,15 @@ impl StateStore {
         StateStoreReadHandle::new(inner)
     }
 
+    /// Calls `f` with a read handle while holding the trie's read guard, so
+    /// no write is published to the trie until `f` returns
+    pub fn with_read_handle<T>(&self, f: impl FnOnce(&StateStoreReadHandle) -> T) -> T {
+        let guard = self.trie.read_handle.enter();
+        let inner = guard.as_deref().cloned().unwrap_or_default();
+
+        f(&StateStoreReadHandle::new(InnerTrieWrapper::new(inner)))
+    }
+
     /// Commits uncommitted changes to the underlying trie by calling
     /// `publish()` Will wait for EACH ReadHandle to be consumed.
     fn commit_changes(&mut self) {
@@ -158,6 +182,8 @@ impl StateStore {
             .update(update)
             .map_err(|err| StorageError::Other(err.to_string()))?;
 
+        self.trie.insert_uncommitted(key, account);
+
         Ok(())
     }
 
@@ -263,8 +289,40 @@ impl StateStore {
         self.trie.root()
     }
 
-    pub fn extend(&mut self, accounts: Vec<(Address, Account)>) {
-        self.trie.extend(accounts)
+    /// Inserts a batch of accounts, returning the outcome of each insert in
+    /// the order they were provided.
+    ///
+    /// Addresses already present in the store or earlier in the batch are
+    /// reported as `StorageError::RecordExists` without aborting the rest of
+    /// the batch.
+    pub fn extend(&mut self, accounts: Vec<(Address, Account)>) -> Vec<(Address, Result<()>)> {
+        let read_handle = self.read_handle();
+        let mut batch_addresses = HashSet::new();
+
+        let outcomes = accounts
+            .into_iter()
+            .map(|(key, account)| {
+                if read_handle.get(&key).is_ok() || !batch_addresses.insert(key.clone()) {
+                    return (key, Err(StorageError::RecordExists));
+                }
+
+                let outcome = self.insert_uncommited(key.clone(), account);
+
+                (key, outcome)
+            })
+            .collect();
+
+        // NOTE: changes can't be committed while a read handle is alive
+        drop(read_handle);
+        self.commit_changes();
+
+        outcomes
+    }
+
+    /// Writes accounts back exactly as they were exported, skipping the
+    /// checks `insert` applies to brand new accounts.
+    pub(crate) fn restore(&mut self, accounts: Vec<(Address, Account)>) {
+        self.trie.extend(accounts);
     }
 
     pub fn factory(&self) -> StateStoreReadHandleFactory {
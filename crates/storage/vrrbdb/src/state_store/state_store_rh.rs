@@ -1,4 +1,7 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
 
 use lr_trie::{InnerTrieWrapper, ReadHandleFactory};
 use patriecia::inner::InnerTrie;
@@ -12,11 +15,17 @@ use crate::RocksDbAdapter;
 #[derive(Debug, Clone)]
 pub struct StateStoreReadHandle {
     inner: InnerTrieWrapper<RocksDbAdapter>,
+    /// Addresses of the accounts within the trie in order, sorted the first
+    /// time a page is read and shared with clones
+    ordered_addresses: Arc<Mutex<Option<Arc<Vec<Address>>>>>,
 }
 
 impl StateStoreReadHandle {
     pub fn new(inner: InnerTrieWrapper<RocksDbAdapter>) -> Self {
-        Self { inner }
+        Self {
+            inner,
+            ordered_addresses: Arc::default(),
+        }
     }
 
     /// Returns `Some(Account)` if an account exist under given PublicKey.
@@ -57,6 +66,52 @@ impl StateStoreReadHandle {
             .collect()
     }
 
+    /// Returns up to `limit` accounts starting at `offset`, along with the
+    /// total number of accounts in the store.
+    ///
+    /// Accounts are ordered by address, so consecutive pages read from the
+    /// same handle neither overlap nor skip any account. Addresses are only
+    /// sorted once per handle, every page after that reads just its own
+    /// accounts.
+    pub fn entries_paged(&self, offset: usize, limit: usize) -> (Vec<(Address, Account)>, usize) {
+        let addresses = self.ordered_addresses();
+
+        let page = addresses
+            .get(offset..)
+            .unwrap_or_default()
+            .iter()
+            .take(limit)
+            .filter_map(|address| {
+                self.get(address)
+                    .ok()
+                    .map(|account| (address.clone(), account))
+            })
+            .collect();
+
+        (page, addresses.len())
+    }
+
+    fn ordered_addresses(&self) -> Arc<Vec<Address>> {
+        let mut ordered_addresses = match self.ordered_addresses.lock() {
+            Ok(ordered_addresses) => ordered_addresses,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        ordered_addresses
+            .get_or_insert_with(|| {
+                let mut addresses = self
+                    .inner
+                    .iter()
+                    .filter_map(|(key, _)| bincode::deserialize::<Address>(&key).ok())
+                    .collect::<Vec<Address>>();
+
+                addresses.sort_unstable();
+
+                Arc::new(addresses)
+            })
+            .clone()
+    }
+
     /// Returns a number of initialized accounts in the database
     pub fn len(&self) -> usize {
         self.inner.len()
@@ -1,17 +1,30 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{collections::HashMap, fs, path::PathBuf, sync::Arc};
 
-use lr_trie::{LeftRightTrie, Proof, H256};
+use lr_trie::{InnerTrieWrapper, LeftRightTrie, Proof, H256};
 use storage_utils::Result;
-use vrrb_core::txn::{TransactionDigest, Txn};
+use vrrb_core::{
+    txn::{TransactionDigest, Txn},
+    txn_receipts::TxnReceipt,
+};
 
 use crate::RocksDbAdapter;
 
 mod transaction_store_rh;
 pub use transaction_store_rh::*;
 
+/// Maps an address to the digests of every transaction it sent or received,
+/// in the order they were inserted
+type AddressIndex = LeftRightTrie<'static, String, Vec<TransactionDigest>, RocksDbAdapter>;
+
+/// Maps a digest to the latest receipt of the transaction, be it confirmed or
+/// not
+type Receipts = LeftRightTrie<'static, TransactionDigest, TxnReceipt, RocksDbAdapter>;
+
 #[derive(Debug, Clone)]
 pub struct TransactionStore {
     trie: LeftRightTrie<'static, TransactionDigest, Txn, RocksDbAdapter>,
+    index: AddressIndex,
+    receipts: Receipts,
 }
 
 impl Default for TransactionStore {
@@ -21,47 +34,151 @@ impl Default for TransactionStore {
             .join("db")
             .join("transactions");
 
+        let index_adapter =
+            RocksDbAdapter::new(db_path.join("index"), "transaction_index").unwrap_or_default();
+        let receipts_adapter =
+            RocksDbAdapter::new(db_path.join("receipts"), "transaction_receipts")
+                .unwrap_or_default();
         let db_adapter = RocksDbAdapter::new(db_path, "transactions").unwrap_or_default();
 
         let trie = LeftRightTrie::new(Arc::new(db_adapter));
-
-        Self { trie }
+        let index = LeftRightTrie::new(Arc::new(index_adapter));
+        let receipts = LeftRightTrie::new(Arc::new(receipts_adapter));
+
+        Self {
+            trie,
+            index,
+            receipts,
+        }
     }
 }
 
 impl TransactionStore {
-    /// Returns new, empty instance of TransactionStore
-    pub fn new(path: &PathBuf) -> Self {
-        let path = path.join("transactions");
-        let db_adapter = RocksDbAdapter::new(path.to_owned(), "transactions").unwrap_or_default();
+    /// Returns new, empty instance of TransactionStore stored within `path`,
+    /// creating the directory if needed
+    pub fn new(path: &PathBuf) -> Result<Self> {
+        fs::create_dir_all(path)?;
+
+        let db_adapter = RocksDbAdapter::new(path.to_owned(), "transactions")?;
+        let index_adapter = RocksDbAdapter::new(path.join("index"), "transaction_index")?;
+        let receipts_adapter = RocksDbAdapter::new(path.join("receipts"), "transaction_receipts")?;
+
         let trie = LeftRightTrie::new(Arc::new(db_adapter));
+        let index = LeftRightTrie::new(Arc::new(index_adapter));
+        let receipts = LeftRightTrie::new(Arc::new(receipts_adapter));
+
+        Ok(Self {
+            trie,
+            index,
+            receipts,
+        })
+    }
 
-        Self { trie }
+    /// Opens the TransactionStore stored within `path` without locking it.
+    /// Writes to the returned store fail.
+    pub fn open_read_only(path: &PathBuf) -> Result<Self> {
+        let db_adapter = RocksDbAdapter::new_read_only(path.to_owned(), "transactions")?;
+        let index_adapter = RocksDbAdapter::new_read_only(path.join("index"), "transaction_index")?;
+        let receipts_adapter =
+            RocksDbAdapter::new_read_only(path.join("receipts"), "transaction_receipts")?;
+
+        let trie = LeftRightTrie::new(Arc::new(db_adapter));
+        let index = LeftRightTrie::new(Arc::new(index_adapter));
+        let receipts = LeftRightTrie::new(Arc::new(receipts_adapter));
+
+        Ok(Self {
+            trie,
+            index,
+            receipts,
+        })
     }
 
     pub fn factory(&self) -> TransactionStoreReadHandleFactory {
-        let inner = self.trie.factory();
-
-        TransactionStoreReadHandleFactory::new(inner)
+        TransactionStoreReadHandleFactory::new(
+            self.trie.factory(),
+            self.index.factory(),
+            self.receipts.factory(),
+        )
     }
 
     pub fn read_handle(&self) -> TransactionStoreReadHandle {
+        // NOTE: the index is read before the transactions so every digest it
+        // holds is guaranteed to resolve, see `extend`
+        let index = self.index.handle();
         let inner = self.trie.handle();
-        TransactionStoreReadHandle::new(inner)
+        let receipts = self.receipts.handle();
+
+        TransactionStoreReadHandle::new(inner, index, receipts)
+    }
+
+    /// Calls `f` with a read handle while holding the read guards of every
+    /// trie within the store, so no write is published to any of them until
+    /// `f` returns
+    pub fn with_read_handle<T>(&self, f: impl FnOnce(&TransactionStoreReadHandle) -> T) -> T {
+        let index_guard = self.index.read_handle.enter();
+        let inner_guard = self.trie.read_handle.enter();
+        let receipts_guard = self.receipts.read_handle.enter();
+
+        let handle = TransactionStoreReadHandle::new(
+            InnerTrieWrapper::new(inner_guard.as_deref().cloned().unwrap_or_default()),
+            InnerTrieWrapper::new(index_guard.as_deref().cloned().unwrap_or_default()),
+            InnerTrieWrapper::new(receipts_guard.as_deref().cloned().unwrap_or_default()),
+        );
+
+        f(&handle)
     }
 
     pub fn insert(&mut self, txn: Txn) -> Result<()> {
-        self.trie.insert(txn.digest(), txn);
+        self.extend(vec![txn]);
         Ok(())
     }
 
+    /// Inserts a batch of transactions and indexes them by the addresses
+    /// involved in them.
+    ///
+    /// Transactions are published before the index entries pointing at them,
+    /// so readers never find a digest in the index that doesn't resolve to a
+    /// transaction.
     pub fn extend(&mut self, transactions: Vec<Txn>) {
+        let index_entries = self.index_entries(&transactions);
+
         let transactions = transactions
             .into_iter()
             .map(|txn| (txn.digest(), txn))
             .collect();
 
-        self.trie.extend(transactions)
+        self.trie.extend(transactions);
+        self.index.extend(index_entries);
+    }
+
+    /// Stores `receipt`, replacing any earlier receipt of the same
+    /// transaction
+    pub fn insert_receipt(&mut self, receipt: TxnReceipt) {
+        self.receipts
+            .extend(vec![(receipt.digest.clone(), receipt)]);
+    }
+
+    /// Appends the digests of the given transactions to the index entries of
+    /// their senders and receivers
+    fn index_entries(&self, transactions: &[Txn]) -> Vec<(String, Vec<TransactionDigest>)> {
+        let index = self.index.handle();
+        let mut entries: HashMap<String, Vec<TransactionDigest>> = HashMap::new();
+
+        for txn in transactions {
+            let digest = txn.digest();
+
+            for address in [&txn.sender_address, &txn.receiver_address] {
+                let digests = entries
+                    .entry(address.clone())
+                    .or_insert_with(|| index.get(address).unwrap_or_default());
+
+                if !digests.contains(&digest) {
+                    digests.push(digest.clone());
+                }
+            }
+        }
+
+        entries.into_iter().collect()
     }
 
     pub fn root_hash(&self) -> Option<H256> {
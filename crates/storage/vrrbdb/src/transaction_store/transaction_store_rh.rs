@@ -2,19 +2,33 @@ use std::collections::HashMap;
 
 use lr_trie::{InnerTrieWrapper, ReadHandleFactory};
 use patriecia::inner::InnerTrie;
+use primitives::Address;
 use storage_utils::{Result, StorageError};
-use vrrb_core::txn::{TransactionDigest, Txn};
+use vrrb_core::{
+    txn::{TransactionDigest, Txn},
+    txn_receipts::TxnReceipt,
+};
 
 use crate::RocksDbAdapter;
 
 #[derive(Debug, Clone)]
 pub struct TransactionStoreReadHandle {
     inner: InnerTrieWrapper<RocksDbAdapter>,
+    index: InnerTrieWrapper<RocksDbAdapter>,
+    receipts: InnerTrieWrapper<RocksDbAdapter>,
 }
 
 impl TransactionStoreReadHandle {
-    pub fn new(inner: InnerTrieWrapper<RocksDbAdapter>) -> Self {
-        Self { inner }
+    pub fn new(
+        inner: InnerTrieWrapper<RocksDbAdapter>,
+        index: InnerTrieWrapper<RocksDbAdapter>,
+        receipts: InnerTrieWrapper<RocksDbAdapter>,
+    ) -> Self {
+        Self {
+            inner,
+            index,
+            receipts,
+        }
     }
 
     pub fn get(&self, key: &TransactionDigest) -> Result<Txn> {
@@ -37,6 +51,29 @@ impl TransactionStoreReadHandle {
         transactions
     }
 
+    /// Returns the transaction with the given digest, if any
+    pub fn get_transaction(&self, digest: &TransactionDigest) -> Option<Txn> {
+        self.get(digest).ok()
+    }
+
+    /// Returns the latest receipt stored for the transaction with the given
+    /// digest, if any
+    pub fn get_receipt(&self, digest: &TransactionDigest) -> Option<TxnReceipt> {
+        self.receipts.get(digest).ok()
+    }
+
+    /// Returns every transaction sent or received by `address`, in the order
+    /// they were inserted
+    pub fn get_transactions_by_address(&self, address: &Address) -> Vec<Txn> {
+        let digests: Vec<TransactionDigest> =
+            self.index.get(&address.to_string()).unwrap_or_default();
+
+        digests
+            .iter()
+            .filter_map(|digest| self.get_transaction(digest))
+            .collect()
+    }
+
     pub fn entries(&self) -> HashMap<TransactionDigest, Txn> {
         // TODO: revisit and refactor into inner wrapper
         self.inner
@@ -64,23 +101,45 @@ impl TransactionStoreReadHandle {
 #[derive(Debug, Clone)]
 pub struct TransactionStoreReadHandleFactory {
     inner: ReadHandleFactory<InnerTrie<RocksDbAdapter>>,
+    index: ReadHandleFactory<InnerTrie<RocksDbAdapter>>,
+    receipts: ReadHandleFactory<InnerTrie<RocksDbAdapter>>,
 }
 
 impl TransactionStoreReadHandleFactory {
-    pub fn new(inner: ReadHandleFactory<InnerTrie<RocksDbAdapter>>) -> Self {
-        Self { inner }
+    pub fn new(
+        inner: ReadHandleFactory<InnerTrie<RocksDbAdapter>>,
+        index: ReadHandleFactory<InnerTrie<RocksDbAdapter>>,
+        receipts: ReadHandleFactory<InnerTrie<RocksDbAdapter>>,
+    ) -> Self {
+        Self {
+            inner,
+            index,
+            receipts,
+        }
     }
 
     pub fn handle(&self) -> TransactionStoreReadHandle {
-        let handle = self
-            .inner
+        // NOTE: the index is read first, see `TransactionStore::read_handle`
+        let index = Self::enter(&self.index);
+        let inner = Self::enter(&self.inner);
+        let receipts = Self::enter(&self.receipts);
+
+        TransactionStoreReadHandle {
+            inner,
+            index,
+            receipts,
+        }
+    }
+
+    fn enter(
+        factory: &ReadHandleFactory<InnerTrie<RocksDbAdapter>>,
+    ) -> InnerTrieWrapper<RocksDbAdapter> {
+        let handle = factory
             .handle()
             .enter()
             .map(|guard| guard.clone())
             .unwrap_or_default();
 
-        let inner = InnerTrieWrapper::new(handle);
-
-        TransactionStoreReadHandle { inner }
+        InnerTrieWrapper::new(handle)
     }
 }
@@ -1,15 +1,25 @@
-use std::{collections::HashMap, fmt::Display, path::PathBuf};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+};
 
 use lr_trie::H256;
 use primitives::Address;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use storage_utils::{Result, StorageError};
 use vrrb_core::{
     account::{Account, UpdateArgs},
+    key_rotation::KeyRotation,
     txn::Txn,
+    txn_receipts::TxnReceipt,
 };
 
 use crate::{
+    EventRecord,
+    EventStore,
     StateStore,
     StateStoreReadHandleFactory,
     TransactionStore,
@@ -17,6 +27,8 @@ use crate::{
     VrrbDbReadHandle,
 };
 
+/// Configures where a `VrrbDb` keeps its stores. Stores without an explicit
+/// path are kept in their own directory within `path`.
 #[derive(Debug, Clone)]
 pub struct VrrbDbConfig {
     pub path: PathBuf,
@@ -40,21 +52,124 @@ impl Default for VrrbDbConfig {
     }
 }
 
+impl VrrbDbConfig {
+    /// Directory the state store is kept in
+    pub fn state_store_dir(&self) -> PathBuf {
+        Self::store_dir(&self.path, &self.state_store_path, "state")
+    }
+
+    /// Directory the transaction store is kept in
+    pub fn transaction_store_dir(&self) -> PathBuf {
+        Self::store_dir(&self.path, &self.transaction_store_path, "transactions")
+    }
+
+    /// Directory the event store is kept in
+    pub fn event_store_dir(&self) -> PathBuf {
+        Self::store_dir(&self.path, &self.event_store_path, "events")
+    }
+
+    fn store_dir(path: &Path, explicit_path: &Option<String>, default_dir: &str) -> PathBuf {
+        explicit_path
+            .as_ref()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| path.join(default_dir))
+    }
+
+    /// Checks that no two stores share a directory and that every explicitly
+    /// configured store directory exists or can be created.
+    pub fn validate(&self) -> Result<()> {
+        let stores = [
+            ("state", self.state_store_dir()),
+            ("transaction", self.transaction_store_dir()),
+            ("event", self.event_store_dir()),
+        ];
+
+        for (idx, (second, path)) in stores.iter().enumerate() {
+            if let Some((first, _)) = stores[..idx].iter().find(|(_, other)| other == path) {
+                return Err(StorageError::ConflictingStorePaths {
+                    first: *first,
+                    second: *second,
+                    path: path.clone(),
+                });
+            }
+        }
+
+        let explicit_paths = [
+            &self.state_store_path,
+            &self.transaction_store_path,
+            &self.event_store_path,
+        ];
+
+        for path in explicit_paths.into_iter().flatten() {
+            fs::create_dir_all(path).map_err(|source| StorageError::UncreatableStorePath {
+                path: PathBuf::from(path),
+                source,
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Version of the archive format written by `VrrbDb::export_snapshot`. Bump it
+/// whenever the layout of `VrrbDbSnapshot` changes.
+pub const VRRBDB_SNAPSHOT_VERSION: u32 = 2;
+
+/// Point-in-time copy of every entry held by a `VrrbDb`, along with the
+/// events logged to its event store
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VrrbDbSnapshot {
+    pub version: u32,
+    pub accounts: Vec<(Address, Account)>,
+    pub transactions: Vec<Txn>,
+    pub events: Vec<EventRecord>,
+}
+
+/// Used to peek at a snapshot's version before attempting to decode the rest
+/// of it
+#[derive(Deserialize)]
+struct VrrbDbSnapshotHeader {
+    version: u32,
+}
+
 #[derive(Debug)]
 pub struct VrrbDb {
     state_store: StateStore,
     transaction_store: TransactionStore,
+    /// Where the node logs events to, see `EventStore`. It's written to by
+    /// the node's event store module rather than through the db.
+    event_store_dir: Option<PathBuf>,
 }
 
 impl VrrbDb {
-    pub fn new(config: VrrbDbConfig) -> Self {
-        let state_store = StateStore::new(&config.path);
-        let transaction_store = TransactionStore::new(&config.path);
+    /// Opens the stores described by `config`, failing if its store paths
+    /// conflict with each other, can't be created or the storage backend
+    /// can't be opened within them.
+    pub fn new(config: VrrbDbConfig) -> Result<Self> {
+        config.validate()?;
 
-        Self {
+        let state_store = StateStore::new(&config.state_store_dir())?;
+        let transaction_store = TransactionStore::new(&config.transaction_store_dir())?;
+
+        Ok(Self {
             state_store,
             transaction_store,
-        }
+            event_store_dir: Some(config.event_store_dir()),
+        })
+    }
+
+    /// Opens an existing db for inspection, without locking it or creating
+    /// anything that's missing. It can be opened while a node is running on
+    /// it, but any write to it fails.
+    pub fn open_read_only(config: &VrrbDbConfig) -> Result<Self> {
+        let state_store = StateStore::open_read_only(&config.state_store_dir())?;
+        let transaction_store = TransactionStore::open_read_only(&config.transaction_store_dir())?;
+
+        Ok(Self {
+            state_store,
+            transaction_store,
+            event_store_dir: Some(config.event_store_dir()),
+        })
     }
 
     pub fn read_handle(&self) -> VrrbDbReadHandle {
@@ -65,6 +180,7 @@ impl VrrbDb {
         Self {
             state_store,
             transaction_store,
+            event_store_dir: None,
         }
     }
 
@@ -103,9 +219,20 @@ impl VrrbDb {
         self.state_store.insert(key, account)
     }
 
-    /// Adds multiplpe accounts to current state tree.
-    pub fn extend_accounts(&mut self, accounts: Vec<(Address, Account)>) {
-        self.state_store.extend(accounts);
+    /// Adds multiplpe accounts to current state tree, returning the outcome
+    /// of each insert in the order they were provided.
+    pub fn extend_accounts(
+        &mut self,
+        accounts: Vec<(Address, Account)>,
+    ) -> Vec<(Address, Result<()>)> {
+        self.state_store.extend(accounts)
+    }
+
+    /// Writes accounts synced from a peer exactly as the peer stores them,
+    /// overwriting local copies and skipping the checks `insert_account`
+    /// applies to brand new accounts.
+    pub fn restore_accounts(&mut self, accounts: Vec<(Address, Account)>) {
+        self.state_store.restore(accounts);
     }
 
     /// Updates an account on the current state tree.
@@ -120,6 +247,31 @@ impl VrrbDb {
                     storage: Some(account.storage),
                     code: Some(account.code),
                     digests: Some(account.digests),
+                    pubkey: None,
+                },
+            )
+            .map_err(|err| StorageError::Other(err.to_string()))
+    }
+
+    /// Replaces the public key of an account, once the rotation is checked
+    /// against the key and nonce currently stored for it
+    pub fn rotate_account_key(&mut self, rotation: KeyRotation) -> Result<()> {
+        let account = self
+            .read_handle()
+            .get_account(&rotation.address)
+            .ok_or_else(|| StorageError::NotFound(rotation.address.to_string()))?;
+
+        rotation
+            .validate_against(&account)
+            .map_err(|err| StorageError::Other(err.to_string()))?;
+
+        self.state_store
+            .update(
+                rotation.address,
+                UpdateArgs {
+                    nonce: account.nonce + 1,
+                    pubkey: Some(rotation.new_pubkey),
+                    ..Default::default()
                 },
             )
             .map_err(|err| StorageError::Other(err.to_string()))
@@ -148,6 +300,85 @@ impl VrrbDb {
     pub fn extend_transactions(&mut self, transactions: Vec<Txn>) {
         self.transaction_store.extend(transactions);
     }
+
+    /// Stores the receipt of a transaction, replacing any earlier receipt of
+    /// the same transaction. The transaction itself doesn't need to be
+    /// stored.
+    pub fn insert_txn_receipt(&mut self, receipt: TxnReceipt) {
+        self.transaction_store.insert_receipt(receipt);
+    }
+
+    /// Writes the contents of every store to a single archive at `path`.
+    ///
+    /// The read guards of every store are held until everything was
+    /// collected, so writes landing while the export runs are not part of it.
+    /// The event store is read from a read-only instance opened meanwhile, so
+    /// it only holds the events logged before the export.
+    pub fn export_snapshot(&self, path: &Path) -> Result<()> {
+        let snapshot: Result<VrrbDbSnapshot> = self.state_store.with_read_handle(|state_handle| {
+            self.transaction_store
+                .with_read_handle(|transaction_handle| {
+                    Ok(VrrbDbSnapshot {
+                        version: VRRBDB_SNAPSHOT_VERSION,
+                        accounts: state_handle.entries().into_iter().collect(),
+                        transactions: transaction_handle.entries().into_values().collect(),
+                        events: self.logged_events()?,
+                    })
+                })
+        });
+
+        let bytes =
+            serde_json::to_vec(&snapshot?).map_err(|err| StorageError::Other(err.to_string()))?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, bytes)?;
+
+        Ok(())
+    }
+
+    /// Rebuilds a db at `config.path` from an archive written by
+    /// `export_snapshot`.
+    pub fn import_snapshot(path: &Path, config: &VrrbDbConfig) -> Result<Self> {
+        let bytes = fs::read(path)?;
+
+        let header: VrrbDbSnapshotHeader =
+            serde_json::from_slice(&bytes).map_err(|err| StorageError::Other(err.to_string()))?;
+
+        if header.version != VRRBDB_SNAPSHOT_VERSION {
+            return Err(StorageError::Other(format!(
+                "unsupported snapshot version {}, expected {}",
+                header.version, VRRBDB_SNAPSHOT_VERSION
+            )));
+        }
+
+        let snapshot: VrrbDbSnapshot =
+            serde_json::from_slice(&bytes).map_err(|err| StorageError::Other(err.to_string()))?;
+
+        let mut db = Self::new(config.clone())?;
+
+        db.state_store.restore(snapshot.accounts);
+        db.transaction_store.extend(snapshot.transactions);
+
+        if !snapshot.events.is_empty() {
+            EventStore::new(&config.event_store_dir())?.restore(snapshot.events)?;
+        }
+
+        Ok(db)
+    }
+
+    /// Returns every event logged to the event store, none if the node never
+    /// kept one
+    fn logged_events(&self) -> Result<Vec<EventRecord>> {
+        match &self.event_store_dir {
+            Some(event_store_dir) if event_store_dir.exists() => {
+                EventStore::open_read_only(event_store_dir)?.get_events(0, usize::MAX)
+            },
+            _ => Ok(vec![]),
+        }
+    }
 }
 
 impl Clone for VrrbDb {
@@ -155,6 +386,7 @@ impl Clone for VrrbDb {
         Self {
             state_store: self.state_store.clone(),
             transaction_store: self.transaction_store.clone(),
+            event_store_dir: self.event_store_dir.clone(),
         }
     }
 }
@@ -4,9 +4,10 @@ use primitives::Address;
 use vrrb_core::{
     account::Account,
     txn::{TransactionDigest, Txn},
+    txn_receipts::TxnReceipt,
 };
 
-use crate::{StateStoreReadHandleFactory, TransactionStoreReadHandleFactory};
+use crate::{StateStoreReadHandle, StateStoreReadHandleFactory, TransactionStoreReadHandleFactory};
 
 #[derive(Debug, Clone)]
 pub struct VrrbDbReadHandle {
@@ -30,8 +31,28 @@ impl VrrbDbReadHandle {
         self.state_store_handle_factory.handle().entries()
     }
 
+    /// Returns a handle to the state trie as it is right now, writes made
+    /// afterwards aren't visible through it. Paging through the state should
+    /// go through a single one, see `StateStoreReadHandle::entries_paged`.
+    pub fn state_store_snapshot(&self) -> StateStoreReadHandle {
+        self.state_store_handle_factory.handle()
+    }
+
+    /// Returns the account stored under the given address, if any
+    pub fn get_account(&self, address: &Address) -> Option<Account> {
+        self.state_store_handle_factory.handle().get(address).ok()
+    }
+
     /// Returns a copy of all values stored within the state trie
     pub fn transaction_store_values(&self) -> HashMap<TransactionDigest, Txn> {
         self.transaction_store_handle_factory.handle().entries()
     }
+
+    /// Returns the latest stored receipt of the transaction with the given
+    /// digest, if any
+    pub fn get_txn_receipt(&self, digest: &TransactionDigest) -> Option<TxnReceipt> {
+        self.transaction_store_handle_factory
+            .handle()
+            .get_receipt(digest)
+    }
 }
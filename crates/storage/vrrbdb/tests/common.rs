@@ -18,7 +18,7 @@ pub fn generate_random_string() -> String {
 
 pub fn generate_random_address() -> (SecretKey, Address) {
     let kp = Keypair::random();
-    (kp.miner_kp.0, Address::new(kp.miner_kp.1))
+    (*kp.txn_keypair().secret_key(), kp.txn_keypair().address())
 }
 
 pub fn generate_random_transaction(
@@ -0,0 +1,151 @@
+use std::{env, fs};
+
+use storage_utils::StorageError;
+use vrrbdb::{VrrbDb, VrrbDbConfig};
+
+mod common;
+
+use common::generate_random_string;
+
+fn mock_config() -> VrrbDbConfig {
+    VrrbDbConfig {
+        path: env::temp_dir().join(generate_random_string()),
+        state_store_path: None,
+        transaction_store_path: None,
+        event_store_path: None,
+    }
+}
+
+#[test]
+fn stores_default_to_directories_within_the_db_path() {
+    let config = mock_config();
+
+    assert_eq!(config.state_store_dir(), config.path.join("state"));
+    assert_eq!(
+        config.transaction_store_dir(),
+        config.path.join("transactions")
+    );
+    assert_eq!(config.event_store_dir(), config.path.join("events"));
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn explicit_store_paths_are_used() {
+    let store_path = env::temp_dir().join(generate_random_string());
+
+    let config = VrrbDbConfig {
+        state_store_path: Some(store_path.to_string_lossy().to_string()),
+        ..mock_config()
+    };
+
+    VrrbDb::new(config).unwrap();
+
+    assert!(store_path.is_dir());
+}
+
+#[test]
+fn explicit_store_paths_must_not_collide() {
+    let store_path = env::temp_dir()
+        .join(generate_random_string())
+        .to_string_lossy()
+        .to_string();
+
+    let config = VrrbDbConfig {
+        state_store_path: Some(store_path.clone()),
+        transaction_store_path: Some(store_path),
+        ..mock_config()
+    };
+
+    let err = VrrbDb::new(config).unwrap_err();
+
+    assert!(matches!(
+        err,
+        StorageError::ConflictingStorePaths {
+            first: "state",
+            second: "transaction",
+            ..
+        }
+    ));
+}
+
+#[test]
+fn explicit_store_paths_must_not_collide_with_default_ones() {
+    let config = mock_config();
+
+    let config = VrrbDbConfig {
+        event_store_path: Some(config.state_store_dir().to_string_lossy().to_string()),
+        ..config
+    };
+
+    let err = config.validate().unwrap_err();
+
+    assert!(matches!(
+        err,
+        StorageError::ConflictingStorePaths {
+            first: "state",
+            second: "event",
+            ..
+        }
+    ));
+}
+
+#[test]
+fn explicit_store_paths_must_be_creatable() {
+    // NOTE: a directory can't be created beneath a regular file, regardless of
+    // the permissions the tests run with
+    let file_path = env::temp_dir().join(generate_random_string());
+    fs::write(&file_path, b"not a directory").unwrap();
+
+    let config = VrrbDbConfig {
+        transaction_store_path: Some(file_path.join("transactions").to_string_lossy().to_string()),
+        ..mock_config()
+    };
+
+    let err = VrrbDb::new(config).unwrap_err();
+
+    assert!(matches!(err, StorageError::UncreatableStorePath { .. }));
+}
+
+#[test]
+fn uncreatable_db_paths_are_reported() {
+    let file_path = env::temp_dir().join(generate_random_string());
+    fs::write(&file_path, b"not a directory").unwrap();
+
+    let config = VrrbDbConfig {
+        path: file_path.join("db"),
+        ..mock_config()
+    };
+
+    let err = VrrbDb::new(config).unwrap_err();
+
+    assert!(matches!(err, StorageError::Io(_)));
+}
+
+#[test]
+fn stores_already_opened_elsewhere_are_reported() {
+    let config = mock_config();
+
+    let _db = VrrbDb::new(config.clone()).unwrap();
+
+    // NOTE: the storage backend holds a lock on its directory while open
+    let err = VrrbDb::new(config).unwrap_err();
+
+    assert!(matches!(err, StorageError::Other(_)));
+}
+
+#[test]
+fn stores_opened_elsewhere_can_be_opened_read_only() {
+    let config = mock_config();
+
+    let _db = VrrbDb::new(config.clone()).unwrap();
+
+    assert!(VrrbDb::open_read_only(&config).is_ok());
+}
+
+#[test]
+fn missing_stores_are_not_created_when_opened_read_only() {
+    let config = mock_config();
+
+    assert!(VrrbDb::open_read_only(&config).is_err());
+    assert!(!config.path.exists());
+}
@@ -0,0 +1,31 @@
+use std::{env, thread, time::Duration};
+
+use vrrb_core::event_router::Event;
+use vrrbdb::EventStore;
+
+mod common;
+
+use common::{generate_random_string, generate_random_valid_transaction};
+
+#[test]
+fn events_can_be_read_back_in_order() {
+    let store = EventStore::new(&env::temp_dir().join(generate_random_string())).unwrap();
+
+    let txn = generate_random_valid_transaction();
+
+    let first = store.append(Event::TxnValidated(txn.clone())).unwrap();
+    let second = store
+        .append(Event::TxnAddedToMempool(txn.digest()))
+        .unwrap();
+
+    thread::sleep(Duration::from_millis(5));
+
+    let third = store.append(Event::BlockConfirmed(vec![1, 2, 3])).unwrap();
+
+    assert_eq!(
+        store.get_events(0, 10).unwrap(),
+        vec![first.clone(), second.clone(), third.clone()]
+    );
+    assert_eq!(store.get_events(0, 2).unwrap(), vec![first, second]);
+    assert_eq!(store.get_events(third.timestamp, 10).unwrap(), vec![third]);
+}
@@ -0,0 +1,122 @@
+use std::env;
+
+use primitives::Address;
+use storage_utils::StorageError;
+use vrrb_core::account::Account;
+use vrrbdb::{Genesis, GenesisOutcome, VrrbDb, VrrbDbConfig};
+
+mod common;
+
+use common::{generate_random_address, generate_random_string};
+
+fn mock_config() -> VrrbDbConfig {
+    VrrbDbConfig {
+        path: env::temp_dir().join(generate_random_string()),
+        state_store_path: None,
+        transaction_store_path: None,
+        event_store_path: None,
+    }
+}
+
+fn mock_genesis(allocations: &[(&Address, u128)]) -> Genesis {
+    let json = allocations
+        .iter()
+        .map(|(address, credits)| format!(r#""{address}": {{ "credits": {credits} }}"#))
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    Genesis::from_json(&format!("{{ {json} }}")).unwrap()
+}
+
+#[test]
+fn genesis_is_applied_once_to_an_empty_db() {
+    let config = mock_config();
+    let mut db = VrrbDb::new(config.clone()).unwrap();
+
+    let (_, addr1) = generate_random_address();
+    let (_, addr2) = generate_random_address();
+    let genesis = mock_genesis(&[(&addr1, 1_000), (&addr2, 250)]);
+
+    assert_eq!(
+        db.apply_genesis(&genesis, &config).unwrap(),
+        GenesisOutcome::Applied
+    );
+    assert_eq!(
+        config.recorded_genesis_hash().unwrap().as_deref(),
+        Some(genesis.hash())
+    );
+
+    let account1 = db.read_handle().get_account(&addr1).unwrap();
+    assert_eq!(account1.credits, 1_000);
+    assert_eq!(db.read_handle().get_account(&addr2).unwrap().credits, 250);
+
+    // NOTE: state moving on from genesis must survive it being applied again
+    db.update_account(
+        addr1.clone(),
+        Account {
+            credits: 1_000,
+            debits: 100,
+            ..account1
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        db.apply_genesis(&genesis, &config).unwrap(),
+        GenesisOutcome::AlreadyApplied
+    );
+
+    let account1 = db.read_handle().get_account(&addr1).unwrap();
+    assert_eq!(account1.debits, 100);
+    assert_eq!(account1.nonce, 1);
+}
+
+#[test]
+fn genesis_is_refused_by_dbs_holding_other_state() {
+    let (_, addr1) = generate_random_address();
+    let (_, addr2) = generate_random_address();
+
+    let config = mock_config();
+    let mut db = VrrbDb::new(config.clone()).unwrap();
+    db.insert_account(addr1.clone(), Account::default())
+        .unwrap();
+
+    assert!(matches!(
+        db.apply_genesis(&mock_genesis(&[(&addr2, 10)]), &config),
+        Err(StorageError::GenesisRefused(_))
+    ));
+    assert!(db.read_handle().get_account(&addr2).is_none());
+    assert_eq!(config.recorded_genesis_hash().unwrap(), None);
+
+    let config = mock_config();
+    let mut db = VrrbDb::new(config.clone()).unwrap();
+    db.apply_genesis(&mock_genesis(&[(&addr1, 10)]), &config)
+        .unwrap();
+
+    assert!(matches!(
+        db.apply_genesis(&mock_genesis(&[(&addr1, 20)]), &config),
+        Err(StorageError::GenesisRefused(_))
+    ));
+    assert_eq!(db.read_handle().get_account(&addr1).unwrap().credits, 10);
+}
+
+#[test]
+fn genesis_hash_ignores_formatting() {
+    let (_, addr) = generate_random_address();
+
+    let compact = Genesis::from_json(&format!(r#"{{"{addr}":{{"credits":5}}}}"#)).unwrap();
+    let spaced = Genesis::from_json(&format!(
+        "{{\n  \"{addr}\": {{ \"credits\": 5, \"code\": null }}\n}}"
+    ))
+    .unwrap();
+
+    let legacy =
+        Genesis::from_json(&format!(r#"{{"{}":{{"credits":5}}}}"#, addr.public_key())).unwrap();
+
+    assert_eq!(compact.hash(), spaced.hash());
+    assert_eq!(compact.hash(), legacy.hash());
+    assert!(matches!(
+        Genesis::from_json(r#"{ "not an address": { "credits": 5 } }"#),
+        Err(StorageError::InvalidGenesis(_))
+    ));
+}
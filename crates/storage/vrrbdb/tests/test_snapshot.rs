@@ -0,0 +1,111 @@
+use std::env;
+
+use storage_utils::StorageError;
+use vrrb_core::{account::Account, event_router::Event};
+use vrrbdb::{EventStore, VrrbDb, VrrbDbConfig, VrrbDbSnapshot};
+
+mod common;
+
+use common::{generate_random_address, generate_random_string, generate_random_valid_transaction};
+
+fn mock_config() -> VrrbDbConfig {
+    VrrbDbConfig {
+        path: env::temp_dir().join(generate_random_string()),
+        state_store_path: None,
+        transaction_store_path: None,
+        event_store_path: None,
+    }
+}
+
+#[test]
+fn snapshots_can_be_imported_into_a_fresh_db() {
+    let config = mock_config();
+    let mut db = VrrbDb::new(config.clone()).unwrap();
+
+    // NOTE: kept open, like the node's event store module does
+    let event_store = EventStore::new(&config.event_store_dir()).unwrap();
+    event_store.append(Event::CheckAbandoned).unwrap();
+    event_store.append(Event::NoOp).unwrap();
+
+    let (_, addr1) = generate_random_address();
+    let (_, addr2) = generate_random_address();
+
+    db.insert_account(
+        addr1.clone(),
+        Account {
+            credits: 100,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    db.insert_account(addr2.clone(), Account::default())
+        .unwrap();
+
+    // NOTE: updated accounts carry a nonce, which regular inserts reject
+    db.update_account(
+        addr1,
+        Account {
+            credits: 250,
+            debits: 50,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    db.extend_transactions(vec![
+        generate_random_valid_transaction(),
+        generate_random_valid_transaction(),
+        generate_random_valid_transaction(),
+    ]);
+
+    let snapshot_path = env::temp_dir()
+        .join(generate_random_string())
+        .join("vrrbdb.snapshot");
+
+    db.export_snapshot(&snapshot_path).unwrap();
+
+    let imported_config = mock_config();
+    let imported = VrrbDb::import_snapshot(&snapshot_path, &imported_config).unwrap();
+
+    let state_entries = db.state_store_factory().handle().entries();
+    let transaction_entries = db.transaction_store_factory().handle().entries();
+
+    assert_eq!(state_entries.len(), 2);
+    assert_eq!(transaction_entries.len(), 3);
+    assert_eq!(
+        imported.state_store_factory().handle().entries(),
+        state_entries
+    );
+    assert_eq!(
+        imported.transaction_store_factory().handle().entries(),
+        transaction_entries
+    );
+
+    let events = event_store.get_events(0, 10).unwrap();
+    let imported_events = EventStore::new(&imported_config.event_store_dir())
+        .unwrap()
+        .get_events(0, 10)
+        .unwrap();
+
+    assert_eq!(events.len(), 2);
+    assert_eq!(imported_events, events);
+}
+
+#[test]
+fn snapshots_with_unsupported_versions_are_rejected() {
+    let snapshot = VrrbDbSnapshot {
+        version: u32::MAX,
+        accounts: vec![],
+        transactions: vec![],
+        events: vec![],
+    };
+
+    let snapshot_path = env::temp_dir().join(generate_random_string());
+
+    std::fs::write(&snapshot_path, serde_json::to_vec(&snapshot).unwrap()).unwrap();
+
+    let err = VrrbDb::import_snapshot(&snapshot_path, &mock_config()).unwrap_err();
+
+    assert!(matches!(err, StorageError::Other(_)));
+}
@@ -1,8 +1,13 @@
-use std::{collections::HashMap, env, fs};
+use std::{
+    collections::{BTreeMap, HashMap},
+    env,
+    fs,
+};
 
 use primitives::Address;
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
-use vrrb_core::{account::Account, keypair::Keypair};
+use storage_utils::StorageError;
+use vrrb_core::{account::Account, key_rotation::KeyRotation, keypair::Keypair};
 use vrrbdb::{VrrbDb, VrrbDbConfig};
 
 mod common;
@@ -20,7 +25,8 @@ fn accounts_can_be_added() {
         state_store_path: None,
         transaction_store_path: None,
         event_store_path: None,
-    });
+    })
+    .unwrap();
 
     let (_, addr1) = generate_random_address();
     let (_, addr2) = generate_random_address();
@@ -38,6 +44,8 @@ fn accounts_can_be_added() {
             storage: None,
             code: None,
             pubkey: vec![],
+            previous_pubkey: None,
+            rotated_at: None,
             digests: HashMap::new(),
             created_at: 0,
             updated_at: None,
@@ -55,6 +63,8 @@ fn accounts_can_be_added() {
             storage: None,
             code: None,
             pubkey: vec![],
+            previous_pubkey: None,
+            rotated_at: None,
             digests: HashMap::new(),
             created_at: 0,
             updated_at: None,
@@ -77,6 +87,8 @@ fn accounts_can_be_added() {
                 storage: None,
                 code: None,
                 pubkey: vec![],
+                previous_pubkey: None,
+                rotated_at: None,
                 digests: HashMap::new(),
                 created_at: 0,
                 updated_at: None,
@@ -92,6 +104,8 @@ fn accounts_can_be_added() {
                 storage: None,
                 code: None,
                 pubkey: vec![],
+                previous_pubkey: None,
+                rotated_at: None,
                 digests: HashMap::new(),
                 created_at: 0,
                 updated_at: None,
@@ -107,6 +121,8 @@ fn accounts_can_be_added() {
                 storage: None,
                 code: None,
                 pubkey: vec![],
+                previous_pubkey: None,
+                rotated_at: None,
                 digests: HashMap::new(),
                 created_at: 0,
                 updated_at: None,
@@ -118,3 +134,233 @@ fn accounts_can_be_added() {
 
     assert_eq!(entries.len(), 5);
 }
+
+#[test]
+fn extending_accounts_reports_duplicates_per_entry() {
+    let temp_dir_path = env::temp_dir();
+    let state_backup_path = temp_dir_path.join(format!("{}", generate_random_string()));
+
+    let mut db = VrrbDb::new(VrrbDbConfig {
+        path: state_backup_path,
+        state_store_path: None,
+        transaction_store_path: None,
+        event_store_path: None,
+    })
+    .unwrap();
+
+    let (_, addr1) = generate_random_address();
+    let (_, addr2) = generate_random_address();
+    let (_, addr3) = generate_random_address();
+
+    db.insert_account(addr1.clone(), Account::default())
+        .unwrap();
+
+    let outcomes = db.extend_accounts(vec![
+        (addr1.clone(), Account::default()),
+        (addr2.clone(), Account::default()),
+        (addr2.clone(), Account::default()),
+        (addr3.clone(), Account::default()),
+    ]);
+
+    let duplicates = outcomes
+        .into_iter()
+        .map(|(address, outcome)| (address, matches!(outcome, Err(StorageError::RecordExists))))
+        .collect::<Vec<(Address, bool)>>();
+
+    assert_eq!(
+        duplicates,
+        vec![
+            (addr1, true),
+            (addr2.clone(), false),
+            (addr2, true),
+            (addr3, false)
+        ]
+    );
+
+    let entries = db.state_store_factory().handle().entries();
+
+    assert_eq!(entries.len(), 3);
+}
+
+fn mock_contract_account() -> Account {
+    let storage = BTreeMap::from([
+        (String::from("owner"), String::from("aaa1")),
+        (String::from("total_supply"), String::from("1000000")),
+    ]);
+
+    Account {
+        storage: Some(serde_json::to_string(&storage).unwrap()),
+        code: Some(String::from("0061736d0100000001070160027f7f017f")),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn account_storage_and_code_are_persisted() {
+    let temp_dir_path = env::temp_dir();
+    let state_backup_path = temp_dir_path.join(format!("{}", generate_random_string()));
+
+    let mut db = VrrbDb::new(VrrbDbConfig {
+        path: state_backup_path,
+        state_store_path: None,
+        transaction_store_path: None,
+        event_store_path: None,
+    })
+    .unwrap();
+
+    let (_, addr) = generate_random_address();
+    let account = mock_contract_account();
+
+    db.insert_account(addr.clone(), account.clone()).unwrap();
+
+    let stored_account = db.read_handle().get_account(&addr).unwrap();
+
+    assert_eq!(stored_account.storage, account.storage);
+    assert_eq!(stored_account.code, account.code);
+    assert_eq!(
+        db.state_store_factory().handle().entries().get(&addr),
+        Some(&account)
+    );
+
+    let storage: BTreeMap<String, String> =
+        serde_json::from_str(stored_account.storage.as_ref().unwrap()).unwrap();
+
+    assert_eq!(storage.get("total_supply").unwrap(), "1000000");
+}
+
+#[test]
+fn account_updates_replace_storage_and_code() {
+    let temp_dir_path = env::temp_dir();
+    let state_backup_path = temp_dir_path.join(format!("{}", generate_random_string()));
+
+    let mut db = VrrbDb::new(VrrbDbConfig {
+        path: state_backup_path,
+        state_store_path: None,
+        transaction_store_path: None,
+        event_store_path: None,
+    })
+    .unwrap();
+
+    let (_, addr) = generate_random_address();
+
+    db.insert_account(addr.clone(), Account::default()).unwrap();
+
+    let account = mock_contract_account();
+
+    db.update_account(addr.clone(), account.clone()).unwrap();
+
+    let stored_account = db.read_handle().get_account(&addr).unwrap();
+
+    assert_eq!(stored_account.nonce, 1);
+    assert_eq!(stored_account.storage, account.storage);
+    assert_eq!(stored_account.code, account.code);
+}
+
+#[test]
+fn accounts_can_be_paginated() {
+    let temp_dir_path = env::temp_dir();
+    let state_backup_path = temp_dir_path.join(format!("{}", generate_random_string()));
+
+    let mut db = VrrbDb::new(VrrbDbConfig {
+        path: state_backup_path,
+        state_store_path: None,
+        transaction_store_path: None,
+        event_store_path: None,
+    })
+    .unwrap();
+
+    let mut addresses = (0..5)
+        .map(|credits| {
+            let (_, addr) = generate_random_address();
+
+            db.insert_account(
+                addr.clone(),
+                Account {
+                    credits,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            addr
+        })
+        .collect::<Vec<Address>>();
+
+    addresses.sort();
+
+    let handle = db.state_store_factory().handle();
+
+    let first_page = handle.entries_paged(0, 2);
+
+    // NOTE: pages keep coming from the addresses the first one was read from
+    let (_, addr) = generate_random_address();
+    db.insert_account(addr, Account::default()).unwrap();
+
+    let pages = std::iter::once(first_page)
+        .chain((1..4).map(|page| handle.entries_paged(page * 2, 2)))
+        .collect::<Vec<(Vec<(Address, Account)>, usize)>>();
+
+    let page_sizes = pages
+        .iter()
+        .map(|(page, total)| (page.len(), *total))
+        .collect::<Vec<(usize, usize)>>();
+
+    assert_eq!(page_sizes, vec![(2, 5), (2, 5), (1, 5), (0, 5)]);
+
+    let paged_addresses = pages
+        .into_iter()
+        .flat_map(|(page, _)| page.into_iter().map(|(addr, _)| addr))
+        .collect::<Vec<Address>>();
+
+    assert_eq!(paged_addresses, addresses);
+}
+
+#[test]
+fn account_keys_can_be_rotated_by_their_owner() {
+    let mut db = VrrbDb::new(VrrbDbConfig {
+        path: env::temp_dir().join(generate_random_string()),
+        state_store_path: None,
+        transaction_store_path: None,
+        event_store_path: None,
+    })
+    .unwrap();
+
+    let (secret_key, address) = generate_random_address();
+    let (new_secret_key, new_address) = generate_random_address();
+
+    db.insert_account(address.clone(), Account::new(address.public_key()))
+        .unwrap();
+
+    let rotation = KeyRotation::new(address.clone(), new_address.public_key(), 0, &secret_key);
+
+    db.rotate_account_key(rotation.clone()).unwrap();
+
+    let account = db.read_handle().get_account(&address).unwrap();
+
+    assert_eq!(
+        account.pubkey,
+        new_address.public_key().serialize().to_vec()
+    );
+    assert_eq!(account.nonce, 1);
+    assert_eq!(
+        account.previous_pubkey,
+        Some(address.public_key().serialize().to_vec())
+    );
+    assert!(account.rotated_at.is_some());
+
+    // NOTE: the rotation was bound to the nonce it was applied at
+    assert!(db.rotate_account_key(rotation).is_err());
+
+    // NOTE: the previous key can't rotate the account anymore
+    let (_, other_address) = generate_random_address();
+    let stale = KeyRotation::new(address.clone(), other_address.public_key(), 1, &secret_key);
+    assert!(db.rotate_account_key(stale).is_err());
+
+    let rotation = KeyRotation::new(
+        address.clone(),
+        other_address.public_key(),
+        1,
+        &new_secret_key,
+    );
+    assert!(db.rotate_account_key(rotation).is_ok());
+}
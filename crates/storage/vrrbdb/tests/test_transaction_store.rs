@@ -6,6 +6,7 @@ use vrrb_core::{
     account::Account,
     keypair::Keypair,
     txn::{NewTxnArgs, Txn},
+    txn_receipts::TxnReceipt,
 };
 use vrrbdb::{VrrbDb, VrrbDbConfig};
 
@@ -28,7 +29,8 @@ fn transactions_can_be_added() {
         state_store_path: None,
         transaction_store_path: None,
         event_store_path: None,
-    });
+    })
+    .unwrap();
 
     let txn1 = generate_random_valid_transaction();
     let txn2 = generate_random_valid_transaction();
@@ -50,3 +52,73 @@ fn transactions_can_be_added() {
 
     assert_eq!(entries.len(), 5);
 }
+
+#[test]
+fn transactions_can_be_queried_by_address() {
+    let temp_dir_path = env::temp_dir();
+    let state_backup_path = temp_dir_path.join(format!("{}", generate_random_string()));
+
+    let mut db = VrrbDb::new(VrrbDbConfig {
+        path: state_backup_path,
+        state_store_path: None,
+        transaction_store_path: None,
+        event_store_path: None,
+    })
+    .unwrap();
+
+    let (alice_sk, alice) = generate_random_address();
+    let (bob_sk, bob) = generate_random_address();
+    let (_, carol) = generate_random_address();
+
+    let txn1 = generate_random_transaction(alice_sk, alice.clone(), bob.clone());
+    let txn2 = generate_random_transaction(bob_sk, bob.clone(), alice.clone());
+    let txn3 = generate_random_transaction(alice_sk, alice.clone(), carol.clone());
+
+    db.insert_transaction(txn1.clone()).unwrap();
+    db.extend_transactions(vec![txn2.clone(), txn3.clone()]);
+
+    // NOTE: re-inserting a transaction must not index it twice
+    db.insert_transaction(txn1.clone()).unwrap();
+
+    let handle = db.transaction_store_factory().handle();
+
+    assert_eq!(
+        handle.get_transactions_by_address(&alice),
+        vec![txn1.clone(), txn2.clone(), txn3.clone()]
+    );
+    assert_eq!(
+        handle.get_transactions_by_address(&bob),
+        vec![txn1.clone(), txn2]
+    );
+    assert_eq!(handle.get_transactions_by_address(&carol), vec![txn3]);
+
+    let (_, stranger) = generate_random_address();
+
+    assert!(handle.get_transactions_by_address(&stranger).is_empty());
+    assert_eq!(handle.get_transaction(&txn1.digest()), Some(txn1));
+}
+
+#[test]
+fn txn_receipts_can_be_stored_and_replaced() {
+    let mut db = VrrbDb::new(VrrbDbConfig {
+        path: env::temp_dir().join(generate_random_string()),
+        state_store_path: None,
+        transaction_store_path: None,
+        event_store_path: None,
+    })
+    .unwrap();
+
+    let digest = generate_random_valid_transaction().digest();
+    let read_handle = db.read_handle();
+
+    assert_eq!(read_handle.get_txn_receipt(&digest), None);
+
+    db.insert_txn_receipt(TxnReceipt::pending(digest.clone()));
+    db.insert_txn_receipt(TxnReceipt::included(digest.clone(), Some(4)));
+
+    assert_eq!(
+        read_handle.get_txn_receipt(&digest),
+        Some(TxnReceipt::included(digest, Some(4)))
+    );
+    assert!(read_handle.transaction_store_values().is_empty());
+}
@@ -1,7 +1,13 @@
+use std::{fmt, str::FromStr, sync::Mutex};
+
 use primitives::Environment;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use tracing::{Dispatch, Level};
 use tracing_subscriber::{
+    filter::LevelFilter,
     fmt::MakeWriter,
+    reload,
     util::{SubscriberInitExt, TryInitError},
 };
 
@@ -19,36 +25,208 @@ pub enum TelemetryError {
 
 type Result<T> = std::result::Result<T, TelemetryError>;
 
+/// Most verbose level of the events that get logged
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    #[default]
+    Info,
+    Warn,
+    Error,
+}
+
+impl From<LogLevel> for Level {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Trace => Level::TRACE,
+            LogLevel::Debug => Level::DEBUG,
+            LogLevel::Info => Level::INFO,
+            LogLevel::Warn => Level::WARN,
+            LogLevel::Error => Level::ERROR,
+        }
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = TelemetryError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "trace" => Ok(LogLevel::Trace),
+            "debug" => Ok(LogLevel::Debug),
+            "info" => Ok(LogLevel::Info),
+            "warn" => Ok(LogLevel::Warn),
+            "error" => Ok(LogLevel::Error),
+            _ => Err(TelemetryError::Other(format!(
+                "unknown log level {s}, expected one of trace, debug, info, warn or error"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LogLevel::Trace => "trace",
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+/// How log lines are laid out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human readable lines, meant for terminals
+    Pretty,
+    /// One JSON object per line, meant for log aggregators
+    #[default]
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = TelemetryError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "pretty" => Ok(LogFormat::Pretty),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(TelemetryError::Other(format!(
+                "unknown log format {s}, expected pretty or json"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogFormat::Pretty => write!(f, "pretty"),
+            LogFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// Changes the level a subscriber logs events at while it's in use
+type LevelReloader = Box<dyn Fn(LogLevel) -> Result<()> + Send + Sync>;
+
+/// Changes the level of the global subscriber, set by `init_with`
+static GLOBAL_LEVEL_RELOADER: Mutex<Option<LevelReloader>> = Mutex::new(None);
+
 // TODO: figure out the proper generic sig to export a telemetry builder instead
 #[derive(Debug)]
 pub struct TelemetrySubscriber {}
 
 impl TelemetrySubscriber {
+    /// Sets up the global subscriber, logging JSON lines of events at info
+    /// level and above to `out`
     pub fn init<W>(out: W) -> Result<()>
+    where
+        W: for<'s> MakeWriter<'s> + 'static + Sync + Send,
+    {
+        Self::init_with(out, LogLevel::default(), LogFormat::default())
+    }
+
+    /// Sets up the global subscriber, logging events at `level` and above to
+    /// `out` in the given `format`
+    pub fn init_with<W>(out: W, level: LogLevel, format: LogFormat) -> Result<()>
+    where
+        W: for<'s> MakeWriter<'s> + 'static + Sync + Send,
+    {
+        let (subscriber, reload_level) = Self::build_reloadable(out, level, format);
+
+        subscriber.try_init()?;
+
+        if let Ok(mut reloader) = GLOBAL_LEVEL_RELOADER.lock() {
+            *reloader = Some(reload_level);
+        }
+
+        Ok(())
+    }
+
+    /// Changes the level the global subscriber logs events at, e.g. when a
+    /// running node reloads its config. Fails unless the global subscriber
+    /// was set up through `init_with`.
+    pub fn set_level(level: LogLevel) -> Result<()> {
+        let reloader = GLOBAL_LEVEL_RELOADER
+            .lock()
+            .map_err(|err| TelemetryError::Other(err.to_string()))?;
+
+        match reloader.as_ref() {
+            Some(reload_level) => reload_level(level),
+            None => Err(TelemetryError::Other(String::from(
+                "the global subscriber can't change its level",
+            ))),
+        }
+    }
+
+    /// Builds a subscriber without installing it, e.g. to scope it to a
+    /// thread with `tracing::subscriber::set_default`
+    pub fn build<W>(out: W, level: LogLevel, format: LogFormat) -> Dispatch
+    where
+        W: for<'s> MakeWriter<'s> + 'static + Sync + Send,
+    {
+        Self::build_reloadable(out, level, format).0
+    }
+
+    /// Builds a subscriber along with a function that changes the level it
+    /// logs events at
+    fn build_reloadable<W>(out: W, level: LogLevel, format: LogFormat) -> (Dispatch, LevelReloader)
     where
         W: for<'s> MakeWriter<'s> + 'static + Sync + Send,
     {
         let environ = primitives::get_vrrb_environment();
         let is_local_env = matches!(environ, Environment::Local);
 
-        let sub = tracing_subscriber::fmt()
+        let builder = tracing_subscriber::fmt()
             .with_writer(out)
+            .with_max_level(Level::from(level))
             .with_file(is_local_env)
-            .with_line_number(is_local_env)
-            .json()
-            .with_current_span(false)
-            .flatten_event(true)
-            .with_span_list(false)
-            .finish();
+            .with_line_number(is_local_env);
 
-        sub.try_init()?;
+        match format {
+            LogFormat::Pretty => {
+                let builder = builder.pretty().with_filter_reloading();
+                let reload_level = Self::level_reloader(builder.reload_handle());
 
-        Ok(())
+                (Dispatch::new(builder.finish()), reload_level)
+            },
+            LogFormat::Json => {
+                let builder = builder
+                    .json()
+                    .with_current_span(false)
+                    .flatten_event(true)
+                    .with_span_list(false)
+                    .with_filter_reloading();
+                let reload_level = Self::level_reloader(builder.reload_handle());
+
+                (Dispatch::new(builder.finish()), reload_level)
+            },
+        }
+    }
+
+    fn level_reloader<S: 'static>(handle: reload::Handle<LevelFilter, S>) -> LevelReloader {
+        Box::new(move |level| {
+            handle
+                .reload(LevelFilter::from_level(level.into()))
+                .map_err(|err| TelemetryError::Other(err.to_string()))
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::{
+        io::Write,
+        sync::{Arc, Mutex},
+    };
 
     use tracing_subscriber::fmt::TestWriter;
 
@@ -62,4 +240,86 @@ mod tests {
 
         tracing::info!("hello world 2");
     }
+
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for CapturedLogs {
+        type Writer = Self;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn json_format_produces_parseable_lines() {
+        let logs = CapturedLogs::default();
+        let subscriber = TelemetrySubscriber::build(logs.clone(), LogLevel::Info, LogFormat::Json);
+
+        tracing::dispatcher::with_default(&subscriber, || {
+            tracing::info!(peer_count = 3, "node started");
+            tracing::warn!("mempool is \"almost\" full");
+            tracing::debug!("filtered out by the log level");
+        });
+
+        let contents = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        let lines = contents
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["level"], "INFO");
+        assert_eq!(lines[0]["message"], "node started");
+        assert_eq!(lines[0]["peer_count"], 3);
+        assert_eq!(lines[1]["message"], "mempool is \"almost\" full");
+    }
+
+    #[test]
+    fn subscribers_change_their_level_while_in_use() {
+        let logs = CapturedLogs::default();
+        let (subscriber, reload_level) =
+            TelemetrySubscriber::build_reloadable(logs.clone(), LogLevel::Info, LogFormat::Json);
+
+        tracing::dispatcher::with_default(&subscriber, || {
+            tracing::debug!("filtered out by the initial log level");
+
+            reload_level(LogLevel::Debug).unwrap();
+
+            tracing::debug!("logged once the level was lowered");
+        });
+
+        let contents = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        let lines = contents
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0]["message"], "logged once the level was lowered");
+    }
+
+    #[test]
+    fn log_levels_and_formats_parse_from_their_names() {
+        assert_eq!("trace".parse::<LogLevel>().unwrap(), LogLevel::Trace);
+        assert_eq!("WARN".parse::<LogLevel>().unwrap(), LogLevel::Warn);
+        assert_eq!(LogLevel::Error.to_string(), "error");
+        assert!("loud".parse::<LogLevel>().is_err());
+
+        assert_eq!("pretty".parse::<LogFormat>().unwrap(), LogFormat::Pretty);
+        assert_eq!(LogFormat::Json.to_string(), "json");
+        assert!("xml".parse::<LogFormat>().is_err());
+    }
 }
@@ -40,7 +40,7 @@ mod tests {
         Txn::new(NewTxnArgs {
             timestamp: 0,
             sender_address: random_string(rng),
-            sender_public_key: KeyPair::random().miner_kp.1,
+            sender_public_key: *KeyPair::random().txn_keypair().public_key(),
             receiver_address: random_string(rng),
             token: None,
             amount: 0,
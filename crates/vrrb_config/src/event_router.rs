@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+pub use vrrb_core::event_router::BackpressurePolicy;
+
+/// Capacities of each of the event router's topic channels, along with how
+/// each topic handles events published once its buffer is full. By default
+/// its oldest pending events are dropped in favor of new ones, except on the
+/// storage topic: it carries accepted transactions into the mempool, so the
+/// router waits for it to drain instead of losing any.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct EventRouterConfig {
+    pub control_buffer: usize,
+    pub state_buffer: usize,
+    pub network_buffer: usize,
+    pub consensus_buffer: usize,
+    pub storage_buffer: usize,
+    pub control_policy: BackpressurePolicy,
+    pub state_policy: BackpressurePolicy,
+    pub network_policy: BackpressurePolicy,
+    pub consensus_policy: BackpressurePolicy,
+    pub storage_policy: BackpressurePolicy,
+}
+
+impl Default for EventRouterConfig {
+    fn default() -> Self {
+        Self {
+            control_buffer: 1,
+            state_buffer: 1,
+            network_buffer: 100,
+            consensus_buffer: 100,
+            storage_buffer: 100,
+            control_policy: BackpressurePolicy::default(),
+            state_policy: BackpressurePolicy::default(),
+            network_policy: BackpressurePolicy::default(),
+            consensus_policy: BackpressurePolicy::default(),
+            storage_policy: BackpressurePolicy::Block,
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use vrrb_core::{
+    fee::{FeeModel, FlatFee, PerByteFee},
+    txn::TxAmount,
+};
+
+/// How the minimum fee of a transaction is computed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeModelKind {
+    /// Every transaction pays `base_fee`
+    Flat,
+    /// Transactions pay `base_fee` plus `fee_per_byte` for each byte of
+    /// their encoding
+    #[default]
+    PerByte,
+}
+
+/// Minimum fees transactions have to offer to be accepted by the node. Both
+/// rates default to zero, so transactions are free unless configured
+/// otherwise.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct FeeConfig {
+    pub model: FeeModelKind,
+
+    /// Fee charged for every transaction
+    pub base_fee: TxAmount,
+
+    /// Fee charged for every byte of a transaction, on top of `base_fee`.
+    /// Ignored by the flat fee model.
+    pub fee_per_byte: TxAmount,
+}
+
+impl FeeConfig {
+    /// Returns the fee model described by this config
+    pub fn fee_model(&self) -> Arc<dyn FeeModel> {
+        match self.model {
+            FeeModelKind::Flat => Arc::new(FlatFee {
+                base_fee: self.base_fee,
+            }),
+            FeeModelKind::PerByte => Arc::new(PerByteFee {
+                base_fee: self.base_fee,
+                fee_per_byte: self.fee_per_byte,
+            }),
+        }
+    }
+}
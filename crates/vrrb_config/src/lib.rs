@@ -1,13 +1,27 @@
 mod bootstrap;
+mod event_router;
+mod fee;
+mod mining;
 mod node_config;
+pub mod result;
+mod runtime;
+mod supervision;
+mod template;
 
+pub use event_router::*;
+pub use fee::*;
+pub use mining::*;
 pub use node_config::*;
+pub use result::*;
+pub use runtime::*;
+pub use supervision::*;
 
 #[cfg(test)]
 mod tests {
     use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 
     use primitives::NodeType;
+    use telemetry::LogLevel;
     use vrrb_core::keypair::Keypair;
 
     use super::*;
@@ -31,9 +45,100 @@ mod tests {
             .http_api_shutdown_timeout(None)
             .node_type(NodeType::Full)
             .bootstrap_node_addresses(vec![addr])
-            .keypair(keypair)
+            .keypair(Some(keypair))
             .bootstrap_config(None)
             .build()
             .unwrap();
     }
+
+    fn write_temp_config(extension: &str, contents: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(vrrb_core::helpers::generate_random_string());
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join(format!("node.{extension}"));
+        std::fs::write(&path, contents).unwrap();
+
+        path
+    }
+
+    #[test]
+    fn can_be_loaded_from_a_toml_file() {
+        let path = write_temp_config(
+            "toml",
+            r#"
+            id = "node-from-toml"
+            node_type = "bootstrap"
+            jsonrpc_server_address = "127.0.0.1:9293"
+            "#,
+        );
+
+        let config = NodeConfig::from_file(&path).unwrap();
+
+        assert_eq!(config.id, "node-from-toml");
+        assert_eq!(config.node_type, NodeType::Bootstrap);
+        assert_eq!(
+            config.jsonrpc_server_address,
+            "127.0.0.1:9293".parse().unwrap()
+        );
+
+        // NOTE: missing fields take their default values
+        assert_eq!(config.http_api_title, NodeConfig::default().http_api_title);
+    }
+
+    #[test]
+    fn can_be_loaded_from_a_yaml_file() {
+        let path = write_temp_config(
+            "yaml",
+            "id: node-from-yaml\nidx: 7\ndisable_networking: true\n",
+        );
+
+        let config = NodeConfig::from_file(&path).unwrap();
+
+        assert_eq!(config.id, "node-from-yaml");
+        assert_eq!(config.idx, 7);
+        assert!(config.disable_networking);
+    }
+
+    #[test]
+    fn invalid_values_report_the_offending_key() {
+        let path = write_temp_config("toml", r#"http_api_address = "not an address""#);
+
+        let err = NodeConfig::from_file(&path).unwrap_err();
+
+        assert!(err.to_string().contains("http_api_address"));
+    }
+
+    #[test]
+    fn only_non_reloadable_changes_require_a_restart() {
+        let current = NodeConfig {
+            jsonrpc_server_address: "127.0.0.1:9293".parse().unwrap(),
+            ..Default::default()
+        };
+
+        let mut reloaded = current.clone();
+        reloaded.mempool_max_size = Some(10);
+        reloaded.log_level = LogLevel::Debug;
+        reloaded.jsonrpc_server_address = "127.0.0.1:0".parse().unwrap();
+
+        assert!(current.restart_required_changes(&reloaded).is_empty());
+
+        reloaded.node_type = NodeType::Bootstrap;
+        reloaded.udp_gossip_address = "127.0.0.1:9000".parse().unwrap();
+        reloaded.http_api_title = String::from("reloaded");
+
+        assert_eq!(
+            current.restart_required_changes(&reloaded),
+            vec!["udp_gossip_address", "node_type", "http_api_title"]
+        );
+    }
+
+    #[test]
+    fn unknown_file_formats_are_rejected() {
+        let path = write_temp_config("ini", "id = abc");
+
+        assert!(matches!(
+            NodeConfig::from_file(&path),
+            Err(Error::UnsupportedFormat(_))
+        ));
+    }
 }
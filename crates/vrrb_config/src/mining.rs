@@ -0,0 +1,28 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Pacing of the node's block production attempts
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MiningConfig {
+    /// Minimum spacing between two consecutive block production attempts
+    pub target_block_interval: Duration,
+
+    /// Difficulty blocks are mined at
+    pub difficulty: u64,
+
+    /// Upper bound on how long the miner sleeps between checks of an empty
+    /// mempool. The wait doubles on every empty check until it reaches it.
+    pub max_idle_backoff: Duration,
+}
+
+impl Default for MiningConfig {
+    fn default() -> Self {
+        Self {
+            target_block_interval: Duration::from_secs(5),
+            difficulty: 1,
+            max_idle_backoff: Duration::from_secs(30),
+        }
+    }
+}
@@ -1,18 +1,64 @@
 use std::{
     net::{IpAddr, Ipv4Addr, SocketAddr},
-    path::PathBuf,
+    path::{Path, PathBuf},
     time::Duration,
 };
 
+use config::{Config, File, FileFormat};
 use derive_builder::Builder;
-use primitives::{NodeId, NodeIdx, NodeType, DEFAULT_VRRB_DATA_DIR_PATH};
-use serde::Deserialize;
+use network::config::BroadcastEngineConfig;
+use primitives::{
+    ChainId,
+    NodeId,
+    NodeIdx,
+    NodeType,
+    DEFAULT_CHAIN_ID,
+    DEFAULT_VRRB_DATA_DIR_PATH,
+};
+use serde::{Deserialize, Serialize};
+use telemetry::{LogFormat, LogLevel};
 use uuid::Uuid;
 use vrrb_core::keypair::Keypair;
+pub use vrrb_core::txn::DEFAULT_MAX_TXN_SIZE_BYTES;
+
+use crate::{
+    bootstrap::BootstrapConfig,
+    Error,
+    EventRouterConfig,
+    FeeConfig,
+    MiningConfig,
+    Result,
+    RuntimeConfig,
+    SupervisionConfig,
+};
+
+/// Default amount of time the node waits for each of its modules to stop
+/// before aborting them
+pub const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default amount of time the node waits for its modules to start before
+/// giving up on starting
+pub const DEFAULT_READINESS_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default amount of time a running node waits to be connected to
+/// `min_bootstrap_peers` peers
+pub const DEFAULT_BOOTSTRAP_QUORUM_TIMEOUT: Duration = Duration::from_secs(60);
 
-use crate::bootstrap::BootstrapConfig;
+/// Default upper bound on the amount of transactions kept in the mempool
+pub const DEFAULT_MEMPOOL_MAX_SIZE: usize = 100_000;
 
-#[derive(Builder, Debug, Clone, Deserialize)]
+/// Default amount of time transactions are kept in the mempool before they
+/// expire
+pub const DEFAULT_MEMPOOL_TXN_TTL: Duration = Duration::from_secs(3 * 60 * 60);
+
+/// Default amount of transactions allowed to wait for validation at once
+pub const DEFAULT_VALIDATION_QUEUE_DEPTH: usize = 10_000;
+
+/// Default amount of worker threads transactions are validated on
+pub const DEFAULT_VALIDATION_PARALLELISM: usize = 4;
+
+#[derive(Builder, Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct NodeConfig {
     /// UUID that identifies each node
     pub id: NodeId,
@@ -62,16 +108,318 @@ pub struct NodeConfig {
     #[builder(default = "false")]
     pub preload_mock_state: bool,
 
+    /// JSON file of the accounts the chain starts out with. It's only applied
+    /// to an empty db, nodes restarting on a db initialized from it carry on
+    /// with their state.
+    #[builder(default)]
+    pub genesis_path: Option<PathBuf>,
+
     /// Bootstrap configuration
     pub bootstrap_config: Option<BootstrapConfig>,
 
-    pub keypair: Keypair,
+    /// Node keypair. Never written to or read from serialized configs, it is
+    /// always loaded from the node's keypair file instead, so it is left
+    /// unset until the keypair is resolved
+    #[serde(skip)]
+    #[builder(default)]
+    pub keypair: Option<Keypair>,
 
+    /// Runs the node as a local-only instance. The gossip network, broadcast
+    /// controller and state sync modules aren't started, while the state,
+    /// mempool and JSON-RPC server still are. Transactions submitted over
+    /// JSON-RPC are turned away since they couldn't be shared with the
+    /// network, unless `accept_txns_without_networking` is set.
     #[builder(default = "false")]
     pub disable_networking: bool,
+
+    /// Lets a node running with `disable_networking` take transactions
+    /// submitted over JSON-RPC into its local mempool, where they're
+    /// validated as usual but never gossiped. Has no effect while networking
+    /// is enabled.
+    #[builder(default = "false")]
+    pub accept_txns_without_networking: bool,
+
+    /// Upper bound on the time spent waiting for each runtime module to stop
+    /// once a shutdown has been requested
+    #[builder(default = "DEFAULT_SHUTDOWN_TIMEOUT")]
+    pub shutdown_timeout: Duration,
+
+    /// Upper bound on the time spent waiting for every runtime module to be
+    /// running on startup. The node neither announces itself to nor accepts
+    /// peers until then, and fails to start if the timeout elapses first.
+    #[builder(default = "DEFAULT_READINESS_TIMEOUT")]
+    pub readiness_timeout: Duration,
+
+    /// Amount of peers, e.g. the bootstrap nodes and the peers discovered
+    /// through them, the node has to be connected to before it mines and
+    /// validates transactions. Running nodes keep syncing until then. No
+    /// quorum is required when set to 0 or with networking disabled.
+    #[builder(default)]
+    pub min_bootstrap_peers: usize,
+
+    /// Upper bound on the time a running node waits to be connected to
+    /// `min_bootstrap_peers` peers
+    #[builder(default = "DEFAULT_BOOTSTRAP_QUORUM_TIMEOUT")]
+    pub bootstrap_quorum_timeout: Duration,
+
+    /// Shuts the node down should it not reach its bootstrap quorum in time.
+    /// It otherwise logs a warning and starts mining and validating anyway.
+    #[builder(default)]
+    pub exit_without_bootstrap_quorum: bool,
+
+    /// File the mempool's pending transactions are persisted to on shutdown
+    /// and restored from on startup. Persistence is disabled when unset.
+    #[builder(default)]
+    pub mempool_snapshot_path: Option<PathBuf>,
+
+    /// Directory significant events, e.g. confirmed blocks or peers joining,
+    /// are logged to for auditing. Logging is disabled when unset.
+    #[builder(default)]
+    pub event_store_path: Option<PathBuf>,
+
+    /// Buffer sizes of the topics within the node's event router
+    #[builder(default)]
+    pub event_router_config: EventRouterConfig,
+
+    /// Whether each runtime module is restarted or shuts the node down
+    /// should it fail while the node is running
+    #[builder(default)]
+    pub supervision_config: SupervisionConfig,
+
+    /// Worker and blocking thread counts of the tokio runtime the node runs
+    /// on, applied by the CLI when it builds that runtime
+    #[builder(default)]
+    pub runtime_config: RuntimeConfig,
+
+    /// Upper bound on the amount of pending transactions kept in the mempool.
+    /// Once reached, the ones paying the lowest fees are evicted first.
+    #[builder(default = "Some(DEFAULT_MEMPOOL_MAX_SIZE)")]
+    pub mempool_max_size: Option<usize>,
+
+    /// Time transactions are kept in the mempool before they expire. Expired
+    /// transactions are dropped by a periodic sweep, at the latest
+    /// `MEMPOOL_SWEEP_INTERVAL` after they expired.
+    #[builder(default = "DEFAULT_MEMPOOL_TXN_TTL")]
+    pub mempool_txn_ttl: Duration,
+
+    /// Block production interval and difficulty used by the mining module
+    #[builder(default)]
+    pub mining_config: MiningConfig,
+
+    /// Reconnection backoff, RaptorQ reassembly limits and per peer rate
+    /// limits of the node's broadcast engine
+    #[builder(default)]
+    pub broadcast_engine_config: BroadcastEngineConfig,
+
+    /// Minimum fees transactions have to offer to be accepted
+    #[builder(default)]
+    pub fee_config: FeeConfig,
+
+    /// Amount of transactions allowed to wait for validation at once. New
+    /// submissions are turned away as busy while the queue is full.
+    #[builder(default = "DEFAULT_VALIDATION_QUEUE_DEPTH")]
+    pub validation_queue_depth: usize,
+
+    /// Amount of worker threads the validator module spreads transactions
+    /// across. Transactions from the same sender are always validated on the
+    /// same worker, in nonce order.
+    #[builder(default = "DEFAULT_VALIDATION_PARALLELISM")]
+    pub validation_parallelism: usize,
+
+    /// Network this node belongs to, reported to clients so they can tell
+    /// networks apart
+    #[builder(default = "DEFAULT_CHAIN_ID")]
+    pub chain_id: ChainId,
+
+    /// Turns away transactions that don't specify a chain id. They're
+    /// accepted by default so transactions signed before chain ids were
+    /// introduced keep going through.
+    #[builder(default)]
+    pub reject_txns_without_chain_id: bool,
+
+    /// Largest transaction, in bytes once encoded, the node takes in. Larger
+    /// ones are rejected both on submission and by the validator.
+    #[builder(default = "DEFAULT_MAX_TXN_SIZE_BYTES")]
+    pub max_txn_size_bytes: usize,
+
+    /// Most verbose level of the events the node logs
+    #[builder(default)]
+    pub log_level: LogLevel,
+
+    /// Whether logs are written as human readable lines or as JSON objects
+    #[builder(default)]
+    pub log_format: LogFormat,
+
+    /// Token JSON-RPC clients must present to list, add or remove peers.
+    /// Those methods are open to every client when unset.
+    #[builder(default)]
+    pub jsonrpc_admin_token: Option<String>,
 }
 
 impl NodeConfig {
+    /// Loads a node config from a TOML or YAML file, depending on the file's
+    /// extension. Fields missing from the file take their default values.
+    pub fn from_file(path: &Path) -> Result<NodeConfig> {
+        let format = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => FileFormat::Toml,
+            Some("yaml") | Some("yml") => FileFormat::Yaml,
+            _ => return Err(Error::UnsupportedFormat(path.display().to_string())),
+        };
+
+        let invalid_config = |err: config::ConfigError| Error::InvalidConfig {
+            path: path.display().to_string(),
+            reason: err.to_string(),
+        };
+
+        Config::builder()
+            .add_source(File::from(path).format(format))
+            .build()
+            .and_then(|config| config.try_deserialize())
+            .map_err(invalid_config)
+    }
+
+    /// Lists the fields that differ from `other` but can't be changed on a
+    /// running node. Only the mempool's capacity, the log level and the
+    /// shutdown timeout can be reloaded live, the keypair is never reloaded.
+    ///
+    /// A bind address on port 0 in `other` matches whichever port was bound
+    /// on the same IP, so reloading the config a node was started with never
+    /// reports its addresses as changed.
+    pub fn restart_required_changes(&self, other: &NodeConfig) -> Vec<&'static str> {
+        let same_address =
+            |a: SocketAddr, b: SocketAddr| a == b || (b.port() == 0 && a.ip() == b.ip());
+
+        let changes = [
+            ("id", self.id != other.id),
+            ("idx", self.idx != other.idx),
+            ("data_dir", self.data_dir != other.data_dir),
+            ("db_path", self.db_path != other.db_path),
+            (
+                "raptorq_gossip_address",
+                !same_address(self.raptorq_gossip_address, other.raptorq_gossip_address),
+            ),
+            (
+                "udp_gossip_address",
+                !same_address(self.udp_gossip_address, other.udp_gossip_address),
+            ),
+            ("node_type", self.node_type != other.node_type),
+            (
+                "bootstrap_node_addresses",
+                self.bootstrap_node_addresses != other.bootstrap_node_addresses,
+            ),
+            (
+                "http_api_address",
+                !same_address(self.http_api_address, other.http_api_address),
+            ),
+            (
+                "http_api_title",
+                self.http_api_title != other.http_api_title,
+            ),
+            (
+                "http_api_version",
+                self.http_api_version != other.http_api_version,
+            ),
+            (
+                "http_api_shutdown_timeout",
+                self.http_api_shutdown_timeout != other.http_api_shutdown_timeout,
+            ),
+            (
+                "jsonrpc_server_address",
+                !same_address(self.jsonrpc_server_address, other.jsonrpc_server_address),
+            ),
+            (
+                "preload_mock_state",
+                self.preload_mock_state != other.preload_mock_state,
+            ),
+            ("genesis_path", self.genesis_path != other.genesis_path),
+            (
+                "bootstrap_config",
+                self.bootstrap_config != other.bootstrap_config,
+            ),
+            (
+                "disable_networking",
+                self.disable_networking != other.disable_networking,
+            ),
+            (
+                "accept_txns_without_networking",
+                self.accept_txns_without_networking != other.accept_txns_without_networking,
+            ),
+            (
+                "readiness_timeout",
+                self.readiness_timeout != other.readiness_timeout,
+            ),
+            (
+                "min_bootstrap_peers",
+                self.min_bootstrap_peers != other.min_bootstrap_peers,
+            ),
+            (
+                "bootstrap_quorum_timeout",
+                self.bootstrap_quorum_timeout != other.bootstrap_quorum_timeout,
+            ),
+            (
+                "exit_without_bootstrap_quorum",
+                self.exit_without_bootstrap_quorum != other.exit_without_bootstrap_quorum,
+            ),
+            (
+                "mempool_snapshot_path",
+                self.mempool_snapshot_path != other.mempool_snapshot_path,
+            ),
+            (
+                "event_store_path",
+                self.event_store_path != other.event_store_path,
+            ),
+            (
+                "event_router_config",
+                self.event_router_config != other.event_router_config,
+            ),
+            (
+                "supervision_config",
+                self.supervision_config != other.supervision_config,
+            ),
+            (
+                "runtime_config",
+                self.runtime_config != other.runtime_config,
+            ),
+            (
+                "mempool_txn_ttl",
+                self.mempool_txn_ttl != other.mempool_txn_ttl,
+            ),
+            ("mining_config", self.mining_config != other.mining_config),
+            (
+                "broadcast_engine_config",
+                self.broadcast_engine_config != other.broadcast_engine_config,
+            ),
+            ("fee_config", self.fee_config != other.fee_config),
+            (
+                "validation_queue_depth",
+                self.validation_queue_depth != other.validation_queue_depth,
+            ),
+            (
+                "validation_parallelism",
+                self.validation_parallelism != other.validation_parallelism,
+            ),
+            ("chain_id", self.chain_id != other.chain_id),
+            (
+                "reject_txns_without_chain_id",
+                self.reject_txns_without_chain_id != other.reject_txns_without_chain_id,
+            ),
+            (
+                "max_txn_size_bytes",
+                self.max_txn_size_bytes != other.max_txn_size_bytes,
+            ),
+            ("log_format", self.log_format != other.log_format),
+            (
+                "jsonrpc_admin_token",
+                self.jsonrpc_admin_token != other.jsonrpc_admin_token,
+            ),
+        ];
+
+        changes
+            .into_iter()
+            .filter_map(|(field, changed)| changed.then_some(field))
+            .collect()
+    }
+
     pub fn db_path(&self) -> &PathBuf {
         // TODO: refactor to Option and check if present and return configured db path
         // or default path within vrrb's data dir
@@ -104,8 +452,35 @@ impl NodeConfig {
             http_api_shutdown_timeout: self.http_api_shutdown_timeout,
             jsonrpc_server_address: self.jsonrpc_server_address,
             preload_mock_state: self.preload_mock_state,
+            genesis_path: self.genesis_path.clone(),
             bootstrap_config: self.bootstrap_config.clone(),
-            keypair: self.keypair.clone(),
+            keypair: self.keypair.clone().or_else(|| other.keypair.clone()),
+            shutdown_timeout: self.shutdown_timeout,
+            readiness_timeout: self.readiness_timeout,
+            min_bootstrap_peers: self.min_bootstrap_peers,
+            bootstrap_quorum_timeout: self.bootstrap_quorum_timeout,
+            exit_without_bootstrap_quorum: self.exit_without_bootstrap_quorum,
+            mempool_snapshot_path: self.mempool_snapshot_path.clone(),
+            event_store_path: self.event_store_path.clone(),
+            event_router_config: self.event_router_config.clone(),
+            supervision_config: self.supervision_config.clone(),
+            runtime_config: self.runtime_config.clone(),
+            mempool_max_size: self.mempool_max_size,
+            mempool_txn_ttl: self.mempool_txn_ttl,
+            mining_config: self.mining_config.clone(),
+            broadcast_engine_config: self.broadcast_engine_config.clone(),
+            fee_config: self.fee_config.clone(),
+            validation_queue_depth: self.validation_queue_depth,
+            validation_parallelism: self.validation_parallelism,
+            chain_id: self.chain_id,
+            reject_txns_without_chain_id: self.reject_txns_without_chain_id,
+            max_txn_size_bytes: self.max_txn_size_bytes,
+            log_level: self.log_level,
+            log_format: self.log_format,
+            jsonrpc_admin_token: self
+                .jsonrpc_admin_token
+                .clone()
+                .or_else(|| other.jsonrpc_admin_token.clone()),
             ..other
         }
     }
@@ -133,9 +508,36 @@ impl Default for NodeConfig {
             http_api_shutdown_timeout: None,
             jsonrpc_server_address: ipv4_localhost_with_random_port,
             preload_mock_state: false,
+            genesis_path: None,
             bootstrap_config: None,
-            keypair: Keypair::random(),
+            keypair: None,
             disable_networking: false,
+            accept_txns_without_networking: false,
+            shutdown_timeout: DEFAULT_SHUTDOWN_TIMEOUT,
+            readiness_timeout: DEFAULT_READINESS_TIMEOUT,
+            min_bootstrap_peers: 0,
+            bootstrap_quorum_timeout: DEFAULT_BOOTSTRAP_QUORUM_TIMEOUT,
+            exit_without_bootstrap_quorum: false,
+            // NOTE: same as the builder's, nodes started through the CLI keep
+            // their snapshot within their data dir unless configured otherwise
+            mempool_snapshot_path: None,
+            event_store_path: None,
+            event_router_config: EventRouterConfig::default(),
+            supervision_config: SupervisionConfig::default(),
+            runtime_config: RuntimeConfig::default(),
+            mempool_max_size: Some(DEFAULT_MEMPOOL_MAX_SIZE),
+            mempool_txn_ttl: DEFAULT_MEMPOOL_TXN_TTL,
+            mining_config: MiningConfig::default(),
+            broadcast_engine_config: BroadcastEngineConfig::default(),
+            fee_config: FeeConfig::default(),
+            validation_queue_depth: DEFAULT_VALIDATION_QUEUE_DEPTH,
+            validation_parallelism: DEFAULT_VALIDATION_PARALLELISM,
+            chain_id: DEFAULT_CHAIN_ID,
+            reject_txns_without_chain_id: false,
+            max_txn_size_bytes: DEFAULT_MAX_TXN_SIZE_BYTES,
+            log_level: LogLevel::default(),
+            log_format: LogFormat::default(),
+            jsonrpc_admin_token: None,
         }
     }
 }
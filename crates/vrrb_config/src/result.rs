@@ -0,0 +1,15 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("unsupported config file format: {0}")]
+    UnsupportedFormat(String),
+
+    #[error("invalid config file {path}: {reason}")]
+    InvalidConfig { path: String, reason: String },
+
+    #[error("{0}")]
+    Other(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
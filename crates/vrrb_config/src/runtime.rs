@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+
+/// Default upper bound on the threads blocking work is run on, the same as
+/// tokio's own
+pub const DEFAULT_MAX_BLOCKING_THREADS: usize = 512;
+
+/// Sizing of the tokio runtime the node's tasks run on
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RuntimeConfig {
+    /// Threads tasks are scheduled across. Defaults to one per core the
+    /// process may use, as reported by `std::thread::available_parallelism`.
+    pub worker_threads: Option<usize>,
+
+    /// Upper bound on the threads spawned on top of the worker threads to run
+    /// blocking work, such as disk io, on
+    pub max_blocking_threads: usize,
+}
+
+impl RuntimeConfig {
+    /// Returns the amount of worker threads the runtime is built with, at
+    /// least one
+    pub fn worker_threads(&self) -> usize {
+        self.worker_threads
+            .unwrap_or_else(|| {
+                std::thread::available_parallelism()
+                    .map(usize::from)
+                    .unwrap_or(1)
+            })
+            .max(1)
+    }
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        Self {
+            worker_threads: None,
+            max_blocking_threads: DEFAULT_MAX_BLOCKING_THREADS,
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// What the node does once one of its runtime modules stops while the node
+/// is running, be it because it panicked, failed or returned early
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModuleFailurePolicy {
+    /// Starts the module over, up to `SupervisionConfig::max_restarts` times.
+    /// Modules that can't be restarted shut the node down instead.
+    Restart,
+    /// Shuts the whole node down
+    Shutdown,
+    /// Logs the failure, the node carries on without the module
+    Ignore,
+}
+
+/// How the node reacts to each of its runtime modules failing, keyed by
+/// module name.
+///
+/// Only the gossip network, miner, vm and event store modules can be
+/// restarted. Every other one holds state the rest of the node relies on, so
+/// they shut the node down by default, except for the broadcast controller
+/// and state sync module which the node can do without.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SupervisionConfig {
+    pub mempool: ModuleFailurePolicy,
+    pub state_store: ModuleFailurePolicy,
+    pub gossip_network: ModuleFailurePolicy,
+    pub broadcast_controller: ModuleFailurePolicy,
+    pub state_sync: ModuleFailurePolicy,
+    pub jsonrpc_server: ModuleFailurePolicy,
+    pub validator: ModuleFailurePolicy,
+    pub miner: ModuleFailurePolicy,
+    pub vm: ModuleFailurePolicy,
+    pub event_store: ModuleFailurePolicy,
+
+    /// Times each module is restarted before the node shuts down instead
+    pub max_restarts: u32,
+
+    /// Time waited before a failed module is restarted
+    pub restart_delay: Duration,
+}
+
+impl SupervisionConfig {
+    /// Returns the policy of the module named `module`, modules the config
+    /// knows nothing about shut the node down
+    pub fn policy(&self, module: &str) -> ModuleFailurePolicy {
+        match module {
+            "mempool" => self.mempool,
+            "state_store" => self.state_store,
+            "gossip_network" => self.gossip_network,
+            "broadcast_controller" => self.broadcast_controller,
+            "state_sync" => self.state_sync,
+            "jsonrpc_server" => self.jsonrpc_server,
+            "validator" => self.validator,
+            "miner" => self.miner,
+            "vm" => self.vm,
+            "event_store" => self.event_store,
+            _ => ModuleFailurePolicy::Shutdown,
+        }
+    }
+}
+
+impl Default for SupervisionConfig {
+    fn default() -> Self {
+        Self {
+            mempool: ModuleFailurePolicy::Shutdown,
+            state_store: ModuleFailurePolicy::Shutdown,
+            gossip_network: ModuleFailurePolicy::Restart,
+            broadcast_controller: ModuleFailurePolicy::Ignore,
+            state_sync: ModuleFailurePolicy::Ignore,
+            jsonrpc_server: ModuleFailurePolicy::Shutdown,
+            validator: ModuleFailurePolicy::Shutdown,
+            miner: ModuleFailurePolicy::Restart,
+            vm: ModuleFailurePolicy::Restart,
+            event_store: ModuleFailurePolicy::Restart,
+            max_restarts: 5,
+            restart_delay: Duration::from_secs(1),
+        }
+    }
+}
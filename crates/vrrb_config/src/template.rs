@@ -0,0 +1,280 @@
+use std::fmt::Write;
+
+use serde_json::Value;
+
+use crate::{Error, NodeConfig, Result};
+
+const TEMPLATE_HEADER: &str = "\
+# VRRB node configuration
+#
+# Every field is set to its default value. Fields left out of this file take
+# their default values too, so it can be trimmed down to the ones that matter.
+# Durations are written as `{ secs = .., nanos = .. }` tables.
+";
+
+/// Description of every field written to a config template, in the order
+/// they're written in
+const FIELD_DOCS: &[(&str, &str)] = &[
+    ("id", "UUID that identifies the node"),
+    (
+        "idx",
+        "Index identifying the node within the p2p network",
+    ),
+    (
+        "data_dir",
+        "Directory every piece of node information is persisted to",
+    ),
+    ("db_path", "Directory the node's db lives in"),
+    (
+        "raptorq_gossip_address",
+        "Address the node listens for network events through RaptorQ",
+    ),
+    (
+        "udp_gossip_address",
+        "Address the node listens for network events through udp2p",
+    ),
+    (
+        "node_type",
+        "Type of the node, one of full, light, archive, miner, bootstrap,\nvalidator, master_node, rpc_node or observer",
+    ),
+    (
+        "bootstrap_node_addresses",
+        "Addresses of the bootstrap nodes used for peer discovery and initial\nstate sync",
+    ),
+    (
+        "http_api_address",
+        "Address the HTTP API listens for connections",
+    ),
+    (
+        "http_api_title",
+        "Title displayed on the HTTP API docs",
+    ),
+    (
+        "http_api_version",
+        "Version displayed on the HTTP API docs",
+    ),
+    (
+        "http_api_shutdown_timeout",
+        "Time the HTTP API server is given to shut down",
+    ),
+    (
+        "jsonrpc_server_address",
+        "Address the node listens for JSON-RPC connections",
+    ),
+    (
+        "preload_mock_state",
+        "Fills the state with mock accounts on startup, for testing only",
+    ),
+    (
+        "genesis_path",
+        "JSON file of the accounts the chain starts out with, only applied to\nan empty db",
+    ),
+    ("bootstrap_config", "Bootstrap configuration"),
+    (
+        "disable_networking",
+        "Runs the node without joining the network, its gossip, broadcast\ncontroller and state sync modules aren't started",
+    ),
+    (
+        "accept_txns_without_networking",
+        "Keeps txns submitted while networking is disabled in the local\nmempool instead of turning them away",
+    ),
+    (
+        "shutdown_timeout",
+        "Time each runtime module is given to stop once a shutdown is\nrequested",
+    ),
+    (
+        "readiness_timeout",
+        "Time the runtime modules are given to start before the node gives up\non starting",
+    ),
+    (
+        "min_bootstrap_peers",
+        "Peers the node has to be connected to before it mines and validates,\n0 to not wait for any",
+    ),
+    (
+        "bootstrap_quorum_timeout",
+        "Time a running node waits to be connected to min_bootstrap_peers peers",
+    ),
+    (
+        "exit_without_bootstrap_quorum",
+        "Shuts the node down instead of carrying on should it not reach its\nbootstrap quorum in time",
+    ),
+    (
+        "mempool_snapshot_path",
+        "File pending transactions are persisted to on shutdown and restored\nfrom on startup",
+    ),
+    (
+        "event_store_path",
+        "Directory significant events are logged to for auditing",
+    ),
+    (
+        "event_router_config",
+        "Buffer sizes of the topics within the node's event router and how\neach handles events once full, one of drop_oldest, drop_new or block",
+    ),
+    (
+        "supervision_config",
+        "Whether each runtime module is restarted or shuts the node down should\nit fail, one of restart, shutdown or ignore. Only the gossip_network, miner, vm\nand event_store modules can be restarted, up to max_restarts times each",
+    ),
+    (
+        "runtime_config",
+        "Threads the node's tasks are scheduled across, one per available core\nwhen unset, and the most threads spawned to run blocking work on",
+    ),
+    (
+        "mempool_max_size",
+        "Upper bound on the amount of pending transactions, the ones paying\nthe lowest fees are evicted first",
+    ),
+    (
+        "mempool_txn_ttl",
+        "Time pending transactions are kept before they expire",
+    ),
+    (
+        "mining_config",
+        "Block production interval and difficulty",
+    ),
+    (
+        "broadcast_engine_config",
+        "Reconnection backoff, message reassembly limits and per peer rate\nlimits of the gossip network",
+    ),
+    (
+        "fee_config",
+        "Minimum fees transactions have to offer, model is either flat or\nper_byte",
+    ),
+    (
+        "validation_queue_depth",
+        "Amount of transactions allowed to wait for validation at once",
+    ),
+    (
+        "validation_parallelism",
+        "Amount of worker threads transactions are validated on",
+    ),
+    ("chain_id", "Network the node belongs to"),
+    (
+        "reject_txns_without_chain_id",
+        "Turns away transactions that don't specify a chain id",
+    ),
+    (
+        "max_txn_size_bytes",
+        "Largest transaction, in bytes once encoded, the node takes in",
+    ),
+    (
+        "log_level",
+        "Most verbose level logged, one of error, warn, info, debug or trace",
+    ),
+    ("log_format", "Layout of log lines, either text or json"),
+    (
+        "jsonrpc_admin_token",
+        "Token JSON-RPC clients must present to manage peers",
+    ),
+];
+
+impl NodeConfig {
+    /// Renders the config as a TOML file documenting every field, which
+    /// `NodeConfig::from_file` loads back into the same config. Unset fields
+    /// are written commented out.
+    pub fn to_toml_template(&self) -> Result<String> {
+        let fields = match serde_json::to_value(self) {
+            Ok(Value::Object(fields)) => fields,
+            Ok(_) => return Err(Error::Other(String::from("config is not a table"))),
+            Err(err) => return Err(Error::Other(err.to_string())),
+        };
+
+        let mut template = String::from(TEMPLATE_HEADER);
+
+        for (key, doc) in FIELD_DOCS {
+            template.push('\n');
+
+            for line in doc.lines() {
+                let _ = writeln!(template, "# {line}");
+            }
+
+            match fields.get(*key).map(render_value) {
+                Some(Some(value)) => {
+                    let _ = writeln!(template, "{key} = {value}");
+                },
+                _ => {
+                    let _ = writeln!(template, "# {key} is unset by default");
+                },
+            }
+        }
+
+        Ok(template)
+    }
+}
+
+/// Renders a value as TOML, nested tables inline. Returns `None` for nulls,
+/// which TOML can't express.
+fn render_value(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::Bool(value) => Some(value.to_string()),
+        Value::Number(value) => Some(value.to_string()),
+        // NOTE: JSON string escapes are valid within TOML basic strings
+        Value::String(_) => Some(value.to_string()),
+        Value::Array(values) => {
+            let values = values.iter().filter_map(render_value).collect::<Vec<_>>();
+
+            Some(format!("[{}]", values.join(", ")))
+        },
+        Value::Object(fields) => {
+            let fields = fields
+                .iter()
+                .filter_map(|(key, value)| {
+                    render_value(value).map(|value| format!("{key} = {value}"))
+                })
+                .collect::<Vec<_>>();
+
+            if fields.is_empty() {
+                return Some(String::from("{}"));
+            }
+
+            Some(format!("{{ {} }}", fields.join(", ")))
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use primitives::NodeType;
+
+    use super::*;
+
+    #[test]
+    fn templates_load_back_into_the_same_config() {
+        let config = NodeConfig {
+            node_type: NodeType::Validator,
+            bootstrap_node_addresses: vec!["127.0.0.1:9000".parse().unwrap()],
+            ..Default::default()
+        };
+
+        let dir = std::env::temp_dir().join(vrrb_core::helpers::generate_random_string());
+        fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join("node.toml");
+        fs::write(&path, config.to_toml_template().unwrap()).unwrap();
+
+        let loaded = NodeConfig::from_file(&path).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&loaded).unwrap(),
+            serde_json::to_value(&config).unwrap()
+        );
+
+        fs::remove_dir_all(&dir).unwrap_or_default();
+    }
+
+    #[test]
+    fn every_field_is_documented() {
+        let fields = match serde_json::to_value(NodeConfig::default()).unwrap() {
+            Value::Object(fields) => fields,
+            value => panic!("unexpected config value {value}"),
+        };
+
+        for key in fields.keys() {
+            assert!(
+                FIELD_DOCS.iter().any(|(documented, _)| documented == key),
+                "{key} is missing from the config template"
+            );
+        }
+    }
+}
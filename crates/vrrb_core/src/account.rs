@@ -16,6 +16,7 @@ pub enum AccountField {
     Storage(Option<String>),
     Code(Option<String>),
     Digests(HashMap<AccountNonce, TransactionDigest>),
+    PubKey(SerializedPublicKey),
 }
 
 /// Struct representing the desired updates to be applied to account.
@@ -27,6 +28,7 @@ pub struct UpdateArgs {
     pub storage: Option<Option<String>>,
     pub code: Option<Option<String>>,
     pub digests: Option<HashMap<AccountNonce, TransactionDigest>>,
+    pub pubkey: Option<SerializedPublicKey>,
 }
 
 // The AccountFieldsUpdate will be compared by `nonce`. This way the updates can
@@ -51,9 +53,18 @@ pub struct Account {
     pub nonce: AccountNonce,
     pub credits: u128,
     pub debits: u128,
+    /// Serialized storage of the contract deployed to this account, if any
     pub storage: Option<String>,
+    /// Code of the contract deployed to this account, if any
     pub code: Option<String>,
     pub pubkey: SerializedPublicKey,
+    /// Key `pubkey` replaced when the account was last rotated, see
+    /// `accepts_signing_key`
+    #[serde(default)]
+    pub previous_pubkey: Option<SerializedPublicKey>,
+    /// When `pubkey` was last rotated, as a unix timestamp
+    #[serde(default)]
+    pub rotated_at: Option<i64>,
     pub digests: HashMap<AccountNonce, TransactionDigest>,
     pub created_at: i64,
     pub updated_at: Option<i64>,
@@ -86,12 +97,31 @@ impl Account {
             storage,
             code,
             pubkey,
+            previous_pubkey: None,
+            rotated_at: None,
             digests,
             created_at: Utc::now().timestamp(),
             updated_at: None,
         }
     }
 
+    /// Whether transactions signed with `pubkey` may spend from the account
+    /// at `now`. Besides its current key, the key it was rotated away from
+    /// is accepted for `grace_period_secs` after the rotation, so
+    /// transactions signed before it are still valid.
+    pub fn accepts_signing_key(&self, pubkey: &[u8], now: i64, grace_period_secs: i64) -> bool {
+        if self.pubkey == pubkey {
+            return true;
+        }
+
+        match (&self.previous_pubkey, self.rotated_at) {
+            (Some(previous_pubkey), Some(rotated_at)) => {
+                previous_pubkey == pubkey && now <= rotated_at.saturating_add(grace_period_secs)
+            },
+            _ => false,
+        }
+    }
+
     /// Modifies accounts hash, recalculating it using account's fields.
     fn rehash(&mut self) {
         let mut hasher = Sha256::new();
@@ -164,6 +194,13 @@ impl Account {
             AccountField::Digests(digests) => {
                 self.digests.extend(digests);
             },
+
+            AccountField::PubKey(pubkey) => {
+                if pubkey != self.pubkey {
+                    self.previous_pubkey = Some(std::mem::replace(&mut self.pubkey, pubkey));
+                    self.rotated_at = Some(Utc::now().timestamp());
+                }
+            },
         }
         Ok(())
     }
@@ -208,6 +245,9 @@ impl Account {
         if let Some(digests) = args.digests {
             self.update_single_field_no_hash(AccountField::Digests(digests))?;
         }
+        if let Some(pubkey) = args.pubkey {
+            self.update_single_field_no_hash(AccountField::PubKey(pubkey))?;
+        }
 
         self.updated_at = Some(Utc::now().timestamp());
         self.bump_nonce();
@@ -234,4 +274,29 @@ mod tests {
 
         assert_eq!(account.nonce, 0);
     }
+
+    #[test]
+    fn previous_keys_are_accepted_until_the_grace_period_ends() {
+        let (_, previous_pk) = generate_account_keypair();
+        let (_, new_pk) = generate_account_keypair();
+        let (_, other_pk) = generate_account_keypair();
+
+        let mut account = Account::new(previous_pk);
+        account
+            .update(UpdateArgs {
+                nonce: 1,
+                pubkey: Some(new_pk.serialize().to_vec()),
+                ..Default::default()
+            })
+            .unwrap();
+
+        let rotated_at = account.rotated_at.unwrap();
+        let new_pk = new_pk.serialize();
+        let previous_pk = previous_pk.serialize();
+
+        assert!(account.accepts_signing_key(&new_pk, rotated_at + 100, 60));
+        assert!(account.accepts_signing_key(&previous_pk, rotated_at + 60, 60));
+        assert!(!account.accepts_signing_key(&previous_pk, rotated_at + 61, 60));
+        assert!(!account.accepts_signing_key(&other_pk.serialize(), rotated_at, 60));
+    }
 }
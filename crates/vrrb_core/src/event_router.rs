@@ -1,4 +1,9 @@
-use std::{collections::HashMap, net::SocketAddr};
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
 
 use primitives::{
     Address,
@@ -6,21 +11,24 @@ use primitives::{
     NodeIdx,
     NodeType,
     PeerId,
+    ProtocolVersion,
     QuorumPublicKey,
     QuorumType,
     RawSignature,
+    RuntimeModuleState,
     TxHashString,
 };
 use serde::{Deserialize, Serialize};
-use telemetry::{error, info};
+use telemetry::{error, info, warn};
 use tokio::sync::{
-    broadcast::{self, Sender},
+    broadcast::{self, error::RecvError, Sender},
     mpsc::{UnboundedReceiver, UnboundedSender},
 };
 
 use crate::{
     account::Account,
-    txn::{TransactionDigest, Txn},
+    key_rotation::KeyRotation,
+    txn::{TransactionDigest, Txn, ValidationError},
     Error,
 };
 
@@ -61,6 +69,38 @@ pub struct VoteReceipt {
     pub signature: RawSignature,
 }
 
+/// A program to be run on the node's VM
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct VmExecutionRequest {
+    /// Identifies the execution within its result, e.g. the digest of the txn
+    /// carrying the contract call
+    pub id: String,
+    /// RISC-V machine code to run
+    pub code: Vec<u8>,
+    /// Gas limit of the execution, i.e. the amount of instructions the
+    /// program may execute before it is aborted
+    pub max_steps: u64,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize, thiserror::Error)]
+pub enum VmError {
+    #[error("out of gas after {steps} steps")]
+    OutOfGas { steps: u64 },
+
+    #[error("program faulted: {0}")]
+    Fault(String),
+}
+
+/// Outcome of a `VmExecutionRequest`
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct VmExecutionResult {
+    pub id: String,
+    /// Amount of instructions executed, i.e. the gas consumed
+    pub steps: u64,
+    /// The value the program left in register a0 once it ran to completion
+    pub outcome: Result<u64, VmError>,
+}
+
 #[derive(Default, Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub struct QuorumCertifiedTxn {
     sender_farmer_id: Vec<u8>,
@@ -71,12 +111,41 @@ pub struct QuorumCertifiedTxn {
     signature: RawSignature,
 }
 
+/// Why a node is shutting down
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ShutdownReason {
+    /// An operator asked the node to stop, e.g. through ctrl+c
+    Operator,
+    /// The node ran into an error it can't recover from
+    FatalError(String),
+    /// The node is restarting to pick up a new configuration
+    ConfigReload,
+    /// The node is restarting to run a newer version
+    Upgrade,
+}
+
+impl std::fmt::Display for ShutdownReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShutdownReason::Operator => write!(f, "requested by operator"),
+            ShutdownReason::FatalError(err) => write!(f, "fatal error: {err}"),
+            ShutdownReason::ConfigReload => write!(f, "configuration reload"),
+            ShutdownReason::Upgrade => write!(f, "upgrade"),
+        }
+    }
+}
+
 #[derive(Default, Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
 #[non_exhaustive]
 pub enum Event {
     #[default]
     NoOp,
+    /// Same as a `Shutdown` with `ShutdownReason::Operator`
     Stop,
+    /// Asks every runtime module to shut down
+    Shutdown {
+        reason: ShutdownReason,
+    },
     /// New txn came from network, requires validation
     #[deprecated(note = "replaced by NewTxnCreated")]
     TxnCreated(Vec<u8>),
@@ -87,8 +156,35 @@ pub enum Event {
     /// Batch of validated txns
     TxnBatchValidated(Vec<TransactionDigest>),
     TxnAddedToMempool(TransactionDigest),
+    /// A txn failed validation and won't make it into a block
+    TxnRejected {
+        txn_hash: TransactionDigest,
+        reason: ValidationError,
+    },
+    /// A txn stayed in the mempool for longer than its TTL and was dropped
+    TxnExpired {
+        txn_hash: TransactionDigest,
+    },
+    /// A txn was evicted from the full mempool and won't make it into a block
+    TxnEvicted {
+        txn_hash: TransactionDigest,
+    },
+    /// The mempool's capacity was changed while the node is running
+    MempoolMaxSizeUpdated(Option<usize>),
+    /// The mempool is due to drop its expired txns
+    MempoolSweepDue,
+    /// A program, e.g. a contract call, should be run on the node's VM
+    VmExecutionRequested(VmExecutionRequest),
+    /// The node's VM finished running a program
+    VmExecutionCompleted(VmExecutionResult),
     BlockReceived,
     BlockConfirmed(Vec<u8>),
+    /// A block holding the given txns was committed to the chain at
+    /// `block_height`
+    BlockCommitted {
+        block_height: u64,
+        txns: Vec<TransactionDigest>,
+    },
     ClaimCreated(Vec<u8>),
     ClaimProcessed(Vec<u8>),
     UpdateLastBlock(Vec<u8>),
@@ -97,12 +193,64 @@ pub enum Event {
     CheckAbandoned,
     PeerRequestedStateSync(PeerData),
 
+    /// Asks the node to catch up with the state of the given peer
+    StateSyncRequest(PeerData),
+
+    /// Accounts synced from a peer, to be written to the node's state exactly
+    /// as the peer stores them
+    StateSyncChunk {
+        peer: PeerData,
+        offset: u64,
+        total: u64,
+        accounts: Vec<(Address, AccountBytes)>,
+    },
+
+    /// The state module's reply to a `StateSyncChunk`: the amount of
+    /// accounts it wrote to the node's state, or why it didn't
+    StateSyncChunkApplied {
+        peer: PeerData,
+        offset: u64,
+        applied: std::result::Result<u64, String>,
+    },
+
+    /// The node caught up with the state of the given peer
+    StateSyncComplete {
+        peer: PeerData,
+        accounts: u64,
+    },
+
     /// A peer joined the network, should be added to the node's peer list
     PeerJoined(PeerData),
 
     /// Peer abandoned the network. Should be removed from the node's peer list
     PeerLeft(PeerData),
 
+    /// A dropped connection to a peer was re-established
+    PeerReconnected(PeerId),
+
+    /// A peer could not be reached after exhausting all reconnection attempts
+    PeerUnreachable(PeerId),
+
+    /// A peer speaks a protocol version this node can't talk to, so the two
+    /// won't peer
+    PeerIncompatible {
+        peer: PeerData,
+        local_version: ProtocolVersion,
+        remote_version: ProtocolVersion,
+    },
+
+    /// A subscriber of the given topic fell behind and missed that many
+    /// events, which likely calls for a state resync
+    SubscriberLagged(Topic, u64),
+
+    /// A runtime module moved from one lifecycle state to another
+    ModuleStateChanged {
+        module_name: String,
+        from: RuntimeModuleState,
+        to: RuntimeModuleState,
+        reason: Option<String>,
+    },
+
     /// A Event to start the DKG process.
     DkgInitiate,
 
@@ -124,6 +272,11 @@ pub enum Event {
 
     Farm,
 
+    /// A block production attempt is due at the given difficulty
+    MineBlock {
+        difficulty: u64,
+    },
+
     Vote(Vote, QuorumType, FarmerQuorumThreshold),
     PullQuorumCertifiedTxns(usize),
     QuorumCertifiedTxns(QuorumCertifiedTxn),
@@ -134,6 +287,7 @@ pub enum Event {
     AccountCreated(Address),
 
     AccountUpdateRequested((Address, AccountBytes)),
+    AccountKeyRotationRequested(KeyRotation),
     UpdatedAccount(AccountBytes),
     // SendTxn(u32, String, u128), // address number, receiver address, amount
     // ProcessTxnValidator(Vec<u8>),
@@ -190,6 +344,43 @@ pub enum Event {
     // PublicKeySetSync,
 }
 
+impl Event {
+    /// Returns true for events asking the node to shut down, i.e. `Stop` and
+    /// `Shutdown`
+    pub fn is_shutdown(&self) -> bool {
+        self.shutdown_reason().is_some()
+    }
+
+    /// Returns why the node is being asked to shut down, if this event asks
+    /// it to
+    pub fn shutdown_reason(&self) -> Option<ShutdownReason> {
+        match self {
+            Event::Stop => Some(ShutdownReason::Operator),
+            Event::Shutdown { reason } => Some(reason.clone()),
+            _ => None,
+        }
+    }
+
+    /// Returns true for events worth keeping a durable record of: blocks
+    /// being confirmed, txns being accepted or rejected and peers coming and
+    /// going
+    pub fn is_auditable(&self) -> bool {
+        matches!(
+            self,
+            Event::BlockConfirmed(_)
+                | Event::BlockCommitted { .. }
+                | Event::TxnValidated(_)
+                | Event::TxnAddedToMempool(_)
+                | Event::TxnRejected { .. }
+                | Event::TxnExpired { .. }
+                | Event::TxnEvicted { .. }
+                | Event::PeerJoined(_)
+                | Event::PeerLeft(_)
+                | Event::AccountKeyRotationRequested(_)
+        )
+    }
+}
+
 impl From<&theater::Message> for Event {
     fn from(msg: &theater::Message) -> Self {
         serde_json::from_slice(&msg.data).unwrap_or_default()
@@ -214,7 +405,7 @@ impl From<Event> for Vec<u8> {
     }
 }
 
-#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
 /// Contains all the potential topics.
 pub enum Topic {
     Control,
@@ -222,6 +413,9 @@ pub enum Topic {
     Network,
     Storage,
     Consensus,
+    /// Copies of significant events published to any other topic, see
+    /// `Event::is_auditable`. Only routed when the topic has been added.
+    Audit,
 }
 
 /// EventRouter is an internal message bus that coordinates interaction
@@ -229,10 +423,187 @@ pub enum Topic {
 pub struct EventRouter {
     /// Map of async transmitters to various runtime modules
     topics: HashMap<Topic, Sender<Event>>,
+
+    /// Effective buffer capacity of each topic
+    capacities: HashMap<Topic, usize>,
+
+    /// Subscriptions spanning several topics, see `subscribe_many`
+    merged_subscriptions: Vec<MergedSubscription>,
+
+    /// How each topic handles events published while it's saturated, topics
+    /// without one use the default policy
+    backpressure_policies: HashMap<Topic, BackpressurePolicy>,
+
+    metrics: EventRouterMetrics,
+}
+
+/// Interval at which a saturated `Block` topic is checked for room
+const BLOCKED_TOPIC_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// How the event router handles events published to a topic whose
+/// subscribers have yet to receive as many events as the topic can buffer
+#[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackpressurePolicy {
+    /// Publishes the event, overwriting the oldest event some subscriber has
+    /// yet to receive. Lagging subscribers are told how many they missed.
+    #[default]
+    DropOldest,
+    /// Drops the event being published
+    DropNew,
+    /// Waits for the subscribers to catch up before publishing the event.
+    /// Routing of every other topic waits along with it, so it only suits
+    /// topics whose subscribers keep up.
+    Block,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PublishError {
+    #[error("topic {0:?} has not been added")]
+    UnknownTopic(Topic),
+
+    #[error("subscribers of topic {0:?} are saturated")]
+    Saturated(Topic),
+
+    #[error("topic {0:?} has no subscribers")]
+    NoSubscribers(Topic),
+}
+
+/// Topics a merged subscription receives the events of
+#[derive(Debug, Clone)]
+enum TopicFilter {
+    /// Every topic but the Audit topic, which only holds copies of events
+    /// published to the others
+    All,
+    Only(HashSet<Topic>),
+}
+
+impl TopicFilter {
+    fn matches(&self, topic: &Topic) -> bool {
+        match self {
+            TopicFilter::All => *topic != Topic::Audit,
+            TopicFilter::Only(topics) => topics.contains(topic),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct MergedSubscription {
+    filter: TopicFilter,
+    /// Effective buffer capacity of the subscription
+    capacity: usize,
+    sender: Sender<Event>,
+}
+
+/// Counts of the events that went through an `EventRouter`, per topic. Clones
+/// share the same underlying counts.
+#[derive(Debug, Clone, Default)]
+pub struct EventRouterMetrics {
+    /// Events published to the router
+    pub published: EventCounter,
+
+    /// Events handed over to a topic's subscribers. Events are counted once
+    /// per topic, regardless of how many subscribers it has.
+    pub delivered: EventCounter,
+
+    /// Events the router dropped, either because a topic's buffer overflowed
+    /// or because it had no subscribers at all
+    pub dropped: EventCounter,
+}
+
+/// Counts of the events that went through a single topic
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TopicMetrics {
+    pub published: u64,
+    pub delivered: u64,
+    pub dropped: u64,
+}
+
+impl EventRouterMetrics {
+    pub fn topic(&self, topic: &Topic) -> TopicMetrics {
+        TopicMetrics {
+            published: self.published.get(topic),
+            delivered: self.delivered.get(topic),
+            dropped: self.dropped.get(topic),
+        }
+    }
+
+    /// Returns the counts of every topic that saw at least one event
+    pub fn snapshot(&self) -> HashMap<Topic, TopicMetrics> {
+        let topics = self
+            .published
+            .snapshot()
+            .into_keys()
+            .chain(self.delivered.snapshot().into_keys())
+            .chain(self.dropped.snapshot().into_keys())
+            .collect::<HashSet<Topic>>();
+
+        topics
+            .into_iter()
+            .map(|topic| {
+                let metrics = self.topic(&topic);
+                (topic, metrics)
+            })
+            .collect()
+    }
+}
+
+/// Tracks an amount of events per topic. Clones share the same underlying
+/// counts.
+#[derive(Debug, Clone, Default)]
+pub struct EventCounter {
+    counts: Arc<RwLock<HashMap<Topic, u64>>>,
+}
+
+impl EventCounter {
+    /// Returns the amount of events counted on the given topic so far
+    pub fn get(&self, topic: &Topic) -> u64 {
+        self.counts
+            .read()
+            .map(|counts| counts.get(topic).copied().unwrap_or_default())
+            .unwrap_or_default()
+    }
+
+    /// Returns the amount of events counted so far on every topic that has
+    /// at least one
+    pub fn snapshot(&self) -> HashMap<Topic, u64> {
+        self.counts
+            .read()
+            .map(|counts| counts.clone())
+            .unwrap_or_default()
+    }
+
+    fn increment(&self, topic: &Topic) {
+        if let Ok(mut counts) = self.counts.write() {
+            *counts.entry(topic.clone()).or_default() += 1;
+        }
+    }
 }
 
 pub type DirectedEvent = (Topic, Event);
 
+/// EventSubscriber is a cheaply cloneable handle that subscribes to the
+/// topics of an event router, even once the router itself has been moved
+/// into the task routing its events. Only topics added before the handle was
+/// created can be subscribed to.
+#[derive(Debug, Clone)]
+pub struct EventSubscriber {
+    topics: HashMap<Topic, Sender<Event>>,
+}
+
+impl EventSubscriber {
+    pub fn subscribe(
+        &self,
+        topic: &Topic,
+    ) -> std::result::Result<broadcast::Receiver<Event>, Error> {
+        if let Some(sender) = self.topics.get(topic) {
+            Ok(sender.subscribe())
+        } else {
+            Err(Error::Other(format!("unable to subscribe to {topic:?}")))
+        }
+    }
+}
+
 impl Default for EventRouter {
     fn default() -> Self {
         Self::new()
@@ -243,6 +614,10 @@ impl EventRouter {
     pub fn new() -> Self {
         Self {
             topics: HashMap::new(),
+            capacities: HashMap::new(),
+            merged_subscriptions: vec![],
+            backpressure_policies: HashMap::new(),
+            metrics: EventRouterMetrics::default(),
         }
     }
 
@@ -250,9 +625,28 @@ impl EventRouter {
         let buffer = size.unwrap_or(1);
         let (tx, _) = broadcast::channel(buffer);
 
+        // NOTE: broadcast channels round their capacity up to the next power of two
+        self.capacities
+            .insert(topic.clone(), buffer.next_power_of_two());
         self.topics.insert(topic, tx);
     }
 
+    /// Returns a handle to the counts of events published, delivered and
+    /// dropped on each topic
+    pub fn metrics(&self) -> EventRouterMetrics {
+        self.metrics.clone()
+    }
+
+    /// Returns a handle to the counts of events received on each topic
+    pub fn processed_events(&self) -> EventCounter {
+        self.metrics.published.clone()
+    }
+
+    /// Returns a handle to the counts of events dropped on each topic
+    pub fn dropped_events(&self) -> EventCounter {
+        self.metrics.dropped.clone()
+    }
+
     pub fn subscribe(
         &self,
         topic: &Topic,
@@ -264,27 +658,277 @@ impl EventRouter {
         }
     }
 
+    /// Returns a handle that subscribes to the topics added so far, see
+    /// `EventSubscriber`
+    pub fn subscriber(&self) -> EventSubscriber {
+        EventSubscriber {
+            topics: self.topics.clone(),
+        }
+    }
+
+    /// Subscribes to several topics at once, the events published to any of
+    /// them are merged into the returned receiver. Events keep their order
+    /// within a topic, their order across topics is best effort.
+    ///
+    /// Every topic must have been added already. The receiver buffers as
+    /// many events as the given topics combined.
+    pub fn subscribe_many(
+        &mut self,
+        topics: &[Topic],
+    ) -> std::result::Result<broadcast::Receiver<Event>, Error> {
+        if let Some(topic) = topics.iter().find(|topic| !self.topics.contains_key(topic)) {
+            return Err(Error::Other(format!("unable to subscribe to {topic:?}")));
+        }
+
+        let topics = topics.iter().cloned().collect::<HashSet<Topic>>();
+        let capacity = topics
+            .iter()
+            .filter_map(|topic| self.capacities.get(topic))
+            .sum();
+
+        Ok(self.add_merged_subscription(TopicFilter::Only(topics), capacity))
+    }
+
+    /// Subscribes to every topic, including the ones added later on, see
+    /// `subscribe_many`. Auditable events are only received once, from the
+    /// topic they were published to.
+    pub fn subscribe_all(&mut self) -> broadcast::Receiver<Event> {
+        let capacity = self.capacities.values().sum();
+
+        self.add_merged_subscription(TopicFilter::All, capacity)
+    }
+
+    fn add_merged_subscription(
+        &mut self,
+        filter: TopicFilter,
+        capacity: usize,
+    ) -> broadcast::Receiver<Event> {
+        let (sender, receiver) = broadcast::channel(capacity.max(1));
+
+        self.merged_subscriptions.push(MergedSubscription {
+            filter,
+            capacity: capacity.max(1).next_power_of_two(),
+            sender,
+        });
+
+        receiver
+    }
+
     /// Starts the event router, distributing all incomming events to all
     /// subscribers
     pub async fn start(&mut self, event_rx: &mut UnboundedReceiver<DirectedEvent>) {
         while let Some((topic, event)) = event_rx.recv().await {
-            if event == Event::Stop {
-                info!("event router received stop signal");
-                self.fan_out_event(Event::Stop, &topic);
+            self.metrics.published.increment(&topic);
+
+            if let Some(reason) = event.shutdown_reason() {
+                info!("event router received stop signal: {reason}");
+
+                // NOTE: every module must be notified of a stop, regardless of
+                // the topic it listens to and of the topic's backpressure
+                // policy. Merged subscribers are notified only once.
+                let topics = self.topics.keys().cloned().collect::<Vec<Topic>>();
+                for topic in topics {
+                    let delivered = self.send_to_topic(event.clone(), &topic);
+                    self.record_delivery(&event, &topic, delivered);
+                }
+
+                for subscription in self.merged_subscriptions.iter() {
+                    let _ = subscription.sender.send(event.clone());
+                }
 
                 return;
             }
 
-            self.fan_out_event(event, &topic);
+            if event.is_auditable() {
+                self.publish(&Topic::Audit, event.clone()).await;
+            }
+
+            self.publish(&topic, event).await;
+        }
+    }
+
+    /// Publishes an event to a topic according to the topic's
+    /// `BackpressurePolicy`, waiting for its subscribers to catch up first if
+    /// it's a `Block` topic
+    async fn publish(&mut self, topic: &Topic, event: Event) {
+        if self.backpressure_policy(topic) == BackpressurePolicy::Block {
+            while self.is_saturated(topic) {
+                tokio::time::sleep(BLOCKED_TOPIC_POLL_INTERVAL).await;
+            }
+        }
+
+        // NOTE: saturation and the lack of subscribers are already logged and
+        // counted
+        let _ = self.try_publish(topic, event);
+    }
+
+    /// Publishes an event to a topic right away, without waiting for its
+    /// subscribers. Fails with `PublishError::Saturated` if every pending
+    /// slot of the topic's buffer was taken, in which case the event was
+    /// handled according to the topic's `BackpressurePolicy`:
+    ///
+    /// - `DropOldest`: the event was published, overwriting the oldest pending
+    ///   one
+    /// - `DropNew`: the event was dropped
+    /// - `Block`: the event was not published and may be retried
+    pub fn try_publish(
+        &mut self,
+        topic: &Topic,
+        event: Event,
+    ) -> std::result::Result<(), PublishError> {
+        if !self.topics.contains_key(topic) {
+            return Err(PublishError::UnknownTopic(topic.clone()));
+        }
+
+        let saturated = self.is_saturated(topic);
+
+        match self.backpressure_policy(topic) {
+            BackpressurePolicy::DropOldest => {},
+            BackpressurePolicy::DropNew if saturated => {
+                warn!("topic {topic:?} is full, dropping event {event:?}");
+                self.metrics.dropped.increment(topic);
+                return Err(PublishError::Saturated(topic.clone()));
+            },
+            BackpressurePolicy::Block if saturated => {
+                return Err(PublishError::Saturated(topic.clone()));
+            },
+            BackpressurePolicy::DropNew | BackpressurePolicy::Block => {},
         }
+
+        if !self.fan_out_event(event, topic) {
+            return Err(PublishError::NoSubscribers(topic.clone()));
+        }
+
+        if saturated {
+            return Err(PublishError::Saturated(topic.clone()));
+        }
+
+        Ok(())
+    }
+
+    /// Whether the direct or merged subscribers of a topic have yet to
+    /// receive as many events as they can buffer
+    pub fn is_saturated(&self, topic: &Topic) -> bool {
+        let topic_saturated = match (self.topics.get(topic), self.capacities.get(topic)) {
+            (Some(sender), Some(capacity)) => sender.len() >= *capacity,
+            _ => false,
+        };
+
+        topic_saturated
+            || self.merged_subscriptions.iter().any(|subscription| {
+                subscription.filter.matches(topic)
+                    && subscription.sender.len() >= subscription.capacity
+            })
+    }
+
+    /// Sets how the router handles events published to a saturated topic,
+    /// topics drop their oldest pending events unless told otherwise
+    pub fn set_backpressure_policy(&mut self, topic: Topic, policy: BackpressurePolicy) {
+        self.backpressure_policies.insert(topic, policy);
+    }
+
+    pub fn backpressure_policy(&self, topic: &Topic) -> BackpressurePolicy {
+        self.backpressure_policies
+            .get(topic)
+            .copied()
+            .unwrap_or_default()
     }
 
-    fn fan_out_event(&mut self, event: Event, topic: &Topic) {
-        if let Some(topic_sender) = self.topics.get_mut(topic) {
-            if let Err(err) = topic_sender.send(event.clone()) {
-                error!("failed to send event {event:?} to topic {topic:?}: {err:?}");
+    /// Hands an event over to the subscribers of `topic`, merged subscribers
+    /// included. Returns whether any of them received it.
+    fn fan_out_event(&mut self, event: Event, topic: &Topic) -> bool {
+        if !self.topics.contains_key(topic) {
+            return false;
+        }
+
+        let mut delivered = self.send_to_topic(event.clone(), topic);
+
+        // NOTE: subscriptions whose receivers were all dropped are forgotten
+        let mut dropped_subscriptions = vec![];
+
+        for (idx, subscription) in self.merged_subscriptions.iter().enumerate() {
+            if !subscription.filter.matches(topic) {
+                continue;
+            }
+
+            if subscription.sender.len() >= subscription.capacity {
+                warn!("merged subscription to {topic:?} is full, its oldest pending event will be dropped");
+                self.metrics.dropped.increment(topic);
+            }
+
+            match subscription.sender.send(event.clone()) {
+                Ok(_) => delivered = true,
+                Err(_) => dropped_subscriptions.push(idx),
             }
         }
+
+        for idx in dropped_subscriptions.into_iter().rev() {
+            self.merged_subscriptions.remove(idx);
+        }
+
+        self.record_delivery(&event, topic, delivered);
+
+        delivered
+    }
+
+    fn record_delivery(&self, event: &Event, topic: &Topic, delivered: bool) {
+        if delivered {
+            self.metrics.delivered.increment(topic);
+        } else {
+            error!("failed to send event {event:?} to topic {topic:?}: no subscribers");
+            self.metrics.dropped.increment(topic);
+        }
+    }
+
+    /// Sends an event to the direct subscribers of `topic`, returns whether
+    /// any of them received it
+    fn send_to_topic(&mut self, event: Event, topic: &Topic) -> bool {
+        let topic_sender = match self.topics.get(topic) {
+            Some(topic_sender) => topic_sender,
+            None => return false,
+        };
+
+        let capacity = self.capacities.get(topic).copied().unwrap_or(1);
+
+        // NOTE: sending to a full topic overwrites the oldest event that at
+        // least one subscriber hasn't received yet
+        if topic_sender.len() >= capacity {
+            warn!("topic {topic:?} is full, its oldest pending event will be dropped");
+            self.metrics.dropped.increment(topic);
+        }
+
+        topic_sender.send(event).is_ok()
+    }
+}
+
+/// Receives the next event published to a topic. If the subscriber fell behind
+/// in the meantime, the amount of events it missed is reported to the Control
+/// topic and the oldest event still buffered is returned instead. Only fails
+/// once the topic has been closed.
+///
+/// Lag on the Control topic itself is only logged, since reporting it there
+/// would crowd out the very events its subscribers are missing.
+pub async fn recv_reporting_lag(
+    events_rx: &mut broadcast::Receiver<Event>,
+    topic: &Topic,
+    events_tx: &UnboundedSender<DirectedEvent>,
+) -> std::result::Result<Event, RecvError> {
+    loop {
+        match events_rx.recv().await {
+            Err(RecvError::Lagged(missed)) => {
+                warn!("subscriber of topic {topic:?} lagged behind, missing {missed} events");
+
+                if *topic == Topic::Control {
+                    continue;
+                }
+
+                let lag_event = Event::SubscriberLagged(topic.clone(), missed);
+                if let Err(err) = events_tx.send((Topic::Control, lag_event)) {
+                    error!("failed to report lag on topic {topic:?}: {err}");
+                }
+            },
+            result => return result,
+        }
     }
 }
 
@@ -319,6 +963,29 @@ mod tests {
         router.subscribe(&Topic::Control).unwrap();
     }
 
+    #[tokio::test]
+    async fn subscribers_can_subscribe_once_the_router_started() {
+        let (event_tx, mut event_rx) = unbounded_channel::<DirectedEvent>();
+        let mut router = EventRouter::new();
+
+        router.add_topic(Topic::State, Some(10));
+
+        let subscriber = router.subscriber();
+
+        let handle = tokio::spawn(async move {
+            router.start(&mut event_rx).await;
+        });
+
+        let mut subscriber_rx = subscriber.subscribe(&Topic::State).unwrap();
+        assert!(subscriber.subscribe(&Topic::Network).is_err());
+
+        event_tx.send((Topic::State, Event::NoOp)).unwrap();
+        assert_eq!(subscriber_rx.recv().await.unwrap(), Event::NoOp);
+
+        event_tx.send((Topic::Control, Event::Stop)).unwrap();
+        handle.await.unwrap();
+    }
+
     #[tokio::test]
     async fn should_stop_when_issued_stop_event() {
         let (event_tx, mut event_rx) = unbounded_channel::<DirectedEvent>();
@@ -338,6 +1005,390 @@ mod tests {
 
         assert_eq!(subscriber_rx.try_recv().unwrap(), Event::Stop);
     }
+
+    #[tokio::test]
+    async fn should_deliver_shutdown_reasons_to_every_topic() {
+        let (event_tx, mut event_rx) = unbounded_channel::<DirectedEvent>();
+        let mut router = EventRouter::new();
+
+        router.add_topic(Topic::Control, Some(10));
+        router.add_topic(Topic::Storage, Some(10));
+
+        let mut control_rx = router.subscribe(&Topic::Control).unwrap();
+        let mut storage_rx = router.subscribe(&Topic::Storage).unwrap();
+
+        let handle = tokio::spawn(async move {
+            router.start(&mut event_rx).await;
+        });
+
+        let shutdown = Event::Shutdown {
+            reason: ShutdownReason::FatalError(String::from("disk full")),
+        };
+
+        event_tx.send((Topic::Control, shutdown.clone())).unwrap();
+
+        handle.await.unwrap();
+
+        for received in [
+            control_rx.try_recv().unwrap(),
+            storage_rx.try_recv().unwrap(),
+        ] {
+            assert_eq!(received, shutdown);
+            assert_eq!(
+                received.shutdown_reason(),
+                Some(ShutdownReason::FatalError(String::from("disk full")))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn should_copy_auditable_events_to_the_audit_topic() {
+        let (event_tx, mut event_rx) = unbounded_channel::<DirectedEvent>();
+        let mut router = EventRouter::new();
+
+        router.add_topic(Topic::Storage, Some(10));
+        router.add_topic(Topic::Audit, Some(10));
+
+        let mut storage_rx = router.subscribe(&Topic::Storage).unwrap();
+        let mut audit_rx = router.subscribe(&Topic::Audit).unwrap();
+
+        let handle = tokio::spawn(async move {
+            router.start(&mut event_rx).await;
+        });
+
+        let block_confirmed = Event::BlockConfirmed(vec![1, 2, 3]);
+
+        event_tx.send((Topic::Storage, Event::NoOp)).unwrap();
+        event_tx
+            .send((Topic::Storage, block_confirmed.clone()))
+            .unwrap();
+        event_tx.send((Topic::Control, Event::Stop)).unwrap();
+
+        handle.await.unwrap();
+
+        assert_eq!(storage_rx.try_recv().unwrap(), Event::NoOp);
+        assert_eq!(storage_rx.try_recv().unwrap(), block_confirmed);
+        assert_eq!(audit_rx.try_recv().unwrap(), block_confirmed);
+        assert_eq!(audit_rx.try_recv().unwrap(), Event::Stop);
+    }
+
+    #[test]
+    fn stop_is_an_operator_shutdown() {
+        assert!(Event::Stop.is_shutdown());
+        assert_eq!(
+            Event::Stop.shutdown_reason(),
+            Some(ShutdownReason::Operator)
+        );
+        assert!(!Event::NoOp.is_shutdown());
+    }
+
+    #[tokio::test]
+    async fn should_report_lagging_subscribers() {
+        let (events_tx, mut events_rx) = unbounded_channel::<DirectedEvent>();
+        let mut router = EventRouter::new();
+
+        router.add_topic(Topic::Consensus, Some(2));
+
+        let mut subscriber_rx = router.subscribe(&Topic::Consensus).unwrap();
+
+        for i in 0..5 {
+            router.fan_out_event(Event::AckPartCommitment(i), &Topic::Consensus);
+        }
+
+        let event = recv_reporting_lag(&mut subscriber_rx, &Topic::Consensus, &events_tx)
+            .await
+            .unwrap();
+
+        assert_eq!(event, Event::AckPartCommitment(3));
+        assert_eq!(
+            events_rx.try_recv().unwrap(),
+            (Topic::Control, Event::SubscriberLagged(Topic::Consensus, 3))
+        );
+    }
+
+    #[tokio::test]
+    async fn should_not_report_lag_on_the_control_topic_to_itself() {
+        let (events_tx, mut events_rx) = unbounded_channel::<DirectedEvent>();
+        let mut router = EventRouter::new();
+
+        router.add_topic(Topic::Control, Some(2));
+
+        let mut subscriber_rx = router.subscribe(&Topic::Control).unwrap();
+
+        for i in 0..5 {
+            router.fan_out_event(Event::AckPartCommitment(i), &Topic::Control);
+        }
+
+        let event = recv_reporting_lag(&mut subscriber_rx, &Topic::Control, &events_tx)
+            .await
+            .unwrap();
+
+        assert_eq!(event, Event::AckPartCommitment(3));
+        assert!(events_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn should_count_events_dropped_by_full_topics() {
+        let mut router = EventRouter::new();
+
+        router.add_topic(Topic::Consensus, Some(2));
+
+        let _subscriber_rx = router.subscribe(&Topic::Consensus).unwrap();
+        let dropped_events = router.dropped_events();
+
+        for _ in 0..5 {
+            router.fan_out_event(Event::NoOp, &Topic::Consensus);
+        }
+
+        assert_eq!(dropped_events.get(&Topic::Consensus), 3);
+        assert_eq!(dropped_events.get(&Topic::Control), 0);
+    }
+
+    #[tokio::test]
+    async fn should_track_metrics_of_topics_with_slow_subscribers() {
+        let (event_tx, mut event_rx) = unbounded_channel::<DirectedEvent>();
+        let mut router = EventRouter::new();
+
+        router.add_topic(Topic::Control, Some(10));
+        router.add_topic(Topic::Consensus, Some(4));
+
+        // NOTE: never reads, so the topic's buffer fills up
+        let _slow_subscriber_rx = router.subscribe(&Topic::Consensus).unwrap();
+        let metrics = router.metrics();
+
+        let handle = tokio::spawn(async move {
+            router.start(&mut event_rx).await;
+        });
+
+        for i in 0..10 {
+            event_tx
+                .send((Topic::Consensus, Event::AckPartCommitment(i)))
+                .unwrap();
+        }
+        event_tx.send((Topic::Control, Event::Stop)).unwrap();
+
+        handle.await.unwrap();
+
+        assert_eq!(
+            metrics.topic(&Topic::Consensus),
+            TopicMetrics {
+                published: 10,
+                // NOTE: the stop signal is fanned out to every topic
+                delivered: 11,
+                dropped: 7,
+            }
+        );
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot[&Topic::Control].published, 1);
+        assert_eq!(snapshot[&Topic::Control].dropped, 1);
+        assert!(!snapshot.contains_key(&Topic::Storage));
+    }
+
+    #[tokio::test]
+    async fn should_merge_events_of_several_topics_into_one_subscription() {
+        let (event_tx, mut event_rx) = unbounded_channel::<DirectedEvent>();
+        let mut router = EventRouter::new();
+
+        router.add_topic(Topic::Control, Some(10));
+        router.add_topic(Topic::Storage, Some(10));
+        router.add_topic(Topic::Consensus, Some(10));
+
+        let mut merged_rx = router
+            .subscribe_many(&[Topic::Storage, Topic::Consensus])
+            .unwrap();
+        let mut all_rx = router.subscribe_all();
+
+        assert!(router.subscribe_many(&[Topic::Network]).is_err());
+
+        let handle = tokio::spawn(async move {
+            router.start(&mut event_rx).await;
+        });
+
+        event_tx
+            .send((Topic::Storage, Event::AckPartCommitment(1)))
+            .unwrap();
+        event_tx
+            .send((Topic::Consensus, Event::AckPartCommitment(2)))
+            .unwrap();
+        event_tx
+            .send((Topic::Control, Event::AckPartCommitment(3)))
+            .unwrap();
+        event_tx.send((Topic::Control, Event::Stop)).unwrap();
+
+        handle.await.unwrap();
+
+        assert_eq!(merged_rx.try_recv().unwrap(), Event::AckPartCommitment(1));
+        assert_eq!(merged_rx.try_recv().unwrap(), Event::AckPartCommitment(2));
+        assert_eq!(merged_rx.try_recv().unwrap(), Event::Stop);
+        assert!(merged_rx.try_recv().is_err());
+
+        assert_eq!(all_rx.try_recv().unwrap(), Event::AckPartCommitment(1));
+        assert_eq!(all_rx.try_recv().unwrap(), Event::AckPartCommitment(2));
+        assert_eq!(all_rx.try_recv().unwrap(), Event::AckPartCommitment(3));
+        assert_eq!(all_rx.try_recv().unwrap(), Event::Stop);
+        assert!(all_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn saturated_drop_oldest_topics_overwrite_pending_events() {
+        let mut router = EventRouter::new();
+
+        router.add_topic(Topic::Consensus, Some(2));
+
+        let mut subscriber_rx = router.subscribe(&Topic::Consensus).unwrap();
+
+        assert!(router
+            .try_publish(&Topic::Consensus, Event::AckPartCommitment(1))
+            .is_ok());
+        assert!(router
+            .try_publish(&Topic::Consensus, Event::AckPartCommitment(2))
+            .is_ok());
+        assert!(router.is_saturated(&Topic::Consensus));
+        assert_eq!(
+            router.try_publish(&Topic::Consensus, Event::AckPartCommitment(3)),
+            Err(PublishError::Saturated(Topic::Consensus))
+        );
+
+        assert!(matches!(
+            subscriber_rx.try_recv(),
+            Err(broadcast::error::TryRecvError::Lagged(1))
+        ));
+        assert_eq!(
+            subscriber_rx.try_recv().unwrap(),
+            Event::AckPartCommitment(2)
+        );
+        assert_eq!(
+            subscriber_rx.try_recv().unwrap(),
+            Event::AckPartCommitment(3)
+        );
+        assert_eq!(router.metrics().topic(&Topic::Consensus).dropped, 1);
+    }
+
+    #[tokio::test]
+    async fn saturated_drop_new_topics_drop_published_events() {
+        let mut router = EventRouter::new();
+
+        router.add_topic(Topic::Consensus, Some(2));
+        router.set_backpressure_policy(Topic::Consensus, BackpressurePolicy::DropNew);
+
+        let mut subscriber_rx = router.subscribe(&Topic::Consensus).unwrap();
+
+        for i in 1..=2 {
+            assert!(router
+                .try_publish(&Topic::Consensus, Event::AckPartCommitment(i))
+                .is_ok());
+        }
+
+        assert_eq!(
+            router.try_publish(&Topic::Consensus, Event::AckPartCommitment(3)),
+            Err(PublishError::Saturated(Topic::Consensus))
+        );
+
+        assert_eq!(
+            subscriber_rx.try_recv().unwrap(),
+            Event::AckPartCommitment(1)
+        );
+        assert_eq!(
+            subscriber_rx.try_recv().unwrap(),
+            Event::AckPartCommitment(2)
+        );
+        assert!(subscriber_rx.try_recv().is_err());
+
+        let metrics = router.metrics().topic(&Topic::Consensus);
+        assert_eq!(metrics.delivered, 2);
+        assert_eq!(metrics.dropped, 1);
+    }
+
+    #[tokio::test]
+    async fn saturated_block_topics_wait_for_subscribers_to_catch_up() {
+        let (event_tx, mut event_rx) = unbounded_channel::<DirectedEvent>();
+        let mut router = EventRouter::new();
+
+        router.add_topic(Topic::Control, Some(10));
+        router.add_topic(Topic::Consensus, Some(2));
+        router.set_backpressure_policy(Topic::Consensus, BackpressurePolicy::Block);
+
+        let mut subscriber_rx = router.subscribe(&Topic::Consensus).unwrap();
+        let _control_rx = router.subscribe(&Topic::Control).unwrap();
+
+        // NOTE: blocking topics turn events away rather than wait when
+        // published to directly
+        router
+            .try_publish(&Topic::Consensus, Event::AckPartCommitment(0))
+            .unwrap();
+        router
+            .try_publish(&Topic::Consensus, Event::AckPartCommitment(1))
+            .unwrap();
+        assert_eq!(
+            router.try_publish(&Topic::Consensus, Event::AckPartCommitment(2)),
+            Err(PublishError::Saturated(Topic::Consensus))
+        );
+
+        let metrics = router.metrics();
+
+        let handle = tokio::spawn(async move {
+            router.start(&mut event_rx).await;
+        });
+
+        for i in 2..10 {
+            event_tx
+                .send((Topic::Consensus, Event::AckPartCommitment(i)))
+                .unwrap();
+        }
+
+        // NOTE: the subscriber is slower than the publisher, yet misses none
+        // of the events
+        for i in 0..10 {
+            tokio::time::sleep(Duration::from_millis(2)).await;
+            assert_eq!(
+                subscriber_rx.recv().await.unwrap(),
+                Event::AckPartCommitment(i)
+            );
+        }
+
+        event_tx.send((Topic::Control, Event::Stop)).unwrap();
+        handle.await.unwrap();
+
+        assert_eq!(metrics.topic(&Topic::Consensus).dropped, 0);
+    }
+
+    #[tokio::test]
+    async fn should_count_events_sent_to_topics_without_subscribers() {
+        let mut router = EventRouter::new();
+
+        router.add_topic(Topic::Storage, Some(10));
+        router.fan_out_event(Event::NoOp, &Topic::Storage);
+
+        assert_eq!(router.dropped_events().get(&Topic::Storage), 1);
+    }
+
+    #[tokio::test]
+    async fn should_count_events_received_on_each_topic() {
+        let (event_tx, mut event_rx) = unbounded_channel::<DirectedEvent>();
+        let mut router = EventRouter::new();
+
+        router.add_topic(Topic::Control, Some(10));
+        router.add_topic(Topic::Storage, Some(10));
+
+        let _control_rx = router.subscribe(&Topic::Control).unwrap();
+        let _storage_rx = router.subscribe(&Topic::Storage).unwrap();
+        let processed_events = router.processed_events();
+
+        let handle = tokio::spawn(async move {
+            router.start(&mut event_rx).await;
+        });
+
+        event_tx.send((Topic::Storage, Event::NoOp)).unwrap();
+        event_tx.send((Topic::Storage, Event::NoOp)).unwrap();
+        event_tx.send((Topic::Control, Event::Stop)).unwrap();
+
+        handle.await.unwrap();
+
+        assert_eq!(processed_events.get(&Topic::Storage), 2);
+        assert_eq!(processed_events.get(&Topic::Control), 1);
+        assert_eq!(processed_events.get(&Topic::Consensus), 0);
+    }
 }
 
 // NOTE: kept for reference
@@ -0,0 +1,69 @@
+use std::fmt::Debug;
+
+use crate::txn::{TxAmount, Txn};
+
+/// Computes the minimum fee a transaction has to offer for the node to accept
+/// it. Transactions may offer more to be prioritized over others.
+pub trait FeeModel: Debug + Send + Sync {
+    /// Minimum fee `txn` has to offer
+    fn estimate_fee(&self, txn: &Txn) -> TxAmount;
+}
+
+/// Charges every transaction the same fee, regardless of its size
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FlatFee {
+    pub base_fee: TxAmount,
+}
+
+impl FeeModel for FlatFee {
+    fn estimate_fee(&self, _txn: &Txn) -> TxAmount {
+        self.base_fee
+    }
+}
+
+/// Charges a base fee plus a fee for every byte of the encoded transaction
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PerByteFee {
+    pub base_fee: TxAmount,
+    pub fee_per_byte: TxAmount,
+}
+
+impl FeeModel for PerByteFee {
+    fn estimate_fee(&self, txn: &Txn) -> TxAmount {
+        let size = txn.encode().len() as TxAmount;
+
+        self.base_fee
+            .saturating_add(self.fee_per_byte.saturating_mul(size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txn::{null_txn, Token};
+
+    #[test]
+    fn per_byte_fees_grow_with_the_txn_size() {
+        let model = PerByteFee {
+            base_fee: 100,
+            fee_per_byte: 2,
+        };
+
+        let small = null_txn();
+        let mut large = null_txn();
+        large.set_token(Token {
+            name: "a".repeat(256),
+            ..Token::default()
+        });
+
+        let small_size = small.encode().len() as TxAmount;
+        let large_size = large.encode().len() as TxAmount;
+
+        assert_eq!(model.estimate_fee(&small), 100 + 2 * small_size);
+        assert_eq!(model.estimate_fee(&large), 100 + 2 * large_size);
+        assert!(model.estimate_fee(&large) >= model.estimate_fee(&small) + 2 * 256);
+
+        let flat = FlatFee { base_fee: 100 };
+        assert_eq!(flat.estimate_fee(&small), flat.estimate_fee(&large));
+    }
+}
@@ -11,7 +11,7 @@ use secp256k1::generate_keypair;
 use sha2::Sha256;
 use sha256::Sha256Digest;
 
-use crate::{txn::Txn, Error};
+use crate::{keyfile, txn::Txn, Error};
 
 pub fn gen_sha256_digest_string<D: Sha256Digest>(data: D) -> String {
     sha256::digest(data)
@@ -45,11 +45,35 @@ pub fn size_of_txn_list(txns: &LinkedHashMap<String, Txn>) -> usize {
         .sum()
 }
 
-fn read_keypair_file<F: AsRef<Path>>(path: F) -> crate::Result<(SecretKey, PublicKey)> {
+/// Reads a keypair file, asking for its passphrase through
+/// `keyfile::read_passphrase` if it's encrypted
+pub fn read_keypair_file<F: AsRef<Path>>(path: F) -> crate::Result<(SecretKey, PublicKey)> {
+    read_keypair_file_with(path, keyfile::read_passphrase)
+}
+
+/// Reads a keypair file that may be encrypted with `passphrase`
+pub fn read_keypair_file_with_passphrase<F: AsRef<Path>>(
+    path: F,
+    passphrase: &str,
+) -> crate::Result<(SecretKey, PublicKey)> {
+    read_keypair_file_with(path, || Ok(passphrase.to_string()))
+}
+
+fn read_keypair_file_with<F, P>(path: F, passphrase: P) -> crate::Result<(SecretKey, PublicKey)>
+where
+    F: AsRef<Path>,
+    P: FnOnce() -> keyfile::Result<String>,
+{
+    let mut contents = String::new();
+
     match crate::storage_utils::read_file(path.as_ref()) {
-        Ok(mut file) => read_keypair(&mut file),
-        Err(e) => Err(Error::Other(e.to_string())),
-    }
+        Ok(mut file) => file.read_to_string(&mut contents)?,
+        Err(e) => return Err(Error::Other(e.to_string())),
+    };
+
+    let body = keyfile::decode(&contents, passphrase)?;
+
+    read_keypair(&mut body.as_bytes())
 }
 
 pub fn read_or_generate_keypair_file<F: AsRef<Path>>(
@@ -57,6 +81,8 @@ pub fn read_or_generate_keypair_file<F: AsRef<Path>>(
 ) -> crate::Result<(SecretKey, PublicKey)> {
     let keypair = match read_keypair_file(&path) {
         Ok(keypair) => keypair,
+        // NOTE: encrypted keypairs that can't be decrypted are never replaced
+        Err(err @ Error::Keyfile(_)) => return Err(err),
         Err(err) => {
             telemetry::warn!("Failed to read keypair file: {}", err);
             telemetry::info!("Generating new keypair");
@@ -77,6 +103,24 @@ pub fn read_or_generate_keypair_file<F: AsRef<Path>>(
 pub fn write_keypair_file<F: AsRef<Path>>(
     path: F,
     keypair: &(SecretKey, PublicKey),
+) -> crate::Result<()> {
+    write_keypair_file_with(path, keypair, None)
+}
+
+/// Writes a keypair file encrypted with `passphrase`, see `keyfile::encode`
+pub fn write_encrypted_keypair_file<F: AsRef<Path>>(
+    path: F,
+    keypair: &(SecretKey, PublicKey),
+    passphrase: &str,
+) -> crate::Result<()> {
+    write_keypair_file_with(path, keypair, Some(passphrase))
+}
+
+/// Writes a keypair file, encrypted if a `passphrase` is provided
+pub fn write_keypair_file_with<F: AsRef<Path>>(
+    path: F,
+    keypair: &(SecretKey, PublicKey),
+    passphrase: Option<&str>,
 ) -> crate::Result<()> {
     let (secret_key, public_key) = keypair;
 
@@ -86,8 +130,8 @@ pub fn write_keypair_file<F: AsRef<Path>>(
     let pk_ser = bincode::serialize(public_key)
         .map_err(|err| crate::Error::Other(format!("failed to serialize public key: {err}")))?;
 
-    // TODO: store keypairs securely
-    let contents = format!("{}\n{}", hex::encode(sk_ser), hex::encode(pk_ser));
+    let body = format!("{}\n{}", hex::encode(sk_ser), hex::encode(pk_ser));
+    let contents = keyfile::encode(&body, passphrase)?;
 
     std::fs::write(path, contents)
         .map_err(|err| crate::Error::Other(format!("failed to write keypair file: {err}")))?;
@@ -116,3 +160,72 @@ fn read_keypair<R: Read>(reader: &mut R) -> crate::Result<(SecretKey, PublicKey)
 
     Ok((deserialized_sk, deserialized_pk))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tmp_keypair_path() -> PathBuf {
+        std::env::temp_dir().join(format!("keypair-{}", generate_random_string()))
+    }
+
+    #[test]
+    fn plaintext_keypair_files_round_trip() {
+        let path = tmp_keypair_path();
+        let keypair = generate_keypair(&mut rand::thread_rng());
+
+        write_keypair_file(&path, &keypair).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(contents.starts_with(keyfile::PLAINTEXT_HEADER));
+        assert_eq!(read_keypair_file(&path).unwrap(), keypair);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn encrypted_keypair_files_round_trip() {
+        let path = tmp_keypair_path();
+        let keypair = generate_keypair(&mut rand::thread_rng());
+
+        write_encrypted_keypair_file(&path, &keypair, "correct horse").unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(keyfile::is_encrypted(&contents));
+        assert!(!contents.contains(&hex::encode(bincode::serialize(&keypair.0).unwrap())));
+
+        assert_eq!(
+            read_keypair_file_with_passphrase(&path, "correct horse").unwrap(),
+            keypair
+        );
+        assert!(matches!(
+            read_keypair_file_with_passphrase(&path, "battery staple"),
+            Err(Error::Keyfile(keyfile::KeyfileError::WrongPassphrase))
+        ));
+
+        // NOTE: a keypair that can't be decrypted must not be replaced by a new one
+        std::env::set_var(keyfile::PASSPHRASE_ENV_VAR, "battery staple");
+        assert!(read_or_generate_keypair_file(&path).is_err());
+        std::env::remove_var(keyfile::PASSPHRASE_ENV_VAR);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), contents);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn legacy_keypair_files_still_load() {
+        let path = tmp_keypair_path();
+        let (secret_key, public_key) = generate_keypair(&mut rand::thread_rng());
+
+        let legacy_contents = format!(
+            "{}\n{}",
+            hex::encode(bincode::serialize(&secret_key).unwrap()),
+            hex::encode(bincode::serialize(&public_key).unwrap())
+        );
+        std::fs::write(&path, legacy_contents).unwrap();
+
+        assert_eq!(read_keypair_file(&path).unwrap(), (secret_key, public_key));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
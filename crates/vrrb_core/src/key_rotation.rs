@@ -0,0 +1,131 @@
+use primitives::{Address, PublicKey, SecretKey, SerializedPublicKey};
+use secp256k1::{ecdsa::Signature, Message, Secp256k1};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::account::{Account, AccountNonce};
+
+/// How long transactions signed with an account's previous key are still
+/// accepted once the account's key is rotated, in seconds
+pub const KEY_ROTATION_GRACE_PERIOD_SECS: i64 = 24 * 60 * 60;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum KeyRotationError {
+    #[error("invalid key rotation signature: {0}")]
+    BadSignature(String),
+
+    #[error("invalid public key: {0}")]
+    InvalidPublicKey(String),
+
+    #[error("key rotation nonce {provided} does not match the account's nonce {expected}")]
+    NonceMismatch {
+        provided: AccountNonce,
+        expected: AccountNonce,
+    },
+}
+
+/// Replaces the public key of the account stored under `address`. It must be
+/// signed with the key being replaced, and is only valid at the account's
+/// current `nonce` so it can't be replayed once applied.
+///
+/// The account keeps its address, only the key its owner signs with changes.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyRotation {
+    pub address: Address,
+    pub new_pubkey: SerializedPublicKey,
+    pub nonce: AccountNonce,
+    pub signature: Signature,
+}
+
+impl KeyRotation {
+    /// Builds a rotation of the account under `address` to `new_pubkey`,
+    /// signed with `current_secret_key`
+    pub fn new(
+        address: Address,
+        new_pubkey: PublicKey,
+        nonce: AccountNonce,
+        current_secret_key: &SecretKey,
+    ) -> Self {
+        let new_pubkey = new_pubkey.serialize().to_vec();
+        let message = signing_message(&address, &new_pubkey, nonce);
+
+        Self {
+            address,
+            new_pubkey,
+            nonce,
+            signature: Secp256k1::signing_only().sign_ecdsa(&message, current_secret_key),
+        }
+    }
+
+    /// Checks the rotation was signed with the account's current key and
+    /// targets its current nonce
+    pub fn validate_against(&self, account: &Account) -> Result<(), KeyRotationError> {
+        PublicKey::from_slice(&self.new_pubkey)
+            .map_err(|err| KeyRotationError::InvalidPublicKey(err.to_string()))?;
+
+        let current_pubkey = PublicKey::from_slice(&account.pubkey)
+            .map_err(|err| KeyRotationError::InvalidPublicKey(err.to_string()))?;
+
+        Secp256k1::verification_only()
+            .verify_ecdsa(
+                &signing_message(&self.address, &self.new_pubkey, self.nonce),
+                &self.signature,
+                &current_pubkey,
+            )
+            .map_err(|err| KeyRotationError::BadSignature(err.to_string()))?;
+
+        if self.nonce != account.nonce {
+            return Err(KeyRotationError::NonceMismatch {
+                provided: self.nonce,
+                expected: account.nonce,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+fn signing_message(address: &Address, new_pubkey: &[u8], nonce: AccountNonce) -> Message {
+    let mut hasher = Sha256::new();
+    hasher.update(address.public_key().serialize());
+    hasher.update(new_pubkey);
+    hasher.update(nonce.to_be_bytes());
+
+    Message::from_slice(&hasher.finalize()).expect("sha256 digests are 32 bytes long")
+}
+
+#[cfg(test)]
+mod tests {
+    use primitives::generate_account_keypair;
+
+    use super::*;
+
+    #[test]
+    fn rotations_must_be_signed_with_the_current_key() {
+        let (secret_key, public_key) = generate_account_keypair();
+        let (other_secret_key, _) = generate_account_keypair();
+        let (_, new_public_key) = generate_account_keypair();
+
+        let address = Address::new(public_key);
+        let account = Account::new(public_key);
+
+        let rotation = KeyRotation::new(address.clone(), new_public_key, 0, &secret_key);
+        assert_eq!(rotation.validate_against(&account), Ok(()));
+
+        let forged = KeyRotation::new(address.clone(), new_public_key, 0, &other_secret_key);
+        assert!(matches!(
+            forged.validate_against(&account),
+            Err(KeyRotationError::BadSignature(_))
+        ));
+
+        let stale = KeyRotation::new(address, new_public_key, 3, &secret_key);
+        assert_eq!(
+            stale.validate_against(&account),
+            Err(KeyRotationError::NonceMismatch {
+                provided: 3,
+                expected: 0
+            })
+        );
+    }
+}
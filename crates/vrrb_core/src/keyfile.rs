@@ -0,0 +1,286 @@
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm,
+    Nonce,
+};
+use rand::RngCore;
+use thiserror::Error;
+
+/// First line of keyfiles holding their keys in plaintext
+pub const PLAINTEXT_HEADER: &str = "vrrb-keyfile v1 plaintext";
+
+/// First line of keyfiles whose keys are encrypted with AES-256-GCM under a
+/// key derived from a passphrase with scrypt
+pub const ENCRYPTED_HEADER: &str = "vrrb-keyfile v1 scrypt-aes256gcm";
+
+/// Environment variable read for a keyfile passphrase before prompting for it
+pub const PASSPHRASE_ENV_VAR: &str = "VRRB_KEYFILE_PASSPHRASE";
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const KEY_LEN: usize = 32;
+
+#[derive(Debug, Error)]
+pub enum KeyfileError {
+    #[error("keyfile is malformed: {0}")]
+    Malformed(String),
+
+    #[error("unable to decrypt keyfile, the passphrase is likely wrong")]
+    WrongPassphrase,
+
+    #[error("keyfile is encrypted but no passphrase was provided: {0}")]
+    PassphraseUnavailable(String),
+
+    #[error("invalid key derivation parameters: {0}")]
+    InvalidParams(String),
+
+    #[error("unable to encrypt keyfile: {0}")]
+    Encryption(String),
+}
+
+pub type Result<T> = std::result::Result<T, KeyfileError>;
+
+/// scrypt cost parameters a keyfile's encryption key was derived with. They
+/// are stored alongside the ciphertext so they can be raised without breaking
+/// existing files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        let recommended = scrypt::Params::recommended();
+
+        Self {
+            log_n: recommended.log_n(),
+            r: recommended.r(),
+            p: recommended.p(),
+        }
+    }
+}
+
+impl KdfParams {
+    fn derive_key(&self, passphrase: &str, salt: &[u8]) -> Result<[u8; KEY_LEN]> {
+        let params = scrypt::Params::new(self.log_n, self.r, self.p)
+            .map_err(|err| KeyfileError::InvalidParams(err.to_string()))?;
+
+        let mut key = [0u8; KEY_LEN];
+        scrypt::scrypt(passphrase.as_bytes(), salt, &params, &mut key)
+            .map_err(|err| KeyfileError::InvalidParams(err.to_string()))?;
+
+        Ok(key)
+    }
+}
+
+/// Wraps the `body` of a keyfile with a header, encrypting it first when a
+/// `passphrase` is provided.
+///
+/// Encrypted keyfiles are laid out as:
+///
+/// ```text
+/// vrrb-keyfile v1 scrypt-aes256gcm
+/// <log_n> <r> <p>
+/// <hex encoded salt>
+/// <hex encoded nonce>
+/// <hex encoded ciphertext>
+/// ```
+pub fn encode(body: &str, passphrase: Option<&str>) -> Result<String> {
+    encode_with_params(body, passphrase, KdfParams::default())
+}
+
+pub fn encode_with_params(
+    body: &str,
+    passphrase: Option<&str>,
+    params: KdfParams,
+) -> Result<String> {
+    let passphrase = match passphrase {
+        Some(passphrase) => passphrase,
+        None => return Ok(format!("{PLAINTEXT_HEADER}\n{body}")),
+    };
+
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    let key = params.derive_key(passphrase, &salt)?;
+
+    let ciphertext = Aes256Gcm::new_from_slice(&key)
+        .map_err(|err| KeyfileError::Encryption(err.to_string()))?
+        .encrypt(Nonce::from_slice(&nonce), body.as_bytes())
+        .map_err(|err| KeyfileError::Encryption(err.to_string()))?;
+
+    Ok(format!(
+        "{ENCRYPTED_HEADER}\n{} {} {}\n{}\n{}\n{}",
+        params.log_n,
+        params.r,
+        params.p,
+        hex::encode(salt),
+        hex::encode(nonce),
+        hex::encode(ciphertext)
+    ))
+}
+
+/// Returns true if `contents` are those of an encrypted keyfile
+pub fn is_encrypted(contents: &str) -> bool {
+    contents.lines().next().map(str::trim) == Some(ENCRYPTED_HEADER)
+}
+
+/// Returns the body of a keyfile, stripping its header and decrypting it if
+/// needed. `passphrase` is only called for encrypted keyfiles.
+///
+/// Keyfiles written before headers were introduced are returned as they are.
+pub fn decode<P>(contents: &str, passphrase: P) -> Result<String>
+where
+    P: FnOnce() -> Result<String>,
+{
+    let (header, rest) = contents.split_once('\n').unwrap_or((contents, ""));
+
+    match header.trim() {
+        PLAINTEXT_HEADER => Ok(rest.to_string()),
+        ENCRYPTED_HEADER => decrypt(rest, &passphrase()?),
+        header if header.starts_with("vrrb-keyfile") => Err(KeyfileError::Malformed(format!(
+            "unsupported keyfile format: {header}"
+        ))),
+        _ => Ok(contents.to_string()),
+    }
+}
+
+fn decrypt(contents: &str, passphrase: &str) -> Result<String> {
+    let mut lines = contents.lines().map(str::trim);
+
+    let mut next_line = |name: &str| {
+        lines
+            .next()
+            .ok_or_else(|| KeyfileError::Malformed(format!("missing {name}")))
+    };
+
+    let params = parse_params(next_line("key derivation parameters")?)?;
+    let salt = decode_hex(next_line("salt")?, "salt")?;
+    let nonce = decode_hex(next_line("nonce")?, "nonce")?;
+    let ciphertext = decode_hex(next_line("ciphertext")?, "ciphertext")?;
+
+    if nonce.len() != NONCE_LEN {
+        return Err(KeyfileError::Malformed(format!(
+            "nonce must be {NONCE_LEN} bytes long"
+        )));
+    }
+
+    let key = params.derive_key(passphrase, &salt)?;
+
+    let body = Aes256Gcm::new_from_slice(&key)
+        .map_err(|err| KeyfileError::Encryption(err.to_string()))?
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+        .map_err(|_| KeyfileError::WrongPassphrase)?;
+
+    String::from_utf8(body).map_err(|err| KeyfileError::Malformed(err.to_string()))
+}
+
+fn parse_params(line: &str) -> Result<KdfParams> {
+    let values = line
+        .split_whitespace()
+        .map(|value| value.parse::<u32>())
+        .collect::<std::result::Result<Vec<u32>, _>>()
+        .map_err(|err| KeyfileError::Malformed(format!("invalid scrypt parameter: {err}")))?;
+
+    match values[..] {
+        [log_n, r, p] => Ok(KdfParams {
+            log_n: u8::try_from(log_n)
+                .map_err(|err| KeyfileError::InvalidParams(err.to_string()))?,
+            r,
+            p,
+        }),
+        _ => Err(KeyfileError::Malformed(
+            "expected 3 scrypt parameters".to_string(),
+        )),
+    }
+}
+
+fn decode_hex(value: &str, name: &str) -> Result<Vec<u8>> {
+    hex::decode(value).map_err(|err| KeyfileError::Malformed(format!("invalid {name}: {err}")))
+}
+
+/// Returns the passphrase of an encrypted keyfile, read from
+/// `PASSPHRASE_ENV_VAR` if it's set or prompted for on the terminal otherwise
+pub fn read_passphrase() -> Result<String> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV_VAR) {
+        return Ok(passphrase);
+    }
+
+    rpassword::prompt_password("Keyfile passphrase: ")
+        .map_err(|err| KeyfileError::PassphraseUnavailable(err.to_string()))
+}
+
+/// Returns the passphrase to encrypt a new keyfile with. Like
+/// `read_passphrase` it's read from `PASSPHRASE_ENV_VAR` when set, otherwise
+/// it's prompted for twice to catch typos.
+pub fn read_new_passphrase() -> Result<String> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV_VAR) {
+        return Ok(passphrase);
+    }
+
+    let passphrase = rpassword::prompt_password("New keyfile passphrase: ")
+        .map_err(|err| KeyfileError::PassphraseUnavailable(err.to_string()))?;
+
+    let confirmation = rpassword::prompt_password("Confirm keyfile passphrase: ")
+        .map_err(|err| KeyfileError::PassphraseUnavailable(err.to_string()))?;
+
+    if passphrase != confirmation {
+        return Err(KeyfileError::PassphraseUnavailable(
+            "passphrases don't match".to_string(),
+        ));
+    }
+
+    if passphrase.is_empty() {
+        return Err(KeyfileError::PassphraseUnavailable(
+            "passphrase can't be empty".to_string(),
+        ));
+    }
+
+    Ok(passphrase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cheap parameters so tests don't spend seconds deriving keys
+    const TEST_PARAMS: KdfParams = KdfParams {
+        log_n: 4,
+        r: 8,
+        p: 1,
+    };
+
+    #[test]
+    fn encrypted_keyfiles_need_the_right_passphrase() {
+        let contents = encode_with_params("secret\nkeys", Some("hunter2"), TEST_PARAMS).unwrap();
+
+        assert!(is_encrypted(&contents));
+        assert!(!contents.contains("secret"));
+
+        let body = decode(&contents, || Ok("hunter2".to_string())).unwrap();
+        assert_eq!(body, "secret\nkeys");
+
+        assert!(matches!(
+            decode(&contents, || Ok("hunter3".to_string())),
+            Err(KeyfileError::WrongPassphrase)
+        ));
+    }
+
+    #[test]
+    fn plaintext_and_legacy_keyfiles_never_ask_for_a_passphrase() {
+        let no_passphrase = || -> Result<String> { panic!("asked for a passphrase") };
+
+        let contents = encode("plain\nkeys", None).unwrap();
+        assert!(!is_encrypted(&contents));
+        assert_eq!(decode(&contents, no_passphrase).unwrap(), "plain\nkeys");
+
+        assert_eq!(
+            decode("legacy\nkeys", no_passphrase).unwrap(),
+            "legacy\nkeys"
+        );
+    }
+}
@@ -9,14 +9,15 @@ use hbbft::crypto::{
     serde_impl::SerdeSecret,
     PublicKey as Validator_Pk,
     SecretKey as Validator_Sk,
+    Signature as Validator_Signature,
 };
-use primitives::SerializedSecretKey as SecretKeyBytes;
+use primitives::{Address, SerializedSecretKey as SecretKeyBytes};
 use rand::SeedableRng;
 use secp256k1::{ecdsa::Signature, Message, Secp256k1, SecretKey};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::storage_utils;
+use crate::{keyfile, storage_utils};
 
 pub type MinerSk = secp256k1::SecretKey;
 pub type MinerPk = secp256k1::PublicKey;
@@ -24,10 +25,122 @@ pub type MinerPk = secp256k1::PublicKey;
 pub type SecretKeys = (MinerSk, Validator_Sk);
 pub type PublicKeys = (MinerPk, Validator_Pk);
 
+/// secp256k1 keypair signing transactions and other account level messages.
+/// Account addresses are derived from its public key.
+///
+/// It's a different type than `ConsensusKeyPair`, so one can't be passed where
+/// the other is expected:
+///
+/// ```
+/// use vrrb_core::{keypair::KeyPair, txn::Txn};
+///
+/// fn sign(txn: &mut Txn, keypair: &KeyPair) {
+///     txn.sign(keypair.txn_keypair().secret_key());
+/// }
+/// ```
+///
+/// ```compile_fail
+/// use vrrb_core::{keypair::KeyPair, txn::Txn};
+///
+/// fn sign(txn: &mut Txn, keypair: &KeyPair) {
+///     txn.sign(keypair.consensus_keypair().secret_key());
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct TransactionKeyPair {
+    secret_key: MinerSk,
+    public_key: MinerPk,
+}
+
+impl TransactionKeyPair {
+    pub fn random() -> Self {
+        let (secret_key, public_key) = Secp256k1::new().generate_keypair(&mut rand::thread_rng());
+
+        Self {
+            secret_key,
+            public_key,
+        }
+    }
+
+    pub fn secret_key(&self) -> &MinerSk {
+        &self.secret_key
+    }
+
+    pub fn public_key(&self) -> &MinerPk {
+        &self.public_key
+    }
+
+    /// Address of the account owned by this keypair
+    pub fn address(&self) -> Address {
+        Address::new(self.public_key)
+    }
+
+    /// Signs the sha256 digest of `msg`
+    pub fn sign(&self, msg: &[u8]) -> Signature {
+        let msg = Message::from_hashed_data::<secp256k1::hashes::sha256::Hash>(msg);
+
+        Secp256k1::signing_only().sign_ecdsa(&msg, &self.secret_key)
+    }
+}
+
+impl From<MinerSk> for TransactionKeyPair {
+    fn from(secret_key: MinerSk) -> Self {
+        Self {
+            public_key: MinerPk::from_secret_key(&Secp256k1::signing_only(), &secret_key),
+            secret_key,
+        }
+    }
+}
+
+/// Transaction keypairs are stored by wallets as plain `(secret, public)`
+/// tuples, see `helpers::write_keypair_file`
+impl From<TransactionKeyPair> for (MinerSk, MinerPk) {
+    fn from(keypair: TransactionKeyPair) -> Self {
+        (keypair.secret_key, keypair.public_key)
+    }
+}
+
+/// hbbft threshold keypair a node takes part in consensus with
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConsensusKeyPair {
+    secret_key: Validator_Sk,
+    public_key: Validator_Pk,
+}
+
+impl ConsensusKeyPair {
+    pub fn random() -> Self {
+        Self::from(Validator_Sk::random())
+    }
+
+    pub fn secret_key(&self) -> &Validator_Sk {
+        &self.secret_key
+    }
+
+    pub fn public_key(&self) -> &Validator_Pk {
+        &self.public_key
+    }
+
+    pub fn sign<M: AsRef<[u8]>>(&self, msg: M) -> Validator_Signature {
+        self.secret_key.sign(msg)
+    }
+}
+
+impl From<Validator_Sk> for ConsensusKeyPair {
+    fn from(secret_key: Validator_Sk) -> Self {
+        Self {
+            public_key: secret_key.public_key(),
+            secret_key,
+        }
+    }
+}
+
+/// A node's keys: the keypair it signs transactions with and the one it takes
+/// part in consensus with. Both are always accessed through their own typed
+/// accessors so they can't be mixed up.
 #[derive(Debug, Clone, Deserialize)]
 pub struct KeyPair {
-    pub miner_kp: (MinerSk, MinerPk),
-    pub validator_kp: (Validator_Sk, Validator_Pk),
+    txn_kp: TransactionKeyPair,
+    consensus_kp: ConsensusKeyPair,
 }
 
 /// Alias for KeyPair, to avoid frustrations because of subtle typos
@@ -53,21 +166,90 @@ pub enum KeyPairError {
     SignatureVerificationFailed(String),
     #[error("Failed to de-serialize {0} key ")]
     InvalidKey(String),
+    #[error("Failed to open keypair file ,details : {0}")]
+    Keyfile(#[from] keyfile::KeyfileError),
+    #[error("Malformed {0:?} keypair file ,details : {1}")]
+    MalformedFile(KeypairFormat, String),
+    #[error("Unsupported keypair file format ,details : {0}")]
+    UnsupportedFormat(String),
 }
 
 pub type Result<T> = std::result::Result<T, KeyPairError>;
 
+/// Bytes `KeypairFormat::Binary` keypair files start with
+pub const BINARY_KEYPAIR_MAGIC: &[u8] = b"VRRBKP\x01";
+
+/// Tag of the PEM block `KeypairFormat::Pem` keypair files hold
+pub const PEM_KEYPAIR_TAG: &str = "VRRB KEYPAIR";
+
+/// Layouts a `Keypair` can be written to a file in, see
+/// `write_keypair_file_as`. Files are told apart by their contents when read,
+/// so `read_keypair_file` reads any of them regardless of their extension.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeypairFormat {
+    /// Hex encoded secret keys, one per line, behind a keyfile header. The
+    /// layout keypair files have always been written in.
+    #[default]
+    Hex,
+    /// JSON object holding every key hex encoded:
+    ///
+    /// ```json
+    /// {
+    ///   "transaction_secret_key": "...",
+    ///   "transaction_public_key": "...",
+    ///   "consensus_secret_key": "...",
+    ///   "consensus_public_key": "..."
+    /// }
+    /// ```
+    Json,
+    /// The binary layout, armored as a `VRRB KEYPAIR` PEM block
+    Pem,
+    /// `BINARY_KEYPAIR_MAGIC` followed by the bincode encoded secret keys
+    Binary,
+}
+
+impl KeypairFormat {
+    /// Guesses the format a keypair file should be written in from its
+    /// extension: `.json`, `.pem` or `.bin`
+    pub fn from_extension<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_lowercase();
+
+        match extension.as_str() {
+            "json" => Some(KeypairFormat::Json),
+            "pem" => Some(KeypairFormat::Pem),
+            "bin" => Some(KeypairFormat::Binary),
+            _ => None,
+        }
+    }
+
+    /// Detects the format of a keypair file from its contents. Encrypted
+    /// files and files written before formats were introduced are reported
+    /// as `KeypairFormat::Hex`.
+    pub fn detect(contents: &[u8]) -> Self {
+        if contents.starts_with(BINARY_KEYPAIR_MAGIC) {
+            return KeypairFormat::Binary;
+        }
+
+        let text = String::from_utf8_lossy(contents);
+        let text = text.trim_start();
+
+        if text.starts_with("-----BEGIN") {
+            KeypairFormat::Pem
+        } else if text.starts_with('{') {
+            KeypairFormat::Json
+        } else {
+            KeypairFormat::Hex
+        }
+    }
+}
+
 impl KeyPair {
     /// Constructs a new, random `Keypair` using thread_rng() which uses RNG
     pub fn random() -> Self {
-        let validator_sk: Validator_Sk = Validator_Sk::random();
-        let validator_pk: Validator_Pk = validator_sk.public_key();
-        let secp = Secp256k1::new();
-        let mut rng = rand::thread_rng();
-        let (miner_sk, miner_pk) = secp.generate_keypair(&mut rng);
         KeyPair {
-            miner_kp: (miner_sk, miner_pk),
-            validator_kp: (validator_sk, validator_pk),
+            txn_kp: TransactionKeyPair::random(),
+            consensus_kp: ConsensusKeyPair::random(),
         }
     }
 
@@ -81,15 +263,26 @@ impl KeyPair {
     ///
     /// A KeyPair struct
     pub fn new(validator_sk: Validator_Sk, miner_sk: MinerSk) -> Self {
-        let secp = Secp256k1::new();
-        let pk = MinerPk::from_secret_key(&secp, &miner_sk);
-        let validator_pk = validator_sk.public_key();
+        Self::from_keypairs(miner_sk.into(), validator_sk.into())
+    }
+
+    pub fn from_keypairs(txn_kp: TransactionKeyPair, consensus_kp: ConsensusKeyPair) -> Self {
         KeyPair {
-            miner_kp: (miner_sk, pk),
-            validator_kp: (validator_sk, validator_pk),
+            txn_kp,
+            consensus_kp,
         }
     }
 
+    /// Keypair this node signs transactions with
+    pub fn txn_keypair(&self) -> &TransactionKeyPair {
+        &self.txn_kp
+    }
+
+    /// Keypair this node takes part in consensus with
+    pub fn consensus_keypair(&self) -> &ConsensusKeyPair {
+        &self.consensus_kp
+    }
+
     /// Returns this `Keypair` as a byte array
     pub fn from_bytes(validator_key_bytes: &[u8], miner_key_bytes: &[u8]) -> Result<KeyPair> {
         let result = bincode::deserialize::<SerdeSecret<Validator_Sk>>(validator_key_bytes);
@@ -125,7 +318,7 @@ impl KeyPair {
     /// Returns  Both Validator and Miner `Secret key` as a byte array
     pub fn to_bytes(&self) -> Result<(Vec<u8>, Vec<u8>)> {
         let mut keys = (vec![], vec![]);
-        match bincode::serialize(&SerdeSecret(self.validator_kp.0.clone())) {
+        match bincode::serialize(&SerdeSecret(self.consensus_kp.secret_key.clone())) {
             Ok(serialized_sk) => {
                 keys.0 = serialized_sk;
             },
@@ -136,13 +329,13 @@ impl KeyPair {
                 ));
             },
         };
-        keys.1 = self.miner_kp.0.secret_bytes().to_vec();
+        keys.1 = self.txn_kp.secret_key.secret_bytes().to_vec();
         Ok(keys)
     }
 
     /// Returns this Validator `PublicKey` as a byte array
     pub fn to_validator_pk_bytes(&self) -> Result<Vec<u8>> {
-        match bincode::serialize(&self.validator_kp.1) {
+        match bincode::serialize(&self.consensus_kp.public_key) {
             Ok(serialized_pk) => Ok(serialized_pk),
             Err(e) => Err(KeyPairError::SerializeKeyError(
                 String::from("validator public"),
@@ -158,13 +351,13 @@ impl KeyPair {
 
     /// Gets this `Keypair`'s SecretKey
     pub fn get_secret_keys(&self) -> (&Validator_Sk, &MinerSk) {
-        (&self.validator_kp.0, &self.miner_kp.0)
+        (self.consensus_kp.secret_key(), self.txn_kp.secret_key())
     }
 
     /// > This function returns a tuple of references to the public keys of the
     /// > validator and miner
     pub fn get_public_keys(&self) -> (&Validator_Pk, &MinerPk) {
-        (&self.validator_kp.1, &self.miner_kp.1)
+        (self.consensus_kp.public_key(), self.txn_kp.public_key())
     }
 
     /// > It takes a message and a secret key, and returns a signature
@@ -251,7 +444,7 @@ impl KeyPair {
     ///
     /// The miner secret key.
     pub fn get_miner_secret_key(&self) -> &MinerSk {
-        &self.miner_kp.0
+        self.txn_kp.secret_key()
     }
 
     /// It returns the public key of the miner.
@@ -260,7 +453,7 @@ impl KeyPair {
     ///
     /// The public key of the miner.
     pub fn get_miner_public_key(&self) -> &MinerPk {
-        &self.miner_kp.1
+        self.txn_kp.public_key()
     }
 
     /// > This function returns the secret key of the validator
@@ -269,7 +462,7 @@ impl KeyPair {
     ///
     /// The validator secret key.
     pub fn get_validator_secret_key(&self) -> &Validator_Sk {
-        &self.validator_kp.0
+        self.consensus_kp.secret_key()
     }
 
     /// It returns the public key of the validator.
@@ -278,7 +471,7 @@ impl KeyPair {
     ///
     /// The public key of the validator.
     pub fn get_validator_public_key(&self) -> &Validator_Pk {
-        &self.validator_kp.1
+        self.consensus_kp.public_key()
     }
 }
 
@@ -341,12 +534,60 @@ fn get_key_bytes(miner_sk_content: &&str) -> Vec<u8> {
 ///
 /// A Result<KeyPair, KeyPairError>
 pub fn read_keypair_file<F: AsRef<Path>>(path: F) -> Result<KeyPair> {
-    match crate::storage_utils::read_file(path.as_ref()) {
-        Ok(mut file) => read_keypair(&mut file),
-        Err(e) => Err(KeyPairError::FailedToReadFromFile(e.to_string())),
+    read_keypair_file_with(path, keyfile::read_passphrase)
+}
+
+/// Reads a `Keypair` from a file that may be encrypted with `passphrase`
+pub fn read_keypair_file_with_passphrase<F: AsRef<Path>>(
+    path: F,
+    passphrase: &str,
+) -> Result<KeyPair> {
+    read_keypair_file_with(path, || Ok(passphrase.to_string()))
+}
+
+fn read_keypair_file_with<F, P>(path: F, passphrase: P) -> Result<KeyPair>
+where
+    F: AsRef<Path>,
+    P: FnOnce() -> keyfile::Result<String>,
+{
+    let mut contents = vec![];
+
+    crate::storage_utils::read_file(path.as_ref())
+        .map_err(|e| KeyPairError::FailedToReadFromFile(e.to_string()))?
+        .read_to_end(&mut contents)
+        .map_err(|e| KeyPairError::FailedToReadFromFile(e.to_string()))?;
+
+    decode_keypair_file(&contents, passphrase)
+}
+
+/// Decodes the contents of a keypair file written in any `KeypairFormat`
+fn decode_keypair_file<P>(contents: &[u8], passphrase: P) -> Result<KeyPair>
+where
+    P: FnOnce() -> keyfile::Result<String>,
+{
+    match KeypairFormat::detect(contents) {
+        KeypairFormat::Binary => decode_binary_keypair(contents),
+        KeypairFormat::Pem => decode_pem_keypair(keypair_file_text(contents)?),
+        KeypairFormat::Json => decode_json_keypair(keypair_file_text(contents)?),
+        KeypairFormat::Hex => {
+            let body = keyfile::decode(keypair_file_text(contents)?, passphrase)?;
+
+            // NOTE: encrypted keyfiles hold JSON and PEM keypairs too
+            match KeypairFormat::detect(body.as_bytes()) {
+                KeypairFormat::Pem => decode_pem_keypair(&body),
+                KeypairFormat::Json => decode_json_keypair(&body),
+                _ => read_keypair(&mut body.as_bytes()),
+            }
+        },
     }
 }
 
+fn keypair_file_text(contents: &[u8]) -> Result<&str> {
+    std::str::from_utf8(contents).map_err(|err| {
+        KeyPairError::FailedToReadFromFile(format!("keypair file is not valid text: {err}"))
+    })
+}
+
 /// Writes a `Keypair` to a `Write` implementor with HEX-encoding
 /// It takes a `KeyPair` and a `Write`r, and writes the serialized `KeyPair` to
 /// the `Write`r
@@ -384,7 +625,176 @@ pub fn write_keypair_file<F: AsRef<Path>>(
     keypair: &KeyPair,
     outfile: F,
 ) -> Result<(String, String)> {
-    let outfile = outfile.as_ref();
+    write_keypair_file_with(keypair, outfile, None)
+}
+
+/// Writes a `Keypair` to a file, encrypted with `passphrase` if one is
+/// provided. See `keyfile::encode` for the layout of encrypted files.
+pub fn write_keypair_file_with<F: AsRef<Path>>(
+    keypair: &KeyPair,
+    outfile: F,
+    passphrase: Option<&str>,
+) -> Result<(String, String)> {
+    let mut body = vec![];
+    let serialized_keypair = write_keypair(keypair, &mut body)?;
+
+    let contents = keyfile::encode(&String::from_utf8_lossy(&body), passphrase)?;
+
+    write_keypair_contents(outfile.as_ref(), contents.as_bytes())?;
+
+    Ok(serialized_keypair)
+}
+
+/// Writes a `Keypair` to a file in the given `format`, encrypted with
+/// `passphrase` if one is provided. Only `KeypairFormat::Binary` files can't
+/// be encrypted.
+pub fn write_keypair_file_as<F: AsRef<Path>>(
+    keypair: &KeyPair,
+    outfile: F,
+    format: KeypairFormat,
+    passphrase: Option<&str>,
+) -> Result<()> {
+    let body = encode_keypair(keypair, format)?;
+
+    let contents = match (format, passphrase) {
+        (KeypairFormat::Binary, Some(_)) => {
+            return Err(KeyPairError::UnsupportedFormat(String::from(
+                "binary keypair files can't be encrypted",
+            )));
+        },
+        // NOTE: plaintext JSON and PEM files are left without a keyfile
+        // header so other tooling can read them as they are
+        (KeypairFormat::Binary | KeypairFormat::Json | KeypairFormat::Pem, None) => body,
+        (_, passphrase) => {
+            keyfile::encode(&String::from_utf8_lossy(&body), passphrase)?.into_bytes()
+        },
+    };
+
+    write_keypair_contents(outfile.as_ref(), &contents)
+}
+
+/// Encodes a `Keypair` in the given `format`, without a keyfile header
+pub fn encode_keypair(keypair: &KeyPair, format: KeypairFormat) -> Result<Vec<u8>> {
+    match format {
+        KeypairFormat::Hex => {
+            let mut body = vec![];
+            write_keypair(keypair, &mut body)?;
+
+            Ok(body)
+        },
+        KeypairFormat::Json => {
+            let json_keypair = JsonKeypair::from_keypair(keypair)?;
+
+            serde_json::to_vec_pretty(&json_keypair).map_err(|err| {
+                KeyPairError::SerializeKeyError(String::from("JSON"), err.to_string())
+            })
+        },
+        KeypairFormat::Binary => encode_binary_keypair(keypair),
+        KeypairFormat::Pem => {
+            let block = pem::Pem {
+                tag: String::from(PEM_KEYPAIR_TAG),
+                contents: encode_binary_keypair(keypair)?,
+            };
+
+            Ok(pem::encode(&block).into_bytes())
+        },
+    }
+}
+
+fn encode_binary_keypair(keypair: &KeyPair) -> Result<Vec<u8>> {
+    let secret_keys = bincode::serialize(&keypair.to_bytes()?)
+        .map_err(|err| KeyPairError::SerializeKeyError(String::from("binary"), err.to_string()))?;
+
+    Ok([BINARY_KEYPAIR_MAGIC, secret_keys.as_slice()].concat())
+}
+
+fn decode_binary_keypair(contents: &[u8]) -> Result<KeyPair> {
+    let secret_keys = contents.strip_prefix(BINARY_KEYPAIR_MAGIC).ok_or_else(|| {
+        KeyPairError::MalformedFile(KeypairFormat::Binary, String::from("missing magic bytes"))
+    })?;
+
+    let (validator_sk, miner_sk) = bincode::deserialize::<(Vec<u8>, Vec<u8>)>(secret_keys)
+        .map_err(|err| KeyPairError::MalformedFile(KeypairFormat::Binary, err.to_string()))?;
+
+    KeyPair::from_bytes(&validator_sk, &miner_sk)
+}
+
+fn decode_pem_keypair(contents: &str) -> Result<KeyPair> {
+    let block = pem::parse(contents)
+        .map_err(|err| KeyPairError::MalformedFile(KeypairFormat::Pem, err.to_string()))?;
+
+    if block.tag != PEM_KEYPAIR_TAG {
+        return Err(KeyPairError::MalformedFile(
+            KeypairFormat::Pem,
+            format!("expected a {PEM_KEYPAIR_TAG} block, found {}", block.tag),
+        ));
+    }
+
+    decode_binary_keypair(&block.contents)
+}
+
+fn decode_json_keypair(contents: &str) -> Result<KeyPair> {
+    let json_keypair = serde_json::from_str::<JsonKeypair>(contents)
+        .map_err(|err| KeyPairError::MalformedFile(KeypairFormat::Json, err.to_string()))?;
+
+    json_keypair.into_keypair()
+}
+
+/// Layout of `KeypairFormat::Json` keypair files, every key hex encoded
+#[derive(Debug, Serialize, Deserialize)]
+struct JsonKeypair {
+    transaction_secret_key: String,
+    transaction_public_key: String,
+    consensus_secret_key: String,
+    consensus_public_key: String,
+}
+
+impl JsonKeypair {
+    fn from_keypair(keypair: &KeyPair) -> Result<Self> {
+        let (consensus_secret_key, transaction_secret_key) = keypair.to_bytes()?;
+
+        Ok(Self {
+            transaction_secret_key: hex::encode(transaction_secret_key),
+            transaction_public_key: hex::encode(keypair.to_miner_pk_bytes()?),
+            consensus_secret_key: hex::encode(consensus_secret_key),
+            consensus_public_key: hex::encode(keypair.to_validator_pk_bytes()?),
+        })
+    }
+
+    /// Rebuilds the keypair from its secret keys, making sure the public
+    /// keys listed alongside them match
+    fn into_keypair(self) -> Result<KeyPair> {
+        let decode = |name: &str, value: &str| {
+            hex::decode(value).map_err(|err| {
+                KeyPairError::MalformedFile(KeypairFormat::Json, format!("invalid {name}: {err}"))
+            })
+        };
+
+        let keypair = KeyPair::from_bytes(
+            &decode("consensus_secret_key", &self.consensus_secret_key)?,
+            &decode("transaction_secret_key", &self.transaction_secret_key)?,
+        )?;
+
+        let expected = Self::from_keypair(&keypair)?;
+
+        if !expected
+            .transaction_public_key
+            .eq_ignore_ascii_case(&self.transaction_public_key)
+            || !expected
+                .consensus_public_key
+                .eq_ignore_ascii_case(&self.consensus_public_key)
+        {
+            return Err(KeyPairError::MalformedFile(
+                KeypairFormat::Json,
+                String::from("public keys don't match the secret keys"),
+            ));
+        }
+
+        Ok(keypair)
+    }
+}
+
+fn write_keypair_contents(outfile: &Path, contents: &[u8]) -> Result<()> {
     if let Some(outdir) = outfile.parent() {
         if let Err(_e) = storage_utils::create_dir(outdir) {
             return Err(KeyPairError::IOError(
@@ -409,7 +819,9 @@ pub fn write_keypair_file<F: AsRef<Path>>(
     .create(true)
     .open(outfile)
     {
-        Ok(mut f) => write_keypair(keypair, &mut f),
+        Ok(mut f) => f
+            .write_all(contents)
+            .map_err(|err| KeyPairError::IOError(err.to_string())),
         Err(_) => Err(KeyPairError::IOError(
             "Failed to open directory for storage of  secret key".to_string(),
         )),
@@ -496,7 +908,10 @@ mod tests {
         let miner_sk = deserialized_key.get_miner_secret_key();
         let miner_pk = deserialized_key.get_miner_public_key();
 
-        assert_eq!(keypair.validator_kp.0.sign(msg), validator_sk.sign(msg));
+        assert_eq!(
+            keypair.consensus_keypair().sign(msg),
+            validator_sk.sign(msg)
+        );
         assert!(validator_pk.verify(&validator_sk.sign(msg), msg));
         let validator_pbytes = deserialized_key.to_validator_pk_bytes().unwrap();
         let validator_pkey = KeyPair::from_validator_pk_bytes(&validator_pbytes).unwrap();
@@ -505,23 +920,23 @@ mod tests {
         let secp = Secp256k1::new();
         let msg = Message::from_hashed_data::<secp256k1::hashes::sha256::Hash>(msg.as_bytes());
         assert_eq!(
-            secp.sign_ecdsa(&msg, &keypair.miner_kp.0),
+            secp.sign_ecdsa(&msg, keypair.txn_keypair().secret_key()),
             secp.sign_ecdsa(&msg, miner_sk)
         );
 
-        let sig = secp.sign_ecdsa(&msg, &keypair.miner_kp.0);
+        let sig = secp.sign_ecdsa(&msg, keypair.txn_keypair().secret_key());
         assert!(secp.verify_ecdsa(&msg, &sig, miner_pk).is_ok());
         let miner_pbytes = deserialized_key.to_miner_pk_bytes().unwrap();
         let miner_pkey = KeyPair::from_miner_pk_bytes(&miner_pbytes).unwrap();
         assert!(secp.verify_ecdsa(&msg, &sig, &miner_pkey).is_ok());
         let sig = KeyPair::ecdsa_sign(
             "Hello VRRB".as_bytes(),
-            keypair.miner_kp.0.secret_bytes().to_vec(),
+            keypair.txn_keypair().secret_key().secret_bytes().to_vec(),
         );
         let status = KeyPair::verify_ecdsa_sign(
             sig.unwrap(),
             "Hello VRRB".as_bytes(),
-            keypair.miner_kp.1.serialize().to_vec(),
+            keypair.txn_keypair().public_key().serialize().to_vec(),
         );
         assert!(status.is_ok());
     }
@@ -532,4 +947,189 @@ mod tests {
         write_keypair_file(&KeyPair::random(), &outfile).unwrap();
         write_keypair_file(&KeyPair::random(), &outfile).unwrap();
     }
+
+    #[test]
+    fn test_keypairs_rebuilt_from_their_secret_keys() {
+        let keypair = KeyPair::random();
+
+        let txn_kp = TransactionKeyPair::from(*keypair.txn_keypair().secret_key());
+        let consensus_kp = ConsensusKeyPair::from(keypair.consensus_keypair().secret_key().clone());
+
+        assert_eq!(&txn_kp, keypair.txn_keypair());
+        assert_eq!(
+            txn_kp.address(),
+            Address::new(*keypair.get_miner_public_key())
+        );
+        assert_eq!(
+            consensus_kp.public_key(),
+            keypair.consensus_keypair().public_key()
+        );
+
+        let (secret_key, public_key) = txn_kp.into();
+        assert_eq!(&secret_key, keypair.get_miner_secret_key());
+        assert_eq!(&public_key, keypair.get_miner_public_key());
+    }
+
+    #[test]
+    fn test_encrypted_keypair_file_round_trip() {
+        let outfile = tmp_file_path("test_encrypted_keypair_file_round_trip.json");
+        let keypair = KeyPair::random();
+
+        write_keypair_file_with(&keypair, &outfile, Some("passphrase")).unwrap();
+
+        let contents = std::fs::read_to_string(&outfile).unwrap();
+        assert!(keyfile::is_encrypted(&contents));
+
+        let read_keypair = read_keypair_file_with_passphrase(&outfile, "passphrase").unwrap();
+        assert_eq!(read_keypair.txn_keypair(), keypair.txn_keypair());
+        assert_eq!(
+            read_keypair.consensus_keypair().public_key(),
+            keypair.consensus_keypair().public_key()
+        );
+
+        assert!(matches!(
+            read_keypair_file_with_passphrase(&outfile, "wrong passphrase"),
+            Err(KeyPairError::Keyfile(
+                keyfile::KeyfileError::WrongPassphrase
+            ))
+        ));
+
+        std::fs::remove_file(&outfile).unwrap();
+    }
+
+    fn assert_same_keypair(read_keypair: &KeyPair, keypair: &KeyPair) {
+        assert_eq!(read_keypair.txn_keypair(), keypair.txn_keypair());
+        assert_eq!(
+            read_keypair.consensus_keypair().public_key(),
+            keypair.consensus_keypair().public_key()
+        );
+    }
+
+    #[test]
+    fn test_keypair_file_formats_round_trip() {
+        let formats = [
+            KeypairFormat::Hex,
+            KeypairFormat::Json,
+            KeypairFormat::Pem,
+            KeypairFormat::Binary,
+        ];
+
+        for format in formats {
+            let outfile = tmp_file_path(&format!("test_{format:?}_keypair_file_round_trip"));
+            let keypair = KeyPair::random();
+
+            write_keypair_file_as(&keypair, &outfile, format, None).unwrap();
+
+            let contents = std::fs::read(&outfile).unwrap();
+            assert_eq!(KeypairFormat::detect(&contents), format);
+
+            assert_same_keypair(&read_keypair_file(&outfile).unwrap(), &keypair);
+
+            std::fs::remove_file(&outfile).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_encrypted_keypair_file_formats_round_trip() {
+        for format in [KeypairFormat::Json, KeypairFormat::Pem] {
+            let outfile = tmp_file_path(&format!("test_encrypted_{format:?}_keypair_file"));
+            let keypair = KeyPair::random();
+
+            write_keypair_file_as(&keypair, &outfile, format, Some("passphrase")).unwrap();
+
+            let contents = std::fs::read_to_string(&outfile).unwrap();
+            assert!(keyfile::is_encrypted(&contents));
+
+            let read_keypair = read_keypair_file_with_passphrase(&outfile, "passphrase").unwrap();
+            assert_same_keypair(&read_keypair, &keypair);
+
+            std::fs::remove_file(&outfile).unwrap();
+        }
+
+        let outfile = tmp_file_path("test_encrypted_binary_keypair_file");
+
+        assert!(matches!(
+            write_keypair_file_as(
+                &KeyPair::random(),
+                &outfile,
+                KeypairFormat::Binary,
+                Some("passphrase")
+            ),
+            Err(KeyPairError::UnsupportedFormat(_))
+        ));
+        assert!(!Path::new(&outfile).exists());
+    }
+
+    #[test]
+    fn test_json_keypair_files_are_plain_json() {
+        let outfile = tmp_file_path("test_json_keypair_files_are_plain_json");
+        let keypair = KeyPair::random();
+
+        write_keypair_file_as(&keypair, &outfile, KeypairFormat::Json, None).unwrap();
+
+        let contents = std::fs::read_to_string(&outfile).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&contents).unwrap();
+
+        assert_eq!(
+            value["transaction_public_key"],
+            hex::encode(keypair.get_miner_public_key().serialize())
+        );
+        assert_eq!(
+            value["transaction_secret_key"],
+            hex::encode(keypair.get_miner_secret_key().secret_bytes())
+        );
+        assert!(value["consensus_secret_key"].is_string());
+        assert!(value["consensus_public_key"].is_string());
+
+        std::fs::remove_file(&outfile).unwrap();
+    }
+
+    #[test]
+    fn test_json_keypair_files_with_mismatched_public_keys_are_rejected() {
+        let keypair = KeyPair::random();
+
+        let mut value: serde_json::Value =
+            serde_json::from_slice(&encode_keypair(&keypair, KeypairFormat::Json).unwrap())
+                .unwrap();
+
+        value["transaction_public_key"] =
+            hex::encode(KeyPair::random().get_miner_public_key().serialize()).into();
+
+        assert!(matches!(
+            decode_keypair_file(value.to_string().as_bytes(), keyfile::read_passphrase),
+            Err(KeyPairError::MalformedFile(KeypairFormat::Json, _))
+        ));
+    }
+
+    #[test]
+    fn test_headerless_keypair_files_still_load() {
+        let outfile = tmp_file_path("test_headerless_keypair_files_still_load");
+        let keypair = KeyPair::random();
+
+        let mut legacy_contents = vec![];
+        write_keypair(&keypair, &mut legacy_contents).unwrap();
+        std::fs::create_dir_all(Path::new(&outfile).parent().unwrap()).unwrap();
+        std::fs::write(&outfile, legacy_contents).unwrap();
+
+        assert_same_keypair(&read_keypair_file(&outfile).unwrap(), &keypair);
+
+        std::fs::remove_file(&outfile).unwrap();
+    }
+
+    #[test]
+    fn test_keypair_formats_from_extensions() {
+        assert_eq!(
+            KeypairFormat::from_extension("keys.json"),
+            Some(KeypairFormat::Json)
+        );
+        assert_eq!(
+            KeypairFormat::from_extension("keys.PEM"),
+            Some(KeypairFormat::Pem)
+        );
+        assert_eq!(
+            KeypairFormat::from_extension("keys.bin"),
+            Some(KeypairFormat::Binary)
+        );
+        assert_eq!(KeypairFormat::from_extension("keypair"), None);
+    }
 }
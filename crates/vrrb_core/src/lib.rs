@@ -5,16 +5,21 @@ pub mod cache;
 pub mod claim;
 pub mod component;
 pub mod event_router;
+pub mod fee;
 pub mod handler;
 pub mod helpers;
+pub mod key_rotation;
+pub mod keyfile;
 pub mod keypair;
 pub mod ledger;
+pub mod node_status;
 pub mod nonceable;
 pub mod ownable;
 pub mod result;
 pub mod serde_helpers;
 pub mod storage_utils;
 pub mod txn;
+pub mod txn_receipts;
 pub mod updateable;
 pub mod verifiable;
 pub use result::*;
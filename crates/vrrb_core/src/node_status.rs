@@ -0,0 +1,327 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+        RwLock,
+    },
+    time::Instant,
+};
+
+use primitives::{NodeType, RuntimeModuleState};
+use tokio::sync::watch;
+
+/// NodeStatusHandle is a cheaply cloneable handle to a node's live runtime
+/// status. The node and its runtime modules update it as things change while
+/// readers, such as the JSON-RPC server, always observe the current values.
+#[derive(Debug, Clone)]
+pub struct NodeStatusHandle {
+    started_at: Instant,
+    running_status: Arc<RwLock<RuntimeModuleState>>,
+    peer_count: Arc<AtomicUsize>,
+    block_height: Arc<AtomicU64>,
+    module_states: Arc<RwLock<HashMap<String, RuntimeModuleState>>>,
+    peer_node_types: Arc<RwLock<HashMap<NodeType, usize>>>,
+    genesis_hash: Arc<RwLock<Option<String>>>,
+    ready: Arc<watch::Sender<bool>>,
+    operational: Arc<watch::Sender<bool>>,
+}
+
+impl Default for NodeStatusHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NodeStatusHandle {
+    pub fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            running_status: Arc::new(RwLock::new(RuntimeModuleState::Starting)),
+            peer_count: Arc::new(AtomicUsize::new(0)),
+            block_height: Arc::new(AtomicU64::new(0)),
+            module_states: Arc::new(RwLock::new(HashMap::new())),
+            peer_node_types: Arc::new(RwLock::new(HashMap::new())),
+            genesis_hash: Arc::new(RwLock::new(None)),
+            ready: Arc::new(watch::channel(false).0),
+            operational: Arc::new(watch::channel(false).0),
+        }
+    }
+
+    pub fn running_status(&self) -> RuntimeModuleState {
+        self.running_status
+            .read()
+            .map(|status| status.clone())
+            .unwrap_or(RuntimeModuleState::Terminating)
+    }
+
+    pub fn set_running_status(&self, status: RuntimeModuleState) {
+        if let Ok(mut current) = self.running_status.write() {
+            *current = status;
+        }
+    }
+
+    /// Number of seconds elapsed since the handle was created
+    pub fn uptime_secs(&self) -> u64 {
+        self.started_at.elapsed().as_secs()
+    }
+
+    pub fn peer_count(&self) -> usize {
+        self.peer_count.load(Ordering::SeqCst)
+    }
+
+    pub fn peer_added(&self) {
+        self.peer_count.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn peer_removed(&self) {
+        let _ = self
+            .peer_count
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| {
+                count.checked_sub(1)
+            });
+    }
+
+    /// Counts a peer of type `node_type` joining, see `peer_node_types`
+    pub fn peer_joined(&self, node_type: NodeType) {
+        self.peer_added();
+
+        if let Ok(mut node_types) = self.peer_node_types.write() {
+            *node_types.entry(node_type).or_default() += 1;
+        }
+    }
+
+    pub fn peer_left(&self, node_type: NodeType) {
+        self.peer_removed();
+
+        if let Ok(mut node_types) = self.peer_node_types.write() {
+            if let Some(count) = node_types.get_mut(&node_type) {
+                *count = count.saturating_sub(1);
+
+                if *count == 0 {
+                    node_types.remove(&node_type);
+                }
+            }
+        }
+    }
+
+    /// Returns how many of the node's current peers are of each node type
+    pub fn peer_node_types(&self) -> HashMap<NodeType, usize> {
+        self.peer_node_types
+            .read()
+            .map(|node_types| node_types.clone())
+            .unwrap_or_default()
+    }
+
+    /// Hash of the network's genesis block, `None` until the node learns it
+    pub fn genesis_hash(&self) -> Option<String> {
+        self.genesis_hash.read().ok().and_then(|hash| hash.clone())
+    }
+
+    pub fn set_genesis_hash(&self, hash: String) {
+        if let Ok(mut genesis_hash) = self.genesis_hash.write() {
+            *genesis_hash = Some(hash);
+        }
+    }
+
+    pub fn block_height(&self) -> u64 {
+        self.block_height.load(Ordering::SeqCst)
+    }
+
+    pub fn set_block_height(&self, height: u64) {
+        self.block_height.store(height, Ordering::SeqCst);
+    }
+
+    /// Returns the latest known state of every runtime module, keyed by
+    /// module name
+    pub fn module_states(&self) -> HashMap<String, RuntimeModuleState> {
+        self.module_states
+            .read()
+            .map(|states| states.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn set_module_state(&self, module_name: &str, state: RuntimeModuleState) {
+        if let Ok(mut states) = self.module_states.write() {
+            states.insert(module_name.to_string(), state);
+        }
+    }
+
+    /// Registers a module the node has to wait for before it is ready, see
+    /// `modules_running`. The module is tracked as starting until it reports
+    /// a state of its own.
+    pub fn expect_module(&self, module_name: &str) {
+        if let Ok(mut states) = self.module_states.write() {
+            states
+                .entry(module_name.to_string())
+                .or_insert(RuntimeModuleState::Starting);
+        }
+    }
+
+    /// Returns the names of the tracked modules that are not running, sorted
+    pub fn modules_not_running(&self) -> Vec<String> {
+        let mut modules = self
+            .module_states()
+            .into_iter()
+            .filter(|(_, state)| *state != RuntimeModuleState::Running)
+            .map(|(module_name, _)| module_name)
+            .collect::<Vec<_>>();
+
+        modules.sort();
+        modules
+    }
+
+    /// Whether every tracked module reported it is running
+    pub fn modules_running(&self) -> bool {
+        self.modules_not_running().is_empty()
+    }
+
+    /// Whether the node is up and may take part in the network, i.e. it
+    /// announces itself to and accepts peers
+    pub fn is_ready(&self) -> bool {
+        *self.ready.borrow()
+    }
+
+    pub fn set_ready(&self, ready: bool) {
+        self.ready.send_replace(ready);
+    }
+
+    /// Waits until the node is ready, returns right away if it already is
+    pub async fn ready(&self) {
+        wait_until_set(&self.ready).await;
+    }
+
+    /// Whether the node is fully operational, i.e. it is connected to as
+    /// many peers as it needs and may mine and validate. Ready nodes are
+    /// still syncing until then.
+    pub fn is_operational(&self) -> bool {
+        *self.operational.borrow()
+    }
+
+    pub fn set_operational(&self, operational: bool) {
+        self.operational.send_replace(operational);
+    }
+
+    /// Waits until the node is operational, returns right away if it already
+    /// is
+    pub async fn operational(&self) {
+        wait_until_set(&self.operational).await;
+    }
+}
+
+async fn wait_until_set(flag: &watch::Sender<bool>) {
+    let mut flag = flag.subscribe();
+
+    while !*flag.borrow_and_update() {
+        // NOTE: the sender lives as long as the handle holding it does
+        if flag.changed().await.is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clones_share_the_same_status() {
+        let handle = NodeStatusHandle::new();
+        let reader = handle.clone();
+
+        handle.set_running_status(RuntimeModuleState::Running);
+        handle.peer_added();
+        handle.peer_added();
+        handle.peer_removed();
+        handle.set_block_height(42);
+
+        assert_eq!(reader.running_status(), RuntimeModuleState::Running);
+        assert_eq!(reader.peer_count(), 1);
+        assert_eq!(reader.block_height(), 42);
+    }
+
+    #[test]
+    fn module_states_keep_the_latest_state_per_module() {
+        let handle = NodeStatusHandle::new();
+        let reader = handle.clone();
+
+        handle.set_module_state("Mempool module", RuntimeModuleState::Running);
+        handle.set_module_state("State module", RuntimeModuleState::Running);
+        handle.set_module_state("Mempool module", RuntimeModuleState::Stopped);
+
+        let states = reader.module_states();
+
+        assert_eq!(states.len(), 2);
+        assert_eq!(states["Mempool module"], RuntimeModuleState::Stopped);
+        assert_eq!(states["State module"], RuntimeModuleState::Running);
+    }
+
+    #[tokio::test]
+    async fn modules_are_running_once_every_expected_one_reports_it_is() {
+        let handle = NodeStatusHandle::new();
+
+        handle.expect_module("Mempool module");
+        handle.expect_module("State module");
+        handle.set_module_state("Mempool module", RuntimeModuleState::Running);
+
+        assert!(!handle.modules_running());
+        assert_eq!(handle.modules_not_running(), vec!["State module"]);
+
+        // NOTE: expecting a module that already reported leaves its state be
+        handle.set_module_state("State module", RuntimeModuleState::Running);
+        handle.expect_module("State module");
+
+        assert!(handle.modules_running());
+        assert!(!handle.is_ready());
+
+        let reader = handle.clone();
+        let waiter = tokio::spawn(async move { reader.ready().await });
+
+        handle.set_ready(true);
+
+        waiter.await.unwrap();
+        assert!(handle.is_ready());
+    }
+
+    #[tokio::test]
+    async fn nodes_are_operational_only_once_told_so() {
+        let handle = NodeStatusHandle::new();
+        handle.set_ready(true);
+
+        assert!(!handle.is_operational());
+
+        let reader = handle.clone();
+        let waiter = tokio::spawn(async move { reader.operational().await });
+
+        handle.set_operational(true);
+
+        waiter.await.unwrap();
+        assert!(handle.is_operational());
+    }
+
+    #[test]
+    fn peers_are_counted_per_node_type() {
+        let handle = NodeStatusHandle::new();
+
+        handle.peer_joined(NodeType::Full);
+        handle.peer_joined(NodeType::Full);
+        handle.peer_joined(NodeType::Validator);
+        handle.peer_left(NodeType::Validator);
+        handle.peer_left(NodeType::Miner);
+
+        assert_eq!(handle.peer_count(), 1);
+        assert_eq!(
+            handle.peer_node_types(),
+            HashMap::from([(NodeType::Full, 2)])
+        );
+    }
+
+    #[test]
+    fn peer_count_never_underflows() {
+        let handle = NodeStatusHandle::new();
+
+        handle.peer_removed();
+
+        assert_eq!(handle.peer_count(), 0);
+    }
+}
@@ -8,6 +8,9 @@ pub enum Error {
     #[error("serde_json error: {0}")]
     SerdeJson(#[from] serde_json::Error),
 
+    #[error("keyfile error: {0}")]
+    Keyfile(#[from] crate::keyfile::KeyfileError),
+
     #[error("{0}")]
     Other(String),
 }
@@ -5,9 +5,12 @@ use std::{
     str::FromStr,
 };
 
+use chrono::Utc;
 use primitives::{
+    Address,
     ByteSlice,
     ByteVec,
+    ChainId,
     Digest as PrimitiveDigest,
     PublicKey,
     SecretKey,
@@ -23,18 +26,71 @@ use utils::hash_data;
 
 /// This module contains the basic structure of simple transaction
 use crate::{
+    account::Account,
     accountable::Accountable,
     helpers::gen_sha256_digest_string,
+    key_rotation::KEY_ROTATION_GRACE_PERIOD_SECS,
     result,
     serde_helpers::{decode_from_binary_byte_slice, decode_from_json_byte_slice, encode_to_binary},
 };
 use crate::{serde_helpers::encode_to_json, verifiable::Verifiable};
 
+/// Default upper bound on the size of a single transaction, in bytes
+pub const DEFAULT_MAX_TXN_SIZE_BYTES: usize = 128 * 1024;
+
 /// A simple custom error type
 #[derive(thiserror::Error, Clone, Debug, Serialize, Deserialize)]
 pub enum TxnError {
     #[error("invalid transaction: {0}")]
     InvalidTxn(String),
+
+    #[error("invalid transaction signature: {0}")]
+    InvalidSignature(String),
+}
+
+/// Reasons a transaction can be rejected when validated against the current
+/// state of the chain
+#[derive(thiserror::Error, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ValidationError {
+    #[error("invalid transaction signature: {0}")]
+    BadSignature(String),
+
+    /// `amount` is what the transaction spends in total, its fee included
+    #[error("sender balance {balance} cannot cover an amount of {amount}")]
+    InsufficientBalance { balance: TxAmount, amount: TxAmount },
+
+    #[error("transaction amount {amount} plus its fee of {fee} overflows")]
+    AmountOverflow { amount: TxAmount, fee: TxAmount },
+
+    #[error("transaction is not signed with a key of account {0}")]
+    UnauthorizedKey(String),
+
+    #[error("transaction nonce {got} is too low, expected {expected} or higher")]
+    NonceTooLow { expected: TxNonce, got: TxNonce },
+
+    #[error("transaction nonce {got} is too far ahead, expected {max} or lower")]
+    NonceTooHigh { max: TxNonce, got: TxNonce },
+
+    #[error("transaction is already pending")]
+    Duplicate,
+
+    #[error("transaction is meant for chain {provided}, not chain {expected}")]
+    ChainIdMismatch {
+        provided: ChainId,
+        expected: ChainId,
+    },
+
+    #[error("transaction doesn't specify a chain id, expected chain {expected}")]
+    MissingChainId { expected: ChainId },
+
+    #[error("transaction fee {declared} is below the minimum fee of {minimum}")]
+    FeeTooLow {
+        declared: TxAmount,
+        minimum: TxAmount,
+    },
+
+    #[error("transaction is {size} bytes, over the maximum of {max} bytes")]
+    TooLarge { size: usize, max: usize },
 }
 
 pub type TxNonce = u128;
@@ -86,6 +142,20 @@ pub struct Txn {
     pub receiver_address: String,
     token: Token,
     amount: TxAmount,
+    /// Fee offered by the sender, covered by its signature so relayers can't
+    /// rewrite it
+    #[serde(default)]
+    fee: TxAmount,
+    /// Chain the transaction is meant for, covered by its signature so it
+    /// can't be replayed on other networks. `None` for transactions signed
+    /// before chain ids were introduced
+    #[serde(default)]
+    chain_id: Option<ChainId>,
+    /// Minimum fee the validator computed for the transaction once it
+    /// passed validation. Recorded by validators, so like `validators` it
+    /// isn't signed over
+    #[serde(default)]
+    computed_fee: Option<TxAmount>,
     pub signature: Signature,
     pub validators: Option<HashMap<String, bool>>,
     pub nonce: TxNonce,
@@ -123,6 +193,9 @@ impl Txn {
             receiver_address: args.receiver_address,
             token,
             amount: args.amount,
+            fee: 0,
+            chain_id: None,
+            computed_fee: None,
             signature: args.signature,
             validators: args.validators,
             nonce: args.nonce,
@@ -149,7 +222,7 @@ impl Txn {
     }
 
     pub fn encode_to_string(&self) -> String {
-        format!(
+        let encoded = format!(
             "{},{},{},{},{},{:?},{}",
             &self.timestamp,
             &self.sender_address,
@@ -158,7 +231,16 @@ impl Txn {
             &self.amount,
             &self.token,
             &self.nonce.clone()
-        )
+        );
+
+        // NOTE: legacy txns keep the digest they had before chain ids and
+        // fees, fees are only left out when they're 0 so a relayer can't
+        // rewrite them without changing the digest
+        match (self.chain_id, self.fee) {
+            (None, 0) => encoded,
+            (Some(chain_id), 0) => format!("{encoded},{chain_id}"),
+            (chain_id, fee) => format!("{encoded},{chain_id:?},{fee}"),
+        }
     }
 
     /// Produces a SHA 256 hash slice of bytes from the transaction
@@ -171,6 +253,11 @@ impl Txn {
         encode_to_binary(self).unwrap_or_default()
     }
 
+    /// Size of the transaction once encoded, in bytes
+    pub fn size(&self) -> usize {
+        self.encode().len()
+    }
+
     /// Encodes the transaction into a JSON-serialized byte vector
     pub fn encode_to_json(&self) -> Vec<u8> {
         encode_to_json(self).unwrap_or_default()
@@ -189,6 +276,38 @@ impl Txn {
         self.amount()
     }
 
+    pub fn fee(&self) -> TxAmount {
+        self.fee
+    }
+
+    /// Sets the fee the transaction offers. Since the fee is signed over, it
+    /// must be set before signing the transaction.
+    pub fn set_fee(&mut self, fee: TxAmount) {
+        self.fee = fee;
+    }
+
+    /// Minimum fee computed for the transaction when it was validated, if it
+    /// was
+    pub fn computed_fee(&self) -> Option<TxAmount> {
+        self.computed_fee
+    }
+
+    /// Records the minimum fee computed for the transaction as it passes
+    /// validation
+    pub fn set_computed_fee(&mut self, fee: TxAmount) {
+        self.computed_fee = Some(fee);
+    }
+
+    pub fn chain_id(&self) -> Option<ChainId> {
+        self.chain_id
+    }
+
+    /// Sets the chain the transaction is meant for. Since the chain id is
+    /// signed over, it must be set before signing the transaction.
+    pub fn set_chain_id(&mut self, chain_id: ChainId) {
+        self.chain_id = Some(chain_id);
+    }
+
     pub fn token(&self) -> Token {
         self.token.clone()
     }
@@ -206,14 +325,36 @@ impl Txn {
     }
 
     pub fn build_payload(&self) -> String {
-        hash_data!(
-            self.sender_address.clone(),
-            self.sender_public_key.clone(),
-            self.receiver_address.clone(),
-            self.token.clone(),
-            self.amount.clone(),
-            self.nonce.clone()
-        )
+        match (self.chain_id, self.fee) {
+            (chain_id, fee) if fee > 0 => hash_data!(
+                self.sender_address.clone(),
+                self.sender_public_key.clone(),
+                self.receiver_address.clone(),
+                self.token.clone(),
+                self.amount.clone(),
+                self.nonce.clone(),
+                chain_id,
+                fee
+            ),
+            (Some(chain_id), _) => hash_data!(
+                self.sender_address.clone(),
+                self.sender_public_key.clone(),
+                self.receiver_address.clone(),
+                self.token.clone(),
+                self.amount.clone(),
+                self.nonce.clone(),
+                chain_id
+            ),
+            // NOTE: signatures of legacy txns still verify
+            (None, _) => hash_data!(
+                self.sender_address.clone(),
+                self.sender_public_key.clone(),
+                self.receiver_address.clone(),
+                self.token.clone(),
+                self.amount.clone(),
+                self.nonce.clone()
+            ),
+        }
     }
 
     fn from_byte_slice(data: ByteSlice) -> Self {
@@ -237,21 +378,154 @@ impl Txn {
         Txn::from_str(data).unwrap_or(null_txn())
     }
 
+    /// Message a transaction's signature is computed over
+    fn signing_message(&self) -> Message {
+        type H = secp256k1::hashes::sha256::Hash;
+
+        Message::from_hashed_data::<H>(self.build_payload().as_bytes())
+    }
+
     pub fn sign(&mut self, sk: &SecretKey) {
         // TODO: refactor signing out the txn structure definition
-        if let payload = self.build_payload() {
-            let message = Message::from_slice(payload.as_bytes());
-            match message {
-                Ok(msg) => {
-                    let sig = sk.sign_ecdsa(msg);
-                    self.signature = sig.into();
-                },
-                _ => { /*TODO return Result<(), SignatureError>*/ },
-            }
-        } else {
-            self.build_payload();
-            self.sign(sk);
+        self.signature = sk.sign_ecdsa(self.signing_message());
+    }
+
+    /// Checks that the transaction was signed by the owner of
+    /// `sender_public_key`
+    pub fn verify_signature(&self) -> Result<(), TxnError> {
+        Secp256k1::verification_only()
+            .verify_ecdsa(
+                &self.signing_message(),
+                &self.signature,
+                &self.sender_public_key,
+            )
+            .map_err(|err| TxnError::InvalidSignature(err.to_string()))
+    }
+
+    /// Address of the account the transaction spends from.
+    ///
+    /// Accounts keep their address once their key is rotated, so it is read
+    /// from `sender_address` rather than derived from `sender_public_key`,
+    /// which is only used for transactions that don't carry a valid address.
+    pub fn sender(&self) -> Address {
+        Address::from_str(&self.sender_address)
+            .unwrap_or_else(|_| Address::new(self.sender_public_key))
+    }
+
+    /// Checks the transaction's signature, then its signing key, nonce and
+    /// amount plus fee against the sender's account. An account that doesn't
+    /// exist yet is treated as having a nonce and balance of 0, and can
+    /// only be spent from with the key its address derives from.
+    ///
+    /// Guards against replays by requiring the nonce to be past the current
+    /// nonce of the account, as read from state when the transaction is
+    /// validated.
+    pub fn validate_against(
+        &self,
+        sender_account: Option<&Account>,
+    ) -> Result<(), ValidationError> {
+        self.verify_signature().map_err(|err| match err {
+            TxnError::InvalidSignature(reason) | TxnError::InvalidTxn(reason) => {
+                ValidationError::BadSignature(reason)
+            },
+        })?;
+
+        let sender = self.sender();
+
+        let authorized = match sender_account {
+            Some(account) => account.accepts_signing_key(
+                &self.sender_public_key.serialize(),
+                Utc::now().timestamp(),
+                KEY_ROTATION_GRACE_PERIOD_SECS,
+            ),
+            None => sender == Address::new(self.sender_public_key),
+        };
+
+        if !authorized {
+            return Err(ValidationError::UnauthorizedKey(sender.to_string()));
+        }
+
+        let (current_nonce, balance) = sender_account
+            .map(|account| {
+                (
+                    account.nonce as TxNonce,
+                    account.credits.saturating_sub(account.debits),
+                )
+            })
+            .unwrap_or_default();
+
+        if self.nonce <= current_nonce {
+            return Err(ValidationError::NonceTooLow {
+                expected: current_nonce + 1,
+                got: self.nonce,
+            });
+        }
+
+        let total = self.total_cost()?;
+
+        if balance < total {
+            return Err(ValidationError::InsufficientBalance {
+                balance,
+                amount: total,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// What the transaction debits from the sender's account, i.e. its
+    /// amount plus the fee it offers
+    pub fn total_cost(&self) -> Result<TxAmount, ValidationError> {
+        self.amount
+            .checked_add(self.fee)
+            .ok_or(ValidationError::AmountOverflow {
+                amount: self.amount,
+                fee: self.fee,
+            })
+    }
+
+    /// Checks the transaction is meant for the chain identified by
+    /// `expected`. Transactions without a chain id are only accepted when
+    /// `accept_missing` is set, which lets nodes keep processing transactions
+    /// signed before chain ids were introduced.
+    pub fn validate_chain_id(
+        &self,
+        expected: ChainId,
+        accept_missing: bool,
+    ) -> Result<(), ValidationError> {
+        match self.chain_id {
+            Some(provided) if provided != expected => {
+                Err(ValidationError::ChainIdMismatch { provided, expected })
+            },
+            None if !accept_missing => Err(ValidationError::MissingChainId { expected }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the fee offered by the transaction covers the `minimum` fee
+    /// the node charges for it. The offered fee is signed over, so it can
+    /// only be relied on once the signature is verified too, see
+    /// `validate_against`
+    pub fn validate_fee(&self, minimum: TxAmount) -> Result<(), ValidationError> {
+        if self.fee < minimum {
+            return Err(ValidationError::FeeTooLow {
+                declared: self.fee,
+                minimum,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Checks the transaction doesn't encode to more than `max` bytes
+    pub fn validate_size(&self, max: usize) -> Result<(), ValidationError> {
+        let size = self.size();
+
+        if size > max {
+            return Err(ValidationError::TooLarge { size, max });
         }
+
+        Ok(())
     }
 }
 
@@ -273,6 +547,9 @@ pub fn null_txn() -> Txn {
         receiver_address: String::new(),
         token: Token::default(),
         amount: 0,
+        fee: 0,
+        chain_id: None,
+        computed_fee: None,
         signature,
         validators: None,
         nonce: 0,
@@ -352,6 +629,8 @@ impl Hash for Txn {
         self.amount.hash(state);
         self.signature.hash(state);
         self.nonce.hash(state);
+        self.chain_id.hash(state);
+        self.fee.hash(state);
     }
 
     fn hash_slice<H: Hasher>(data: &[Self], state: &mut H)
@@ -479,3 +758,256 @@ impl<'a> From<ByteSlice<'a>> for TransactionDigest {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keypair::KeyPair;
+
+    fn signed_txn(keypair: &KeyPair, amount: TxAmount, nonce: TxNonce) -> Txn {
+        let mut txn = Txn::new(NewTxnArgs {
+            timestamp: 0,
+            sender_address: String::from("0x192abcdef01234567890fedcba09876543210"),
+            sender_public_key: *keypair.get_miner_public_key(),
+            receiver_address: String::from("0x192abcdef01234567890fedcba09876543211"),
+            token: None,
+            amount,
+            signature: null_txn().signature,
+            validators: Some(HashMap::new()),
+            nonce,
+        });
+
+        txn.sign(keypair.get_miner_secret_key());
+        txn
+    }
+
+    fn funded_account(keypair: &KeyPair, credits: u128, nonce: u32) -> Account {
+        let mut account = Account::new(*keypair.get_miner_public_key());
+        account.credits = credits;
+        account.nonce = nonce;
+        account
+    }
+
+    #[test]
+    fn valid_txns_pass_validation() {
+        let keypair = KeyPair::random();
+        let txn = signed_txn(&keypair, 10, 2);
+
+        let account = funded_account(&keypair, 100, 1);
+
+        assert_eq!(txn.validate_against(Some(&account)), Ok(()));
+    }
+
+    #[test]
+    fn txns_signed_by_someone_else_are_rejected() {
+        let keypair = KeyPair::random();
+        let mut txn = signed_txn(&keypair, 10, 0);
+        txn.sign(KeyPair::random().get_miner_secret_key());
+
+        let account = funded_account(&keypair, 100, 0);
+
+        assert!(matches!(
+            txn.validate_against(Some(&account)),
+            Err(ValidationError::BadSignature(_))
+        ));
+    }
+
+    #[test]
+    fn rotated_accounts_are_spent_from_with_their_current_key() {
+        let previous_keypair = KeyPair::random();
+        let new_keypair = KeyPair::random();
+        let address = Address::new(*previous_keypair.get_miner_public_key());
+
+        let mut account = funded_account(&previous_keypair, 100, 0);
+        account
+            .update_field(crate::account::AccountField::PubKey(
+                new_keypair.get_miner_public_key().serialize().to_vec(),
+            ))
+            .unwrap();
+
+        let sign_as = |keypair: &KeyPair| {
+            let mut txn = signed_txn(keypair, 10, 1);
+            txn.sender_address = address.to_string();
+            txn.sign(keypair.get_miner_secret_key());
+            txn
+        };
+
+        assert_eq!(sign_as(&new_keypair).sender(), address);
+        assert_eq!(
+            sign_as(&new_keypair).validate_against(Some(&account)),
+            Ok(())
+        );
+
+        // NOTE: still within the grace period
+        assert_eq!(
+            sign_as(&previous_keypair).validate_against(Some(&account)),
+            Ok(())
+        );
+
+        account.rotated_at = Some(Utc::now().timestamp() - KEY_ROTATION_GRACE_PERIOD_SECS - 1);
+        assert_eq!(
+            sign_as(&previous_keypair).validate_against(Some(&account)),
+            Err(ValidationError::UnauthorizedKey(address.to_string()))
+        );
+
+        assert_eq!(
+            sign_as(&KeyPair::random()).validate_against(Some(&account)),
+            Err(ValidationError::UnauthorizedKey(address.to_string()))
+        );
+
+        // NOTE: accounts that don't exist yet can't be claimed by any key
+        assert_eq!(
+            sign_as(&new_keypair).validate_against(None),
+            Err(ValidationError::UnauthorizedKey(address.to_string()))
+        );
+    }
+
+    #[test]
+    fn txns_reusing_a_nonce_are_rejected() {
+        let keypair = KeyPair::random();
+        let account = funded_account(&keypair, 100, 3);
+
+        for nonce in [2, 3] {
+            assert_eq!(
+                signed_txn(&keypair, 10, nonce).validate_against(Some(&account)),
+                Err(ValidationError::NonceTooLow {
+                    expected: 4,
+                    got: nonce,
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn txns_exceeding_the_sender_balance_are_rejected() {
+        let keypair = KeyPair::random();
+        let txn = signed_txn(&keypair, 10, 1);
+
+        assert_eq!(
+            txn.validate_against(None),
+            Err(ValidationError::InsufficientBalance {
+                balance: 0,
+                amount: 10,
+            })
+        );
+    }
+
+    #[test]
+    fn txns_whose_fee_exceeds_the_remaining_balance_are_rejected() {
+        let keypair = KeyPair::random();
+        let account = funded_account(&keypair, 100, 0);
+
+        let mut txn = signed_txn(&keypair, 100, 1);
+        assert_eq!(txn.validate_against(Some(&account)), Ok(()));
+
+        txn.set_fee(1);
+        txn.sign(keypair.get_miner_secret_key());
+
+        assert_eq!(
+            txn.validate_against(Some(&account)),
+            Err(ValidationError::InsufficientBalance {
+                balance: 100,
+                amount: 101,
+            })
+        );
+    }
+
+    #[test]
+    fn txns_whose_amount_plus_fee_overflows_are_rejected() {
+        let keypair = KeyPair::random();
+        let account = funded_account(&keypair, TxAmount::MAX, 0);
+
+        let mut txn = signed_txn(&keypair, TxAmount::MAX, 1);
+        txn.set_fee(1);
+        txn.sign(keypair.get_miner_secret_key());
+
+        assert_eq!(
+            txn.validate_against(Some(&account)),
+            Err(ValidationError::AmountOverflow {
+                amount: TxAmount::MAX,
+                fee: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn chain_ids_are_signed_over() {
+        let keypair = KeyPair::random();
+        let mut txn = signed_txn(&keypair, 10, 0);
+        let legacy_digest = txn.digest();
+
+        txn.set_chain_id(42);
+        assert_ne!(txn.digest(), legacy_digest);
+        assert!(txn.verify_signature().is_err());
+
+        txn.sign(keypair.get_miner_secret_key());
+        assert!(txn.verify_signature().is_ok());
+
+        // NOTE: moving a signed txn to another chain breaks its signature
+        txn.set_chain_id(1);
+        assert!(txn.verify_signature().is_err());
+    }
+
+    #[test]
+    fn fees_are_signed_over() {
+        let keypair = KeyPair::random();
+        let mut txn = signed_txn(&keypair, 10, 0);
+        let feeless_digest = txn.digest();
+
+        txn.set_fee(5);
+        assert_ne!(txn.digest(), feeless_digest);
+        assert!(txn.verify_signature().is_err());
+
+        txn.sign(keypair.get_miner_secret_key());
+        assert!(txn.verify_signature().is_ok());
+
+        // NOTE: relayers can neither raise nor drop the fee of a signed txn
+        let signed_digest = txn.digest();
+
+        txn.set_fee(50);
+        assert!(txn.verify_signature().is_err());
+
+        txn.set_fee(0);
+        assert!(txn.verify_signature().is_err());
+        assert_ne!(txn.digest(), signed_digest);
+    }
+
+    #[test]
+    fn txns_are_only_valid_on_their_own_chain() {
+        let keypair = KeyPair::random();
+        let mut txn = signed_txn(&keypair, 10, 0);
+
+        assert_eq!(txn.validate_chain_id(42, true), Ok(()));
+        assert_eq!(
+            txn.validate_chain_id(42, false),
+            Err(ValidationError::MissingChainId { expected: 42 })
+        );
+
+        txn.set_chain_id(42);
+        txn.sign(keypair.get_miner_secret_key());
+
+        assert_eq!(txn.validate_chain_id(42, false), Ok(()));
+        assert_eq!(
+            txn.validate_chain_id(1, true),
+            Err(ValidationError::ChainIdMismatch {
+                provided: 42,
+                expected: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn txns_larger_than_the_maximum_size_are_rejected() {
+        let txn = signed_txn(&KeyPair::random(), 10, 0);
+        let size = txn.size();
+
+        assert_eq!(txn.validate_size(size), Ok(()));
+        assert_eq!(
+            txn.validate_size(size - 1),
+            Err(ValidationError::TooLarge {
+                size,
+                max: size - 1,
+            })
+        );
+    }
+}
@@ -0,0 +1,198 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, RwLock},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::txn::TransactionDigest;
+
+/// Default amount of receipts a `TxnReceiptsHandle` holds on to
+pub const DEFAULT_TXN_RECEIPTS_CAPACITY: usize = 100_000;
+
+/// Where a transaction stands in its lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TxnReceiptStatus {
+    /// Waiting to be validated or included
+    Pending,
+    Included,
+    Rejected,
+    /// Left the mempool without being included, e.g. it expired or was
+    /// evicted
+    Dropped,
+}
+
+/// What became of a submitted transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxnReceipt {
+    pub digest: TransactionDigest,
+    pub status: TxnReceiptStatus,
+    /// Height of the chain the transaction was included at, `None` unless it
+    /// was included or if the node doesn't know the height
+    pub block_height: Option<u64>,
+    /// Why the transaction was rejected or dropped, `None` unless it was
+    pub rejection_reason: Option<String>,
+}
+
+impl TxnReceipt {
+    pub fn pending(digest: TransactionDigest) -> Self {
+        Self {
+            digest,
+            status: TxnReceiptStatus::Pending,
+            block_height: None,
+            rejection_reason: None,
+        }
+    }
+
+    pub fn included(digest: TransactionDigest, block_height: Option<u64>) -> Self {
+        Self {
+            digest,
+            status: TxnReceiptStatus::Included,
+            block_height,
+            rejection_reason: None,
+        }
+    }
+
+    pub fn rejected(digest: TransactionDigest, reason: String) -> Self {
+        Self {
+            digest,
+            status: TxnReceiptStatus::Rejected,
+            block_height: None,
+            rejection_reason: Some(reason),
+        }
+    }
+
+    pub fn dropped(digest: TransactionDigest, reason: String) -> Self {
+        Self {
+            digest,
+            status: TxnReceiptStatus::Dropped,
+            block_height: None,
+            rejection_reason: Some(reason),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Receipts {
+    by_digest: HashMap<TransactionDigest, TxnReceipt>,
+    /// Digests in the order they were first recorded in, oldest first
+    order: VecDeque<TransactionDigest>,
+    capacity: usize,
+}
+
+/// TxnReceiptsHandle is a cheaply cloneable record of the lifecycle of the
+/// transactions a node handled. The node's modules record transactions as
+/// they're submitted, included, rejected or dropped while readers, such as the
+/// JSON-RPC server, look receipts up by digest.
+///
+/// Only the latest `capacity` transactions are remembered, the ones recorded
+/// first are forgotten first.
+#[derive(Debug, Clone)]
+pub struct TxnReceiptsHandle {
+    receipts: Arc<RwLock<Receipts>>,
+}
+
+impl Default for TxnReceiptsHandle {
+    fn default() -> Self {
+        Self::new(DEFAULT_TXN_RECEIPTS_CAPACITY)
+    }
+}
+
+impl TxnReceiptsHandle {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            receipts: Arc::new(RwLock::new(Receipts {
+                by_digest: HashMap::new(),
+                order: VecDeque::new(),
+                capacity: capacity.max(1),
+            })),
+        }
+    }
+
+    /// Records `receipt`, replacing any earlier receipt of the same
+    /// transaction
+    pub fn record(&self, receipt: TxnReceipt) {
+        if let Ok(mut receipts) = self.receipts.write() {
+            let digest = receipt.digest.clone();
+
+            if receipts.by_digest.insert(digest.clone(), receipt).is_some() {
+                return;
+            }
+
+            receipts.order.push_back(digest);
+
+            while receipts.order.len() > receipts.capacity {
+                if let Some(oldest) = receipts.order.pop_front() {
+                    receipts.by_digest.remove(&oldest);
+                }
+            }
+        }
+    }
+
+    pub fn get(&self, digest: &TransactionDigest) -> Option<TxnReceipt> {
+        self.receipts
+            .read()
+            .ok()
+            .and_then(|receipts| receipts.by_digest.get(digest).cloned())
+    }
+
+    /// Amount of transactions receipts are held for
+    pub fn len(&self) -> usize {
+        self.receipts
+            .read()
+            .map(|receipts| receipts.by_digest.len())
+            .unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txn::null_txn;
+
+    #[test]
+    fn later_receipts_replace_earlier_ones() {
+        let handle = TxnReceiptsHandle::default();
+        let reader = handle.clone();
+        let digest = null_txn().digest();
+
+        handle.record(TxnReceipt::pending(digest.clone()));
+        assert_eq!(
+            reader.get(&digest).map(|receipt| receipt.status),
+            Some(TxnReceiptStatus::Pending)
+        );
+
+        handle.record(TxnReceipt::included(digest.clone(), Some(3)));
+        assert_eq!(
+            reader.get(&digest),
+            Some(TxnReceipt::included(digest, Some(3)))
+        );
+        assert_eq!(reader.len(), 1);
+    }
+
+    #[test]
+    fn oldest_receipts_are_forgotten_first() {
+        let handle = TxnReceiptsHandle::new(2);
+
+        let digests = (0..3)
+            .map(|nonce| {
+                let mut txn = null_txn();
+                txn.nonce = nonce;
+                txn.digest()
+            })
+            .collect::<Vec<_>>();
+
+        for digest in digests.iter() {
+            handle.record(TxnReceipt::pending(digest.clone()));
+        }
+
+        assert_eq!(handle.len(), 2);
+        assert!(handle.get(&digests[0]).is_none());
+        assert!(handle.get(&digests[2]).is_some());
+    }
+}
@@ -1,26 +1,139 @@
 /// Some basic utility functions (a decay calculator for the now deprecated
 /// monetary policy and a restore db function that can take in a path
-/// to a db file and restore a PickleDB)
-//TODO: Replace PickleDB with LR DB
-use pickledb::{PickleDb, PickleDbDumpPolicy, SerializationMethod};
+/// to a db directory and restore a VrrbDb)
+use std::path::{Path, PathBuf};
+
+use pickledb::{PickleDb, SerializationMethod};
+use storage::{
+    storage_utils::StorageError,
+    vrrbdb::{VrrbDb, VrrbDbConfig, VrrbDbReadHandle},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum RestoreDbError {
+    #[error(
+        "{0} is a legacy PickleDB file, it must be migrated to a VrrbDb before it can be restored"
+    )]
+    LegacyPickleDb(PathBuf),
+
+    #[error("failed to open the VrrbDb: {0}")]
+    Storage(#[from] StorageError),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DecayError {
+    #[error("initial value must be greater than zero")]
+    ZeroInitial,
+
+    #[error("number of epochs must be greater than zero")]
+    ZeroEpochs,
+}
+
+/// Computes the per epoch decay rate needed for `initial` to decay down to one
+/// over `epochs` epochs.
+///
+/// Both `initial` and `epochs` must be greater than zero. Every other `u128`
+/// converts to a finite `f64`, so the returned rate is always finite and non
+/// negative.
+pub fn decay_calculator(initial: u128, epochs: u128) -> Result<f64, DecayError> {
+    if initial == 0 {
+        return Err(DecayError::ZeroInitial);
+    }
+
+    if epochs == 0 {
+        return Err(DecayError::ZeroEpochs);
+    }
 
-pub fn decay_calculator(initial: u128, epochs: u128) -> f64 {
     let b: f64 = 1.0f64 / initial as f64;
     let ln_b = b.log10();
-    (ln_b / epochs as f64) * -1.0
+    Ok((ln_b / epochs as f64) * -1.0)
+}
+
+/// Opens the VrrbDb stored within `path`, creating it if it doesn't exist yet,
+/// and returns it along with a read handle to it.
+///
+/// Legacy PickleDB files are rejected rather than overwritten so their data
+/// isn't lost.
+pub fn restore_db(path: &str) -> Result<(VrrbDb, VrrbDbReadHandle), RestoreDbError> {
+    let path = Path::new(path);
+
+    if is_legacy_pickledb(path) {
+        return Err(RestoreDbError::LegacyPickleDb(path.to_path_buf()));
+    }
+
+    let db = VrrbDb::new(VrrbDbConfig {
+        path: path.to_path_buf(),
+        ..Default::default()
+    })?;
+
+    let read_handle = db.read_handle();
+
+    Ok((db, read_handle))
+}
+
+fn is_legacy_pickledb(path: &Path) -> bool {
+    path.is_file() && PickleDb::load_read_only(path, SerializationMethod::Bin).is_ok()
 }
 
-pub fn restore_db(path: &str) -> PickleDb {
-    match PickleDb::load(
-        path,
-        PickleDbDumpPolicy::DumpUponRequest,
-        SerializationMethod::Bin,
-    ) {
-        Ok(nst) => nst,
-        Err(_) => PickleDb::new(
-            path,
+#[cfg(test)]
+mod tests {
+    use pickledb::PickleDbDumpPolicy;
+    use primitives::Address;
+    use vrrb_core::{account::Account, helpers::generate_random_string, keypair::KeyPair};
+
+    use super::*;
+
+    #[test]
+    fn decay_calculator_rejects_zero_initial() {
+        assert_eq!(decay_calculator(0, 10), Err(DecayError::ZeroInitial));
+    }
+
+    #[test]
+    fn decay_calculator_rejects_zero_epochs() {
+        assert_eq!(decay_calculator(1000, 0), Err(DecayError::ZeroEpochs));
+    }
+
+    #[test]
+    fn decay_calculator_computes_rate() {
+        let rate = decay_calculator(1000, 3).unwrap();
+
+        assert!((rate - 1.0).abs() < 1e-9);
+        assert!(decay_calculator(u128::MAX, 1).unwrap().is_finite());
+    }
+
+    #[test]
+    fn restored_db_handles_share_state() {
+        let path = std::env::temp_dir().join(generate_random_string());
+
+        let (mut db, read_handle) = restore_db(path.to_str().unwrap()).unwrap();
+
+        let address = Address::new(*KeyPair::random().get_miner_public_key());
+        let account = Account::default();
+
+        db.insert_account(address.clone(), account.clone()).unwrap();
+
+        assert_eq!(
+            read_handle.state_store_values().get(&address),
+            Some(&account)
+        );
+    }
+
+    #[test]
+    fn legacy_pickledb_files_are_rejected() {
+        let path = std::env::temp_dir().join(generate_random_string());
+
+        let mut legacy_db = PickleDb::new(
+            &path,
             PickleDbDumpPolicy::DumpUponRequest,
             SerializationMethod::Bin,
-        ),
+        );
+        legacy_db.set("key", &String::from("value")).unwrap();
+        legacy_db.dump().unwrap();
+
+        let err = restore_db(path.to_str().unwrap()).unwrap_err();
+
+        assert!(matches!(err, RestoreDbError::LegacyPickleDb(_)));
+
+        std::fs::remove_file(&path).unwrap_or_default();
     }
 }
@@ -1,6 +1,8 @@
-use std::{fmt::Debug, net::SocketAddr, time::Duration};
+use std::{fmt::Debug, net::SocketAddr, path::PathBuf, time::Duration};
 
-use axum_server::tls_rustls::RustlsConfig;
+use mempool::{LeftRightMempool, MempoolReadHandleFactory};
+use telemetry::Level;
+use vrrb_core::{event_router::EventCounter, node_status::NodeStatusHandle};
 
 /// Configuration store for an HttpApiServer
 // TODO: implement a builder over this config.
@@ -11,7 +13,27 @@ pub struct HttpApiServerConfig {
     pub api_title: String,
     pub api_version: String,
     pub server_timeout: Option<Duration>,
-    pub tls_config: Option<RustlsConfig>,
+    /// Serves the API over HTTPS when set
+    pub tls: Option<TlsConfig>,
+    pub state: HttpApiState,
+    /// Origins allowed to make cross-origin requests. CORS is left unhandled
+    /// when empty
+    pub cors_allowed_origins: Vec<String>,
+    /// Token every request must present as `Authorization: Bearer <token>`.
+    /// Requests go unauthenticated when unset
+    pub bearer_token: Option<String>,
+    /// Logs every request, with credentials redacted, when enabled
+    pub request_logging: bool,
+    /// Level request logs are emitted at
+    pub log_level: Level,
+}
+
+/// Locations of the PEM encoded certificate chain and private key an
+/// HttpApiServer serves HTTPS with
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
 }
 
 /// Configuration store for an HttpApiRouter
@@ -23,4 +45,32 @@ pub struct HttpApiRouterConfig {
     pub api_title: String,
     pub api_version: String,
     pub server_timeout: Option<Duration>,
+    pub state: HttpApiState,
+    pub cors_allowed_origins: Vec<String>,
+    pub bearer_token: Option<String>,
+    pub request_logging: bool,
+    pub log_level: Level,
+}
+
+/// Live node state exposed by the HTTP API's operational endpoints. It is
+/// read from the same handles the JSON-RPC server uses to report status.
+#[derive(Debug, Clone)]
+pub struct HttpApiState {
+    pub node_status_handle: NodeStatusHandle,
+    pub mempool_read_handle_factory: MempoolReadHandleFactory,
+    pub processed_events: EventCounter,
+    pub delivered_events: EventCounter,
+    pub dropped_events: EventCounter,
+}
+
+impl Default for HttpApiState {
+    fn default() -> Self {
+        Self {
+            node_status_handle: NodeStatusHandle::new(),
+            mempool_read_handle_factory: LeftRightMempool::default().factory(),
+            processed_events: EventCounter::default(),
+            delivered_events: EventCounter::default(),
+            dropped_events: EventCounter::default(),
+        }
+    }
 }
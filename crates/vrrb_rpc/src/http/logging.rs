@@ -0,0 +1,186 @@
+use std::time::Instant;
+
+use axum::{
+    http::{header, HeaderMap, HeaderName, Request},
+    middleware::Next,
+    response::Response,
+};
+use telemetry::Level;
+
+/// Headers whose values never make it into request logs
+const REDACTED_HEADERS: [HeaderName; 3] = [
+    header::AUTHORIZATION,
+    header::PROXY_AUTHORIZATION,
+    header::COOKIE,
+];
+
+/// Emits an event at a level only known at runtime
+macro_rules! event_at_level {
+    ($level:expr, $($fields:tt)+) => {
+        if $level == Level::ERROR {
+            telemetry::error!($($fields)+)
+        } else if $level == Level::WARN {
+            telemetry::warn!($($fields)+)
+        } else if $level == Level::INFO {
+            telemetry::info!($($fields)+)
+        } else if $level == Level::DEBUG {
+            telemetry::debug!($($fields)+)
+        } else {
+            telemetry::trace!($($fields)+)
+        }
+    };
+}
+
+/// Logs the method, path, headers, response status and latency of every
+/// request passing through it
+pub async fn log_request<B>(request: Request<B>, next: Next<B>, level: Level) -> Response {
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let headers = redact_headers(request.headers());
+    let started_at = Instant::now();
+
+    let response = next.run(request).await;
+
+    let status = response.status().as_u16();
+    let latency_ms = started_at.elapsed().as_secs_f64() * 1000.0;
+
+    event_at_level!(
+        level,
+        %method,
+        path,
+        status,
+        latency_ms,
+        ?headers,
+        "handled HTTP request"
+    );
+
+    response
+}
+
+fn redact_headers(headers: &HeaderMap) -> Vec<String> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if REDACTED_HEADERS.contains(name) {
+                "[redacted]"
+            } else {
+                value.to_str().unwrap_or("[non-utf8]")
+            };
+
+            format!("{name}: {value}")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Write,
+        net::SocketAddr,
+        sync::{Arc, Mutex},
+    };
+
+    use axum::{body::Body, http::StatusCode};
+    use tower::ServiceExt;
+    use tracing_subscriber::fmt::MakeWriter;
+
+    use super::*;
+    use crate::http::{create_router, HttpApiRouterConfig, HttpApiState};
+
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+    impl CapturedLogs {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    impl Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for CapturedLogs {
+        type Writer = Self;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    fn create_config(request_logging: bool) -> HttpApiRouterConfig {
+        HttpApiRouterConfig {
+            address: SocketAddr::from(([127, 0, 0, 1], 0)),
+            api_title: "Node HTTP API".to_string(),
+            api_version: "1.0".to_string(),
+            server_timeout: None,
+            state: HttpApiState::default(),
+            cors_allowed_origins: vec![],
+            bearer_token: Some("s3cr3t".to_string()),
+            request_logging,
+            log_level: Level::INFO,
+        }
+    }
+
+    async fn send_authorized_request(config: &HttpApiRouterConfig) -> StatusCode {
+        let request = Request::builder()
+            .uri("/health")
+            .method("GET")
+            .header(header::AUTHORIZATION, "Bearer s3cr3t")
+            .body(Body::empty())
+            .unwrap();
+
+        create_router(config)
+            .unwrap()
+            .oneshot(request)
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn handled_requests_are_logged_with_redacted_credentials() {
+        let logs = CapturedLogs::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(logs.clone())
+            .with_max_level(Level::INFO)
+            .with_ansi(false)
+            .finish();
+        let _guard = telemetry::tracing::subscriber::set_default(subscriber);
+
+        let status = send_authorized_request(&create_config(true)).await;
+
+        assert_eq!(status, StatusCode::OK);
+
+        let logs = logs.contents();
+        assert!(logs.contains("handled HTTP request"));
+        assert!(logs.contains("method=GET"));
+        assert!(logs.contains("path=\"/health\""));
+        assert!(logs.contains("status=200"));
+        assert!(logs.contains("authorization: [redacted]"));
+        assert!(!logs.contains("s3cr3t"));
+    }
+
+    #[tokio::test]
+    async fn requests_are_not_logged_when_logging_is_disabled() {
+        let logs = CapturedLogs::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(logs.clone())
+            .with_max_level(Level::INFO)
+            .with_ansi(false)
+            .finish();
+        let _guard = telemetry::tracing::subscriber::set_default(subscriber);
+
+        let status = send_authorized_request(&create_config(false)).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert!(!logs.contents().contains("handled HTTP request"));
+    }
+}
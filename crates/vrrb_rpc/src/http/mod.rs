@@ -1,7 +1,9 @@
 mod config;
+mod logging;
 mod router;
 mod routes;
 mod server;
+mod tls;
 
 pub use config::*;
 pub use router::*;
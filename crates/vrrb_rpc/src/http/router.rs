@@ -1,19 +1,80 @@
-use axum::{routing::get, Router};
+use axum::{
+    body::Body,
+    http::{header, HeaderValue, Method, Request},
+    middleware::{self, Next},
+    routing::get,
+    Extension,
+    Router,
+};
 use tower::ServiceBuilder;
-use tower_http::trace::TraceLayer;
+use tower_http::{
+    auth::RequireAuthorizationLayer,
+    cors::{AllowOrigin, CorsLayer},
+    trace::TraceLayer,
+};
 use vrrb_core::event_router::Event;
 
-use crate::http::{
-    routes::{accounts, health},
-    HttpApiRouterConfig,
+use crate::{
+    http::{
+        logging,
+        routes::{accounts, health, metrics},
+        HttpApiRouterConfig,
+    },
+    ApiError,
+    Result,
 };
 
-pub fn create_router(config: &HttpApiRouterConfig) -> Router {
-    Router::new()
+pub fn create_router(config: &HttpApiRouterConfig) -> Result<Router> {
+    let mut router = Router::new()
         .route("/", get(|| async { "index" }))
         .route("/health", get(health::health_check))
+        .route("/metrics", get(metrics::metrics))
         .nest("/accounts", accounts::create_account_router())
-        .layer(ServiceBuilder::new().layer(TraceLayer::new_for_http()))
+        .layer(
+            ServiceBuilder::new()
+                .layer(TraceLayer::new_for_http())
+                .layer(Extension(config.state.clone())),
+        );
+
+    if let Some(token) = &config.bearer_token {
+        HeaderValue::from_str(&format!("Bearer {token}"))
+            .map_err(|err| ApiError::Other(format!("invalid bearer token: {err}")))?;
+
+        router = router.layer(RequireAuthorizationLayer::bearer(token));
+    }
+
+    // NOTE: applied after authorization so preflight requests are answered
+    // before they reach the authorization check
+    if !config.cors_allowed_origins.is_empty() {
+        let origins = config
+            .cors_allowed_origins
+            .iter()
+            .map(|origin| {
+                HeaderValue::from_str(origin)
+                    .map_err(|err| ApiError::Other(format!("invalid CORS origin {origin}: {err}")))
+            })
+            .collect::<Result<Vec<HeaderValue>>>()?;
+
+        let cors = CorsLayer::new()
+            .allow_origin(AllowOrigin::list(origins))
+            .allow_methods([Method::GET, Method::POST, Method::PUT])
+            .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE]);
+
+        router = router.layer(cors);
+    }
+
+    // NOTE: left out entirely when disabled so it costs nothing per request
+    if config.request_logging {
+        let level = config.log_level;
+
+        router = router.layer(middleware::from_fn(
+            move |request: Request<Body>, next: Next<Body>| {
+                logging::log_request(request, next, level)
+            },
+        ));
+    }
+
+    Ok(router)
 }
 
 #[cfg(test)]
@@ -27,6 +88,7 @@ mod tests {
     use tower::{Service, ServiceExt};
 
     use super::*;
+    use crate::http::HttpApiState;
 
     #[tokio::test]
     async fn index_should_exist() {
@@ -41,9 +103,14 @@ mod tests {
             api_title,
             api_version,
             server_timeout: None,
+            state: HttpApiState::default(),
+            cors_allowed_origins: vec![],
+            bearer_token: None,
+            request_logging: false,
+            log_level: telemetry::Level::INFO,
         };
 
-        let mut router = create_router(&config);
+        let mut router = create_router(&config).unwrap();
 
         let request = Request::builder()
             .uri("/")
@@ -54,4 +121,87 @@ mod tests {
         let response = router.ready().await.unwrap().call(request).await.unwrap();
         assert_eq!(response.status(), StatusCode::OK);
     }
+
+    fn create_secured_config(cors_allowed_origins: Vec<String>) -> HttpApiRouterConfig {
+        HttpApiRouterConfig {
+            address: SocketAddr::from(([127, 0, 0, 1], 0)),
+            api_title: "Node HTTP API".to_string(),
+            api_version: "1.0".to_string(),
+            server_timeout: None,
+            state: HttpApiState::default(),
+            cors_allowed_origins,
+            bearer_token: Some("s3cr3t".to_string()),
+            request_logging: false,
+            log_level: telemetry::Level::INFO,
+        }
+    }
+
+    #[tokio::test]
+    async fn requests_without_a_matching_token_are_unauthorized() {
+        let router = create_router(&create_secured_config(vec![])).unwrap();
+
+        let request = Request::builder()
+            .uri("/health")
+            .method("GET")
+            .header(header::AUTHORIZATION, "Bearer wrong")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let request = Request::builder()
+            .uri("/health")
+            .method("GET")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = router.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn requests_with_a_matching_token_are_served() {
+        let router = create_router(&create_secured_config(vec![])).unwrap();
+
+        let request = Request::builder()
+            .uri("/health")
+            .method("GET")
+            .header(header::AUTHORIZATION, "Bearer s3cr3t")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = router.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn preflight_requests_are_answered_for_allowed_origins() {
+        let origin = "https://explorer.vrrb.io";
+        let router = create_router(&create_secured_config(vec![origin.to_string()])).unwrap();
+
+        let request = Request::builder()
+            .uri("/health")
+            .method(Method::OPTIONS)
+            .header(header::ORIGIN, origin)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+            .header(header::ACCESS_CONTROL_REQUEST_HEADERS, "authorization")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = router.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            origin
+        );
+    }
+
+    #[test]
+    fn invalid_cors_origins_are_rejected() {
+        let config = create_secured_config(vec!["not\na valid origin".to_string()]);
+
+        assert!(create_router(&config).is_err());
+    }
 }
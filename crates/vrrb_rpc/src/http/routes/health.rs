@@ -1,8 +1,13 @@
-use axum::Json;
+use axum::{Extension, Json};
 use serde_json::{json, Value};
 
-pub async fn health_check() -> Json<Value> {
+use crate::http::HttpApiState;
+
+pub async fn health_check(Extension(state): Extension<HttpApiState>) -> Json<Value> {
     Json(json!({
         "status": "ok",
+        "running_status": state.node_status_handle.running_status(),
+        "operational": state.node_status_handle.is_operational(),
+        "uptime_secs": state.node_status_handle.uptime_secs(),
     }))
 }
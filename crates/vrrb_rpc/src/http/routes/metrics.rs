@@ -0,0 +1,115 @@
+use std::{collections::HashMap, fmt::Write};
+
+use axum::{http::header, response::IntoResponse, Extension};
+use vrrb_core::event_router::Topic;
+
+use crate::http::HttpApiState;
+
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Renders the node's counters in the Prometheus text exposition format
+pub async fn metrics(Extension(state): Extension<HttpApiState>) -> impl IntoResponse {
+    let mut body = String::new();
+    let mempool = state.mempool_read_handle_factory.metrics();
+
+    write_metric(
+        &mut body,
+        "vrrb_peer_count",
+        "gauge",
+        "Number of peers the node is connected to",
+        state.node_status_handle.peer_count() as u64,
+    );
+
+    write_metric(
+        &mut body,
+        "vrrb_mempool_size",
+        "gauge",
+        "Number of transactions pending in the mempool",
+        mempool.size as u64,
+    );
+
+    write_metric(
+        &mut body,
+        "vrrb_mempool_queued",
+        "gauge",
+        "Number of transactions queued in the mempool behind a nonce gap",
+        mempool.queued as u64,
+    );
+
+    write_metric(
+        &mut body,
+        "vrrb_mempool_inserts_total",
+        "counter",
+        "Number of transactions inserted into the mempool",
+        mempool.inserts,
+    );
+
+    write_metric(
+        &mut body,
+        "vrrb_mempool_duplicates_total",
+        "counter",
+        "Number of transactions the mempool rejected as duplicates",
+        mempool.duplicates,
+    );
+
+    write_metric(
+        &mut body,
+        "vrrb_mempool_evictions_total",
+        "counter",
+        "Number of transactions evicted from a full mempool",
+        mempool.evictions,
+    );
+
+    write_metric(
+        &mut body,
+        "vrrb_mempool_read_lag",
+        "gauge",
+        "Number of mempool writes not yet visible to readers",
+        mempool.read_lag,
+    );
+
+    write_topic_metric(
+        &mut body,
+        "vrrb_events_processed_total",
+        "Number of events received by the event router",
+        state.processed_events.snapshot(),
+    );
+
+    write_topic_metric(
+        &mut body,
+        "vrrb_events_delivered_total",
+        "Number of events the event router handed over to subscribers",
+        state.delivered_events.snapshot(),
+    );
+
+    write_topic_metric(
+        &mut body,
+        "vrrb_events_dropped_total",
+        "Number of events dropped by the event router",
+        state.dropped_events.snapshot(),
+    );
+
+    ([(header::CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)], body)
+}
+
+fn write_metric(body: &mut String, name: &str, kind: &str, help: &str, value: u64) {
+    let _ = writeln!(body, "# HELP {name} {help}");
+    let _ = writeln!(body, "# TYPE {name} {kind}");
+    let _ = writeln!(body, "{name} {value}");
+}
+
+fn write_topic_metric(body: &mut String, name: &str, help: &str, counts: HashMap<Topic, u64>) {
+    let _ = writeln!(body, "# HELP {name} {help}");
+    let _ = writeln!(body, "# TYPE {name} counter");
+
+    // NOTE: sorted so scrapes list topics in a stable order
+    let mut counts = counts
+        .into_iter()
+        .map(|(topic, count)| (format!("{topic:?}").to_lowercase(), count))
+        .collect::<Vec<(String, u64)>>();
+    counts.sort();
+
+    for (topic, count) in counts {
+        let _ = writeln!(body, "{name}{{topic=\"{topic}\"}} {count}");
+    }
+}
@@ -10,7 +10,12 @@ use tokio::sync::broadcast::Receiver;
 use vrrb_core::event_router::Event;
 
 use crate::{
-    http::{router::create_router, HttpApiRouterConfig, HttpApiServerConfig},
+    http::{
+        router::create_router,
+        tls::load_rustls_config,
+        HttpApiRouterConfig,
+        HttpApiServerConfig,
+    },
     ApiError,
     Result,
 };
@@ -32,17 +37,25 @@ impl Debug for HttpApiServer {
 
 impl HttpApiServer {
     pub fn new(config: HttpApiServerConfig) -> Result<Self> {
-        let address = &config.address.parse().unwrap();
+        let address: &SocketAddr = &config
+            .address
+            .parse()
+            .map_err(|err| ApiError::Other(format!("invalid address {}: {err}", config.address)))?;
 
         let router_config = HttpApiRouterConfig {
             address: *address,
             api_title: config.api_title.clone(),
             api_version: config.api_version.clone(),
             server_timeout: config.server_timeout,
+            state: config.state,
+            cors_allowed_origins: config.cors_allowed_origins,
+            bearer_token: config.bearer_token,
+            request_logging: config.request_logging,
+            log_level: config.log_level,
         };
 
-        let tls_config = config.tls_config;
-        let router = create_router(&router_config);
+        let tls_config = config.tls.as_ref().map(load_rustls_config).transpose()?;
+        let router = create_router(&router_config)?;
         let listener = TcpListener::bind(address).map_err(|err| {
             ApiError::Other(format!("unable to bind to address {address}: {}", err))
         })?;
@@ -0,0 +1,68 @@
+use std::{fs::File, io::BufReader, path::Path, sync::Arc};
+
+use axum_server::tls_rustls::RustlsConfig;
+use rustls::{Certificate, PrivateKey, ServerConfig};
+use rustls_pemfile::Item;
+
+use crate::{http::TlsConfig, ApiError, Result};
+
+/// Loads the configured certificate chain and private key up front, so
+/// problems with either surface when the server is built rather than on its
+/// first connection
+pub(crate) fn load_rustls_config(tls: &TlsConfig) -> Result<RustlsConfig> {
+    let certs = read_certs(&tls.cert_path)?;
+    let key = read_private_key(&tls.key_path)?;
+
+    let mut config = ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|err| ApiError::InvalidTlsConfig(format!("unusable certificate or key: {err}")))?;
+
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok(RustlsConfig::from_config(Arc::new(config)))
+}
+
+fn open_pem_file(path: &Path) -> Result<BufReader<File>> {
+    File::open(path).map(BufReader::new).map_err(|err| {
+        ApiError::InvalidTlsConfig(format!("unable to open {}: {err}", path.display()))
+    })
+}
+
+fn read_certs(path: &Path) -> Result<Vec<Certificate>> {
+    let certs = rustls_pemfile::certs(&mut open_pem_file(path)?).map_err(|err| {
+        ApiError::InvalidTlsConfig(format!(
+            "unable to read certificates from {}: {err}",
+            path.display()
+        ))
+    })?;
+
+    if certs.is_empty() {
+        return Err(ApiError::InvalidTlsConfig(format!(
+            "no certificates found in {}",
+            path.display()
+        )));
+    }
+
+    Ok(certs.into_iter().map(Certificate).collect())
+}
+
+fn read_private_key(path: &Path) -> Result<PrivateKey> {
+    let items = rustls_pemfile::read_all(&mut open_pem_file(path)?).map_err(|err| {
+        ApiError::InvalidTlsConfig(format!(
+            "unable to read private key from {}: {err}",
+            path.display()
+        ))
+    })?;
+
+    items
+        .into_iter()
+        .find_map(|item| match item {
+            Item::PKCS8Key(key) | Item::RSAKey(key) | Item::ECKey(key) => Some(PrivateKey(key)),
+            _ => None,
+        })
+        .ok_or_else(|| {
+            ApiError::InvalidTlsConfig(format!("no private key found in {}", path.display()))
+        })
+}
@@ -15,6 +15,9 @@ pub enum ApiError {
     #[error("invalid address provided: {0}")]
     InvalidAddr(SocketAddr),
 
+    #[error("invalid TLS configuration: {0}")]
+    InvalidTlsConfig(String),
+
     #[error("{0}")]
     Other(String),
 }
@@ -2,11 +2,23 @@ use std::{collections::HashMap, net::SocketAddr};
 
 use async_trait::async_trait;
 use jsonrpsee::{core::Error, proc_macros::rpc, types::SubscriptionResult};
-use primitives::{Address, NodeType, SerializedPublicKey};
+use mempool::TxnRecord;
+use primitives::{
+    Address,
+    ChainId,
+    NodeId,
+    NodeType,
+    PeerId,
+    ProtocolVersion,
+    RuntimeModuleState,
+    SerializedPublicKey,
+};
 use serde::{Deserialize, Serialize};
 use vrrb_core::{
     account::Account,
+    key_rotation::KeyRotation,
     txn::{NewTxnArgs, TransactionDigest, TxAmount, TxNonce, TxPayload, TxSignature, Txn},
+    txn_receipts::TxnReceipt,
 };
 
 pub type ExampleHash = [u8; 32];
@@ -14,6 +26,100 @@ pub type ExampleStorageKey = Vec<u8>;
 pub type FullStateSnapshot = HashMap<Address, Account>;
 pub type FullMempoolSnapshot = Vec<Txn>;
 
+/// Live health information about the node a client is connected to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeStatus {
+    pub node_id: NodeId,
+    pub node_type: NodeType,
+    pub running_status: RuntimeModuleState,
+    pub uptime_secs: u64,
+    pub peer_count: usize,
+    pub mempool_size: usize,
+    /// Amount of transactions waiting to be validated
+    pub validation_queue_depth: usize,
+    pub current_block_height: u64,
+}
+
+/// Parameters of the network the node a client is connected to belongs to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkParams {
+    /// Identifies the network, transactions meant for one network must not be
+    /// submitted to another
+    pub chain_id: ChainId,
+    pub protocol_version: ProtocolVersion,
+    /// Hash of the network's genesis block, `None` until the node knows it
+    pub genesis_hash: Option<String>,
+    pub current_block_height: u64,
+    /// How many of the node's peers run as each node type
+    pub node_type_distribution: HashMap<NodeType, usize>,
+}
+
+/// State of the node's connection to a peer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PeerConnectionState {
+    Connected,
+    /// The connection dropped and the node is trying to re-establish it
+    Reconnecting,
+    /// The node knows about the peer but holds no connection to it
+    Known,
+}
+
+/// Peer of the node a client is connected to, see `getPeers`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerInfo {
+    pub peer_id: PeerId,
+    pub address: SocketAddr,
+    pub connection_state: PeerConnectionState,
+    /// Unix timestamp, in seconds, of the last time the node heard from or
+    /// connected to the peer
+    pub last_seen: Option<u64>,
+}
+
+/// Outcome of checking a transaction without submitting it, see
+/// `validateTransaction`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationResult {
+    pub digest: TransactionDigest,
+    /// Fee the transaction would pay once accepted: the fee it offers, or
+    /// the minimum fee the node charges for it if it offers less
+    pub estimated_fee: TxAmount,
+    /// Why `submitTransaction` would turn the transaction away, or `None` if
+    /// it would accept it
+    pub rejection: Option<Rejection>,
+}
+
+impl ValidationResult {
+    pub fn is_accepted(&self) -> bool {
+        self.rejection.is_none()
+    }
+}
+
+/// Reason a transaction would be rejected. `code` is the one
+/// `submitTransaction` would fail with, see `RpcError`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rejection {
+    pub code: i32,
+    pub reason: String,
+}
+
+/// Outcome of a single transaction of a `submitTransactions` batch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubmitResult {
+    pub digest: TransactionDigest,
+    /// Why the transaction was turned away, or `None` if it was accepted
+    pub rejection: Option<Rejection>,
+}
+
+impl SubmitResult {
+    pub fn is_accepted(&self) -> bool {
+        self.rejection.is_none()
+    }
+}
+
+/// Largest amount of transactions a `submitTransactions` batch may hold
+pub const MAX_SUBMIT_BATCH_SIZE: usize = 1_000;
+
 #[rpc(server, client, namespace = "state")]
 #[async_trait]
 pub trait Rpc {
@@ -25,10 +131,59 @@ pub trait Rpc {
     #[method(name = "getFullMempool")]
     async fn get_full_mempool(&self) -> Result<FullMempoolSnapshot, Error>;
 
+    /// Returns up to `limit` pending transaction records, including their fee,
+    /// nonce and the time they were added to the mempool
+    #[method(name = "getMempool")]
+    async fn get_mempool(&self, limit: Option<usize>) -> Result<Vec<TxnRecord>, Error>;
+
     /// Returns the node type this client is connected to
     #[method(name = "getNodeType")]
     async fn get_node_type(&self) -> Result<NodeType, Error>;
 
+    /// Returns the current runtime status of the node this client is connected
+    /// to
+    #[method(name = "getNodeStatus")]
+    async fn get_node_status(&self) -> Result<NodeStatus, Error>;
+
+    /// Returns the chain id, protocol version and other parameters of the
+    /// network the node belongs to
+    #[method(name = "getNetworkParams")]
+    async fn get_network_params(&self) -> Result<NetworkParams, Error>;
+
+    /// Lists the peers of the node. Requires the admin token if the node is
+    /// configured with one.
+    #[method(name = "getPeers")]
+    async fn get_peers(&self, token: Option<String>) -> Result<Vec<PeerInfo>, Error>;
+
+    /// Connects the node to the peer at `address`, returning once connected
+    /// or failing once the connection attempt times out. Requires the admin
+    /// token if the node is configured with one.
+    #[method(name = "addPeer")]
+    async fn add_peer(&self, address: SocketAddr, token: Option<String>)
+        -> Result<PeerInfo, Error>;
+
+    /// Disconnects the node from a peer and forgets about it. Requires the
+    /// admin token if the node is configured with one.
+    #[method(name = "removePeer")]
+    async fn remove_peer(&self, peer_id: PeerId, token: Option<String>) -> Result<(), Error>;
+
+    /// Verifies and queues a signed transaction into the mempool, returning
+    /// its digest
+    #[method(name = "submitTransaction")]
+    async fn submit_transaction(&self, txn: Txn) -> Result<TransactionDigest, Error>;
+
+    /// Submits up to `MAX_SUBMIT_BATCH_SIZE` transactions at once. Each one is
+    /// checked and queued as `submitTransaction` would, one rejected
+    /// transaction doesn't keep the others from being accepted. Results are
+    /// listed in the order the transactions were given in.
+    #[method(name = "submitTransactions")]
+    async fn submit_transactions(&self, txns: Vec<Txn>) -> Result<Vec<SubmitResult>, Error>;
+
+    /// Runs the checks `submitTransaction` would against current state,
+    /// without queueing the transaction or otherwise modifying the node
+    #[method(name = "validateTransaction")]
+    async fn validate_transaction(&self, txn: Txn) -> Result<ValidationResult, Error>;
+
     /// Create a new transaction
     #[method(name = "createTxn")]
     async fn create_txn(&self, args: NewTxnArgs) -> Result<Txn, Error>;
@@ -37,6 +192,16 @@ pub trait Rpc {
     #[method(name = "getTransaction")]
     async fn get_transaction(&self, transaction_digest: TransactionDigest) -> Result<Txn, Error>;
 
+    /// Returns whether a transaction is pending, was included, along with
+    /// the block height it was included at, or was rejected or dropped from
+    /// the mempool, along with the reason. `None` if the node doesn't know
+    /// about the transaction.
+    #[method(name = "getTransactionReceipt")]
+    async fn get_transaction_receipt(
+        &self,
+        transaction_digest: TransactionDigest,
+    ) -> Result<Option<TxnReceipt>, Error>;
+
     /// List a group of transactions
     #[method(name = "listTransactions")]
     async fn list_transactions(
@@ -47,11 +212,42 @@ pub trait Rpc {
     #[method(name = "createAccount")]
     async fn create_account(&self, address: Address, account: Account) -> Result<(), Error>;
 
+    /// Replaces the state of the account stored under `address`. Accounts
+    /// keep their address once their key is rotated, so it isn't derived
+    /// from the account's public key.
     #[method(name = "updateAccount")]
-    async fn update_account(&self, account: Account) -> Result<(), Error>;
+    async fn update_account(&self, address: Address, account: Account) -> Result<(), Error>;
 
+    /// Replaces the public key of an account. The rotation must be signed
+    /// with the account's current key, see `KeyRotation`.
+    #[method(name = "rotateAccountKey")]
+    async fn rotate_account_key(&self, rotation: KeyRotation) -> Result<(), Error>;
+
+    /// Returns the account stored under the given address, or `None` if it
+    /// doesn't exist yet
     #[method(name = "getAccount")]
-    async fn get_account(&self, address: Address) -> Result<Account, Error>;
+    async fn get_account(&self, address: Address) -> Result<Option<Account>, Error>;
+
+    /// Returns the nonce of the account stored under the given address, 0 if
+    /// it doesn't exist yet
+    #[method(name = "getAccountNonce")]
+    async fn get_account_nonce(&self, address: Address) -> Result<u128, Error>;
+
+    /// Returns the nonce the next transaction sent from the given address
+    /// should use, accounting for the ones still pending in the mempool, so
+    /// clients can submit several transactions in a row without their nonces
+    /// colliding
+    #[method(name = "getNextNonce")]
+    async fn get_next_nonce(&self, address: Address) -> Result<u128, Error>;
+
+    /// Pushes the serialized form of every block the node confirms from the
+    /// moment of subscribing
+    #[subscription(
+        name = "subscribeBlocks" => "blockConfirmed",
+        unsubscribe = "unsubscribeBlocks",
+        item = Vec<u8>
+    )]
+    fn subscribe_blocks(&self);
 
     //#[method(name = "faucetDrip")]
     //async fn faucet_drip(&self, address: Address) -> Result<(), Error>;
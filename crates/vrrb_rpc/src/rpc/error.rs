@@ -0,0 +1,266 @@
+use jsonrpsee::{
+    core::Error,
+    types::error::{CallError, ErrorObject},
+};
+use mempool::ValidationQueueError;
+use primitives::Address;
+use vrrb_core::{
+    key_rotation::KeyRotationError,
+    txn::{TransactionDigest, TxAmount, TxNonce, ValidationError},
+};
+
+/// Errors reported by the JSON-RPC server. Each variant maps to a stable
+/// numeric code clients can branch on, regardless of the message attached to
+/// it:
+///
+/// | Code   | Variant               | Meaning                                         |
+/// |--------|-----------------------|-------------------------------------------------|
+/// | -32001 | `AccountNotFound`     | no account is stored under the address          |
+/// | -32002 | `InvalidSignature`    | the transaction signature does not verify       |
+/// | -32003 | `Busy`                | the validation queue is full, retry later       |
+/// | -32004 | `NonceTooLow`         | the nonce was already used by the sender        |
+/// | -32005 | `NotAccepted`         | the node does not take transactions in          |
+/// | -32006 | `InsufficientBalance` | the sender can't cover the transaction amount   |
+/// | -32007 | `Duplicate`           | the transaction is already pending              |
+/// | -32008 | `TransactionNotFound` | no transaction is stored under the digest       |
+/// | -32009 | `WrongChain`          | the transaction is meant for another network    |
+/// | -32010 | `Unauthorized`        | the admin token is missing or doesn't match     |
+/// | -32011 | `PeerNotFound`        | no peer is known under the id                   |
+/// | -32012 | `PeerUnreachable`     | the node could not connect to the peer          |
+/// | -32013 | `FeeTooLow`           | the transaction offers less than the minimum fee |
+/// | -32014 | `ReadOnly`            | the node only serves reads, e.g. an observer    |
+/// | -32015 | `TooLarge`            | the transaction exceeds the maximum size        |
+/// | -32602 | `InvalidParams`       | the request parameters are malformed            |
+/// | -32603 | `Internal`            | the node failed to process an otherwise valid request |
+///
+/// -32602 and -32603 are the codes reserved by the JSON-RPC 2.0 spec, the
+/// rest live in its range for server errors.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RpcError {
+    #[error("account {0} not found")]
+    AccountNotFound(Address),
+
+    #[error("invalid transaction signature: {0}")]
+    InvalidSignature(String),
+
+    #[error("node is busy validating other transactions, try again later")]
+    Busy,
+
+    #[error("transaction nonce {provided} is lower than the account's nonce {expected}")]
+    NonceTooLow {
+        provided: TxNonce,
+        expected: TxNonce,
+    },
+
+    #[error("node does not accept transactions: {0}")]
+    NotAccepted(String),
+
+    #[error("sender balance {balance} cannot cover an amount of {amount}")]
+    InsufficientBalance { balance: TxAmount, amount: TxAmount },
+
+    #[error("transaction is already pending")]
+    Duplicate,
+
+    #[error("transaction {0} not found")]
+    TransactionNotFound(TransactionDigest),
+
+    #[error("transaction is not meant for this network: {0}")]
+    WrongChain(String),
+
+    #[error("missing or invalid admin token")]
+    Unauthorized,
+
+    #[error("peer {0} not found")]
+    PeerNotFound(String),
+
+    #[error("peer is unreachable: {0}")]
+    PeerUnreachable(String),
+
+    #[error("transaction fee {declared} is below the minimum fee of {minimum}")]
+    FeeTooLow {
+        declared: TxAmount,
+        minimum: TxAmount,
+    },
+
+    #[error("node is read-only")]
+    ReadOnly,
+
+    #[error("transaction is {size} bytes, over the maximum of {max} bytes")]
+    TooLarge { size: usize, max: usize },
+
+    #[error("invalid params: {0}")]
+    InvalidParams(String),
+
+    #[error("internal error: {0}")]
+    Internal(String),
+}
+
+impl RpcError {
+    pub const ACCOUNT_NOT_FOUND: i32 = -32001;
+    pub const BUSY: i32 = -32003;
+    pub const DUPLICATE: i32 = -32007;
+    pub const FEE_TOO_LOW: i32 = -32013;
+    pub const INSUFFICIENT_BALANCE: i32 = -32006;
+    pub const INTERNAL: i32 = -32603;
+    pub const INVALID_PARAMS: i32 = -32602;
+    pub const INVALID_SIGNATURE: i32 = -32002;
+    pub const NONCE_TOO_LOW: i32 = -32004;
+    pub const NOT_ACCEPTED: i32 = -32005;
+    pub const PEER_NOT_FOUND: i32 = -32011;
+    pub const PEER_UNREACHABLE: i32 = -32012;
+    pub const READ_ONLY: i32 = -32014;
+    pub const TOO_LARGE: i32 = -32015;
+    pub const TRANSACTION_NOT_FOUND: i32 = -32008;
+    pub const UNAUTHORIZED: i32 = -32010;
+    pub const WRONG_CHAIN: i32 = -32009;
+
+    /// JSON-RPC error code reported to clients
+    pub fn code(&self) -> i32 {
+        match self {
+            RpcError::AccountNotFound(_) => Self::ACCOUNT_NOT_FOUND,
+            RpcError::InvalidSignature(_) => Self::INVALID_SIGNATURE,
+            RpcError::Busy => Self::BUSY,
+            RpcError::NonceTooLow { .. } => Self::NONCE_TOO_LOW,
+            RpcError::NotAccepted(_) => Self::NOT_ACCEPTED,
+            RpcError::InsufficientBalance { .. } => Self::INSUFFICIENT_BALANCE,
+            RpcError::Duplicate => Self::DUPLICATE,
+            RpcError::TransactionNotFound(_) => Self::TRANSACTION_NOT_FOUND,
+            RpcError::WrongChain(_) => Self::WRONG_CHAIN,
+            RpcError::Unauthorized => Self::UNAUTHORIZED,
+            RpcError::PeerNotFound(_) => Self::PEER_NOT_FOUND,
+            RpcError::PeerUnreachable(_) => Self::PEER_UNREACHABLE,
+            RpcError::FeeTooLow { .. } => Self::FEE_TOO_LOW,
+            RpcError::ReadOnly => Self::READ_ONLY,
+            RpcError::TooLarge { .. } => Self::TOO_LARGE,
+            RpcError::InvalidParams(_) => Self::INVALID_PARAMS,
+            RpcError::Internal(_) => Self::INTERNAL,
+        }
+    }
+}
+
+/// Extracts the JSON-RPC error code from an error returned by a client call,
+/// or `None` if the call failed before the server could answer it, e.g. on a
+/// transport error
+pub fn error_code(err: &Error) -> Option<i32> {
+    match err {
+        Error::Call(CallError::Custom(err)) => Some(err.code()),
+        Error::Call(CallError::InvalidParams(_)) => Some(RpcError::INVALID_PARAMS),
+        Error::Call(CallError::Failed(_)) => Some(RpcError::INTERNAL),
+        _ => None,
+    }
+}
+
+impl From<ValidationError> for RpcError {
+    fn from(err: ValidationError) -> Self {
+        match err {
+            ValidationError::BadSignature(reason) => RpcError::InvalidSignature(reason),
+            ValidationError::UnauthorizedKey(sender) => {
+                RpcError::InvalidSignature(format!("not signed with a key of account {sender}"))
+            },
+            ValidationError::InsufficientBalance { balance, amount } => {
+                RpcError::InsufficientBalance { balance, amount }
+            },
+            ValidationError::NonceTooLow { expected, got } => RpcError::NonceTooLow {
+                provided: got,
+                expected,
+            },
+            ValidationError::NonceTooHigh { .. } | ValidationError::AmountOverflow { .. } => {
+                RpcError::InvalidParams(err.to_string())
+            },
+            ValidationError::Duplicate => RpcError::Duplicate,
+            ValidationError::ChainIdMismatch { .. } | ValidationError::MissingChainId { .. } => {
+                RpcError::WrongChain(err.to_string())
+            },
+            ValidationError::FeeTooLow { declared, minimum } => {
+                RpcError::FeeTooLow { declared, minimum }
+            },
+            ValidationError::TooLarge { size, max } => RpcError::TooLarge { size, max },
+        }
+    }
+}
+
+impl From<KeyRotationError> for RpcError {
+    fn from(err: KeyRotationError) -> Self {
+        match err {
+            KeyRotationError::BadSignature(reason) => RpcError::InvalidSignature(reason),
+            KeyRotationError::InvalidPublicKey(_) | KeyRotationError::NonceMismatch { .. } => {
+                RpcError::InvalidParams(err.to_string())
+            },
+        }
+    }
+}
+
+impl From<ValidationQueueError> for RpcError {
+    fn from(err: ValidationQueueError) -> Self {
+        match err {
+            ValidationQueueError::Busy => RpcError::Busy,
+            ValidationQueueError::Closed => RpcError::Internal(err.to_string()),
+        }
+    }
+}
+
+impl From<RpcError> for Error {
+    fn from(err: RpcError) -> Self {
+        Error::Call(CallError::Custom(ErrorObject::owned(
+            err.code(),
+            err.to_string(),
+            None::<()>,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_map_to_their_documented_codes() {
+        let address = Address::new(*vrrb_core::keypair::KeyPair::random().get_miner_public_key());
+
+        let table = [
+            (RpcError::AccountNotFound(address), -32001),
+            (RpcError::InvalidSignature(String::new()), -32002),
+            (RpcError::Busy, -32003),
+            (
+                RpcError::NonceTooLow {
+                    provided: 0,
+                    expected: 1,
+                },
+                -32004,
+            ),
+            (RpcError::NotAccepted(String::new()), -32005),
+            (
+                RpcError::InsufficientBalance {
+                    balance: 0,
+                    amount: 1,
+                },
+                -32006,
+            ),
+            (RpcError::Duplicate, -32007),
+            (
+                RpcError::TransactionNotFound(TransactionDigest::default()),
+                -32008,
+            ),
+            (RpcError::WrongChain(String::new()), -32009),
+            (RpcError::Unauthorized, -32010),
+            (RpcError::PeerNotFound(String::new()), -32011),
+            (RpcError::PeerUnreachable(String::new()), -32012),
+            (
+                RpcError::FeeTooLow {
+                    declared: 0,
+                    minimum: 1,
+                },
+                -32013,
+            ),
+            (RpcError::ReadOnly, -32014),
+            (RpcError::TooLarge { size: 2, max: 1 }, -32015),
+            (RpcError::InvalidParams(String::new()), -32602),
+            (RpcError::Internal(String::new()), -32603),
+        ];
+
+        for (err, code) in table {
+            assert_eq!(err.code(), code);
+            assert_eq!(error_code(&Error::from(err)), Some(code));
+        }
+    }
+}
@@ -1,6 +1,10 @@
 pub mod api;
 pub mod client;
+mod error;
+mod peers;
 mod server;
 mod server_impl;
+pub use error::*;
+pub use peers::*;
 pub use server::*;
 pub use server_impl::*;
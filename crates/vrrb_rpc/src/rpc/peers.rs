@@ -0,0 +1,51 @@
+use std::{fmt::Debug, net::SocketAddr, time::Duration};
+
+use async_trait::async_trait;
+use primitives::PeerId;
+
+use super::{api::PeerInfo, RpcError};
+
+/// How long `addPeer` waits for a connection to the new peer before giving up
+pub const PEER_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PeerTableError {
+    #[error("unable to connect to {0}: {1}")]
+    Unreachable(SocketAddr, String),
+
+    #[error("connecting to {0} timed out")]
+    Timeout(SocketAddr),
+
+    #[error("no peer with id {0} is known")]
+    UnknownPeer(String),
+}
+
+impl From<PeerTableError> for RpcError {
+    fn from(err: PeerTableError) -> Self {
+        match err {
+            PeerTableError::Unreachable(..) | PeerTableError::Timeout(_) => {
+                RpcError::PeerUnreachable(err.to_string())
+            },
+            PeerTableError::UnknownPeer(peer_id) => RpcError::PeerNotFound(peer_id),
+        }
+    }
+}
+
+/// Peer table of the node's broadcast engine, as seen by the JSON-RPC server.
+/// Kept behind a trait so the server doesn't depend on the network stack.
+#[async_trait]
+pub trait PeerTable: Debug + Send + Sync {
+    /// Lists every peer the node is connected to or knows about
+    fn peers(&self) -> Vec<PeerInfo>;
+
+    /// Connects to the peer at `addr`, returning once connected or after
+    /// `timeout`
+    async fn add_peer(
+        &self,
+        addr: SocketAddr,
+        timeout: Duration,
+    ) -> Result<PeerInfo, PeerTableError>;
+
+    /// Disconnects from the peer identified by `peer_id` and forgets about it
+    fn remove_peer(&self, peer_id: &PeerId) -> Result<(), PeerTableError>;
+}
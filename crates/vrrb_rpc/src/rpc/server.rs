@@ -1,4 +1,7 @@
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::Arc,
+};
 
 use async_trait::async_trait;
 use jsonrpsee::{
@@ -6,25 +9,60 @@ use jsonrpsee::{
     server::{ServerBuilder, ServerHandle, SubscriptionSink},
     types::SubscriptionResult,
 };
-use mempool::{LeftRightMempool, Mempool, MempoolReadHandleFactory};
-use primitives::NodeType;
+use mempool::{LeftRightMempool, Mempool, MempoolReadHandleFactory, ValidationQueue};
+use primitives::{ChainId, NodeId, NodeType, DEFAULT_CHAIN_ID};
 use storage::vrrbdb::{VrrbDb, VrrbDbConfig, VrrbDbReadHandle};
-use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio::sync::{
+    broadcast,
+    mpsc::{unbounded_channel, UnboundedSender},
+};
 use vrrb_core::{
     account::Account,
     event_router::{DirectedEvent, Event, Topic},
-    txn::NewTxnArgs,
+    fee::{FeeModel, FlatFee},
+    node_status::NodeStatusHandle,
+    txn::{NewTxnArgs, DEFAULT_MAX_TXN_SIZE_BYTES},
+    txn_receipts::TxnReceiptsHandle,
 };
 
-use crate::rpc::{api::RpcServer, server_impl::RpcServerImpl};
+use crate::rpc::{api::RpcServer, server_impl::RpcServerImpl, PeerTable};
+
+/// Amount of confirmed blocks buffered for each block subscriber
+pub const CONFIRMED_BLOCKS_BUFFER_SIZE: usize = 100;
 
 #[derive(Debug, Clone)]
 pub struct JsonRpcServerConfig {
     pub address: SocketAddr,
     pub vrrbdb_read_handle: VrrbDbReadHandle,
     pub mempool_read_handle_factory: MempoolReadHandleFactory,
+    pub node_id: NodeId,
     pub node_type: NodeType,
+    /// Chain id of the network the node belongs to
+    pub chain_id: ChainId,
+    /// Turns away transactions that don't specify a chain id
+    pub reject_txns_without_chain_id: bool,
+    /// Computes the minimum fee submitted transactions have to offer
+    pub fee_model: Arc<dyn FeeModel>,
+    /// Submitted transactions encoding to more bytes than this are rejected
+    pub max_txn_size_bytes: usize,
+    /// Turns away transaction submissions, set while networking is disabled
+    /// unless the node is configured to keep them in its local mempool
+    pub reject_txns_without_networking: bool,
+    pub node_status_handle: NodeStatusHandle,
     pub events_tx: UnboundedSender<DirectedEvent>,
+    /// Queue submitted transactions wait in until the validator gets to them
+    pub validation_queue: ValidationQueue,
+    /// Lifecycle of the transactions the node handled, submitted ones are
+    /// recorded as pending
+    pub txn_receipts: TxnReceiptsHandle,
+    /// Publishes confirmed blocks to every client subscribed to them
+    pub confirmed_blocks_tx: broadcast::Sender<Vec<u8>>,
+    /// Peer table of the node's broadcast engine, unset while networking is
+    /// disabled
+    pub peer_table: Option<Arc<dyn PeerTable>>,
+    /// Token clients must present to manage peers, every client may when
+    /// unset
+    pub admin_token: Option<String>,
 }
 
 #[derive(Debug)]
@@ -35,10 +73,22 @@ impl JsonRpcServer {
         let server = ServerBuilder::default().build(config.address).await?;
 
         let server_impl = RpcServerImpl {
+            node_id: config.node_id.clone(),
             node_type: config.node_type,
+            chain_id: config.chain_id,
+            reject_txns_without_chain_id: config.reject_txns_without_chain_id,
+            fee_model: config.fee_model.clone(),
+            max_txn_size_bytes: config.max_txn_size_bytes,
+            reject_txns_without_networking: config.reject_txns_without_networking,
+            node_status_handle: config.node_status_handle.clone(),
             events_tx: config.events_tx.clone(),
             vrrbdb_read_handle: config.vrrbdb_read_handle.clone(),
             mempool_read_handle_factory: config.mempool_read_handle_factory.clone(),
+            validation_queue: config.validation_queue.clone(),
+            txn_receipts: config.txn_receipts.clone(),
+            confirmed_blocks_tx: config.confirmed_blocks_tx.clone(),
+            peer_table: config.peer_table.clone(),
+            admin_token: config.admin_token.clone(),
         };
 
         let addr = server.local_addr()?;
@@ -61,21 +111,37 @@ impl Default for JsonRpcServerConfig {
 
         vrrbdb_config.path = db_path;
 
-        let vrrbdb = VrrbDb::new(vrrbdb_config);
+        let vrrbdb = VrrbDb::new(vrrbdb_config).expect("failed to open a temporary VrrbDb");
         let vrrbdb_read_handle = vrrbdb.read_handle();
 
         let mempool = LeftRightMempool::default();
         let mempool_read_handle_factory = mempool.factory();
 
+        let node_id = String::from("rpc-node");
         let node_type = NodeType::RPCNode;
+        let node_status_handle = NodeStatusHandle::new();
         let (events_tx, _) = unbounded_channel();
+        let (validation_queue, _) = ValidationQueue::new(100);
+        let (confirmed_blocks_tx, _) = broadcast::channel(CONFIRMED_BLOCKS_BUFFER_SIZE);
 
         JsonRpcServerConfig {
             address,
             vrrbdb_read_handle,
             mempool_read_handle_factory,
+            node_id,
             node_type,
+            chain_id: DEFAULT_CHAIN_ID,
+            reject_txns_without_chain_id: false,
+            fee_model: Arc::new(FlatFee::default()),
+            max_txn_size_bytes: DEFAULT_MAX_TXN_SIZE_BYTES,
+            reject_txns_without_networking: false,
+            node_status_handle,
             events_tx,
+            validation_queue,
+            txn_receipts: TxnReceiptsHandle::default(),
+            confirmed_blocks_tx,
+            peer_table: None,
+            admin_token: None,
         }
     }
 }
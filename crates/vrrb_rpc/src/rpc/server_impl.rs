@@ -1,4 +1,8 @@
-use std::{collections::HashMap, net::SocketAddr};
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+    sync::Arc,
+};
 
 use async_trait::async_trait;
 use jsonrpsee::{
@@ -6,26 +10,66 @@ use jsonrpsee::{
     server::{ServerBuilder, SubscriptionSink},
     types::SubscriptionResult,
 };
-use mempool::MempoolReadHandleFactory;
-use primitives::{Address, NodeType};
+use mempool::{MempoolReadHandleFactory, TxnRecord, ValidationQueue};
+use primitives::{Address, ChainId, NodeId, NodeType, PeerId, PublicKey, PROTOCOL_VERSION};
 use storage::vrrbdb::VrrbDbReadHandle;
-use telemetry::{debug, error};
-use tokio::sync::mpsc::UnboundedSender;
+use telemetry::{debug, error, warn};
+use tokio::sync::{
+    broadcast::{self, error::RecvError},
+    mpsc::UnboundedSender,
+};
 use vrrb_core::{
     account::Account,
     event_router::{DirectedEvent, Event, Topic},
+    fee::FeeModel,
+    key_rotation::KeyRotation,
+    node_status::NodeStatusHandle,
     serde_helpers::{encode_to_binary, encode_to_json},
-    txn::{NewTxnArgs, TransactionDigest, Txn},
+    txn::{NewTxnArgs, TransactionDigest, TxAmount, Txn},
+    txn_receipts::{TxnReceipt, TxnReceiptsHandle},
 };
 
-use super::api::FullMempoolSnapshot;
+use super::{
+    api::{
+        FullMempoolSnapshot,
+        NetworkParams,
+        NodeStatus,
+        PeerInfo,
+        Rejection,
+        SubmitResult,
+        ValidationResult,
+        MAX_SUBMIT_BATCH_SIZE,
+    },
+    PeerTable,
+    RpcError,
+    PEER_CONNECT_TIMEOUT,
+};
 use crate::rpc::api::{FullStateSnapshot, RpcServer};
 
+fn rejection(err: RpcError) -> Rejection {
+    Rejection {
+        code: err.code(),
+        reason: err.to_string(),
+    }
+}
+
 pub struct RpcServerImpl {
+    pub node_id: NodeId,
     pub node_type: NodeType,
+    pub chain_id: ChainId,
+    pub reject_txns_without_chain_id: bool,
+    pub fee_model: Arc<dyn FeeModel>,
+    pub max_txn_size_bytes: usize,
+    pub reject_txns_without_networking: bool,
+    pub node_status_handle: NodeStatusHandle,
     pub vrrbdb_read_handle: VrrbDbReadHandle,
     pub mempool_read_handle_factory: MempoolReadHandleFactory,
     pub events_tx: UnboundedSender<DirectedEvent>,
+    pub validation_queue: ValidationQueue,
+    pub txn_receipts: TxnReceiptsHandle,
+    pub confirmed_blocks_tx: broadcast::Sender<Vec<u8>>,
+    pub peer_table: Option<Arc<dyn PeerTable>>,
+    pub admin_token: Option<String>,
 }
 
 #[async_trait]
@@ -42,37 +86,173 @@ impl RpcServer for RpcServerImpl {
         Ok(values)
     }
 
+    async fn get_mempool(&self, limit: Option<usize>) -> Result<Vec<TxnRecord>, Error> {
+        let records = self.mempool_read_handle_factory.records(limit);
+
+        Ok(records)
+    }
+
     async fn get_node_type(&self) -> Result<NodeType, Error> {
         Ok(self.node_type)
     }
 
+    async fn get_node_status(&self) -> Result<NodeStatus, Error> {
+        let status = NodeStatus {
+            node_id: self.node_id.clone(),
+            node_type: self.node_type,
+            running_status: self.node_status_handle.running_status(),
+            uptime_secs: self.node_status_handle.uptime_secs(),
+            peer_count: self.node_status_handle.peer_count(),
+            mempool_size: self.mempool_read_handle_factory.handle().len(),
+            validation_queue_depth: self.validation_queue.depth(),
+            current_block_height: self.node_status_handle.block_height(),
+        };
+
+        Ok(status)
+    }
+
+    async fn get_network_params(&self) -> Result<NetworkParams, Error> {
+        let params = NetworkParams {
+            chain_id: self.chain_id,
+            protocol_version: PROTOCOL_VERSION,
+            genesis_hash: self.node_status_handle.genesis_hash(),
+            current_block_height: self.node_status_handle.block_height(),
+            node_type_distribution: self.node_status_handle.peer_node_types(),
+        };
+
+        Ok(params)
+    }
+
+    async fn get_peers(&self, token: Option<String>) -> Result<Vec<PeerInfo>, Error> {
+        self.authorize(token.as_deref())?;
+
+        let peers = self
+            .peer_table
+            .as_ref()
+            .map(|peer_table| peer_table.peers())
+            .unwrap_or_default();
+
+        Ok(peers)
+    }
+
+    async fn add_peer(
+        &self,
+        address: SocketAddr,
+        token: Option<String>,
+    ) -> Result<PeerInfo, Error> {
+        self.authorize(token.as_deref())?;
+
+        let peer_table = self
+            .peer_table
+            .as_ref()
+            .ok_or_else(|| RpcError::PeerUnreachable(String::from("networking is disabled")))?;
+
+        let peer = peer_table
+            .add_peer(address, PEER_CONNECT_TIMEOUT)
+            .await
+            .map_err(RpcError::from)?;
+
+        Ok(peer)
+    }
+
+    async fn remove_peer(&self, peer_id: PeerId, token: Option<String>) -> Result<(), Error> {
+        self.authorize(token.as_deref())?;
+
+        let peer_table = self
+            .peer_table
+            .as_ref()
+            .ok_or_else(|| RpcError::PeerNotFound(String::from_utf8_lossy(&peer_id).into()))?;
+
+        peer_table.remove_peer(&peer_id).map_err(RpcError::from)?;
+
+        Ok(())
+    }
+
+    async fn submit_transaction(&self, txn: Txn) -> Result<TransactionDigest, Error> {
+        self.check_submission(&txn)?;
+
+        let txn_digest = txn.digest();
+
+        self.queue_txn(txn)?;
+
+        Ok(txn_digest)
+    }
+
+    async fn submit_transactions(&self, txns: Vec<Txn>) -> Result<Vec<SubmitResult>, Error> {
+        if txns.len() > MAX_SUBMIT_BATCH_SIZE {
+            return Err(RpcError::InvalidParams(format!(
+                "batch holds {} transactions, over the maximum of {MAX_SUBMIT_BATCH_SIZE}",
+                txns.len()
+            ))
+            .into());
+        }
+
+        // NOTE: accepted transactions only reach the mempool once the event
+        // router delivers them, so copies within the batch are caught here
+        let mut accepted = HashSet::new();
+
+        let results = txns
+            .into_iter()
+            .map(|txn| {
+                let digest = txn.digest();
+
+                let outcome = if accepted.contains(&digest) {
+                    Err(RpcError::Duplicate)
+                } else {
+                    self.check_submission(&txn)
+                        .and_then(|_| self.queue_txn(txn))
+                };
+
+                if outcome.is_ok() {
+                    accepted.insert(digest.clone());
+                }
+
+                SubmitResult {
+                    digest,
+                    rejection: outcome.err().map(rejection),
+                }
+            })
+            .collect();
+
+        Ok(results)
+    }
+
+    async fn validate_transaction(&self, txn: Txn) -> Result<ValidationResult, Error> {
+        let rejection = self.check_submission(&txn).err().map(rejection);
+
+        Ok(ValidationResult {
+            digest: txn.digest(),
+            estimated_fee: txn.fee().max(self.estimate_fee(&txn)),
+            rejection,
+        })
+    }
+
     async fn create_txn(&self, args: NewTxnArgs) -> Result<Txn, Error> {
+        self.check_writable()?;
+
         let txn = Txn::new(args);
         let event = Event::NewTxnCreated(txn.clone());
 
         debug!("{:?}", event);
 
         if self.events_tx.is_closed() {
-            let err = Error::Custom("event router is closed".to_string());
+            let err = RpcError::Internal("event router is closed".to_string());
 
             error!("failed to publish write: {:?}", err);
 
-            return Err(err);
+            return Err(err.into());
         }
 
-        self.events_tx
-            .send((Topic::Storage, event))
-            .map_err(|err| {
-                error!("could not queue transaction to mempool: {err}");
-                Error::Custom(err.to_string())
-            })?;
+        self.queue_txn(txn.clone())?;
 
         Ok(txn)
     }
 
     async fn create_account(&self, address: Address, account: Account) -> Result<(), Error> {
+        self.check_writable()?;
+
         let account_bytes =
-            encode_to_binary(&account).map_err(|err| Error::Custom(err.to_string()))?;
+            encode_to_binary(&account).map_err(|err| RpcError::Internal(err.to_string()))?;
 
         let event = Event::CreateAccountRequested((address.clone(), account_bytes));
 
@@ -82,7 +262,7 @@ impl RpcServer for RpcServerImpl {
             .send((Topic::Storage, event.clone()))
             .map_err(|err| {
                 error!("could not create account: {err}");
-                Error::Custom(err.to_string())
+                RpcError::Internal(err.to_string())
             })?;
 
         telemetry::info!("requested account creation for address: {}", address);
@@ -90,17 +270,59 @@ impl RpcServer for RpcServerImpl {
         Ok(())
     }
 
-    async fn update_account(&self, account: Account) -> Result<(), Error> {
-        debug!("Received an updateAccount RPC request");
+    async fn update_account(&self, address: Address, account: Account) -> Result<(), Error> {
+        debug!("Received an updateAccount RPC request for {address}");
+
+        self.check_writable()?;
+
+        PublicKey::from_slice(&account.pubkey)
+            .map_err(|err| RpcError::InvalidParams(format!("invalid account public key: {err}")))?;
+
+        if self.vrrbdb_read_handle.get_account(&address).is_none() {
+            return Err(RpcError::AccountNotFound(address).into());
+        }
 
         let account_bytes =
-            encode_to_binary(&account).map_err(|err| Error::Custom(err.to_string()))?;
+            encode_to_binary(&account).map_err(|err| RpcError::Internal(err.to_string()))?;
+
+        let event = Event::AccountUpdateRequested((address.clone(), account_bytes));
 
-        // let event = Event::RequestedAccountUpdate((account.hash, account_bytes));
-        // self.events_tx.send((Topic::State, event)).map_err(|err| {
-        //     error!("could not update account: {err}");
-        //     Error::Custom(err.to_string())
-        // })?;
+        self.events_tx
+            .send((Topic::Storage, event))
+            .map_err(|err| {
+                error!("could not update account: {err}");
+                RpcError::Internal(err.to_string())
+            })?;
+
+        telemetry::info!("requested account update for address: {}", address);
+
+        Ok(())
+    }
+
+    async fn rotate_account_key(&self, rotation: KeyRotation) -> Result<(), Error> {
+        debug!(
+            "Received a rotateAccountKey RPC request for {}",
+            rotation.address
+        );
+
+        self.check_writable()?;
+
+        let account = self
+            .vrrbdb_read_handle
+            .get_account(&rotation.address)
+            .ok_or_else(|| RpcError::AccountNotFound(rotation.address.clone()))?;
+
+        // NOTE: the state module checks it again before applying it
+        rotation
+            .validate_against(&account)
+            .map_err(RpcError::from)?;
+
+        self.events_tx
+            .send((Topic::Storage, Event::AccountKeyRotationRequested(rotation)))
+            .map_err(|err| {
+                error!("could not rotate account key: {err}");
+                RpcError::Internal(err.to_string())
+            })?;
 
         Ok(())
     }
@@ -114,10 +336,46 @@ impl RpcServer for RpcServerImpl {
 
         match value {
             Some(txn) => return Ok(txn.to_owned()),
-            None => return Err(Error::Custom("unable to find transaction".to_string())),
+            None => return Err(RpcError::TransactionNotFound(transaction_digest).into()),
         }
     }
 
+    async fn get_transaction_receipt(
+        &self,
+        transaction_digest: TransactionDigest,
+    ) -> Result<Option<TxnReceipt>, Error> {
+        debug!("Received a getTransactionReceipt RPC request for {transaction_digest}");
+
+        if let Some(receipt) = self.txn_receipts.get(&transaction_digest) {
+            return Ok(Some(receipt));
+        }
+
+        // NOTE: the receipts held in memory only cover the latest txns, older
+        // ones are looked up in the transaction store
+        if let Some(receipt) = self.vrrbdb_read_handle.get_txn_receipt(&transaction_digest) {
+            return Ok(Some(receipt));
+        }
+
+        // NOTE: txns stored before receipts were persisted have none, they're
+        // looked up in the mempool and among the stored txns instead
+        if self
+            .mempool_read_handle_factory
+            .contains(&transaction_digest)
+        {
+            return Ok(Some(TxnReceipt::pending(transaction_digest)));
+        }
+
+        if self
+            .vrrbdb_read_handle
+            .transaction_store_values()
+            .contains_key(&transaction_digest)
+        {
+            return Ok(Some(TxnReceipt::included(transaction_digest, None)));
+        }
+
+        Ok(None)
+    }
+
     async fn list_transactions(
         &self,
         digests: Vec<TransactionDigest>,
@@ -138,17 +396,161 @@ impl RpcServer for RpcServerImpl {
         Ok(values)
     }
 
-    async fn get_account(&self, address: Address) -> Result<Account, Error> {
+    async fn get_account(&self, address: Address) -> Result<Option<Account>, Error> {
         telemetry::info!("retrieving account {address}");
 
-        let values = self.vrrbdb_read_handle.state_store_values();
-        let value = values.get(&address);
+        let value = self.vrrbdb_read_handle.get_account(&address);
 
         debug!("Received getAccount RPC Request: {value:?}");
 
-        match value {
-            Some(account) => return Ok(account.to_owned()),
-            None => return Err(Error::Custom("unable to find account".to_string())),
+        Ok(value)
+    }
+
+    async fn get_account_nonce(&self, address: Address) -> Result<u128, Error> {
+        debug!("Received getAccountNonce RPC Request for {address}");
+
+        let nonce = self
+            .vrrbdb_read_handle
+            .get_account(&address)
+            .map(|account| account.nonce as u128)
+            .unwrap_or_default();
+
+        Ok(nonce)
+    }
+
+    async fn get_next_nonce(&self, address: Address) -> Result<u128, Error> {
+        debug!("Received getNextNonce RPC Request for {address}");
+
+        let account_nonce = self.get_account_nonce(address.clone()).await?;
+
+        // NOTE: account nonces are the last one their sender used, the
+        // mempool knows of the ones used since, up to the first gap
+        let next_nonce = self
+            .mempool_read_handle_factory
+            .next_nonce_of(&address)
+            .map_or(account_nonce + 1, |next_nonce| {
+                next_nonce.max(account_nonce + 1)
+            });
+
+        Ok(next_nonce)
+    }
+
+    fn subscribe_blocks(&self, mut sink: SubscriptionSink) -> SubscriptionResult {
+        let mut confirmed_blocks_rx = self.confirmed_blocks_tx.subscribe();
+
+        sink.accept()?;
+
+        // NOTE: the forwarding task ends as soon as the client unsubscribes or
+        // disconnects, so it can't outlive the subscription
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = sink.closed() => break,
+                    block = confirmed_blocks_rx.recv() => match block {
+                        Ok(block) => match sink.send(&block) {
+                            Ok(true) => {},
+                            Ok(false) => break,
+                            Err(err) => error!("failed to serialize confirmed block: {err}"),
+                        },
+                        Err(RecvError::Lagged(missed)) => {
+                            warn!("block subscriber lagged behind, missing {missed} blocks");
+                        },
+                        Err(RecvError::Closed) => break,
+                    },
+                }
+            }
+        });
+
+        Ok(())
+    }
+}
+
+impl RpcServerImpl {
+    /// Minimum fee the node charges for `txn`
+    pub fn estimate_fee(&self, txn: &Txn) -> TxAmount {
+        self.fee_model.estimate_fee(txn)
+    }
+
+    /// Checks `token` against the admin token, if one is configured
+    fn authorize(&self, token: Option<&str>) -> Result<(), RpcError> {
+        match &self.admin_token {
+            Some(admin_token) if token != Some(admin_token.as_str()) => Err(RpcError::Unauthorized),
+            _ => Ok(()),
         }
     }
+
+    /// Hands a transaction over to the mempool and queues it for validation.
+    /// Room in the validation queue is reserved first, so a busy node turns
+    /// the transaction away before the mempool ever sees it.
+    fn queue_txn(&self, txn: Txn) -> Result<(), RpcError> {
+        let permit = self.validation_queue.try_reserve()?;
+
+        self.events_tx
+            .send((Topic::Storage, Event::NewTxnCreated(txn.clone())))
+            .map_err(|err| {
+                error!("could not queue transaction to mempool: {err}");
+                RpcError::Internal(err.to_string())
+            })?;
+
+        let txn_digest = txn.digest();
+
+        permit.send(txn);
+
+        self.txn_receipts.record(TxnReceipt::pending(txn_digest));
+
+        Ok(())
+    }
+
+    /// Turns away writes on nodes that only serve reads
+    fn check_writable(&self) -> Result<(), RpcError> {
+        if self.node_type.is_read_only() {
+            return Err(RpcError::ReadOnly);
+        }
+
+        Ok(())
+    }
+
+    /// Runs the checks a transaction must pass before it is accepted into the
+    /// node's mempool
+    fn check_submission(&self, txn: &Txn) -> Result<(), RpcError> {
+        self.check_writable()?;
+
+        if self.reject_txns_without_networking {
+            return Err(RpcError::NotAccepted(String::from(
+                "networking is disabled",
+            )));
+        }
+
+        if !accepts_transactions(self.node_type) {
+            return Err(RpcError::NotAccepted(format!(
+                "{:?} nodes don't accept transactions",
+                self.node_type
+            )));
+        }
+
+        // NOTE: checked ahead of everything else so oversized txns aren't
+        // hashed or looked up any further
+        txn.validate_size(self.max_txn_size_bytes)?;
+
+        if self.mempool_read_handle_factory.contains(&txn.digest()) {
+            return Err(RpcError::Duplicate);
+        }
+
+        let sender_account = self.vrrbdb_read_handle.get_account(&txn.sender());
+
+        // NOTE: same checks the validator module runs once the txn is pending
+        txn.validate_chain_id(self.chain_id, !self.reject_txns_without_chain_id)?;
+        txn.validate_fee(self.estimate_fee(txn))?;
+        txn.validate_against(sender_account.as_ref())?;
+
+        Ok(())
+    }
+}
+
+/// Node types that take transactions in from clients
+fn accepts_transactions(node_type: NodeType) -> bool {
+    matches!(
+        node_type,
+        NodeType::Full | NodeType::Light | NodeType::Validator | NodeType::RPCNode
+    )
 }
@@ -1,11 +1,18 @@
-use std::str::FromStr;
+use std::{path::PathBuf, str::FromStr};
 
 use axum::{body::Body, http::Request};
-use axum_server::tls_rustls::RustlsConfig;
-use hyper::{Client, StatusCode};
+use hyper::{body::to_bytes, Client, StatusCode};
+use mempool::LeftRightMempool;
+use primitives::RuntimeModuleState;
+use serde_json::Value;
 use tokio::sync::broadcast::channel;
-use vrrb_core::event_router::Event;
-use vrrb_rpc::http::*;
+use vrrb_core::{
+    event_router::{Event, EventCounter},
+    helpers::generate_random_string,
+    node_status::NodeStatusHandle,
+    txn::null_txn,
+};
+use vrrb_rpc::{http::*, ApiError};
 
 mod common;
 
@@ -16,7 +23,12 @@ async fn server_starts_and_stops() {
         api_title: "Node HTTP API".into(),
         api_version: "1.0".into(),
         server_timeout: None,
-        tls_config: None,
+        tls: None,
+        cors_allowed_origins: vec![],
+        bearer_token: None,
+        request_logging: false,
+        log_level: telemetry::Level::INFO,
+        state: HttpApiState::default(),
     };
 
     let api = HttpApiServer::new(config).unwrap();
@@ -48,19 +60,188 @@ async fn server_starts_and_stops() {
 }
 
 #[tokio::test]
-#[ignore = "refactoring tls support on another PR"]
+async fn server_accepts_ipv6_connections() {
+    let config = HttpApiServerConfig {
+        address: "[::1]:0".into(),
+        api_title: "Node HTTP API".into(),
+        api_version: "1.0".into(),
+        server_timeout: None,
+        tls: None,
+        cors_allowed_origins: vec![],
+        bearer_token: None,
+        request_logging: false,
+        log_level: telemetry::Level::INFO,
+        state: HttpApiState::default(),
+    };
+
+    let api = HttpApiServer::new(config).unwrap();
+
+    let (ctrl_tx, mut ctrl_rx) = channel(1);
+
+    let addr = api.address().unwrap();
+
+    assert!(addr.is_ipv6());
+
+    let server_handle = tokio::spawn(async move {
+        api.start(&mut ctrl_rx).await.unwrap();
+    });
+
+    let client = Client::new();
+
+    let response = client
+        .request(
+            Request::builder()
+                .uri(format!("http://{}/health", addr))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    ctrl_tx.send(Event::Stop).unwrap();
+    server_handle.await.unwrap();
+}
+
+#[tokio::test]
+async fn health_reports_the_node_running_status() {
+    let node_status_handle = NodeStatusHandle::new();
+    node_status_handle.set_running_status(RuntimeModuleState::Running);
+
+    let config = HttpApiServerConfig {
+        address: "127.0.0.1:0".into(),
+        api_title: "Node HTTP API".into(),
+        api_version: "1.0".into(),
+        server_timeout: None,
+        tls: None,
+        cors_allowed_origins: vec![],
+        bearer_token: None,
+        request_logging: false,
+        log_level: telemetry::Level::INFO,
+        state: HttpApiState {
+            node_status_handle,
+            ..HttpApiState::default()
+        },
+    };
+
+    let api = HttpApiServer::new(config).unwrap();
+
+    let (ctrl_tx, mut ctrl_rx) = channel(1);
+
+    let addr = api.address().unwrap();
+
+    let server_handle = tokio::spawn(async move {
+        api.start(&mut ctrl_rx).await.unwrap();
+    });
+
+    let response = Client::new()
+        .get(format!("http://{}/health", addr).parse().unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = to_bytes(response.into_body()).await.unwrap();
+    let health: Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(health["status"], "ok");
+    assert_eq!(health["running_status"], "running");
+    assert!(health["uptime_secs"].is_u64());
+
+    ctrl_tx.send(Event::Stop).unwrap();
+    server_handle.await.unwrap();
+}
+
+#[tokio::test]
+async fn metrics_are_exposed_in_prometheus_format() {
+    let node_status_handle = NodeStatusHandle::new();
+    node_status_handle.peer_added();
+    node_status_handle.peer_added();
+
+    let mut mempool = LeftRightMempool::default();
+    mempool.insert(null_txn()).unwrap();
+    mempool.insert(null_txn()).unwrap();
+
+    let config = HttpApiServerConfig {
+        address: "127.0.0.1:0".into(),
+        api_title: "Node HTTP API".into(),
+        api_version: "1.0".into(),
+        server_timeout: None,
+        tls: None,
+        cors_allowed_origins: vec![],
+        bearer_token: None,
+        request_logging: false,
+        log_level: telemetry::Level::INFO,
+        state: HttpApiState {
+            node_status_handle,
+            mempool_read_handle_factory: mempool.factory(),
+            processed_events: EventCounter::default(),
+            delivered_events: EventCounter::default(),
+            dropped_events: EventCounter::default(),
+        },
+    };
+
+    let api = HttpApiServer::new(config).unwrap();
+
+    let (ctrl_tx, mut ctrl_rx) = channel(1);
+
+    let addr = api.address().unwrap();
+
+    let server_handle = tokio::spawn(async move {
+        api.start(&mut ctrl_rx).await.unwrap();
+    });
+
+    let response = Client::new()
+        .get(format!("http://{}/metrics", addr).parse().unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = to_bytes(response.into_body()).await.unwrap();
+    let metrics = String::from_utf8(body.to_vec()).unwrap();
+
+    assert!(metrics.contains("# TYPE vrrb_peer_count gauge"));
+    assert!(metrics.contains("vrrb_peer_count 2"));
+    assert!(metrics.contains("vrrb_mempool_size 1"));
+    assert!(metrics.contains("# TYPE vrrb_mempool_inserts_total counter"));
+    assert!(metrics.contains("vrrb_mempool_inserts_total 1"));
+    assert!(metrics.contains("vrrb_mempool_duplicates_total 1"));
+    assert!(metrics.contains("vrrb_mempool_evictions_total 0"));
+    assert!(metrics.contains("vrrb_mempool_read_lag 0"));
+    assert!(metrics.contains("# TYPE vrrb_events_processed_total counter"));
+    assert!(metrics.contains("# TYPE vrrb_events_delivered_total counter"));
+    assert!(metrics.contains("# TYPE vrrb_events_dropped_total counter"));
+
+    ctrl_tx.send(Event::Stop).unwrap();
+    server_handle.await.unwrap();
+}
+
+fn write_pem_file(contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(generate_random_string());
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+#[tokio::test]
 async fn server_uses_https() {
-    let tls_config =
-        RustlsConfig::from_pem(common::MOCK_TEST_CERT.into(), common::MOCK_TEST_KEY.into())
-            .await
-            .unwrap();
+    let tls = TlsConfig {
+        cert_path: write_pem_file(common::MOCK_TEST_CERT),
+        key_path: write_pem_file(common::MOCK_TEST_KEY),
+    };
 
     let config = HttpApiServerConfig {
         address: "127.0.0.1:0".into(),
         api_title: "Node HTTP API".into(),
         api_version: "1.0".into(),
         server_timeout: None,
-        tls_config: Some(tls_config),
+        tls: Some(tls),
+        cors_allowed_origins: vec![],
+        bearer_token: None,
+        request_logging: false,
+        log_level: telemetry::Level::INFO,
+        state: HttpApiState::default(),
     };
 
     let api = HttpApiServer::new(config).unwrap();
@@ -73,7 +254,15 @@ async fn server_uses_https() {
         api.start(&mut ctrl_rx).await.unwrap();
     });
 
-    let response = reqwest::get(&format!("https://{}/health", addr))
+    // NOTE: the mock certificate is self-signed
+    let client = reqwest::Client::builder()
+        .danger_accept_invalid_certs(true)
+        .build()
+        .unwrap();
+
+    let response = client
+        .get(format!("https://{}/health", addr))
+        .send()
         .await
         .unwrap();
 
@@ -82,3 +271,28 @@ async fn server_uses_https() {
     ctrl_tx.send(Event::Stop).unwrap();
     server_handle.await.unwrap();
 }
+
+#[tokio::test]
+async fn server_fails_to_build_with_missing_tls_files() {
+    let tls = TlsConfig {
+        cert_path: std::env::temp_dir().join(generate_random_string()),
+        key_path: write_pem_file(common::MOCK_TEST_KEY),
+    };
+
+    let config = HttpApiServerConfig {
+        address: "127.0.0.1:0".into(),
+        api_title: "Node HTTP API".into(),
+        api_version: "1.0".into(),
+        server_timeout: None,
+        tls: Some(tls),
+        cors_allowed_origins: vec![],
+        bearer_token: None,
+        request_logging: false,
+        log_level: telemetry::Level::INFO,
+        state: HttpApiState::default(),
+    };
+
+    let err = HttpApiServer::new(config).unwrap_err();
+
+    assert!(matches!(err, ApiError::InvalidTlsConfig(_)));
+}
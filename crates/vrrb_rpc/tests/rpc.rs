@@ -0,0 +1,1135 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use jsonrpsee::{
+    core::{client::ClientT, Error},
+    rpc_params,
+};
+use mempool::{LeftRightMempool, ValidationQueue};
+use primitives::{Address, NodeType, PeerId, PROTOCOL_VERSION};
+use storage::vrrbdb::{VrrbDb, VrrbDbConfig};
+use tokio::sync::{
+    broadcast,
+    mpsc::{unbounded_channel, Receiver, UnboundedReceiver},
+};
+use vrrb_core::{
+    account::Account,
+    event_router::{DirectedEvent, Event, Topic},
+    fee::{FeeModel, PerByteFee},
+    key_rotation::KeyRotation,
+    keypair::KeyPair,
+    txn::{null_txn, NewTxnArgs, TransactionDigest, Txn},
+    txn_receipts::{TxnReceipt, TxnReceiptStatus},
+};
+use vrrb_rpc::rpc::{
+    api::{PeerConnectionState, PeerInfo, RpcClient, RpcServer, MAX_SUBMIT_BATCH_SIZE},
+    client::create_client,
+    JsonRpcServer,
+    JsonRpcServerConfig,
+    PeerTable,
+    PeerTableError,
+    RpcError,
+    RpcServerImpl,
+};
+
+fn create_server_impl() -> (
+    RpcServerImpl,
+    UnboundedReceiver<DirectedEvent>,
+    Receiver<Txn>,
+) {
+    create_server_impl_with_queue_depth(100)
+}
+
+fn create_server_impl_with_queue_depth(
+    validation_queue_depth: usize,
+) -> (
+    RpcServerImpl,
+    UnboundedReceiver<DirectedEvent>,
+    Receiver<Txn>,
+) {
+    let config = JsonRpcServerConfig::default();
+    let (events_tx, events_rx) = unbounded_channel();
+    let (validation_queue, validation_rx) = ValidationQueue::new(validation_queue_depth);
+
+    let server_impl = RpcServerImpl {
+        node_id: config.node_id,
+        node_type: config.node_type,
+        chain_id: config.chain_id,
+        reject_txns_without_chain_id: false,
+        fee_model: config.fee_model,
+        max_txn_size_bytes: config.max_txn_size_bytes,
+        reject_txns_without_networking: false,
+        node_status_handle: config.node_status_handle,
+        vrrbdb_read_handle: config.vrrbdb_read_handle,
+        mempool_read_handle_factory: config.mempool_read_handle_factory,
+        validation_queue,
+        txn_receipts: config.txn_receipts,
+        events_tx,
+        confirmed_blocks_tx: config.confirmed_blocks_tx,
+        peer_table: None,
+        admin_token: None,
+    };
+
+    (server_impl, events_rx, validation_rx)
+}
+
+fn create_signed_txn(keypair: &KeyPair) -> Txn {
+    create_signed_txn_with_nonce(keypair, 1)
+}
+
+fn create_signed_txn_with_nonce(keypair: &KeyPair, nonce: u128) -> Txn {
+    let mut txn = Txn::new(NewTxnArgs {
+        timestamp: 0,
+        sender_address: String::from("0x192abcdef01234567890fedcba09876543210"),
+        sender_public_key: *keypair.get_miner_public_key(),
+        receiver_address: String::from("0x192abcdef01234567890fedcba09876543211"),
+        token: None,
+        amount: 10,
+        signature: null_txn().signature,
+        validators: Some(HashMap::new()),
+        nonce,
+    });
+
+    txn.sign(keypair.get_miner_secret_key());
+    txn
+}
+
+/// Points the server at a fresh state store holding a funded account for
+/// the given keypair. The returned store must outlive the server's use of it.
+fn fund_sender(server_impl: &mut RpcServerImpl, keypair: &KeyPair, credits: u128) -> VrrbDb {
+    let mut vrrbdb_config = VrrbDbConfig::default();
+    vrrbdb_config.path = std::env::temp_dir().join(vrrb_core::helpers::generate_random_string());
+
+    let mut vrrbdb = VrrbDb::new(vrrbdb_config).unwrap();
+    server_impl.vrrbdb_read_handle = vrrbdb.read_handle();
+
+    let mut account = Account::new(*keypair.get_miner_public_key());
+    account.credits = credits;
+
+    vrrbdb
+        .insert_account(Address::new(*keypair.get_miner_public_key()), account)
+        .unwrap();
+
+    vrrbdb
+}
+
+fn error_code(err: Error) -> i32 {
+    vrrb_rpc::rpc::error_code(&err).unwrap_or_else(|| panic!("unexpected error: {err:?}"))
+}
+
+#[tokio::test]
+async fn submit_transaction_forwards_signed_txns_to_mempool() {
+    let (mut server_impl, mut events_rx, _validation_rx) = create_server_impl();
+    let keypair = KeyPair::random();
+    let _vrrbdb = fund_sender(&mut server_impl, &keypair, 100);
+
+    let txn = create_signed_txn(&keypair);
+
+    let digest = server_impl.submit_transaction(txn.clone()).await.unwrap();
+
+    assert_eq!(digest, txn.digest());
+    assert_eq!(
+        events_rx.recv().await.unwrap(),
+        (Topic::Storage, Event::NewTxnCreated(txn))
+    );
+}
+
+#[tokio::test]
+async fn submit_transaction_rejects_bad_signatures() {
+    let (server_impl, mut events_rx, _validation_rx) = create_server_impl();
+
+    let mut txn = create_signed_txn(&KeyPair::random());
+    txn.sign(KeyPair::random().get_miner_secret_key());
+
+    let err = server_impl.submit_transaction(txn).await.unwrap_err();
+
+    assert_eq!(error_code(err), -32002);
+    assert!(events_rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn submit_transaction_rejects_reused_nonces() {
+    let (mut server_impl, mut events_rx, _validation_rx) = create_server_impl();
+    let keypair = KeyPair::random();
+    let mut vrrbdb = fund_sender(&mut server_impl, &keypair, 100);
+
+    // NOTE: bumps the sender's nonce past the one the txn uses
+    let address = Address::new(*keypair.get_miner_public_key());
+    let account = server_impl
+        .get_account(address.clone())
+        .await
+        .unwrap()
+        .unwrap();
+    vrrbdb.update_account(address, account).unwrap();
+
+    let txn = create_signed_txn(&keypair);
+
+    let err = server_impl.submit_transaction(txn).await.unwrap_err();
+
+    assert_eq!(error_code(err), -32004);
+    assert!(events_rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn submit_transaction_rejects_amounts_above_the_sender_balance() {
+    let (mut server_impl, mut events_rx, _validation_rx) = create_server_impl();
+    let keypair = KeyPair::random();
+    let _vrrbdb = fund_sender(&mut server_impl, &keypair, 5);
+
+    let txn = create_signed_txn(&keypair);
+
+    let err = server_impl.submit_transaction(txn).await.unwrap_err();
+
+    assert_eq!(error_code(err), -32006);
+    assert!(events_rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn submit_transaction_is_rejected_without_networking() {
+    let (mut server_impl, mut events_rx, _validation_rx) = create_server_impl();
+    server_impl.reject_txns_without_networking = true;
+
+    let txn = create_signed_txn(&KeyPair::random());
+
+    let err = server_impl.submit_transaction(txn).await.unwrap_err();
+
+    assert_eq!(error_code(err), -32005);
+    assert!(events_rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn observer_nodes_serve_reads_but_refuse_writes() {
+    let (mut server_impl, mut events_rx, mut validation_rx) = create_server_impl();
+    server_impl.node_type = NodeType::Observer;
+
+    let keypair = KeyPair::random();
+    let _vrrbdb = fund_sender(&mut server_impl, &keypair, 100);
+    let address = Address::new(*keypair.get_miner_public_key());
+    let txn = create_signed_txn(&keypair);
+
+    let err = server_impl
+        .submit_transaction(txn.clone())
+        .await
+        .unwrap_err();
+    assert_eq!(error_code(err), RpcError::READ_ONLY);
+
+    let rejection = server_impl
+        .validate_transaction(txn.clone())
+        .await
+        .unwrap()
+        .rejection
+        .unwrap();
+    assert_eq!(rejection.code, RpcError::READ_ONLY);
+    assert_eq!(rejection.reason, "node is read-only");
+
+    let err = server_impl
+        .create_account(
+            address.clone(),
+            Account::new(*keypair.get_miner_public_key()),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(error_code(err), RpcError::READ_ONLY);
+
+    let err = server_impl
+        .create_txn(NewTxnArgs {
+            timestamp: 0,
+            sender_address: String::new(),
+            sender_public_key: txn.sender_public_key,
+            receiver_address: String::new(),
+            token: None,
+            amount: 0,
+            signature: txn.signature,
+            validators: None,
+            nonce: 0,
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(error_code(err), RpcError::READ_ONLY);
+
+    assert!(events_rx.try_recv().is_err());
+    assert!(validation_rx.try_recv().is_err());
+
+    assert_eq!(
+        server_impl
+            .get_account(address)
+            .await
+            .unwrap()
+            .unwrap()
+            .credits,
+        100
+    );
+    assert_eq!(
+        server_impl.get_node_type().await.unwrap(),
+        NodeType::Observer
+    );
+}
+
+#[tokio::test]
+async fn get_account_reads_accounts_from_state() {
+    let (mut server_impl, _events_rx, _validation_rx) = create_server_impl();
+
+    let mut vrrbdb_config = VrrbDbConfig::default();
+    vrrbdb_config.path = std::env::temp_dir().join(vrrb_core::helpers::generate_random_string());
+
+    let mut vrrbdb = VrrbDb::new(vrrbdb_config).unwrap();
+    server_impl.vrrbdb_read_handle = vrrbdb.read_handle();
+
+    let keypair = KeyPair::random();
+    let address = Address::new(*keypair.get_miner_public_key());
+
+    let account = Account::new(*keypair.get_miner_public_key());
+
+    vrrbdb
+        .insert_account(address.clone(), account.clone())
+        .unwrap();
+
+    assert_eq!(
+        server_impl.get_account(address.clone()).await.unwrap(),
+        Some(account.clone())
+    );
+    assert_eq!(
+        server_impl
+            .get_account_nonce(address.clone())
+            .await
+            .unwrap(),
+        0
+    );
+
+    vrrbdb.update_account(address.clone(), account).unwrap();
+
+    assert_eq!(server_impl.get_account_nonce(address).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn get_account_returns_none_for_unknown_accounts() {
+    let (server_impl, _events_rx, _validation_rx) = create_server_impl();
+
+    let address = Address::new(*KeyPair::random().get_miner_public_key());
+
+    assert_eq!(
+        server_impl.get_account(address.clone()).await.unwrap(),
+        None
+    );
+    assert_eq!(server_impl.get_account_nonce(address).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn get_next_nonce_accounts_for_pending_txns() {
+    let (mut server_impl, _events_rx, _validation_rx) = create_server_impl();
+    let keypair = KeyPair::random();
+    let address = Address::new(*keypair.get_miner_public_key());
+
+    let mut vrrbdb = fund_sender(&mut server_impl, &keypair, 1_000);
+    let mut account = vrrbdb.read_handle().get_account(&address).unwrap();
+    account.nonce = 3;
+    vrrbdb.restore_accounts(vec![(address.clone(), account)]);
+
+    let mut mempool = LeftRightMempool::default();
+    server_impl.mempool_read_handle_factory = mempool.factory();
+
+    assert_eq!(
+        server_impl.get_next_nonce(address.clone()).await.unwrap(),
+        4
+    );
+
+    // NOTE: nonce 9 is queued behind the gap from 6 to 8, which is the one
+    // to fill next
+    for nonce in [4, 5, 9] {
+        mempool
+            .insert_at_nonce(create_signed_txn_with_nonce(&keypair, nonce), 4)
+            .unwrap();
+    }
+    mempool
+        .insert(create_signed_txn(&KeyPair::random()))
+        .unwrap();
+
+    assert_eq!(
+        server_impl.get_next_nonce(address.clone()).await.unwrap(),
+        6
+    );
+
+    for nonce in 6..9 {
+        mempool
+            .insert_at_nonce(create_signed_txn_with_nonce(&keypair, nonce), 4)
+            .unwrap();
+    }
+
+    assert_eq!(server_impl.get_next_nonce(address).await.unwrap(), 10);
+}
+
+#[tokio::test]
+async fn submit_transaction_reports_busy_once_the_validation_queue_fills_up() {
+    let (mut server_impl, mut events_rx, _validation_rx) = create_server_impl_with_queue_depth(8);
+    let keypair = KeyPair::random();
+    let _vrrbdb = fund_sender(&mut server_impl, &keypair, 1_000);
+
+    let mut accepted = 0;
+    for nonce in 1..=50 {
+        let txn = create_signed_txn_with_nonce(&keypair, nonce);
+
+        match server_impl.submit_transaction(txn).await {
+            Ok(_) => accepted += 1,
+            Err(err) => assert_eq!(error_code(err), -32003),
+        }
+    }
+
+    assert_eq!(accepted, 8);
+    assert_eq!(server_impl.validation_queue.depth(), 8);
+
+    // NOTE: every accepted txn was handed to the event router, see the node's
+    // rpc_api tests for their way through the storage topic
+    let mut forwarded = 0;
+    while let Ok((topic, event)) = events_rx.try_recv() {
+        assert_eq!(topic, Topic::Storage);
+        assert!(matches!(event, Event::NewTxnCreated(_)));
+        forwarded += 1;
+    }
+
+    assert_eq!(forwarded, accepted);
+}
+
+async fn start_server() -> (
+    jsonrpsee::server::ServerHandle,
+    SocketAddr,
+    broadcast::Sender<Vec<u8>>,
+) {
+    let mut config = JsonRpcServerConfig::default();
+    config.address = SocketAddr::from(([127, 0, 0, 1], 0));
+
+    let confirmed_blocks_tx = config.confirmed_blocks_tx.clone();
+    let (server_handle, addr) = JsonRpcServer::run(&config).await.unwrap();
+
+    (server_handle, addr, confirmed_blocks_tx)
+}
+
+/// Waits for every block subscription forwarding task to wind down
+async fn wait_for_block_subscribers_to_leave(confirmed_blocks_tx: &broadcast::Sender<Vec<u8>>) {
+    let started_at = Instant::now();
+
+    while confirmed_blocks_tx.receiver_count() > 0 {
+        assert!(
+            started_at.elapsed() < Duration::from_secs(5),
+            "block subscription task outlived its subscription"
+        );
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+#[tokio::test]
+async fn subscribe_blocks_pushes_confirmed_blocks_until_unsubscribed() {
+    let (server_handle, addr, confirmed_blocks_tx) = start_server().await;
+    let client = create_client(addr).await.unwrap();
+
+    let mut subscription = client.subscribe_blocks().await.unwrap();
+
+    confirmed_blocks_tx.send(vec![1, 2, 3]).unwrap();
+
+    assert_eq!(subscription.next().await.unwrap().unwrap(), vec![1, 2, 3]);
+
+    subscription.unsubscribe().await.unwrap();
+
+    wait_for_block_subscribers_to_leave(&confirmed_blocks_tx).await;
+
+    server_handle.stop().unwrap();
+}
+
+#[tokio::test]
+async fn subscribe_blocks_cleans_up_after_disconnected_clients() {
+    let (server_handle, addr, confirmed_blocks_tx) = start_server().await;
+    let client = create_client(addr).await.unwrap();
+
+    let subscription = client.subscribe_blocks().await.unwrap();
+
+    assert_eq!(confirmed_blocks_tx.receiver_count(), 1);
+
+    drop(subscription);
+    drop(client);
+
+    wait_for_block_subscribers_to_leave(&confirmed_blocks_tx).await;
+
+    server_handle.stop().unwrap();
+}
+
+#[tokio::test]
+async fn submit_transaction_rejects_txns_already_in_the_mempool() {
+    let (mut server_impl, mut events_rx, _validation_rx) = create_server_impl();
+    let keypair = KeyPair::random();
+    let _vrrbdb = fund_sender(&mut server_impl, &keypair, 100);
+
+    let txn = create_signed_txn(&keypair);
+
+    let mut mempool = LeftRightMempool::default();
+    mempool.insert(txn.clone()).unwrap();
+    server_impl.mempool_read_handle_factory = mempool.factory();
+
+    let err = server_impl.submit_transaction(txn).await.unwrap_err();
+
+    assert_eq!(error_code(err), RpcError::DUPLICATE);
+    assert!(events_rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn create_txn_reports_an_internal_error_once_the_event_router_is_closed() {
+    let (server_impl, events_rx, _validation_rx) = create_server_impl();
+    drop(events_rx);
+
+    let err = server_impl
+        .create_txn(NewTxnArgs {
+            timestamp: 0,
+            sender_address: String::from("0x192abcdef01234567890fedcba09876543210"),
+            sender_public_key: *KeyPair::random().get_miner_public_key(),
+            receiver_address: String::from("0x192abcdef01234567890fedcba09876543211"),
+            token: None,
+            amount: 10,
+            signature: null_txn().signature,
+            validators: Some(HashMap::new()),
+            nonce: 0,
+        })
+        .await
+        .unwrap_err();
+
+    assert_eq!(error_code(err), RpcError::INTERNAL);
+}
+
+#[tokio::test]
+async fn get_transaction_reports_unknown_digests() {
+    let (server_impl, _events_rx, _validation_rx) = create_server_impl();
+
+    let err = server_impl
+        .get_transaction(TransactionDigest::default())
+        .await
+        .unwrap_err();
+
+    assert_eq!(error_code(err), RpcError::TRANSACTION_NOT_FOUND);
+}
+
+#[tokio::test]
+async fn get_transaction_receipt_follows_txns_from_pending_to_included() {
+    let (mut server_impl, _events_rx, _validation_rx) = create_server_impl();
+    let keypair = KeyPair::random();
+    let mut vrrbdb = fund_sender(&mut server_impl, &keypair, 100);
+
+    let txn = create_signed_txn(&keypair);
+    let digest = server_impl.submit_transaction(txn).await.unwrap();
+
+    let receipt = server_impl
+        .get_transaction_receipt(digest.clone())
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(receipt.status, TxnReceiptStatus::Pending);
+    assert_eq!(receipt.block_height, None);
+
+    // NOTE: the state module records txns as included once a block holding
+    // them is committed
+    server_impl
+        .txn_receipts
+        .record(TxnReceipt::included(digest.clone(), Some(12)));
+
+    let receipt = server_impl
+        .get_transaction_receipt(digest.clone())
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(receipt.status, TxnReceiptStatus::Included);
+    assert_eq!(receipt.block_height, Some(12));
+
+    // NOTE: receipts stored by the state module outlive the ones held in
+    // memory
+    let dropped = create_signed_txn_with_nonce(&keypair, 2);
+    vrrbdb.insert_txn_receipt(TxnReceipt::dropped(
+        dropped.digest(),
+        String::from("expired in the mempool"),
+    ));
+
+    assert_eq!(
+        server_impl
+            .get_transaction_receipt(dropped.digest())
+            .await
+            .unwrap()
+            .map(|receipt| receipt.status),
+        Some(TxnReceiptStatus::Dropped)
+    );
+
+    // NOTE: txns stored before receipts were persisted have no receipt, nor
+    // a known height
+    let stored = create_signed_txn_with_nonce(&keypair, 1);
+    vrrbdb.insert_transaction(stored.clone()).unwrap();
+
+    assert_eq!(
+        server_impl
+            .get_transaction_receipt(stored.digest())
+            .await
+            .unwrap(),
+        Some(TxnReceipt::included(stored.digest(), None))
+    );
+
+    assert_eq!(
+        server_impl
+            .get_transaction_receipt(TransactionDigest::default())
+            .await
+            .unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+async fn update_account_reports_unknown_accounts() {
+    let (mut server_impl, mut events_rx, _validation_rx) = create_server_impl();
+    let keypair = KeyPair::random();
+    let _vrrbdb = fund_sender(&mut server_impl, &keypair, 100);
+
+    let known = Address::new(*keypair.get_miner_public_key());
+    let unknown = Address::new(*KeyPair::random().get_miner_public_key());
+
+    server_impl
+        .update_account(known.clone(), Account::new(*keypair.get_miner_public_key()))
+        .await
+        .unwrap();
+
+    let (topic, event) = events_rx.try_recv().unwrap();
+    assert_eq!(topic, Topic::Storage);
+    assert!(matches!(
+        event,
+        Event::AccountUpdateRequested((address, _)) if address == known
+    ));
+
+    let err = server_impl
+        .update_account(unknown.clone(), Account::new(unknown.public_key()))
+        .await
+        .unwrap_err();
+
+    assert_eq!(error_code(err), RpcError::ACCOUNT_NOT_FOUND);
+    assert!(events_rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn update_account_rejects_malformed_public_keys() {
+    let (server_impl, _events_rx, _validation_rx) = create_server_impl();
+
+    let mut account = Account::new(*KeyPair::random().get_miner_public_key());
+    account.pubkey = vec![0; 3];
+
+    let err = server_impl
+        .update_account(
+            Address::new(*KeyPair::random().get_miner_public_key()),
+            account,
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(error_code(err), RpcError::INVALID_PARAMS);
+}
+
+#[tokio::test]
+async fn update_account_targets_accounts_by_address_after_a_key_rotation() {
+    let (mut server_impl, mut events_rx, _validation_rx) = create_server_impl();
+    let keypair = KeyPair::random();
+    let _vrrbdb = fund_sender(&mut server_impl, &keypair, 100);
+
+    let address = Address::new(*keypair.get_miner_public_key());
+    let rotated = Account::new(*KeyPair::random().get_miner_public_key());
+
+    server_impl
+        .update_account(address.clone(), rotated)
+        .await
+        .unwrap();
+
+    let (_, event) = events_rx.try_recv().unwrap();
+    assert!(matches!(
+        event,
+        Event::AccountUpdateRequested((target, _)) if target == address
+    ));
+}
+
+#[tokio::test]
+async fn error_codes_survive_the_trip_to_clients() {
+    let (server_handle, addr, _confirmed_blocks_tx) = start_server().await;
+    let client = create_client(addr).await.unwrap();
+
+    let err = client
+        .get_transaction(TransactionDigest::default())
+        .await
+        .unwrap_err();
+
+    assert_eq!(error_code(err), RpcError::TRANSACTION_NOT_FOUND);
+
+    server_handle.stop().unwrap();
+}
+
+#[tokio::test]
+async fn get_account_rejects_mistyped_addresses() {
+    let (server_handle, addr, _confirmed_blocks_tx) = start_server().await;
+    let client = create_client(addr).await.unwrap();
+
+    let address = Address::new(*KeyPair::random().get_miner_public_key()).to_string();
+
+    // NOTE: swaps the last checksum character for another one
+    let replacement = if address.ends_with('q') { 'p' } else { 'q' };
+    let mistyped = format!("{}{replacement}", &address[..address.len() - 1]);
+
+    let err = client
+        .request::<Option<Account>, _>("state_getAccount", rpc_params![mistyped])
+        .await
+        .unwrap_err();
+
+    assert_eq!(error_code(err), RpcError::INVALID_PARAMS);
+
+    server_handle.stop().unwrap();
+}
+
+#[tokio::test]
+async fn rotate_account_key_forwards_rotations_signed_by_the_current_key() {
+    let (mut server_impl, mut events_rx, _validation_rx) = create_server_impl();
+    let keypair = KeyPair::random();
+    let _vrrbdb = fund_sender(&mut server_impl, &keypair, 100);
+
+    let address = Address::new(*keypair.get_miner_public_key());
+    let new_keypair = KeyPair::random();
+
+    let forged = KeyRotation::new(
+        address.clone(),
+        *new_keypair.get_miner_public_key(),
+        0,
+        new_keypair.get_miner_secret_key(),
+    );
+
+    let err = server_impl.rotate_account_key(forged).await.unwrap_err();
+    assert_eq!(error_code(err), RpcError::INVALID_SIGNATURE);
+
+    let unknown = KeyRotation::new(
+        Address::new(*new_keypair.get_miner_public_key()),
+        *keypair.get_miner_public_key(),
+        0,
+        new_keypair.get_miner_secret_key(),
+    );
+
+    let err = server_impl.rotate_account_key(unknown).await.unwrap_err();
+    assert_eq!(error_code(err), RpcError::ACCOUNT_NOT_FOUND);
+
+    assert!(events_rx.try_recv().is_err());
+
+    let rotation = KeyRotation::new(
+        address,
+        *new_keypair.get_miner_public_key(),
+        0,
+        keypair.get_miner_secret_key(),
+    );
+
+    server_impl
+        .rotate_account_key(rotation.clone())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        events_rx.recv().await.unwrap(),
+        (Topic::Storage, Event::AccountKeyRotationRequested(rotation))
+    );
+}
+
+#[tokio::test]
+async fn validate_transaction_accepts_valid_txns_without_queueing_them() {
+    let (mut server_impl, mut events_rx, mut validation_rx) = create_server_impl();
+    let keypair = KeyPair::random();
+    let _vrrbdb = fund_sender(&mut server_impl, &keypair, 100);
+
+    let mut txn = create_signed_txn(&keypair);
+    txn.set_fee(3);
+    txn.sign(keypair.get_miner_secret_key());
+
+    let result = server_impl.validate_transaction(txn.clone()).await.unwrap();
+
+    assert!(result.is_accepted());
+    assert_eq!(result.digest, txn.digest());
+    assert_eq!(result.estimated_fee, 3);
+
+    assert!(events_rx.try_recv().is_err());
+    assert!(validation_rx.try_recv().is_err());
+    assert!(server_impl.get_full_mempool().await.unwrap().is_empty());
+
+    let address = Address::new(*keypair.get_miner_public_key());
+    assert_eq!(server_impl.get_account_nonce(address).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn txns_offering_less_than_the_minimum_fee_are_rejected() {
+    let (mut server_impl, _events_rx, mut validation_rx) = create_server_impl();
+    let keypair = KeyPair::random();
+    let _vrrbdb = fund_sender(&mut server_impl, &keypair, 100);
+
+    let fee_model = PerByteFee {
+        base_fee: 5,
+        fee_per_byte: 1,
+    };
+    server_impl.fee_model = Arc::new(fee_model);
+
+    let mut txn = create_signed_txn(&keypair);
+    let minimum = fee_model.estimate_fee(&txn);
+    txn.set_fee(minimum - 1);
+    txn.sign(keypair.get_miner_secret_key());
+
+    let result = server_impl.validate_transaction(txn.clone()).await.unwrap();
+    assert_eq!(result.estimated_fee, minimum);
+    assert_eq!(result.rejection.unwrap().code, RpcError::FEE_TOO_LOW);
+
+    let err = server_impl
+        .submit_transaction(txn.clone())
+        .await
+        .unwrap_err();
+    assert_eq!(error_code(err), RpcError::FEE_TOO_LOW);
+    assert!(validation_rx.try_recv().is_err());
+
+    txn.set_fee(minimum);
+    txn.sign(keypair.get_miner_secret_key());
+
+    let result = server_impl.validate_transaction(txn.clone()).await.unwrap();
+    assert!(result.is_accepted());
+    assert_eq!(result.estimated_fee, minimum);
+}
+
+#[tokio::test]
+async fn submit_transaction_rejects_oversized_txns() {
+    let (mut server_impl, mut events_rx, mut validation_rx) = create_server_impl();
+    let keypair = KeyPair::random();
+    let _vrrbdb = fund_sender(&mut server_impl, &keypair, 100);
+
+    let txn = create_signed_txn(&keypair);
+    server_impl.max_txn_size_bytes = txn.size() - 1;
+
+    let err = server_impl
+        .submit_transaction(txn.clone())
+        .await
+        .unwrap_err();
+
+    assert_eq!(error_code(err), RpcError::TOO_LARGE);
+    assert!(validation_rx.try_recv().is_err());
+    assert!(events_rx.try_recv().is_err());
+    assert!(server_impl.get_full_mempool().await.unwrap().is_empty());
+
+    server_impl.max_txn_size_bytes = txn.size();
+
+    server_impl.submit_transaction(txn.clone()).await.unwrap();
+    assert_eq!(validation_rx.try_recv().unwrap(), txn);
+}
+
+#[tokio::test]
+async fn submit_transactions_accepts_valid_txns_and_rejects_invalid_ones() {
+    let (mut server_impl, mut events_rx, mut validation_rx) = create_server_impl();
+    let keypair = KeyPair::random();
+    let poor_keypair = KeyPair::random();
+    let mut vrrbdb = fund_sender(&mut server_impl, &keypair, 100);
+
+    let mut poor_account = Account::new(*poor_keypair.get_miner_public_key());
+    poor_account.credits = 5;
+    vrrbdb
+        .insert_account(
+            Address::new(*poor_keypair.get_miner_public_key()),
+            poor_account,
+        )
+        .unwrap();
+
+    let first = create_signed_txn_with_nonce(&keypair, 1);
+    let second = create_signed_txn_with_nonce(&keypair, 2);
+
+    let mut badly_signed = create_signed_txn_with_nonce(&keypair, 3);
+    badly_signed.sign(KeyPair::random().get_miner_secret_key());
+
+    let overdrawn = create_signed_txn(&poor_keypair);
+
+    let batch = vec![
+        first.clone(),
+        badly_signed.clone(),
+        first.clone(),
+        overdrawn.clone(),
+        second.clone(),
+    ];
+
+    let results = server_impl.submit_transactions(batch).await.unwrap();
+
+    let outcomes = results
+        .iter()
+        .map(|result| {
+            (
+                result.digest.clone(),
+                result.rejection.as_ref().map(|rejection| rejection.code),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        outcomes,
+        vec![
+            (first.digest(), None),
+            (badly_signed.digest(), Some(RpcError::INVALID_SIGNATURE)),
+            (first.digest(), Some(RpcError::DUPLICATE)),
+            (overdrawn.digest(), Some(RpcError::INSUFFICIENT_BALANCE)),
+            (second.digest(), None),
+        ]
+    );
+
+    // NOTE: only the accepted transactions are queued, in order
+    for txn in [first, second] {
+        assert_eq!(
+            events_rx.try_recv().unwrap(),
+            (Topic::Storage, Event::NewTxnCreated(txn.clone()))
+        );
+        assert_eq!(validation_rx.try_recv().unwrap(), txn);
+    }
+
+    assert!(events_rx.try_recv().is_err());
+    assert!(validation_rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn submit_transactions_refuses_oversized_batches() {
+    let (server_impl, mut events_rx, _validation_rx) = create_server_impl();
+
+    let batch = vec![null_txn(); MAX_SUBMIT_BATCH_SIZE + 1];
+
+    let err = server_impl.submit_transactions(batch).await.unwrap_err();
+
+    assert_eq!(error_code(err), RpcError::INVALID_PARAMS);
+    assert!(events_rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn validate_transaction_reports_why_txns_would_be_rejected() {
+    let (mut server_impl, mut events_rx, mut validation_rx) = create_server_impl();
+    let keypair = KeyPair::random();
+    let poor_keypair = KeyPair::random();
+    let mut vrrbdb = fund_sender(&mut server_impl, &keypair, 100);
+
+    let mut poor_account = Account::new(*poor_keypair.get_miner_public_key());
+    poor_account.credits = 5;
+    vrrbdb
+        .insert_account(
+            Address::new(*poor_keypair.get_miner_public_key()),
+            poor_account,
+        )
+        .unwrap();
+
+    let mut badly_signed = create_signed_txn(&keypair);
+    badly_signed.sign(KeyPair::random().get_miner_secret_key());
+
+    let overdrawn = create_signed_txn(&poor_keypair);
+
+    let pending = create_signed_txn_with_nonce(&keypair, 2);
+    let mut mempool = LeftRightMempool::default();
+    mempool.insert(pending.clone()).unwrap();
+    server_impl.mempool_read_handle_factory = mempool.factory();
+
+    // NOTE: bumps the sender's nonce past the one `reused_nonce` uses
+    let address = Address::new(*keypair.get_miner_public_key());
+    let account = server_impl
+        .get_account(address.clone())
+        .await
+        .unwrap()
+        .unwrap();
+    vrrbdb.update_account(address, account).unwrap();
+
+    let reused_nonce = create_signed_txn(&keypair);
+
+    for (txn, code) in [
+        (badly_signed, RpcError::INVALID_SIGNATURE),
+        (reused_nonce, RpcError::NONCE_TOO_LOW),
+        (overdrawn, RpcError::INSUFFICIENT_BALANCE),
+        (pending, RpcError::DUPLICATE),
+    ] {
+        let result = server_impl.validate_transaction(txn).await.unwrap();
+
+        let rejection = result.rejection.expect("transaction should be rejected");
+        assert_eq!(rejection.code, code, "{}", rejection.reason);
+    }
+
+    server_impl.reject_txns_without_networking = true;
+
+    let result = server_impl
+        .validate_transaction(create_signed_txn_with_nonce(&keypair, 1))
+        .await
+        .unwrap();
+    assert_eq!(result.rejection.unwrap().code, RpcError::NOT_ACCEPTED);
+
+    assert!(events_rx.try_recv().is_err());
+    assert!(validation_rx.try_recv().is_err());
+    assert_eq!(server_impl.get_full_mempool().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn get_network_params_reports_the_configured_chain_id() {
+    let mut config = JsonRpcServerConfig::default();
+    config.address = SocketAddr::from(([127, 0, 0, 1], 0));
+    config.chain_id = 42;
+
+    config.node_status_handle.peer_joined(NodeType::Validator);
+    config.node_status_handle.peer_joined(NodeType::Validator);
+    config.node_status_handle.peer_joined(NodeType::Miner);
+
+    let (server_handle, addr) = JsonRpcServer::run(&config).await.unwrap();
+    let client = create_client(addr).await.unwrap();
+
+    let params = client.get_network_params().await.unwrap();
+
+    assert_eq!(params.chain_id, 42);
+    assert_eq!(params.protocol_version, PROTOCOL_VERSION);
+    assert_eq!(params.genesis_hash, None);
+    assert_eq!(
+        params.node_type_distribution,
+        HashMap::from([(NodeType::Validator, 2), (NodeType::Miner, 1)])
+    );
+
+    server_handle.stop().unwrap();
+}
+
+#[tokio::test]
+async fn submit_transaction_accepts_txns_meant_for_its_chain() {
+    let (mut server_impl, _events_rx, _validation_rx) = create_server_impl();
+    server_impl.reject_txns_without_chain_id = true;
+
+    let keypair = KeyPair::random();
+    let _vrrbdb = fund_sender(&mut server_impl, &keypair, 100);
+
+    let mut txn = create_signed_txn(&keypair);
+    txn.set_chain_id(server_impl.chain_id);
+    txn.sign(keypair.get_miner_secret_key());
+
+    assert_eq!(
+        server_impl.submit_transaction(txn.clone()).await.unwrap(),
+        txn.digest()
+    );
+}
+
+#[tokio::test]
+async fn submit_transaction_rejects_txns_meant_for_other_chains() {
+    let (mut server_impl, _events_rx, _validation_rx) = create_server_impl();
+    let keypair = KeyPair::random();
+    let _vrrbdb = fund_sender(&mut server_impl, &keypair, 100);
+
+    let mut txn = create_signed_txn(&keypair);
+    txn.set_chain_id(server_impl.chain_id + 1);
+    txn.sign(keypair.get_miner_secret_key());
+
+    let err = server_impl.submit_transaction(txn).await.unwrap_err();
+    assert_eq!(error_code(err), RpcError::WRONG_CHAIN);
+
+    // NOTE: txns without a chain id are accepted unless configured otherwise
+    let legacy_txn = create_signed_txn(&keypair);
+    assert!(server_impl
+        .validate_transaction(legacy_txn.clone())
+        .await
+        .unwrap()
+        .is_accepted());
+
+    server_impl.reject_txns_without_chain_id = true;
+
+    let err = server_impl
+        .submit_transaction(legacy_txn)
+        .await
+        .unwrap_err();
+    assert_eq!(error_code(err), RpcError::WRONG_CHAIN);
+}
+
+/// Peer table that "connects" to any address right away
+#[derive(Debug, Default)]
+struct StubPeerTable {
+    peers: Mutex<Vec<PeerInfo>>,
+}
+
+#[async_trait]
+impl PeerTable for StubPeerTable {
+    fn peers(&self) -> Vec<PeerInfo> {
+        self.peers.lock().unwrap().clone()
+    }
+
+    async fn add_peer(
+        &self,
+        addr: SocketAddr,
+        _timeout: Duration,
+    ) -> Result<PeerInfo, PeerTableError> {
+        let peer = PeerInfo {
+            peer_id: addr.to_string().into_bytes(),
+            address: addr,
+            connection_state: PeerConnectionState::Connected,
+            last_seen: Some(1_700_000_000),
+        };
+
+        self.peers.lock().unwrap().push(peer.clone());
+
+        Ok(peer)
+    }
+
+    fn remove_peer(&self, peer_id: &PeerId) -> Result<(), PeerTableError> {
+        let mut peers = self.peers.lock().unwrap();
+        let count = peers.len();
+
+        peers.retain(|peer| &peer.peer_id != peer_id);
+
+        if peers.len() == count {
+            return Err(PeerTableError::UnknownPeer(
+                String::from_utf8_lossy(peer_id).into(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn added_peers_are_listed_until_removed() {
+    let mut config = JsonRpcServerConfig::default();
+    config.address = SocketAddr::from(([127, 0, 0, 1], 0));
+    config.peer_table = Some(Arc::new(StubPeerTable::default()));
+
+    let (server_handle, addr) = JsonRpcServer::run(&config).await.unwrap();
+    let client = create_client(addr).await.unwrap();
+
+    let peer_addr = SocketAddr::from(([127, 0, 0, 1], 9000));
+    let peer = client.add_peer(peer_addr, None).await.unwrap();
+
+    assert_eq!(peer.address, peer_addr);
+    assert_eq!(peer.connection_state, PeerConnectionState::Connected);
+    assert_eq!(client.get_peers(None).await.unwrap(), vec![peer.clone()]);
+
+    client
+        .remove_peer(peer.peer_id.clone(), None)
+        .await
+        .unwrap();
+    assert!(client.get_peers(None).await.unwrap().is_empty());
+
+    let err = client.remove_peer(peer.peer_id, None).await.unwrap_err();
+    assert_eq!(error_code(err), RpcError::PEER_NOT_FOUND);
+
+    server_handle.stop().unwrap();
+}
+
+#[tokio::test]
+async fn managing_peers_requires_the_admin_token_when_configured() {
+    let (mut server_impl, _events_rx, _validation_rx) = create_server_impl();
+    server_impl.peer_table = Some(Arc::new(StubPeerTable::default()));
+    server_impl.admin_token = Some(String::from("s3cr3t"));
+
+    let peer_addr = SocketAddr::from(([127, 0, 0, 1], 9000));
+
+    let err = server_impl.add_peer(peer_addr, None).await.unwrap_err();
+    assert_eq!(error_code(err), RpcError::UNAUTHORIZED);
+
+    let err = server_impl
+        .get_peers(Some(String::from("wrong")))
+        .await
+        .unwrap_err();
+    assert_eq!(error_code(err), RpcError::UNAUTHORIZED);
+
+    let token = Some(String::from("s3cr3t"));
+    server_impl
+        .add_peer(peer_addr, token.clone())
+        .await
+        .unwrap();
+    assert_eq!(server_impl.get_peers(token).await.unwrap().len(), 1);
+}
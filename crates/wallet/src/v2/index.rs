@@ -0,0 +1,171 @@
+use std::{
+    fs::OpenOptions,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+use primitives::Address;
+use serde::{Deserialize, Serialize};
+
+use super::{AddressAlias, WalletError, WalletResult};
+
+pub const WALLET_INDEX_FILE_NAME: &str = "wallet_index.json";
+
+const LOCK_RETRIES: usize = 100;
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A single account tracked by the wallet index
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletIndexEntry {
+    pub alias: AddressAlias,
+    pub address: Address,
+    pub created_at: i64,
+    pub key_path: PathBuf,
+}
+
+/// Manifest of every account created by a wallet, stored as
+/// `wallet_index.json` within the wallet's data directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletIndex {
+    pub accounts: Vec<WalletIndexEntry>,
+}
+
+impl WalletIndex {
+    /// Reads the index stored at `path`, returning an empty one if it doesn't
+    /// exist yet.
+    pub fn load(path: &Path) -> WalletResult<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = std::fs::read_to_string(path).map_err(|err| {
+            WalletError::Custom(format!("unable to read {}: {err}", path.display()))
+        })?;
+
+        serde_json::from_str(&contents).map_err(|err| {
+            WalletError::Custom(format!("unable to parse {}: {err}", path.display()))
+        })
+    }
+
+    /// Adds `entry` to the index stored at `path`, replacing any entry
+    /// registered under the same alias.
+    ///
+    /// Writers are serialized through a lock file and the new index is
+    /// renamed into place, so readers never observe a partially written file.
+    pub fn record(path: &Path, entry: WalletIndexEntry) -> WalletResult<Self> {
+        let _lock = IndexLock::acquire(path)?;
+
+        let mut index = Self::load(path)?;
+        index
+            .accounts
+            .retain(|existing| existing.alias != entry.alias);
+        index.accounts.push(entry);
+        index.accounts.sort_by_key(|entry| entry.alias);
+
+        let contents = serde_json::to_string_pretty(&index)
+            .map_err(|err| WalletError::Custom(format!("unable to serialize index: {err}")))?;
+
+        let tmp_path = path.with_extension("json.tmp");
+
+        std::fs::write(&tmp_path, contents).map_err(|err| {
+            WalletError::Custom(format!("unable to write {}: {err}", tmp_path.display()))
+        })?;
+
+        std::fs::rename(&tmp_path, path).map_err(|err| {
+            WalletError::Custom(format!("unable to write {}: {err}", path.display()))
+        })?;
+
+        Ok(index)
+    }
+}
+
+/// Lock file guarding writes to the wallet index, removed when dropped
+struct IndexLock {
+    path: PathBuf,
+}
+
+impl IndexLock {
+    fn acquire(index_path: &Path) -> WalletResult<Self> {
+        let path = index_path.with_extension("json.lock");
+
+        for _ in 0..LOCK_RETRIES {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => return Ok(Self { path }),
+                Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+                    thread::sleep(LOCK_RETRY_INTERVAL);
+                },
+                Err(err) => {
+                    return Err(WalletError::Custom(format!(
+                        "unable to lock {}: {err}",
+                        index_path.display()
+                    )))
+                },
+            }
+        }
+
+        Err(WalletError::Custom(format!(
+            "timed out waiting for lock on {}",
+            index_path.display()
+        )))
+    }
+}
+
+impl Drop for IndexLock {
+    fn drop(&mut self) {
+        std::fs::remove_file(&self.path).unwrap_or_default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use secp256k1::{generate_keypair, rand};
+
+    use super::*;
+
+    fn entry(alias: AddressAlias) -> WalletIndexEntry {
+        let (_, public_key) = generate_keypair(&mut rand::thread_rng());
+
+        WalletIndexEntry {
+            alias,
+            address: Address::new(public_key),
+            created_at: 0,
+            key_path: PathBuf::from(format!("accounts/{alias}/keys")),
+        }
+    }
+
+    #[test]
+    fn concurrent_records_are_all_kept() {
+        let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(WALLET_INDEX_FILE_NAME);
+
+        let handles = (0..8)
+            .map(|alias| {
+                let path = path.clone();
+                thread::spawn(move || WalletIndex::record(&path, entry(alias)).unwrap())
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let index = WalletIndex::load(&path).unwrap();
+        let aliases = index
+            .accounts
+            .iter()
+            .map(|entry| entry.alias)
+            .collect::<Vec<_>>();
+
+        assert_eq!(aliases, (0..8).collect::<Vec<_>>());
+
+        let replacement = entry(3);
+        let index = WalletIndex::record(&path, replacement.clone()).unwrap();
+
+        assert_eq!(index.accounts.len(), 8);
+        assert!(index.accounts.contains(&replacement));
+
+        std::fs::remove_dir_all(&dir).unwrap_or_default();
+    }
+}
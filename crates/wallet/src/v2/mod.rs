@@ -1,10 +1,15 @@
+mod index;
+
 use std::{
     collections::HashMap,
     net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::PathBuf,
     str::FromStr,
 };
 
+pub use index::*;
 use jsonrpsee::core::client::Client;
+use mempool::TxnRecord;
 use primitives::Address;
 use secp256k1::{ecdsa::Signature, Message, PublicKey, Secp256k1, SecretKey};
 use serde::{Deserialize, Serialize};
@@ -14,6 +19,7 @@ use thiserror::Error;
 use vrrb_core::{
     account::Account,
     helpers::{gen_sha256_digest_string, write_keypair_file},
+    key_rotation::KeyRotation,
     keypair::KeyPairError,
     txn::{Token, TransactionDigest, Txn},
 };
@@ -29,6 +35,12 @@ pub enum WalletError {
     #[error("API error: {0}")]
     ApiError(#[from] vrrb_rpc::ApiError),
 
+    #[error("address {expected} does not match {derived}, derived from its public key")]
+    AddressMismatch { expected: Address, derived: Address },
+
+    #[error("account {0} already exists")]
+    AccountExists(Address),
+
     #[error("custom error")]
     Custom(String),
 }
@@ -44,6 +56,7 @@ pub struct Wallet {
     pub addresses: HashMap<AddressAlias, Address>,
     pub accounts: HashMap<Address, Account>,
     pub nonce: u128,
+    data_dir: Option<PathBuf>,
 }
 
 #[derive(Debug)]
@@ -53,6 +66,9 @@ pub struct WalletConfig {
     pub public_key: PublicKey,
     pub accounts: HashMap<Address, Account>,
     pub addresses: HashMap<AddressAlias, Address>,
+    /// Directory holding the wallet index and the `accounts/<alias>/keys`
+    /// files. Accounts aren't indexed when unset.
+    pub data_dir: Option<PathBuf>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -80,6 +96,7 @@ impl Default for WalletConfig {
             public_key,
             accounts,
             addresses,
+            data_dir: None,
         }
     }
 }
@@ -95,6 +112,7 @@ impl Wallet {
 
         let addresses = config.addresses;
         let accounts = config.accounts;
+        let data_dir = config.data_dir;
 
         //TODO: get rpc server address from config file or env variable
         let client = create_client(config.rpc_server_address).await?;
@@ -112,6 +130,7 @@ impl Wallet {
             addresses,
             accounts,
             nonce: 0,
+            data_dir,
         };
 
         Ok(wallet)
@@ -126,8 +145,8 @@ impl Wallet {
         }
     }
 
-    pub async fn get_mempool(&self) -> Result<Vec<Txn>, WalletError> {
-        let mempool = self.client.get_full_mempool().await?;
+    pub async fn get_mempool(&self, limit: Option<usize>) -> Result<Vec<TxnRecord>, WalletError> {
+        let mempool = self.client.get_mempool(limit).await?;
 
         Ok(mempool)
     }
@@ -205,7 +224,9 @@ impl Wallet {
         }
     }
 
-    pub async fn get_account(&mut self, address: Address) -> WalletResult<Account> {
+    /// Returns the account stored under the given address, or `None` if it
+    /// hasn't been created yet
+    pub async fn get_account(&mut self, address: Address) -> WalletResult<Option<Account>> {
         let account = self.client.get_account(address).await.map_err(|err| {
             error!("{:?}", err.to_string());
 
@@ -256,6 +277,7 @@ impl Wallet {
                 addresses: HashMap::new(),
                 accounts: HashMap::new(),
                 nonce: 0,
+                data_dir: None,
             };
 
             wallet.get_new_address();
@@ -263,8 +285,9 @@ impl Wallet {
             let mut accounts = HashMap::new();
             let addresses = wallet.addresses.clone();
             for (_, addr) in addresses.iter() {
-                let account = wallet.get_account(addr.clone()).await?;
-                accounts.insert(addr.to_owned(), account);
+                if let Some(account) = wallet.get_account(addr.clone()).await? {
+                    accounts.insert(addr.to_owned(), account);
+                }
             }
 
             wallet.accounts = accounts;
@@ -306,17 +329,83 @@ impl Wallet {
         public_key: PublicKey,
     ) -> Result<(Address, Account), WalletError> {
         let account = Account::new(public_key);
-        let address = Address::new(public_key);
+        let address = Address::from_public_key(&public_key);
+
+        let stored_public_key = PublicKey::from_slice(&account.pubkey)
+            .map_err(|err| WalletError::Custom(format!("invalid account public key: {err}")))?;
+
+        let derived = Address::from_public_key(&stored_public_key);
+
+        if derived != address {
+            return Err(WalletError::AddressMismatch {
+                expected: address,
+                derived,
+            });
+        }
+
+        // NOTE: registering the same address twice would reset its account
+        if self.accounts.contains_key(&address)
+            || self.client.get_account(address.clone()).await?.is_some()
+        {
+            return Err(WalletError::AccountExists(address));
+        }
 
         let result = self
             .client
             .create_account(address.clone(), account.clone())
-            .await
-            .map_err(|err| WalletError::Custom(err.to_string()))?;
+            .await?;
+
+        if let Some(data_dir) = &self.data_dir {
+            let entry = WalletIndexEntry {
+                alias,
+                address: address.clone(),
+                created_at: account.created_at,
+                key_path: data_dir
+                    .join("accounts")
+                    .join(alias.to_string())
+                    .join("keys"),
+            };
+
+            WalletIndex::record(&data_dir.join(WALLET_INDEX_FILE_NAME), entry)?;
+        }
 
         self.addresses.insert(alias, address.clone());
         self.accounts.insert(address.clone(), account.clone());
 
         Ok((address, account))
     }
+
+    /// Replaces the public key of the account stored under `address` with
+    /// `new_public_key`, signing the rotation with `current_secret_key`.
+    ///
+    /// Returns the account as it is stored once the node applies the
+    /// rotation.
+    pub async fn rotate_account_key(
+        &mut self,
+        address: Address,
+        current_secret_key: &SecretKey,
+        new_public_key: PublicKey,
+    ) -> WalletResult<Account> {
+        let mut account = self
+            .client
+            .get_account(address.clone())
+            .await?
+            .ok_or_else(|| WalletError::Custom(format!("account {address} not found")))?;
+
+        let rotation = KeyRotation::new(
+            address.clone(),
+            new_public_key,
+            account.nonce,
+            current_secret_key,
+        );
+
+        self.client.rotate_account_key(rotation).await?;
+
+        account.pubkey = new_public_key.serialize().to_vec();
+        account.bump_nonce();
+
+        self.accounts.insert(address, account.clone());
+
+        Ok(account)
+    }
 }
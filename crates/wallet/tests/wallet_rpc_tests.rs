@@ -1,12 +1,18 @@
 use std::net::SocketAddr;
 
-use primitives::{PublicKey, SecretKey};
+use primitives::{Address, PublicKey, SecretKey};
 use secp256k1::{generate_keypair, Message, Secp256k1};
 use serial_test::serial;
+use storage::vrrbdb::{VrrbDb, VrrbDbConfig};
 use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
-use vrrb_core::{helpers::read_or_generate_keypair_file, keypair::Keypair, txn::Token};
+use vrrb_core::{
+    account::Account,
+    helpers::read_or_generate_keypair_file,
+    keypair::Keypair,
+    txn::Token,
+};
 use vrrb_rpc::rpc::{JsonRpcServer, JsonRpcServerConfig};
-use wallet::v2::{Wallet, WalletConfig};
+use wallet::v2::{Wallet, WalletConfig, WalletError};
 
 #[tokio::test]
 #[serial]
@@ -102,3 +108,46 @@ pub async fn wallet_sends_create_account_request_to_rpc_server() {
 
     let (address, account) = wallet.create_account(1, public_key).await.unwrap();
 }
+
+#[tokio::test]
+#[serial]
+pub async fn creating_an_existing_account_fails() {
+    let mut vrrbdb_config = VrrbDbConfig::default();
+    vrrbdb_config.path = std::env::temp_dir().join(vrrb_core::helpers::generate_random_string());
+    let mut vrrbdb = VrrbDb::new(vrrbdb_config).unwrap();
+
+    let mut json_rpc_server_config = JsonRpcServerConfig::default();
+    json_rpc_server_config.vrrbdb_read_handle = vrrbdb.read_handle();
+
+    let (handle, socket_addr) = JsonRpcServer::run(&json_rpc_server_config).await.unwrap();
+
+    tokio::spawn(handle.stopped());
+
+    let mut wallet_config = WalletConfig::default();
+    wallet_config.rpc_server_address = socket_addr;
+
+    let mut wallet = Wallet::new(wallet_config).await.unwrap();
+
+    let (_, public_key) = generate_keypair(&mut rand::thread_rng());
+
+    let (address, _) = wallet.create_account(1, public_key).await.unwrap();
+
+    match wallet.create_account(2, public_key).await {
+        Err(WalletError::AccountExists(existing)) => assert_eq!(existing, address),
+        other => panic!("expected the account to exist already, got {other:?}"),
+    }
+    assert_eq!(wallet.addresses.get(&2), None);
+
+    // NOTE: accounts other wallets registered are found in state
+    let (_, other_public_key) = generate_keypair(&mut rand::thread_rng());
+    let other_address = Address::new(other_public_key);
+
+    vrrbdb
+        .insert_account(other_address.clone(), Account::new(other_public_key))
+        .unwrap();
+
+    match wallet.create_account(3, other_public_key).await {
+        Err(WalletError::AccountExists(existing)) => assert_eq!(existing, other_address),
+        other => panic!("expected the account to exist already, got {other:?}"),
+    }
+}